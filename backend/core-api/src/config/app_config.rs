@@ -5,6 +5,8 @@ use std::env;
 use std::net::IpAddr;
 use std::str::FromStr;
 use std::collections::HashSet;
+use chrono::{NaiveDate, NaiveTime};
+use rust_decimal::Decimal;
 use once_cell::sync::Lazy; // Use Lazy for static config
 
 // Define a struct to hold all application configurations loaded from environment
@@ -24,6 +26,7 @@ pub struct AppConfig {
     pub jwt_secret: String,
     pub jwt_duration_hours: u32,
     pub allowed_ips: HashSet<IpAddr>, // For IP whitelist middleware
+    pub trusted_proxies: HashSet<IpAddr>, // Reverse proxies whose X-Forwarded-For is believed for the client address
 
     // Integration Configs
     pub btcpay_url: String,
@@ -46,6 +49,83 @@ pub struct AppConfig {
     pub deutsche_bank_client_id: String,
     pub deutsche_bank_client_secret: String,
 
+    // Background Workers
+    pub outbox_poll_interval_secs: u64, // How often the payment outbox worker looks for due dispatches
+    pub scheduler_poll_interval_secs: u64, // How often the standing-order scheduler looks for due payments
+
+    // Business calendar
+    pub bank_holidays: Vec<NaiveDate>, // Non-business days besides weekends (scheduled payments roll around them)
+
+    // ACH origination (NACHA files for bulk payouts)
+    pub ach_odfi_routing_number: String, // Our ODFI (immediate destination)
+    pub ach_odfi_name: String,
+    pub ach_company_id: String, // Immediate origin / company identification
+    pub ach_company_name: String, // Shown on the receivers' statements
+
+    // Card Gateways
+    pub card_routing_config_path: String, // JSON card gateways and the rules routing payments between them, loaded at startup
+
+    // Fees
+    pub fee_schedule_path: String, // JSON fee schedule loaded at startup
+
+    // Approvals
+    pub approval_policy_path: String, // JSON maker-checker policies loaded at startup
+
+    // Limits
+    pub limits_policy_path: String, // JSON transaction limits (per-transaction, daily, weekly, monthly) loaded at startup
+
+    // Sanctions
+    pub sanctions_config_path: String, // JSON screening settings naming the OFAC/EU/UN list files, loaded at startup
+    pub sanctions_screening_enabled: bool, // Screen outbound wires and crypto withdrawals (disable only in development)
+
+    // AML
+    pub aml_config_path: String, // JSON monitoring scenarios and thresholds, loaded at startup
+    pub aml_monitor_interval_secs: u64, // How often new and changed transactions are monitored
+
+    // CTR
+    pub ctr_config_path: String, // JSON threshold, cash transaction types and filer details, loaded at startup
+    pub ctr_run_interval_secs: u64, // How often the last completed business day is (re-)aggregated
+
+    // Travel Rule
+    pub travel_rule_config_path: String, // JSON thresholds, missing-data action and counterparty VASP directory
+    pub travel_rule_exchange_interval_secs: u64, // How often pending exchanges with counterparty VASPs are run
+
+    // Fraud Rules
+    pub fraud_rules_path: String, // TOML/YAML rule file, reloaded when it changes
+    pub fraud_rules_reload_interval_secs: u64, // How often the rule file is checked for changes
+
+    // IP Intelligence
+    pub ip_intel_config_path: String, // JSON naming the GeoIP/ASN .mmdb files and Tor/VPN/hosting lists, loaded at startup
+    pub ip_intel_enabled: bool, // Look up client addresses for the fraud rules and login history (disable only in development)
+
+    // Beneficiaries
+    pub beneficiary_cooling_off_hours: i64, // Reduced limits after a payee is verified (or re-verified)
+    pub beneficiary_cooling_off_max_payment: Decimal, // Per payment while cooling off
+    pub beneficiary_cooling_off_max_total: Decimal, // All payments to the payee while cooling off
+    pub beneficiary_require_saved: bool, // Refuse payments to payees that are not saved
+
+    // FX
+    pub fx_spread_bps: i32, // Margin taken off the mid rate on conversions, in basis points
+    pub fx_quote_ttl_secs: i64, // How long a locked conversion quote can be executed
+
+    // Webhooks
+    pub webhook_tolerance_secs: i64, // Allowed clock skew on signed webhook timestamps
+    pub stripe_webhook_secret: Option<String>, // Stripe endpoint signing secret (whsec_...)
+    pub btcpay_webhook_secret: Option<String>, // BTCPay store webhook secret
+    pub partner_bank_webhook_public_key: Option<String>, // Partner bank Ed25519 public key (hex)
+    pub webhook_allow_mock: bool, // Accept the unsigned "mock" provider (never in production)
+
+    // Reconciliation
+    pub nostro_accounts_path: String, // JSON list of nostro accounts to reconcile, loaded at startup
+    pub reconciliation_interval_secs: u64, // How often statements are pulled and matched
+
+    // Treasury
+    pub eod_run_time: NaiveTime, // UTC time the end-of-day position report is produced on business days
+    pub eod_report_signing_key: Option<String>, // Ed25519 seed (hex) signing EOD reports; ephemeral if unset
+
+    // Interest
+    pub interest_run_interval_secs: u64, // How often daily accrual and monthly capitalization are brought up to date
+
     // Add other config sections as needed
 }
 
@@ -65,6 +145,33 @@ impl AppConfig {
              log::debug!("Allowed IPs loaded: {:?}", allowed_ips);
         }
 
+        // Comma-separated, like ALLOWED_IPS; empty means requests come straight from clients
+        let trusted_proxies_str = env::var("TRUSTED_PROXIES").unwrap_or_default();
+        let trusted_proxies = trusted_proxies_str
+            .split(',')
+            .map(str::trim)
+            .filter(|ip_str| !ip_str.is_empty())
+            .map(|ip_str| IpAddr::from_str(ip_str).map_err(|e| {
+                ApiError::ConfigurationError(format!("Invalid address '{}' in TRUSTED_PROXIES: {}", ip_str, e))
+            }))
+            .collect::<Result<HashSet<IpAddr>, ApiError>>()?;
+
+        // Comma-separated ISO dates, e.g. "2025-12-25,2026-01-01"
+        let bank_holidays = env::var("BANK_HOLIDAYS").unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|date_str| !date_str.is_empty())
+            .map(|date_str| NaiveDate::parse_from_str(date_str, "%Y-%m-%d").map_err(|e| {
+                ApiError::ConfigurationError(format!("Invalid date '{}' in BANK_HOLIDAYS: {}", date_str, e))
+            }))
+            .collect::<Result<Vec<NaiveDate>, ApiError>>()?;
+
+        // "HH:MM", UTC
+        let eod_run_time_str = env::var("EOD_RUN_TIME").unwrap_or_else(|_| "21:00".to_string());
+        let eod_run_time = NaiveTime::parse_from_str(eod_run_time_str.trim(), "%H:%M").map_err(|e| {
+            ApiError::ConfigurationError(format!("Invalid EOD_RUN_TIME '{}': {}", eod_run_time_str, e))
+        })?;
+
 
         Ok(AppConfig {
             // Server
@@ -81,6 +188,7 @@ impl AppConfig {
             jwt_secret: get_env("JWT_SECRET")?,
             jwt_duration_hours: get_env_parse("JWT_DURATION_HOURS")?,
             allowed_ips,
+            trusted_proxies,
 
             // Integrations
             btcpay_url: get_env("BTCPAY_URL")?,
@@ -102,6 +210,81 @@ impl AppConfig {
             // ... load other keys ...
              deutsche_bank_client_id: get_env("DEUTSCHE_BANK_CLIENT_ID")?,
              deutsche_bank_client_secret: get_env("DEUTSCHE_BANK_CLIENT_SECRET")?,
+
+            // Background Workers
+            outbox_poll_interval_secs: get_env_parse::<u64>("OUTBOX_POLL_INTERVAL_SECS").unwrap_or(5),
+            scheduler_poll_interval_secs: get_env_parse::<u64>("SCHEDULER_POLL_INTERVAL_SECS").unwrap_or(60),
+
+            // Business calendar
+            bank_holidays,
+
+            // ACH origination
+            ach_odfi_routing_number: get_env("ACH_ODFI_ROUTING_NUMBER").unwrap_or_else(|_| "021000021".to_string()),
+            ach_odfi_name: get_env("ACH_ODFI_NAME").unwrap_or_else(|_| "ODFI".to_string()),
+            ach_company_id: get_env("ACH_COMPANY_ID").unwrap_or_else(|_| "1000000000".to_string()),
+            ach_company_name: get_env("ACH_COMPANY_NAME").unwrap_or_else(|_| "ELIGHTS".to_string()),
+
+            // Card Gateways
+            card_routing_config_path: get_env("CARD_ROUTING_CONFIG_PATH").unwrap_or_else(|_| "config/card_routing.json".to_string()),
+
+            // Fees
+            fee_schedule_path: get_env("FEE_SCHEDULE_PATH").unwrap_or_else(|_| "config/fee_schedule.json".to_string()),
+
+            // Approvals
+            approval_policy_path: get_env("APPROVAL_POLICY_PATH").unwrap_or_else(|_| "config/approval_policies.json".to_string()),
+            limits_policy_path: get_env("LIMITS_POLICY_PATH").unwrap_or_else(|_| "config/transaction_limits.json".to_string()),
+
+            // Sanctions
+            sanctions_config_path: get_env("SANCTIONS_CONFIG_PATH").unwrap_or_else(|_| "config/sanctions.json".to_string()),
+            sanctions_screening_enabled: get_env_parse::<bool>("SANCTIONS_SCREENING_ENABLED").unwrap_or(true),
+
+            // AML
+            aml_config_path: get_env("AML_CONFIG_PATH").unwrap_or_else(|_| "config/aml_scenarios.json".to_string()),
+            aml_monitor_interval_secs: get_env_parse::<u64>("AML_MONITOR_INTERVAL_SECS").unwrap_or(300),
+
+            // CTR
+            ctr_config_path: get_env("CTR_CONFIG_PATH").unwrap_or_else(|_| "config/ctr.json".to_string()),
+            ctr_run_interval_secs: get_env_parse::<u64>("CTR_RUN_INTERVAL_SECS").unwrap_or(3600),
+
+            // Travel Rule
+            travel_rule_config_path: get_env("TRAVEL_RULE_CONFIG_PATH").unwrap_or_else(|_| "config/travel_rule.json".to_string()),
+            travel_rule_exchange_interval_secs: get_env_parse::<u64>("TRAVEL_RULE_EXCHANGE_INTERVAL_SECS").unwrap_or(30),
+
+            // Fraud Rules
+            fraud_rules_path: get_env("FRAUD_RULES_PATH").unwrap_or_else(|_| "config/fraud_rules.toml".to_string()),
+            fraud_rules_reload_interval_secs: get_env_parse::<u64>("FRAUD_RULES_RELOAD_INTERVAL_SECS").unwrap_or(10),
+
+            // IP Intelligence
+            ip_intel_config_path: get_env("IP_INTEL_CONFIG_PATH").unwrap_or_else(|_| "config/ip_intel.json".to_string()),
+            ip_intel_enabled: get_env_parse::<bool>("IP_INTEL_ENABLED").unwrap_or(true),
+
+            // Beneficiaries
+            beneficiary_cooling_off_hours: get_env_parse::<i64>("BENEFICIARY_COOLING_OFF_HOURS").unwrap_or(24),
+            beneficiary_cooling_off_max_payment: get_env_parse::<Decimal>("BENEFICIARY_COOLING_OFF_MAX_PAYMENT").unwrap_or(Decimal::new(1000, 0)),
+            beneficiary_cooling_off_max_total: get_env_parse::<Decimal>("BENEFICIARY_COOLING_OFF_MAX_TOTAL").unwrap_or(Decimal::new(2500, 0)),
+            beneficiary_require_saved: get_env_parse::<bool>("BENEFICIARY_REQUIRE_SAVED").unwrap_or(false),
+
+            // FX
+            fx_spread_bps: get_env_parse::<i32>("FX_SPREAD_BPS").unwrap_or(50),
+            fx_quote_ttl_secs: get_env_parse::<i64>("FX_QUOTE_TTL_SECS").unwrap_or(30),
+
+            // Webhooks
+            webhook_tolerance_secs: get_env_parse::<i64>("WEBHOOK_TOLERANCE_SECS").unwrap_or(300),
+            stripe_webhook_secret: env::var("STRIPE_WEBHOOK_SECRET").ok(),
+            btcpay_webhook_secret: env::var("BTCPAY_WEBHOOK_SECRET").ok(),
+            partner_bank_webhook_public_key: env::var("PARTNER_BANK_WEBHOOK_PUBLIC_KEY").ok(),
+            webhook_allow_mock: get_env_parse::<bool>("WEBHOOK_ALLOW_MOCK").unwrap_or(false),
+
+            // Reconciliation
+            nostro_accounts_path: get_env("NOSTRO_ACCOUNTS_PATH").unwrap_or_else(|_| "config/nostro_accounts.json".to_string()),
+            reconciliation_interval_secs: get_env_parse::<u64>("RECONCILIATION_INTERVAL_SECS").unwrap_or(3600),
+
+            // Treasury
+            eod_run_time,
+            eod_report_signing_key: env::var("EOD_REPORT_SIGNING_KEY").ok(),
+
+            // Interest
+            interest_run_interval_secs: get_env_parse::<u64>("INTEREST_RUN_INTERVAL_SECS").unwrap_or(3600),
         })
     }
 }
@@ -85,6 +85,10 @@ impl ResponseError for ApiError {
                 domain::DomainError::Authentication(_) => StatusCode::UNAUTHORIZED,
                 domain::DomainError::Authorization(_) => StatusCode::FORBIDDEN,
                 domain::DomainError::InsufficientFunds(_) => StatusCode::BAD_REQUEST, // Or CONFLICT?
+                domain::DomainError::IdempotencyConflict(_) => StatusCode::CONFLICT,
+                domain::DomainError::InvalidStateTransition { .. } => StatusCode::CONFLICT,
+                domain::DomainError::QuoteExpired(_) => StatusCode::GONE,
+                domain::DomainError::LimitExceeded { .. } => StatusCode::UNPROCESSABLE_ENTITY,
                 domain::DomainError::NotSupported(_) => StatusCode::NOT_IMPLEMENTED,
                 _ => StatusCode::INTERNAL_SERVER_ERROR, // Default internal for other domain errors
             },
@@ -107,6 +111,10 @@ impl ResponseError for ApiError {
                  domain::DomainError::Authentication(m) => m.clone(),
                  domain::DomainError::Authorization(m) => m.clone(),
                   domain::DomainError::InsufficientFunds(_) => "Insufficient funds".to_string(),
+                 domain::DomainError::IdempotencyConflict(m) => m.clone(),
+                 e @ domain::DomainError::InvalidStateTransition { .. } => e.to_string(),
+                 e @ domain::DomainError::QuoteExpired(_) => e.to_string(),
+                 e @ domain::DomainError::LimitExceeded { .. } => e.to_string(),
                  // Hide internal details for other domain errors
                  _ => "An internal processing error occurred".to_string(),
              },
@@ -114,9 +122,17 @@ impl ResponseError for ApiError {
              _ => "An unexpected internal error occurred".to_string(),
         };
 
+        // Limit breaches also say which limit and when it resets, so clients can tell the user
+        let body = match self {
+            ApiError::DomainLogicError(domain::DomainError::LimitExceeded { limit_id, resets_at, .. }) => {
+                json!({ "error": error_message, "limit_id": limit_id, "resets_at": resets_at })
+            }
+            _ => json!({ "error": error_message }),
+        };
+
         HttpResponse::build(status)
             .insert_header(ContentType::json())
-            .json(json!({ "error": error_message }))
+            .json(body)
     }
 }
 
@@ -0,0 +1,170 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/handlers/aml.rs
+use crate::db::{get_db_conn, DbPool};
+use crate::error::ApiError;
+use crate::middlewares::auth_guard::{AuthenticatedUser, COMPLIANCE_ROLES};
+use actix_web::{web, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use domain::aml::{self, AmlConfig};
+use domain::models::AmlCaseStatus;
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct CasesQuery {
+    status: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AssignRequest {
+    assignee_id: Option<Uuid>, // Defaults to the caller
+}
+
+#[derive(Deserialize)]
+pub struct CloseRequest {
+    comment: String, // Required: why the activity is not suspicious
+}
+
+#[derive(Deserialize)]
+pub struct SarRequest {
+    sar_reference: String, // Reference given by the regulator on filing
+    comment: String,
+}
+
+#[derive(Deserialize)]
+pub struct BatchRequest {
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+}
+
+/// Investigation queue, highest risk first. Compliance only.
+pub async fn list_cases(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    query: web::Query<CasesQuery>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let status = match query.status.as_deref() {
+        Some(value) => Some(
+            AmlCaseStatus::parse(&value.to_uppercase())
+                .ok_or_else(|| ApiError::BadRequest(format!("Unknown status '{}'", value)))?,
+        ),
+        None => None,
+    };
+    let mut conn = get_db_conn(&db_pool)?;
+    let cases = web::block(move || aml::list_cases(&mut conn, status))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(cases))
+}
+
+/// One case with its alerts. Compliance only.
+pub async fn get_case(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let case_id = path.into_inner();
+    let mut conn = get_db_conn(&db_pool)?;
+    let case = web::block(move || aml::get_case(&mut conn, case_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(case))
+}
+
+/// Takes a case into investigation.
+pub async fn assign_case(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: web::Json<AssignRequest>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let case_id = path.into_inner();
+    let assignee_id = body.into_inner().assignee_id.unwrap_or(user.user_id);
+    log::info!("User {} assigning AML case {} to {}", user.username, case_id, assignee_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let case = web::block(move || aml::assign_case(&mut conn, case_id, user.user_id, assignee_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(case))
+}
+
+/// Closes a case with no suspicious activity found.
+pub async fn close_case(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: web::Json<CloseRequest>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let case_id = path.into_inner();
+    let comment = body.into_inner().comment;
+    log::info!("User {} closing AML case {}", user.username, case_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let case = web::block(move || aml::close_case(&mut conn, case_id, user.user_id, &comment, Utc::now()))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(case))
+}
+
+/// Records the filing of a SAR for a case.
+pub async fn file_sar(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: web::Json<SarRequest>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let case_id = path.into_inner();
+    let request = body.into_inner();
+    log::info!("User {} filing SAR {} for AML case {}", user.username, request.sar_reference, case_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let case = web::block(move || {
+        aml::file_sar(&mut conn, case_id, user.user_id, &request.sar_reference, &request.comment, Utc::now())
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(case))
+}
+
+/// SAR-ready summary of a case: subject, activity period, totals, alerts, transactions and narrative.
+pub async fn sar_summary(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let case_id = path.into_inner();
+    let mut conn = get_db_conn(&db_pool)?;
+    let summary = web::block(move || aml::sar_summary(&mut conn, case_id, Utc::now()))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(summary))
+}
+
+/// Monitors a past period in batch (e.g. after a scenario change). Compliance only.
+pub async fn run_batch(
+    db_pool: web::Data<DbPool>,
+    aml_config: web::Data<AmlConfig>,
+    user: AuthenticatedUser,
+    body: web::Json<BatchRequest>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let BatchRequest { from, to } = body.into_inner();
+    log::info!("User {} running AML batch {} - {}", user.username, from, to);
+    let mut conn = get_db_conn(&db_pool)?;
+    let summary = web::block(move || aml::run_batch(&mut conn, &aml_config, from, to))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(summary))
+}
+
+/// Scenarios in force. Compliance only.
+pub async fn list_scenarios(
+    aml_config: web::Data<AmlConfig>,
+    user: AuthenticatedUser,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    Ok(HttpResponse::Ok().json(aml_config.get_ref()))
+}
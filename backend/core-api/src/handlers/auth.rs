@@ -3,9 +3,12 @@ use crate::db::{get_db_conn, DbPool};
 use crate::error::{ApiError, internal_error};
 use crate::models::{ApiAuthResponse, ApiLoginRequest, ApiRegisterRequest, ApiRegisterResponse};
 use crate::config::AppConfig; // Import AppConfig
-use actix_web::{web, HttpResponse, Responder};
+use crate::utils::request_context;
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
 use domain::security::auth::{authenticate_user, hash_password}; // Use domain auth functions
 use domain::models::{NewUser}; // Use domain model for insertion
+use domain::ip_intel::{self, IpIntelligence};
+use chrono::Utc;
 use std::sync::Arc; // For Arc<AppConfig>
 use uuid::Uuid;
 
@@ -21,6 +24,10 @@ pub async fn register(
         return Err(ApiError::ValidationError("Password must be at least 8 characters".to_string()));
     }
     // TODO: Add email format validation
+    let declared_country = info.country_code.as_deref().map(|c| c.trim().to_uppercase());
+    if declared_country.as_deref().is_some_and(|c| c.len() != 2 || !c.chars().all(|ch| ch.is_ascii_alphabetic())) {
+        return Err(ApiError::ValidationError("country_code must be an ISO 3166-1 alpha-2 code".to_string()));
+    }
 
     // Hash password (CPU intensive, run in blocking thread)
     let password = info.password.clone();
@@ -42,6 +49,7 @@ pub async fn register(
             username: &username,
             email: &email,
             password_hash: &password_hash,
+            country_code: declared_country.as_deref(), // Compared with where requests come from by the fraud rules
         };
 
         // Insert and return the new user's ID
@@ -74,9 +82,12 @@ pub async fn register(
 }
 
 /// Handles user login requests.
+/// Successful logins are recorded with the geolocation of the client address and checked for impossible travel.
 pub async fn login(
     db_pool: web::Data<DbPool>,
     app_config: web::Data<Arc<AppConfig>>, // Get config from app state
+    ip_intel: web::Data<IpIntelligence>, // Offline GeoIP/ASN and Tor/VPN/hosting lookups of the client address
+    req: HttpRequest,
     info: web::Json<ApiLoginRequest>,
 ) -> Result<impl Responder, ApiError> {
     log::info!("Login attempt for user: {}", info.username);
@@ -85,6 +96,7 @@ pub async fn login(
     let username = info.username.clone();
     let password = info.password.clone();
     let config = app_config.get_ref().clone(); // Clone Arc<AppConfig> for blocking thread
+    let device = request_context::device_context(&req, &ip_intel);
 
     // Fetch user and authenticate (potentially blocking)
     let auth_result = web::block(move || {
@@ -102,14 +114,20 @@ pub async fn login(
 
         // 2. Authenticate (Verify password and generate token)
         // Requires JWT feature enabled in domain crate for real tokens
-        authenticate_user(
+        let auth_token = authenticate_user(
              &user.username,
              &password, // Use cloned password
              &user.password_hash,
              "user", // TODO: Get actual user role
              config.jwt_secret.as_bytes(),
              config.jwt_duration_hours,
-        )
+        )?;
+
+        // 3. Record the login; a failure to record must not lock the customer out
+        if let Err(e) = ip_intel::record_login(&mut conn, user.user_id, &device, Utc::now()) {
+            log::error!("Failed to record login of user {}: {}", user.user_id, e);
+        }
+        Ok::<_, domain::DomainError>(auth_token)
     })
     .await?; // Handle blocking error
 
@@ -0,0 +1,116 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/handlers/beneficiaries.rs
+use crate::db::{get_db_conn, DbPool};
+use crate::error::ApiError;
+use crate::middlewares::auth_guard::AuthenticatedUser;
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use domain::beneficiaries::{self, CoolingOffLimits, CreateBeneficiary, EditBeneficiary};
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct VerifyBeneficiaryRequest {
+    password: String, // Step-up: the customer's login password
+}
+
+/// Saves a payee. It has to be verified before it can be paid.
+pub async fn create_beneficiary(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    body: web::Json<CreateBeneficiary>,
+) -> Result<impl Responder, ApiError> {
+    log::info!("User {} saving a beneficiary", user.username);
+    let mut conn = get_db_conn(&db_pool)?;
+    let user_id = user.user_id;
+    let beneficiary = web::block(move || beneficiaries::create_beneficiary(&mut conn, user_id, &body))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Created().json(beneficiary))
+}
+
+/// Lists the caller's saved payees.
+pub async fn list_beneficiaries(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+) -> Result<impl Responder, ApiError> {
+    let mut conn = get_db_conn(&db_pool)?;
+    let user_id = user.user_id;
+    let list = web::block(move || beneficiaries::list_beneficiaries(&mut conn, user_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Ok().json(list))
+}
+
+/// One of the caller's saved payees.
+pub async fn get_beneficiary(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    let beneficiary_id = path.into_inner();
+    let mut conn = get_db_conn(&db_pool)?;
+    let user_id = user.user_id;
+    let beneficiary = web::block(move || beneficiaries::get_beneficiary(&mut conn, user_id, beneficiary_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Ok().json(beneficiary))
+}
+
+/// Renames a payee or changes its payment details (which requires verifying it again).
+pub async fn edit_beneficiary(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: web::Json<EditBeneficiary>,
+) -> Result<impl Responder, ApiError> {
+    let beneficiary_id = path.into_inner();
+    log::info!("User {} editing beneficiary {}", user.username, beneficiary_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let user_id = user.user_id;
+    let beneficiary = web::block(move || beneficiaries::edit_beneficiary(&mut conn, user_id, beneficiary_id, &body))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Ok().json(beneficiary))
+}
+
+/// Verifies a new or edited payee with the caller's password; its cooling-off period starts now.
+pub async fn verify_beneficiary(
+    db_pool: web::Data<DbPool>,
+    beneficiary_limits: web::Data<CoolingOffLimits>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: web::Json<VerifyBeneficiaryRequest>,
+) -> Result<impl Responder, ApiError> {
+    let beneficiary_id = path.into_inner();
+    log::info!("User {} verifying beneficiary {}", user.username, beneficiary_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let user_id = user.user_id;
+    let beneficiary = web::block(move || {
+        beneficiaries::verify_beneficiary(&mut conn, user_id, beneficiary_id, &body.password, &beneficiary_limits, Utc::now())
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Ok().json(beneficiary))
+}
+
+/// Removes a payee from the caller's list.
+pub async fn delete_beneficiary(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    let beneficiary_id = path.into_inner();
+    log::info!("User {} removing beneficiary {}", user.username, beneficiary_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let user_id = user.user_id;
+    web::block(move || beneficiaries::disable_beneficiary(&mut conn, user_id, beneficiary_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -1,78 +1,187 @@
 // /home/inno/elights_jobes-research/backend/core-api/src/handlers/conversion.rs
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{http::StatusCode, web, HttpRequest, HttpResponse, Responder};
 use serde::{Deserialize, Serialize};
+use crate::config::AppConfig;
+use crate::db::{get_db_conn, DbPool};
 use crate::error::ApiError;
+use crate::middlewares::auth_guard::AuthenticatedUser;
+use crate::utils::idempotency::{self, IdempotencyStart, SCOPE_CURRENCY_CONVERSION};
+use cryptography_exchange::{MockRateService, RateService};
+use domain::fees::FeeSchedule;
+use domain::payments::conversion::{self, LockQuote};
 use rust_decimal::Decimal;
+use std::sync::Arc;
+use uuid::Uuid;
 
 #[derive(Debug, Deserialize)]
+pub struct ConversionQuoteRequest {
+    source_wallet_id: Uuid, // Debited, in its own currency
+    target_wallet_id: Uuid, // Credited, in its own currency
+    amount: Decimal, // In the source wallet's currency, fee not included
+}
+
+#[derive(Debug, Deserialize, Serialize)] // Serialize used for idempotency fingerprinting
 pub struct CurrencyConversionRequest {
-    amount: Decimal, // Use Decimal
-    from_currency: String, // ISO 4217
-    to_currency: String,   // ISO 4217
+    quote_id: Uuid, // From POST /conversions/quote
 }
 
 #[derive(Debug, Serialize)]
 pub struct CurrencyConversionResponse {
+    quote_id: Uuid,
+    debit_transaction_id: Uuid,
+    credit_transaction_id: Uuid,
+    fee_transaction_id: Option<Uuid>,
     original_amount: String,
     converted_amount: String,
+    fee_amount: String,
     from_currency: String,
     to_currency: String,
-    rate: String,
+    rate: String, // Applied rate, spread included
+    mid_rate: String,
+    spread_bps: i32,
+    executed_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-/// Gets the current conversion rate between two currencies.
+/// Gets the current (indicative) mid rate between two currencies.
 pub async fn get_conversion_rate(
     // TODO: Inject rate service client
     query: web::Query<ConversionRateQuery>,
 ) -> Result<impl Responder, ApiError> {
     log::info!("Fetching conversion rate: {} -> {}", query.from, query.to);
-    // --- Placeholder Logic ---
-    // 1. Validate currency codes
-    // 2. Call external FX rate service API
-    let dummy_rate = Decimal::new(110, 2); // Example rate 1.10
+    let rate_service = MockRateService::default(); // Using mock for now
+    let quote = rate_service.get_rate(&query.from.to_uppercase(), &query.to.to_uppercase()).await?;
 
     Ok(HttpResponse::Ok().json(serde_json::json!({
-        "from": query.from,
-        "to": query.to,
-        "rate": dummy_rate.to_string(),
-        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "from": quote.from_currency,
+        "to": quote.to_currency,
+        "rate": quote.rate.to_string(),
+        "timestamp": quote.timestamp.to_rfc3339(),
     })))
-    // --- End Placeholder Logic ---
 }
 
+/// Locks a rate for converting between two of the caller's wallets. Requires authentication.
+/// The quote can be executed until `expires_at`.
+pub async fn lock_conversion_quote(
+    db_pool: web::Data<DbPool>,
+    app_config: web::Data<Arc<AppConfig>>,
+    fee_schedule: web::Data<FeeSchedule>,
+    user: AuthenticatedUser,
+    info: web::Json<ConversionQuoteRequest>,
+) -> Result<impl Responder, ApiError> {
+    let request = info.into_inner();
+    log::info!("User {} locking FX quote: {} from wallet {} to wallet {}",
+        user.username, request.amount, request.source_wallet_id, request.target_wallet_id);
+    if request.amount <= Decimal::ZERO {
+        return Err(ApiError::ValidationError("Amount must be positive".to_string()));
+    }
+
+    // The wallets decide the currency pair
+    let mut conn = get_db_conn(&db_pool)?;
+    let (source_wallet_id, target_wallet_id, user_id) = (request.source_wallet_id, request.target_wallet_id, user.user_id);
+    let (mut conn, from_currency, to_currency) = web::block(move || {
+        use crate::schema::wallets::dsl as w;
+        use diesel::prelude::*;
+        let currency_of = |conn: &mut diesel::PgConnection, wallet_id: Uuid| {
+            w::wallets
+                .filter(w::wallet_id.eq(wallet_id))
+                .filter(w::user_id.eq(user_id)) // Authorization check
+                .select(w::currency_code)
+                .first::<String>(conn)
+                .map_err(|e| match e {
+                    diesel::result::Error::NotFound => domain::DomainError::NotFound(format!("Wallet {} not found", wallet_id)),
+                    other => domain::DomainError::DieselError(other),
+                })
+        };
+        let from_currency = currency_of(&mut conn, source_wallet_id)?;
+        let to_currency = currency_of(&mut conn, target_wallet_id)?;
+        Ok::<_, domain::DomainError>((conn, from_currency, to_currency))
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+
+    // TODO: Use a real RateService implementation, potentially injected via web::Data
+    let rate_service = MockRateService::default(); // Using mock for now
+    let rate = rate_service.get_rate(&from_currency, &to_currency).await?;
+
+    let spread_bps = app_config.fx_spread_bps;
+    let ttl = chrono::Duration::seconds(app_config.fx_quote_ttl_secs);
+    let quote = web::block(move || {
+        conversion::lock_quote(&mut conn, LockQuote {
+            user_id,
+            source_wallet_id,
+            target_wallet_id,
+            source_amount: request.amount,
+            mid_rate: rate.rate,
+            spread_bps,
+            ttl,
+            rate_source: Some("MockRateService"),
+            fee_schedule: Some(&*fee_schedule),
+        })
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
 
-/// Performs a currency conversion.
+    Ok(HttpResponse::Created().json(quote))
+}
+
+/// Executes a locked quote: debits the source wallet and credits the target wallet as two linked
+/// Conversion transactions. Rejected once the quote has expired. Honours the `Idempotency-Key` header.
 pub async fn perform_currency_conversion(
-    // TODO: Inject rate service client
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    req: HttpRequest,
     info: web::Json<CurrencyConversionRequest>,
 ) -> Result<impl Responder, ApiError> {
-     log::info!("Performing conversion: {} {} -> {}", info.amount, info.from_currency, info.to_currency);
+    log::info!("User {} executing FX quote {}", user.username, info.quote_id);
 
-    if info.amount <= Decimal::ZERO {
-        return Err(ApiError::ValidationError("Amount must be positive".to_string()));
-    }
-     // --- Placeholder Logic ---
-     // 1. Validate currency codes
-     // 2. Fetch current conversion rate
-     let dummy_rate = Decimal::new(110, 2); // Example rate 1.10
-
-     // 3. Calculate converted amount
-     let converted_amount = info.amount * dummy_rate; // Simplified calculation
+    let idempotency_key = idempotency::idempotency_key_from_request(&req, None)?;
+    let claim = match idempotency::begin(&db_pool, Some(user.user_id), SCOPE_CURRENCY_CONVERSION, idempotency_key, &*info).await? {
+        IdempotencyStart::Replay(stored_response) => return Ok(stored_response),
+        IdempotencyStart::Proceed(claim) => claim,
+    };
 
-     // TODO: Handle potential fees, spreads, precision for real conversions
+    let mut conn = match get_db_conn(&db_pool) {
+        Ok(conn) => conn,
+        Err(e) => {
+            claim.release(&db_pool).await;
+            return Err(e);
+        }
+    };
+    let (quote_id, user_id) = (info.quote_id, user.user_id);
+    let outcome = match web::block(move || conversion::execute_quote(&mut conn, quote_id, user_id, chrono::Utc::now()))
+        .await // Handle blocking error
+        .map_err(ApiError::from)
+        .and_then(|r| r.map_err(ApiError::DomainLogicError))
+    {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            // Nothing was committed for this key, let the client retry with it
+            claim.release(&db_pool).await;
+            return Err(e);
+        }
+    };
 
-     Ok(HttpResponse::Ok().json(CurrencyConversionResponse {
-        original_amount: info.amount.to_string(),
-        converted_amount: converted_amount.to_string(),
-        from_currency: info.from_currency.clone(),
-        to_currency: info.to_currency.clone(),
-        rate: dummy_rate.to_string(),
-    }))
-     // --- End Placeholder Logic ---
+    let quote = &outcome.quote;
+    let response = CurrencyConversionResponse {
+        quote_id: quote.quote_id,
+        debit_transaction_id: outcome.debit_leg.transaction_id,
+        credit_transaction_id: outcome.credit_leg.transaction_id,
+        fee_transaction_id: outcome.fee.as_ref().map(|fee| fee.transaction_id),
+        original_amount: quote.source_amount.to_string(),
+        converted_amount: quote.target_amount.to_string(),
+        fee_amount: quote.fee_amount.to_string(),
+        from_currency: quote.from_currency.clone(),
+        to_currency: quote.to_currency.clone(),
+        rate: quote.applied_rate.to_string(),
+        mid_rate: quote.mid_rate.to_string(),
+        spread_bps: quote.spread_bps,
+        executed_at: quote.executed_at,
+    };
+    claim.complete(&db_pool, StatusCode::OK, &response, Some(outcome.debit_leg.transaction_id)).await
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ConversionRateQuery {
     from: String,
     to: String,
-}
\ No newline at end of file
+}
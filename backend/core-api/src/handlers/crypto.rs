@@ -2,27 +2,37 @@
 use crate::db::{get_db_conn, DbPool};
 use crate::error::{ApiError, internal_error};
 use crate::models::{
-    ApiCryptoConversionRequest, ApiCryptoConversionResponse, ApiCryptoWithdrawalRequest,
+    ApiCryptoConversionRequest, ApiCryptoWithdrawalRequest,
     ApiCryptoWithdrawalResponse, ApiWalletBalanceResponse
 };
 use crate::config::AppConfig;
 use crate::middlewares::auth_guard::AuthenticatedUser;
-use actix_web::{web, HttpResponse, Responder};
+use crate::utils::idempotency::{self, IdempotencyStart, SCOPE_CRYPTO_WITHDRAWAL};
+use crate::utils::request_context;
+use actix_web::{http::StatusCode, web, HttpRequest, HttpResponse, Responder};
 use std::sync::Arc;
 use uuid::Uuid;
 use rust_decimal::Decimal;
 // Import exchange clients and traits
 use cryptography_exchange::{
-    CurrencyConverter, MockCurrencyConverter, RateService, MockRateService,
+    RateService, MockRateService,
     BTCPayClient, // Assuming client is shared via web::Data
     #[cfg(feature = "monero_support")] MoneroWalletRpcClient, // Assuming client is shared
 };
 // Import domain models/utils
-use domain::crypto::utils::{atomic_units_to_xmr, xmr_to_atomic_units};
 use domain::models::{Wallet, Transaction, NewTransaction, TransactionType, TransactionStatus};
-
-
-/// Gets a conversion quote.
+use domain::travel_rule::{self, TravelRuleConfig, TravelRuleSubmission, WithdrawalCheck};
+use domain::fraud::{self, FraudCheck, FraudDetectionContext, FraudRules};
+use domain::approvals::ApprovalPolicySet;
+use domain::beneficiaries::{CheckedPayee, CoolingOffLimits};
+use domain::limits::LimitPolicy;
+use domain::payments::checks::{OutboundChecks, OutboundPayment};
+use domain::sanctions::SanctionsScreener;
+use domain::ip_intel::IpIntelligence;
+use chrono::Utc;
+
+
+/// Gets an indicative conversion rate. To convert, lock a quote via `/conversions/quote` and execute it.
 pub async fn get_conversion_quote(
     _user: AuthenticatedUser,
     info: web::Json<ApiCryptoConversionRequest>,
@@ -35,43 +45,11 @@ pub async fn get_conversion_quote(
     Ok(HttpResponse::Ok().json(quote))
 }
 
-/// Executes a crypto conversion.
-pub async fn execute_crypto_conversion(
-    _user: AuthenticatedUser,
-    info: web::Json<ApiCryptoConversionRequest>,
-    // Inject converter service if needed
-) -> Result<impl Responder, ApiError> {
-     // TODO: Use a real CurrencyConverter implementation via web::Data
-     let converter = MockCurrencyConverter::default(); // Using mock for now
-
-     let domain_request = cryptography_exchange::ConversionRequest {
-          from_currency: info.from_currency.clone(),
-          to_currency: info.to_currency.clone(),
-          amount: info.amount,
-     };
-
-     let result = converter.execute_conversion(&domain_request).await?;
-
-     // TODO: Persist conversion as a transaction in the DB
-
-     Ok(HttpResponse::Ok().json(ApiCryptoConversionResponse{
-          original_amount: result.original_amount.to_string(),
-          converted_amount: result.converted_amount.to_string(),
-          from_currency: result.from_currency,
-          to_currency: result.to_currency,
-          rate: Some(result.rate_used.to_string()),
-          timestamp: chrono::Utc::now(),
-     }))
-}
-
-
-/// Gets the balance for a specific internal crypto wallet.
+/// Gets the ledger and available balance of an internal wallet.
+/// The ledger balance is what has been posted; the available balance also excludes active holds
+/// (pending withdrawals, card authorizations, uncleared deposits).
 pub async fn get_wallet_balance(
     db_pool: web::Data<DbPool>,
-    #[cfg(feature = "monero_support")] // Conditionally inject Monero client
-    monero_client: web::Data<MoneroWalletRpcClient>,
-    // Inject BTCPay client if balance comes from there? Usually from node/RPC.
-    // btcpay_client: web::Data<BTCPayClient>,
     user: AuthenticatedUser,
     path: web::Path<Uuid>,
 ) -> Result<impl Responder, ApiError> {
@@ -79,60 +57,32 @@ pub async fn get_wallet_balance(
     log::info!("User {} fetching balance for wallet ID: {}", user.username, wallet_id);
 
     let mut conn = get_db_conn(&db_pool)?;
+    let owner_id = user.user_id;
 
-    // Fetch wallet details from DB within web::block
-    let wallet = web::block(move || {
-        use crate::schema::wallets::dsl::*;
+    // Fetch wallet (scoped to the caller) and its balances from the ledger within web::block
+    let (wallet, balances) = web::block(move || {
+        use crate::schema::wallets::dsl as w;
         use diesel::prelude::*;
-         wallets
-            .filter(wallet_id.eq(wallet_id))
-            // TODO: Add authorization check: .filter(user_id.eq(user.user_id))
+        let wallet = w::wallets
+            .filter(w::wallet_id.eq(wallet_id))
+            .filter(w::user_id.eq(owner_id)) // Authorization check
             .select(Wallet::as_select())
             .first::<Wallet>(&mut conn)
+            .map_err(|e| match e {
+                diesel::result::Error::NotFound => domain::DomainError::NotFound(format!("Wallet {} not found", wallet_id)),
+                other => domain::DomainError::DieselError(other),
+            })?;
+        let balances = domain::ledger::wallet_balances(&mut conn, wallet.wallet_id)?;
+        Ok::<_, domain::DomainError>((wallet, balances))
     })
-    .await?
-    .map_err(|e| match e {
-        diesel::result::Error::NotFound => ApiError::NotFound(format!("Wallet {} not found", wallet_id)),
-        _ => internal_error(e),
-    })?;
-
-    // TODO: Ensure user is authorized to access this wallet (user.user_id == wallet.user_id)
-    if wallet.user_id != user.user_id {
-         return Err(ApiError::AuthorizationError("User not authorized for this wallet".to_string()));
-    }
-
-    // Get balance from appropriate source based on wallet type
-    let balance: Decimal = match wallet.wallet_type.as_str() {
-        "CryptoXmr" => {
-            #[cfg(feature = "monero_support")]
-            {
-                let balance_info = monero_client.get_balance().await?;
-                // Use unlocked_balance for spendable amount
-                atomic_units_to_xmr(balance_info.unlocked_balance)
-            }
-            #[cfg(not(feature = "monero_support"))]
-            {
-                 log::error!("Monero feature not enabled, cannot get XMR balance");
-                 return Err(ApiError::InternalError("Monero support not enabled".to_string()));
-             }
-        }
-         "CryptoBtc" => {
-             // TODO: Implement balance check via BTCPay Server or connected Bitcoin node RPC
-             log::warn!("BTC balance check not implemented yet for wallet {}", wallet_id);
-             Decimal::ZERO // Placeholder
-         }
-        _ => {
-            log::error!("Balance check not supported for wallet type: {}", wallet.wallet_type);
-            return Err(ApiError::BadRequest("Unsupported wallet type for balance check".to_string()));
-        }
-    };
-
-    // Optionally update DB balance cache here? Or rely on external source?
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
 
     Ok(HttpResponse::Ok().json(ApiWalletBalanceResponse {
         wallet_id: wallet.wallet_id,
         currency: wallet.currency_code,
-        balance: balance.to_string(),
+        ledger_balance: balances.ledger_balance.to_string(),
+        available_balance: balances.available_balance.to_string(),
         wallet_type: wallet.wallet_type,
         address: wallet.address,
     }))
@@ -178,29 +128,58 @@ pub async fn get_deposit_address(
 
 
 /// Initiates a cryptocurrency withdrawal.
+/// Honours the `Idempotency-Key` header so a retried withdrawal is never broadcast twice.
+/// The debit and an outbox entry are committed together; the outbox worker broadcasts the
+/// transaction and reverses the debit if the broadcast fails.
+/// Above the Travel Rule threshold, originator and beneficiary data is required: a withdrawal missing it is
+/// refused or held (RequiresAction, nothing debited) per configuration, and one to another VASP is held until
+/// that VASP accepts the data exchange. Withdrawals the fraud rules flag are held for an analyst (RequiresAction)
+/// and those they block fail, before anything is debited.
 pub async fn initiate_crypto_withdrawal(
     db_pool: web::Data<DbPool>,
-    app_config: web::Data<Arc<AppConfig>>,
-    #[cfg(feature = "monero_support")] monero_client: web::Data<MoneroWalletRpcClient>,
-    // btcpay_client: web::Data<BTCPayClient>, // Needed for BTC payouts
+    travel_rule_config: web::Data<TravelRuleConfig>,
+    fraud_rules: web::Data<FraudRules>,
+    limit_policy: web::Data<LimitPolicy>, // Per-transaction and rolling limits
+    approval_policies: web::Data<ApprovalPolicySet>, // Payments needing a second person's approval
+    beneficiary_limits: web::Data<CoolingOffLimits>, // Saved-payee verification and cooling-off limits
+    sanctions_screener: web::Data<Option<Arc<SanctionsScreener>>>, // Watchlist screening (None when disabled)
+    ip_intel: web::Data<IpIntelligence>,
     user: AuthenticatedUser,
+    req: HttpRequest,
     info: web::Json<ApiCryptoWithdrawalRequest>,
 ) -> Result<impl Responder, ApiError> {
     log::info!("User {} initiating withdrawal from wallet {}", user.username, info.source_wallet_id);
 
-    let mut conn = get_db_conn(&db_pool)?;
-    let config = app_config.get_ref().clone(); // Clone Arc<AppConfig>
+    let idempotency_key = idempotency::idempotency_key_from_request(&req, info.idempotency_key.as_deref())?;
+    let claim = match idempotency::begin(
+        &db_pool, Some(user.user_id), SCOPE_CRYPTO_WITHDRAWAL, idempotency_key.clone(), &*info,
+    ).await? {
+        IdempotencyStart::Replay(stored_response) => return Ok(stored_response),
+        IdempotencyStart::Proceed(claim) => claim,
+    };
+
+    let mut conn = match get_db_conn(&db_pool) {
+        Ok(conn) => conn,
+        Err(e) => {
+            claim.release(&db_pool).await;
+            return Err(e);
+        }
+    };
     let request_info = info.into_inner(); // Move info out of Json
+    let device = request_context::device_context(&req, &ip_intel);
+    let rules = fraud_rules.current(); // The rules in force when the withdrawal was requested
 
-    // Fetch wallet, check balance, submit withdrawal (potentially blocking operations)
+    // Check wallet, debit it and queue the broadcast in one DB transaction
     let withdrawal_result = web::block(move || {
          use crate::schema::wallets::dsl as w;
          use crate::schema::transactions::dsl as t;
+         use crate::schema::users::dsl as u;
          use diesel::prelude::*;
-         use domain::models::WalletStatus;
-         use cryptography_exchange::monero_wallet::json_rpc::Destination; // Import Destination
+         use domain::approvals::PendingDispatch;
+         use domain::models::{OutboxOperation, WalletStatus};
+         use domain::payments::outbox::{self, OutboxPayload};
+         use domain::payments::state_machine::{self, TransitionUpdate};
 
-         // --- DB Transaction ---
          conn.transaction(|conn| {
               // 1. Fetch source wallet, check owner, status, currency, and lock
               let wallet: Wallet = w::wallets
@@ -213,119 +192,213 @@ pub async fn initiate_crypto_withdrawal(
               if wallet.status != WalletStatus::Active.to_string() {
                   return Err(domain::DomainError::Validation("Source wallet is not active".to_string()));
               }
-              // Infer currency from wallet if not provided in request, or validate if provided
+              if request_info.amount <= Decimal::ZERO {
+                  return Err(domain::DomainError::Validation("Amount must be positive".to_string()));
+              }
               let currency_code = &wallet.currency_code;
 
-              // 2. Check sufficient funds
-               // Note: Balance check against RPC is better but done outside DB transaction usually.
-               // Check against DB balance as initial guard.
-              if wallet.balance < request_info.amount {
-                  return Err(domain::DomainError::InsufficientFunds(request_info.source_wallet_id));
-              }
+              // 2. Check sufficient funds against the available balance (pending withdrawals/auths excluded)
+              domain::ledger::ensure_available(conn, request_info.source_wallet_id, request_info.amount)?;
 
-              // 3. Create Transaction record (Pending)
+              // 3. Saved payee checks (verified, cooling-off limits), then the transaction record (Pending until
+              //    the funds are held), linked to its payee
+              let transaction_type = match currency_code.as_str() { // Set type based on currency
+                  "XMR" => TransactionType::CryptoXmrSend,
+                  "BTC" => TransactionType::CryptoBtcSend,
+                  _ => return Err(domain::DomainError::NotSupported("Unsupported currency for crypto withdrawal".to_string())),
+              };
+              let payment = OutboundPayment {
+                  user_id: user.user_id,
+                  source_wallet: &wallet,
+                  payment_type: &transaction_type,
+                  amount: request_info.amount,
+                  currency: currency_code,
+                  ach_details: None,
+                  wire_details: None,
+                  crypto_address: Some(&request_info.destination_address),
+                  device: Some(&device),
+              };
+              let checks = OutboundChecks {
+                  approval_policies: Some(&*approval_policies),
+                  beneficiary_limits: Some(&*beneficiary_limits),
+                  limit_policy: Some(&*limit_policy),
+                  sanctions_screener: sanctions_screener.as_deref(),
+                  ..Default::default()
+              };
+              let payee = checks.check_payee(conn, &payment, Utc::now())?;
+              let mut metadata = json!({"destination_address": request_info.destination_address});
+              for (key, value) in payee.iter().flat_map(CheckedPayee::metadata) {
+                  metadata[key] = value;
+              }
               let new_tx = NewTransaction {
                   transaction_id: None,
                   debit_wallet_id: Some(request_info.source_wallet_id),
                   credit_wallet_id: None, // External destination
-                  transaction_type: match currency_code.as_str() { // Set type based on currency
-                        "XMR" => TransactionType::CryptoXmrSend.to_string(),
-                        "BTC" => TransactionType::CryptoBtcSend.to_string(),
-                        _ => return Err(domain::DomainError::NotSupported("Unsupported currency for crypto withdrawal".to_string())),
-                  }.as_str(),
+                  transaction_type: transaction_type.to_string().as_str(),
                   status: TransactionStatus::Pending.to_string().as_str(),
                   amount: request_info.amount,
                   currency_code: currency_code,
                   description: Some("Crypto Withdrawal"),
-                  external_ref_id: None, // Set later with network Tx Hash
-                  metadata: Some(json!({"destination_address": request_info.destination_address})),
+                  external_ref_id: None, // Set by the outbox worker with the network Tx Hash
+                  metadata: Some(metadata),
+                  scheduled_run_id: None,
               };
               let transaction: Transaction = diesel::insert_into(t::transactions)
                   .values(&new_tx)
                   .get_result(conn)?;
 
-             // 4. Debit Source Wallet DB Balance
-             let new_balance = wallet.balance - request_info.amount;
-             diesel::update(w::wallets.find(request_info.source_wallet_id))
-                 .set(w::balance.eq(domain::utils::decimal_to_bigdecimal(new_balance))) // Use BigDecimal
-                 .execute(conn)?;
+              // 4. Limits count the withdrawal until it fails, is cancelled or expires; a breach rolls it back.
+              //    A destination address on a watchlist stops the withdrawal for compliance review
+              checks.consume_limits(conn, &payment, &transaction, Utc::now())?;
+              let payload = OutboxPayload {
+                  crypto_address: Some(request_info.destination_address.clone()),
+                  crypto_payment_id: request_info.payment_id.clone(),
+                  ..Default::default()
+              };
+              let dispatch = PendingDispatch {
+                  operation: OutboxOperation::CryptoSend,
+                  payload: payload.clone(),
+                  fee_quote: None,
+                  idempotency_key: idempotency_key.clone(),
+              };
+              if let Some(transaction) = checks.screen_sanctions(conn, &payment, &transaction, &dispatch, Utc::now())? {
+                  return Ok(transaction);
+              }
+
+              // 5. Travel Rule: record originator/beneficiary data; a withdrawal waiting for it (or for the
+              //    beneficiary VASP) is held here, before anything is debited or queued
+              let travel_rule_metadata = match travel_rule::check_withdrawal(
+                  conn, &travel_rule_config, &transaction, user.user_id, &request_info.destination_address,
+                  request_info.travel_rule.as_ref(), &dispatch, Utc::now(),
+              )? {
+                  WithdrawalCheck::Held(transaction) => return Ok(transaction),
+                  WithdrawalCheck::Proceed { metadata } => metadata,
+              };
 
-              // --- Commit DB Transaction before external call ---
-              Ok((transaction, wallet.wallet_type, currency_code.to_string())) // Pass necessary info out
-         }) // End DB transaction
+              // 6. Fraud rules: a flagged withdrawal waits for an analyst, a blocked one fails
+              let customer: Option<domain::models::User> = u::users.find(user.user_id).first(conn).optional()?;
+              let context = FraudDetectionContext {
+                  transaction: &transaction,
+                  source_wallet: Some(&wallet),
+                  user: customer.as_ref(),
+                  device: Some(&device),
+                  destination_country: None,
+                  destination_address: Some(&request_info.destination_address),
+              };
+              let mut metadata = travel_rule_metadata.unwrap_or_else(|| serde_json::json!({}));
+              match fraud::screen_payment(conn, &rules, &context, user.user_id, &dispatch, Utc::now())? {
+                  FraudCheck::Stopped(transaction) => return Ok(transaction),
+                  FraudCheck::Proceed { metadata: fraud_metadata } => {
+                      if let (Some(merged), Some(fraud_metadata)) = (metadata.as_object_mut(), fraud_metadata.as_object()) {
+                          merged.extend(fraud_metadata.clone());
+                      }
+                  }
+              }
+
+              // 7. Second pair of eyes: a withdrawal matching an approval policy waits for approval. The Travel Rule
+              //    and fraud results are recorded first so they stay on a parked withdrawal
+              let update = TransitionUpdate { metadata: Some(metadata), ..Default::default() };
+              let transaction = state_machine::apply_transition(conn, &transaction, TransactionStatus::Pending, update, &user.user_id.to_string())?;
+              if let Some(transaction) = checks.require_approval(conn, &payment, &transaction, &dispatch, Utc::now())? {
+                  return Ok(transaction);
+              }
+
+              // 8. Hold the funds: Pending -> Processing places a wallet hold (available balance only, no journal entry)
+              let transaction = state_machine::apply_transition(conn, &transaction, TransactionStatus::Processing, TransitionUpdate::default(), &user.user_id.to_string())?;
+
+              // 9. Queue the broadcast in the same commit
+              outbox::enqueue_outbound(conn, &transaction, OutboxOperation::CryptoSend, &payload, idempotency_key.as_deref())?;
+
+              Ok(transaction)
+         })
     }).await?; // Handle blocking error
 
-    // --- External Call (Outside DB Transaction) ---
-    let (mut transaction, wallet_type, currency_code) = withdrawal_result.map_err(ApiError::DomainLogicError)?; // Get result or map domain error
-
-    let external_call_result: Result<String, ExchangeError> = match wallet_type.as_str() {
-         "CryptoXmr" => {
-            #[cfg(feature = "monero_support")]
-            {
-                let atomic_amount = xmr_to_atomic_units(request_info.amount)
-                    .ok_or(ExchangeError::InvalidInput("Invalid amount for XMR conversion".to_string()))?;
-                 let dest = Destination { amount: atomic_amount, address: request_info.destination_address };
-                 // Use injected Monero client
-                 // Need to acquire client data again as it wasn't passed to web::block
-                 // This highlights complexity of mixing shared state and web::block
-                 // Alternative: Initialize client inside web::block using config? Less ideal.
-                 // For now, assume we can get the client again (this needs refinement)
-                 let temp_monero_client = web::Data::new(MoneroWalletRpcClient::new()?); // Re-init - **IMPROVE THIS**
-
-                 temp_monero_client.transfer(vec![dest], request_info.payment_id, None, None).await
-                     .map(|res| res.tx_hash) // Return Tx Hash on success
-            }
-             #[cfg(not(feature = "monero_support"))]
-             { Err(ExchangeError::ConfigurationError("Monero support not enabled".to_string())) }
-         }
-         "CryptoBtc" => {
-             // TODO: Use injected BTCPayClient to create a Payout
-             // let btcpay_client = ... // Get from web::Data again (needs refinement)
-             // let payout_req = CreatePayoutRequest { ... };
-             // btcpay_client.create_payout(Some(&config.btcpay_default_store_id), &payout_req).await
-             //   .map(|res| res.payout_id) // Return Payout ID on success
-             Err(ExchangeError::InternalError("BTC payout not implemented".to_string())) // Placeholder
-         }
-        _ => Err(ExchangeError::UnsupportedCurrency(wallet_type)),
+    let transaction = match withdrawal_result {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            // DB transaction rolled back, nothing was debited
+            claim.release(&db_pool).await;
+            return Err(ApiError::DomainLogicError(e));
+        }
     };
 
-    // --- Final DB Update (New Transaction) ---
-    let mut conn = get_db_conn(&db_pool)?; // Get new connection for update
-     let final_status;
-     let external_ref;
-     match external_call_result {
-         Ok(tx_hash_or_ref) => {
-             final_status = TransactionStatus::Submitted; // Or Completed if call is synchronous
-             external_ref = Some(tx_hash_or_ref);
-             log::info!("Crypto withdrawal successful for Tx: {}, Ref: {}", transaction.transaction_id, external_ref.as_deref().unwrap_or("-"));
-         }
-         Err(e) => {
-             final_status = TransactionStatus::Failed;
-             external_ref = None;
-             log::error!("Crypto withdrawal failed for Tx: {}: {}", transaction.transaction_id, e);
-              // TODO: CRITICAL - Re-credit source wallet in a new DB transaction!
-         }
-     };
+    let held_for_fraud_review = transaction.metadata.as_ref()
+        .and_then(|m| m.pointer("/fraud/status"))
+        .is_some_and(|s| s == "OPEN");
+    let held_for_compliance = transaction.metadata.as_ref()
+        .and_then(|m| m.pointer("/sanctions/case_id"))
+        .is_some();
+    let awaiting_approval = transaction.metadata.as_ref()
+        .and_then(|m| m.pointer("/approval/approval_id"))
+        .is_some();
+    let response = if transaction.status == TransactionStatus::RequiresAction.to_string() && awaiting_approval {
+        ApiCryptoWithdrawalResponse {
+            transaction_id: transaction.transaction_id,
+            status: TransactionStatus::RequiresAction,
+            message: "Withdrawal awaiting approval".to_string(),
+        }
+    } else if transaction.status == TransactionStatus::RequiresAction.to_string() && (held_for_fraud_review || held_for_compliance) {
+        ApiCryptoWithdrawalResponse {
+            transaction_id: transaction.transaction_id,
+            status: TransactionStatus::RequiresAction,
+            message: "Withdrawal held for review".to_string(),
+        }
+    } else if transaction.status == TransactionStatus::RequiresAction.to_string() {
+        ApiCryptoWithdrawalResponse {
+            transaction_id: transaction.transaction_id,
+            status: TransactionStatus::RequiresAction,
+            message: "Withdrawal held for Travel Rule information, see /crypto/withdrawals/{id}/travel-rule".to_string(),
+        }
+    } else if transaction.status == TransactionStatus::Failed.to_string() {
+        ApiCryptoWithdrawalResponse {
+            transaction_id: transaction.transaction_id,
+            status: TransactionStatus::Failed,
+            message: "Withdrawal declined".to_string(),
+        }
+    } else {
+        ApiCryptoWithdrawalResponse {
+            transaction_id: transaction.transaction_id,
+            status: TransactionStatus::Processing,
+            message: "Withdrawal queued for broadcast".to_string(),
+        }
+    };
+    claim.complete(&db_pool, StatusCode::ACCEPTED, &response, Some(transaction.transaction_id)).await
+}
 
-     // Update transaction in DB (new transaction)
-     transaction = web::block(move || {
-          let update_tx = UpdateTransaction {
-              status: Some(final_status.to_string().as_str()),
-              external_ref_id: external_ref.as_deref(),
-              metadata: transaction.metadata, // Keep existing metadata unless updated
-              settlement_at: if final_status == TransactionStatus::Completed { Some(chrono::Utc::now()) } else { None },
-          };
-          diesel::update(crate::schema::transactions::table.find(transaction.transaction_id))
-              .set(&update_tx)
-              .get_result(&mut conn)
-      }).await?.map_err(internal_error)?; // Handle potential DB error on update
-
-
-    Ok(HttpResponse::Accepted().json(ApiCryptoWithdrawalResponse {
-        transaction_id: transaction.transaction_id,
-        status: final_status, // Return the final status after external call attempt
-        message: "Withdrawal submitted".to_string(),
-    }))
+
+/// Gets the Travel Rule data recorded for one of the caller's withdrawals and where its exchange stands.
+pub async fn get_withdrawal_travel_rule(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    let transaction_id = path.into_inner();
+    let mut conn = get_db_conn(&db_pool)?;
+    let transfer = web::block(move || travel_rule::transfer_for_withdrawal(&mut conn, transaction_id, user.user_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(transfer))
+}
+
+/// Supplies the Travel Rule data missing from a held withdrawal. Once complete the withdrawal waits for the
+/// beneficiary VASP, or is debited and queued at once when it goes to a self-hosted wallet.
+pub async fn supply_withdrawal_travel_rule(
+    db_pool: web::Data<DbPool>,
+    travel_rule_config: web::Data<TravelRuleConfig>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    info: web::Json<TravelRuleSubmission>,
+) -> Result<impl Responder, ApiError> {
+    let transaction_id = path.into_inner();
+    let submission = info.into_inner();
+    log::info!("User {} supplying Travel Rule data for withdrawal {}", user.username, transaction_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let transfer = web::block(move || {
+        travel_rule::supply_data(&mut conn, &travel_rule_config, transaction_id, user.user_id, &submission, Utc::now())
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(transfer))
 }
 
 
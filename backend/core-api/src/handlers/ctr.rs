@@ -0,0 +1,249 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/handlers/ctr.rs
+use crate::db::{get_db_conn, DbPool};
+use crate::error::ApiError;
+use crate::middlewares::auth_guard::{AuthenticatedUser, COMPLIANCE_ROLES};
+use actix_web::http::header;
+use actix_web::{web, HttpResponse, Responder};
+use chrono::{NaiveDate, Utc};
+use domain::ctr::{self, CtrAcknowledgement, CtrConfig, GrantExemption};
+use domain::models::{CtrExemptionType, CtrReportStatus};
+use domain::payments::BusinessCalendar;
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct ReportsQuery {
+    status: Option<String>,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+#[derive(Deserialize)]
+pub struct RunRequest {
+    business_date: NaiveDate,
+}
+
+#[derive(Deserialize)]
+pub struct AcknowledgementRequest {
+    acknowledgements: Vec<CtrAcknowledgement>,
+}
+
+#[derive(Deserialize)]
+pub struct ExemptionsQuery {
+    user_id: Option<Uuid>,
+}
+
+#[derive(Deserialize)]
+pub struct GrantExemptionRequest {
+    user_id: Uuid,
+    exemption_type: String, // PHASE_I or PHASE_II
+    reason: String,
+    effective_from: NaiveDate,
+    #[serde(default)]
+    expires_on: Option<NaiveDate>,
+}
+
+/// Reports by status and business day. Compliance only.
+pub async fn list_reports(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    query: web::Query<ReportsQuery>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let ReportsQuery { status, from, to } = query.into_inner();
+    let status = match status.as_deref() {
+        Some(value) => Some(
+            CtrReportStatus::parse(&value.to_uppercase())
+                .ok_or_else(|| ApiError::BadRequest(format!("Unknown status '{}'", value)))?,
+        ),
+        None => None,
+    };
+    let mut conn = get_db_conn(&db_pool)?;
+    let reports = web::block(move || ctr::list_reports(&mut conn, status, from, to))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(reports))
+}
+
+pub async fn get_report(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let report_id = path.into_inner();
+    let mut conn = get_db_conn(&db_pool)?;
+    let report = web::block(move || ctr::get_report(&mut conn, report_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// (Re-)aggregates one business day, e.g. after late corrections. Compliance only.
+pub async fn run_business_day(
+    db_pool: web::Data<DbPool>,
+    ctr_config: web::Data<CtrConfig>,
+    calendar: web::Data<BusinessCalendar>,
+    user: AuthenticatedUser,
+    body: web::Json<RunRequest>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let business_date = body.into_inner().business_date;
+    if business_date >= Utc::now().date_naive() {
+        return Err(ApiError::BadRequest("Only completed business days can be reported".to_string()));
+    }
+    log::info!("User {} running CTR aggregation for {}", user.username, business_date);
+    let mut conn = get_db_conn(&db_pool)?;
+    let summary = web::block(move || ctr::run_business_day(&mut conn, &ctr_config, &calendar, business_date))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(summary))
+}
+
+pub async fn list_batches(db_pool: web::Data<DbPool>, user: AuthenticatedUser) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let mut conn = get_db_conn(&db_pool)?;
+    let batches = web::block(move || ctr::list_batches(&mut conn))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(batches))
+}
+
+/// Puts every report waiting to be filed into a new FinCEN batch file.
+pub async fn generate_batch(
+    db_pool: web::Data<DbPool>,
+    ctr_config: web::Data<CtrConfig>,
+    user: AuthenticatedUser,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    log::info!("User {} generating a CTR batch", user.username);
+    let mut conn = get_db_conn(&db_pool)?;
+    let batch = web::block(move || ctr::generate_batch(&mut conn, &ctr_config, user.user_id, Utc::now()))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Created().json(batch))
+}
+
+pub async fn get_batch(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let batch_id = path.into_inner();
+    let mut conn = get_db_conn(&db_pool)?;
+    let batch = web::block(move || ctr::get_batch(&mut conn, batch_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(batch))
+}
+
+/// The batch XML file, for upload to BSA E-Filing.
+pub async fn download_batch(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let batch_id = path.into_inner();
+    let mut conn = get_db_conn(&db_pool)?;
+    let batch = web::block(move || ctr::get_batch(&mut conn, batch_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok()
+        .content_type("application/xml")
+        .insert_header((header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", batch.file_name)))
+        .body(batch.xml))
+}
+
+/// Records that the batch was uploaded.
+pub async fn mark_batch_submitted(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let batch_id = path.into_inner();
+    log::info!("User {} marking CTR batch {} submitted", user.username, batch_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let batch = web::block(move || ctr::mark_batch_submitted(&mut conn, batch_id, user.user_id, Utc::now()))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(batch))
+}
+
+/// Applies FinCEN's acknowledgement: BSA IDs for accepted reports, errors for rejected ones.
+pub async fn record_acknowledgement(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: web::Json<AcknowledgementRequest>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let batch_id = path.into_inner();
+    let acknowledgements = body.into_inner().acknowledgements;
+    log::info!("User {} recording the acknowledgement of CTR batch {}", user.username, batch_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let batch = web::block(move || {
+        ctr::record_acknowledgement(&mut conn, batch_id, &acknowledgements, user.user_id, Utc::now())
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(batch))
+}
+
+pub async fn list_exemptions(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    query: web::Query<ExemptionsQuery>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let user_id = query.user_id;
+    let mut conn = get_db_conn(&db_pool)?;
+    let exemptions = web::block(move || ctr::list_exemptions(&mut conn, user_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(exemptions))
+}
+
+/// Exempts a customer from CTR filing from a date.
+pub async fn grant_exemption(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    body: web::Json<GrantExemptionRequest>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let request = body.into_inner();
+    let exemption_type = CtrExemptionType::parse(&request.exemption_type.to_uppercase())
+        .ok_or_else(|| ApiError::BadRequest(format!("Unknown exemption type '{}'", request.exemption_type)))?;
+    log::info!("User {} granting a {} CTR exemption to {}", user.username, exemption_type.as_str(), request.user_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let exemption = web::block(move || {
+        let grant = GrantExemption {
+            user_id: request.user_id,
+            exemption_type,
+            reason: &request.reason,
+            effective_from: request.effective_from,
+            expires_on: request.expires_on,
+        };
+        ctr::grant_exemption(&mut conn, &grant, user.user_id)
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Created().json(exemption))
+}
+
+pub async fn revoke_exemption(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let exemption_id = path.into_inner();
+    log::info!("User {} revoking CTR exemption {}", user.username, exemption_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let exemption = web::block(move || ctr::revoke_exemption(&mut conn, exemption_id, user.user_id, Utc::now()))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(exemption))
+}
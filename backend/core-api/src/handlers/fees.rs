@@ -0,0 +1,65 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/handlers/fees.rs
+use crate::db::{get_db_conn, DbPool};
+use crate::error::ApiError;
+use crate::middlewares::auth_guard::{AuthenticatedUser, FINANCE_ROLES};
+use crate::models::{ApiFeeQuoteRequest, ApiFeeRevenueQuery};
+use actix_web::{web, HttpResponse, Responder};
+use domain::fees::{self, FeeSchedule};
+use rust_decimal::Decimal;
+
+/// Prices a payment for the calling user before it is submitted. Requires authentication.
+pub async fn quote_fee(
+    db_pool: web::Data<DbPool>,
+    fee_schedule: web::Data<FeeSchedule>,
+    user: AuthenticatedUser,
+    info: web::Json<ApiFeeQuoteRequest>,
+) -> Result<impl Responder, ApiError> {
+    let request = info.into_inner();
+    if request.amount <= Decimal::ZERO {
+        return Err(ApiError::ValidationError("Amount must be positive".to_string()));
+    }
+    if request.currency.len() != 3 || !request.currency.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(ApiError::ValidationError(format!("Invalid currency code: {}", request.currency)));
+    }
+    log::info!("User {} requesting fee quote: {:?} {} {}", user.username, request.payment_type, request.amount, request.currency);
+
+    let mut conn = get_db_conn(&db_pool)?;
+    let user_id = user.user_id;
+    let quote = web::block(move || {
+        fees::quote_for_user(
+            &mut conn, &fee_schedule, user_id, &request.payment_type, request.amount,
+            &request.currency.to_uppercase(), request.charge_bearer.unwrap_or_default(),
+        )
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?
+    .ok_or_else(|| ApiError::BadRequest("Payment type is not priced".to_string()))?;
+
+    Ok(HttpResponse::Ok().json(quote))
+}
+
+/// Fee income per currency and rail for a period. Finance/admin only.
+pub async fn get_fee_revenue(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    query: web::Query<ApiFeeRevenueQuery>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let query = query.into_inner();
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from >= to {
+            return Err(ApiError::ValidationError("'from' must be before 'to'".to_string()));
+        }
+    }
+    log::info!("User {} requesting fee revenue (currency {:?}, {:?} - {:?})", user.username, query.currency, query.from, query.to);
+
+    let mut conn = get_db_conn(&db_pool)?;
+    let report = web::block(move || {
+        let currency = query.currency.map(|c| c.to_uppercase());
+        fees::fee_revenue(&mut conn, currency.as_deref(), query.from, query.to)
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
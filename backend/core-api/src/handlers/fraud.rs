@@ -0,0 +1,240 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/handlers/fraud.rs
+use crate::db::{get_db_conn, DbPool};
+use crate::error::ApiError;
+use crate::middlewares::auth_guard::{AuthenticatedUser, ADMIN_ROLES, COMPLIANCE_ROLES};
+use actix_web::{web, HttpResponse, Responder};
+use chrono::{DateTime, Duration, Utc};
+use domain::approvals::ApprovalPolicySet;
+use domain::fraud::{self, FraudAction, FraudRuleSet, FraudRules};
+use domain::ip_intel::{self, IpIntelligence};
+use domain::models::FraudReviewStatus;
+use serde::Deserialize;
+use serde_json::json;
+use std::net::IpAddr;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct EvaluationsQuery {
+    review_status: Option<String>,
+    decision: Option<String>, // Applied decision
+}
+
+#[derive(Deserialize)]
+pub struct ReviewRequest {
+    comment: String, // Required: why the payment is (or is not) fraudulent
+}
+
+#[derive(Deserialize)]
+pub struct PeriodQuery {
+    from: Option<DateTime<Utc>>, // Default: 30 days ago
+    to: Option<DateTime<Utc>>, // Default: now
+}
+
+#[derive(Deserialize)]
+pub struct ReplayRequest {
+    rules: String, // Candidate rule file content
+    #[serde(default)]
+    format: Option<String>, // "toml" (default) or "yaml"
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+pub struct LoginsQuery {
+    limit: Option<i64>, // Default 50, at most 500
+}
+
+fn period(from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> Result<(DateTime<Utc>, DateTime<Utc>), ApiError> {
+    let to = to.unwrap_or_else(Utc::now);
+    let from = from.unwrap_or(to - Duration::days(30));
+    if from >= to {
+        return Err(ApiError::BadRequest("'from' must be before 'to'".to_string()));
+    }
+    Ok((from, to))
+}
+
+/// Evaluations newest first, e.g. the review queue (`review_status=OPEN`). Compliance only.
+pub async fn list_evaluations(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    query: web::Query<EvaluationsQuery>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let review_status = match query.review_status.as_deref() {
+        Some(value) => Some(
+            FraudReviewStatus::parse(&value.to_uppercase())
+                .ok_or_else(|| ApiError::BadRequest(format!("Unknown review status '{}'", value)))?,
+        ),
+        None => None,
+    };
+    let decision = match query.decision.as_deref() {
+        Some(value) => Some(
+            FraudAction::parse(&value.to_uppercase())
+                .ok_or_else(|| ApiError::BadRequest(format!("Unknown decision '{}'", value)))?,
+        ),
+        None => None,
+    };
+    let mut conn = get_db_conn(&db_pool)?;
+    let evaluations = web::block(move || fraud::list_evaluations(&mut conn, review_status, decision))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(evaluations))
+}
+
+/// One evaluation with its features and the rules that fired. Compliance only.
+pub async fn get_evaluation(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let evaluation_id = path.into_inner();
+    let mut conn = get_db_conn(&db_pool)?;
+    let evaluation = web::block(move || fraud::get_evaluation(&mut conn, evaluation_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(evaluation))
+}
+
+/// The evaluation of a payment. Compliance only.
+pub async fn get_transaction_evaluation(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let transaction_id = path.into_inner();
+    let mut conn = get_db_conn(&db_pool)?;
+    let evaluation = web::block(move || fraud::evaluation_for_transaction(&mut conn, transaction_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(evaluation))
+}
+
+/// Clears a payment held for review; it goes on to approval (if a policy requires it) or to its rail.
+pub async fn clear_review(
+    db_pool: web::Data<DbPool>,
+    approval_policies: web::Data<ApprovalPolicySet>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: web::Json<ReviewRequest>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let evaluation_id = path.into_inner();
+    let comment = body.into_inner().comment;
+    log::info!("User {} clearing fraud review {}", user.username, evaluation_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let evaluation = web::block(move || {
+        fraud::clear_review(&mut conn, evaluation_id, user.user_id, &comment, Some(&approval_policies), Utc::now())
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(evaluation))
+}
+
+/// Confirms fraud on a payment held for review; the payment is cancelled.
+pub async fn confirm_review(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: web::Json<ReviewRequest>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let evaluation_id = path.into_inner();
+    let comment = body.into_inner().comment;
+    log::info!("User {} confirming fraud review {}", user.username, evaluation_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let evaluation = web::block(move || fraud::confirm_review(&mut conn, evaluation_id, user.user_id, &comment, Utc::now()))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(evaluation))
+}
+
+/// The rule set in force and the file it was loaded from. Compliance only.
+pub async fn get_rules(fraud_rules: web::Data<FraudRules>, user: AuthenticatedUser) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    Ok(HttpResponse::Ok().json(json!({
+        "path": fraud_rules.path().map(|p| p.display().to_string()),
+        "rules": fraud_rules.current().as_ref(),
+    })))
+}
+
+/// Reloads the rule file now instead of waiting for the watcher. Admin only.
+pub async fn reload_rules(fraud_rules: web::Data<FraudRules>, user: AuthenticatedUser) -> Result<impl Responder, ApiError> {
+    user.require_role(ADMIN_ROLES)?;
+    let reloaded = fraud_rules.reload_if_changed().map_err(ApiError::DomainLogicError)?;
+    log::info!("User {} requested a fraud rule reload (changed: {})", user.username, reloaded);
+    Ok(HttpResponse::Ok().json(json!({"reloaded": reloaded, "version": fraud_rules.current().version})))
+}
+
+/// How often each rule fired over a period and how its hits were reviewed. Compliance only.
+pub async fn rule_stats(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    query: web::Query<PeriodQuery>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let (from, to) = period(query.from, query.to)?;
+    let mut conn = get_db_conn(&db_pool)?;
+    let stats = web::block(move || fraud::rule_hit_stats(&mut conn, from, to))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(json!({"from": from, "to": to, "rules": stats})))
+}
+
+/// Runs a candidate rule file over the features recorded for past payments, without changing anything.
+pub async fn replay_rules(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    body: web::Json<ReplayRequest>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let request = body.into_inner();
+    let (from, to) = period(request.from, request.to)?;
+    let candidate = match request.format.as_deref().map(str::to_lowercase).as_deref() {
+        None | Some("toml") => FraudRuleSet::from_toml_str(&request.rules),
+        Some("yaml") | Some("yml") => FraudRuleSet::from_yaml_str(&request.rules),
+        Some(other) => return Err(ApiError::BadRequest(format!("Unknown rule file format '{}'", other))),
+    }
+    .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let mut conn = get_db_conn(&db_pool)?;
+    let summary = web::block(move || fraud::replay_rules(&mut conn, &candidate, from, to))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(summary))
+}
+
+/// The IP intelligence sources in use (database builds, list sizes, risk weights). Compliance only.
+pub async fn ip_intel_status(ip_intel: web::Data<IpIntelligence>, user: AuthenticatedUser) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    Ok(HttpResponse::Ok().json(ip_intel.status()))
+}
+
+/// What the local databases and lists know about an address. Compliance only.
+pub async fn lookup_ip(
+    ip_intel: web::Data<IpIntelligence>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let raw = path.into_inner();
+    let ip = raw.parse::<IpAddr>().map_err(|_| ApiError::BadRequest(format!("Invalid IP address '{}'", raw)))?;
+    Ok(HttpResponse::Ok().json(json!({"ip": ip, "info": ip_intel.lookup(ip)})))
+}
+
+/// A customer's recent logins with their geolocation and impossible travel flags, newest first. Compliance only.
+pub async fn list_user_logins(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    query: web::Query<LoginsQuery>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let user_id = path.into_inner();
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+    let mut conn = get_db_conn(&db_pool)?;
+    let logins = web::block(move || ip_intel::list_logins(&mut conn, user_id, limit))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(logins))
+}
@@ -0,0 +1,122 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/handlers/inbound.rs
+use crate::db::{get_db_conn, DbPool};
+use crate::error::ApiError;
+use crate::middlewares::auth_guard::{AuthenticatedUser, FINANCE_ROLES};
+use actix_web::{web, HttpResponse, Responder};
+use domain::payments::inbound::{self, InboundDisposition, InboundNotice};
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct AssignSuspenseRequest {
+    wallet_id: Uuid,
+    #[serde(default)]
+    note: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ReturnSuspenseRequest {
+    #[serde(default)]
+    note: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct OpenVirtualAccountRequest {
+    wallet_id: Uuid,
+}
+
+/// Takes a normalized inbound notice from a rail adapter. Finance/admin only.
+pub async fn receive_inbound_notice(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    body: web::Json<InboundNotice>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let notice = body.into_inner();
+    log::info!("User {} submitting inbound {} notice {}", user.username, notice.rail.as_str(), notice.external_reference);
+
+    let mut conn = get_db_conn(&db_pool)?;
+    let outcome = web::block(move || inbound::process_inbound(&mut conn, &notice))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+
+    Ok(match outcome.disposition {
+        InboundDisposition::Duplicate => HttpResponse::Ok().json(outcome),
+        _ => HttpResponse::Created().json(outcome),
+    })
+}
+
+/// Unmatched inbound payments awaiting a decision. Finance/admin only.
+pub async fn list_open_suspense(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let mut conn = get_db_conn(&db_pool)?;
+    let items = web::block(move || inbound::list_open_suspense(&mut conn))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Ok().json(items))
+}
+
+/// Credits a suspense item to a wallet. Finance/admin only.
+pub async fn assign_suspense(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: web::Json<AssignSuspenseRequest>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let suspense_id = path.into_inner();
+    let body = body.into_inner();
+    log::info!("User {} assigning suspense item {} to wallet {}", user.username, suspense_id, body.wallet_id);
+
+    let mut conn = get_db_conn(&db_pool)?;
+    let resolution = web::block(move || {
+        inbound::assign_suspense(&mut conn, suspense_id, body.wallet_id, user.user_id, &user.username, body.note.as_deref())
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Ok().json(resolution))
+}
+
+/// Sends a suspense item back to the originator. Finance/admin only.
+pub async fn return_suspense(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: web::Json<ReturnSuspenseRequest>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let suspense_id = path.into_inner();
+    let body = body.into_inner();
+    log::info!("User {} returning suspense item {}", user.username, suspense_id);
+
+    let mut conn = get_db_conn(&db_pool)?;
+    let resolution = web::block(move || {
+        inbound::return_suspense(&mut conn, suspense_id, user.user_id, &user.username, body.note.as_deref())
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Ok().json(resolution))
+}
+
+/// Issues a virtual account number that routes ACH credits and wires to one of the caller's wallets.
+pub async fn open_virtual_account(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    body: web::Json<OpenVirtualAccountRequest>,
+) -> Result<impl Responder, ApiError> {
+    let wallet_id = body.wallet_id;
+    log::info!("User {} opening a virtual account for wallet {}", user.username, wallet_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let user_id = user.user_id;
+    let account = web::block(move || inbound::open_virtual_account(&mut conn, wallet_id, user_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Created().json(account))
+}
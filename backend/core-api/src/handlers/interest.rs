@@ -0,0 +1,127 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/handlers/interest.rs
+use crate::db::{get_db_conn, DbPool};
+use crate::error::ApiError;
+use crate::middlewares::auth_guard::{AuthenticatedUser, FINANCE_ROLES};
+use actix_web::{web, HttpResponse, Responder};
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use domain::interest::{self, CreateRateVersion};
+use domain::models::Wallet;
+use domain::DomainError;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct RateVersionsQuery {
+    wallet_type: Option<String>,
+    currency: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AccrualsQuery {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+#[derive(Serialize)]
+struct WalletInterestResponse {
+    wallet_id: Uuid,
+    #[serde(with = "rust_decimal::serde::str")]
+    accrued_total: rust_decimal::Decimal, // Sum of the listed days, unrounded
+    accruals: Vec<domain::models::InterestAccrual>,
+    postings: Vec<domain::models::InterestPosting>,
+}
+
+/// The wallet if the user owns it (finance/admin may read any wallet).
+fn readable_wallet(conn: &mut diesel::PgConnection, user: &AuthenticatedUser, wallet_id: Uuid) -> Result<Wallet, DomainError> {
+    use crate::schema::wallets::dsl as w;
+    use diesel::prelude::*;
+    let mut query = w::wallets.filter(w::wallet_id.eq(wallet_id)).into_boxed();
+    if user.require_role(FINANCE_ROLES).is_err() {
+        query = query.filter(w::user_id.eq(user.user_id)); // Authorization check
+    }
+    query.first(conn).map_err(|e| match e {
+        diesel::result::Error::NotFound => DomainError::NotFound(format!("Wallet {} not found", wallet_id)),
+        other => DomainError::DieselError(other),
+    })
+}
+
+/// Records a new rate version (possibly backdated; affected days are recomputed). Finance/admin only.
+pub async fn create_rate_version(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    body: web::Json<CreateRateVersion>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let request = body.into_inner();
+    log::info!(
+        "User {} creating interest rate version for {} {} effective {}",
+        user.username, request.wallet_type, request.currency_code, request.effective_from
+    );
+    let mut conn = get_db_conn(&db_pool)?;
+    let version = web::block(move || interest::create_rate_version(&mut conn, &request, Some(user.user_id), &user.username))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Created().json(version))
+}
+
+/// Rate versions, newest effective date first. Finance/admin only.
+pub async fn list_rate_versions(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    query: web::Query<RateVersionsQuery>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let query = query.into_inner();
+    let mut conn = get_db_conn(&db_pool)?;
+    let versions = web::block(move || {
+        interest::list_rate_versions(&mut conn, query.wallet_type.as_deref(), query.currency.as_deref())
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(versions))
+}
+
+/// Daily accruals (default: the current month) and capitalizations of a wallet.
+pub async fn get_wallet_interest(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    query: web::Query<AccrualsQuery>,
+) -> Result<impl Responder, ApiError> {
+    let wallet_id = path.into_inner();
+    let today = Utc::now().date_naive();
+    let to = query.to.unwrap_or(today - Duration::days(1));
+    let from = query.from.unwrap_or_else(|| to.with_day(1).unwrap_or(to));
+    if from > to {
+        return Err(ApiError::BadRequest("'from' is after 'to'".to_string()));
+    }
+    let mut conn = get_db_conn(&db_pool)?;
+    let response = web::block(move || {
+        let wallet = readable_wallet(&mut conn, &user, wallet_id)?;
+        let accruals = interest::list_accruals(&mut conn, wallet.wallet_id, from, to)?;
+        let postings = interest::list_postings(&mut conn, wallet.wallet_id)?;
+        Ok::<_, DomainError>(WalletInterestResponse {
+            wallet_id: wallet.wallet_id,
+            accrued_total: accruals.iter().map(|accrual| accrual.amount).sum(),
+            accruals,
+            postings,
+        })
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Runs accrual through yesterday and the previous month's capitalization now. Finance/admin only.
+pub async fn run_interest(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    log::info!("User {} running interest accrual and capitalization", user.username);
+    let mut conn = get_db_conn(&db_pool)?;
+    let summary = web::block(move || interest::run_interest(&mut conn, Utc::now(), &user.username))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(summary))
+}
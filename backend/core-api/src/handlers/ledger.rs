@@ -0,0 +1,52 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/handlers/ledger.rs
+use crate::db::{get_db_conn, DbPool};
+use crate::error::ApiError;
+use crate::middlewares::auth_guard::{AuthenticatedUser, FINANCE_ROLES};
+use crate::models::ApiTrialBalanceQuery;
+use actix_web::{web, HttpResponse, Responder};
+use domain::ledger;
+
+/// Per-currency debit/credit totals for a period. Finance/admin only.
+pub async fn get_trial_balance(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    query: web::Query<ApiTrialBalanceQuery>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let query = query.into_inner();
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from >= to {
+            return Err(ApiError::ValidationError("'from' must be before 'to'".to_string()));
+        }
+    }
+    log::info!("User {} requesting trial balance (currency {:?}, {:?} - {:?})", user.username, query.currency, query.from, query.to);
+
+    let mut conn = get_db_conn(&db_pool)?;
+    let trial_balance = web::block(move || {
+        let currency = query.currency.map(|c| c.to_uppercase());
+        ledger::trial_balance(&mut conn, currency.as_deref(), query.from, query.to)
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+
+    if !trial_balance.balanced {
+        log::error!("Trial balance does not balance: {:?}", trial_balance.rows);
+    }
+    Ok(HttpResponse::Ok().json(trial_balance))
+}
+
+/// Wallets whose cached balance differs from the ledger. Finance/admin only.
+pub async fn get_wallet_balance_drift(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let mut conn = get_db_conn(&db_pool)?;
+    let drift = web::block(move || ledger::wallet_balance_drift(&mut conn))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    if !drift.is_empty() {
+        log::error!("{} wallet(s) out of step with the ledger", drift.len());
+    }
+    Ok(HttpResponse::Ok().json(drift))
+}
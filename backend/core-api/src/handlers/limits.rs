@@ -0,0 +1,175 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/handlers/limits.rs
+use crate::db::{get_db_conn, DbPool};
+use crate::error::ApiError;
+use crate::middlewares::auth_guard::{AuthenticatedUser, ADMIN_ROLES, FINANCE_ROLES};
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use domain::limits::{self, LimitIncreaseRequest, LimitPolicy};
+use domain::models::{LimitOverrideStatus, LimitSubjectType};
+use domain::DomainError;
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct UsageQuery {
+    user_id: Option<Uuid>, // Finance/admin only; defaults to the caller
+}
+
+#[derive(Deserialize)]
+pub struct IncreasesQuery {
+    status: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DecisionRequest {
+    #[serde(default)]
+    comment: Option<String>,
+}
+
+/// Ids of the wallets owned by `user_id`.
+fn wallet_ids_of(conn: &mut diesel::PgConnection, user_id: Uuid) -> Result<Vec<Uuid>, DomainError> {
+    use crate::schema::wallets::dsl as w;
+    use diesel::prelude::*;
+    Ok(w::wallets.filter(w::user_id.eq(user_id)).select(w::wallet_id).load(conn)?)
+}
+
+/// Current-window usage against each limit, with remaining amounts and reset times.
+pub async fn get_usage(
+    db_pool: web::Data<DbPool>,
+    limit_policy: web::Data<LimitPolicy>,
+    user: AuthenticatedUser,
+    query: web::Query<UsageQuery>,
+) -> Result<impl Responder, ApiError> {
+    let user_id = match query.user_id {
+        Some(other) if other != user.user_id => {
+            user.require_role(FINANCE_ROLES)?;
+            other
+        }
+        _ => user.user_id,
+    };
+    let mut conn = get_db_conn(&db_pool)?;
+    let usage = web::block(move || {
+        let wallet_ids = wallet_ids_of(&mut conn, user_id)?;
+        limits::current_usage(&mut conn, &limit_policy, user_id, &wallet_ids, Utc::now())
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(usage))
+}
+
+/// Asks for a temporary increase on the caller's own user or wallet (finance/admin: on anyone's).
+/// It takes effect only once an admin approves it.
+pub async fn request_increase(
+    db_pool: web::Data<DbPool>,
+    limit_policy: web::Data<LimitPolicy>,
+    user: AuthenticatedUser,
+    body: web::Json<LimitIncreaseRequest>,
+) -> Result<impl Responder, ApiError> {
+    let request = body.into_inner();
+    let subject = limit_policy
+        .rule(&request.rule_id)
+        .map(|rule| rule.subject)
+        .ok_or_else(|| ApiError::NotFound(format!("Limit rule '{}' not found", request.rule_id)))?;
+    log::info!("User {} requesting a temporary increase of limit {} for {}", user.username, request.rule_id, request.subject_id);
+
+    let mut conn = get_db_conn(&db_pool)?;
+    let increase = web::block(move || {
+        if user.require_role(FINANCE_ROLES).is_err() {
+            let owned = match subject {
+                LimitSubjectType::User => request.subject_id == user.user_id,
+                LimitSubjectType::Wallet => wallet_ids_of(&mut conn, user.user_id)?.contains(&request.subject_id),
+            };
+            if !owned {
+                return Err(DomainError::NotFound(format!("{} {} not found", subject.as_str(), request.subject_id)));
+            }
+        }
+        limits::request_increase(&mut conn, &limit_policy, &request, user.user_id, &user.username, Utc::now())
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Created().json(increase))
+}
+
+/// Increase requests, newest first. Customers see their own; finance/admin see all.
+pub async fn list_increases(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    query: web::Query<IncreasesQuery>,
+) -> Result<impl Responder, ApiError> {
+    let status = match query.status.as_deref() {
+        Some(value) => Some(
+            LimitOverrideStatus::parse(&value.to_uppercase())
+                .ok_or_else(|| ApiError::BadRequest(format!("Unknown status '{}'", value)))?,
+        ),
+        None => None,
+    };
+    let mut conn = get_db_conn(&db_pool)?;
+    let increases = web::block(move || {
+        if user.require_role(FINANCE_ROLES).is_ok() {
+            return limits::list_overrides(&mut conn, status, None);
+        }
+        let mut subject_ids = wallet_ids_of(&mut conn, user.user_id)?;
+        subject_ids.push(user.user_id);
+        limits::list_overrides(&mut conn, status, Some(&subject_ids))
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(increases))
+}
+
+/// Approves a pending increase. Admin only, and not the requester.
+pub async fn approve_increase(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: Option<web::Json<DecisionRequest>>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(ADMIN_ROLES)?;
+    let override_id = path.into_inner();
+    let comment = body.and_then(|body| body.into_inner().comment);
+    let mut conn = get_db_conn(&db_pool)?;
+    let increase = web::block(move || {
+        limits::approve_increase(&mut conn, override_id, user.user_id, &user.username, comment.as_deref(), Utc::now())
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(increase))
+}
+
+/// Rejects a pending increase. Admin only.
+pub async fn reject_increase(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: Option<web::Json<DecisionRequest>>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(ADMIN_ROLES)?;
+    let override_id = path.into_inner();
+    let comment = body.and_then(|body| body.into_inner().comment);
+    let mut conn = get_db_conn(&db_pool)?;
+    let increase = web::block(move || {
+        limits::reject_increase(&mut conn, override_id, user.user_id, &user.username, comment.as_deref(), Utc::now())
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(increase))
+}
+
+/// Ends an approved increase early. Admin only.
+pub async fn revoke_increase(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: Option<web::Json<DecisionRequest>>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(ADMIN_ROLES)?;
+    let override_id = path.into_inner();
+    let comment = body.and_then(|body| body.into_inner().comment);
+    let mut conn = get_db_conn(&db_pool)?;
+    let increase = web::block(move || {
+        limits::revoke_increase(&mut conn, override_id, user.user_id, &user.username, comment.as_deref(), Utc::now())
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(increase))
+}
@@ -1,6 +1,22 @@
 // /home/inno/elights_jobes-research/backend/core-api/src/handlers/mod.rs
+pub mod aml;
 pub mod auth;
+pub mod beneficiaries;
+pub mod conversion;
+pub mod ctr;
 pub mod crypto;
+pub mod fees;
+pub mod fraud;
 pub mod ft_integration;
+pub mod inbound;
+pub mod interest;
+pub mod ledger;
+pub mod limits;
 pub mod payments;
+pub mod payouts;
+pub mod reconciliation;
+pub mod sanctions;
+pub mod schedules;
+pub mod travel_rule;
+pub mod treasury;
 // pub mod health; // Optional
\ No newline at end of file
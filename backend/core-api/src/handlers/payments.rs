@@ -3,34 +3,73 @@ use crate::db::{get_db_conn, DbPool};
 use crate::error::{ApiError, internal_error};
 use crate::models::{ApiInitiatePaymentRequest, ApiPaymentResponse, ApiPaymentStatusResponse};
 use crate::config::AppConfig;
-use crate::middlewares::auth_guard::AuthenticatedUser; // Import claims from auth middleware
-use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use crate::middlewares::auth_guard::{AuthenticatedUser, FINANCE_ROLES}; // Import claims from auth middleware
+use crate::utils::idempotency::{self, IdempotencyStart, SCOPE_PAYMENT_INITIATE};
+use crate::utils::request_context;
+use actix_web::{http::StatusCode, web, HttpRequest, HttpResponse, Responder};
 use domain::payments::{PaymentProcessor, PaymentRequest as DomainPaymentRequest}; // Use domain processor/request
 use domain::models::{Transaction, AchDetails, WireDetails, CheckDetails}; // Import domain details
 use std::sync::Arc;
 use uuid::Uuid;
-use bank_integrations::gateway::MockPaymentGateway; // Using mock gateway for now
+use domain::payments::PaymentGateway; // Card gateway router built from CARD_ROUTING_CONFIG_PATH
+use domain::fees::FeeSchedule;
+use domain::approvals::{self, ApprovalPolicySet, Approver};
+use domain::beneficiaries::CoolingOffLimits;
+use domain::limits::LimitPolicy;
+use domain::sanctions::SanctionsScreener;
+use domain::fraud::FraudRules;
+use domain::ip_intel::IpIntelligence;
+use domain::models::{TransactionStatus, WebhookEvent, WebhookEventStatus};
+use domain::webhooks::{self, WebhookDispatch, WebhookRegistry, WebhookRequest};
+use chrono::Utc;
+use serde::Deserialize;
 
 /// Initiates a payment. Requires authentication.
+/// Honours the `Idempotency-Key` header: a retry replays the original response instead of paying twice.
 pub async fn initiate_payment(
     db_pool: web::Data<DbPool>,
     _app_config: web::Data<Arc<AppConfig>>, // Get config if needed by processor
+    fee_schedule: web::Data<FeeSchedule>, // Fees charged on top of / out of the payment
+    approval_policies: web::Data<ApprovalPolicySet>, // Payments needing a second person's approval
+    beneficiary_limits: web::Data<CoolingOffLimits>, // Saved-payee verification and cooling-off limits
+    limit_policy: web::Data<LimitPolicy>, // Per-transaction and rolling limits
+    sanctions_screener: web::Data<Option<Arc<SanctionsScreener>>>, // Watchlist screening (None when disabled)
+    fraud_rules: web::Data<FraudRules>, // Fraud rules in force (hot-reloaded)
+    ip_intel: web::Data<IpIntelligence>, // Offline GeoIP/ASN and Tor/VPN/hosting lookups of the client address
+    card_gateway: web::Data<dyn PaymentGateway>, // One gateway for the whole service (authorizations outlive a request)
     user: AuthenticatedUser, // Claims from AuthGuard middleware
+    req: HttpRequest,
     info: web::Json<ApiInitiatePaymentRequest>,
 ) -> Result<impl Responder, ApiError> {
     log::info!("User {} initiating payment: Type={:?}, Amount={} {}",
         user.username, info.payment_type, info.amount, info.currency);
 
-    let mut conn = get_db_conn(&db_pool)?;
+    // --- Idempotency Check ---
+    let idempotency_key = idempotency::idempotency_key_from_request(&req, info.idempotency_key.as_deref())?;
+    let claim = match idempotency::begin(
+        &db_pool, Some(user.user_id), SCOPE_PAYMENT_INITIATE, idempotency_key.clone(), &*info,
+    ).await? {
+        IdempotencyStart::Replay(stored_response) => return Ok(stored_response),
+        IdempotencyStart::Proceed(claim) => claim,
+    };
+
+    let mut conn = match get_db_conn(&db_pool) {
+        Ok(conn) => conn,
+        Err(e) => {
+            claim.release(&db_pool).await;
+            return Err(e);
+        }
+    };
 
     // --- Create Domain Payment Request ---
     // TODO: Map API request fields to DomainPaymentRequest fields carefully
     // Requires parsing API details into domain detail structs
     let domain_ach_details: Option<AchDetails> = if let (Some(r), Some(a)) = (&info.ach_routing, &info.ach_account) {
-         Some(AchDetails { routing_number: r.clone(), account_number: a.clone() })
+         Some(AchDetails { routing_number: r.clone(), account_number: a.clone(), ..Default::default() })
     } else { None };
     // TODO: Map other detail types (Wire, Check) similarly
 
+    let device = request_context::device_context(&req, &ip_intel); // Client IP and its intelligence, user agent, device id for the fraud rules
     let domain_request = DomainPaymentRequest {
         initiating_user_id: user.user_id, // Get user ID from JWT claims
         amount: info.amount,
@@ -45,27 +84,60 @@ pub async fn initiate_payment(
         crypto_address: None, // Not for fiat payments
         description: info.description.as_deref().unwrap_or("Payment Initiation"),
         metadata: info.metadata.clone(),
+        idempotency_key: idempotency_key.as_deref(),
+        charge_bearer: info.charge_bearer.unwrap_or_default(),
+        device: Some(&device),
+        scheduled_run_id: None, // Only the payment scheduler sets it
     };
 
     // --- Use Payment Processor ---
     // TODO: Inject real card gateway implementation based on config
-    let mock_card_gateway = MockPaymentGateway::default();
-
-    let processor = PaymentProcessor::new(&mut conn, &mock_card_gateway);
+    let rules = fraud_rules.current(); // The same rules for the whole payment, even if the file is reloaded meanwhile
+    let mut processor = PaymentProcessor::new(&mut conn, card_gateway.get_ref())
+        .with_fee_schedule(&fee_schedule)
+        .with_approval_policies(&approval_policies)
+        .with_beneficiary_limits(&beneficiary_limits)
+        .with_limit_policy(&limit_policy)
+        .with_fraud_rules(&rules);
+    if let Some(screener) = sanctions_screener.as_deref() {
+        processor = processor.with_sanctions_screener(screener);
+    }
 
     // Processor handles DB transaction, validation, debit, external calls (stubs), status updates
     // Run the processor logic in a blocking thread if it makes synchronous DB calls heavily
-    let transaction_result = web::block(move || processor.process_outbound_payment(domain_request))
-        .await? // Handle blocking error
-        .map_err(ApiError::DomainLogicError)?; // Map DomainError
+    let transaction_result = match web::block(move || processor.process_outbound_payment(domain_request))
+        .await // Handle blocking error
+        .map_err(ApiError::from)
+        .and_then(|r| r.map_err(ApiError::DomainLogicError)) // Map DomainError
+    {
+        Ok(tx) => tx,
+        Err(e) => {
+            // Nothing was committed for this key, let the client retry with it
+            claim.release(&db_pool).await;
+            return Err(e);
+        }
+    };
 
-    Ok(HttpResponse::Accepted().json(ApiPaymentResponse {
+    let status = TransactionStatus::from_str(&transaction_result.status)
+        .unwrap_or(TransactionStatus::Unknown); // Convert string back to enum
+    let held_for_screening = transaction_result.metadata.as_ref().is_some_and(|m| m.get("sanctions").is_some());
+    let held_for_fraud_review = transaction_result.metadata.as_ref()
+        .and_then(|m| m.pointer("/fraud/status"))
+        .is_some_and(|s| s == "OPEN");
+    let message = match status {
+        TransactionStatus::RequiresAction if held_for_screening => format!("Payment {:?} is held for compliance review.", info.payment_type),
+        TransactionStatus::RequiresAction if held_for_fraud_review => format!("Payment {:?} is held for review.", info.payment_type),
+        TransactionStatus::Failed => format!("Payment {:?} was declined.", info.payment_type),
+        TransactionStatus::RequiresAction => format!("Payment {:?} is awaiting approval.", info.payment_type),
+        _ => format!("Payment {:?} submitted successfully.", info.payment_type),
+    };
+    let response = ApiPaymentResponse {
         transaction_id: transaction_result.transaction_id,
-        status: domain::models::TransactionStatus::from_str(&transaction_result.status)
-            .unwrap_or(domain::models::TransactionStatus::Unknown), // Convert string back to enum
-        message: format!("Payment {:?} submitted successfully.", info.payment_type),
+        status,
+        message,
         created_at: transaction_result.created_at,
-    }))
+    };
+    claim.complete(&db_pool, StatusCode::ACCEPTED, &response, Some(transaction_result.transaction_id)).await
 }
 
 /// Gets the status of a specific payment transaction. Requires authentication.
@@ -114,52 +186,213 @@ pub async fn get_payment_status(
     Ok(HttpResponse::Ok().json(response))
 }
 
-/// Handles incoming payment webhooks. Public endpoint, requires signature verification.
+#[derive(Debug, Deserialize)]
+pub struct ApprovalDecisionRequest {
+    comment: Option<String>, // Kept with the decision and in the audit log
+}
+
+/// Payments awaiting the caller's approval (not their own, not already decided), oldest deadline first.
+pub async fn list_pending_approvals(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+) -> Result<impl Responder, ApiError> {
+    let mut conn = get_db_conn(&db_pool)?;
+    let approvals = web::block(move || {
+        let role = user.role.to_lowercase();
+        approvals::list_pending_approvals(&mut conn, &Approver { user_id: user.user_id, role: &role }, Utc::now())
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Ok().json(approvals))
+}
+
+/// Approval state and decisions of a payment. Visible to its initiator and to eligible approvers.
+pub async fn get_payment_approval(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    let transaction_id = path.into_inner();
+    let mut conn = get_db_conn(&db_pool)?;
+    let approval = web::block(move || {
+        let role = user.role.to_lowercase();
+        approvals::get_approval(&mut conn, transaction_id, &Approver { user_id: user.user_id, role: &role })
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Ok().json(approval))
+}
+
+/// Approves a payment awaiting approval. The last required approval releases it to its rail.
+pub async fn approve_payment(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: web::Json<ApprovalDecisionRequest>,
+) -> Result<impl Responder, ApiError> {
+    let transaction_id = path.into_inner();
+    log::info!("User {} approving payment {}", user.username, transaction_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let approval = web::block(move || {
+        let role = user.role.to_lowercase();
+        approvals::approve_payment(&mut conn, transaction_id, &Approver { user_id: user.user_id, role: &role },
+            body.comment.as_deref(), Utc::now())
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Ok().json(approval))
+}
+
+/// Rejects a payment awaiting approval; it is cancelled without any funds having moved.
+pub async fn reject_payment(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: web::Json<ApprovalDecisionRequest>,
+) -> Result<impl Responder, ApiError> {
+    let transaction_id = path.into_inner();
+    log::info!("User {} rejecting payment {}", user.username, transaction_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let approval = web::block(move || {
+        let role = user.role.to_lowercase();
+        approvals::reject_payment(&mut conn, transaction_id, &Approver { user_id: user.user_id, role: &role },
+            body.comment.as_deref(), Utc::now())
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Ok().json(approval))
+}
+
+/// What a webhook delivery came to; worked out on the blocking pool.
+enum WebhookOutcome {
+    InProgress(WebhookEvent), // Another delivery of the same event is applying it
+    Duplicate(WebhookEvent), // Already applied (or failed for good); only acknowledged
+    Dispatched(WebhookDispatch),
+}
+
+/// Handles incoming payment webhooks. Public endpoint: the provider's signature is the authentication.
+/// Deliveries are verified and stored before anything is applied; a replayed event is only acknowledged.
 pub async fn handle_payment_webhook(
     db_pool: web::Data<DbPool>,
-    _app_config: web::Data<Arc<AppConfig>>, // For webhook secrets
+    registry: web::Data<WebhookRegistry>, // Providers enabled by configuration
+    card_gateway: web::Data<dyn PaymentGateway>,
     path: web::Path<String>, // Get provider name from path
     payload: web::Bytes, // Raw payload for signature verification
     req: HttpRequest,
 ) -> Result<impl Responder, ApiError> {
-    let provider = path.into_inner();
-    log::info!("Received payment webhook from provider: {}", provider);
-
-    // --- 1. Signature Verification ---
-    // TODO: Implement signature verification based on provider
-    match provider.as_str() {
-        // "stripe" => {
-        //     let sig_header = req.headers().get("Stripe-Signature")....;
-        //     let secret = app_config.stripe_webhook_secret;
-        //     verify_stripe_signature(&payload, sig_header, &secret)?;
-        // }
-        "mock" => { // Allow mock provider for testing
-            log::warn!("Processing mock webhook - skipping signature verification.");
-         }
-        _ => {
-            log::error!("Unsupported webhook provider: {}", provider);
-            return Err(ApiError::BadRequest("Unsupported webhook provider".to_string()));
-        }
+    let provider_name = path.into_inner();
+    log::info!("Received payment webhook from provider: {}", provider_name);
+    if registry.get(&provider_name).is_none() {
+        log::error!("Unsupported webhook provider: {}", provider_name);
+        return Err(ApiError::NotFound("Unsupported webhook provider".to_string()));
     }
+    let headers: Vec<(String, String)> = req.headers().iter()
+        .filter_map(|(name, value)| value.to_str().ok().map(|value| (name.as_str().to_string(), value.to_string())))
+        .collect();
+    let received_at = Utc::now();
 
-    // --- 2. Parse Payload ---
-     let event_data: serde_json::Value = serde_json::from_slice(&payload)
-         .map_err(|e| ApiError::BadRequest(format!("Invalid webhook JSON payload: {}", e)))?;
-    log::debug!("Webhook payload parsed: {:?}", event_data);
+    let mut conn = get_db_conn(&db_pool)?;
+    let registry = registry.into_inner();
+    let card_gateway = card_gateway.into_inner();
+    let name = provider_name.clone();
+    let outcome = web::block(move || {
+        let provider = registry.get(&name)
+            .ok_or_else(|| domain::DomainError::Internal(format!("Webhook provider '{}' disappeared", name)))?;
+        let request = WebhookRequest { headers, body: &payload, received_at };
+        let delivery = webhooks::accept_delivery(&mut conn, provider, &request)?;
+        if delivery.in_progress() {
+            return Ok(WebhookOutcome::InProgress(delivery.event));
+        }
+        if !delivery.needs_processing() {
+            return Ok(WebhookOutcome::Duplicate(delivery.event));
+        }
+        // Webhooks only move existing payments between statuses; no gateway calls are made
+        let mut processor = PaymentProcessor::new(&mut conn, card_gateway.as_ref());
+        futures::executor::block_on(processor.handle_webhook_event(provider, &delivery.event))
+            .map(WebhookOutcome::Dispatched)
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?; // Non-2xx makes the provider redeliver
+
+    let dispatch = match outcome {
+        // Non-2xx makes the provider retry later
+        WebhookOutcome::InProgress(event) => return Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "event_id": event.event_id,
+            "status": event.status,
+            "duplicate": true,
+        }))),
+        WebhookOutcome::Duplicate(event) => return Ok(HttpResponse::Ok().json(serde_json::json!({
+            "event_id": event.event_id,
+            "status": event.status,
+            "duplicate": true,
+        }))),
+        WebhookOutcome::Dispatched(dispatch) => dispatch,
+    };
+
+    log::info!("Webhook from provider '{}' processed successfully.", provider_name);
+    Ok(HttpResponse::Ok().json(dispatch)) // Return 200 OK to acknowledge
+}
 
+#[derive(Deserialize)]
+pub struct WebhookEventsQuery {
+    provider: Option<String>,
+    status: Option<String>,
+    limit: Option<i64>,
+}
 
-    // --- 3. Process Event & Update DB ---
-    // Use PaymentProcessor or dedicated webhook handler service
+/// Stored webhook deliveries, newest first. Finance/admin only.
+pub async fn list_webhook_events(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    query: web::Query<WebhookEventsQuery>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let query = query.into_inner();
+    let status = query.status.as_deref()
+        .map(|s| WebhookEventStatus::parse(s).ok_or_else(|| ApiError::BadRequest(format!("Unknown webhook status '{}'", s))))
+        .transpose()?;
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
     let mut conn = get_db_conn(&db_pool)?;
-    // TODO: Implement processor.handle_webhook_event(...)
-    // This function needs to parse the provider-specific payload,
-    // map it to internal status updates (e.g., Completed, Failed, Returned),
-    // and call processor.update_payment_status(...) within a DB transaction.
-    // Example conceptual call:
-    // processor.handle_webhook_event(&provider, event_data).await?;
-
-    log::info!("Webhook from provider '{}' processed successfully.", provider);
-    Ok(HttpResponse::Ok().finish()) // Return 200 OK to acknowledge
+    let events = web::block(move || webhooks::list_events(&mut conn, query.provider.as_deref(), status, limit))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(events))
+}
+
+/// Applies a stored delivery again from its raw payload (e.g. after fixing the cause of a failure).
+/// Finance/admin only.
+pub async fn reprocess_webhook_event(
+    db_pool: web::Data<DbPool>,
+    registry: web::Data<WebhookRegistry>,
+    card_gateway: web::Data<dyn PaymentGateway>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let event_id = path.into_inner();
+    log::info!("User {} reprocessing webhook event {}", user.username, event_id);
+
+    let mut conn = get_db_conn(&db_pool)?;
+    let registry = registry.into_inner();
+    let card_gateway = card_gateway.into_inner();
+    let dispatch = web::block(move || {
+        let event = webhooks::get_event(&mut conn, event_id).map_err(ApiError::DomainLogicError)?;
+        let provider = registry.get(&event.provider)
+            .ok_or_else(|| ApiError::BadRequest(format!("Webhook provider '{}' is not enabled", event.provider)))?;
+        let event = webhooks::claim_for_reprocess(&mut conn, event_id)
+            .map_err(ApiError::DomainLogicError)?
+            .ok_or_else(|| ApiError::BadRequest(format!("Webhook event {} is being processed, try again later", event_id)))?;
+
+        let mut processor = PaymentProcessor::new(&mut conn, card_gateway.as_ref());
+        futures::executor::block_on(processor.handle_webhook_event(provider, &event))
+            .map_err(ApiError::DomainLogicError)
+    })
+    .await??; // Blocking error, then the handler's own
+    Ok(HttpResponse::Ok().json(dispatch))
 }
 
 // Helper for enum FromStr (add to domain models or utils)
@@ -169,6 +402,7 @@ impl std::str::FromStr for domain::models::TransactionStatus {
          match s {
              "Pending" => Ok(Self::Pending), "Processing" => Ok(Self::Processing),
              "RequiresAction" => Ok(Self::RequiresAction), "Authorized" => Ok(Self::Authorized),
+             "Capturing" => Ok(Self::Capturing),
              "Submitted" => Ok(Self::Submitted), "Settled" => Ok(Self::Settled),
              "Completed" => Ok(Self::Completed), "Failed" => Ok(Self::Failed),
              "Cancelled" => Ok(Self::Cancelled), "Returned" => Ok(Self::Returned),
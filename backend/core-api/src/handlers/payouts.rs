@@ -0,0 +1,119 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/handlers/payouts.rs
+use crate::db::{get_db_conn, DbPool};
+use crate::error::ApiError;
+use crate::middlewares::auth_guard::AuthenticatedUser;
+use actix_web::{http::header, web, HttpRequest, HttpResponse, Responder};
+use domain::approvals::ApprovalPolicySet;
+use domain::beneficiaries::CoolingOffLimits;
+use domain::fees::{ChargeBearer, FeeSchedule};
+use domain::fraud::FraudRules;
+use domain::limits::LimitPolicy;
+use domain::payments::checks::OutboundChecks;
+use domain::payments::payout_batch::{self, CreatePayoutBatch, PayoutFileFormat};
+use domain::sanctions::SanctionsScreener;
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Largest payout file body accepted (5000 rows fit comfortably).
+pub const MAX_PAYOUT_FILE_BYTES: usize = 5 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+pub struct PayoutUploadQuery {
+    source_wallet_id: Uuid, // Debited for every row, in its own currency
+    file_name: Option<String>, // Original file name, kept for the report
+    charge_bearer: Option<ChargeBearer>, // Applies to every row, defaults to SHA
+}
+
+/// File format from the Content-Type header: `text/csv` or `application/json`.
+fn file_format(req: &HttpRequest) -> Result<PayoutFileFormat, ApiError> {
+    let content_type = req.headers().get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_lowercase();
+    if content_type.starts_with("text/csv") {
+        Ok(PayoutFileFormat::Csv)
+    } else if content_type.starts_with("application/json") {
+        Ok(PayoutFileFormat::Json)
+    } else {
+        Err(ApiError::BadRequest("Payout files must be sent as text/csv or application/json".to_string()))
+    }
+}
+
+/// Uploads a payout file (ACH credits and outbound wires). Every row is validated and goes through the
+/// same payee, limit, sanctions, fraud and approval checks as a single payment; valid rows are booked
+/// against the source wallet in one go and the response carries the per-row report.
+/// Fails without booking anything when the wallet cannot cover all valid rows.
+pub async fn create_payout_batch(
+    db_pool: web::Data<DbPool>,
+    fee_schedule: web::Data<FeeSchedule>,
+    approval_policies: web::Data<ApprovalPolicySet>, // Payments needing a second person's approval
+    beneficiary_limits: web::Data<CoolingOffLimits>, // Saved-payee verification and cooling-off limits
+    limit_policy: web::Data<LimitPolicy>, // Per-transaction and rolling limits
+    sanctions_screener: web::Data<Option<Arc<SanctionsScreener>>>, // Watchlist screening (None when disabled)
+    fraud_rules: web::Data<FraudRules>, // Fraud rules in force (hot-reloaded)
+    user: AuthenticatedUser,
+    req: HttpRequest,
+    query: web::Query<PayoutUploadQuery>,
+    body: web::Bytes,
+) -> Result<impl Responder, ApiError> {
+    let format = file_format(&req)?;
+    let query = query.into_inner();
+    log::info!("User {} uploading {} payout file {:?} ({} bytes) for wallet {}",
+        user.username, format.as_str(), query.file_name, body.len(), query.source_wallet_id);
+
+    let mut conn = get_db_conn(&db_pool)?;
+    let user_id = user.user_id;
+    let rules = fraud_rules.current(); // The same rules for every row of the file
+    let report = web::block(move || {
+        let checks = OutboundChecks {
+            approval_policies: Some(&*approval_policies),
+            beneficiary_limits: Some(&*beneficiary_limits),
+            limit_policy: Some(&*limit_policy),
+            sanctions_screener: sanctions_screener.as_deref(),
+            fraud_rules: Some(&*rules),
+        };
+        payout_batch::create_batch(&mut conn, CreatePayoutBatch {
+            user_id,
+            source_wallet_id: query.source_wallet_id,
+            format,
+            file_name: query.file_name.as_deref(),
+            content: &body,
+            charge_bearer: query.charge_bearer.unwrap_or_default(),
+        }, Some(&*fee_schedule), checks)
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Created().json(report))
+}
+
+/// Lists the caller's payout batches with their settled / failed / returned counts.
+pub async fn list_payout_batches(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+) -> Result<impl Responder, ApiError> {
+    let mut conn = get_db_conn(&db_pool)?;
+    let user_id = user.user_id;
+    let batches = web::block(move || payout_batch::list_batches(&mut conn, user_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Ok().json(batches))
+}
+
+/// One of the caller's payout batches with its per-row report.
+pub async fn get_payout_batch(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    let batch_id = path.into_inner();
+    let mut conn = get_db_conn(&db_pool)?;
+    let user_id = user.user_id;
+    let report = web::block(move || payout_batch::get_batch(&mut conn, batch_id, user_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
@@ -0,0 +1,227 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/handlers/reconciliation.rs
+use crate::db::{get_db_conn, DbPool};
+use crate::error::ApiError;
+use crate::middlewares::auth_guard::{AuthenticatedUser, FINANCE_ROLES};
+use crate::services::reconciliation_worker::BankStatementSource;
+use actix_web::{web, HttpResponse, Responder};
+use chrono::{Duration, NaiveDate, Utc};
+use domain::models::{ExceptionResolution, ExceptionStatus, ExceptionType};
+use domain::reconciliation::{self, NostroAccountSet};
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct RunReconciliationRequest {
+    #[serde(default = "default_pull")]
+    pull: bool, // false = only re-match statement lines already stored
+}
+
+fn default_pull() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+pub struct StatementLinesQuery {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    #[serde(default)]
+    unmatched: bool,
+}
+
+#[derive(Deserialize)]
+pub struct ExceptionsQuery {
+    account: Option<String>,
+    status: Option<String>,
+    #[serde(rename = "type")]
+    exception_type: Option<String>,
+    limit: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct ManualMatchRequest {
+    nostro_account: String,
+    line_ids: Vec<Uuid>,
+    transaction_ids: Vec<Uuid>,
+    #[serde(default)]
+    note: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ResolveExceptionRequest {
+    resolution: ExceptionResolution, // ACCEPTED, WRITTEN_OFF or IGNORED
+    #[serde(default)]
+    note: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct BreakReportQuery {
+    date: Option<NaiveDate>, // Defaults to yesterday
+    account: Option<String>,
+}
+
+/// Nostro accounts under reconciliation. Finance/admin only.
+pub async fn list_nostro_accounts(
+    accounts: web::Data<NostroAccountSet>,
+    user: AuthenticatedUser,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    Ok(HttpResponse::Ok().json(&accounts.accounts))
+}
+
+/// Pulls the account's statement (unless `pull` is false) and reconciles it now. Finance/admin only.
+pub async fn run_reconciliation(
+    db_pool: web::Data<DbPool>,
+    accounts: web::Data<NostroAccountSet>,
+    source: web::Data<BankStatementSource>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+    body: Option<web::Json<RunReconciliationRequest>>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let code = path.into_inner();
+    let account = accounts.get(&code)
+        .ok_or_else(|| ApiError::NotFound(format!("Nostro account '{}' is not configured", code)))?
+        .clone();
+    let pull = body.map_or(true, |body| body.pull);
+    log::info!("User {} running reconciliation of {} (pull: {})", user.username, code, pull);
+
+    let mut conn = get_db_conn(&db_pool)?;
+    let run = if pull {
+        reconciliation::pull_and_reconcile(&mut conn, source.get_ref(), &account, Utc::now()).await
+            .map_err(ApiError::DomainLogicError)?
+    } else {
+        web::block(move || reconciliation::reconcile_account(&mut conn, &account, Utc::now()))
+            .await? // Handle blocking error
+            .map_err(ApiError::DomainLogicError)?
+    };
+    Ok(HttpResponse::Ok().json(run))
+}
+
+/// Statement lines of an account, by booking date (default: the last 7 days). Finance/admin only.
+pub async fn list_statement_lines(
+    db_pool: web::Data<DbPool>,
+    accounts: web::Data<NostroAccountSet>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+    query: web::Query<StatementLinesQuery>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let code = path.into_inner();
+    if accounts.get(&code).is_none() {
+        return Err(ApiError::NotFound(format!("Nostro account '{}' is not configured", code)));
+    }
+    let to = query.to.unwrap_or_else(|| Utc::now().date_naive());
+    let from = query.from.unwrap_or(to - Duration::days(7));
+    if from > to {
+        return Err(ApiError::BadRequest("'from' is after 'to'".to_string()));
+    }
+    let only_unmatched = query.unmatched;
+    let mut conn = get_db_conn(&db_pool)?;
+    let lines = web::block(move || reconciliation::list_statement_lines(&mut conn, &code, from, to, only_unmatched))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(lines))
+}
+
+/// Reconciliation breaks, oldest first (default: open ones). Finance/admin only.
+pub async fn list_exceptions(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    query: web::Query<ExceptionsQuery>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let query = query.into_inner();
+    let status = match query.status.as_deref() {
+        None => Some(ExceptionStatus::Open),
+        Some("ALL") => None,
+        Some(s) => Some(ExceptionStatus::parse(s).ok_or_else(|| ApiError::BadRequest(format!("Unknown status '{}'", s)))?),
+    };
+    let exception_type = query.exception_type.as_deref()
+        .map(|t| ExceptionType::parse(t).ok_or_else(|| ApiError::BadRequest(format!("Unknown exception type '{}'", t))))
+        .transpose()?;
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+    let mut conn = get_db_conn(&db_pool)?;
+    let exceptions = web::block(move || {
+        reconciliation::list_exceptions(&mut conn, query.account.as_deref(), status, exception_type, limit)
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(exceptions))
+}
+
+/// One reconciliation break. Finance/admin only.
+pub async fn get_exception(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let exception_id = path.into_inner();
+    let mut conn = get_db_conn(&db_pool)?;
+    let exception = web::block(move || reconciliation::get_exception(&mut conn, exception_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(exception))
+}
+
+/// Closes a break without a match: accept or write off an amount difference, or ignore. Finance/admin only.
+pub async fn resolve_exception(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: web::Json<ResolveExceptionRequest>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let exception_id = path.into_inner();
+    let body = body.into_inner();
+    log::info!("User {} resolving reconciliation exception {} as {}", user.username, exception_id, body.resolution.as_str());
+
+    let mut conn = get_db_conn(&db_pool)?;
+    let outcome = web::block(move || {
+        reconciliation::resolve_exception(&mut conn, exception_id, body.resolution, user.user_id, &user.username, body.note.as_deref())
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(outcome))
+}
+
+/// Matches statement lines and transactions by hand; closes the breaks they explain. Finance/admin only.
+pub async fn create_manual_match(
+    db_pool: web::Data<DbPool>,
+    accounts: web::Data<NostroAccountSet>,
+    user: AuthenticatedUser,
+    body: web::Json<ManualMatchRequest>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let body = body.into_inner();
+    log::info!("User {} matching {} line(s) with {} transaction(s) on {}",
+        user.username, body.line_ids.len(), body.transaction_ids.len(), body.nostro_account);
+
+    let mut conn = get_db_conn(&db_pool)?;
+    let outcome = web::block(move || {
+        reconciliation::match_manually(
+            &mut conn, &accounts, &body.nostro_account, &body.line_ids, &body.transaction_ids,
+            user.user_id, &user.username, body.note.as_deref(),
+        )
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Created().json(outcome))
+}
+
+/// Daily break report for one or all nostro accounts. Finance/admin only.
+pub async fn get_break_report(
+    db_pool: web::Data<DbPool>,
+    accounts: web::Data<NostroAccountSet>,
+    user: AuthenticatedUser,
+    query: web::Query<BreakReportQuery>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let query = query.into_inner();
+    let business_date = query.date.unwrap_or_else(|| Utc::now().date_naive() - Duration::days(1));
+    let mut conn = get_db_conn(&db_pool)?;
+    let report = web::block(move || reconciliation::break_report(&mut conn, &accounts, query.account.as_deref(), business_date))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(report))
+}
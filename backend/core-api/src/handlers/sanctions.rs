@@ -0,0 +1,128 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/handlers/sanctions.rs
+use crate::db::{get_db_conn, DbPool};
+use crate::error::ApiError;
+use crate::middlewares::auth_guard::{AuthenticatedUser, COMPLIANCE_ROLES};
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use domain::approvals::ApprovalPolicySet;
+use domain::models::SanctionsCaseStatus;
+use domain::sanctions::{self, SanctionsScreener, ScreeningParty};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct CasesQuery {
+    status: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ReviewRequest {
+    comment: String, // Required: why the hit is (or is not) the listed party
+}
+
+#[derive(Deserialize)]
+pub struct ScreenRequest {
+    parties: Vec<ScreeningParty>,
+}
+
+fn screener_of(screener: &web::Data<Option<Arc<SanctionsScreener>>>) -> Result<&SanctionsScreener, ApiError> {
+    screener.as_deref().ok_or_else(|| ApiError::ConfigurationError("Sanctions screening is disabled".to_string()))
+}
+
+/// The review queue, oldest first. Compliance only.
+pub async fn list_cases(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    query: web::Query<CasesQuery>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let status = match query.status.as_deref() {
+        Some(value) => Some(
+            SanctionsCaseStatus::parse(&value.to_uppercase())
+                .ok_or_else(|| ApiError::BadRequest(format!("Unknown status '{}'", value)))?,
+        ),
+        None => None,
+    };
+    let mut conn = get_db_conn(&db_pool)?;
+    let cases = web::block(move || sanctions::list_cases(&mut conn, status))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(cases))
+}
+
+/// One case with its parties, hits and the lists screened against. Compliance only.
+pub async fn get_case(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let case_id = path.into_inner();
+    let mut conn = get_db_conn(&db_pool)?;
+    let case = web::block(move || sanctions::get_case(&mut conn, case_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(case))
+}
+
+/// Clears a false positive; the payment goes on to approval (if a policy requires it) or to its rail.
+pub async fn clear_case(
+    db_pool: web::Data<DbPool>,
+    approval_policies: web::Data<ApprovalPolicySet>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: web::Json<ReviewRequest>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let case_id = path.into_inner();
+    let comment = body.into_inner().comment;
+    log::info!("User {} clearing sanctions case {}", user.username, case_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let case = web::block(move || {
+        sanctions::clear_case(&mut conn, case_id, user.user_id, &comment, Some(&approval_policies), Utc::now())
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(case))
+}
+
+/// Confirms a true hit; the payment is cancelled.
+pub async fn confirm_case(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    body: web::Json<ReviewRequest>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let case_id = path.into_inner();
+    let comment = body.into_inner().comment;
+    log::info!("User {} confirming sanctions case {}", user.username, case_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let case = web::block(move || sanctions::confirm_case(&mut conn, case_id, user.user_id, &comment, Utc::now()))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(case))
+}
+
+/// Screens arbitrary parties without creating a case. Compliance only.
+pub async fn screen_parties(
+    sanctions_screener: web::Data<Option<Arc<SanctionsScreener>>>,
+    user: AuthenticatedUser,
+    body: web::Json<ScreenRequest>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let screener = screener_of(&sanctions_screener)?;
+    let hits = screener.screen(&body.parties);
+    Ok(HttpResponse::Ok().json(json!({"hits": hits, "lists": screener.lists()})))
+}
+
+/// The watchlist files loaded at startup, with entry counts. Compliance only.
+pub async fn list_watchlists(
+    sanctions_screener: web::Data<Option<Arc<SanctionsScreener>>>,
+    user: AuthenticatedUser,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    Ok(HttpResponse::Ok().json(screener_of(&sanctions_screener)?.lists()))
+}
@@ -0,0 +1,146 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/handlers/schedules.rs
+use crate::db::{get_db_conn, DbPool};
+use crate::error::ApiError;
+use crate::middlewares::auth_guard::AuthenticatedUser;
+use crate::models::ApiCreateScheduleRequest;
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use domain::payments::scheduler::{self, CreateSchedule, ScheduledPaymentDetails};
+use domain::payments::BusinessCalendar;
+use uuid::Uuid;
+
+/// Creates a future-dated or recurring ACH credit / outbound wire from one of the caller's wallets.
+pub async fn create_schedule(
+    db_pool: web::Data<DbPool>,
+    calendar: web::Data<BusinessCalendar>,
+    user: AuthenticatedUser,
+    info: web::Json<ApiCreateScheduleRequest>,
+) -> Result<impl Responder, ApiError> {
+    let request = info.into_inner();
+    log::info!("User {} creating payment schedule: {:?} {} from wallet {}, {:?} starting {}",
+        user.username, request.payment_type, request.amount, request.source_wallet_id, request.recurrence.frequency, request.start_date);
+
+    let mut conn = get_db_conn(&db_pool)?;
+    let user_id = user.user_id;
+    let schedule = web::block(move || {
+        scheduler::create_schedule(&mut conn, CreateSchedule {
+            user_id,
+            source_wallet_id: request.source_wallet_id,
+            payment_type: request.payment_type,
+            amount: request.amount,
+            charge_bearer: request.charge_bearer.unwrap_or_default(),
+            details: ScheduledPaymentDetails { ach_details: request.ach_details, wire_details: request.wire_details },
+            description: request.description.as_deref(),
+            recurrence: request.recurrence,
+            start_date: request.start_date,
+            end_date: request.end_date,
+            max_occurrences: request.max_occurrences,
+            business_day_convention: request.business_day_convention.unwrap_or_default(),
+            missed_run_policy: request.missed_run_policy.unwrap_or_default(),
+        }, &calendar, Utc::now().date_naive())
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Created().json(schedule))
+}
+
+/// Lists the caller's payment schedules.
+pub async fn list_schedules(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+) -> Result<impl Responder, ApiError> {
+    let mut conn = get_db_conn(&db_pool)?;
+    let user_id = user.user_id;
+    let schedules = web::block(move || scheduler::list_schedules(&mut conn, user_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Ok().json(schedules))
+}
+
+/// One of the caller's schedules with the outcome of each occurrence.
+pub async fn get_schedule(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    let schedule_id = path.into_inner();
+    let mut conn = get_db_conn(&db_pool)?;
+    let user_id = user.user_id;
+    let schedule = web::block(move || scheduler::get_schedule(&mut conn, schedule_id, user_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Ok().json(schedule))
+}
+
+/// Pauses a schedule; nothing is paid until it is resumed.
+pub async fn pause_schedule(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    let schedule_id = path.into_inner();
+    log::info!("User {} pausing payment schedule {}", user.username, schedule_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let user_id = user.user_id;
+    let schedule = web::block(move || scheduler::pause_schedule(&mut conn, schedule_id, user_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Ok().json(schedule))
+}
+
+/// Resumes a paused schedule from its next occurrence due today or later.
+pub async fn resume_schedule(
+    db_pool: web::Data<DbPool>,
+    calendar: web::Data<BusinessCalendar>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    let schedule_id = path.into_inner();
+    log::info!("User {} resuming payment schedule {}", user.username, schedule_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let user_id = user.user_id;
+    let schedule = web::block(move || scheduler::resume_schedule(&mut conn, schedule_id, user_id, &calendar, Utc::now().date_naive()))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Ok().json(schedule))
+}
+
+/// Skips the next occurrence of a schedule.
+pub async fn skip_next_occurrence(
+    db_pool: web::Data<DbPool>,
+    calendar: web::Data<BusinessCalendar>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    let schedule_id = path.into_inner();
+    log::info!("User {} skipping next occurrence of payment schedule {}", user.username, schedule_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let user_id = user.user_id;
+    let schedule = web::block(move || scheduler::skip_next_occurrence(&mut conn, schedule_id, user_id, &calendar))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Ok().json(schedule))
+}
+
+/// Cancels a schedule. Payments already submitted are not recalled.
+pub async fn cancel_schedule(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    let schedule_id = path.into_inner();
+    log::info!("User {} cancelling payment schedule {}", user.username, schedule_id);
+    let mut conn = get_db_conn(&db_pool)?;
+    let user_id = user.user_id;
+    let schedule = web::block(move || scheduler::cancel_schedule(&mut conn, schedule_id, user_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+
+    Ok(HttpResponse::Ok().json(schedule))
+}
@@ -0,0 +1,64 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/handlers/travel_rule.rs
+use crate::db::{get_db_conn, DbPool};
+use crate::error::ApiError;
+use crate::middlewares::auth_guard::{AuthenticatedUser, COMPLIANCE_ROLES};
+use actix_web::{web, HttpResponse, Responder};
+use domain::models::TravelRuleStatus;
+use domain::travel_rule::{self, TravelRuleConfig};
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct TransfersQuery {
+    status: Option<String>,
+}
+
+/// Travel Rule records of crypto withdrawals, newest first. Compliance only.
+pub async fn list_transfers(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    query: web::Query<TransfersQuery>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let status = match query.into_inner().status {
+        Some(value) => Some(
+            TravelRuleStatus::parse(&value.to_uppercase())
+                .ok_or_else(|| ApiError::BadRequest(format!("Unknown status '{}'", value)))?,
+        ),
+        None => None,
+    };
+    let mut conn = get_db_conn(&db_pool)?;
+    let transfers = web::block(move || travel_rule::list_transfers(&mut conn, status))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(transfers))
+}
+
+/// One record with the IVMS101 data and the counterparty's last answer. Compliance only.
+pub async fn get_transfer(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(COMPLIANCE_ROLES)?;
+    let transfer_id = path.into_inner();
+    let mut conn = get_db_conn(&db_pool)?;
+    let transfer = web::block(move || travel_rule::get_transfer(&mut conn, transfer_id))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(transfer))
+}
+
+/// The VASP directory and thresholds in force, so customers' apps can offer the known counterparties.
+pub async fn list_vasps(travel_rule_config: web::Data<TravelRuleConfig>, _user: AuthenticatedUser) -> Result<impl Responder, ApiError> {
+    let vasps: Vec<_> = travel_rule_config
+        .counterparty_vasps
+        .iter()
+        .map(|vasp| serde_json::json!({"vasp_id": vasp.vasp_id, "name": vasp.name, "country": vasp.country}))
+        .collect();
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "thresholds": travel_rule_config.thresholds,
+        "on_missing_data": travel_rule_config.on_missing_data,
+        "counterparty_vasps": vasps,
+    })))
+}
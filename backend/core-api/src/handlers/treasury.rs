@@ -0,0 +1,119 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/handlers/treasury.rs
+use crate::db::{get_db_conn, DbPool};
+use crate::error::ApiError;
+use crate::middlewares::auth_guard::{AuthenticatedUser, FINANCE_ROLES};
+use crate::services::reconciliation_worker::BankStatementSource;
+use actix_web::{web, HttpResponse, Responder};
+use chrono::{Duration, NaiveDate, Utc};
+use domain::payments::BusinessCalendar;
+use domain::reconciliation::NostroAccountSet;
+use domain::treasury::{self, ReportSigner};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct RunEodRequest {
+    #[serde(default)]
+    business_date: Option<NaiveDate>, // Defaults to today
+}
+
+#[derive(Deserialize)]
+pub struct SnapshotsQuery {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+/// Current positions and today's projected liquidity, not stored or signed. Finance/admin only.
+pub async fn get_live_positions(
+    db_pool: web::Data<DbPool>,
+    accounts: web::Data<NostroAccountSet>,
+    source: web::Data<BankStatementSource>,
+    user: AuthenticatedUser,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let balances = treasury::fetch_balances(source.get_ref(), &accounts).await;
+    let now = Utc::now();
+    let mut conn = get_db_conn(&db_pool)?;
+    let report = web::block(move || {
+        treasury::build_report(&mut conn, &accounts, &balances, now.date_naive(), now.date_naive(), now)
+    })
+    .await? // Handle blocking error
+    .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// Produces (or re-produces) the signed EOD position snapshot for a business day. Finance/admin only.
+pub async fn run_eod(
+    db_pool: web::Data<DbPool>,
+    accounts: web::Data<NostroAccountSet>,
+    source: web::Data<BankStatementSource>,
+    calendar: web::Data<BusinessCalendar>,
+    signer: web::Data<ReportSigner>,
+    user: AuthenticatedUser,
+    body: Option<web::Json<RunEodRequest>>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let now = Utc::now();
+    let business_date = body.and_then(|body| body.business_date).unwrap_or_else(|| now.date_naive());
+    if business_date > now.date_naive() {
+        return Err(ApiError::BadRequest("Cannot produce an EOD report for a future business date".to_string()));
+    }
+    log::info!("User {} running the EOD position report for {}", user.username, business_date);
+
+    let mut conn = get_db_conn(&db_pool)?;
+    let snapshot = treasury::run_end_of_day(
+        &mut conn, source.get_ref(), &accounts, &calendar, &signer, business_date, &user.username, now,
+    )
+    .await
+    .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Created().json(snapshot))
+}
+
+/// Stored snapshots, newest first (default: the last 30 days). Finance/admin only.
+pub async fn list_eod_snapshots(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    query: web::Query<SnapshotsQuery>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let to = query.to.unwrap_or_else(|| Utc::now().date_naive());
+    let from = query.from.unwrap_or(to - Duration::days(30));
+    if from > to {
+        return Err(ApiError::BadRequest("'from' is after 'to'".to_string()));
+    }
+    let mut conn = get_db_conn(&db_pool)?;
+    let snapshots = web::block(move || treasury::list_snapshots(&mut conn, from, to))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(snapshots))
+}
+
+/// The signed snapshot of one business day. Finance/admin only.
+pub async fn get_eod_snapshot(
+    db_pool: web::Data<DbPool>,
+    user: AuthenticatedUser,
+    path: web::Path<NaiveDate>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let business_date = path.into_inner();
+    let mut conn = get_db_conn(&db_pool)?;
+    let snapshot = web::block(move || treasury::get_snapshot(&mut conn, business_date))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(snapshot))
+}
+
+/// Checks a stored snapshot's signature. Finance/admin only.
+pub async fn verify_eod_snapshot(
+    db_pool: web::Data<DbPool>,
+    signer: web::Data<ReportSigner>,
+    user: AuthenticatedUser,
+    path: web::Path<NaiveDate>,
+) -> Result<impl Responder, ApiError> {
+    user.require_role(FINANCE_ROLES)?;
+    let business_date = path.into_inner();
+    let mut conn = get_db_conn(&db_pool)?;
+    let snapshot = web::block(move || treasury::get_snapshot(&mut conn, business_date))
+        .await? // Handle blocking error
+        .map_err(ApiError::DomainLogicError)?;
+    Ok(HttpResponse::Ok().json(treasury::verify_snapshot(&snapshot, &signer)))
+}
@@ -7,11 +7,38 @@ use core_api::db::{init_db_pool};
 use core_api::middlewares::{auth_guard::AuthGuard, logger::RequestLogger}; // Added AuthGuard
 use core_api::routes::configure_routes;
 use core_api::services::ft_client::FtApiClient; // Import FT Client
+use core_api::utils::request_context::TrustedProxies; // Reverse proxies allowed to name the client address
+use core_api::services::outbox_worker::spawn_outbox_worker; // Outbound payment dispatcher
+use core_api::services::hold_expiry::spawn_hold_expiry_sweeper; // Lapsed wallet holds
+use core_api::services::payment_scheduler::spawn_payment_scheduler; // Standing orders
+use core_api::services::reconciliation_worker::{spawn_reconciliation_worker, BankStatementSource}; // Nostro reconciliation
+use core_api::services::eod_worker::spawn_eod_worker; // End-of-day nostro positions
+use core_api::services::interest_worker::spawn_interest_worker; // Daily accrual, monthly capitalization
+use core_api::services::aml_worker::spawn_aml_worker; // Streaming and daily batch AML monitoring
+use core_api::services::ctr_worker::spawn_ctr_worker; // Daily CTR aggregation
+use core_api::services::travel_rule_worker::spawn_travel_rule_worker; // Travel Rule exchanges with counterparty VASPs
+use core_api::services::fraud_rules_watcher::spawn_fraud_rules_watcher; // Fraud rule file hot reload
 use core_api::utils::http_clients::{init_http_clients, HttpClients}; // Import HTTP Clients
 
 use actix_cors::Cors; // Import CORS
 use actix_web::{middleware::Logger as ActixLogger, web, App, HttpServer}; // Use ActixLogger alias
 use std::sync::Arc;
+use domain::config::load_json; // JSON configuration files (fees, limits, policies, screening, ...)
+use domain::fees::FeeSchedule; // Fee rules loaded from FEE_SCHEDULE_PATH
+use domain::approvals::ApprovalPolicySet; // Maker-checker policies loaded from APPROVAL_POLICY_PATH
+use domain::beneficiaries::CoolingOffLimits; // Limits on newly verified payees
+use domain::limits::LimitPolicy; // Transaction limits loaded from LIMITS_POLICY_PATH
+use domain::sanctions::{SanctionsConfig, SanctionsScreener}; // Watchlists named in SANCTIONS_CONFIG_PATH
+use domain::aml::AmlConfig; // Monitoring scenarios from AML_CONFIG_PATH
+use domain::ctr::CtrConfig; // CTR threshold, cash types and filer from CTR_CONFIG_PATH
+use domain::travel_rule::TravelRuleConfig; // Travel Rule thresholds and VASP directory from TRAVEL_RULE_CONFIG_PATH
+use domain::fraud::FraudRules; // Declarative fraud rules from FRAUD_RULES_PATH, reloaded on change
+use domain::ip_intel::{IpIntelConfig, IpIntelligence}; // Offline GeoIP/ASN databases and Tor/VPN/hosting lists
+use domain::payments::{BusinessCalendar, GatewayRoutingConfig, PaymentGateway, RoutingPaymentGateway}; // Weekends + BANK_HOLIDAYS; card gateways from CARD_ROUTING_CONFIG_PATH
+use domain::payments::NachaOriginator; // ACH_* origination settings for payout NACHA files
+use domain::reconciliation::NostroAccountSet; // Nostro accounts loaded from NOSTRO_ACCOUNTS_PATH
+use domain::treasury::ReportSigner; // Signs EOD position reports (EOD_REPORT_SIGNING_KEY)
+use domain::webhooks::{BtcPayProvider, MockWebhookProvider, PartnerBankProvider, StripeProvider, WebhookRegistry}; // Signed provider webhooks
 
 // Import other necessary crates/modules
 use cryptography_exchange::btcpay::BTCPayClient;
@@ -38,6 +65,141 @@ async fn main() -> std::io::Result<()> {
     let db_pool = init_db_pool(&CONFIG.database_url)
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?; // Convert ApiError to io::Error
     log::info!("Database connection pool initialized.");
+    let _hold_expiry_sweeper = spawn_hold_expiry_sweeper(db_pool.clone(), std::time::Duration::from_secs(300));
+
+    // --- Load Fee Schedule ---
+    // Refuse to start on a broken schedule rather than silently charging nothing
+    let fee_schedule = load_json::<FeeSchedule>(&CONFIG.fee_schedule_path)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+
+    // --- Load Approval Policies ---
+    // Same reasoning: a broken policy file must not let payments skip approval
+    let approval_policies = load_json::<ApprovalPolicySet>(&CONFIG.approval_policy_path)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+
+    // --- Load Transaction Limits ---
+    // A broken limits file must not lift every limit either
+    let limit_policy = load_json::<LimitPolicy>(&CONFIG.limits_policy_path)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+
+    // --- Load Sanctions Lists ---
+    // Unreadable lists stop the service: payments must never go out unscreened by accident
+    let sanctions_screener = if CONFIG.sanctions_screening_enabled {
+        let screener = load_json::<SanctionsConfig>(&CONFIG.sanctions_config_path)
+            .and_then(|config| SanctionsScreener::load(&config))
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+        log::info!("Sanctions screening enabled ({} watchlist entries).", screener.entry_count());
+        Some(Arc::new(screener))
+    } else {
+        log::warn!("SANCTIONS_SCREENING_ENABLED is false: outbound payments are NOT screened.");
+        None
+    };
+
+    // --- Start Payment Outbox Worker ---
+    // Dispatches debited outbound payments to their rail and resumes anything left over from a crash;
+    // also sends the NACHA file of each bulk payout batch. Started after the sanctions lists so built wire
+    // messages are screened before they leave
+    let ach_originator = NachaOriginator {
+        odfi_routing_number: CONFIG.ach_odfi_routing_number.clone(),
+        odfi_name: CONFIG.ach_odfi_name.clone(),
+        company_id: CONFIG.ach_company_id.clone(),
+        company_name: CONFIG.ach_company_name.clone(),
+    };
+    let _outbox_worker = spawn_outbox_worker(
+        db_pool.clone(),
+        ach_originator,
+        std::time::Duration::from_secs(CONFIG.outbox_poll_interval_secs),
+        sanctions_screener.clone(),
+    );
+
+    // --- Load AML Monitoring Scenarios ---
+    // A broken scenario file must not silently switch monitoring off
+    let aml_config = load_json::<AmlConfig>(&CONFIG.aml_config_path)
+        .map(Arc::new)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+
+    // --- Load CTR Configuration ---
+    let ctr_config = load_json::<CtrConfig>(&CONFIG.ctr_config_path)
+        .map(Arc::new)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+
+    // --- Load Travel Rule Configuration ---
+    // Without it crypto withdrawals would leave with no originator/beneficiary data
+    let travel_rule_config = load_json::<TravelRuleConfig>(&CONFIG.travel_rule_config_path)
+        .map(Arc::new)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+
+    // --- Load Fraud Rules ---
+    // The service does not start without valid rules; later edits that do not validate are ignored by the watcher
+    let fraud_rules = FraudRules::load(&CONFIG.fraud_rules_path)
+        .map(Arc::new)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+    let _fraud_rules_watcher = spawn_fraud_rules_watcher(
+        fraud_rules.clone(),
+        std::time::Duration::from_secs(CONFIG.fraud_rules_reload_interval_secs),
+    );
+
+    // --- Load IP Intelligence ---
+    // Unreadable databases or lists stop the service rather than silently scoring every address as unknown
+    let ip_intel = if CONFIG.ip_intel_enabled {
+        load_json::<IpIntelConfig>(&CONFIG.ip_intel_config_path)
+            .and_then(|config| IpIntelligence::load(&config))
+            .map(Arc::new)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?
+    } else {
+        log::warn!("IP_INTEL_ENABLED is false: client addresses are NOT geolocated or checked against Tor/VPN lists.");
+        Arc::new(IpIntelligence::disabled())
+    };
+
+    // --- Beneficiary Cooling-Off Limits ---
+    let beneficiary_limits = CoolingOffLimits {
+        period: chrono::Duration::hours(CONFIG.beneficiary_cooling_off_hours),
+        max_payment_amount: CONFIG.beneficiary_cooling_off_max_payment,
+        max_total_amount: CONFIG.beneficiary_cooling_off_max_total,
+        require_saved_beneficiary: CONFIG.beneficiary_require_saved,
+    };
+
+    // --- Webhook Providers ---
+    // Only providers with a configured secret/key are accepted; deliveries for others are refused
+    let webhook_tolerance = chrono::Duration::seconds(CONFIG.webhook_tolerance_secs);
+    let mut webhook_registry = WebhookRegistry::default();
+    if let Some(secret) = &CONFIG.stripe_webhook_secret {
+        webhook_registry = webhook_registry.register(StripeProvider::new(secret, webhook_tolerance));
+    }
+    if let Some(secret) = &CONFIG.btcpay_webhook_secret {
+        webhook_registry = webhook_registry.register(BtcPayProvider::new(secret));
+    }
+    if let Some(public_key) = &CONFIG.partner_bank_webhook_public_key {
+        let provider = PartnerBankProvider::new("partner_bank", public_key, webhook_tolerance)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+        webhook_registry = webhook_registry.register(provider);
+    }
+    if CONFIG.webhook_allow_mock {
+        log::warn!("WEBHOOK_ALLOW_MOCK is set: unsigned mock webhooks are accepted.");
+        webhook_registry = webhook_registry.register(MockWebhookProvider);
+    }
+
+    // --- Start Payment Scheduler ---
+    // Submits due standing orders; scheduled payments roll around weekends and bank holidays
+    let business_calendar = BusinessCalendar::new(CONFIG.bank_holidays.iter().copied());
+    // One router for the service: an authorization made by one request is captured/voided by later ones.
+    // A broken routing file stops the service rather than leaving card payments without a gateway
+    let card_routing = load_json::<GatewayRoutingConfig>(&CONFIG.card_routing_config_path)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+    let card_gateway: Arc<dyn PaymentGateway> = Arc::new(RoutingPaymentGateway::from_config(&card_routing)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?);
+    let _payment_scheduler = spawn_payment_scheduler(
+        db_pool.clone(),
+        fee_schedule.clone(),
+        approval_policies.clone(),
+        beneficiary_limits.clone(),
+        limit_policy.clone(),
+        sanctions_screener.clone(),
+        fraud_rules.clone(),
+        business_calendar.clone(),
+        card_gateway.clone(),
+        std::time::Duration::from_secs(CONFIG.scheduler_poll_interval_secs),
+    );
 
     // --- Initialize Shared HTTP Clients (Standard & Tor) ---
     let http_clients = init_http_clients(&CONFIG)
@@ -60,12 +222,80 @@ async fn main() -> std::io::Result<()> {
 
     // Example Bank Clients
     // TODO: Handle errors for each client individually
-    let chase_client = ChaseClient::new().expect("Failed to init Chase Client");
-    let wf_client = WellsFargoClient::new().expect("Failed to init Wells Fargo Client");
-    let db_client = DeutscheBankClient::new().expect("Failed to init Deutsche Bank Client");
-    let bnp_client = BnpParibasClient::new().expect("Failed to init BNP Paribas Client");
+    let chase_client = Arc::new(ChaseClient::new().expect("Failed to init Chase Client"));
+    let wf_client = Arc::new(WellsFargoClient::new().expect("Failed to init Wells Fargo Client"));
+    let db_client = Arc::new(DeutscheBankClient::new().expect("Failed to init Deutsche Bank Client"));
+    let bnp_client = Arc::new(BnpParibasClient::new().expect("Failed to init BNP Paribas Client"));
     log::info!("Bank integration clients initialized.");
 
+    // --- Start Reconciliation Worker ---
+    // Nostro statements are pulled through the same bank clients and matched against our transactions
+    let nostro_accounts = load_json::<NostroAccountSet>(&CONFIG.nostro_accounts_path)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+    let statement_source = BankStatementSource::default()
+        .with_client("chase", chase_client.clone())
+        .with_client("wells_fargo", wf_client.clone())
+        .with_client("deutsche_bank", db_client.clone())
+        .with_client("bnp_paribas", bnp_client.clone());
+    let _reconciliation_worker = spawn_reconciliation_worker(
+        db_pool.clone(),
+        nostro_accounts.clone(),
+        statement_source.clone(),
+        std::time::Duration::from_secs(CONFIG.reconciliation_interval_secs),
+    );
+
+    // --- Start EOD Position Worker ---
+    // Daily signed report of nostro balances, projected next-day liquidity and shortfalls
+    let report_signer = match &CONFIG.eod_report_signing_key {
+        Some(seed) => ReportSigner::from_hex_seed(seed)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?,
+        None => {
+            log::warn!("EOD_REPORT_SIGNING_KEY is not set: EOD reports are signed with a throwaway key.");
+            ReportSigner::ephemeral()
+        }
+    };
+    let _eod_worker = spawn_eod_worker(
+        db_pool.clone(),
+        nostro_accounts.clone(),
+        statement_source.clone(),
+        business_calendar.clone(),
+        report_signer.clone(),
+        CONFIG.eod_run_time,
+    );
+
+    // --- Start Interest Worker ---
+    // Accrues savings wallets daily and capitalizes the previous month's interest
+    let _interest_worker = spawn_interest_worker(
+        db_pool.clone(),
+        std::time::Duration::from_secs(CONFIG.interest_run_interval_secs),
+    );
+
+    // --- Start AML Monitoring Worker ---
+    // Monitors new and changed transactions, and re-runs the previous day in batch once a day
+    let _aml_worker = spawn_aml_worker(
+        db_pool.clone(),
+        aml_config.clone(),
+        std::time::Duration::from_secs(CONFIG.aml_monitor_interval_secs),
+    );
+
+    // --- Start CTR Worker ---
+    // Aggregates the last completed business day into Currency Transaction Reports
+    let _ctr_worker = spawn_ctr_worker(
+        db_pool.clone(),
+        ctr_config.clone(),
+        business_calendar.clone(),
+        std::time::Duration::from_secs(CONFIG.ctr_run_interval_secs),
+    );
+
+    // --- Start Travel Rule Worker ---
+    // Sends IVMS101 data of held withdrawals to beneficiary VASPs and releases or cancels them on the answer
+    let _travel_rule_worker = spawn_travel_rule_worker(
+        db_pool.clone(),
+        travel_rule_config.clone(),
+        http_clients.standard_client.clone(),
+        std::time::Duration::from_secs(CONFIG.travel_rule_exchange_interval_secs),
+    );
+
     // Financial Times API Client
     let ft_client = FtApiClient::new(
         CONFIG.ft_api_key.clone(),
@@ -100,11 +330,28 @@ async fn main() -> std::io::Result<()> {
     #[cfg(feature = "monero_support")]
     let shared_monero = web::Data::new(monero_client);
     let shared_ft_client = web::Data::new(ft_client);
+    let shared_fee_schedule = web::Data::new(fee_schedule);
+    let shared_approval_policies = web::Data::new(approval_policies);
+    let shared_beneficiary_limits = web::Data::new(beneficiary_limits);
+    let shared_limit_policy = web::Data::new(limit_policy);
+    let shared_sanctions_screener = web::Data::new(sanctions_screener);
+    let shared_aml_config = web::Data::from(aml_config);
+    let shared_ctr_config = web::Data::from(ctr_config);
+    let shared_travel_rule_config = web::Data::from(travel_rule_config);
+    let shared_fraud_rules = web::Data::from(fraud_rules);
+    let shared_ip_intel = web::Data::from(ip_intel);
+    let shared_trusted_proxies = web::Data::new(TrustedProxies(CONFIG.trusted_proxies.clone()));
+    let shared_business_calendar = web::Data::new(business_calendar);
+    let shared_card_gateway = web::Data::from(card_gateway);
+    let shared_webhook_registry = web::Data::new(webhook_registry);
     // Share bank clients
-    let shared_chase = web::Data::new(chase_client);
-    let shared_wf = web::Data::new(wf_client);
-    let shared_db = web::Data::new(db_client);
-    let shared_bnp = web::Data::new(bnp_client);
+    let shared_chase = web::Data::from(chase_client);
+    let shared_wf = web::Data::from(wf_client);
+    let shared_db = web::Data::from(db_client);
+    let shared_bnp = web::Data::from(bnp_client);
+    let shared_nostro_accounts = web::Data::new(nostro_accounts);
+    let shared_statement_source = web::Data::new(statement_source);
+    let shared_report_signer = web::Data::new(report_signer);
 
 
     // --- Start Actix HTTP Server ---
@@ -125,6 +372,23 @@ async fn main() -> std::io::Result<()> {
             .app_data(shared_db_pool.clone())
             .app_data(web::Data::from(app_config.clone())) // Share Arc<AppConfig>
             .app_data(shared_http_clients.clone())
+            .app_data(shared_fee_schedule.clone())
+            .app_data(shared_approval_policies.clone())
+            .app_data(shared_beneficiary_limits.clone())
+            .app_data(shared_limit_policy.clone())
+            .app_data(shared_sanctions_screener.clone())
+            .app_data(shared_aml_config.clone())
+            .app_data(shared_ctr_config.clone())
+            .app_data(shared_travel_rule_config.clone())
+            .app_data(shared_fraud_rules.clone())
+            .app_data(shared_ip_intel.clone())
+            .app_data(shared_trusted_proxies.clone())
+            .app_data(shared_business_calendar.clone())
+            .app_data(shared_card_gateway.clone())
+            .app_data(shared_webhook_registry.clone())
+            .app_data(shared_nostro_accounts.clone())
+            .app_data(shared_statement_source.clone())
+            .app_data(shared_report_signer.clone())
             // Share external service clients
             .app_data(shared_btcpay.clone())
             #[cfg(feature = "monero_support")]
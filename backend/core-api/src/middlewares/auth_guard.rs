@@ -22,6 +22,27 @@ pub struct AuthenticatedUser {
     pub role: String, // Role from JWT
 }
 
+/// Roles allowed to read institution-wide books (ledger, reports).
+pub const FINANCE_ROLES: &[&str] = &["admin", "finance"];
+
+/// Roles allowed to change customer limits (approving temporary increases).
+pub const ADMIN_ROLES: &[&str] = &["admin"];
+
+/// Roles allowed to review sanctions screening hits.
+pub const COMPLIANCE_ROLES: &[&str] = &["admin", "compliance"];
+
+impl AuthenticatedUser {
+    /// Fails with `AuthorizationError` unless the user's role is one of `roles`.
+    pub fn require_role(&self, roles: &[&str]) -> Result<(), ApiError> {
+        if roles.iter().any(|role| self.role.eq_ignore_ascii_case(role)) {
+            Ok(())
+        } else {
+            log::warn!("User {} with role '{}' denied (requires one of {:?})", self.user_id, self.role, roles);
+            Err(ApiError::AuthorizationError("Insufficient role for this operation".to_string()))
+        }
+    }
+}
+
 // --- Middleware Factory ---
 #[derive(Clone)]
 pub struct AuthGuard;
@@ -2,7 +2,10 @@
 use serde::{Deserialize, Serialize};
 use rust_decimal::Decimal;
 use uuid::Uuid;
-use domain::models::{TransactionStatus, TransactionType}; // Use domain enums
+use domain::models::{AchDetails, TransactionStatus, TransactionType, WireDetails}; // Use domain enums
+use domain::fees::ChargeBearer;
+use domain::travel_rule::TravelRuleSubmission;
+use domain::payments::recurrence::{BusinessDayConvention, MissedRunPolicy, RecurrenceRule};
 
 // --- Auth Models ---
 #[derive(Debug, Deserialize)]
@@ -16,6 +19,8 @@ pub struct ApiRegisterRequest {
     pub username: String,
     pub password: String,
     pub email: String,
+    #[serde(default)]
+    pub country_code: Option<String>, // Country of residence (ISO 3166-1 alpha-2)
 }
 
 #[derive(Debug, Serialize)]
@@ -31,7 +36,7 @@ pub struct ApiRegisterResponse {
 }
 
 // --- Payment Models ---
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)] // Serialize used for idempotency fingerprinting
 pub struct ApiInitiatePaymentRequest {
     pub amount: Decimal,
     pub currency: String,
@@ -49,8 +54,9 @@ pub struct ApiInitiatePaymentRequest {
     pub ach_account: Option<String>,
     // ... other needed fields ...
     pub description: Option<String>,
-    pub idempotency_key: Option<String>, // For preventing duplicate requests
+    pub idempotency_key: Option<String>, // Prefer the Idempotency-Key header; kept for older clients
     pub metadata: Option<serde_json::Value>,
+    pub charge_bearer: Option<ChargeBearer>, // OUR / SHA / BEN, defaults to SHA
 }
 
 #[derive(Debug, Serialize)]
@@ -79,24 +85,14 @@ pub struct ApiPaymentStatusResponse {
 
 
 // --- Crypto Models ---
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct ApiCryptoConversionRequest {
     pub amount: Decimal,
     pub from_currency: String, // e.g., "BTC", "XMR", "USD", "EUR"
     pub to_currency: String,
 }
 
-#[derive(Debug, Serialize)]
-pub struct ApiCryptoConversionResponse {
-    pub original_amount: String,
-    pub converted_amount: String,
-    pub from_currency: String,
-    pub to_currency: String,
-    pub rate: Option<String>,
-    pub timestamp: chrono::DateTime<chrono::Utc>,
-}
-
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct ApiCryptoWithdrawalRequest {
     pub source_wallet_id: Uuid, // Internal wallet ID
     pub amount: Decimal,
@@ -104,6 +100,8 @@ pub struct ApiCryptoWithdrawalRequest {
     pub destination_address: String,
     pub payment_id: Option<String>, // For Monero
     pub idempotency_key: Option<String>,
+    #[serde(default)]
+    pub travel_rule: Option<TravelRuleSubmission>, // Originator/beneficiary data, required above the Travel Rule threshold
 }
 
 #[derive(Debug, Serialize)]
@@ -117,11 +115,54 @@ pub struct ApiCryptoWithdrawalResponse {
 pub struct ApiWalletBalanceResponse {
     pub wallet_id: Uuid,
     pub currency: String,
-    pub balance: String, // Decimal as string
+    pub ledger_balance: String, // Posted balance, decimal as string
+    pub available_balance: String, // Ledger balance minus active holds
     pub wallet_type: String,
     pub address: Option<String>, // Public address if applicable
 }
 
+// --- Ledger Models ---
+#[derive(Debug, Deserialize)]
+pub struct ApiTrialBalanceQuery {
+    pub currency: Option<String>,
+    pub from: Option<chrono::DateTime<chrono::Utc>>, // Inclusive
+    pub to: Option<chrono::DateTime<chrono::Utc>>, // Exclusive
+}
+
+// --- Fee Models ---
+#[derive(Debug, Deserialize)]
+pub struct ApiFeeQuoteRequest {
+    pub payment_type: TransactionType,
+    pub amount: Decimal,
+    pub currency: String,
+    pub charge_bearer: Option<ChargeBearer>, // Defaults to SHA
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiFeeRevenueQuery {
+    pub currency: Option<String>,
+    pub from: Option<chrono::DateTime<chrono::Utc>>, // Inclusive
+    pub to: Option<chrono::DateTime<chrono::Utc>>, // Exclusive
+}
+
+// --- Payment Schedule Models ---
+#[derive(Debug, Deserialize)]
+pub struct ApiCreateScheduleRequest {
+    pub source_wallet_id: Uuid,
+    pub payment_type: TransactionType, // AchCredit or WireOutbound
+    pub amount: Decimal, // In the source wallet's currency
+    pub charge_bearer: Option<ChargeBearer>, // Defaults to SHA
+    pub ach_details: Option<AchDetails>,
+    pub wire_details: Option<WireDetails>,
+    pub description: Option<String>,
+    pub recurrence: RecurrenceRule, // e.g. {"frequency": "MONTHLY", "day_of_month": 1}
+    pub start_date: chrono::NaiveDate,
+    pub end_date: Option<chrono::NaiveDate>, // Inclusive
+    pub max_occurrences: Option<i32>,
+    pub business_day_convention: Option<BusinessDayConvention>, // Defaults to FOLLOWING
+    pub missed_run_policy: Option<MissedRunPolicy>, // Defaults to RUN_ONCE
+}
+
 // --- FT Models ---
 #[derive(Debug, Deserialize)]
 pub struct FtNotificationPayload {
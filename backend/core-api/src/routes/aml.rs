@@ -0,0 +1,20 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/routes/aml.rs
+use actix_web::web;
+use crate::handlers::aml::{list_cases, get_case, assign_case, close_case, file_sar, sar_summary, run_batch, list_scenarios};
+use crate::middlewares::auth_guard::AuthGuard; // Compliance roles are checked in the handlers
+
+/// Configures AML monitoring routes: `/api/v1/aml/...`
+pub fn configure_aml_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/aml")
+            .route("/cases", web::get().to(list_cases).wrap(AuthGuard))
+            .route("/cases/{case_id}", web::get().to(get_case).wrap(AuthGuard))
+            .route("/cases/{case_id}/assign", web::post().to(assign_case).wrap(AuthGuard))
+            .route("/cases/{case_id}/close", web::post().to(close_case).wrap(AuthGuard))
+            .route("/cases/{case_id}/sar", web::post().to(file_sar).wrap(AuthGuard))
+            .route("/cases/{case_id}/sar-summary", web::get().to(sar_summary).wrap(AuthGuard))
+            // Re-running a period and the scenarios in force
+            .route("/batch", web::post().to(run_batch).wrap(AuthGuard))
+            .route("/scenarios", web::get().to(list_scenarios).wrap(AuthGuard))
+    );
+}
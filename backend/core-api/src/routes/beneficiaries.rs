@@ -0,0 +1,20 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/routes/beneficiaries.rs
+use actix_web::web;
+use crate::handlers::beneficiaries::{
+    create_beneficiary, list_beneficiaries, get_beneficiary, edit_beneficiary, verify_beneficiary, delete_beneficiary,
+};
+use crate::middlewares::auth_guard::AuthGuard; // Beneficiaries belong to the caller
+
+/// Configures saved-payee routes: `/api/v1/beneficiaries/...`
+pub fn configure_beneficiary_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/beneficiaries")
+            .route("", web::post().to(create_beneficiary).wrap(AuthGuard))
+            .route("", web::get().to(list_beneficiaries).wrap(AuthGuard))
+            .route("/{beneficiary_id}", web::get().to(get_beneficiary).wrap(AuthGuard))
+            .route("/{beneficiary_id}", web::put().to(edit_beneficiary).wrap(AuthGuard))
+            .route("/{beneficiary_id}", web::delete().to(delete_beneficiary).wrap(AuthGuard))
+            // Step-up verification of new or edited payees
+            .route("/{beneficiary_id}/verify", web::post().to(verify_beneficiary).wrap(AuthGuard))
+    );
+}
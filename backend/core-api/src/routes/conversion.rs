@@ -1,12 +1,14 @@
 // /home/inno/elights_jobes-research/backend/core-api/src/routes/conversion.rs
 use actix_web::web;
-use crate::handlers::conversion::{get_conversion_rate, perform_currency_conversion}; // Import handlers
+use crate::handlers::conversion::{get_conversion_rate, lock_conversion_quote, perform_currency_conversion}; // Import handlers
+use crate::middlewares::auth_guard::AuthGuard; // Quotes and executions move funds between the caller's wallets
 
-/// Configures currency conversion related routes.
+/// Configures currency conversion related routes: `/api/v1/conversions/...`
 pub fn configure_conversion_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/conversions") // Scope for conversion endpoints
-            .route("/rate", web::get().to(get_conversion_rate)) // Get rate only
-            .route("/execute", web::post().to(perform_currency_conversion)) // Perform conversion
+            .route("/rate", web::get().to(get_conversion_rate)) // Indicative mid rate only
+            .route("/quote", web::post().to(lock_conversion_quote).wrap(AuthGuard)) // Lock a rate
+            .route("/execute", web::post().to(perform_currency_conversion).wrap(AuthGuard)) // Book a locked quote
     );
-}
\ No newline at end of file
+}
@@ -2,12 +2,14 @@
 use actix_web::web;
 use crate::handlers::crypto::{
     get_conversion_quote, // Renamed from convert_crypto
-    execute_crypto_conversion, // New handler for execution
     get_wallet_balance,
     initiate_crypto_withdrawal,
+    get_withdrawal_travel_rule,
+    supply_withdrawal_travel_rule,
     get_deposit_address,
     handle_crypto_webhook, // For BTCPay/Monero notifications
 };
+use crate::handlers::conversion::perform_currency_conversion; // Crypto and fiat conversions share one flow
 use crate::middlewares::auth_guard::AuthGuard; // Requires authentication
 
 /// Configures cryptocurrency related routes: `/api/v1/crypto/...`
@@ -16,7 +18,7 @@ pub fn configure_crypto_routes(cfg: &mut web::ServiceConfig) {
         web::scope("/crypto")
             // Conversion endpoints
             .route("/quote", web::post().to(get_conversion_quote).wrap(AuthGuard)) // Get quote
-            .route("/convert", web::post().to(execute_crypto_conversion).wrap(AuthGuard)) // Execute conversion
+            .route("/convert", web::post().to(perform_currency_conversion).wrap(AuthGuard)) // Execute a locked quote (see /conversions/quote)
 
             // Wallet management endpoints
             .route("/wallets/{wallet_id}/balance", web::get().to(get_wallet_balance).wrap(AuthGuard))
@@ -24,6 +26,8 @@ pub fn configure_crypto_routes(cfg: &mut web::ServiceConfig) {
 
             // Withdrawal endpoint
             .route("/withdrawals", web::post().to(initiate_crypto_withdrawal).wrap(AuthGuard))
+            .route("/withdrawals/{transaction_id}/travel-rule", web::get().to(get_withdrawal_travel_rule).wrap(AuthGuard))
+            .route("/withdrawals/{transaction_id}/travel-rule", web::put().to(supply_withdrawal_travel_rule).wrap(AuthGuard)) // Complete held data
 
             // Webhook for incoming crypto events (e.g., BTCPay invoice updates)
             .route("/webhook/{provider}", web::post().to(handle_crypto_webhook)) // provider=btcpay, monero ?
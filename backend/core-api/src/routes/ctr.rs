@@ -0,0 +1,28 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/routes/ctr.rs
+use actix_web::web;
+use crate::handlers::ctr::{
+    list_reports, get_report, run_business_day, list_batches, generate_batch, get_batch, download_batch,
+    mark_batch_submitted, record_acknowledgement, list_exemptions, grant_exemption, revoke_exemption,
+};
+use crate::middlewares::auth_guard::AuthGuard; // Compliance roles are checked in the handlers
+
+/// Configures Currency Transaction Report routes: `/api/v1/ctr/...`
+pub fn configure_ctr_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/ctr")
+            .route("/reports", web::get().to(list_reports).wrap(AuthGuard))
+            .route("/reports/{report_id}", web::get().to(get_report).wrap(AuthGuard))
+            .route("/run", web::post().to(run_business_day).wrap(AuthGuard))
+            // Batch files: generate, download, then record submission and FinCEN's acknowledgement
+            .route("/batches", web::get().to(list_batches).wrap(AuthGuard))
+            .route("/batches", web::post().to(generate_batch).wrap(AuthGuard))
+            .route("/batches/{batch_id}", web::get().to(get_batch).wrap(AuthGuard))
+            .route("/batches/{batch_id}/xml", web::get().to(download_batch).wrap(AuthGuard))
+            .route("/batches/{batch_id}/submitted", web::post().to(mark_batch_submitted).wrap(AuthGuard))
+            .route("/batches/{batch_id}/acknowledgement", web::post().to(record_acknowledgement).wrap(AuthGuard))
+            // Exempt persons
+            .route("/exemptions", web::get().to(list_exemptions).wrap(AuthGuard))
+            .route("/exemptions", web::post().to(grant_exemption).wrap(AuthGuard))
+            .route("/exemptions/{exemption_id}/revoke", web::post().to(revoke_exemption).wrap(AuthGuard))
+    );
+}
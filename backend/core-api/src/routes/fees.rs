@@ -0,0 +1,13 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/routes/fees.rs
+use actix_web::web;
+use crate::handlers::fees::{quote_fee, get_fee_revenue};
+use crate::middlewares::auth_guard::AuthGuard; // Requires authentication (+ finance role for revenue, checked in handler)
+
+/// Configures fee routes: `/api/v1/fees/...`
+pub fn configure_fee_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/fees")
+            .route("/quote", web::post().to(quote_fee).wrap(AuthGuard))
+            .route("/revenue", web::get().to(get_fee_revenue).wrap(AuthGuard))
+    );
+}
@@ -0,0 +1,28 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/routes/fraud.rs
+use actix_web::web;
+use crate::handlers::fraud::{
+    list_evaluations, get_evaluation, get_transaction_evaluation, clear_review, confirm_review, get_rules, reload_rules,
+    rule_stats, replay_rules, ip_intel_status, lookup_ip, list_user_logins,
+};
+use crate::middlewares::auth_guard::AuthGuard; // Compliance/admin roles are checked in the handlers
+
+/// Configures fraud rule routes: `/api/v1/fraud/...`
+pub fn configure_fraud_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/fraud")
+            .route("/evaluations", web::get().to(list_evaluations).wrap(AuthGuard))
+            .route("/evaluations/{evaluation_id}", web::get().to(get_evaluation).wrap(AuthGuard))
+            .route("/evaluations/{evaluation_id}/clear", web::post().to(clear_review).wrap(AuthGuard))
+            .route("/evaluations/{evaluation_id}/confirm", web::post().to(confirm_review).wrap(AuthGuard))
+            .route("/transactions/{transaction_id}", web::get().to(get_transaction_evaluation).wrap(AuthGuard))
+            // Rules in force, hot reload and tuning
+            .route("/rules", web::get().to(get_rules).wrap(AuthGuard))
+            .route("/rules/reload", web::post().to(reload_rules).wrap(AuthGuard))
+            .route("/rules/stats", web::get().to(rule_stats).wrap(AuthGuard))
+            .route("/rules/replay", web::post().to(replay_rules).wrap(AuthGuard))
+            // IP intelligence behind the device.* features, login history
+            .route("/ip-intel", web::get().to(ip_intel_status).wrap(AuthGuard))
+            .route("/ip-intel/{ip}", web::get().to(lookup_ip).wrap(AuthGuard))
+            .route("/users/{user_id}/logins", web::get().to(list_user_logins).wrap(AuthGuard))
+    );
+}
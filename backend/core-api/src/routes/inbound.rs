@@ -0,0 +1,20 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/routes/inbound.rs
+use actix_web::web;
+use crate::handlers::inbound::{
+    receive_inbound_notice, list_open_suspense, assign_suspense, return_suspense, open_virtual_account,
+};
+use crate::middlewares::auth_guard::AuthGuard; // Notices and suspense are finance-only, checked in the handlers
+
+/// Configures inbound payment routes: `/api/v1/inbound/...`
+pub fn configure_inbound_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/inbound")
+            .route("/notices", web::post().to(receive_inbound_notice).wrap(AuthGuard))
+            // Unmatched payments
+            .route("/suspense", web::get().to(list_open_suspense).wrap(AuthGuard))
+            .route("/suspense/{suspense_id}/assign", web::post().to(assign_suspense).wrap(AuthGuard))
+            .route("/suspense/{suspense_id}/return", web::post().to(return_suspense).wrap(AuthGuard))
+            // Customer-facing account numbers for receiving funds
+            .route("/virtual-accounts", web::post().to(open_virtual_account).wrap(AuthGuard))
+    );
+}
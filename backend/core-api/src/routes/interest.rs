@@ -0,0 +1,16 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/routes/interest.rs
+use actix_web::web;
+use crate::handlers::interest::{create_rate_version, list_rate_versions, get_wallet_interest, run_interest};
+use crate::middlewares::auth_guard::AuthGuard; // Rate management is finance-only, checked in the handlers
+
+/// Configures interest routes: `/api/v1/interest/...`
+pub fn configure_interest_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/interest")
+            // Effective-dated rate versions per wallet type and currency
+            .route("/rates", web::post().to(create_rate_version).wrap(AuthGuard))
+            .route("/rates", web::get().to(list_rate_versions).wrap(AuthGuard))
+            .route("/wallets/{wallet_id}", web::get().to(get_wallet_interest).wrap(AuthGuard))
+            .route("/run", web::post().to(run_interest).wrap(AuthGuard))
+    );
+}
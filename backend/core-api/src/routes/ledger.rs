@@ -0,0 +1,13 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/routes/ledger.rs
+use actix_web::web;
+use crate::handlers::ledger::{get_trial_balance, get_wallet_balance_drift};
+use crate::middlewares::auth_guard::AuthGuard; // Requires authentication (+ finance role, checked in handlers)
+
+/// Configures ledger reporting routes: `/api/v1/ledger/...`
+pub fn configure_ledger_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/ledger")
+            .route("/trial-balance", web::get().to(get_trial_balance).wrap(AuthGuard))
+            .route("/wallet-drift", web::get().to(get_wallet_balance_drift).wrap(AuthGuard))
+    );
+}
@@ -0,0 +1,18 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/routes/limits.rs
+use actix_web::web;
+use crate::handlers::limits::{get_usage, request_increase, list_increases, approve_increase, reject_increase, revoke_increase};
+use crate::middlewares::auth_guard::AuthGuard; // Decisions are admin-only, checked in the handlers
+
+/// Configures transaction limit routes: `/api/v1/limits/...`
+pub fn configure_limit_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/limits")
+            .route("/usage", web::get().to(get_usage).wrap(AuthGuard))
+            // Temporary increases: requested by the customer (or finance), decided by an admin
+            .route("/increases", web::post().to(request_increase).wrap(AuthGuard))
+            .route("/increases", web::get().to(list_increases).wrap(AuthGuard))
+            .route("/increases/{override_id}/approve", web::post().to(approve_increase).wrap(AuthGuard))
+            .route("/increases/{override_id}/reject", web::post().to(reject_increase).wrap(AuthGuard))
+            .route("/increases/{override_id}/revoke", web::post().to(revoke_increase).wrap(AuthGuard))
+    );
+}
@@ -3,9 +3,25 @@ use actix_web::web;
 
 // Import route module configurations
 mod auth;
+mod beneficiaries; // Saved payees and their verification
+mod conversion; // FX quotes and wallet-to-wallet conversions
 mod crypto;
+mod fees; // Fee quotes and fee revenue
 mod ft_integration; // Financial Times API integration routes
+mod inbound; // Inbound payment notices, suspense queue, virtual accounts
+mod interest; // Interest rate versions, accruals and capitalization
+mod ledger; // Trial balance and ledger checks
+mod limits; // Transaction limit usage and temporary increases
 mod payments;
+mod payouts; // Bulk payout file uploads and batch reports
+mod reconciliation; // Nostro statement reconciliation, breaks and break reports
+mod sanctions; // Sanctions screening cases and ad-hoc screening
+mod aml; // AML monitoring cases, SAR summaries and batch runs
+mod ctr; // Currency Transaction Reports, batch filings and exemptions
+mod travel_rule; // Travel Rule records of crypto withdrawals and the VASP directory
+mod fraud; // Fraud rule evaluations, analyst review and rule tuning
+mod schedules; // Standing orders (future-dated / recurring payments)
+mod treasury; // EOD nostro positions and liquidity projections
 // mod health; // Optional: Add a health check route
 
 /// Configures all API routes under the `/api/v1` scope.
@@ -16,7 +32,23 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
             .configure(auth::configure_auth_routes)
             .configure(payments::configure_payment_routes)
             .configure(crypto::configure_crypto_routes)
+            .configure(conversion::configure_conversion_routes)
             .configure(ft_integration::configure_ft_routes)
+            .configure(ledger::configure_ledger_routes)
+            .configure(fees::configure_fee_routes)
+            .configure(schedules::configure_schedule_routes)
+            .configure(payouts::configure_payout_routes)
+            .configure(beneficiaries::configure_beneficiary_routes)
+            .configure(inbound::configure_inbound_routes)
+            .configure(reconciliation::configure_reconciliation_routes)
+            .configure(treasury::configure_treasury_routes)
+            .configure(interest::configure_interest_routes)
+            .configure(limits::configure_limit_routes)
+            .configure(sanctions::configure_sanctions_routes)
+            .configure(aml::configure_aml_routes)
+            .configure(ctr::configure_ctr_routes)
+            .configure(travel_rule::configure_travel_rule_routes)
+            .configure(fraud::configure_fraud_routes)
             // Add configurations for other route modules here
             // e.g., user profile management, admin endpoints
     );
@@ -1,6 +1,10 @@
 // /home/inno/elights_jobes-research/backend/core-api/src/routes/payments.rs
 use actix_web::web;
-use crate::handlers::payments::{initiate_payment, get_payment_status, handle_payment_webhook};
+use crate::handlers::payments::{
+    initiate_payment, get_payment_status, handle_payment_webhook,
+    list_pending_approvals, get_payment_approval, approve_payment, reject_payment,
+    list_webhook_events, reprocess_webhook_event,
+};
 use crate::middlewares::auth_guard::AuthGuard; // Import auth middleware
 
 /// Configures payment related routes: `/api/v1/payments/...`
@@ -11,8 +15,16 @@ pub fn configure_payment_routes(cfg: &mut web::ServiceConfig) {
             .route("/initiate", web::post().to(initiate_payment).wrap(AuthGuard))
             // Endpoint to get status of a specific transaction (needs auth)
             .route("/status/{transaction_id}", web::get().to(get_payment_status).wrap(AuthGuard))
+            // Maker-checker: payments awaiting the caller's approval, and deciding on them (needs auth)
+            .route("/approvals", web::get().to(list_pending_approvals).wrap(AuthGuard))
+            .route("/{transaction_id}/approval", web::get().to(get_payment_approval).wrap(AuthGuard))
+            .route("/{transaction_id}/approve", web::post().to(approve_payment).wrap(AuthGuard))
+            .route("/{transaction_id}/reject", web::post().to(reject_payment).wrap(AuthGuard))
             // Public endpoint for receiving payment status updates from external providers
             .route("/webhook/{provider}", web::post().to(handle_payment_webhook)) // e.g., provider=stripe, btcpay
+            // Stored webhook deliveries and reprocessing them (needs auth, finance/admin)
+            .route("/webhooks/events", web::get().to(list_webhook_events).wrap(AuthGuard))
+            .route("/webhooks/events/{event_id}/reprocess", web::post().to(reprocess_webhook_event).wrap(AuthGuard))
             // TODO: Add routes for listing user's payment history (with pagination)
             // .route("/history", web::get().to(list_payment_history).wrap(AuthGuard))
     );
@@ -0,0 +1,15 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/routes/payouts.rs
+use actix_web::web;
+use crate::handlers::payouts::{create_payout_batch, list_payout_batches, get_payout_batch, MAX_PAYOUT_FILE_BYTES};
+use crate::middlewares::auth_guard::AuthGuard; // Batches are uploaded against the caller's own wallets
+
+/// Configures bulk payout routes: `/api/v1/payouts/...`
+pub fn configure_payout_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/payouts")
+            .app_data(web::PayloadConfig::new(MAX_PAYOUT_FILE_BYTES)) // Raw CSV/JSON file bodies
+            .route("/batches", web::post().to(create_payout_batch).wrap(AuthGuard))
+            .route("/batches", web::get().to(list_payout_batches).wrap(AuthGuard))
+            .route("/batches/{batch_id}", web::get().to(get_payout_batch).wrap(AuthGuard))
+    );
+}
@@ -0,0 +1,24 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/routes/reconciliation.rs
+use actix_web::web;
+use crate::handlers::reconciliation::{
+    list_nostro_accounts, run_reconciliation, list_statement_lines, list_exceptions, get_exception,
+    resolve_exception, create_manual_match, get_break_report,
+};
+use crate::middlewares::auth_guard::AuthGuard; // Reconciliation is finance-only, checked in the handlers
+
+/// Configures reconciliation routes: `/api/v1/reconciliation/...`
+pub fn configure_reconciliation_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/reconciliation")
+            // Nostro accounts and their statements
+            .route("/accounts", web::get().to(list_nostro_accounts).wrap(AuthGuard))
+            .route("/accounts/{code}/run", web::post().to(run_reconciliation).wrap(AuthGuard))
+            .route("/accounts/{code}/lines", web::get().to(list_statement_lines).wrap(AuthGuard))
+            // Breaks and their manual resolution
+            .route("/exceptions", web::get().to(list_exceptions).wrap(AuthGuard))
+            .route("/exceptions/{exception_id}", web::get().to(get_exception).wrap(AuthGuard))
+            .route("/exceptions/{exception_id}/resolve", web::post().to(resolve_exception).wrap(AuthGuard))
+            .route("/matches", web::post().to(create_manual_match).wrap(AuthGuard))
+            .route("/reports/breaks", web::get().to(get_break_report).wrap(AuthGuard))
+    );
+}
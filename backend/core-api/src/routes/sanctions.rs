@@ -0,0 +1,18 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/routes/sanctions.rs
+use actix_web::web;
+use crate::handlers::sanctions::{list_cases, get_case, clear_case, confirm_case, screen_parties, list_watchlists};
+use crate::middlewares::auth_guard::AuthGuard; // Compliance roles are checked in the handlers
+
+/// Configures sanctions screening routes: `/api/v1/sanctions/...`
+pub fn configure_sanctions_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/sanctions")
+            .route("/cases", web::get().to(list_cases).wrap(AuthGuard))
+            .route("/cases/{case_id}", web::get().to(get_case).wrap(AuthGuard))
+            .route("/cases/{case_id}/clear", web::post().to(clear_case).wrap(AuthGuard))
+            .route("/cases/{case_id}/confirm", web::post().to(confirm_case).wrap(AuthGuard))
+            // Ad-hoc checks (e.g. before onboarding a counterparty) and the lists in force
+            .route("/screen", web::post().to(screen_parties).wrap(AuthGuard))
+            .route("/lists", web::get().to(list_watchlists).wrap(AuthGuard))
+    );
+}
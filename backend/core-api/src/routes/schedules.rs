@@ -0,0 +1,20 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/routes/schedules.rs
+use actix_web::web;
+use crate::handlers::schedules::{
+    create_schedule, list_schedules, get_schedule, pause_schedule, resume_schedule, skip_next_occurrence, cancel_schedule,
+};
+use crate::middlewares::auth_guard::AuthGuard; // All schedule routes act on the caller's own schedules
+
+/// Configures standing-order routes: `/api/v1/schedules/...`
+pub fn configure_schedule_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/schedules")
+            .route("", web::post().to(create_schedule).wrap(AuthGuard))
+            .route("", web::get().to(list_schedules).wrap(AuthGuard))
+            .route("/{schedule_id}", web::get().to(get_schedule).wrap(AuthGuard))
+            .route("/{schedule_id}/pause", web::post().to(pause_schedule).wrap(AuthGuard))
+            .route("/{schedule_id}/resume", web::post().to(resume_schedule).wrap(AuthGuard))
+            .route("/{schedule_id}/skip", web::post().to(skip_next_occurrence).wrap(AuthGuard))
+            .route("/{schedule_id}/cancel", web::post().to(cancel_schedule).wrap(AuthGuard))
+    );
+}
@@ -0,0 +1,15 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/routes/travel_rule.rs
+use actix_web::web;
+use crate::handlers::travel_rule::{list_transfers, get_transfer, list_vasps};
+use crate::middlewares::auth_guard::AuthGuard; // Compliance roles are checked in the handlers
+
+/// Configures Travel Rule routes: `/api/v1/travel-rule/...`
+/// Customers supply held withdrawals' data under `/crypto/withdrawals/{transaction_id}/travel-rule`.
+pub fn configure_travel_rule_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/travel-rule")
+            .route("/vasps", web::get().to(list_vasps).wrap(AuthGuard)) // Any authenticated user
+            .route("/transfers", web::get().to(list_transfers).wrap(AuthGuard))
+            .route("/transfers/{transfer_id}", web::get().to(get_transfer).wrap(AuthGuard))
+    );
+}
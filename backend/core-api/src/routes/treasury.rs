@@ -0,0 +1,19 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/routes/treasury.rs
+use actix_web::web;
+use crate::handlers::treasury::{
+    get_live_positions, run_eod, list_eod_snapshots, get_eod_snapshot, verify_eod_snapshot,
+};
+use crate::middlewares::auth_guard::AuthGuard; // Treasury views are finance-only, checked in the handlers
+
+/// Configures treasury routes: `/api/v1/treasury/...`
+pub fn configure_treasury_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/treasury")
+            .route("/positions", web::get().to(get_live_positions).wrap(AuthGuard))
+            // End-of-day position reports, one signed snapshot per business day
+            .route("/eod/run", web::post().to(run_eod).wrap(AuthGuard))
+            .route("/eod/snapshots", web::get().to(list_eod_snapshots).wrap(AuthGuard))
+            .route("/eod/snapshots/{business_date}", web::get().to(get_eod_snapshot).wrap(AuthGuard))
+            .route("/eod/snapshots/{business_date}/verify", web::get().to(verify_eod_snapshot).wrap(AuthGuard))
+    );
+}
@@ -0,0 +1,47 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/services/aml_worker.rs
+// Runs AML monitoring: every interval, transactions created or changed since the last pass are monitored
+// with their customers' recent history; once a day, the whole previous day (UTC) is re-run in batch to catch
+// anything the streaming pass missed. Both are idempotent (alerts already raised are only extended), so a
+// restart simply repeats the day's batch.
+use crate::db::DbPool;
+use chrono::{Duration as ChronoDuration, NaiveDate, Utc};
+use domain::aml::{self, AmlConfig};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Runs the monitoring passes on their own thread (Diesel calls are blocking) every `interval`.
+pub fn spawn_aml_worker(db_pool: DbPool, config: Arc<AmlConfig>, interval: Duration) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        log::info!("AML monitoring worker started (interval {:?})", interval);
+        let mut batch_done_for: Option<NaiveDate> = None;
+        loop {
+            match db_pool.get() {
+                Ok(mut conn) => {
+                    let now = Utc::now();
+                    match aml::monitor_new_transactions(&mut conn, &config, now) {
+                        Ok(summary) if summary.alerts_raised > 0 => log::warn!(
+                            "AML monitoring raised {} alert(s) ({} case(s) opened) over {} transactions",
+                            summary.alerts_raised, summary.cases_opened, summary.transactions_seen
+                        ),
+                        Ok(_) => {}
+                        Err(e) => log::error!("AML streaming monitoring failed: {}", e),
+                    }
+
+                    let yesterday = now.date_naive() - ChronoDuration::days(1);
+                    if batch_done_for != Some(yesterday) {
+                        let from = yesterday.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+                        match aml::run_batch(&mut conn, &config, from, from + ChronoDuration::days(1)) {
+                            Ok(summary) => {
+                                log::info!("AML batch for {}: {} alert(s) raised, {} updated", yesterday, summary.alerts_raised, summary.alerts_updated);
+                                batch_done_for = Some(yesterday);
+                            }
+                            Err(e) => log::error!("AML batch for {} failed: {}", yesterday, e),
+                        }
+                    }
+                }
+                Err(e) => log::error!("AML worker could not get DB connection: {}", e),
+            }
+            std::thread::sleep(interval);
+        }
+    })
+}
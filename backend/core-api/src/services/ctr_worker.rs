@@ -0,0 +1,38 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/services/ctr_worker.rs
+// Aggregates the last completed business day into Currency Transaction Reports. Each run is idempotent (unchanged
+// reports are left alone, late activity refreshes reports not batched yet), so the interval only bounds how soon
+// a new day is picked up. Batch files are generated and submitted by compliance through the API.
+use crate::db::DbPool;
+use chrono::{Duration as ChronoDuration, Utc};
+use domain::ctr::{self, CtrConfig};
+use domain::payments::{BusinessCalendar, BusinessDayConvention};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Runs the CTR aggregation on its own thread (Diesel calls are blocking) every `interval`.
+pub fn spawn_ctr_worker(
+    db_pool: DbPool,
+    config: Arc<CtrConfig>,
+    calendar: BusinessCalendar,
+    interval: Duration,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        log::info!("CTR worker started (interval {:?})", interval);
+        loop {
+            let yesterday = Utc::now().date_naive() - ChronoDuration::days(1);
+            let business_date = calendar.adjust(yesterday, BusinessDayConvention::Preceding);
+            match db_pool.get() {
+                Ok(mut conn) => match ctr::run_business_day(&mut conn, &config, &calendar, business_date) {
+                    Ok(summary) if summary.reports_created + summary.reports_updated > 0 => log::info!(
+                        "CTRs for {}: {} created, {} updated ({} exempt)",
+                        business_date, summary.reports_created, summary.reports_updated, summary.exempt
+                    ),
+                    Ok(_) => {}
+                    Err(e) => log::error!("CTR run for {} failed: {}", business_date, e),
+                },
+                Err(e) => log::error!("CTR worker could not get DB connection: {}", e),
+            }
+            std::thread::sleep(interval);
+        }
+    })
+}
@@ -0,0 +1,80 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/services/eod_worker.rs
+// Balances for the end-of-day position report come from the same bank clients as statements.
+// The worker produces the report once per business day after the configured run time (UTC); a
+// restart later that day finds the stored snapshot and does not run again.
+use crate::db::DbPool;
+use crate::services::reconciliation_worker::BankStatementSource;
+use async_trait::async_trait;
+use chrono::{NaiveTime, Utc};
+use domain::payments::BusinessCalendar;
+use domain::reconciliation::{NostroAccount, NostroAccountSet};
+use domain::treasury::{self, BalanceSource, NostroBalance, ReportSigner};
+use domain::DomainError;
+use std::time::Duration;
+
+const ACTOR: &str = "eod_worker";
+/// How often the worker checks whether the day's report is due.
+const POLL_INTERVAL: Duration = Duration::from_secs(300);
+
+#[async_trait]
+impl BalanceSource for BankStatementSource {
+    async fn fetch_balance(&self, account: &NostroAccount) -> Result<NostroBalance, DomainError> {
+        let client = self.client_for(account)?;
+        let balance = client.fetch_balance(&account.account_id).await
+            .map_err(|e| DomainError::ExternalService(format!("{} balance for {}: {}", client.bank_name(), account.code, e)))?;
+        if !balance.currency.eq_ignore_ascii_case(&account.currency) {
+            return Err(DomainError::ExternalService(format!(
+                "{} reported a {} balance for {}, expected {}", client.bank_name(), balance.currency, account.code, account.currency
+            )));
+        }
+        Ok(NostroBalance {
+            available: balance.available_balance,
+            ledger: balance.ledger_balance,
+            as_of: balance.balance_timestamp,
+        })
+    }
+}
+
+/// Starts the end-of-day worker on its own thread (Diesel calls are blocking).
+pub fn spawn_eod_worker(
+    db_pool: DbPool,
+    accounts: NostroAccountSet,
+    source: BankStatementSource,
+    calendar: BusinessCalendar,
+    signer: ReportSigner,
+    run_time: NaiveTime,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(rt) => rt,
+            Err(e) => {
+                log::error!("Failed to start EOD worker runtime: {}", e);
+                return;
+            }
+        };
+        runtime.block_on(async move {
+            log::info!("EOD position worker started (runs at {} UTC on business days)", run_time);
+            loop {
+                let now = Utc::now();
+                let today = now.date_naive();
+                if calendar.is_business_day(today) && now.time() >= run_time {
+                    match db_pool.get() {
+                        Ok(mut conn) => match treasury::get_snapshot(&mut conn, today) {
+                            Ok(_) => {} // Already produced today
+                            Err(DomainError::NotFound(_)) => {
+                                if let Err(e) = treasury::run_end_of_day(
+                                    &mut conn, &source, &accounts, &calendar, &signer, today, ACTOR, now,
+                                ).await {
+                                    log::error!("EOD position report for {} failed: {}", today, e);
+                                }
+                            }
+                            Err(e) => log::error!("EOD worker could not look up today's snapshot: {}", e),
+                        },
+                        Err(e) => log::error!("EOD worker could not get DB connection: {}", e),
+                    }
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        });
+    })
+}
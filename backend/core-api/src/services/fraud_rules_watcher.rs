@@ -0,0 +1,21 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/services/fraud_rules_watcher.rs
+// Reloads the fraud rule file when it changes, so rules can be added or tuned without a deploy. A file that does
+// not parse or validate is logged and the rules in force are kept until it is fixed.
+use domain::fraud::FraudRules;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Checks the rule file on its own thread every `interval`.
+pub fn spawn_fraud_rules_watcher(fraud_rules: Arc<FraudRules>, interval: Duration) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        log::info!("Fraud rules watcher started on {:?} (interval {:?})", fraud_rules.path(), interval);
+        loop {
+            std::thread::sleep(interval);
+            match fraud_rules.reload_if_changed() {
+                Ok(true) => log::info!("Fraud rules {} now in force", fraud_rules.current().version),
+                Ok(false) => {}
+                Err(e) => log::error!("Fraud rule file not reloaded, previous rules stay in force: {}", e),
+            }
+        }
+    })
+}
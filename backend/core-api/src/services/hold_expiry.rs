@@ -0,0 +1,44 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/services/hold_expiry.rs
+// Marks lapsed wallet holds (uncaptured card authorizations, cleared checks) as EXPIRED.
+// Expired holds already stop counting against the available balance; this keeps the holds table accurate.
+// The same sweep closes FX quotes that were never executed (execution already rejects them once expired)
+// and expires payments whose approval window lapsed before enough approvers signed off, or that were held for
+// Travel Rule data or a counterparty VASP's answer longer than the hold window.
+use crate::db::DbPool;
+use chrono::Utc;
+use std::time::Duration;
+
+/// Runs the sweep on its own thread (Diesel calls are blocking) every `interval`.
+pub fn spawn_hold_expiry_sweeper(db_pool: DbPool, interval: Duration) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        log::info!("Hold expiry sweeper started (interval {:?})", interval);
+        loop {
+            match db_pool.get() {
+                Ok(mut conn) => {
+                    match domain::ledger::expire_holds(&mut conn, Utc::now()) {
+                        Ok(0) => {}
+                        Ok(count) => log::info!("Expired {} wallet holds", count),
+                        Err(e) => log::error!("Hold expiry sweep failed: {}", e),
+                    }
+                    match domain::payments::expire_quotes(&mut conn, Utc::now()) {
+                        Ok(0) => {}
+                        Ok(count) => log::info!("Expired {} FX quotes", count),
+                        Err(e) => log::error!("FX quote expiry sweep failed: {}", e),
+                    }
+                    match domain::approvals::expire_approvals(&mut conn, Utc::now()) {
+                        Ok(0) => {}
+                        Ok(count) => log::info!("Expired {} payments awaiting approval", count),
+                        Err(e) => log::error!("Approval expiry sweep failed: {}", e),
+                    }
+                    match domain::travel_rule::expire_transfers(&mut conn, Utc::now()) {
+                        Ok(0) => {}
+                        Ok(count) => log::info!("Expired {} withdrawals held for the Travel Rule", count),
+                        Err(e) => log::error!("Travel Rule hold expiry sweep failed: {}", e),
+                    }
+                }
+                Err(e) => log::error!("Hold expiry sweeper could not get DB connection: {}", e),
+            }
+            std::thread::sleep(interval);
+        }
+    })
+}
@@ -0,0 +1,31 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/services/interest_worker.rs
+// Keeps savings-wallet interest current: accrues every interest-bearing wallet through yesterday
+// (recomputing days changed by backdated rates or value-dated transactions) and capitalizes the
+// previous month once its accruals are complete. Each run is idempotent, so the interval only bounds
+// how soon after midnight (UTC) a new day or month is picked up.
+use crate::db::DbPool;
+use chrono::Utc;
+use std::time::Duration;
+
+const ACTOR: &str = "interest_worker";
+
+/// Runs the interest cycle on its own thread (Diesel calls are blocking) every `interval`.
+pub fn spawn_interest_worker(db_pool: DbPool, interval: Duration) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        log::info!("Interest worker started (interval {:?})", interval);
+        loop {
+            match db_pool.get() {
+                Ok(mut conn) => match domain::interest::run_interest(&mut conn, Utc::now(), ACTOR) {
+                    Ok(summary) if summary.days_written > 0 => log::info!(
+                        "Interest accrued through {} ({} wallets, {} days recomputed)",
+                        summary.accrued_through, summary.wallets_accrued, summary.days_written
+                    ),
+                    Ok(_) => {}
+                    Err(e) => log::error!("Interest run failed: {}", e),
+                },
+                Err(e) => log::error!("Interest worker could not get DB connection: {}", e),
+            }
+            std::thread::sleep(interval);
+        }
+    })
+}
@@ -2,4 +2,14 @@
 // Contains clients or services specific to the API layer, often wrapping external APIs.
 
 pub mod ft_client; // Client for Financial Times API
+pub mod outbox_worker; // Background dispatcher for the payment outbox (ACH/Wire/crypto)
+pub mod hold_expiry; // Background sweep of lapsed wallet holds
+pub mod payment_scheduler; // Background submission of due standing orders
+pub mod reconciliation_worker; // Nostro statement pulls and reconciliation runs
+pub mod eod_worker; // Daily signed nostro position report
+pub mod interest_worker; // Daily interest accrual and monthly capitalization
+pub mod aml_worker; // Streaming and daily batch AML transaction monitoring
+pub mod ctr_worker; // Daily Currency Transaction Report aggregation
+pub mod travel_rule_worker; // Travel Rule exchanges with beneficiary VASPs
+pub mod fraud_rules_watcher; // Hot reload of the fraud rule file
 // Add other clients if needed (e.g., specific rate providers, compliance check services)
\ No newline at end of file
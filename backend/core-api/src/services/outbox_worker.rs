@@ -0,0 +1,137 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/services/outbox_worker.rs
+// Background runner for the domain payment outbox, plus the crypto dispatcher it needs
+// (wallet RPC clients live in the API layer).
+use crate::db::DbPool;
+use async_trait::async_trait;
+use cryptography_exchange::ExchangeError;
+#[cfg(feature = "monero_support")]
+use cryptography_exchange::MoneroWalletRpcClient;
+use domain::models::{OutboxOperation, PaymentOutbox, Transaction};
+use domain::payments::ach::NachaOriginator;
+use domain::payments::outbox::{DispatchError, OutboundDispatcher, OutboxPayload, OutboxWorker, RailDispatcher};
+use domain::payments::payout_batch::PayoutBatchWorker;
+use domain::sanctions::SanctionsScreener;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Broadcasts crypto withdrawals queued in the outbox.
+pub struct CryptoWithdrawalDispatcher {
+    #[cfg(feature = "monero_support")]
+    pub monero_client: Option<Arc<MoneroWalletRpcClient>>,
+}
+
+impl CryptoWithdrawalDispatcher {
+    pub fn new() -> Self {
+        CryptoWithdrawalDispatcher {
+            #[cfg(feature = "monero_support")]
+            monero_client: MoneroWalletRpcClient::new().ok().map(Arc::new),
+        }
+    }
+}
+
+/// Errors raised before anything reached the network are safe to reverse; the rest may have broadcast.
+fn classify_exchange_error(err: ExchangeError) -> DispatchError {
+    match err {
+        ExchangeError::InvalidInput(msg) | ExchangeError::UnsupportedCurrency(msg) => DispatchError::Rejected(msg),
+        ExchangeError::ConfigurationError(msg) => DispatchError::Unavailable(msg),
+        ExchangeError::NotInitialized(what) => DispatchError::Unavailable(format!("{} not initialized", what)),
+        ExchangeError::JsonRpcError { code, message } => DispatchError::Rejected(format!("RPC error {}: {}", code, message)),
+        other => DispatchError::Ambiguous(other.to_string()),
+    }
+}
+
+#[async_trait]
+impl OutboundDispatcher for CryptoWithdrawalDispatcher {
+    async fn dispatch(
+        &self,
+        _entry: &PaymentOutbox,
+        transaction: &Transaction,
+        payload: &OutboxPayload,
+    ) -> Result<String, DispatchError> {
+        let address = payload.crypto_address.clone()
+            .ok_or_else(|| DispatchError::Rejected("Missing destination address".to_string()))?;
+
+        match transaction.currency_code.as_str() {
+            "XMR" => {
+                #[cfg(feature = "monero_support")]
+                {
+                    use cryptography_exchange::monero_wallet::json_rpc::Destination;
+                    use domain::crypto::utils::xmr_to_atomic_units;
+
+                    let client = self.monero_client.as_ref()
+                        .ok_or_else(|| DispatchError::Unavailable("Monero wallet RPC not available".to_string()))?;
+                    let atomic_amount = xmr_to_atomic_units(transaction.amount)
+                        .ok_or_else(|| DispatchError::Rejected("Invalid amount for XMR conversion".to_string()))?;
+                    let dest = Destination { amount: atomic_amount, address };
+                    client.transfer(vec![dest], payload.crypto_payment_id.clone(), None, None).await
+                        .map(|res| res.tx_hash) // Tx Hash becomes the external reference
+                        .map_err(classify_exchange_error)
+                }
+                #[cfg(not(feature = "monero_support"))]
+                {
+                    let _ = address;
+                    Err(classify_exchange_error(ExchangeError::ConfigurationError("Monero support not enabled".to_string())))
+                }
+            }
+            "BTC" => {
+                // TODO: Create a BTCPay payout (payout id as external ref); BTCPay de-duplicates on payout metadata
+                let _ = address;
+                Err(DispatchError::Unavailable("BTC payout not implemented".to_string()))
+            }
+            other => Err(classify_exchange_error(ExchangeError::UnsupportedCurrency(other.to_string()))),
+        }
+    }
+
+    fn deduplicates(&self, _operation: OutboxOperation) -> bool {
+        // Wallet RPC transfers carry no idempotency key: an interrupted broadcast must be checked by hand
+        false
+    }
+}
+
+/// Starts the outbox worker on its own thread (Diesel calls are blocking) and polls every `interval`.
+/// The same loop sends the NACHA files of bulk payout batches, originated as `originator`. With a
+/// screener, the parties of each built wire message are screened before submission.
+pub fn spawn_outbox_worker(
+    db_pool: DbPool,
+    originator: NachaOriginator,
+    interval: Duration,
+    sanctions_screener: Option<Arc<SanctionsScreener>>,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(rt) => rt,
+            Err(e) => {
+                log::error!("Failed to start outbox worker runtime: {}", e);
+                return;
+            }
+        };
+        let mut dispatcher = RailDispatcher::new().with_crypto_dispatcher(Arc::new(CryptoWithdrawalDispatcher::new()));
+        if let Some(screener) = sanctions_screener {
+            dispatcher = dispatcher.with_sanctions_screener(screener);
+        }
+
+        runtime.block_on(async move {
+            let worker = OutboxWorker::new(&dispatcher);
+            let batch_worker = PayoutBatchWorker::new(&originator);
+            log::info!("Payment outbox worker started (interval {:?})", interval);
+            loop {
+                match db_pool.get() {
+                    Ok(mut conn) => {
+                        match worker.run_once(&mut conn).await {
+                            Ok(0) => {}
+                            Ok(count) => log::info!("Outbox worker processed {} entries", count),
+                            Err(e) => log::error!("Outbox worker run failed: {}", e),
+                        }
+                        match batch_worker.run_once(&mut conn).await {
+                            Ok(0) => {}
+                            Ok(count) => log::info!("Outbox worker dispatched {} payout NACHA files", count),
+                            Err(e) => log::error!("Payout batch dispatch run failed: {}", e),
+                        }
+                    }
+                    Err(e) => log::error!("Outbox worker could not get DB connection: {}", e),
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+    })
+}
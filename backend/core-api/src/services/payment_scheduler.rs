@@ -0,0 +1,61 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/services/payment_scheduler.rs
+// Background runner for standing orders: submits the scheduled payments due today through the payment processor.
+// Occurrences missed while the service was down are handled per schedule (missed-run policy) on the next poll.
+use crate::db::DbPool;
+use chrono::Utc;
+use domain::approvals::ApprovalPolicySet;
+use domain::beneficiaries::CoolingOffLimits;
+use domain::fees::FeeSchedule;
+use domain::limits::LimitPolicy;
+use domain::sanctions::SanctionsScreener;
+use domain::fraud::FraudRules;
+use domain::payments::{BusinessCalendar, PaymentGateway, ScheduledPaymentWorker};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Starts the scheduler on its own thread (Diesel calls are blocking) and polls every `interval`.
+pub fn spawn_payment_scheduler(
+    db_pool: DbPool,
+    fee_schedule: FeeSchedule,
+    approval_policies: ApprovalPolicySet,
+    beneficiary_limits: CoolingOffLimits,
+    limit_policy: LimitPolicy,
+    sanctions_screener: Option<Arc<SanctionsScreener>>, // None when screening is disabled
+    fraud_rules: Arc<FraudRules>, // Hot-reloaded; each run uses the rules in force
+    calendar: BusinessCalendar,
+    card_gateway: Arc<dyn PaymentGateway>, // The API handlers' router: authorizations are shared
+    interval: Duration,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(rt) => rt,
+            Err(e) => {
+                log::error!("Failed to start payment scheduler runtime: {}", e);
+                return;
+            }
+        };
+        runtime.block_on(async move {
+            let mut worker = ScheduledPaymentWorker::new(card_gateway.as_ref(), &calendar)
+                .with_fee_schedule(&fee_schedule)
+                .with_approval_policies(&approval_policies)
+                .with_beneficiary_limits(&beneficiary_limits)
+                .with_limit_policy(&limit_policy)
+                .with_fraud_rules(&fraud_rules);
+            if let Some(screener) = sanctions_screener.as_deref() {
+                worker = worker.with_sanctions_screener(screener);
+            }
+            log::info!("Payment scheduler started (interval {:?})", interval);
+            loop {
+                match db_pool.get() {
+                    Ok(mut conn) => match worker.run_once(&mut conn, Utc::now().date_naive()).await {
+                        Ok(0) => {}
+                        Ok(count) => log::info!("Payment scheduler processed {} scheduled payments", count),
+                        Err(e) => log::error!("Payment scheduler run failed: {}", e),
+                    },
+                    Err(e) => log::error!("Payment scheduler could not get DB connection: {}", e),
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+    })
+}
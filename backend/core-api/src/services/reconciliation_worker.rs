@@ -0,0 +1,114 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/services/reconciliation_worker.rs
+// Statement source for the domain reconciliation engine (the bank clients live in the API layer),
+// and the background runner that pulls and reconciles every configured nostro account.
+use crate::db::DbPool;
+use async_trait::async_trait;
+use bank_integrations::{BankClient, BankTransaction, TransactionType as BankTransactionType};
+use chrono::{DateTime, Utc};
+use domain::reconciliation::{self, NostroAccount, NostroAccountSet, StatementEntry, StatementSource};
+use domain::DomainError;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Bank clients by the name nostro accounts use in their `bank` field.
+#[derive(Default, Clone)]
+pub struct BankStatementSource {
+    clients: HashMap<String, Arc<dyn BankClient>>,
+}
+
+impl BankStatementSource {
+    pub fn with_client(mut self, bank: &str, client: Arc<dyn BankClient>) -> Self {
+        self.clients.insert(bank.to_string(), client);
+        self
+    }
+
+    /// The client serving `account`'s bank.
+    pub fn client_for(&self, account: &NostroAccount) -> Result<&Arc<dyn BankClient>, DomainError> {
+        self.clients.get(&account.bank).ok_or_else(|| {
+            DomainError::Configuration(format!("No bank client '{}' for nostro account {}", account.bank, account.code))
+        })
+    }
+}
+
+fn metadata_str(transaction: &BankTransaction, keys: &[&str]) -> Option<String> {
+    let metadata = transaction.metadata.as_ref()?;
+    keys.iter()
+        .find_map(|key| metadata.get(*key).and_then(JsonValue::as_str))
+        .map(str::to_string)
+}
+
+/// Banks report unsigned amounts with a direction; the engine wants credits positive.
+pub fn statement_entry(transaction: &BankTransaction) -> StatementEntry {
+    let amount = transaction.amount.abs();
+    let amount = match transaction.transaction_type {
+        BankTransactionType::Credit | BankTransactionType::TransferIn | BankTransactionType::Interest => amount,
+        BankTransactionType::Debit | BankTransactionType::TransferOut | BankTransactionType::Fee => -amount,
+        BankTransactionType::Other => transaction.amount, // Trust the bank's sign
+    };
+    StatementEntry {
+        bank_transaction_id: transaction.transaction_id.clone(),
+        booked_at: transaction.timestamp,
+        value_date: transaction.value_date,
+        amount,
+        currency: transaction.currency.clone(),
+        reference: metadata_str(transaction, &["end_to_end_id", "reference", "client_reference"]),
+        uetr: metadata_str(transaction, &["uetr", "UETR"]),
+        description: transaction.description.clone(),
+        counterparty_name: transaction.counterparty_name.clone(),
+        raw: serde_json::to_value(transaction).unwrap_or(JsonValue::Null),
+    }
+}
+
+#[async_trait]
+impl StatementSource for BankStatementSource {
+    async fn fetch_statement(
+        &self,
+        account: &NostroAccount,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<StatementEntry>, DomainError> {
+        let client = self.client_for(account)?;
+        let transactions = client.list_transactions(&account.account_id, Some(from), Some(to), None).await
+            .map_err(|e| DomainError::ExternalService(format!("{} statement for {}: {}", client.bank_name(), account.code, e)))?;
+        Ok(transactions.iter().map(statement_entry).collect())
+    }
+}
+
+/// Pulls and reconciles each account on its own thread (Diesel calls are blocking) every `interval`.
+pub fn spawn_reconciliation_worker(
+    db_pool: DbPool,
+    accounts: NostroAccountSet,
+    source: BankStatementSource,
+    interval: Duration,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(rt) => rt,
+            Err(e) => {
+                log::error!("Failed to start reconciliation worker runtime: {}", e);
+                return;
+            }
+        };
+        runtime.block_on(async move {
+            log::info!("Reconciliation worker started ({} accounts, interval {:?})", accounts.accounts.len(), interval);
+            loop {
+                match db_pool.get() {
+                    Ok(mut conn) => {
+                        for account in &accounts.accounts {
+                            // One account failing (bank API down) does not hold up the others
+                            match reconciliation::pull_and_reconcile(&mut conn, &source, account, Utc::now()).await {
+                                Ok(run) => log::info!("Reconciled {}: {} matches, {} new breaks",
+                                    account.code, run.matches.len(), run.exceptions_opened.len()),
+                                Err(e) => log::error!("Reconciliation of {} failed: {}", account.code, e),
+                            }
+                        }
+                    }
+                    Err(e) => log::error!("Reconciliation worker could not get DB connection: {}", e),
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+    })
+}
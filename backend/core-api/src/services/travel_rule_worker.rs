@@ -0,0 +1,108 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/services/travel_rule_worker.rs
+// Background exchange of Travel Rule data with beneficiary VASPs, plus the HTTPS JSON protocol adapter it needs
+// (HTTP clients live in the API layer). Held withdrawals are released or cancelled as the counterparties answer.
+use crate::db::DbPool;
+use async_trait::async_trait;
+use domain::travel_rule::{
+    CounterpartyVasp, LoopbackProtocol, ProtocolError, ProtocolRouter, TransferMessage, TransferReply, TravelRuleConfig,
+    TravelRuleExchange, TravelRuleProtocol, VaspProtocol,
+};
+use reqwest::{Client as HttpClient, StatusCode};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// IVMS101 JSON over HTTPS: `POST {endpoint}/transfers` with the transfer message, `GET {endpoint}/transfers/{reference}`
+/// for a pending decision. The counterparty answers with a `TransferReply` body.
+pub struct HttpsJsonProtocol {
+    http_client: HttpClient,
+}
+
+impl HttpsJsonProtocol {
+    pub fn new(http_client: HttpClient) -> Self {
+        HttpsJsonProtocol { http_client }
+    }
+
+    fn endpoint<'c>(counterparty: &'c CounterpartyVasp) -> Result<&'c str, ProtocolError> {
+        counterparty
+            .endpoint
+            .as_deref()
+            .map(|endpoint| endpoint.trim_end_matches('/'))
+            .ok_or_else(|| ProtocolError::Refused(format!("{} has no endpoint", counterparty.vasp_id)))
+    }
+
+    /// Client errors are definitive; timeouts, throttling and server errors are retried.
+    async fn reply(response: Result<reqwest::Response, reqwest::Error>) -> Result<TransferReply, ProtocolError> {
+        let response = response.map_err(|e| ProtocolError::Unavailable(e.to_string()))?;
+        let status = response.status();
+        if status.is_success() {
+            return response.json::<TransferReply>().await.map_err(|e| ProtocolError::Refused(format!("unreadable reply: {}", e)));
+        }
+        let body = response.text().await.unwrap_or_default();
+        match status {
+            StatusCode::REQUEST_TIMEOUT | StatusCode::TOO_MANY_REQUESTS => Err(ProtocolError::Unavailable(format!("{}: {}", status, body))),
+            _ if status.is_client_error() => Err(ProtocolError::Refused(format!("{}: {}", status, body))),
+            _ => Err(ProtocolError::Unavailable(format!("{}: {}", status, body))),
+        }
+    }
+}
+
+#[async_trait]
+impl TravelRuleProtocol for HttpsJsonProtocol {
+    async fn send(&self, counterparty: &CounterpartyVasp, message: &TransferMessage) -> Result<TransferReply, ProtocolError> {
+        let url = format!("{}/transfers", Self::endpoint(counterparty)?);
+        let response = self
+            .http_client
+            .post(url)
+            .header("Request-Identifier", message.transfer_id.to_string()) // Lets the counterparty drop resent messages
+            .json(message)
+            .send()
+            .await;
+        Self::reply(response).await
+    }
+
+    async fn poll(&self, counterparty: &CounterpartyVasp, transfer_id: Uuid, reference: &str) -> Result<TransferReply, ProtocolError> {
+        let url = format!("{}/transfers/{}", Self::endpoint(counterparty)?, reference);
+        let response = self.http_client.get(url).header("Request-Identifier", transfer_id.to_string()).send().await;
+        Self::reply(response).await
+    }
+}
+
+/// Starts the exchange worker on its own thread (Diesel calls are blocking) and runs it every `interval`.
+/// LOOPBACK counterparties are only answered when the configuration is a sandbox.
+pub fn spawn_travel_rule_worker(
+    db_pool: DbPool,
+    config: Arc<TravelRuleConfig>,
+    http_client: HttpClient,
+    interval: Duration,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(rt) => rt,
+            Err(e) => {
+                log::error!("Failed to start Travel Rule worker runtime: {}", e);
+                return;
+            }
+        };
+        let mut protocols = ProtocolRouter::new().with_adapter(VaspProtocol::HttpsJson, Arc::new(HttpsJsonProtocol::new(http_client)));
+        if config.sandbox {
+            protocols = protocols.with_adapter(VaspProtocol::Loopback, Arc::new(LoopbackProtocol::new()));
+        }
+
+        runtime.block_on(async move {
+            let exchange = TravelRuleExchange::new(&config, &protocols);
+            log::info!("Travel Rule worker started (interval {:?})", interval);
+            loop {
+                match db_pool.get() {
+                    Ok(mut conn) => match exchange.run_once(&mut conn).await {
+                        Ok(0) => {}
+                        Ok(count) => log::info!("Travel Rule worker handled {} exchanges", count),
+                        Err(e) => log::error!("Travel Rule exchange run failed: {}", e),
+                    },
+                    Err(e) => log::error!("Travel Rule worker could not get DB connection: {}", e),
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+    })
+}
@@ -0,0 +1,129 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/utils/idempotency.rs
+// Glue between the `Idempotency-Key` HTTP header and the domain idempotency store.
+use crate::db::{get_db_conn, DbPool};
+use crate::error::ApiError;
+use actix_web::{http::StatusCode, web, HttpRequest, HttpResponse};
+use domain::services::idempotency::{self, IdempotencyOutcome};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Header clients use to make a mutating request safe to retry.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+// Scopes keep keys from different endpoints apart
+pub const SCOPE_PAYMENT_INITIATE: &str = "PAYMENT_INITIATE";
+pub const SCOPE_CRYPTO_WITHDRAWAL: &str = "CRYPTO_WITHDRAWAL";
+pub const SCOPE_CURRENCY_CONVERSION: &str = "CURRENCY_CONVERSION";
+
+/// Result of checking the idempotency store before running a handler.
+pub enum IdempotencyStart {
+    /// Run the handler; pass the claim to `complete`/`release` afterwards.
+    Proceed(IdempotencyClaim),
+    /// The request was already handled, return this stored response.
+    Replay(HttpResponse),
+}
+
+/// A claimed key (or no key at all, if the client did not send one).
+pub struct IdempotencyClaim {
+    key_id: Option<i64>,
+}
+
+impl IdempotencyClaim {
+    /// Stores the handler's response against the key and builds the HTTP response.
+    pub async fn complete<T: Serialize>(
+        self,
+        db_pool: &web::Data<DbPool>,
+        status: StatusCode,
+        body: &T,
+        transaction_id: Option<Uuid>,
+    ) -> Result<HttpResponse, ApiError> {
+        let body_json = serde_json::to_value(body)
+            .map_err(|e| ApiError::ConfigurationError(format!("Failed to serialize response: {}", e)))?;
+
+        if let Some(key_id) = self.key_id {
+            let mut conn = get_db_conn(db_pool)?;
+            let stored = body_json.clone();
+            web::block(move || {
+                idempotency::complete_idempotent_request(&mut conn, key_id, status.as_u16(), &stored, transaction_id)
+            })
+            .await?
+            .map_err(ApiError::DomainLogicError)?;
+        }
+
+        Ok(HttpResponse::build(status).json(body_json))
+    }
+
+    /// Frees the key after a failure so the client can retry with the same key.
+    pub async fn release(self, db_pool: &web::Data<DbPool>) {
+        let Some(key_id) = self.key_id else { return };
+        let released = match get_db_conn(db_pool) {
+            Ok(mut conn) => web::block(move || idempotency::release_idempotent_request(&mut conn, key_id))
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r.map_err(|e| e.to_string())),
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(e) = released {
+            // Key expires on its own; log so a stuck key can be investigated
+            log::error!("Failed to release idempotency key {}: {}", key_id, e);
+        }
+    }
+}
+
+/// Reads the `Idempotency-Key` header, falling back to a key supplied in the JSON body.
+pub fn idempotency_key_from_request(req: &HttpRequest, body_key: Option<&str>) -> Result<Option<String>, ApiError> {
+    let header_key = match req.headers().get(IDEMPOTENCY_KEY_HEADER) {
+        Some(value) => Some(
+            value.to_str()
+                .map_err(|_| ApiError::BadRequest("Invalid Idempotency-Key header".to_string()))?
+                .trim()
+                .to_string(),
+        ),
+        None => None,
+    };
+
+    match (header_key, body_key) {
+        (Some(h), Some(b)) if h != b => Err(ApiError::BadRequest(
+            "Idempotency-Key header does not match idempotency_key in body".to_string(),
+        )),
+        (Some(h), _) => Ok(Some(h)),
+        (None, Some(b)) => Ok(Some(b.to_string())),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Claims `key` for this user/endpoint or returns the stored response of a previous identical call.
+pub async fn begin<T: Serialize>(
+    db_pool: &web::Data<DbPool>,
+    user_id: Option<Uuid>,
+    scope: &'static str,
+    key: Option<String>,
+    request_body: &T,
+) -> Result<IdempotencyStart, ApiError> {
+    let Some(key) = key else {
+        return Ok(IdempotencyStart::Proceed(IdempotencyClaim { key_id: None }));
+    };
+
+    let body_json = serde_json::to_value(request_body)
+        .map_err(|e| ApiError::BadRequest(format!("Unable to fingerprint request: {}", e)))?;
+    let fingerprint = idempotency::fingerprint_request(scope, &body_json);
+
+    let mut conn = get_db_conn(db_pool)?;
+    let outcome = web::block(move || {
+        idempotency::begin_idempotent_request(&mut conn, user_id, scope, &key, &fingerprint)
+    })
+    .await?
+    .map_err(ApiError::DomainLogicError)?;
+
+    Ok(match outcome {
+        IdempotencyOutcome::Proceed(record) => IdempotencyStart::Proceed(IdempotencyClaim { key_id: Some(record.key_id) }),
+        IdempotencyOutcome::Replay { status_code, body } => {
+            let status = StatusCode::from_u16(status_code).unwrap_or(StatusCode::OK);
+            IdempotencyStart::Replay(
+                HttpResponse::build(status)
+                    .insert_header(("Idempotent-Replayed", "true"))
+                    .json(body),
+            )
+        }
+    })
+}
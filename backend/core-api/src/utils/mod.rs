@@ -1,4 +1,6 @@
 // /home/inno/elights_jobes-research/backend/core-api/src/utils/mod.rs
 
 pub mod http_clients; // HTTP client configuration (standard, Tor)
-// Add other API utility modules if needed (e.g., request_context, pagination_helpers)
\ No newline at end of file
+pub mod idempotency; // Idempotency-Key header handling
+pub mod request_context; // Client IP, user agent and device id of a request (fraud rule features)
+// Add other API utility modules if needed (e.g., pagination_helpers)
\ No newline at end of file
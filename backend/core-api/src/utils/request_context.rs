@@ -0,0 +1,64 @@
+// /home/inno/elights_jobes-research/backend/core-api/src/utils/request_context.rs
+// Where a request came from, for the fraud rules' `device.*` features and the login history.
+use actix_web::{web, HttpRequest};
+use domain::fraud::DeviceContext;
+use domain::ip_intel::IpIntelligence;
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+
+pub const DEVICE_ID_HEADER: &str = "X-Device-Id"; // Stable identifier sent by our apps
+
+/// Our own reverse proxies (TRUSTED_PROXIES), registered as app data. `X-Forwarded-For` is only believed
+/// when the peer is one of them; without it every request is attributed to its peer address.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies(pub HashSet<IpAddr>);
+
+/// "1.2.3.4", "1.2.3.4:5678" or "[::1]:5678".
+fn parse_ip(addr: &str) -> Option<IpAddr> {
+    addr.parse::<IpAddr>().ok().or_else(|| addr.parse::<SocketAddr>().ok().map(|s| s.ip()))
+}
+
+/// The client behind `peer`: the peer itself, unless it is a trusted proxy, in which case `X-Forwarded-For`
+/// is walked from the nearest hop and the first address our proxies did not add is the client. Anything
+/// further left was written by the client and is ignored.
+pub fn client_ip(peer: Option<IpAddr>, forwarded_for: Option<&str>, trusted: &HashSet<IpAddr>) -> Option<IpAddr> {
+    let mut client = peer?;
+    if !trusted.contains(&client) {
+        return Some(client);
+    }
+    for hop in forwarded_for.unwrap_or_default().rsplit(',') {
+        match parse_ip(hop.trim()) {
+            Some(ip) => {
+                client = ip;
+                if !trusted.contains(&ip) {
+                    break;
+                }
+            }
+            None => break, // Garbled chain: stop at the last address a trusted proxy vouched for
+        }
+    }
+    Some(client)
+}
+
+/// Client IP (see `client_ip`), what the local IP intelligence knows about it, user agent and device id of a request.
+pub fn device_context(req: &HttpRequest, ip_intel: &IpIntelligence) -> DeviceContext {
+    let header = |name: &str| {
+        req.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| value.chars().take(255).collect::<String>())
+    };
+    let forwarded_for = req.headers().get("X-Forwarded-For").and_then(|value| value.to_str().ok());
+    let ip = match req.app_data::<web::Data<TrustedProxies>>() {
+        Some(trusted) => client_ip(req.peer_addr().map(|s| s.ip()), forwarded_for, &trusted.0),
+        None => req.peer_addr().map(|s| s.ip()),
+    };
+    DeviceContext {
+        ip,
+        user_agent: header("User-Agent"),
+        device_id: header(DEVICE_ID_HEADER),
+        network: ip.map(|ip| ip_intel.lookup(ip)),
+    }
+}
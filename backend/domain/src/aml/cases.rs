@@ -0,0 +1,285 @@
+// /home/inno/elights_jobes-research/backend/domain/src/aml/cases.rs
+// Investigation of AML cases. A case collects every alert on one customer until it is closed (nothing
+// suspicious) or a suspicious activity report is filed; later alerts open a new case. The SAR summary puts
+// together what a filing needs: the subject, the activity period, totals, the alerts and the transactions.
+use crate::error::DomainError;
+use crate::models::{AmlAlert, AmlCase, AmlCaseStatus, AuditOutcome, AuditTargetType, Transaction, User};
+use crate::security::audit;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use uuid::Uuid;
+
+/// A case with its alerts, newest first.
+#[derive(Debug, Clone, Serialize)]
+pub struct CaseWithAlerts {
+    pub case: AmlCase,
+    pub alerts: Vec<AmlAlert>,
+}
+
+/// Subject of a SAR.
+#[derive(Debug, Clone, Serialize)]
+pub struct SarSubject {
+    pub user_id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub kyc_tier: String,
+}
+
+/// Money in and out during the reported activity, per currency.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SarTotals {
+    #[serde(with = "rust_decimal::serde::str")]
+    pub total_in: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub total_out: Decimal,
+    pub transaction_count: usize,
+}
+
+/// A transaction in the reported activity, seen from the subject.
+#[derive(Debug, Clone, Serialize)]
+pub struct SarTransaction {
+    pub transaction_id: Uuid,
+    pub transaction_type: String,
+    pub direction: &'static str, // IN, OUT or INTERNAL
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+    pub currency: String,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What a SAR filing needs, assembled from a case.
+#[derive(Debug, Clone, Serialize)]
+pub struct SarSummary {
+    pub case_id: Uuid,
+    pub case_status: String,
+    pub risk_score: i32,
+    pub subject: SarSubject,
+    pub activity_start: DateTime<Utc>,
+    pub activity_end: DateTime<Utc>,
+    pub totals: BTreeMap<String, SarTotals>,
+    pub scenarios: Vec<String>,
+    pub alerts: Vec<AmlAlert>,
+    pub transactions: Vec<SarTransaction>,
+    pub narrative: String,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Cases by status (all when `None`), highest risk first.
+pub fn list_cases(conn: &mut PgConnection, status: Option<AmlCaseStatus>) -> Result<Vec<AmlCase>, DomainError> {
+    use crate::schema::aml_cases::dsl as ac;
+    let mut query = ac::aml_cases.into_boxed();
+    if let Some(status) = status {
+        query = query.filter(ac::status.eq(status.as_str()));
+    }
+    Ok(query.order((ac::risk_score.desc(), ac::created_at.asc())).limit(500).load(conn)?)
+}
+
+pub fn get_case(conn: &mut PgConnection, case_id: Uuid) -> Result<CaseWithAlerts, DomainError> {
+    use crate::schema::aml_alerts::dsl as aa;
+    use crate::schema::aml_cases::dsl as ac;
+    let case: AmlCase = ac::aml_cases
+        .find(case_id)
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("AML case {} not found", case_id)))?;
+    let alerts = aa::aml_alerts.filter(aa::case_id.eq(case_id)).order(aa::created_at.desc()).load(conn)?;
+    Ok(CaseWithAlerts { case, alerts })
+}
+
+/// Moves an active case to `new_status` and audits it.
+fn set_status(
+    conn: &mut PgConnection,
+    case_id: Uuid,
+    new_status: AmlCaseStatus,
+    officer_id: Uuid,
+    action: &str,
+    details: serde_json::Value,
+    update: impl FnOnce(&mut PgConnection, &AmlCase) -> Result<AmlCase, DomainError>,
+) -> Result<AmlCase, DomainError> {
+    use crate::schema::aml_cases::dsl as ac;
+    conn.transaction(|conn| {
+        let case: AmlCase = ac::aml_cases
+            .find(case_id)
+            .for_update()
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| DomainError::NotFound(format!("AML case {} not found", case_id)))?;
+        if !AmlCaseStatus::parse(&case.status).is_some_and(|s| s.is_active()) {
+            return Err(DomainError::Validation(format!("AML case {} is already {}", case_id, case.status)));
+        }
+        if case.user_id == officer_id {
+            return Err(DomainError::Authorization("An AML case cannot be handled by its subject".to_string()));
+        }
+        let case = update(conn, &case)?;
+        audit::log_db_audit_event(
+            conn, Some(officer_id), &officer_id.to_string(), action, Some(AuditTargetType::AmlCase),
+            Some(&case_id.to_string()), AuditOutcome::Success, Some(details), None,
+        )?;
+        log::info!("AML case {} {} by {}", case_id, new_status.as_str(), officer_id);
+        Ok(case)
+    })
+}
+
+/// Assigns a case to an investigator (the caller or another officer).
+pub fn assign_case(conn: &mut PgConnection, case_id: Uuid, officer_id: Uuid, assignee_id: Uuid) -> Result<AmlCase, DomainError> {
+    use crate::schema::aml_cases::dsl as ac;
+    set_status(conn, case_id, AmlCaseStatus::Investigating, officer_id, "AML_CASE_ASSIGNED", json!({"assigned_to": assignee_id}), |conn, _| {
+        Ok(diesel::update(ac::aml_cases.find(case_id))
+            .set((ac::status.eq(AmlCaseStatus::Investigating.as_str()), ac::assigned_to.eq(Some(assignee_id))))
+            .get_result(conn)?)
+    })
+}
+
+fn require_comment(comment: &str) -> Result<&str, DomainError> {
+    let comment = comment.trim();
+    if comment.is_empty() {
+        return Err(DomainError::Validation("A resolution comment is required".to_string()));
+    }
+    Ok(comment)
+}
+
+/// Closes a case without a filing.
+pub fn close_case(
+    conn: &mut PgConnection,
+    case_id: Uuid,
+    officer_id: Uuid,
+    comment: &str,
+    now: DateTime<Utc>,
+) -> Result<AmlCase, DomainError> {
+    use crate::schema::aml_cases::dsl as ac;
+    let comment = require_comment(comment)?;
+    set_status(conn, case_id, AmlCaseStatus::Closed, officer_id, "AML_CASE_CLOSED", json!({"comment": comment}), |conn, _| {
+        Ok(diesel::update(ac::aml_cases.find(case_id))
+            .set((
+                ac::status.eq(AmlCaseStatus::Closed.as_str()),
+                ac::resolution_comment.eq(Some(comment)),
+                ac::closed_by.eq(Some(officer_id)),
+                ac::closed_at.eq(Some(now)),
+            ))
+            .get_result(conn)?)
+    })
+}
+
+/// Records that a SAR was filed for a case, with the regulator's reference.
+pub fn file_sar(
+    conn: &mut PgConnection,
+    case_id: Uuid,
+    officer_id: Uuid,
+    sar_reference: &str,
+    comment: &str,
+    now: DateTime<Utc>,
+) -> Result<AmlCase, DomainError> {
+    use crate::schema::aml_cases::dsl as ac;
+    let comment = require_comment(comment)?;
+    let sar_reference = sar_reference.trim();
+    if sar_reference.is_empty() {
+        return Err(DomainError::Validation("A SAR reference is required".to_string()));
+    }
+    let details = json!({"sar_reference": sar_reference, "comment": comment});
+    set_status(conn, case_id, AmlCaseStatus::SarFiled, officer_id, "AML_SAR_FILED", details, |conn, _| {
+        Ok(diesel::update(ac::aml_cases.find(case_id))
+            .set((
+                ac::status.eq(AmlCaseStatus::SarFiled.as_str()),
+                ac::sar_reference.eq(Some(sar_reference)),
+                ac::resolution_comment.eq(Some(comment)),
+                ac::closed_by.eq(Some(officer_id)),
+                ac::closed_at.eq(Some(now)),
+            ))
+            .get_result(conn)?)
+    })
+}
+
+/// Assembles the SAR-ready summary of a case.
+pub fn sar_summary(conn: &mut PgConnection, case_id: Uuid, now: DateTime<Utc>) -> Result<SarSummary, DomainError> {
+    use crate::schema::transactions::dsl as t;
+    use crate::schema::users::dsl as u;
+    use crate::schema::wallets::dsl as w;
+
+    let CaseWithAlerts { case, mut alerts } = get_case(conn, case_id)?;
+    alerts.sort_by_key(|alert| alert.window_start);
+    let user: User = u::users.find(case.user_id).first(conn)?;
+    let wallet_ids: HashSet<Uuid> = w::wallets.filter(w::user_id.eq(case.user_id)).select(w::wallet_id).load::<Uuid>(conn)?.into_iter().collect();
+
+    let transaction_ids: BTreeSet<Uuid> = alerts
+        .iter()
+        .flat_map(|alert| serde_json::from_value::<Vec<Uuid>>(alert.transaction_ids.clone()).unwrap_or_default())
+        .collect();
+    let stored: Vec<Transaction> = t::transactions
+        .filter(t::transaction_id.eq_any(transaction_ids.into_iter().collect::<Vec<_>>()))
+        .order(t::created_at.asc())
+        .load(conn)?;
+
+    let mut totals: BTreeMap<String, SarTotals> = BTreeMap::new();
+    let transactions: Vec<SarTransaction> = stored
+        .into_iter()
+        .map(|tx| {
+            let debit = tx.debit_wallet_id.is_some_and(|id| wallet_ids.contains(&id));
+            let credit = tx.credit_wallet_id.is_some_and(|id| wallet_ids.contains(&id));
+            let direction = match (debit, credit) {
+                (true, true) => "INTERNAL",
+                (true, false) => "OUT",
+                _ => "IN",
+            };
+            let entry = totals.entry(tx.currency_code.clone()).or_default();
+            entry.transaction_count += 1;
+            match direction {
+                "IN" => entry.total_in += tx.amount,
+                "OUT" => entry.total_out += tx.amount,
+                _ => {}
+            }
+            SarTransaction {
+                transaction_id: tx.transaction_id,
+                transaction_type: tx.transaction_type,
+                direction,
+                amount: tx.amount,
+                currency: tx.currency_code,
+                status: tx.status,
+                created_at: tx.created_at,
+            }
+        })
+        .collect();
+
+    let activity_start = alerts.iter().map(|a| a.window_start).min().unwrap_or(case.created_at);
+    let activity_end = alerts.iter().map(|a| a.window_end).max().unwrap_or(case.created_at);
+    let scenarios: Vec<String> = alerts.iter().map(|a| a.scenario_id.clone()).collect::<BTreeSet<_>>().into_iter().collect();
+
+    let mut narrative = format!(
+        "Between {} and {}, customer {} ({}, KYC tier {}) was flagged by {} monitoring alert(s) ({}).",
+        activity_start.format("%Y-%m-%d"),
+        activity_end.format("%Y-%m-%d"),
+        user.username,
+        user.user_id,
+        user.kyc_tier,
+        alerts.len(),
+        scenarios.join(", ")
+    );
+    for alert in &alerts {
+        narrative.push_str(&format!(" {}: {}.", alert.scenario_id, alert.summary));
+    }
+    for (currency, total) in &totals {
+        narrative.push_str(&format!(
+            " {} transactions in {} moved {} in and {} out.",
+            total.transaction_count, currency, total.total_in, total.total_out
+        ));
+    }
+
+    Ok(SarSummary {
+        case_id: case.case_id,
+        case_status: case.status,
+        risk_score: case.risk_score,
+        subject: SarSubject { user_id: user.user_id, username: user.username, email: user.email, kyc_tier: user.kyc_tier },
+        activity_start,
+        activity_end,
+        totals,
+        scenarios,
+        alerts,
+        transactions,
+        narrative,
+        generated_at: now,
+    })
+}
@@ -0,0 +1,422 @@
+// /home/inno/elights_jobes-research/backend/domain/src/aml/engine.rs
+// Runs the monitoring scenarios over `transactions`, either for a period (batch) or for whatever changed since
+// the last run (streaming, positioned on (updated_at, transaction_id)). Both re-read each affected customer's
+// recent history, so a pattern completed by one new transaction is seen with the ones before it. Alerts
+// overlapping an earlier alert of the same scenario extend it instead of piling up; new alerts go to the
+// customer's active investigation case, opened on the first alert.
+use super::scenarios::{self, Activity, AlertCandidate, AmlConfig, Channel, Direction, ScenarioKind, UserActivity};
+use crate::error::DomainError;
+use crate::models::{
+    AmlAlert, AmlCase, AmlCaseStatus, AmlMonitorState, AuditOutcome, AuditTargetType, NewAmlAlert, NewAmlCase,
+    TransactionStatus, TransactionType,
+};
+use crate::payments::outbox::OutboxPayload;
+use crate::security::audit;
+use crate::utils::bigdecimal_to_decimal;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use uuid::Uuid;
+
+const ACTOR: &str = "AML_MONITORING";
+const STREAM_MONITOR_ID: &str = "STREAM";
+const STREAM_BATCH_SIZE: i64 = 500;
+const STREAM_LAG_SECS: i64 = 60; // Rows updated in the last minute may belong to transactions not committed yet
+const USERS_PER_CHUNK: usize = 200;
+
+/// What one monitoring run did.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct MonitoringSummary {
+    pub transactions_seen: usize,
+    pub users_evaluated: usize,
+    pub alerts_raised: usize,
+    pub alerts_updated: usize,
+    pub cases_opened: usize,
+}
+
+/// Statuses where money moved (or was committed to move).
+fn monitored_statuses() -> Vec<String> {
+    [
+        TransactionStatus::Processing,
+        TransactionStatus::Submitted,
+        TransactionStatus::Settled,
+        TransactionStatus::Completed,
+        TransactionStatus::Returned,
+        TransactionStatus::Chargeback,
+    ]
+    .iter()
+    .map(|status| status.to_string())
+    .collect()
+}
+
+/// Bookings that are not customer behaviour.
+fn is_monitored_type(kind: &TransactionType) -> bool {
+    !matches!(
+        kind,
+        TransactionType::Fee
+            | TransactionType::InterestCredit
+            | TransactionType::WithholdingTax
+            | TransactionType::CardAuthorization
+            | TransactionType::RtgsStatusUpdate
+            | TransactionType::Unknown
+    )
+}
+
+/// Stable key for the other party of a payment, from what was stored when it was made or received.
+fn counterparty(
+    kind: &TransactionType,
+    direction: Direction,
+    metadata: Option<&serde_json::Value>,
+    payload: Option<&OutboxPayload>,
+    other_wallet: Option<Uuid>,
+) -> Option<String> {
+    if matches!(kind, TransactionType::InternalTransfer) {
+        return other_wallet.map(|wallet_id| format!("WALLET:{}", wallet_id));
+    }
+    match direction {
+        Direction::Out => {
+            if let Some(beneficiary_id) =
+                metadata.and_then(|m| m.get(crate::beneficiaries::BENEFICIARY_KEY)).and_then(|v| v.as_str())
+            {
+                return Some(format!("BENEFICIARY:{}", beneficiary_id));
+            }
+            let payload = payload?;
+            if let Some(wire) = &payload.wire_details {
+                return Some(format!("WIRE:{}:{}", wire.swift_bic.to_uppercase(), wire.account_number));
+            }
+            if let Some(ach) = &payload.ach_details {
+                return Some(format!("ACH:{}:{}", ach.routing_number, ach.account_number));
+            }
+            payload.crypto_address.as_ref().map(|address| format!("CRYPTO:{}", address))
+        }
+        Direction::In => {
+            let inbound = metadata.and_then(|m| m.get("inbound"))?;
+            let field = |name: &str| inbound.get(name).and_then(|v| v.as_str()).filter(|v| !v.is_empty());
+            match (field("originator_bank"), field("originator_account"), field("originator_name")) {
+                (bank, Some(account), _) => Some(format!("ORIGINATOR:{}:{}", bank.unwrap_or(""), account)),
+                (_, None, Some(name)) => Some(format!("ORIGINATOR:{}", name.to_uppercase())),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Wallet owners for the given customers.
+fn wallets_of(conn: &mut PgConnection, user_ids: &[Uuid]) -> Result<HashMap<Uuid, Uuid>, DomainError> {
+    use crate::schema::wallets::dsl as w;
+    let rows: Vec<(Uuid, Uuid)> =
+        w::wallets.filter(w::user_id.eq_any(user_ids)).select((w::wallet_id, w::user_id)).load(conn)?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Customers' activity created in [from, to), per customer, oldest first.
+pub fn load_activity(
+    conn: &mut PgConnection,
+    user_ids: &[Uuid],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<HashMap<Uuid, Vec<Activity>>, DomainError> {
+    use crate::schema::payment_outbox::dsl as po;
+    use crate::schema::transactions::dsl as t;
+
+    let owners = wallets_of(conn, user_ids)?;
+    let wallet_ids: Vec<Uuid> = owners.keys().copied().collect();
+    type Row = (Uuid, Option<Uuid>, Option<Uuid>, String, BigDecimal, String, Option<serde_json::Value>, DateTime<Utc>);
+    let rows: Vec<Row> = t::transactions
+        .filter(t::debit_wallet_id.eq_any(wallet_ids.clone()).or(t::credit_wallet_id.eq_any(wallet_ids)))
+        .filter(t::created_at.ge(from).and(t::created_at.lt(to)))
+        .filter(t::status.eq_any(monitored_statuses()))
+        .order((t::created_at.asc(), t::transaction_id.asc()))
+        .select((t::transaction_id, t::debit_wallet_id, t::credit_wallet_id, t::transaction_type, t::amount, t::currency_code, t::metadata, t::created_at))
+        .load(conn)?;
+
+    let outbound_ids: Vec<Uuid> = rows.iter().filter(|row| row.1.is_some()).map(|row| row.0).collect();
+    let payloads: HashMap<Uuid, OutboxPayload> = po::payment_outbox
+        .filter(po::transaction_id.eq_any(outbound_ids))
+        .select((po::transaction_id, po::payload))
+        .load::<(Uuid, serde_json::Value)>(conn)?
+        .into_iter()
+        .filter_map(|(transaction_id, payload)| serde_json::from_value(payload).ok().map(|p| (transaction_id, p)))
+        .collect();
+
+    let mut activity: HashMap<Uuid, Vec<Activity>> = HashMap::new();
+    for (transaction_id, debit, credit, kind, amount, currency, metadata, created_at) in rows {
+        let kind = TransactionType::parse(&kind).unwrap_or(TransactionType::Unknown);
+        if !is_monitored_type(&kind) {
+            continue;
+        }
+        let external = !matches!(kind, TransactionType::InternalTransfer | TransactionType::Conversion);
+        let amount = bigdecimal_to_decimal(amount);
+        let sides = [(debit, Direction::Out, credit), (credit, Direction::In, debit)];
+        for (wallet, direction, other_wallet) in sides {
+            let Some(wallet_id) = wallet else { continue };
+            let Some(user_id) = owners.get(&wallet_id) else { continue };
+            activity.entry(*user_id).or_default().push(Activity {
+                transaction_id,
+                user_id: *user_id,
+                wallet_id,
+                transaction_type: kind.clone(),
+                direction,
+                channel: Channel::for_currency(&currency),
+                external,
+                amount,
+                currency: currency.clone(),
+                counterparty: counterparty(&kind, direction, metadata.as_ref(), payloads.get(&transaction_id), other_wallet),
+                occurred_at: created_at,
+            });
+        }
+    }
+    Ok(activity)
+}
+
+/// Latest monitored activity of a customer before `before`.
+fn previous_activity_at(
+    conn: &mut PgConnection,
+    wallet_ids: &[Uuid],
+    before: DateTime<Utc>,
+) -> Result<Option<DateTime<Utc>>, DomainError> {
+    use crate::schema::transactions::dsl as t;
+    Ok(t::transactions
+        .filter(t::debit_wallet_id.eq_any(wallet_ids.to_vec()).or(t::credit_wallet_id.eq_any(wallet_ids.to_vec())))
+        .filter(t::created_at.lt(before))
+        .filter(t::status.eq_any(monitored_statuses()))
+        .select(diesel::dsl::max(t::created_at))
+        .first(conn)?)
+}
+
+/// Runs the enabled scenarios over customers' activity in [from, to) plus the history the scenarios need.
+/// Only patterns ending in [from, to) are returned.
+pub fn evaluate_users(
+    conn: &mut PgConnection,
+    config: &AmlConfig,
+    user_ids: &[Uuid],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<AlertCandidate>, DomainError> {
+    let history_from = from - config.lookback();
+    let activity = load_activity(conn, user_ids, history_from, to)?;
+    let needs_dormancy = config.enabled().any(|s| matches!(s.kind, ScenarioKind::DormantReactivation { .. }));
+    let owners = if needs_dormancy { wallets_of(conn, user_ids)? } else { HashMap::new() };
+
+    let mut candidates = Vec::new();
+    for (user_id, activities) in &activity {
+        let previous_activity_at = if needs_dormancy {
+            let wallet_ids: Vec<Uuid> = owners.iter().filter(|(_, owner)| *owner == user_id).map(|(w, _)| *w).collect();
+            previous_activity_at(conn, &wallet_ids, history_from)?
+        } else {
+            None
+        };
+        let user = UserActivity { user_id: *user_id, previous_activity_at, activities };
+        for scenario in config.enabled() {
+            candidates.extend(scenarios::evaluate(scenario, &user).into_iter().filter(|c| c.window_end >= from));
+        }
+    }
+    Ok(candidates)
+}
+
+fn transaction_ids(alert: &AmlAlert) -> BTreeSet<Uuid> {
+    serde_json::from_value(alert.transaction_ids.clone()).unwrap_or_default()
+}
+
+/// The customer's active case, opened if there is none.
+fn active_case(conn: &mut PgConnection, user_id: Uuid, summary: &mut MonitoringSummary) -> Result<AmlCase, DomainError> {
+    use crate::schema::aml_cases::dsl as ac;
+    let active = [AmlCaseStatus::Open.as_str(), AmlCaseStatus::Investigating.as_str()];
+    let find = |conn: &mut PgConnection| -> Result<Option<AmlCase>, DomainError> {
+        Ok(ac::aml_cases.filter(ac::user_id.eq(user_id)).filter(ac::status.eq_any(active)).first(conn).optional()?)
+    };
+    if let Some(case) = find(conn)? {
+        return Ok(case);
+    }
+    // idx_aml_cases_active_user keeps a concurrent run from opening a second case
+    let inserted = diesel::insert_into(crate::schema::aml_cases::table)
+        .values(&NewAmlCase { user_id, status: AmlCaseStatus::Open.as_str() })
+        .on_conflict_do_nothing()
+        .get_result::<AmlCase>(conn)
+        .optional()?;
+    let Some(case) = inserted else {
+        return find(conn)?.ok_or_else(|| DomainError::Internal(format!("No active AML case for user {}", user_id)));
+    };
+    summary.cases_opened += 1;
+    audit::log_db_audit_event(
+        conn, Some(user_id), ACTOR, "AML_CASE_OPENED", Some(AuditTargetType::AmlCase), Some(&case.case_id.to_string()),
+        AuditOutcome::Success, None, None,
+    )?;
+    log::warn!("AML case {} opened for user {}", case.case_id, user_id);
+    Ok(case)
+}
+
+/// Stores candidates as alerts, merging those that overlap an alert already raised by the same scenario.
+pub fn record_alerts(
+    conn: &mut PgConnection,
+    config: &AmlConfig,
+    candidates: &[AlertCandidate],
+    summary: &mut MonitoringSummary,
+) -> Result<(), DomainError> {
+    use crate::schema::aml_alerts::dsl as aa;
+    use crate::schema::aml_cases::dsl as ac;
+
+    for candidate in candidates {
+        let ids: BTreeSet<Uuid> = candidate.transaction_ids.iter().copied().collect();
+        let recent: Vec<(AmlAlert, String)> = aa::aml_alerts
+            .inner_join(ac::aml_cases)
+            .filter(aa::user_id.eq(candidate.user_id))
+            .filter(aa::scenario_id.eq(&candidate.scenario_id))
+            .filter(aa::window_end.ge(candidate.window_start - config.lookback()))
+            .select((AmlAlert::as_select(), ac::status))
+            .load(conn)?;
+        let overlapping = recent.iter().find(|(alert, _)| !transaction_ids(alert).is_disjoint(&ids));
+
+        if let Some((alert, case_status)) = overlapping {
+            let known = transaction_ids(alert);
+            if ids.is_subset(&known) {
+                continue; // Already reported
+            }
+            if AmlCaseStatus::parse(case_status).is_some_and(|s| s.is_active()) {
+                let merged: Vec<Uuid> = known.union(&ids).copied().collect();
+                diesel::update(aa::aml_alerts.find(alert.alert_id))
+                    .set((
+                        aa::transaction_ids.eq(json!(merged)),
+                        aa::window_start.eq(alert.window_start.min(candidate.window_start)),
+                        aa::window_end.eq(alert.window_end.max(candidate.window_end)),
+                        aa::summary.eq(&candidate.summary),
+                        aa::facts.eq(&candidate.facts),
+                    ))
+                    .execute(conn)?;
+                summary.alerts_updated += 1;
+                continue;
+            }
+            // The earlier alert was investigated and closed: the pattern went on, raise it again
+        }
+
+        let case = active_case(conn, candidate.user_id, summary)?;
+        let alert: AmlAlert = diesel::insert_into(crate::schema::aml_alerts::table)
+            .values(&NewAmlAlert {
+                case_id: case.case_id,
+                user_id: candidate.user_id,
+                scenario_id: &candidate.scenario_id,
+                severity: candidate.severity.as_str(),
+                transaction_ids: json!(candidate.transaction_ids),
+                window_start: candidate.window_start,
+                window_end: candidate.window_end,
+                summary: &candidate.summary,
+                facts: candidate.facts.clone(),
+            })
+            .get_result(conn)?;
+        diesel::update(ac::aml_cases.find(case.case_id))
+            .set((ac::alert_count.eq(ac::alert_count + 1), ac::risk_score.eq(ac::risk_score + candidate.severity.weight())))
+            .execute(conn)?;
+        summary.alerts_raised += 1;
+        audit::log_db_audit_event(
+            conn, Some(candidate.user_id), ACTOR, "AML_ALERT_RAISED", Some(AuditTargetType::AmlCase),
+            Some(&case.case_id.to_string()), AuditOutcome::Success,
+            Some(json!({"alert_id": alert.alert_id, "scenario_id": alert.scenario_id, "severity": alert.severity,
+                "transactions": alert.transaction_ids})),
+            None,
+        )?;
+        log::warn!("AML alert {} ({}) on user {}: {}", alert.alert_id, alert.scenario_id, alert.user_id, alert.summary);
+    }
+    Ok(())
+}
+
+fn evaluate_and_record(
+    conn: &mut PgConnection,
+    config: &AmlConfig,
+    user_ids: &[Uuid],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    summary: &mut MonitoringSummary,
+) -> Result<(), DomainError> {
+    for chunk in user_ids.chunks(USERS_PER_CHUNK) {
+        let candidates = evaluate_users(conn, config, chunk, from, to)?;
+        conn.transaction(|conn| record_alerts(conn, config, &candidates, summary))?;
+        summary.users_evaluated += chunk.len();
+    }
+    Ok(())
+}
+
+/// Owners of the wallets on either side of the given transactions.
+fn owners_of(conn: &mut PgConnection, wallet_ids: HashSet<Uuid>) -> Result<Vec<Uuid>, DomainError> {
+    use crate::schema::wallets::dsl as w;
+    let wallet_ids: Vec<Uuid> = wallet_ids.into_iter().collect();
+    Ok(w::wallets.filter(w::wallet_id.eq_any(wallet_ids)).select(w::user_id).distinct().load(conn)?)
+}
+
+/// Monitors every customer active in [from, to).
+pub fn run_batch(
+    conn: &mut PgConnection,
+    config: &AmlConfig,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<MonitoringSummary, DomainError> {
+    use crate::schema::transactions::dsl as t;
+    if from >= to {
+        return Err(DomainError::Validation("Monitoring period must end after it starts".to_string()));
+    }
+    let wallets: Vec<(Option<Uuid>, Option<Uuid>)> = t::transactions
+        .filter(t::created_at.ge(from).and(t::created_at.lt(to)))
+        .filter(t::status.eq_any(monitored_statuses()))
+        .select((t::debit_wallet_id, t::credit_wallet_id))
+        .load(conn)?;
+    let mut summary = MonitoringSummary { transactions_seen: wallets.len(), ..Default::default() };
+    let user_ids = owners_of(conn, wallets.into_iter().flat_map(|(d, c)| [d, c]).flatten().collect())?;
+    evaluate_and_record(conn, config, &user_ids, from, to, &mut summary)?;
+    log::info!("AML batch {} - {}: {:?}", from, to, summary);
+    Ok(summary)
+}
+
+/// Monitors transactions created or changed since the previous call. The first call only records the
+/// starting position; earlier history is covered by `run_batch`.
+pub fn monitor_new_transactions(
+    conn: &mut PgConnection,
+    config: &AmlConfig,
+    now: DateTime<Utc>,
+) -> Result<MonitoringSummary, DomainError> {
+    use crate::schema::aml_monitor_state::dsl as ms;
+    use crate::schema::transactions::dsl as t;
+
+    let mut summary = MonitoringSummary::default();
+    let horizon = now - Duration::seconds(STREAM_LAG_SECS);
+    let state: Option<AmlMonitorState> = ms::aml_monitor_state.find(STREAM_MONITOR_ID).first(conn).optional()?;
+    let Some(state) = state else {
+        diesel::insert_into(ms::aml_monitor_state)
+            .values((ms::monitor_id.eq(STREAM_MONITOR_ID), ms::last_updated_at.eq(horizon), ms::last_transaction_id.eq(Uuid::nil())))
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+        log::info!("AML streaming monitor starts at {}", horizon);
+        return Ok(summary);
+    };
+
+    let (mut cursor_at, mut cursor_id) = (state.last_updated_at, state.last_transaction_id);
+    loop {
+        type Row = (Uuid, Option<Uuid>, Option<Uuid>, DateTime<Utc>, DateTime<Utc>);
+        let rows: Vec<Row> = t::transactions
+            .filter(t::updated_at.gt(cursor_at).or(t::updated_at.eq(cursor_at).and(t::transaction_id.gt(cursor_id))))
+            .filter(t::updated_at.le(horizon))
+            .order((t::updated_at.asc(), t::transaction_id.asc()))
+            .limit(STREAM_BATCH_SIZE)
+            .select((t::transaction_id, t::debit_wallet_id, t::credit_wallet_id, t::created_at, t::updated_at))
+            .load(conn)?;
+        let Some(last) = rows.last() else { break };
+        (cursor_at, cursor_id) = (last.4, last.0);
+
+        summary.transactions_seen += rows.len();
+        let earliest = rows.iter().map(|row| row.3).min().unwrap_or(horizon);
+        let user_ids = owners_of(conn, rows.iter().flat_map(|row| [row.1, row.2]).flatten().collect())?;
+        evaluate_and_record(conn, config, &user_ids, earliest, now, &mut summary)?;
+
+        diesel::update(ms::aml_monitor_state.find(STREAM_MONITOR_ID))
+            .set((ms::last_updated_at.eq(cursor_at), ms::last_transaction_id.eq(cursor_id)))
+            .execute(conn)?;
+        if (rows.len() as i64) < STREAM_BATCH_SIZE {
+            break;
+        }
+    }
+    if summary.transactions_seen > 0 {
+        log::info!("AML streaming monitor up to {}: {:?}", cursor_at, summary);
+    }
+    Ok(summary)
+}
@@ -0,0 +1,12 @@
+// /home/inno/elights_jobes-research/backend/domain/src/aml/mod.rs
+// AML transaction monitoring: configurable scenarios (structuring, rapid fiat/crypto movement, fan-in/fan-out,
+// dormant accounts) run over customers' transactions in batch or streaming mode; alerts are grouped into
+// investigation cases with SAR-ready summaries.
+
+pub mod scenarios; // Scenario configuration and pattern detection over one customer's activity
+pub mod engine; // Loading activity, batch and streaming runs, recording alerts into cases
+pub mod cases; // Investigation workflow and SAR summaries
+
+pub use cases::{assign_case, close_case, file_sar, get_case, list_cases, sar_summary, CaseWithAlerts, SarSummary};
+pub use engine::{evaluate_users, monitor_new_transactions, run_batch, MonitoringSummary};
+pub use scenarios::{Activity, AlertCandidate, AlertSeverity, AmlConfig, Scenario, ScenarioKind};
@@ -0,0 +1,534 @@
+// /home/inno/elights_jobes-research/backend/domain/src/aml/scenarios.rs
+// AML monitoring scenarios, loaded from a JSON file. Each scenario looks at one customer's activity (the
+// transactions on their wallets, oldest first) and reports the patterns it finds:
+// - STRUCTURING: several fiat amounts just under a reporting threshold within a few days,
+// - RAPID_FIAT_CRYPTO: fiat received and sent out as crypto (or the reverse) within hours,
+// - FAN_IN / FAN_OUT: many distinct counterparties paying in / being paid within a window,
+// - DORMANT_REACTIVATION: a large movement after a long period without any activity.
+// Amounts are compared in the transaction currency; no FX conversion is applied.
+use crate::config::{self, JsonConfig};
+use crate::error::DomainError;
+use crate::models::TransactionType;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, HashSet};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AlertSeverity {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl AlertSeverity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertSeverity::Low => "LOW",
+            AlertSeverity::Medium => "MEDIUM",
+            AlertSeverity::High => "HIGH",
+        }
+    }
+
+    /// What an alert adds to its case's risk score.
+    pub fn weight(&self) -> i32 {
+        match self {
+            AlertSeverity::Low => 10,
+            AlertSeverity::Medium => 25,
+            AlertSeverity::High => 50,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Direction {
+    In,
+    Out,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Channel {
+    Fiat,
+    Crypto,
+}
+
+impl Channel {
+    pub fn for_currency(currency: &str) -> Channel {
+        match currency.to_uppercase().as_str() {
+            "BTC" | "XMR" => Channel::Crypto,
+            _ => Channel::Fiat,
+        }
+    }
+}
+
+/// One side of a transaction, seen from the customer owning the wallet.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Activity {
+    pub transaction_id: Uuid,
+    pub user_id: Uuid,
+    pub wallet_id: Uuid,
+    pub transaction_type: TransactionType,
+    pub direction: Direction,
+    pub channel: Channel,
+    pub external: bool, // Enters or leaves the institution (not transfers between wallets, not conversions)
+    pub amount: Decimal,
+    pub currency: String,
+    pub counterparty: Option<String>, // Stable key of the other party, when known
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// A customer's activity over the evaluated window, oldest first.
+#[derive(Debug, Clone)]
+pub struct UserActivity<'a> {
+    pub user_id: Uuid,
+    pub previous_activity_at: Option<DateTime<Utc>>, // Latest activity before the window, for dormancy
+    pub activities: &'a [Activity],
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_margin_pct() -> Decimal {
+    Decimal::new(10, 0)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ScenarioKind {
+    Structuring {
+        threshold: Decimal, // Reporting threshold, e.g. 10000
+        #[serde(default = "default_margin_pct")]
+        margin_pct: Decimal, // Amounts within this % under the threshold count
+        min_count: u32,
+        window_days: i64,
+        #[serde(default)]
+        currencies: Vec<String>, // Empty = any fiat currency
+    },
+    RapidFiatCrypto {
+        window_hours: i64,
+        min_amount: Decimal, // Of the fiat leg
+    },
+    FanIn {
+        window_days: i64,
+        min_counterparties: u32,
+        #[serde(default)]
+        min_total: Decimal,
+    },
+    FanOut {
+        window_days: i64,
+        min_counterparties: u32,
+        #[serde(default)]
+        min_total: Decimal,
+    },
+    DormantReactivation {
+        dormant_days: i64,
+        min_amount: Decimal,
+    },
+}
+
+/// One configured scenario.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Scenario {
+    pub id: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub severity: AlertSeverity,
+    #[serde(flatten)]
+    pub kind: ScenarioKind,
+}
+
+impl Scenario {
+    /// How much history the scenario needs before the activity it reports on.
+    pub fn lookback(&self) -> Duration {
+        match &self.kind {
+            ScenarioKind::Structuring { window_days, .. }
+            | ScenarioKind::FanIn { window_days, .. }
+            | ScenarioKind::FanOut { window_days, .. } => Duration::days(*window_days),
+            ScenarioKind::RapidFiatCrypto { window_hours, .. } => Duration::hours(*window_hours),
+            ScenarioKind::DormantReactivation { .. } => Duration::zero(), // Uses previous_activity_at
+        }
+    }
+}
+
+/// Monitoring configuration.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AmlConfig {
+    #[serde(default)]
+    pub version: Option<String>, // Free text, logged when the configuration is loaded
+    #[serde(default)]
+    pub scenarios: Vec<Scenario>,
+}
+
+impl AmlConfig {
+    pub fn enabled(&self) -> impl Iterator<Item = &Scenario> {
+        self.scenarios.iter().filter(|scenario| scenario.enabled)
+    }
+
+    /// History needed before the reported period by the most demanding enabled scenario.
+    pub fn lookback(&self) -> Duration {
+        self.enabled().map(Scenario::lookback).max().unwrap_or_else(Duration::zero)
+    }
+}
+
+impl JsonConfig for AmlConfig {
+    const NAME: &'static str = "AML configuration";
+
+    /// Rejects scenarios that would never (or always) fire.
+    fn validate(&self) -> Result<(), DomainError> {
+        let mut ids = HashSet::new();
+        for scenario in &self.scenarios {
+            let invalid = |reason: &str| DomainError::Configuration(format!("AML scenario '{}': {}", scenario.id, reason));
+            if scenario.id.trim().is_empty() {
+                return Err(DomainError::Configuration("AML scenario with an empty id".to_string()));
+            }
+            if !ids.insert(scenario.id.as_str()) {
+                return Err(invalid("duplicate id"));
+            }
+            match &scenario.kind {
+                ScenarioKind::Structuring { threshold, margin_pct, min_count, window_days, .. } => {
+                    if *threshold <= Decimal::ZERO {
+                        return Err(invalid("threshold must be positive"));
+                    }
+                    if *margin_pct <= Decimal::ZERO || *margin_pct >= Decimal::ONE_HUNDRED {
+                        return Err(invalid("margin_pct must be between 0 and 100"));
+                    }
+                    if *min_count < 2 || *window_days <= 0 {
+                        return Err(invalid("needs min_count >= 2 and a positive window_days"));
+                    }
+                }
+                ScenarioKind::RapidFiatCrypto { window_hours, min_amount } => {
+                    if *window_hours <= 0 || *min_amount < Decimal::ZERO {
+                        return Err(invalid("needs a positive window_hours and a non-negative min_amount"));
+                    }
+                }
+                ScenarioKind::FanIn { window_days, min_counterparties, min_total }
+                | ScenarioKind::FanOut { window_days, min_counterparties, min_total } => {
+                    if *window_days <= 0 || *min_counterparties < 2 || *min_total < Decimal::ZERO {
+                        return Err(invalid("needs a positive window_days and min_counterparties >= 2"));
+                    }
+                }
+                ScenarioKind::DormantReactivation { dormant_days, min_amount } => {
+                    if *dormant_days <= 0 || *min_amount < Decimal::ZERO {
+                        return Err(invalid("needs a positive dormant_days and a non-negative min_amount"));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn summary(&self) -> String {
+        config::versioned_summary(self.version.as_deref(), self.scenarios.len(), "scenarios")
+    }
+}
+
+/// A pattern found in a customer's activity, before it is recorded.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AlertCandidate {
+    pub scenario_id: String,
+    pub severity: AlertSeverity,
+    pub user_id: Uuid,
+    pub transaction_ids: Vec<Uuid>,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub summary: String,
+    pub facts: serde_json::Value,
+}
+
+fn candidate(
+    scenario: &Scenario,
+    user_id: Uuid,
+    activities: &[&Activity],
+    summary: String,
+    facts: serde_json::Value,
+) -> AlertCandidate {
+    let mut transaction_ids: Vec<Uuid> = activities.iter().map(|a| a.transaction_id).collect();
+    transaction_ids.dedup();
+    AlertCandidate {
+        scenario_id: scenario.id.clone(),
+        severity: scenario.severity,
+        user_id,
+        transaction_ids,
+        window_start: activities.iter().map(|a| a.occurred_at).min().unwrap_or_default(),
+        window_end: activities.iter().map(|a| a.occurred_at).max().unwrap_or_default(),
+        summary,
+        facts,
+    }
+}
+
+fn total(activities: &[&Activity]) -> Decimal {
+    activities.iter().map(|a| a.amount).sum()
+}
+
+fn by_currency<'a>(activities: impl Iterator<Item = &'a Activity>) -> BTreeMap<String, Vec<&'a Activity>> {
+    let mut grouped: BTreeMap<String, Vec<&Activity>> = BTreeMap::new();
+    for activity in activities {
+        grouped.entry(activity.currency.to_uppercase()).or_default().push(activity);
+    }
+    grouped
+}
+
+/// Non-overlapping runs of `items` (oldest first) spanning at most `window` from their first item, for
+/// which `triggers` holds. Each run starts at the earliest item that triggers.
+fn runs<'a>(items: &[&'a Activity], window: Duration, triggers: impl Fn(&[&'a Activity]) -> bool) -> Vec<Vec<&'a Activity>> {
+    let mut found = Vec::new();
+    let mut start = 0;
+    while start < items.len() {
+        let mut end = start;
+        while end < items.len() && items[end].occurred_at - items[start].occurred_at <= window {
+            end += 1;
+        }
+        if triggers(&items[start..end]) {
+            found.push(items[start..end].to_vec());
+            start = end;
+        } else {
+            start += 1;
+        }
+    }
+    found
+}
+
+fn distinct_counterparties(items: &[&Activity]) -> usize {
+    items.iter().filter_map(|a| a.counterparty.as_deref()).collect::<HashSet<_>>().len()
+}
+
+/// Runs one scenario over one customer's activity.
+pub fn evaluate(scenario: &Scenario, user: &UserActivity) -> Vec<AlertCandidate> {
+    let activities = user.activities;
+    match &scenario.kind {
+        ScenarioKind::Structuring { threshold, margin_pct, min_count, window_days, currencies } => {
+            let floor = *threshold - *threshold * *margin_pct / Decimal::ONE_HUNDRED;
+            let near_threshold = activities.iter().filter(|a| {
+                a.channel == Channel::Fiat
+                    && a.external
+                    && a.amount >= floor
+                    && a.amount < *threshold
+                    && (currencies.is_empty() || currencies.iter().any(|c| c.eq_ignore_ascii_case(&a.currency)))
+            });
+            let mut alerts = Vec::new();
+            for (currency, items) in by_currency(near_threshold) {
+                for run in runs(&items, Duration::days(*window_days), |w| w.len() >= *min_count as usize) {
+                    let summary = format!(
+                        "{} {} transactions between {} and {} within {} days (total {} {})",
+                        run.len(), currency, floor, threshold, window_days, total(&run), currency
+                    );
+                    let facts = json!({"currency": currency, "count": run.len(), "total": total(&run).to_string(),
+                        "threshold": threshold.to_string(), "floor": floor.to_string(), "window_days": window_days});
+                    alerts.push(candidate(scenario, user.user_id, &run, summary, facts));
+                }
+            }
+            alerts
+        }
+        ScenarioKind::RapidFiatCrypto { window_hours, min_amount } => {
+            let window = Duration::hours(*window_hours);
+            let mut used: HashSet<Uuid> = HashSet::new();
+            let mut alerts = Vec::new();
+            for inbound in activities.iter().filter(|a| a.direction == Direction::In && a.external) {
+                let out_channel = match inbound.channel {
+                    Channel::Fiat if inbound.amount >= *min_amount => Channel::Crypto,
+                    Channel::Fiat => continue,
+                    Channel::Crypto => Channel::Fiat,
+                };
+                let outs: Vec<&Activity> = activities
+                    .iter()
+                    .filter(|a| a.direction == Direction::Out && a.external && a.channel == out_channel)
+                    .filter(|a| a.occurred_at >= inbound.occurred_at && a.occurred_at - inbound.occurred_at <= window)
+                    .filter(|a| !used.contains(&a.transaction_id))
+                    .collect();
+                if outs.is_empty() || (out_channel == Channel::Fiat && total(&outs) < *min_amount) {
+                    continue;
+                }
+                used.extend(outs.iter().map(|a| a.transaction_id));
+                let direction = if out_channel == Channel::Crypto { "FIAT_TO_CRYPTO" } else { "CRYPTO_TO_FIAT" };
+                let hours = (outs[0].occurred_at - inbound.occurred_at).num_minutes() as f64 / 60.0;
+                let summary = format!(
+                    "{} {} received, then {} outbound {} payment(s) within {:.1} hours",
+                    inbound.amount, inbound.currency, outs.len(), if out_channel == Channel::Crypto { "crypto" } else { "fiat" }, hours
+                );
+                let facts = json!({"direction": direction, "inbound_amount": inbound.amount.to_string(), "inbound_currency": inbound.currency,
+                    "outbound": outs.iter().map(|a| json!({"amount": a.amount.to_string(), "currency": a.currency})).collect::<Vec<_>>(),
+                    "hours_to_first_outbound": hours});
+                let mut legs = vec![inbound];
+                legs.extend(outs);
+                alerts.push(candidate(scenario, user.user_id, &legs, summary, facts));
+            }
+            alerts
+        }
+        ScenarioKind::FanIn { window_days, min_counterparties, min_total }
+        | ScenarioKind::FanOut { window_days, min_counterparties, min_total } => {
+            let direction = if matches!(scenario.kind, ScenarioKind::FanIn { .. }) { Direction::In } else { Direction::Out };
+            let flows = activities.iter().filter(|a| a.direction == direction && a.counterparty.is_some());
+            let mut alerts = Vec::new();
+            for (currency, items) in by_currency(flows) {
+                let triggers = |w: &[&Activity]| distinct_counterparties(w) >= *min_counterparties as usize && total(w) >= *min_total;
+                for run in runs(&items, Duration::days(*window_days), triggers) {
+                    let counterparties = distinct_counterparties(&run);
+                    let summary = format!(
+                        "{} {} {} distinct counterparties within {} days ({} transactions, total {} {})",
+                        if direction == Direction::In { "Received from" } else { "Paid" },
+                        counterparties, currency, window_days, run.len(), total(&run), currency
+                    );
+                    let facts = json!({"direction": direction, "currency": currency, "counterparties": counterparties,
+                        "count": run.len(), "total": total(&run).to_string(), "window_days": window_days});
+                    alerts.push(candidate(scenario, user.user_id, &run, summary, facts));
+                }
+            }
+            alerts
+        }
+        ScenarioKind::DormantReactivation { dormant_days, min_amount } => {
+            let mut alerts = Vec::new();
+            let mut last = user.previous_activity_at;
+            for activity in activities {
+                if let Some(previous) = last {
+                    let idle = activity.occurred_at - previous;
+                    if idle >= Duration::days(*dormant_days) && activity.amount >= *min_amount {
+                        let summary = format!(
+                            "{} {} moved after {} days without activity",
+                            activity.amount, activity.currency, idle.num_days()
+                        );
+                        let facts = json!({"idle_days": idle.num_days(), "last_activity_at": previous,
+                            "amount": activity.amount.to_string(), "currency": activity.currency, "direction": activity.direction});
+                        alerts.push(candidate(scenario, user.user_id, &[activity], summary, facts));
+                    }
+                }
+                last = Some(last.map_or(activity.occurred_at, |l| l.max(activity.occurred_at)));
+            }
+            alerts
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 3, day, hour, 0, 0).unwrap()
+    }
+
+    fn activity(
+        kind: TransactionType,
+        direction: Direction,
+        amount: Decimal,
+        currency: &str,
+        counterparty: Option<&str>,
+        occurred_at: DateTime<Utc>,
+    ) -> Activity {
+        Activity {
+            transaction_id: Uuid::new_v4(),
+            user_id: Uuid::nil(),
+            wallet_id: Uuid::nil(),
+            transaction_type: kind,
+            direction,
+            channel: Channel::for_currency(currency),
+            external: true,
+            amount,
+            currency: currency.to_string(),
+            counterparty: counterparty.map(str::to_string),
+            occurred_at,
+        }
+    }
+
+    fn config() -> AmlConfig {
+        config::parse_json::<AmlConfig>(
+            r#"{"version": "test", "scenarios": [
+                {"id": "structuring", "type": "STRUCTURING", "severity": "HIGH", "threshold": "10000", "min_count": 3, "window_days": 7},
+                {"id": "rapid", "type": "RAPID_FIAT_CRYPTO", "window_hours": 48, "min_amount": "5000"},
+                {"id": "fan-out", "type": "FAN_OUT", "window_days": 7, "min_counterparties": 4, "min_total": "1000"},
+                {"id": "dormant", "type": "DORMANT_REACTIVATION", "severity": "LOW", "dormant_days": 180, "min_amount": "5000"}
+            ]}"#,
+        )
+        .unwrap()
+    }
+
+    fn run(scenario_id: &str, activities: &[Activity], previous: Option<DateTime<Utc>>) -> Vec<AlertCandidate> {
+        let config = config();
+        let scenario = config.scenarios.iter().find(|s| s.id == scenario_id).unwrap();
+        evaluate(scenario, &UserActivity { user_id: Uuid::nil(), previous_activity_at: previous, activities })
+    }
+
+    #[test]
+    fn test_structuring_just_under_threshold() {
+        let deposits = vec![
+            activity(TransactionType::CheckDeposit, Direction::In, dec!(9500), "USD", None, at(1, 10)),
+            activity(TransactionType::WireInbound, Direction::In, dec!(9900), "USD", None, at(3, 10)),
+            activity(TransactionType::AchCredit, Direction::Out, dec!(10000), "USD", None, at(4, 10)), // At the threshold
+            activity(TransactionType::CheckDeposit, Direction::In, dec!(9800), "USD", None, at(6, 10)),
+        ];
+        let alerts = run("structuring", &deposits, None);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].transaction_ids.len(), 3);
+        assert_eq!(alerts[0].severity, AlertSeverity::High);
+        assert_eq!(alerts[0].facts["total"], "29200");
+        assert_eq!((alerts[0].window_start, alerts[0].window_end), (at(1, 10), at(6, 10)));
+
+        // Spread over more than the window: no pattern
+        let spread = vec![deposits[0].clone(), deposits[1].clone(), activity(TransactionType::CheckDeposit, Direction::In, dec!(9800), "USD", None, at(20, 10))];
+        assert!(run("structuring", &spread, None).is_empty());
+    }
+
+    #[test]
+    fn test_rapid_fiat_to_crypto_and_back() {
+        let flows = vec![
+            activity(TransactionType::WireInbound, Direction::In, dec!(20000), "USD", None, at(1, 9)),
+            activity(TransactionType::CryptoBtcSend, Direction::Out, dec!(0.3), "BTC", Some("CRYPTO:bc1q"), at(1, 15)),
+            activity(TransactionType::CryptoXmrReceive, Direction::In, dec!(50), "XMR", None, at(10, 9)),
+            activity(TransactionType::WireOutbound, Direction::Out, dec!(7000), "EUR", Some("WIRE:X"), at(11, 8)),
+        ];
+        let alerts = run("rapid", &flows, None);
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].facts["direction"], "FIAT_TO_CRYPTO");
+        assert_eq!(alerts[0].facts["hours_to_first_outbound"], 6.0);
+        assert_eq!(alerts[1].facts["direction"], "CRYPTO_TO_FIAT");
+
+        // Crypto sent long after the fiat arrived, or after a small deposit
+        let slow = vec![flows[0].clone(), activity(TransactionType::CryptoBtcSend, Direction::Out, dec!(0.3), "BTC", None, at(5, 9))];
+        assert!(run("rapid", &slow, None).is_empty());
+        let small = vec![activity(TransactionType::AchInbound, Direction::In, dec!(100), "USD", None, at(1, 9)), flows[1].clone()];
+        assert!(run("rapid", &small, None).is_empty());
+    }
+
+    #[test]
+    fn test_fan_out_needs_distinct_counterparties() {
+        let payees: Vec<Activity> = (0..5)
+            .map(|i| activity(TransactionType::AchCredit, Direction::Out, dec!(400), "USD", Some(&format!("ACH:{}", i)), at(1 + i, 12)))
+            .collect();
+        let alerts = run("fan-out", &payees, None);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].facts["counterparties"], 5);
+
+        let same_payee: Vec<Activity> = (0..5)
+            .map(|i| activity(TransactionType::AchCredit, Direction::Out, dec!(400), "USD", Some("ACH:1"), at(1 + i, 12)))
+            .collect();
+        assert!(run("fan-out", &same_payee, None).is_empty());
+    }
+
+    #[test]
+    fn test_dormant_account_reactivation() {
+        let payment = vec![activity(TransactionType::WireOutbound, Direction::Out, dec!(8000), "USD", None, at(20, 12))];
+        let long_ago = at(20, 12) - Duration::days(200);
+        let alerts = run("dormant", &payment, Some(long_ago));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].facts["idle_days"], 200);
+        assert!(run("dormant", &payment, Some(at(1, 12))).is_empty());
+        assert!(run("dormant", &payment, None).is_empty()); // First activity of a new customer
+    }
+
+    #[test]
+    fn test_invalid_scenarios_are_rejected() {
+        assert!(config::parse_json::<AmlConfig>(r#"{"scenarios": [{"id": "s", "type": "STRUCTURING", "threshold": "10000", "min_count": 1, "window_days": 7}]}"#).is_err());
+        assert!(config::parse_json::<AmlConfig>(r#"{"scenarios": [{"id": "f", "type": "FAN_IN", "window_days": 0, "min_counterparties": 5}]}"#).is_err());
+        assert!(config::parse_json::<AmlConfig>(r#"{"scenarios": [{"id": "x", "type": "UNKNOWN"}]}"#).is_err());
+        assert_eq!(config().lookback(), Duration::days(7));
+    }
+}
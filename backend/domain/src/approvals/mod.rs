@@ -0,0 +1,11 @@
+// /home/inno/elights_jobes-research/backend/domain/src/approvals/mod.rs
+// Maker-checker approvals: outbound payments matching a policy wait in RequiresAction until approved.
+
+pub mod policy; // Which payments need approval, by amount, rail, destination country, new beneficiary
+pub mod workflow; // Requesting, deciding on and expiring approvals
+
+pub use policy::{destination_country, ApprovalPolicy, ApprovalPolicySet, ApprovalRequirement, PaymentFacts};
+pub use workflow::{
+    approve_payment, expire_approvals, get_approval, list_pending_approvals, reject_payment,
+    request_approval, requirement_for_dispatch, Approver, ApprovalWithDecisions, PendingDispatch,
+};
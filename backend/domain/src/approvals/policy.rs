@@ -0,0 +1,231 @@
+// /home/inno/elights_jobes-research/backend/domain/src/approvals/policy.rs
+// Approval policies: which outbound payments need a second person's sign-off, loaded from a JSON file.
+// Every criterion set on a policy must match (unset = any). When several policies match, the strictest
+// terms apply: the highest approval count, the shortest window, and only roles allowed by all of them.
+use crate::config::{self, JsonConfig};
+use crate::error::DomainError;
+use crate::fees::FeeRail;
+use crate::models::{AchDetails, TransactionType, WireDetails};
+use chrono::Duration;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+fn default_required_approvals() -> u32 {
+    1
+}
+
+fn default_approver_roles() -> Vec<String> {
+    vec!["admin".to_string(), "finance".to_string()]
+}
+
+fn default_window_hours() -> i64 {
+    24
+}
+
+/// One approval rule.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ApprovalPolicy {
+    pub id: String,
+    #[serde(default)]
+    pub rails: Vec<FeeRail>, // Empty = any rail
+    #[serde(default)]
+    pub currency: Option<String>,
+    #[serde(default)]
+    pub min_amount: Option<Decimal>, // Inclusive, in `currency` (set both to compare like with like)
+    #[serde(default)]
+    pub destination_countries: Vec<String>, // ISO 3166 alpha-2 of the beneficiary bank
+    #[serde(default)]
+    pub new_beneficiary_only: bool, // Only when the customer never paid this beneficiary before
+    #[serde(default = "default_required_approvals")]
+    pub required_approvals: u32,
+    #[serde(default = "default_approver_roles")]
+    pub approver_roles: Vec<String>,
+    #[serde(default = "default_window_hours")]
+    pub window_hours: i64, // Approvals must be complete within this time, or the payment expires
+}
+
+/// What a policy is matched against.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaymentFacts<'a> {
+    pub rail: Option<FeeRail>,
+    pub amount: Decimal,
+    pub currency: &'a str,
+    pub destination_country: Option<String>,
+    pub new_beneficiary: bool,
+}
+
+/// Approvals a payment needs before it is released.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApprovalRequirement {
+    pub policy_ids: Vec<String>,
+    pub required_approvals: u32,
+    pub approver_roles: Vec<String>,
+    pub window: Duration,
+}
+
+impl ApprovalPolicy {
+    pub fn matches(&self, facts: &PaymentFacts) -> bool {
+        (self.rails.is_empty() || facts.rail.is_some_and(|rail| self.rails.contains(&rail)))
+            && self.currency.as_deref().map_or(true, |c| c.eq_ignore_ascii_case(facts.currency))
+            && self.min_amount.map_or(true, |min| facts.amount >= min)
+            && (self.destination_countries.is_empty()
+                || facts.destination_country.as_deref()
+                    .is_some_and(|country| self.destination_countries.iter().any(|c| c.eq_ignore_ascii_case(country))))
+            && (!self.new_beneficiary_only || facts.new_beneficiary)
+    }
+}
+
+/// The full set of approval policies in force. An empty set approves nothing (no payment waits).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct ApprovalPolicySet {
+    #[serde(default)]
+    pub version: Option<String>, // Free text, logged when the policies are loaded
+    #[serde(default)]
+    pub policies: Vec<ApprovalPolicy>,
+}
+
+impl ApprovalPolicySet {
+    /// Whether matching needs to know if the beneficiary is new (saves a lookup when no policy cares).
+    pub fn uses_new_beneficiary(&self) -> bool {
+        self.policies.iter().any(|p| p.new_beneficiary_only)
+    }
+
+    /// Combined requirement of every matching policy, or None when the payment can go straight through.
+    /// Fails when the matching policies share no approver role, since nobody could then approve.
+    pub fn evaluate(&self, facts: &PaymentFacts) -> Result<Option<ApprovalRequirement>, DomainError> {
+        let matched: Vec<&ApprovalPolicy> = self.policies.iter().filter(|p| p.matches(facts)).collect();
+        let Some(first) = matched.first() else {
+            return Ok(None);
+        };
+
+        let approver_roles: Vec<String> = first.approver_roles.iter()
+            .filter(|role| matched.iter().all(|p| p.approver_roles.contains(role)))
+            .cloned()
+            .collect();
+        let policy_ids: Vec<String> = matched.iter().map(|p| p.id.clone()).collect();
+        if approver_roles.is_empty() {
+            return Err(DomainError::Configuration(format!("Approval policies {:?} have no approver role in common", policy_ids)));
+        }
+        Ok(Some(ApprovalRequirement {
+            required_approvals: matched.iter().map(|p| p.required_approvals).max().unwrap_or(1),
+            window: Duration::hours(matched.iter().map(|p| p.window_hours).min().unwrap_or_else(default_window_hours)),
+            approver_roles,
+            policy_ids,
+        }))
+    }
+}
+
+impl JsonConfig for ApprovalPolicySet {
+    const NAME: &'static str = "approval policies";
+
+    /// Rejects policies that could never be satisfied or are ambiguous.
+    fn validate(&self) -> Result<(), DomainError> {
+        let mut ids = HashSet::new();
+        for policy in &self.policies {
+            let invalid = |reason: &str| DomainError::Configuration(format!("Approval policy '{}': {}", policy.id, reason));
+            if policy.id.trim().is_empty() {
+                return Err(DomainError::Configuration("Approval policy with an empty id".to_string()));
+            }
+            if !ids.insert(policy.id.as_str()) {
+                return Err(invalid("duplicate id"));
+            }
+            if policy.required_approvals == 0 {
+                return Err(invalid("required_approvals must be at least 1"));
+            }
+            if policy.approver_roles.is_empty() {
+                return Err(invalid("approver_roles cannot be empty"));
+            }
+            if policy.window_hours <= 0 {
+                return Err(invalid("window_hours must be positive"));
+            }
+            if policy.min_amount.is_some_and(|min| min < Decimal::ZERO) {
+                return Err(invalid("min_amount cannot be negative"));
+            }
+            if policy.destination_countries.iter().any(|c| c.len() != 2 || !c.chars().all(|ch| ch.is_ascii_alphabetic())) {
+                return Err(invalid("destination_countries must be ISO 3166 alpha-2 codes"));
+            }
+        }
+        Ok(())
+    }
+
+    fn summary(&self) -> String {
+        config::versioned_summary(self.version.as_deref(), self.policies.len(), "policies")
+    }
+}
+
+/// Country of the beneficiary's bank: from the BIC for wires, always US for ACH.
+pub fn destination_country(payment_type: &TransactionType, ach: Option<&AchDetails>, wire: Option<&WireDetails>) -> Option<String> {
+    match payment_type {
+        TransactionType::AchCredit | TransactionType::AchDebit => ach.map(|_| "US".to_string()),
+        TransactionType::WireOutbound => wire
+            .and_then(|details| details.swift_bic.get(4..6))
+            .map(str::to_uppercase),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn policies() -> ApprovalPolicySet {
+        config::parse_json::<ApprovalPolicySet>(r#"{
+            "version": "test",
+            "policies": [
+                { "id": "high-value", "min_amount": "50000", "currency": "USD" },
+                { "id": "wire-high-risk", "rails": ["WIRE"], "destination_countries": ["IR", "KP"],
+                  "required_approvals": 2, "approver_roles": ["admin"], "window_hours": 4 },
+                { "id": "new-beneficiary-wire", "rails": ["WIRE"], "new_beneficiary_only": true, "min_amount": "10000" }
+            ]
+        }"#).unwrap()
+    }
+
+    fn facts(rail: FeeRail, amount: Decimal, country: &str, new_beneficiary: bool) -> PaymentFacts<'static> {
+        PaymentFacts { rail: Some(rail), amount, currency: "USD", destination_country: Some(country.to_string()), new_beneficiary }
+    }
+
+    #[test]
+    fn test_small_payments_need_no_approval() {
+        assert_eq!(policies().evaluate(&facts(FeeRail::Ach, dec!(100), "US", true)).unwrap(), None);
+        assert_eq!(policies().evaluate(&facts(FeeRail::Wire, dec!(9999.99), "DE", true)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_single_policy_defaults() {
+        let requirement = policies().evaluate(&facts(FeeRail::Ach, dec!(50000), "US", false)).unwrap().unwrap();
+        assert_eq!(requirement.policy_ids, vec!["high-value"]);
+        assert_eq!(requirement.required_approvals, 1);
+        assert_eq!(requirement.approver_roles, vec!["admin", "finance"]);
+        assert_eq!(requirement.window, Duration::hours(24));
+    }
+
+    #[test]
+    fn test_strictest_terms_win_when_policies_overlap() {
+        let requirement = policies().evaluate(&facts(FeeRail::Wire, dec!(60000), "ir", true)).unwrap().unwrap();
+        assert_eq!(requirement.policy_ids, vec!["high-value", "wire-high-risk", "new-beneficiary-wire"]);
+        assert_eq!(requirement.required_approvals, 2);
+        assert_eq!(requirement.approver_roles, vec!["admin"]);
+        assert_eq!(requirement.window, Duration::hours(4));
+    }
+
+    #[test]
+    fn test_invalid_policies_are_rejected() {
+        for json in [
+            r#"{"policies": [{"id": "a"}, {"id": "a"}]}"#,
+            r#"{"policies": [{"id": "a", "required_approvals": 0}]}"#,
+            r#"{"policies": [{"id": "a", "approver_roles": []}]}"#,
+            r#"{"policies": [{"id": "a", "destination_countries": ["USA"]}]}"#,
+        ] {
+            assert!(config::parse_json::<ApprovalPolicySet>(json).is_err(), "{} should be rejected", json);
+        }
+    }
+
+    #[test]
+    fn test_destination_country_from_bic() {
+        let wire = WireDetails { swift_bic: "DEUTDEFF".to_string(), ..Default::default() };
+        assert_eq!(destination_country(&TransactionType::WireOutbound, None, Some(&wire)).as_deref(), Some("DE"));
+        assert_eq!(destination_country(&TransactionType::AchCredit, Some(&AchDetails::default()), None).as_deref(), Some("US"));
+    }
+}
@@ -0,0 +1,378 @@
+// /home/inno/elights_jobes-research/backend/domain/src/approvals/workflow.rs
+// Maker-checker workflow. `request_approval` parks a new outbound payment in RequiresAction: nothing is
+// held, charged or queued yet, and what is needed to release it is kept on the approval row. Approvers
+// (right role, never the initiator, one decision each) approve or reject it; the final approval holds the
+// funds, charges the fee and queues the payment on the outbox in the same DB transaction. Payments still
+// pending when their window lapses are expired by `expire_approvals`.
+use super::policy::{destination_country, ApprovalPolicySet, ApprovalRequirement, PaymentFacts};
+use crate::beneficiaries;
+use crate::error::DomainError;
+use crate::fees::{self, FeeQuote, FeeRail};
+use crate::ledger;
+use crate::models::{
+    ApprovalDecision, ApprovalStatus, AuditOutcome, AuditTargetType, NewPaymentApproval,
+    NewPaymentApprovalDecision, OutboxOperation, PaymentApproval, PaymentApprovalDecision, Transaction,
+    TransactionStatus, TransactionType,
+};
+use crate::payments::outbox::{self, OutboxPayload};
+use crate::payments::state_machine::{self, TransitionUpdate};
+use crate::security::audit;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+const ACTOR: &str = "APPROVAL_WORKFLOW";
+
+/// What the payment processor would have done without the approval step, replayed on release.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PendingDispatch {
+    pub operation: OutboxOperation,
+    pub payload: OutboxPayload,
+    pub fee_quote: Option<FeeQuote>,
+    pub idempotency_key: Option<String>, // Client Idempotency-Key, reused for the rail dispatch key
+}
+
+/// The person deciding, as authenticated by the API.
+#[derive(Debug, Clone)]
+pub struct Approver<'a> {
+    pub user_id: Uuid,
+    pub role: &'a str,
+}
+
+/// An approval with the decisions recorded so far.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApprovalWithDecisions {
+    pub approval: PaymentApproval,
+    pub decisions: Vec<PaymentApprovalDecision>,
+}
+
+fn roles_of(approval: &PaymentApproval) -> Vec<String> {
+    serde_json::from_value(approval.approver_roles.clone()).unwrap_or_default()
+}
+
+/// Whether `approver` may decide on `approval` (right role, not the initiator). Says nothing about
+/// whether they already did.
+pub fn can_decide(approval: &PaymentApproval, approver: &Approver) -> bool {
+    approval.initiator_user_id != approver.user_id && roles_of(approval).iter().any(|role| role == approver.role)
+}
+
+/// Parks a Pending outbound payment until it is approved. Call inside the DB transaction that created it.
+pub fn request_approval(
+    conn: &mut PgConnection,
+    transaction: &Transaction,
+    initiator_user_id: Uuid,
+    requirement: &ApprovalRequirement,
+    dispatch: &PendingDispatch,
+    now: DateTime<Utc>,
+) -> Result<Transaction, DomainError> {
+    let expires_at = now + requirement.window;
+    let pending_dispatch = serde_json::to_value(dispatch)
+        .map_err(|e| DomainError::Internal(format!("Failed to serialize pending dispatch: {}", e)))?;
+    let approval: PaymentApproval = diesel::insert_into(crate::schema::payment_approvals::table)
+        .values(&NewPaymentApproval {
+            transaction_id: transaction.transaction_id,
+            initiator_user_id,
+            status: ApprovalStatus::Pending.as_str(),
+            required_approvals: requirement.required_approvals as i32,
+            approver_roles: json!(requirement.approver_roles),
+            policy_ids: json!(requirement.policy_ids),
+            pending_dispatch,
+            expires_at,
+        })
+        .get_result(conn)?;
+
+    let update = TransitionUpdate {
+        metadata: Some(json!({"approval": {"approval_id": approval.approval_id, "policies": requirement.policy_ids,
+            "required_approvals": requirement.required_approvals, "expires_at": expires_at}})),
+        ..Default::default()
+    };
+    let transaction = state_machine::apply_transition(conn, transaction, TransactionStatus::RequiresAction, update, ACTOR)?;
+
+    audit::log_db_audit_event(
+        conn,
+        Some(initiator_user_id),
+        &initiator_user_id.to_string(),
+        "REQUEST_PAYMENT_APPROVAL",
+        Some(AuditTargetType::Transaction),
+        Some(&transaction.transaction_id.to_string()),
+        AuditOutcome::Success,
+        Some(json!({"approval_id": approval.approval_id, "policies": requirement.policy_ids, "required_approvals": requirement.required_approvals,
+            "approver_roles": requirement.approver_roles, "expires_at": expires_at, "amount": transaction.amount.to_string(), "currency": transaction.currency_code})),
+        None,
+    )?;
+    log::info!("Payment {} awaits {} approval(s) ({:?}) until {}",
+        transaction.transaction_id, requirement.required_approvals, requirement.policy_ids, expires_at);
+    Ok(transaction)
+}
+
+/// Records an approval. The last required approval releases the payment (hold, fee, outbox); if the
+/// wallet can no longer cover it, the decision is not recorded and the error is returned.
+pub fn approve_payment(
+    conn: &mut PgConnection,
+    transaction_id: Uuid,
+    approver: &Approver,
+    comment: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<ApprovalWithDecisions, DomainError> {
+    decide(conn, transaction_id, approver, ApprovalDecision::Approve, comment, now)
+}
+
+/// Records a rejection; the payment is cancelled without anything having been held or charged.
+pub fn reject_payment(
+    conn: &mut PgConnection,
+    transaction_id: Uuid,
+    approver: &Approver,
+    comment: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<ApprovalWithDecisions, DomainError> {
+    decide(conn, transaction_id, approver, ApprovalDecision::Reject, comment, now)
+}
+
+fn decide(
+    conn: &mut PgConnection,
+    transaction_id: Uuid,
+    approver: &Approver,
+    decision: ApprovalDecision,
+    comment: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<ApprovalWithDecisions, DomainError> {
+    use crate::schema::payment_approval_decisions::dsl as d;
+    use crate::schema::payment_approvals::dsl as pa;
+    use crate::schema::transactions::dsl as t;
+    conn.transaction(|conn| {
+        let approval: PaymentApproval = pa::payment_approvals
+            .filter(pa::transaction_id.eq(transaction_id))
+            .for_update()
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| DomainError::NotFound(format!("No approval pending for payment {}", transaction_id)))?;
+
+        if approval.initiator_user_id == approver.user_id {
+            return Err(DomainError::Authorization("The initiator of a payment cannot approve or reject it".to_string()));
+        }
+        if !can_decide(&approval, approver) {
+            return Err(DomainError::Authorization(format!("Role '{}' cannot decide on payment {}", approver.role, transaction_id)));
+        }
+        if approval.status != ApprovalStatus::Pending.as_str() {
+            return Err(DomainError::Validation(format!("Approval for payment {} is already {}", transaction_id, approval.status)));
+        }
+        if now >= approval.expires_at {
+            return Err(DomainError::Validation(format!("Approval window for payment {} expired at {}", transaction_id, approval.expires_at)));
+        }
+        let already_decided = diesel::select(diesel::dsl::exists(
+            d::payment_approval_decisions
+                .filter(d::approval_id.eq(approval.approval_id))
+                .filter(d::approver_user_id.eq(approver.user_id)),
+        ))
+        .get_result::<bool>(conn)?;
+        if already_decided {
+            return Err(DomainError::Validation(format!("You already decided on payment {}", transaction_id)));
+        }
+
+        diesel::insert_into(crate::schema::payment_approval_decisions::table)
+            .values(&NewPaymentApprovalDecision {
+                approval_id: approval.approval_id,
+                approver_user_id: approver.user_id,
+                approver_role: approver.role,
+                decision: decision.as_str(),
+                comment,
+            })
+            .execute(conn)?;
+
+        let transaction: Transaction = t::transactions.find(transaction_id).for_update().first(conn)?;
+        let approval_count = approval.approval_count + i32::from(decision == ApprovalDecision::Approve);
+        let status = match decision {
+            ApprovalDecision::Reject => {
+                let update = TransitionUpdate {
+                    metadata: Some(json!({"approval": {"status": ApprovalStatus::Rejected.as_str(), "rejected_by": approver.user_id, "comment": comment}})),
+                    ..Default::default()
+                };
+                state_machine::apply_transition(conn, &transaction, TransactionStatus::Cancelled, update, ACTOR)?;
+                ApprovalStatus::Rejected
+            }
+            ApprovalDecision::Approve if approval_count >= approval.required_approvals => {
+                release_payment(conn, &approval, &transaction)?;
+                ApprovalStatus::Approved
+            }
+            ApprovalDecision::Approve => ApprovalStatus::Pending,
+        };
+
+        let approval: PaymentApproval = diesel::update(pa::payment_approvals.find(approval.approval_id))
+            .set((
+                pa::approval_count.eq(approval_count),
+                pa::status.eq(status.as_str()),
+                pa::decided_at.eq((status != ApprovalStatus::Pending).then_some(now)),
+            ))
+            .get_result(conn)?;
+
+        let action = match decision {
+            ApprovalDecision::Approve => "APPROVE_PAYMENT",
+            ApprovalDecision::Reject => "REJECT_PAYMENT",
+        };
+        audit::log_db_audit_event(
+            conn,
+            Some(approver.user_id),
+            &approver.user_id.to_string(),
+            action,
+            Some(AuditTargetType::Transaction),
+            Some(&transaction_id.to_string()),
+            AuditOutcome::Success,
+            Some(json!({"approval_id": approval.approval_id, "role": approver.role, "comment": comment,
+                "approval_count": approval.approval_count, "required_approvals": approval.required_approvals, "status": approval.status})),
+            None,
+        )?;
+        log::info!("Payment {} {} by {} ({}/{} approvals, now {})",
+            transaction_id, decision.as_str(), approver.user_id, approval.approval_count, approval.required_approvals, approval.status);
+
+        let decisions = decisions_for(conn, approval.approval_id)?;
+        Ok(ApprovalWithDecisions { approval, decisions })
+    })
+}
+
+/// Does what the payment processor deferred: funds check, hold, fee, outbox entry.
+fn release_payment(conn: &mut PgConnection, approval: &PaymentApproval, transaction: &Transaction) -> Result<(), DomainError> {
+    let dispatch: PendingDispatch = serde_json::from_value(approval.pending_dispatch.clone())
+        .map_err(|e| DomainError::Internal(format!("Approval {} has an invalid pending dispatch: {}", approval.approval_id, e)))?;
+    let metadata = json!({"approval": {"status": ApprovalStatus::Approved.as_str(), "approved_at": Utc::now()}});
+    dispatch_held_payment(conn, transaction, &dispatch, metadata, ACTOR)?;
+    Ok(())
+}
+
+/// Sends a payment parked in RequiresAction on its way: checks the funds again (the balance may have
+/// moved while it waited), holds them, charges the fee and queues `dispatch` on the outbox.
+pub(crate) fn dispatch_held_payment(
+    conn: &mut PgConnection,
+    transaction: &Transaction,
+    dispatch: &PendingDispatch,
+    metadata: serde_json::Value,
+    actor: &str,
+) -> Result<Transaction, DomainError> {
+    let source_wallet_id = transaction.debit_wallet_id
+        .ok_or_else(|| DomainError::Internal(format!("Payment {} has no source wallet", transaction.transaction_id)))?;
+
+    let total_debit = dispatch.fee_quote.as_ref().map(|q| q.total_debit).unwrap_or(transaction.amount);
+    ledger::ensure_available(conn, source_wallet_id, total_debit)?;
+
+    let update = TransitionUpdate { metadata: Some(metadata), ..Default::default() };
+    let transaction = state_machine::apply_transition(conn, transaction, TransactionStatus::Processing, update, actor)?;
+    if let Some(quote) = &dispatch.fee_quote {
+        fees::post_fee(conn, &transaction, quote, actor)?;
+    }
+    outbox::enqueue_outbound(conn, &transaction, dispatch.operation, &dispatch.payload, dispatch.idempotency_key.as_deref())?;
+    log::info!("Released payment {} ({} {})", transaction.transaction_id, transaction.amount, transaction.currency_code);
+    Ok(transaction)
+}
+
+/// The approval a parked payment needs under `policies`, judged on what it will dispatch. Used when
+/// another hold (sanctions review) is lifted before approvals were evaluated.
+pub fn requirement_for_dispatch(
+    conn: &mut PgConnection,
+    policies: &ApprovalPolicySet,
+    transaction: &Transaction,
+    dispatch: &PendingDispatch,
+    now: DateTime<Utc>,
+) -> Result<Option<ApprovalRequirement>, DomainError> {
+    let payment_type = TransactionType::parse(&transaction.transaction_type).ok_or_else(|| {
+        DomainError::Internal(format!("Unknown transaction type '{}' on {}", transaction.transaction_type, transaction.transaction_id))
+    })?;
+    let ach = dispatch.payload.ach_details.as_ref();
+    let wire = dispatch.payload.wire_details.as_ref();
+    let new_beneficiary = policies.uses_new_beneficiary() && beneficiaries::is_new_beneficiary(conn, transaction, now)?;
+    let facts = PaymentFacts {
+        rail: FeeRail::for_transaction_type(&payment_type),
+        amount: dispatch.fee_quote.as_ref().map(|q| q.amount).unwrap_or(transaction.amount), // As requested
+        currency: &transaction.currency_code,
+        destination_country: destination_country(&payment_type, ach, wire),
+        new_beneficiary,
+    };
+    policies.evaluate(&facts)
+}
+
+fn decisions_for(conn: &mut PgConnection, approval_id: Uuid) -> Result<Vec<PaymentApprovalDecision>, DomainError> {
+    use crate::schema::payment_approval_decisions::dsl as d;
+    let decisions = d::payment_approval_decisions
+        .filter(d::approval_id.eq(approval_id))
+        .order(d::created_at.asc())
+        .load(conn)?;
+    Ok(decisions)
+}
+
+/// Approval state of a payment, visible to its initiator and to the users who may decide on it.
+pub fn get_approval(
+    conn: &mut PgConnection,
+    transaction_id: Uuid,
+    viewer: &Approver,
+) -> Result<ApprovalWithDecisions, DomainError> {
+    use crate::schema::payment_approvals::dsl as pa;
+    let approval: PaymentApproval = pa::payment_approvals
+        .filter(pa::transaction_id.eq(transaction_id))
+        .first(conn)
+        .optional()?
+        .filter(|approval| approval.initiator_user_id == viewer.user_id || can_decide(approval, viewer))
+        .ok_or_else(|| DomainError::NotFound(format!("No approval found for payment {}", transaction_id)))?;
+    let decisions = decisions_for(conn, approval.approval_id)?;
+    Ok(ApprovalWithDecisions { approval, decisions })
+}
+
+/// Pending approvals `approver` can still act on, oldest deadline first.
+pub fn list_pending_approvals(
+    conn: &mut PgConnection,
+    approver: &Approver,
+    now: DateTime<Utc>,
+) -> Result<Vec<PaymentApproval>, DomainError> {
+    use crate::schema::payment_approval_decisions::dsl as d;
+    use crate::schema::payment_approvals::dsl as pa;
+    let decided = d::payment_approval_decisions
+        .filter(d::approver_user_id.eq(approver.user_id))
+        .select(d::approval_id);
+    let approvals: Vec<PaymentApproval> = pa::payment_approvals
+        .filter(pa::status.eq(ApprovalStatus::Pending.as_str()))
+        .filter(pa::expires_at.gt(now))
+        .filter(pa::initiator_user_id.ne(approver.user_id))
+        .filter(diesel::dsl::not(pa::approval_id.eq_any(decided)))
+        .order(pa::expires_at.asc())
+        .load(conn)?;
+    Ok(approvals.into_iter().filter(|approval| can_decide(approval, approver)).collect())
+}
+
+/// Expires pending approvals whose window lapsed; their payments move to Expired. Returns how many.
+pub fn expire_approvals(conn: &mut PgConnection, now: DateTime<Utc>) -> Result<usize, DomainError> {
+    use crate::schema::payment_approvals::dsl as pa;
+    use crate::schema::transactions::dsl as t;
+    conn.transaction(|conn| {
+        let lapsed: Vec<PaymentApproval> = pa::payment_approvals
+            .filter(pa::status.eq(ApprovalStatus::Pending.as_str()))
+            .filter(pa::expires_at.le(now))
+            .for_update()
+            .skip_locked()
+            .load(conn)?;
+        for approval in &lapsed {
+            diesel::update(pa::payment_approvals.find(approval.approval_id))
+                .set((pa::status.eq(ApprovalStatus::Expired.as_str()), pa::decided_at.eq(Some(now))))
+                .execute(conn)?;
+            let transaction: Transaction = t::transactions.find(approval.transaction_id).for_update().first(conn)?;
+            let update = TransitionUpdate {
+                metadata: Some(json!({"approval": {"status": ApprovalStatus::Expired.as_str(), "expired_at": now,
+                    "approval_count": approval.approval_count, "required_approvals": approval.required_approvals}})),
+                ..Default::default()
+            };
+            state_machine::apply_transition(conn, &transaction, TransactionStatus::Expired, update, ACTOR)?;
+            audit::log_db_audit_event(
+                conn,
+                Some(approval.initiator_user_id),
+                ACTOR,
+                "EXPIRE_PAYMENT_APPROVAL",
+                Some(AuditTargetType::Transaction),
+                Some(&approval.transaction_id.to_string()),
+                AuditOutcome::Failure,
+                Some(json!({"approval_id": approval.approval_id, "approval_count": approval.approval_count,
+                    "required_approvals": approval.required_approvals, "expires_at": approval.expires_at})),
+                Some("Approval window expired"),
+            )?;
+            log::warn!("Payment {} expired awaiting approval ({}/{})", approval.transaction_id, approval.approval_count, approval.required_approvals);
+        }
+        Ok(lapsed.len())
+    })
+}
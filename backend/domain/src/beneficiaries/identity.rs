@@ -0,0 +1,272 @@
+// /home/inno/elights_jobes-research/backend/domain/src/beneficiaries/identity.rs
+// How a payee is identified: payment details are normalized (spacing, case) and hashed, so a saved
+// beneficiary and a later payment to the same account produce the same hash. Only the hash and a masked
+// form are stored. Also holds the cooling-off limits applied to freshly verified beneficiaries.
+use crate::error::DomainError;
+use crate::models::{AchDetails, BeneficiaryType, TransactionType, WireDetails};
+use crate::payments::validator::{self, ValidationContext};
+use crate::security::hashing;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Payment details of a payee, as entered when saving it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BeneficiaryDetails {
+    Ach { routing_number: String, account_number: String },
+    Iban {
+        iban: String,
+        #[serde(default)]
+        bic: Option<String>,
+    },
+    Wire { swift_bic: String, account_number: String }, // Non-IBAN accounts
+    Crypto { network: String, address: String }, // BTC or XMR
+}
+
+/// Normalized, validated identity of a payee.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BeneficiaryIdentity {
+    pub beneficiary_type: BeneficiaryType,
+    pub identifier: String, // Hashed, never stored
+    pub masked_identifier: String,
+    pub bank_identifier: Option<String>,
+    pub crypto_network: Option<String>,
+    pub country_code: Option<String>,
+}
+
+fn compact(value: &str) -> String {
+    value.chars().filter(|c| !c.is_whitespace() && *c != '-').collect()
+}
+
+/// Last four characters, the rest starred out.
+pub fn mask(identifier: &str) -> String {
+    let chars: Vec<char> = identifier.chars().collect();
+    let visible = chars.len().min(4);
+    let tail: String = chars[chars.len() - visible..].iter().collect();
+    format!("****{}", tail)
+}
+
+/// ISO 13616 check: length, country prefix and the mod-97 checksum.
+pub fn is_valid_iban(iban: &str) -> bool {
+    let iban = compact(iban).to_uppercase();
+    if !(15..=34).contains(&iban.len())
+        || !iban.chars().all(|c| c.is_ascii_alphanumeric())
+        || !iban[..2].chars().all(|c| c.is_ascii_alphabetic())
+        || !iban[2..4].chars().all(|c| c.is_ascii_digit())
+    {
+        return false;
+    }
+    let rearranged = format!("{}{}", &iban[4..], &iban[..4]);
+    let remainder = rearranged.chars().fold(0u32, |acc, c| {
+        let value = c.to_digit(36).unwrap_or(0); // 0-9 stay, A=10 .. Z=35
+        if value >= 10 { (acc * 100 + value) % 97 } else { (acc * 10 + value) % 97 }
+    });
+    remainder == 1
+}
+
+impl BeneficiaryIdentity {
+    /// Validates and normalizes `details`.
+    pub fn from_details(details: &BeneficiaryDetails) -> Result<Self, DomainError> {
+        match details {
+            BeneficiaryDetails::Ach { routing_number, account_number } => {
+                let routing_number = compact(routing_number);
+                let account_number = compact(account_number);
+                let ach = AchDetails { routing_number: routing_number.clone(), account_number: account_number.clone(), ..Default::default() };
+                validator::validate_ach_details(&ach, &ValidationContext { currency: "USD" })?;
+                if account_number.len() > 17 || !account_number.chars().all(|c| c.is_ascii_alphanumeric()) {
+                    return Err(DomainError::Validation("ACH account number must be up to 17 letters or digits".to_string()));
+                }
+                Ok(BeneficiaryIdentity {
+                    beneficiary_type: BeneficiaryType::Ach,
+                    identifier: format!("ACH:{}:{}", routing_number, account_number),
+                    masked_identifier: mask(&account_number),
+                    bank_identifier: Some(routing_number),
+                    crypto_network: None,
+                    country_code: Some("US".to_string()),
+                })
+            }
+            BeneficiaryDetails::Iban { iban, bic } => {
+                let iban = compact(iban).to_uppercase();
+                if !is_valid_iban(&iban) {
+                    return Err(DomainError::Validation("Invalid IBAN".to_string()));
+                }
+                let bic = bic.as_deref().map(|b| compact(b).to_uppercase()).filter(|b| !b.is_empty());
+                if let Some(bic) = &bic {
+                    validator::validate_swift_bic(bic)?;
+                }
+                Ok(BeneficiaryIdentity {
+                    beneficiary_type: BeneficiaryType::Iban,
+                    masked_identifier: mask(&iban),
+                    country_code: Some(iban[..2].to_string()),
+                    identifier: format!("IBAN:{}", iban), // The BIC is not part of the identity
+                    bank_identifier: bic,
+                    crypto_network: None,
+                })
+            }
+            BeneficiaryDetails::Wire { swift_bic, account_number } => {
+                let swift_bic = compact(swift_bic).to_uppercase();
+                let account_number = compact(account_number).to_uppercase();
+                validator::validate_swift_bic(&swift_bic)?;
+                if account_number.is_empty() {
+                    return Err(DomainError::Validation("Wire account number cannot be empty".to_string()));
+                }
+                if is_valid_iban(&account_number) {
+                    // Same payee whether it was entered as an IBAN or as a wire account
+                    return Self::from_details(&BeneficiaryDetails::Iban { iban: account_number, bic: Some(swift_bic) });
+                }
+                Ok(BeneficiaryIdentity {
+                    beneficiary_type: BeneficiaryType::Wire,
+                    identifier: format!("WIRE:{}:{}", swift_bic, account_number),
+                    masked_identifier: mask(&account_number),
+                    country_code: Some(swift_bic[4..6].to_string()),
+                    bank_identifier: Some(swift_bic),
+                    crypto_network: None,
+                })
+            }
+            BeneficiaryDetails::Crypto { network, address } => {
+                let network = network.trim().to_uppercase();
+                if network != "BTC" && network != "XMR" {
+                    return Err(DomainError::Validation(format!("Unsupported crypto network: {}", network)));
+                }
+                let address = address.trim(); // Addresses are case-sensitive
+                if address.len() < 26 || !address.chars().all(|c| c.is_ascii_alphanumeric()) {
+                    return Err(DomainError::Validation("Invalid crypto address".to_string()));
+                }
+                Ok(BeneficiaryIdentity {
+                    beneficiary_type: BeneficiaryType::Crypto,
+                    identifier: format!("CRYPTO:{}:{}", network, address),
+                    masked_identifier: mask(address),
+                    bank_identifier: None,
+                    crypto_network: Some(network),
+                    country_code: None,
+                })
+            }
+        }
+    }
+
+    /// Identity of the payee of an outbound payment, if it has an external one.
+    pub fn for_payment(
+        payment_type: &TransactionType,
+        ach: Option<&AchDetails>,
+        wire: Option<&WireDetails>,
+        crypto_address: Option<&str>,
+    ) -> Result<Option<Self>, DomainError> {
+        let details = match payment_type {
+            TransactionType::AchCredit | TransactionType::AchDebit => ach.map(|d| BeneficiaryDetails::Ach {
+                routing_number: d.routing_number.clone(), account_number: d.account_number.clone(),
+            }),
+            TransactionType::WireOutbound => wire.map(|d| BeneficiaryDetails::Wire {
+                swift_bic: d.swift_bic.clone(), account_number: d.account_number.clone(),
+            }),
+            TransactionType::CryptoBtcSend => crypto_address.map(|a| BeneficiaryDetails::Crypto { network: "BTC".to_string(), address: a.to_string() }),
+            TransactionType::CryptoXmrSend => crypto_address.map(|a| BeneficiaryDetails::Crypto { network: "XMR".to_string(), address: a.to_string() }),
+            _ => None,
+        };
+        details.as_ref().map(Self::from_details).transpose()
+    }
+
+    /// Stored matching key.
+    pub fn identifier_hash(&self) -> Result<String, DomainError> {
+        hashing::hash_sensitive_data(&self.identifier)
+    }
+}
+
+/// Reduced limits while a beneficiary is new (or was just edited and re-verified).
+/// Amounts are in the currency of the payment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoolingOffLimits {
+    pub period: Duration, // Starts at verification
+    pub max_payment_amount: Decimal, // Per payment
+    pub max_total_amount: Decimal, // All payments to the beneficiary during the period
+    pub require_saved_beneficiary: bool, // Refuse payments to payees that are not saved
+}
+
+impl Default for CoolingOffLimits {
+    fn default() -> Self {
+        CoolingOffLimits {
+            period: Duration::hours(24),
+            max_payment_amount: Decimal::new(1000, 0),
+            max_total_amount: Decimal::new(2500, 0),
+            require_saved_beneficiary: false,
+        }
+    }
+}
+
+impl CoolingOffLimits {
+    /// Checks a payment of `amount` to a beneficiary cooling off until `cooling_off_until`, given
+    /// `paid_so_far` to it since verification.
+    pub fn check(
+        &self,
+        cooling_off_until: Option<DateTime<Utc>>,
+        amount: Decimal,
+        paid_so_far: Decimal,
+        now: DateTime<Utc>,
+    ) -> Result<(), DomainError> {
+        let Some(until) = cooling_off_until.filter(|until| now < *until) else {
+            return Ok(());
+        };
+        if amount > self.max_payment_amount {
+            return Err(DomainError::Validation(format!(
+                "New beneficiary: payments are limited to {} each until {}", self.max_payment_amount, until)));
+        }
+        if paid_so_far + amount > self.max_total_amount {
+            return Err(DomainError::Validation(format!(
+                "New beneficiary: {} of {} already used until {}", paid_so_far, self.max_total_amount, until)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_iban_checksum() {
+        assert!(is_valid_iban("DE89 3704 0044 0532 0130 00"));
+        assert!(is_valid_iban("gb29nwbk60161331926819"));
+        assert!(!is_valid_iban("DE89370400440532013001"));
+        assert!(!is_valid_iban("DE8937"));
+    }
+
+    #[test]
+    fn test_same_payee_hashes_the_same() {
+        let entered = BeneficiaryIdentity::from_details(&BeneficiaryDetails::Iban {
+            iban: "de89 3704 0044 0532 0130 00".to_string(), bic: None,
+        }).unwrap();
+        let wire = WireDetails { swift_bic: "COBADEFFXXX".to_string(), account_number: "DE89370400440532013000".to_string(), ..Default::default() };
+        let paid = BeneficiaryIdentity::for_payment(&TransactionType::WireOutbound, None, Some(&wire), None).unwrap().unwrap();
+        assert_eq!(entered.identifier_hash().unwrap(), paid.identifier_hash().unwrap());
+        assert_eq!(paid.masked_identifier, "****3000");
+        assert_eq!(paid.country_code.as_deref(), Some("DE"));
+    }
+
+    #[test]
+    fn test_invalid_details_are_rejected() {
+        assert!(BeneficiaryIdentity::from_details(&BeneficiaryDetails::Ach {
+            routing_number: "123456789".to_string(), account_number: "1234".to_string(),
+        }).is_err());
+        assert!(BeneficiaryIdentity::from_details(&BeneficiaryDetails::Crypto {
+            network: "ETH".to_string(), address: "0x0000000000000000000000000000000000000000".to_string(),
+        }).is_err());
+        let ach = BeneficiaryIdentity::from_details(&BeneficiaryDetails::Ach {
+            routing_number: "021000021".to_string(), account_number: "0001234-5678".to_string(),
+        }).unwrap();
+        assert_eq!(ach.identifier, "ACH:021000021:00012345678");
+    }
+
+    #[test]
+    fn test_cooling_off_limits() {
+        let limits = CoolingOffLimits::default();
+        let now = Utc::now();
+        let until = Some(now + Duration::hours(1));
+        assert!(limits.check(until, dec!(1000), dec!(0), now).is_ok());
+        assert!(limits.check(until, dec!(1000.01), dec!(0), now).is_err());
+        assert!(limits.check(until, dec!(600), dec!(2000), now).is_err());
+        // Over once the period ends
+        assert!(limits.check(Some(now - Duration::seconds(1)), dec!(50000), dec!(0), now).is_ok());
+        assert!(limits.check(None, dec!(50000), dec!(0), now).is_ok());
+    }
+}
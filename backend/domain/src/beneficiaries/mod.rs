@@ -0,0 +1,11 @@
+// /home/inno/elights_jobes-research/backend/domain/src/beneficiaries/mod.rs
+// Saved payees: hashed identifiers, verification of new or edited payees, cooling-off limits.
+
+pub mod identity; // Normalizing, validating and hashing payee details; cooling-off limits
+pub mod store; // Saved beneficiaries, their verification and the checks applied to payments
+
+pub use identity::{BeneficiaryDetails, BeneficiaryIdentity, CoolingOffLimits};
+pub use store::{
+    check_payment, create_beneficiary, disable_beneficiary, edit_beneficiary, get_beneficiary, is_new_beneficiary,
+    list_beneficiaries, verify_beneficiary, CheckedPayee, CreateBeneficiary, EditBeneficiary, BENEFICIARY_KEY, PAYEE_HASH_KEY,
+};
@@ -0,0 +1,408 @@
+// /home/inno/elights_jobes-research/backend/domain/src/beneficiaries/store.rs
+// Saved payees. A beneficiary is created PENDING_VERIFICATION and becomes payable once the customer
+// re-enters their password; verification starts its cooling-off period. Changing the payment details
+// sends it back to PENDING_VERIFICATION (renaming does not). Payments to a saved beneficiary carry its id
+// in the transaction metadata, which is what cooling-off totals and the fraud history check read. Payees
+// that are not saved never leave the cooling-off limits: their payments carry the payee's hashed identity,
+// and the limits apply to what was sent to it over a rolling period.
+use super::identity::{BeneficiaryDetails, BeneficiaryIdentity, CoolingOffLimits};
+use crate::error::DomainError;
+use crate::models::{
+    AuditOutcome, AuditTargetType, Beneficiary, BeneficiaryStatus, NewBeneficiary, Transaction, TransactionStatus, User,
+};
+use crate::security::{audit, auth};
+use crate::utils::bigdecimal_to_decimal;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{Bool, Varchar};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+/// Metadata key linking a payment to the saved beneficiary it was made to.
+pub const BENEFICIARY_KEY: &str = "beneficiary_id";
+
+/// Metadata key holding the hashed identity of an external payee (saved or not).
+pub const PAYEE_HASH_KEY: &str = "payee_hash";
+
+/// The external payee of a checked payment.
+#[derive(Debug, Clone)]
+pub struct CheckedPayee {
+    pub identifier_hash: String, // Recorded under `PAYEE_HASH_KEY`
+    pub beneficiary: Option<Beneficiary>, // Saved beneficiary it matched, recorded under `BENEFICIARY_KEY`
+}
+
+impl CheckedPayee {
+    /// Metadata entries linking the payment to its payee.
+    pub fn metadata(&self) -> Vec<(&'static str, serde_json::Value)> {
+        let mut entries = vec![(PAYEE_HASH_KEY, json!(self.identifier_hash))];
+        if let Some(beneficiary) = &self.beneficiary {
+            entries.push((BENEFICIARY_KEY, json!(beneficiary.beneficiary_id)));
+        }
+        entries
+    }
+}
+
+/// Statuses of payments that did not (or will not) reach the beneficiary.
+const UNPAID_STATUSES: [TransactionStatus; 4] =
+    [TransactionStatus::Failed, TransactionStatus::Cancelled, TransactionStatus::Expired, TransactionStatus::Returned];
+
+/// Saving a new payee.
+#[derive(Debug, Deserialize, Clone)]
+pub struct CreateBeneficiary {
+    pub nickname: String,
+    pub holder_name: Option<String>,
+    pub details: BeneficiaryDetails,
+}
+
+/// Editing a saved payee. New `details` require re-verification.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct EditBeneficiary {
+    pub nickname: Option<String>,
+    pub holder_name: Option<String>,
+    pub details: Option<BeneficiaryDetails>,
+}
+
+fn audit_beneficiary(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    action: &str,
+    beneficiary_id: Uuid,
+    outcome: AuditOutcome,
+    details: serde_json::Value,
+    error_message: Option<&str>,
+) -> Result<(), DomainError> {
+    audit::log_db_audit_event(
+        conn,
+        Some(user_id),
+        &user_id.to_string(),
+        action,
+        Some(AuditTargetType::Beneficiary),
+        Some(&beneficiary_id.to_string()),
+        outcome,
+        Some(details),
+        error_message,
+    )?;
+    Ok(())
+}
+
+fn validate_nickname(nickname: &str) -> Result<&str, DomainError> {
+    let nickname = nickname.trim();
+    if nickname.is_empty() || nickname.len() > 100 {
+        return Err(DomainError::Validation("Nickname must be 1 to 100 characters".to_string()));
+    }
+    Ok(nickname)
+}
+
+/// A saved, non-disabled beneficiary of `user_id` with the same identity, if any.
+fn find_by_identity(conn: &mut PgConnection, user_id: Uuid, identifier_hash: &str) -> Result<Option<Beneficiary>, DomainError> {
+    use crate::schema::beneficiaries::dsl as b;
+    let beneficiary = b::beneficiaries
+        .filter(b::user_id.eq(user_id))
+        .filter(b::identifier_hash.eq(identifier_hash))
+        .filter(b::status.ne(BeneficiaryStatus::Disabled.as_str()))
+        .first(conn)
+        .optional()?;
+    Ok(beneficiary)
+}
+
+/// Saves a payee for `user_id`; it must be verified before it can be paid.
+pub fn create_beneficiary(conn: &mut PgConnection, user_id: Uuid, request: &CreateBeneficiary) -> Result<Beneficiary, DomainError> {
+    let nickname = validate_nickname(&request.nickname)?;
+    let identity = BeneficiaryIdentity::from_details(&request.details)?;
+    let identifier_hash = identity.identifier_hash()?;
+    conn.transaction(|conn| {
+        if let Some(existing) = find_by_identity(conn, user_id, &identifier_hash)? {
+            return Err(DomainError::Validation(format!("This payee is already saved as '{}'", existing.nickname)));
+        }
+        let beneficiary: Beneficiary = diesel::insert_into(crate::schema::beneficiaries::table)
+            .values(&NewBeneficiary {
+                user_id,
+                nickname,
+                beneficiary_type: identity.beneficiary_type.as_str(),
+                identifier_hash: &identifier_hash,
+                masked_identifier: &identity.masked_identifier,
+                bank_identifier: identity.bank_identifier.as_deref(),
+                crypto_network: identity.crypto_network.as_deref(),
+                country_code: identity.country_code.as_deref(),
+                holder_name: request.holder_name.as_deref().map(str::trim).filter(|name| !name.is_empty()),
+                status: BeneficiaryStatus::PendingVerification.as_str(),
+            })
+            .get_result(conn)?;
+        audit_beneficiary(conn, user_id, "CREATE_BENEFICIARY", beneficiary.beneficiary_id, AuditOutcome::Success,
+            json!({"type": beneficiary.beneficiary_type, "masked_identifier": beneficiary.masked_identifier,
+                "bank_identifier": beneficiary.bank_identifier, "country_code": beneficiary.country_code}), None)?;
+        log::info!("User {} saved beneficiary {} ({})", user_id, beneficiary.beneficiary_id, beneficiary.masked_identifier);
+        Ok(beneficiary)
+    })
+}
+
+/// The caller's saved beneficiaries, disabled ones excluded.
+pub fn list_beneficiaries(conn: &mut PgConnection, user_id: Uuid) -> Result<Vec<Beneficiary>, DomainError> {
+    use crate::schema::beneficiaries::dsl as b;
+    let beneficiaries = b::beneficiaries
+        .filter(b::user_id.eq(user_id))
+        .filter(b::status.ne(BeneficiaryStatus::Disabled.as_str()))
+        .order(b::nickname.asc())
+        .load(conn)?;
+    Ok(beneficiaries)
+}
+
+/// One of the caller's beneficiaries.
+pub fn get_beneficiary(conn: &mut PgConnection, user_id: Uuid, beneficiary_id: Uuid) -> Result<Beneficiary, DomainError> {
+    use crate::schema::beneficiaries::dsl as b;
+    b::beneficiaries
+        .find(beneficiary_id)
+        .filter(b::user_id.eq(user_id))
+        .filter(b::status.ne(BeneficiaryStatus::Disabled.as_str()))
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("Beneficiary {} not found", beneficiary_id)))
+}
+
+fn lock_beneficiary(conn: &mut PgConnection, user_id: Uuid, beneficiary_id: Uuid) -> Result<Beneficiary, DomainError> {
+    use crate::schema::beneficiaries::dsl as b;
+    b::beneficiaries
+        .find(beneficiary_id)
+        .filter(b::user_id.eq(user_id))
+        .filter(b::status.ne(BeneficiaryStatus::Disabled.as_str()))
+        .for_update()
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("Beneficiary {} not found", beneficiary_id)))
+}
+
+/// Renames a beneficiary and/or changes its payment details. New details mean a new payee as far as
+/// trust goes: the beneficiary must be verified again and gets a fresh cooling-off period.
+pub fn edit_beneficiary(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    beneficiary_id: Uuid,
+    edit: &EditBeneficiary,
+) -> Result<Beneficiary, DomainError> {
+    use crate::schema::beneficiaries::dsl as b;
+    let nickname = edit.nickname.as_deref().map(validate_nickname).transpose()?;
+    let identity = edit.details.as_ref().map(BeneficiaryIdentity::from_details).transpose()?;
+    conn.transaction(|conn| {
+        let current = lock_beneficiary(conn, user_id, beneficiary_id)?;
+        let mut beneficiary = current.clone();
+        if let Some(nickname) = nickname {
+            beneficiary = diesel::update(b::beneficiaries.find(beneficiary_id))
+                .set(b::nickname.eq(nickname))
+                .get_result(conn)?;
+        }
+        if let Some(holder_name) = edit.holder_name.as_deref() {
+            let holder_name = Some(holder_name.trim()).filter(|name| !name.is_empty());
+            beneficiary = diesel::update(b::beneficiaries.find(beneficiary_id))
+                .set(b::holder_name.eq(holder_name))
+                .get_result(conn)?;
+        }
+        let details_changed = match &identity {
+            Some(identity) => {
+                let identifier_hash = identity.identifier_hash()?;
+                if identifier_hash == current.identifier_hash {
+                    false // Same payee, re-entered
+                } else {
+                    if find_by_identity(conn, user_id, &identifier_hash)?.is_some() {
+                        return Err(DomainError::Validation("This payee is already saved".to_string()));
+                    }
+                    beneficiary = diesel::update(b::beneficiaries.find(beneficiary_id))
+                        .set((
+                            b::beneficiary_type.eq(identity.beneficiary_type.as_str()),
+                            b::identifier_hash.eq(&identifier_hash),
+                            b::masked_identifier.eq(&identity.masked_identifier),
+                            b::bank_identifier.eq(identity.bank_identifier.as_deref()),
+                            b::crypto_network.eq(identity.crypto_network.as_deref()),
+                            b::country_code.eq(identity.country_code.as_deref()),
+                            b::status.eq(BeneficiaryStatus::PendingVerification.as_str()),
+                            b::verified_at.eq(None::<DateTime<Utc>>),
+                            b::cooling_off_until.eq(None::<DateTime<Utc>>),
+                        ))
+                        .get_result(conn)?;
+                    true
+                }
+            }
+            None => false,
+        };
+        audit_beneficiary(conn, user_id, "EDIT_BENEFICIARY", beneficiary_id, AuditOutcome::Success,
+            json!({"details_changed": details_changed, "previous_masked_identifier": current.masked_identifier,
+                "masked_identifier": beneficiary.masked_identifier, "status": beneficiary.status}), None)?;
+        Ok(beneficiary)
+    })
+}
+
+/// Confirms a new or edited beneficiary with the customer's password. Starts the cooling-off period.
+/// A wrong password is audited and fails with `Authorization`.
+pub fn verify_beneficiary(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    beneficiary_id: Uuid,
+    password: &str,
+    limits: &CoolingOffLimits,
+    now: DateTime<Utc>,
+) -> Result<Beneficiary, DomainError> {
+    use crate::schema::beneficiaries::dsl as b;
+    let beneficiary = get_beneficiary(conn, user_id, beneficiary_id)?;
+    if beneficiary.status != BeneficiaryStatus::PendingVerification.as_str() {
+        return Err(DomainError::Validation(format!("Beneficiary {} does not need verification", beneficiary_id)));
+    }
+    let user: User = crate::schema::users::table.find(user_id).first(conn)?;
+    if !auth::verify_password(password, &user.password_hash)? {
+        // Outside the update transaction so the failed attempt stays on record
+        audit_beneficiary(conn, user_id, "VERIFY_BENEFICIARY", beneficiary_id, AuditOutcome::Failure,
+            json!({"masked_identifier": beneficiary.masked_identifier}), Some("Password did not match"))?;
+        return Err(DomainError::Authorization("Verification failed".to_string()));
+    }
+    conn.transaction(|conn| {
+        let current = lock_beneficiary(conn, user_id, beneficiary_id)?;
+        if current.identifier_hash != beneficiary.identifier_hash || current.status != beneficiary.status {
+            return Err(DomainError::Validation(format!("Beneficiary {} changed during verification, try again", beneficiary_id)));
+        }
+        let cooling_off_until = now + limits.period;
+        let beneficiary: Beneficiary = diesel::update(b::beneficiaries.find(beneficiary_id))
+            .set((
+                b::status.eq(BeneficiaryStatus::Active.as_str()),
+                b::verified_at.eq(Some(now)),
+                b::cooling_off_until.eq(Some(cooling_off_until)),
+            ))
+            .get_result(conn)?;
+        audit_beneficiary(conn, user_id, "VERIFY_BENEFICIARY", beneficiary_id, AuditOutcome::Success,
+            json!({"masked_identifier": beneficiary.masked_identifier, "cooling_off_until": cooling_off_until}), None)?;
+        log::info!("Beneficiary {} verified, cooling off until {}", beneficiary_id, cooling_off_until);
+        Ok(beneficiary)
+    })
+}
+
+/// Removes a beneficiary from the customer's list. Its payment history is kept.
+pub fn disable_beneficiary(conn: &mut PgConnection, user_id: Uuid, beneficiary_id: Uuid) -> Result<Beneficiary, DomainError> {
+    use crate::schema::beneficiaries::dsl as b;
+    conn.transaction(|conn| {
+        lock_beneficiary(conn, user_id, beneficiary_id)?;
+        let beneficiary: Beneficiary = diesel::update(b::beneficiaries.find(beneficiary_id))
+            .set(b::status.eq(BeneficiaryStatus::Disabled.as_str()))
+            .get_result(conn)?;
+        audit_beneficiary(conn, user_id, "DISABLE_BENEFICIARY", beneficiary_id, AuditOutcome::Success,
+            json!({"masked_identifier": beneficiary.masked_identifier}), None)?;
+        Ok(beneficiary)
+    })
+}
+
+/// Amount `user_id` sent (or has on its way) since `since` to payments whose metadata `key` is `value`.
+fn paid_to(conn: &mut PgConnection, user_id: Uuid, key: &str, value: String, since: DateTime<Utc>) -> Result<Decimal, DomainError> {
+    use crate::schema::transactions::dsl as t;
+    use crate::schema::wallets::dsl as w;
+    use diesel::dsl::sum;
+    let user_wallets = w::wallets.filter(w::user_id.eq(user_id)).select(w::wallet_id.nullable());
+    let total: Option<BigDecimal> = t::transactions
+        .filter(diesel::dsl::sql::<Bool>(&format!("metadata->>'{}' = ", key)).bind::<Varchar, _>(value))
+        .filter(t::debit_wallet_id.eq_any(user_wallets))
+        .filter(t::created_at.ge(since))
+        .filter(t::status.ne_all(UNPAID_STATUSES.iter().map(|s| s.to_string()).collect::<Vec<_>>()))
+        .select(sum(t::amount))
+        .first(conn)?;
+    Ok(total.map(bigdecimal_to_decimal).unwrap_or(Decimal::ZERO))
+}
+
+/// Matches an outbound payment to the payer's saved beneficiaries and applies their rules: the
+/// beneficiary must be verified and, while cooling off, within the reduced limits. A payee that is not
+/// saved is refused when `limits.require_saved_beneficiary`, and otherwise held to the cooling-off limits
+/// over the last `limits.period`, so leaving a payee unsaved never lifts them. Returns the payee to link
+/// the payment to, `None` for internal transfers.
+pub fn check_payment(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    payee: Option<&BeneficiaryIdentity>,
+    amount: Decimal,
+    limits: &CoolingOffLimits,
+    now: DateTime<Utc>,
+) -> Result<Option<CheckedPayee>, DomainError> {
+    let Some(payee) = payee else {
+        return Ok(None); // Internal transfer, nothing to match
+    };
+    let identifier_hash = payee.identifier_hash()?;
+    let Some(beneficiary) = find_by_identity(conn, user_id, &identifier_hash)? else {
+        if limits.require_saved_beneficiary {
+            return Err(DomainError::Validation("Payee must be saved and verified as a beneficiary before it can be paid".to_string()));
+        }
+        let since = now - limits.period;
+        let paid_so_far = paid_to(conn, user_id, PAYEE_HASH_KEY, identifier_hash.clone(), since)?;
+        if amount > limits.max_payment_amount || paid_so_far + amount > limits.max_total_amount {
+            return Err(DomainError::Validation(format!(
+                "Payee is not saved: payments are limited to {} each and {} per {} hours ({} used); save and verify it to lift the limits",
+                limits.max_payment_amount, limits.max_total_amount, limits.period.num_hours(), paid_so_far)));
+        }
+        return Ok(Some(CheckedPayee { identifier_hash, beneficiary: None }));
+    };
+    if beneficiary.status != BeneficiaryStatus::Active.as_str() {
+        return Err(DomainError::Validation(format!("Beneficiary '{}' must be verified before it can be paid", beneficiary.nickname)));
+    }
+    let paid_so_far = match (beneficiary.cooling_off_until, beneficiary.verified_at) {
+        (Some(until), Some(verified_at)) if now < until => {
+            paid_to(conn, user_id, BENEFICIARY_KEY, beneficiary.beneficiary_id.to_string(), verified_at)?
+        }
+        _ => Decimal::ZERO,
+    };
+    limits.check(beneficiary.cooling_off_until, amount, paid_so_far, now)?;
+    Ok(Some(CheckedPayee { identifier_hash, beneficiary: Some(beneficiary) }))
+}
+
+/// Whether `transaction` pays someone its payer has not successfully paid before. A saved beneficiary
+/// still cooling off counts as new; a payee that is not saved counts as new until a payment to it settled.
+pub fn is_new_beneficiary(conn: &mut PgConnection, transaction: &Transaction, now: DateTime<Utc>) -> Result<bool, DomainError> {
+    use crate::schema::beneficiaries::dsl as b;
+    use crate::schema::transactions::dsl as t;
+
+    let linked = transaction.metadata.as_ref()
+        .and_then(|m| m.get(BENEFICIARY_KEY))
+        .and_then(|id| id.as_str())
+        .and_then(|id| Uuid::parse_str(id).ok());
+    if let Some(beneficiary_id) = linked {
+        let Some(beneficiary) = b::beneficiaries.find(beneficiary_id).first::<Beneficiary>(conn).optional()? else {
+            return Ok(true);
+        };
+        if beneficiary.cooling_off_until.is_some_and(|until| now < until) {
+            return Ok(true);
+        }
+        let paid_before = diesel::select(diesel::dsl::exists(
+            t::transactions
+                .filter(diesel::dsl::sql::<Bool>("metadata->>'beneficiary_id' = ").bind::<Varchar, _>(beneficiary_id.to_string()))
+                .filter(t::transaction_id.ne(transaction.transaction_id))
+                .filter(t::status.eq_any(vec![TransactionStatus::Settled.to_string(), TransactionStatus::Completed.to_string()])),
+        ))
+        .get_result::<bool>(conn)?;
+        return Ok(!paid_before);
+    }
+
+    // Payee that is not saved: has this user paid the same identity before?
+    let payee_hash = transaction.metadata.as_ref().and_then(|m| m.get(PAYEE_HASH_KEY)).and_then(|h| h.as_str());
+    if let (Some(payee_hash), Some(debit_wallet_id)) = (payee_hash, transaction.debit_wallet_id) {
+        use crate::schema::wallets::dsl as w;
+        let owner = w::wallets.find(debit_wallet_id).select(w::user_id);
+        let user_wallets = w::wallets.filter(w::user_id.eq_any(owner)).select(w::wallet_id.nullable());
+        let paid_before = diesel::select(diesel::dsl::exists(
+            t::transactions
+                .filter(diesel::dsl::sql::<Bool>("metadata->>'payee_hash' = ").bind::<Varchar, _>(payee_hash.to_string()))
+                .filter(t::debit_wallet_id.eq_any(user_wallets))
+                .filter(t::transaction_id.ne(transaction.transaction_id))
+                .filter(t::status.eq_any(vec![TransactionStatus::Settled.to_string(), TransactionStatus::Completed.to_string()])),
+        ))
+        .get_result::<bool>(conn)?;
+        return Ok(!paid_before);
+    }
+
+    // Internal transfer: has this wallet paid that wallet before?
+    if let (Some(debit_wallet_id), Some(credit_wallet_id)) = (transaction.debit_wallet_id, transaction.credit_wallet_id) {
+        let paid_before = diesel::select(diesel::dsl::exists(
+            t::transactions
+                .filter(t::debit_wallet_id.eq(debit_wallet_id))
+                .filter(t::credit_wallet_id.eq(credit_wallet_id))
+                .filter(t::transaction_id.ne(transaction.transaction_id))
+                .filter(t::status.eq_any(vec![TransactionStatus::Settled.to_string(), TransactionStatus::Completed.to_string()])),
+        ))
+        .get_result::<bool>(conn)?;
+        return Ok(!paid_before);
+    }
+    Ok(true) // External payee that was never saved
+}
@@ -0,0 +1,79 @@
+// /home/inno/elights_jobes-research/backend/domain/src/config.rs
+// JSON configuration files (fee schedule, limits and approval policies, screening and reporting configurations,
+// nostro accounts). Each file type names itself and checks its own contents; reading, parsing, error reporting
+// and the load log line are shared.
+use crate::error::DomainError;
+use serde::de::DeserializeOwned;
+use std::path::Path;
+
+/// A configuration read from a JSON file.
+pub trait JsonConfig: DeserializeOwned {
+    /// What the file holds, used in errors and logs (e.g. "fee schedule").
+    const NAME: &'static str;
+
+    /// Rejects contents that parse but cannot be used (duplicate ids, empty ranges, ...).
+    fn validate(&self) -> Result<(), DomainError>;
+
+    /// Logged after loading, e.g. the version and how many rules were read.
+    fn summary(&self) -> String;
+}
+
+/// Parses and validates a configuration from its JSON form.
+pub fn parse_json<T: JsonConfig>(json: &str) -> Result<T, DomainError> {
+    let config: T = serde_json::from_str(json)
+        .map_err(|e| DomainError::Configuration(format!("Invalid {}: {}", T::NAME, e)))?;
+    config.validate()?;
+    Ok(config)
+}
+
+/// Reads, parses and validates a configuration file.
+pub fn load_json<T: JsonConfig>(path: impl AsRef<Path>) -> Result<T, DomainError> {
+    let path = path.as_ref();
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| DomainError::Configuration(format!("Cannot read {} {}: {}", T::NAME, path.display(), e)))?;
+    let config: T = parse_json(&json)?;
+    log::info!("Loaded {} {} from {}", T::NAME, config.summary(), path.display());
+    Ok(config)
+}
+
+/// `version (n items)`, the usual summary of a versioned list of rules.
+pub fn versioned_summary(version: Option<&str>, count: usize, items: &str) -> String {
+    format!("{} ({} {})", version.unwrap_or("unversioned"), count, items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    struct Limits {
+        max: u32,
+    }
+
+    impl JsonConfig for Limits {
+        const NAME: &'static str = "test limits";
+
+        fn validate(&self) -> Result<(), DomainError> {
+            if self.max == 0 {
+                return Err(DomainError::Configuration("max must be positive".to_string()));
+            }
+            Ok(())
+        }
+
+        fn summary(&self) -> String {
+            format!("(max {})", self.max)
+        }
+    }
+
+    #[test]
+    fn parses_validates_and_names_the_file() {
+        assert_eq!(parse_json::<Limits>(r#"{"max": 3}"#).unwrap().max, 3);
+        assert!(parse_json::<Limits>(r#"{"max": 0}"#).is_err());
+        let err = parse_json::<Limits>(r#"{"max": "three"}"#).unwrap_err();
+        assert!(err.to_string().contains("Invalid test limits"), "{}", err);
+        let err = load_json::<Limits>("/nonexistent/limits.json").unwrap_err();
+        assert!(err.to_string().contains("Cannot read test limits /nonexistent/limits.json"), "{}", err);
+        assert_eq!(versioned_summary(None, 2, "rules"), "unversioned (2 rules)");
+    }
+}
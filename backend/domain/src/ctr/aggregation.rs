@@ -0,0 +1,171 @@
+// /home/inno/elights_jobes-research/backend/domain/src/ctr/aggregation.rs
+// Multiple-transaction rule: all cash in, and separately all cash out, by or for one person during one business
+// day are added up, and a report is due when either total is more than the threshold. Cash in and cash out are
+// never netted. Activity on a weekend or holiday belongs to the next business day.
+use crate::payments::{BusinessCalendar, BusinessDayConvention};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CashDirection {
+    In,  // Deposits, payments received from the person
+    Out, // Withdrawals, cash paid to the person
+}
+
+/// One cash transaction, seen from the customer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CashMovement {
+    pub transaction_id: Uuid,
+    pub user_id: Uuid,
+    pub wallet_id: Uuid,
+    pub direction: CashDirection,
+    pub activity_code: String, // FinCEN activity type code
+    pub amount: Decimal,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Total of one activity type within a report.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CashDetail {
+    pub direction: CashDirection,
+    pub activity_code: String,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+    pub count: usize,
+}
+
+/// One person's cash activity on one business day that requires a report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CtrAggregate {
+    pub user_id: Uuid,
+    pub business_date: NaiveDate,
+    pub cash_in_total: Decimal,
+    pub cash_out_total: Decimal,
+    pub details: Vec<CashDetail>,
+    pub transaction_ids: Vec<Uuid>,
+    pub wallet_ids: Vec<Uuid>,
+}
+
+/// Business day a transaction belongs to.
+pub fn business_date(calendar: &BusinessCalendar, at: DateTime<Utc>) -> NaiveDate {
+    calendar.adjust(at.date_naive(), BusinessDayConvention::Following)
+}
+
+/// Calendar days (UTC) whose activity belongs to `business_date`: the day itself and the non-business days
+/// just before it.
+pub fn calendar_days(calendar: &BusinessCalendar, business_date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let mut first = business_date;
+    while !calendar.is_business_day(first - Duration::days(1)) {
+        first = first - Duration::days(1);
+    }
+    (first, business_date)
+}
+
+/// FinCEN amounts are whole dollars, rounded up.
+pub fn whole_dollars(amount: Decimal) -> Decimal {
+    amount.ceil()
+}
+
+/// Aggregates movements per person per business day and keeps those over the threshold.
+pub fn aggregate(movements: &[CashMovement], calendar: &BusinessCalendar, threshold: Decimal) -> Vec<CtrAggregate> {
+    let mut per_day: BTreeMap<(Uuid, NaiveDate), Vec<&CashMovement>> = BTreeMap::new();
+    for movement in movements {
+        per_day.entry((movement.user_id, business_date(calendar, movement.occurred_at))).or_default().push(movement);
+    }
+
+    per_day
+        .into_iter()
+        .filter_map(|((user_id, business_date), movements)| {
+            let total = |direction| movements.iter().filter(|m| m.direction == direction).map(|m| m.amount).sum::<Decimal>();
+            let (cash_in_total, cash_out_total) = (total(CashDirection::In), total(CashDirection::Out));
+            if cash_in_total <= threshold && cash_out_total <= threshold {
+                return None;
+            }
+            let mut details: BTreeMap<(CashDirection, &str), CashDetail> = BTreeMap::new();
+            for movement in &movements {
+                let detail = details.entry((movement.direction, movement.activity_code.as_str())).or_insert_with(|| CashDetail {
+                    direction: movement.direction,
+                    activity_code: movement.activity_code.clone(),
+                    amount: Decimal::ZERO,
+                    count: 0,
+                });
+                detail.amount += movement.amount;
+                detail.count += 1;
+            }
+            Some(CtrAggregate {
+                user_id,
+                business_date,
+                cash_in_total,
+                cash_out_total,
+                details: details.into_values().collect(),
+                transaction_ids: movements.iter().map(|m| m.transaction_id).collect::<BTreeSet<_>>().into_iter().collect(),
+                wallet_ids: movements.iter().map(|m| m.wallet_id).collect::<BTreeSet<_>>().into_iter().collect(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    fn movement(user_id: Uuid, direction: CashDirection, amount: Decimal, day: u32, hour: u32) -> CashMovement {
+        CashMovement {
+            transaction_id: Uuid::new_v4(),
+            user_id,
+            wallet_id: user_id,
+            direction,
+            activity_code: if direction == CashDirection::In { "55" } else { "56" }.to_string(),
+            amount,
+            occurred_at: Utc.with_ymd_and_hms(2025, 3, day, hour, 0, 0).unwrap(), // 2025-03-07 is a Friday
+        }
+    }
+
+    #[test]
+    fn test_multiple_transactions_are_combined_per_business_day() {
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let movements = vec![
+            movement(alice, CashDirection::In, dec!(6000), 5, 9),
+            movement(alice, CashDirection::In, dec!(4000.01), 5, 16),
+            movement(alice, CashDirection::In, dec!(9000), 6, 10), // Next day, on its own
+            movement(bob, CashDirection::In, dec!(10000), 5, 9), // Not MORE than the threshold
+        ];
+        let reports = aggregate(&movements, &BusinessCalendar::default(), dec!(10000));
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].user_id, alice);
+        assert_eq!(reports[0].cash_in_total, dec!(10000.01));
+        assert_eq!(reports[0].transaction_ids.len(), 2);
+        assert_eq!(reports[0].details, vec![CashDetail { direction: CashDirection::In, activity_code: "55".to_string(), amount: dec!(10000.01), count: 2 }]);
+        assert_eq!(whole_dollars(reports[0].cash_in_total), dec!(10001));
+    }
+
+    #[test]
+    fn test_cash_in_and_out_are_not_netted() {
+        let alice = Uuid::new_v4();
+        let both_under = vec![movement(alice, CashDirection::In, dec!(8000), 5, 9), movement(alice, CashDirection::Out, dec!(8000), 5, 10)];
+        assert!(aggregate(&both_under, &BusinessCalendar::default(), dec!(10000)).is_empty());
+
+        let out_over = vec![movement(alice, CashDirection::In, dec!(9000), 5, 9), movement(alice, CashDirection::Out, dec!(12000), 5, 10)];
+        let reports = aggregate(&out_over, &BusinessCalendar::default(), dec!(10000));
+        assert_eq!((reports[0].cash_in_total, reports[0].cash_out_total), (dec!(9000), dec!(12000)));
+        assert_eq!(reports[0].details.len(), 2);
+    }
+
+    #[test]
+    fn test_weekend_and_holiday_activity_rolls_into_next_business_day() {
+        let alice = Uuid::new_v4();
+        let monday = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        let calendar = BusinessCalendar::new([monday]); // Monday holiday: Saturday to Monday go to Tuesday
+        let movements = vec![movement(alice, CashDirection::In, dec!(5000), 8, 12), movement(alice, CashDirection::In, dec!(5500), 10, 12)];
+        let reports = aggregate(&movements, &calendar, dec!(10000));
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].business_date, NaiveDate::from_ymd_opt(2025, 3, 11).unwrap());
+        assert_eq!(calendar_days(&calendar, reports[0].business_date), (NaiveDate::from_ymd_opt(2025, 3, 8).unwrap(), reports[0].business_date));
+    }
+}
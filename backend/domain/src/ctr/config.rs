@@ -0,0 +1,135 @@
+// /home/inno/elights_jobes-research/backend/domain/src/ctr/config.rs
+// CTR settings, loaded from a JSON file: the threshold, which transaction types are cash (or cash-equivalent)
+// activity with their FinCEN activity type codes, and the filing institution named in every batch.
+use crate::config::{self, JsonConfig};
+use crate::error::DomainError;
+use crate::models::TransactionType;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+fn default_threshold() -> Decimal {
+    Decimal::new(10000, 0)
+}
+
+fn default_currency() -> String {
+    "USD".to_string()
+}
+
+/// Postal address of a party on the filing.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct PartyAddress {
+    pub street: String,
+    pub city: String,
+    pub state: String, // Two-letter state code
+    pub zip: String,
+    #[serde(default = "default_country")]
+    pub country: String,
+}
+
+fn default_country() -> String {
+    "US".to_string()
+}
+
+/// The institution filing the reports (also the transmitter and the place the transactions occurred).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FilerInfo {
+    pub name: String, // Legal name
+    pub ein: String, // Employer identification number, 9 digits
+    #[serde(default)]
+    pub rssd_id: Option<String>, // Federal Reserve RSSD number
+    pub primary_regulator_code: String, // FinCEN primary federal regulator code
+    pub transmitter_control_code: String, // TCC assigned by BSA E-Filing
+    pub address: PartyAddress,
+    pub phone: String,
+    pub contact_office: String, // Office to contact about the filing
+}
+
+/// CTR configuration.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CtrConfig {
+    #[serde(default)]
+    pub version: Option<String>, // Free text, logged when the configuration is loaded
+    #[serde(default = "default_threshold")]
+    pub threshold: Decimal, // A report is due when cash in or cash out is MORE than this
+    #[serde(default = "default_currency")]
+    pub currency: String, // Only activity in this currency is aggregated
+    #[serde(default)]
+    pub cash_in: BTreeMap<String, String>, // TransactionType credited to a customer -> activity type code (e.g. 55 deposit)
+    #[serde(default)]
+    pub cash_out: BTreeMap<String, String>, // TransactionType debited from a customer -> activity type code (e.g. 56 withdrawal)
+    pub filer: FilerInfo,
+}
+
+impl CtrConfig {
+    /// Activity type code of a transaction type, seen from the customer's side.
+    pub fn activity_code(&self, kind: &str, credited: bool) -> Option<&str> {
+        let codes = if credited { &self.cash_in } else { &self.cash_out };
+        codes.get(kind).map(String::as_str)
+    }
+}
+
+impl JsonConfig for CtrConfig {
+    const NAME: &'static str = "CTR configuration";
+
+    fn validate(&self) -> Result<(), DomainError> {
+        let invalid = |reason: String| DomainError::Configuration(format!("CTR configuration: {}", reason));
+        if self.threshold <= Decimal::ZERO {
+            return Err(invalid("threshold must be positive".to_string()));
+        }
+        if self.currency.len() != 3 {
+            return Err(invalid(format!("'{}' is not a currency code", self.currency)));
+        }
+        if self.cash_in.is_empty() && self.cash_out.is_empty() {
+            return Err(invalid("no cash transaction types configured".to_string()));
+        }
+        for (kind, code) in self.cash_in.iter().chain(self.cash_out.iter()) {
+            if TransactionType::parse(kind).is_none() {
+                return Err(invalid(format!("unknown transaction type '{}'", kind)));
+            }
+            if code.is_empty() || !code.chars().all(|c| c.is_ascii_digit()) {
+                return Err(invalid(format!("activity type code '{}' for {} is not numeric", code, kind)));
+            }
+        }
+        let filer = &self.filer;
+        if filer.name.trim().is_empty() || filer.transmitter_control_code.trim().is_empty() {
+            return Err(invalid("filer name and transmitter control code are required".to_string()));
+        }
+        if filer.ein.len() != 9 || !filer.ein.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid("filer EIN must be 9 digits".to_string()));
+        }
+        Ok(())
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "{} ({} cash-in and {} cash-out types)",
+            self.version.as_deref().unwrap_or("unversioned"),
+            self.cash_in.len(),
+            self.cash_out.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILER: &str = r#""filer": {"name": "Elights Bank", "ein": "123456789", "primary_regulator_code": "7",
+        "transmitter_control_code": "TBSATEST", "address": {"street": "1 Main St", "city": "Wilmington", "state": "DE",
+        "zip": "19801"}, "phone": "3025550100", "contact_office": "BSA Compliance"}"#;
+
+    #[test]
+    fn test_config_defaults_and_validation() {
+        let config = config::parse_json::<CtrConfig>(&format!(r#"{{"cash_in": {{"CheckDeposit": "55"}}, {}}}"#, FILER)).unwrap();
+        assert_eq!(config.threshold, Decimal::new(10000, 0));
+        assert_eq!(config.currency, "USD");
+        assert_eq!(config.filer.address.country, "US");
+        assert_eq!(config.activity_code("CheckDeposit", true), Some("55"));
+        assert_eq!(config.activity_code("CheckDeposit", false), None);
+
+        assert!(config::parse_json::<CtrConfig>(&format!(r#"{{"cash_in": {{"Cash": "55"}}, {}}}"#, FILER)).is_err());
+        assert!(config::parse_json::<CtrConfig>(&format!(r#"{{"cash_in": {{"CheckDeposit": "deposit"}}, {}}}"#, FILER)).is_err());
+        assert!(config::parse_json::<CtrConfig>(&format!(r#"{{{}}}"#, FILER)).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+// /home/inno/elights_jobes-research/backend/domain/src/ctr/exemptions.rs
+// Exempt persons (31 CFR 1020.315). Reports for a person exempt on the business day are still recorded, with
+// status EXEMPT and the exemption that applied, but never put in a batch. Exemptions are revoked, not deleted.
+use crate::error::DomainError;
+use crate::models::{AuditOutcome, AuditTargetType, CtrExemption, CtrExemptionType, NewCtrExemption};
+use crate::security::audit;
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::prelude::*;
+use serde_json::json;
+use uuid::Uuid;
+
+/// Input for `grant_exemption`.
+#[derive(Debug, Clone)]
+pub struct GrantExemption<'a> {
+    pub user_id: Uuid,
+    pub exemption_type: CtrExemptionType,
+    pub reason: &'a str,
+    pub effective_from: NaiveDate,
+    pub expires_on: Option<NaiveDate>,
+}
+
+pub fn grant_exemption(conn: &mut PgConnection, grant: &GrantExemption, granted_by: Uuid) -> Result<CtrExemption, DomainError> {
+    if grant.reason.trim().is_empty() {
+        return Err(DomainError::Validation("An exemption needs a reason".to_string()));
+    }
+    if grant.expires_on.is_some_and(|expires| expires < grant.effective_from) {
+        return Err(DomainError::Validation("An exemption cannot expire before it takes effect".to_string()));
+    }
+    if grant.user_id == granted_by {
+        return Err(DomainError::Authorization("Customers cannot exempt themselves".to_string()));
+    }
+    let exemption: CtrExemption = diesel::insert_into(crate::schema::ctr_exemptions::table)
+        .values(&NewCtrExemption {
+            user_id: grant.user_id,
+            exemption_type: grant.exemption_type.as_str(),
+            reason: grant.reason.trim(),
+            effective_from: grant.effective_from,
+            expires_on: grant.expires_on,
+            created_by: granted_by,
+        })
+        .get_result(conn)?;
+    audit::log_db_audit_event(
+        conn, Some(granted_by), &granted_by.to_string(), "CTR_EXEMPTION_GRANTED", Some(AuditTargetType::CtrReport),
+        Some(&exemption.exemption_id.to_string()), AuditOutcome::Success,
+        Some(json!({"user_id": exemption.user_id, "exemption_type": exemption.exemption_type, "reason": exemption.reason,
+            "effective_from": exemption.effective_from, "expires_on": exemption.expires_on})),
+        None,
+    )?;
+    log::info!("CTR exemption {} granted to user {} by {}", exemption.exemption_id, exemption.user_id, granted_by);
+    Ok(exemption)
+}
+
+pub fn revoke_exemption(
+    conn: &mut PgConnection,
+    exemption_id: Uuid,
+    revoked_by: Uuid,
+    now: DateTime<Utc>,
+) -> Result<CtrExemption, DomainError> {
+    use crate::schema::ctr_exemptions::dsl as ce;
+    conn.transaction(|conn| {
+        let exemption: CtrExemption = ce::ctr_exemptions
+            .find(exemption_id)
+            .for_update()
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| DomainError::NotFound(format!("CTR exemption {} not found", exemption_id)))?;
+        if exemption.revoked_at.is_some() {
+            return Err(DomainError::Validation(format!("CTR exemption {} is already revoked", exemption_id)));
+        }
+        let exemption: CtrExemption = diesel::update(ce::ctr_exemptions.find(exemption_id))
+            .set((ce::revoked_by.eq(Some(revoked_by)), ce::revoked_at.eq(Some(now))))
+            .get_result(conn)?;
+        audit::log_db_audit_event(
+            conn, Some(revoked_by), &revoked_by.to_string(), "CTR_EXEMPTION_REVOKED", Some(AuditTargetType::CtrReport),
+            Some(&exemption_id.to_string()), AuditOutcome::Success, Some(json!({"user_id": exemption.user_id})), None,
+        )?;
+        Ok(exemption)
+    })
+}
+
+/// Exemptions, all or one customer's, newest first.
+pub fn list_exemptions(conn: &mut PgConnection, user_id: Option<Uuid>) -> Result<Vec<CtrExemption>, DomainError> {
+    use crate::schema::ctr_exemptions::dsl as ce;
+    let mut query = ce::ctr_exemptions.into_boxed();
+    if let Some(user_id) = user_id {
+        query = query.filter(ce::user_id.eq(user_id));
+    }
+    Ok(query.order(ce::created_at.desc()).load(conn)?)
+}
+
+/// The exemption covering a customer on a business day, if any. Revocation applies from the day it happened.
+pub fn exemption_on(conn: &mut PgConnection, user_id: Uuid, date: NaiveDate) -> Result<Option<CtrExemption>, DomainError> {
+    use crate::schema::ctr_exemptions::dsl as ce;
+    let candidates: Vec<CtrExemption> = ce::ctr_exemptions
+        .filter(ce::user_id.eq(user_id))
+        .filter(ce::effective_from.le(date))
+        .filter(ce::expires_on.is_null().or(ce::expires_on.ge(date)))
+        .order(ce::effective_from.desc())
+        .load(conn)?;
+    Ok(candidates.into_iter().find(|e| e.revoked_at.map_or(true, |revoked| revoked.date_naive() > date)))
+}
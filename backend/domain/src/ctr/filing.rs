@@ -0,0 +1,421 @@
+// /home/inno/elights_jobes-research/backend/domain/src/ctr/filing.rs
+// CTR reporting job and filing workflow. A business day is aggregated once its activity is final (re-running it
+// refreshes reports not batched yet); PENDING reports are put into a FinCEN batch file, the file is marked
+// submitted once uploaded, and the acknowledgement sets each report ACCEPTED (with its BSA ID) or REJECTED.
+// Rejected reports go into the next batch.
+use super::aggregation::{self, CashDetail, CashDirection, CashMovement, CtrAggregate};
+use super::config::CtrConfig;
+use super::exemptions;
+use super::xml::{self, CtrAccount, CtrFiling, CtrPerson};
+use crate::error::DomainError;
+use crate::models::{
+    AuditOutcome, AuditTargetType, CtrBatch, CtrBatchStatus, CtrReport, CtrReportStatus, NewCtrBatch, NewCtrReport,
+    TransactionStatus, User,
+};
+use crate::payments::BusinessCalendar;
+use crate::security::audit;
+use crate::utils::{bigdecimal_to_decimal, decimal_to_bigdecimal};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const ACTOR: &str = "CTR_REPORTING";
+
+/// What aggregating one business day did.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct CtrRunSummary {
+    pub business_date: Option<NaiveDate>,
+    pub cash_transactions: usize,
+    pub reports_created: usize,
+    pub reports_updated: usize,
+    pub exempt: usize,
+    pub already_filed: usize, // Activity changed after the report was batched: needs an amendment
+}
+
+/// FinCEN's answer for one activity of a submitted batch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CtrAcknowledgement {
+    pub seq_num: i32, // Activity SeqNum in the batch file
+    pub bsa_id: Option<String>, // Assigned when accepted
+    #[serde(default)]
+    pub errors: Vec<String>, // Reasons, when rejected
+}
+
+fn to_json<T: Serialize>(value: &T, what: &str) -> Result<serde_json::Value, DomainError> {
+    serde_json::to_value(value).map_err(|e| DomainError::Internal(format!("Failed to serialize {}: {}", what, e)))
+}
+
+/// Cash movements in the configured currency created on [first_day, last_day] (UTC).
+pub fn load_cash_movements(
+    conn: &mut PgConnection,
+    config: &CtrConfig,
+    first_day: NaiveDate,
+    last_day: NaiveDate,
+) -> Result<Vec<CashMovement>, DomainError> {
+    use crate::schema::transactions::dsl as t;
+    use crate::schema::wallets::dsl as w;
+
+    let from = first_day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    let to = (last_day + Duration::days(1)).and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    let kinds: Vec<String> = config.cash_in.keys().chain(config.cash_out.keys()).cloned().collect();
+    type Row = (Uuid, Option<Uuid>, Option<Uuid>, String, BigDecimal, DateTime<Utc>);
+    let rows: Vec<Row> = t::transactions
+        .filter(t::transaction_type.eq_any(kinds))
+        .filter(t::currency_code.eq(&config.currency))
+        .filter(t::status.eq_any([TransactionStatus::Completed.to_string(), TransactionStatus::Settled.to_string()]))
+        .filter(t::created_at.ge(from).and(t::created_at.lt(to)))
+        .select((t::transaction_id, t::debit_wallet_id, t::credit_wallet_id, t::transaction_type, t::amount, t::created_at))
+        .load(conn)?;
+
+    let wallet_ids: Vec<Uuid> = rows.iter().flat_map(|row| [row.1, row.2]).flatten().collect();
+    let owners: HashMap<Uuid, Uuid> =
+        w::wallets.filter(w::wallet_id.eq_any(wallet_ids)).select((w::wallet_id, w::user_id)).load(conn)?.into_iter().collect();
+
+    let mut movements = Vec::new();
+    for (transaction_id, debit, credit, kind, amount, created_at) in rows {
+        let amount = bigdecimal_to_decimal(amount);
+        for (wallet, credited, direction) in [(credit, true, CashDirection::In), (debit, false, CashDirection::Out)] {
+            let (Some(wallet_id), Some(code)) = (wallet, config.activity_code(&kind, credited)) else { continue };
+            let Some(user_id) = owners.get(&wallet_id) else { continue };
+            movements.push(CashMovement {
+                transaction_id,
+                user_id: *user_id,
+                wallet_id,
+                direction,
+                activity_code: code.to_string(),
+                amount,
+                occurred_at: created_at,
+            });
+        }
+    }
+    Ok(movements)
+}
+
+fn store_aggregate(
+    conn: &mut PgConnection,
+    config: &CtrConfig,
+    aggregate: &CtrAggregate,
+    summary: &mut CtrRunSummary,
+) -> Result<(), DomainError> {
+    use crate::schema::ctr_reports::dsl as cr;
+
+    let exemption = exemptions::exemption_on(conn, aggregate.user_id, aggregate.business_date)?;
+    let status = if exemption.is_some() { CtrReportStatus::Exempt } else { CtrReportStatus::Pending };
+    let details = to_json(&aggregate.details, "CTR details")?;
+    let transaction_ids = json!(aggregate.transaction_ids);
+    let wallet_ids = json!(aggregate.wallet_ids);
+    let exemption_id = exemption.map(|e| e.exemption_id);
+
+    let existing: Option<CtrReport> = cr::ctr_reports
+        .filter(cr::user_id.eq(aggregate.user_id))
+        .filter(cr::business_date.eq(aggregate.business_date))
+        .filter(cr::currency_code.eq(&config.currency))
+        .for_update()
+        .first(conn)
+        .optional()?;
+    let report: CtrReport = match existing {
+        None => {
+            summary.reports_created += 1;
+            diesel::insert_into(cr::ctr_reports)
+                .values(&NewCtrReport {
+                    user_id: aggregate.user_id,
+                    business_date: aggregate.business_date,
+                    currency_code: &config.currency,
+                    cash_in_total: aggregate.cash_in_total,
+                    cash_out_total: aggregate.cash_out_total,
+                    details,
+                    transaction_ids,
+                    wallet_ids,
+                    status: status.as_str(),
+                    exemption_id,
+                })
+                .get_result(conn)?
+        }
+        Some(report) if matches!(CtrReportStatus::parse(&report.status), Some(CtrReportStatus::Pending | CtrReportStatus::Exempt)) => {
+            if report.transaction_ids == transaction_ids && report.status == status.as_str() {
+                return Ok(()); // Nothing changed since the last run
+            }
+            summary.reports_updated += 1;
+            diesel::update(cr::ctr_reports.find(report.report_id))
+                .set((
+                    cr::cash_in_total.eq(decimal_to_bigdecimal(aggregate.cash_in_total)),
+                    cr::cash_out_total.eq(decimal_to_bigdecimal(aggregate.cash_out_total)),
+                    cr::details.eq(details),
+                    cr::transaction_ids.eq(transaction_ids),
+                    cr::wallet_ids.eq(wallet_ids),
+                    cr::status.eq(status.as_str()),
+                    cr::exemption_id.eq(exemption_id),
+                ))
+                .get_result(conn)?
+        }
+        Some(report) => {
+            if report.transaction_ids != transaction_ids {
+                summary.already_filed += 1;
+                log::warn!(
+                    "CTR {} for user {} on {} is {} but its activity changed: file an amendment",
+                    report.report_id, report.user_id, report.business_date, report.status
+                );
+            }
+            return Ok(());
+        }
+    };
+    if status == CtrReportStatus::Exempt {
+        summary.exempt += 1;
+    }
+    audit::log_db_audit_event(
+        conn, Some(report.user_id), ACTOR, "CTR_REPORT_RECORDED", Some(AuditTargetType::CtrReport),
+        Some(&report.report_id.to_string()), AuditOutcome::Success,
+        Some(json!({"business_date": report.business_date, "status": report.status, "cash_in": report.cash_in_total.to_string(),
+            "cash_out": report.cash_out_total.to_string(), "transactions": report.transaction_ids})),
+        None,
+    )?;
+    Ok(())
+}
+
+/// Aggregates one business day and records a report for every person over the threshold.
+pub fn run_business_day(
+    conn: &mut PgConnection,
+    config: &CtrConfig,
+    calendar: &BusinessCalendar,
+    business_date: NaiveDate,
+) -> Result<CtrRunSummary, DomainError> {
+    if !calendar.is_business_day(business_date) {
+        return Err(DomainError::Validation(format!("{} is not a business day", business_date)));
+    }
+    let (first_day, last_day) = aggregation::calendar_days(calendar, business_date);
+    let movements = load_cash_movements(conn, config, first_day, last_day)?;
+    let mut summary = CtrRunSummary { business_date: Some(business_date), cash_transactions: movements.len(), ..Default::default() };
+    let aggregates = aggregation::aggregate(&movements, calendar, config.threshold);
+    conn.transaction(|conn| {
+        for aggregate in aggregates.iter().filter(|a| a.business_date == business_date) {
+            store_aggregate(conn, config, aggregate, &mut summary)?;
+        }
+        Ok::<_, DomainError>(())
+    })?;
+    log::info!("CTR run for {}: {:?}", business_date, summary);
+    Ok(summary)
+}
+
+/// Reports by status and business day range, oldest first.
+pub fn list_reports(
+    conn: &mut PgConnection,
+    status: Option<CtrReportStatus>,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> Result<Vec<CtrReport>, DomainError> {
+    use crate::schema::ctr_reports::dsl as cr;
+    let mut query = cr::ctr_reports.into_boxed();
+    if let Some(status) = status {
+        query = query.filter(cr::status.eq(status.as_str()));
+    }
+    if let Some(from) = from {
+        query = query.filter(cr::business_date.ge(from));
+    }
+    if let Some(to) = to {
+        query = query.filter(cr::business_date.le(to));
+    }
+    Ok(query.order((cr::business_date.asc(), cr::created_at.asc())).limit(1000).load(conn)?)
+}
+
+pub fn get_report(conn: &mut PgConnection, report_id: Uuid) -> Result<CtrReport, DomainError> {
+    use crate::schema::ctr_reports::dsl as cr;
+    cr::ctr_reports
+        .find(report_id)
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("CTR {} not found", report_id)))
+}
+
+pub fn list_batches(conn: &mut PgConnection) -> Result<Vec<CtrBatch>, DomainError> {
+    use crate::schema::ctr_batches::dsl as cb;
+    Ok(cb::ctr_batches.order(cb::created_at.desc()).limit(200).load(conn)?)
+}
+
+pub fn get_batch(conn: &mut PgConnection, batch_id: Uuid) -> Result<CtrBatch, DomainError> {
+    use crate::schema::ctr_batches::dsl as cb;
+    cb::ctr_batches
+        .find(batch_id)
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("CTR batch {} not found", batch_id)))
+}
+
+/// What the filing says about the person. Users hold no name, TIN, birth date or address, so those go out as
+/// unknown; the customer's wallets are the accounts involved.
+fn person_for(user: &User, report: &CtrReport) -> CtrPerson {
+    let wallet_ids: Vec<Uuid> = serde_json::from_value(report.wallet_ids.clone()).unwrap_or_default();
+    let details: Vec<CashDetail> = serde_json::from_value(report.details.clone()).unwrap_or_default();
+    let has = |direction: CashDirection| details.iter().any(|d: &CashDetail| d.direction == direction);
+    CtrPerson {
+        email: Some(user.email.clone()),
+        accounts: wallet_ids
+            .iter()
+            .map(|wallet_id| CtrAccount {
+                account_number: wallet_id.to_string(),
+                cash_in: has(CashDirection::In),
+                cash_out: has(CashDirection::Out),
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// Puts every PENDING (and previously REJECTED) report into a new batch file.
+pub fn generate_batch(
+    conn: &mut PgConnection,
+    config: &CtrConfig,
+    created_by: Uuid,
+    now: DateTime<Utc>,
+) -> Result<CtrBatch, DomainError> {
+    use crate::schema::ctr_batches::dsl as cb;
+    use crate::schema::ctr_reports::dsl as cr;
+    use crate::schema::users::dsl as u;
+
+    conn.transaction(|conn| {
+        let reports: Vec<CtrReport> = cr::ctr_reports
+            .filter(cr::status.eq_any([CtrReportStatus::Pending.as_str(), CtrReportStatus::Rejected.as_str()]))
+            .order((cr::business_date.asc(), cr::report_id.asc()))
+            .for_update()
+            .load(conn)?;
+        if reports.is_empty() {
+            return Err(DomainError::Validation("No CTRs are waiting to be filed".to_string()));
+        }
+        let user_ids: Vec<Uuid> = reports.iter().map(|r| r.user_id).collect();
+        let users: HashMap<Uuid, User> = u::users.filter(u::user_id.eq_any(user_ids)).load::<User>(conn)?.into_iter().map(|u| (u.user_id, u)).collect();
+
+        let mut filings = Vec::with_capacity(reports.len());
+        for report in &reports {
+            let user = users.get(&report.user_id).ok_or_else(|| DomainError::Internal(format!("CTR {} has no customer", report.report_id)))?;
+            let transactions: Vec<Uuid> = serde_json::from_value(report.transaction_ids.clone()).unwrap_or_default();
+            filings.push(CtrFiling {
+                report_id: report.report_id,
+                business_date: report.business_date,
+                cash_in_total: report.cash_in_total,
+                cash_out_total: report.cash_out_total,
+                details: serde_json::from_value(report.details.clone()).unwrap_or_default(),
+                multiple_transactions: transactions.len() > 1,
+                person: person_for(user, report),
+            });
+        }
+        let file = xml::build_batch_xml(&config.filer, &filings, now.date_naive())?;
+
+        let batch_id = Uuid::new_v4();
+        let file_name = format!("CTRX_{}_{}.xml", now.format("%Y%m%d%H%M%S"), &batch_id.simple().to_string()[..8]);
+        let batch: CtrBatch = diesel::insert_into(cb::ctr_batches)
+            .values(&NewCtrBatch {
+                batch_id,
+                file_name: &file_name,
+                xml: &file.xml,
+                report_count: reports.len() as i32,
+                total_amount: file.total_amount,
+                status: CtrBatchStatus::Generated.as_str(),
+                created_by,
+            })
+            .get_result(conn)?;
+        for (report_id, seq_num) in &file.activity_seq_nums {
+            diesel::update(cr::ctr_reports.find(*report_id))
+                .set((
+                    cr::status.eq(CtrReportStatus::Batched.as_str()),
+                    cr::batch_id.eq(Some(batch_id)),
+                    cr::batch_seq_num.eq(Some(*seq_num)),
+                    cr::rejection_reason.eq(None::<String>),
+                ))
+                .execute(conn)?;
+        }
+
+        audit::log_db_audit_event(
+            conn, Some(created_by), &created_by.to_string(), "CTR_BATCH_GENERATED", Some(AuditTargetType::CtrReport),
+            Some(&batch_id.to_string()), AuditOutcome::Success,
+            Some(json!({"file_name": batch.file_name, "reports": batch.report_count, "total_amount": batch.total_amount.to_string()})),
+            None,
+        )?;
+        log::info!("CTR batch {} generated with {} report(s)", batch.file_name, batch.report_count);
+        Ok(batch)
+    })
+}
+
+fn load_batch_for_update(conn: &mut PgConnection, batch_id: Uuid, expected: CtrBatchStatus) -> Result<CtrBatch, DomainError> {
+    use crate::schema::ctr_batches::dsl as cb;
+    let batch: CtrBatch = cb::ctr_batches
+        .find(batch_id)
+        .for_update()
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("CTR batch {} not found", batch_id)))?;
+    if batch.status != expected.as_str() {
+        return Err(DomainError::Validation(format!("CTR batch {} is {}, expected {}", batch_id, batch.status, expected.as_str())));
+    }
+    Ok(batch)
+}
+
+/// Records that the batch file was uploaded to BSA E-Filing.
+pub fn mark_batch_submitted(conn: &mut PgConnection, batch_id: Uuid, actor_id: Uuid, now: DateTime<Utc>) -> Result<CtrBatch, DomainError> {
+    use crate::schema::ctr_batches::dsl as cb;
+    use crate::schema::ctr_reports::dsl as cr;
+    conn.transaction(|conn| {
+        load_batch_for_update(conn, batch_id, CtrBatchStatus::Generated)?;
+        let batch: CtrBatch = diesel::update(cb::ctr_batches.find(batch_id))
+            .set((cb::status.eq(CtrBatchStatus::Submitted.as_str()), cb::submitted_at.eq(Some(now))))
+            .get_result(conn)?;
+        let filed = diesel::update(cr::ctr_reports.filter(cr::batch_id.eq(batch_id)).filter(cr::status.eq(CtrReportStatus::Batched.as_str())))
+            .set((cr::status.eq(CtrReportStatus::Filed.as_str()), cr::filed_at.eq(Some(now))))
+            .execute(conn)?;
+        audit::log_db_audit_event(
+            conn, Some(actor_id), &actor_id.to_string(), "CTR_BATCH_SUBMITTED", Some(AuditTargetType::CtrReport),
+            Some(&batch_id.to_string()), AuditOutcome::Success, Some(json!({"file_name": batch.file_name, "reports": filed})), None,
+        )?;
+        Ok(batch)
+    })
+}
+
+/// Applies FinCEN's acknowledgement of a submitted batch. Activities not mentioned stay FILED.
+pub fn record_acknowledgement(
+    conn: &mut PgConnection,
+    batch_id: Uuid,
+    acknowledgements: &[CtrAcknowledgement],
+    actor_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<CtrBatch, DomainError> {
+    use crate::schema::ctr_batches::dsl as cb;
+    use crate::schema::ctr_reports::dsl as cr;
+    conn.transaction(|conn| {
+        load_batch_for_update(conn, batch_id, CtrBatchStatus::Submitted)?;
+        let (mut accepted, mut rejected) = (0, 0);
+        for ack in acknowledgements {
+            let report: CtrReport = cr::ctr_reports
+                .filter(cr::batch_id.eq(batch_id))
+                .filter(cr::batch_seq_num.eq(ack.seq_num))
+                .first(conn)
+                .optional()?
+                .ok_or_else(|| DomainError::Validation(format!("No activity with SeqNum {} in CTR batch {}", ack.seq_num, batch_id)))?;
+            let target = cr::ctr_reports.find(report.report_id);
+            match (&ack.bsa_id, ack.errors.is_empty()) {
+                (Some(bsa_id), true) => {
+                    diesel::update(target).set((cr::status.eq(CtrReportStatus::Accepted.as_str()), cr::bsa_id.eq(Some(bsa_id)))).execute(conn)?;
+                    accepted += 1;
+                }
+                _ => {
+                    let reason = if ack.errors.is_empty() { "Rejected without a BSA ID".to_string() } else { ack.errors.join("; ") };
+                    diesel::update(target).set((cr::status.eq(CtrReportStatus::Rejected.as_str()), cr::rejection_reason.eq(Some(reason)))).execute(conn)?;
+                    rejected += 1;
+                }
+            }
+        }
+        let batch: CtrBatch = diesel::update(cb::ctr_batches.find(batch_id))
+            .set((cb::status.eq(CtrBatchStatus::Acknowledged.as_str()), cb::acknowledged_at.eq(Some(now))))
+            .get_result(conn)?;
+        let outcome = if rejected > 0 { AuditOutcome::Failure } else { AuditOutcome::Success };
+        audit::log_db_audit_event(
+            conn, Some(actor_id), &actor_id.to_string(), "CTR_BATCH_ACKNOWLEDGED", Some(AuditTargetType::CtrReport),
+            Some(&batch_id.to_string()), outcome, Some(json!({"accepted": accepted, "rejected": rejected})), None,
+        )?;
+        if rejected > 0 {
+            log::warn!("CTR batch {}: {} report(s) rejected, they go into the next batch", batch.file_name, rejected);
+        }
+        Ok(batch)
+    })
+}
@@ -0,0 +1,17 @@
+// /home/inno/elights_jobes-research/backend/domain/src/ctr/mod.rs
+// Currency Transaction Reports: cash (and configured cash-equivalent) activity over $10,000 per person per
+// business day, aggregated under the multiple-transaction rule and filed in FinCEN CTR batch XML files.
+
+pub mod config; // Threshold, cash transaction types and the filing institution
+pub mod aggregation; // Per-person, per-business-day totals under the multiple-transaction rule
+pub mod xml; // FinCEN CTR batch XML
+pub mod exemptions; // Exempt persons
+pub mod filing; // Daily reporting job, batch files, submission and acknowledgements
+
+pub use aggregation::{CashDetail, CashDirection, CashMovement, CtrAggregate};
+pub use config::{CtrConfig, FilerInfo, PartyAddress};
+pub use exemptions::{exemption_on, grant_exemption, list_exemptions, revoke_exemption, GrantExemption};
+pub use filing::{
+    generate_batch, get_batch, get_report, list_batches, list_reports, mark_batch_submitted, record_acknowledgement,
+    run_business_day, CtrAcknowledgement, CtrRunSummary,
+};
@@ -0,0 +1,389 @@
+// /home/inno/elights_jobes-research/backend/domain/src/ctr/xml.rs
+// FinCEN CTR batch XML (form type CTRX, EFL_CTRXBatchSchema). Each report is one Activity with the transmitter
+// (35), transmitter contact (37), reporting institution (30), contact office (8), the institution where the
+// transactions took place (34) and the person involved (50). Every element carrying a SeqNum gets a number
+// unique within the file; the acknowledgement refers to activities by theirs. Identity fields the platform
+// does not hold are sent with FinCEN's "unknown" indicators.
+use super::aggregation::{whole_dollars, CashDetail, CashDirection};
+use super::config::{FilerInfo, PartyAddress};
+use crate::error::DomainError;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+const PARTIES_PER_ACTIVITY: usize = 6;
+
+/// An account of the person involved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CtrAccount {
+    pub account_number: String,
+    pub cash_in: bool,
+    pub cash_out: bool,
+}
+
+/// The person the transactions were conducted by or for.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CtrPerson {
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub tin: Option<String>,
+    pub birth_date: Option<NaiveDate>,
+    pub address: Option<PartyAddress>,
+    pub email: Option<String>,
+    pub accounts: Vec<CtrAccount>,
+}
+
+/// One report to put in a batch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CtrFiling {
+    pub report_id: Uuid,
+    pub business_date: NaiveDate,
+    pub cash_in_total: Decimal,
+    pub cash_out_total: Decimal,
+    pub details: Vec<CashDetail>,
+    pub multiple_transactions: bool,
+    pub person: CtrPerson,
+}
+
+/// A generated batch file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CtrBatchFile {
+    pub xml: String,
+    pub total_amount: Decimal,
+    pub activity_seq_nums: Vec<(Uuid, i32)>, // Report id -> SeqNum of its Activity
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c if c.is_control() => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn date_text(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+fn amount_text(amount: Decimal) -> String {
+    whole_dollars(amount).trunc().to_string()
+}
+
+fn digits(value: &str) -> String {
+    value.chars().filter(|c| c.is_ascii_alphanumeric()).collect()
+}
+
+/// Indented element writer numbering SeqNum attributes.
+struct Writer {
+    out: String,
+    seq: i32,
+    depth: usize,
+}
+
+impl Writer {
+    fn indent(&mut self) {
+        for _ in 0..self.depth {
+            self.out.push_str("  ");
+        }
+    }
+
+    /// Opens an element with the next SeqNum and returns it.
+    fn open(&mut self, tag: &str) -> i32 {
+        self.seq += 1;
+        self.indent();
+        self.out.push_str(&format!("<fc2:{} SeqNum=\"{}\">\n", tag, self.seq));
+        self.depth += 1;
+        self.seq
+    }
+
+    fn close(&mut self, tag: &str) {
+        self.depth -= 1;
+        self.indent();
+        self.out.push_str(&format!("</fc2:{}>\n", tag));
+    }
+
+    fn leaf(&mut self, tag: &str, value: &str) {
+        self.indent();
+        self.out.push_str(&format!("<fc2:{}>{}</fc2:{}>\n", tag, escape(value), tag));
+    }
+
+    fn flag(&mut self, tag: &str) {
+        self.leaf(tag, "Y");
+    }
+
+    fn name(&mut self, full_name: &str) {
+        self.open("PartyName");
+        self.leaf("PartyNameTypeCode", "L");
+        self.leaf("RawPartyFullName", full_name);
+        self.close("PartyName");
+    }
+
+    fn address(&mut self, address: &PartyAddress) {
+        self.open("Address");
+        self.leaf("RawCityText", &address.city);
+        self.leaf("RawCountryCodeText", &address.country);
+        self.leaf("RawStateCodeText", &address.state);
+        self.leaf("RawStreetAddress1Text", &address.street);
+        self.leaf("RawZIPCode", &digits(&address.zip));
+        self.close("Address");
+    }
+
+    fn identification(&mut self, number: &str, type_code: &str) {
+        self.open("PartyIdentification");
+        self.leaf("PartyIdentificationNumberText", &digits(number));
+        self.leaf("PartyIdentificationTypeCode", type_code);
+        self.close("PartyIdentification");
+    }
+
+    fn institution(&mut self, filer: &FilerInfo) {
+        self.leaf("PrimaryRegulatorTypeCode", &filer.primary_regulator_code);
+        self.name(&filer.name);
+        self.address(&filer.address);
+        self.identification(&filer.ein, "2"); // EIN
+        if let Some(rssd_id) = &filer.rssd_id {
+            self.identification(rssd_id, "10"); // RSSD
+        }
+        self.open("OrganizationClassificationTypeSubtype");
+        self.leaf("OrganizationTypeID", "1"); // Depository institution
+        self.close("OrganizationClassificationTypeSubtype");
+    }
+
+    fn person(&mut self, filing: &CtrFiling) {
+        let person = &filing.person;
+        self.open("Party");
+        self.leaf("ActivityPartyTypeCode", "50");
+        match person.birth_date {
+            Some(date) => self.leaf("IndividualBirthDateText", &date_text(date)),
+            None => self.flag("BirthDateUnknownIndicator"),
+        }
+        self.leaf("IndividualEntityCashInAmountText", &amount_text(filing.cash_in_total));
+        self.leaf("IndividualEntityCashOutAmountText", &amount_text(filing.cash_out_total));
+
+        self.open("PartyName");
+        self.leaf("PartyNameTypeCode", "L");
+        match &person.last_name {
+            Some(last) => self.leaf("RawEntityIndividualLastName", last),
+            None => self.flag("EntityLastNameUnknownIndicator"),
+        }
+        match &person.first_name {
+            Some(first) => self.leaf("RawIndividualFirstName", first),
+            None => self.flag("FirstNameUnknownIndicator"),
+        }
+        self.close("PartyName");
+
+        match &person.address {
+            Some(address) => self.address(address),
+            None => {
+                self.open("Address");
+                self.flag("CityUnknownIndicator");
+                self.flag("StateCodeUnknownIndicator");
+                self.flag("StreetAddressUnknownIndicator");
+                self.flag("ZIPCodeUnknownIndicator");
+                self.close("Address");
+            }
+        }
+        match &person.tin {
+            Some(tin) => self.identification(tin, "1"), // SSN/ITIN
+            None => {
+                self.open("PartyIdentification");
+                self.flag("TINUnknownIndicator");
+                self.close("PartyIdentification");
+            }
+        }
+        if let Some(email) = &person.email {
+            self.open("ElectronicAddress");
+            self.leaf("ElectronicAddressText", email);
+            self.leaf("ElectronicAddressTypeCode", "E");
+            self.close("ElectronicAddress");
+        }
+        for account in &person.accounts {
+            let associations = [(account.cash_in, "8"), (account.cash_out, "9")]; // Cash in / cash out account
+            self.open("Account");
+            self.leaf("AccountNumberText", &account.account_number);
+            for (_, code) in associations.iter().filter(|(applies, _)| *applies) {
+                self.open("PartyAccountAssociation");
+                self.leaf("PartyAccountAssociationTypeCode", code);
+                self.close("PartyAccountAssociation");
+            }
+            self.close("Account");
+        }
+        self.close("Party");
+    }
+}
+
+/// Builds the batch file for `filings`, filed on `filing_date`.
+pub fn build_batch_xml(filer: &FilerInfo, filings: &[CtrFiling], filing_date: NaiveDate) -> Result<CtrBatchFile, DomainError> {
+    if filings.is_empty() {
+        return Err(DomainError::Validation("A CTR batch needs at least one report".to_string()));
+    }
+    let total_amount: Decimal =
+        filings.iter().map(|f| whole_dollars(f.cash_in_total) + whole_dollars(f.cash_out_total)).sum();
+
+    let mut w = Writer { out: String::new(), seq: 0, depth: 1 };
+    w.out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    w.out.push_str(&format!(
+        "<fc2:EFilingBatchXML xmlns:fc2=\"www.fincen.gov/base\" xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"www.fincen.gov/base https://www.fincen.gov/base/EFL_CTRXBatchSchema.xsd\" \
+         ActivityCount=\"{}\" TotalAmount=\"{}\" PartyCount=\"{}\">\n",
+        filings.len(),
+        total_amount.trunc(),
+        filings.len() * PARTIES_PER_ACTIVITY
+    ));
+    w.leaf("FormTypeCode", "CTRX");
+
+    let mut activity_seq_nums = Vec::with_capacity(filings.len());
+    for filing in filings {
+        let seq = w.open("Activity");
+        activity_seq_nums.push((filing.report_id, seq));
+        w.leaf("FilingDateText", &date_text(filing_date));
+        w.open("ActivityAssociation");
+        w.flag("InitialReportIndicator");
+        w.close("ActivityAssociation");
+
+        // Transmitter and its contact
+        w.open("Party");
+        w.leaf("ActivityPartyTypeCode", "35");
+        w.name(&filer.name);
+        w.address(&filer.address);
+        w.open("PhoneNumber");
+        w.leaf("PhoneNumberText", &digits(&filer.phone));
+        w.close("PhoneNumber");
+        w.identification(&filer.ein, "4"); // Transmitter TIN
+        w.identification(&filer.transmitter_control_code, "28"); // TCC
+        w.close("Party");
+        w.open("Party");
+        w.leaf("ActivityPartyTypeCode", "37");
+        w.name(&filer.contact_office);
+        w.close("Party");
+
+        // Reporting institution and contact office
+        w.open("Party");
+        w.leaf("ActivityPartyTypeCode", "30");
+        w.institution(filer);
+        w.close("Party");
+        w.open("Party");
+        w.leaf("ActivityPartyTypeCode", "8");
+        w.name(&filer.contact_office);
+        w.open("PhoneNumber");
+        w.leaf("PhoneNumberText", &digits(&filer.phone));
+        w.close("PhoneNumber");
+        w.close("Party");
+
+        // Where the transactions took place, with the cash handled there
+        w.open("Party");
+        w.leaf("ActivityPartyTypeCode", "34");
+        w.leaf("IndividualEntityCashInAmountText", &amount_text(filing.cash_in_total));
+        w.leaf("IndividualEntityCashOutAmountText", &amount_text(filing.cash_out_total));
+        w.institution(filer);
+        w.close("Party");
+
+        w.person(filing);
+
+        w.open("CurrencyTransactionActivity");
+        if filing.multiple_transactions {
+            w.flag("AggregateTransactionIndicator");
+        }
+        w.leaf("TotalCashInReceiveAmountText", &amount_text(filing.cash_in_total));
+        w.leaf("TotalCashOutAmountText", &amount_text(filing.cash_out_total));
+        w.leaf("TransactionDateText", &date_text(filing.business_date));
+        for detail in filing.details.iter().filter(|d| d.direction == CashDirection::In).chain(filing.details.iter().filter(|d| d.direction == CashDirection::Out)) {
+            w.open("CurrencyTransactionActivityDetail");
+            w.leaf("CurrencyTransactionActivityDetailTypeCode", &detail.activity_code);
+            w.leaf("DetailTransactionAmountText", &amount_text(detail.amount));
+            w.close("CurrencyTransactionActivityDetail");
+        }
+        w.close("CurrencyTransactionActivity");
+        w.close("Activity");
+    }
+    w.out.push_str("</fc2:EFilingBatchXML>\n");
+    Ok(CtrBatchFile { xml: w.out, total_amount, activity_seq_nums })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn filer() -> FilerInfo {
+        FilerInfo {
+            name: "Elights Bank & Trust".to_string(),
+            ein: "12-3456789".to_string(),
+            rssd_id: None,
+            primary_regulator_code: "7".to_string(),
+            transmitter_control_code: "TBSATEST".to_string(),
+            address: PartyAddress {
+                street: "1 Main St".to_string(),
+                city: "Wilmington".to_string(),
+                state: "DE".to_string(),
+                zip: "19801".to_string(),
+                country: "US".to_string(),
+            },
+            phone: "(302) 555-0100".to_string(),
+            contact_office: "BSA Compliance".to_string(),
+        }
+    }
+
+    fn filing(cash_in: Decimal, cash_out: Decimal) -> CtrFiling {
+        let mut details = vec![CashDetail { direction: CashDirection::In, activity_code: "55".to_string(), amount: cash_in, count: 2 }];
+        if cash_out > Decimal::ZERO {
+            details.push(CashDetail { direction: CashDirection::Out, activity_code: "56".to_string(), amount: cash_out, count: 1 });
+        }
+        CtrFiling {
+            report_id: Uuid::new_v4(),
+            business_date: NaiveDate::from_ymd_opt(2025, 3, 7).unwrap(),
+            cash_in_total: cash_in,
+            cash_out_total: cash_out,
+            details,
+            multiple_transactions: true,
+            person: CtrPerson {
+                email: Some("alice@example.com".to_string()),
+                accounts: vec![CtrAccount { account_number: "W-1".to_string(), cash_in: true, cash_out: false }],
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_batch_header_counts_and_sequence_numbers() {
+        let filings = vec![filing(dec!(10000.01), Decimal::ZERO), filing(dec!(12000), dec!(500.50))];
+        let file = build_batch_xml(&filer(), &filings, NaiveDate::from_ymd_opt(2025, 3, 10).unwrap()).unwrap();
+        assert_eq!(file.total_amount, dec!(22502)); // 10001 + 12000 + 501
+
+        let doc = roxmltree::Document::parse(&file.xml).unwrap();
+        let root = doc.root_element();
+        assert_eq!(root.attribute("ActivityCount"), Some("2"));
+        assert_eq!(root.attribute("TotalAmount"), Some("22502"));
+        assert_eq!(root.attribute("PartyCount"), Some("12"));
+        let parties = root.descendants().filter(|n| n.tag_name().name() == "Party").count();
+        assert_eq!(parties, 12);
+
+        let seq_nums: Vec<&str> = root.descendants().filter_map(|n| n.attribute("SeqNum")).collect();
+        let unique: std::collections::HashSet<&str> = seq_nums.iter().copied().collect();
+        assert_eq!(seq_nums.len(), unique.len());
+        assert_eq!(file.activity_seq_nums[0].1, 1);
+        assert_eq!(file.activity_seq_nums[0].0, filings[0].report_id);
+    }
+
+    #[test]
+    fn test_amounts_names_and_unknown_indicators() {
+        let file = build_batch_xml(&filer(), &[filing(dec!(10000.01), Decimal::ZERO)], NaiveDate::from_ymd_opt(2025, 3, 10).unwrap()).unwrap();
+        let doc = roxmltree::Document::parse(&file.xml).unwrap();
+        let text = |tag: &str| doc.descendants().find(|n| n.tag_name().name() == tag).and_then(|n| n.text()).map(str::to_string);
+        assert_eq!(text("TotalCashInReceiveAmountText").as_deref(), Some("10001"));
+        assert_eq!(text("TransactionDateText").as_deref(), Some("20250307"));
+        assert_eq!(text("FilingDateText").as_deref(), Some("20250310"));
+        assert_eq!(text("RawPartyFullName").as_deref(), Some("Elights Bank & Trust")); // Escaped in the file
+        assert_eq!(text("PartyIdentificationNumberText").as_deref(), Some("123456789"));
+        assert_eq!(text("TINUnknownIndicator").as_deref(), Some("Y"));
+        assert_eq!(text("ElectronicAddressText").as_deref(), Some("alice@example.com"));
+        assert!(file.xml.contains("Elights Bank &amp; Trust"));
+    }
+}
@@ -52,9 +52,29 @@ pub enum DomainError {
     #[error("External service error: {0}")]
     ExternalService(String),
 
+    #[error("Idempotency conflict: {0}")]
+    IdempotencyConflict(String), // Key reused with a different request or still in flight
+
+    #[error("Invalid state transition for {transaction_type} transaction: {from} -> {to}")]
+    InvalidStateTransition {
+        transaction_type: String,
+        from: String,
+        to: String,
+    },
+
+    #[error("Quote {0} has expired")]
+    QuoteExpired(uuid::Uuid), // Locked rate no longer valid, request a new quote
+
     #[error("Insufficient funds: Wallet ID {0}")]
     InsufficientFunds(uuid::Uuid),
 
+    #[error("Limit {limit_id} exceeded: {detail}")]
+    LimitExceeded {
+        limit_id: String, // Rule id in the limits policy
+        detail: String, // Limit, usage and when it resets
+        resets_at: Option<chrono::DateTime<chrono::Utc>>, // None for per-transaction limits
+    },
+
     #[error("Internal error: {0}")]
     Internal(String),
 
@@ -0,0 +1,223 @@
+// /home/inno/elights_jobes-research/backend/domain/src/fees/engine.rs
+// Charging fees. A fee is its own `Fee` transaction linked to the payment it was charged for
+// (metadata.parent_transaction_id); completing it posts Dr customer wallet / Cr FEE_INCOME:<ccy>.
+// Revenue is read back from the FEE_INCOME ledger accounts, so reversed fees net out automatically.
+use super::schedule::{ChargeBearer, FeeQuote, FeeQuoteRequest, FeeRail, FeeSchedule};
+use crate::error::DomainError;
+use crate::models::{NewTransaction, Transaction, TransactionStatus, TransactionType, User};
+use crate::payments::state_machine::{self, TransitionUpdate};
+use crate::utils::bigdecimal_to_decimal;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable, Numeric, Timestamptz, Varchar};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::json;
+use uuid::Uuid;
+
+/// Metadata key linking a fee transaction to its payment.
+pub const PARENT_TRANSACTION_KEY: &str = "parent_transaction_id";
+
+/// Pricing tier of a customer.
+pub fn customer_tier(conn: &mut PgConnection, user_id: Uuid) -> Result<String, DomainError> {
+    let user: User = crate::schema::users::table
+        .find(user_id)
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("User {} not found", user_id)))?;
+    Ok(user.fee_tier)
+}
+
+/// Quotes a payment of `transaction_type` for `user_id` at the customer's tier.
+/// Transaction types that are not priced (fees themselves) quote at zero.
+pub fn quote_for_user(
+    conn: &mut PgConnection,
+    schedule: &FeeSchedule,
+    user_id: Uuid,
+    transaction_type: &TransactionType,
+    amount: Decimal,
+    currency: &str,
+    charge_bearer: ChargeBearer,
+) -> Result<Option<FeeQuote>, DomainError> {
+    let Some(rail) = FeeRail::for_transaction_type(transaction_type) else {
+        return Ok(None);
+    };
+    let customer_tier = customer_tier(conn, user_id)?;
+    let quote = schedule.quote(&FeeQuoteRequest {
+        rail,
+        amount,
+        currency: currency.to_string(),
+        customer_tier,
+        charge_bearer,
+    })?;
+    Ok(Some(quote))
+}
+
+/// Charges `quote` against the debit wallet of `parent`. Returns `None` when there is nothing to charge
+/// (zero fee, or no internal wallet to take it from). Must run in the same DB transaction as the payment.
+pub fn post_fee(
+    conn: &mut PgConnection,
+    parent: &Transaction,
+    quote: &FeeQuote,
+    actor: &str,
+) -> Result<Option<Transaction>, DomainError> {
+    if quote.fee_amount <= Decimal::ZERO {
+        return Ok(None);
+    }
+    let Some(wallet_id) = parent.debit_wallet_id else {
+        log::warn!("Fee {} {} for Tx {} not charged: no debit wallet", quote.fee_amount, quote.currency, parent.transaction_id);
+        return Ok(None);
+    };
+    if quote.currency != parent.currency_code {
+        return Err(DomainError::Validation(format!(
+            "Fee currency {} does not match payment currency {}", quote.currency, parent.currency_code
+        )));
+    }
+
+    let description = format!("{} fee ({})", quote.rail.as_str(), quote.charge_bearer.as_str());
+    let new_tx = NewTransaction {
+        transaction_id: None,
+        debit_wallet_id: Some(wallet_id),
+        credit_wallet_id: None, // Credited to FEE_INCOME by the ledger postings
+        transaction_type: TransactionType::Fee.to_string().as_str(),
+        status: TransactionStatus::Pending.to_string().as_str(),
+        amount: quote.fee_amount,
+        currency_code: &quote.currency,
+        description: Some(&description),
+        external_ref_id: None,
+        metadata: Some(json!({
+            PARENT_TRANSACTION_KEY: parent.transaction_id,
+            "fee_rule_id": quote.rule_id,
+            "rail": quote.rail.as_str(),
+            "charge_bearer": quote.charge_bearer.as_str(),
+            "payment_amount": quote.amount.to_string(),
+        })),
+        scheduled_run_id: None,
+    };
+    let fee_tx: Transaction = diesel::insert_into(crate::schema::transactions::table)
+        .values(&new_tx)
+        .get_result(conn)?;
+    let fee_tx = state_machine::apply_transition(conn, &fee_tx, TransactionStatus::Completed, TransitionUpdate::default(), actor)?;
+    log::info!("Charged fee {} {} (rule {:?}) for Tx {}", quote.fee_amount, quote.currency, quote.rule_id, parent.transaction_id);
+    Ok(Some(fee_tx))
+}
+
+/// Fee transactions charged for `parent_transaction_id`.
+pub fn linked_fees(conn: &mut PgConnection, parent_transaction_id: Uuid) -> Result<Vec<Transaction>, DomainError> {
+    use crate::schema::transactions::dsl as t;
+    let parent_id = parent_transaction_id.to_string();
+    let fees = t::transactions
+        .filter(t::transaction_type.eq(TransactionType::Fee.to_string()))
+        .filter(diesel::dsl::sql::<diesel::sql_types::Bool>("metadata->>'parent_transaction_id' = ")
+            .bind::<Varchar, _>(parent_id))
+        .order(t::created_at.asc())
+        .for_update()
+        .load::<Transaction>(conn)?;
+    Ok(fees)
+}
+
+/// Refunds the completed fees of a payment that was never executed. Returns how many were reversed.
+pub fn reverse_linked_fees(
+    conn: &mut PgConnection,
+    parent_transaction_id: Uuid,
+    reason: &str,
+    actor: &str,
+) -> Result<usize, DomainError> {
+    let mut reversed = 0;
+    for fee_tx in linked_fees(conn, parent_transaction_id)? {
+        if fee_tx.status != TransactionStatus::Completed.to_string() {
+            continue; // Already refunded
+        }
+        let update = TransitionUpdate {
+            metadata: Some(json!({"fee_refund": {"reason": reason, "refunded_at": Utc::now()}})),
+            ..Default::default()
+        };
+        state_machine::apply_transition(conn, &fee_tx, TransactionStatus::Returned, update, actor)?;
+        reversed += 1;
+    }
+    if reversed > 0 {
+        log::info!("Refunded {} fee(s) for Tx {}: {}", reversed, parent_transaction_id, reason);
+    }
+    Ok(reversed)
+}
+
+/// Fee income for one currency and rail.
+#[derive(Debug, Clone, Serialize)]
+pub struct FeeRevenueRow {
+    pub currency_code: String,
+    pub rail: String,
+    pub fees_charged: i64, // Fee transactions credited in the period
+    #[serde(with = "rust_decimal::serde::str")]
+    pub gross_revenue: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub refunded: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub net_revenue: Decimal,
+}
+
+/// Fee income for `[from, to)`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FeeRevenueReport {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub rows: Vec<FeeRevenueRow>,
+}
+
+#[derive(QueryableByName)]
+struct FeeRevenueSqlRow {
+    #[diesel(sql_type = Varchar)]
+    currency_code: String,
+    #[diesel(sql_type = Varchar)]
+    rail: String,
+    #[diesel(sql_type = BigInt)]
+    fees_charged: i64,
+    #[diesel(sql_type = Numeric)]
+    gross_revenue: BigDecimal,
+    #[diesel(sql_type = Numeric)]
+    refunded: BigDecimal,
+}
+
+/// Sums FEE_INCOME postings in `[from, to)` per currency and rail, optionally for one currency.
+pub fn fee_revenue(
+    conn: &mut PgConnection,
+    currency: Option<&str>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Result<FeeRevenueReport, DomainError> {
+    let raw: Vec<FeeRevenueSqlRow> = diesel::sql_query(
+        "SELECT jl.currency_code, \
+                COALESCE(t.metadata->>'rail', 'UNKNOWN')::varchar AS rail, \
+                COUNT(DISTINCT je.transaction_id) FILTER (WHERE jl.direction = 'CREDIT') AS fees_charged, \
+                COALESCE(SUM(jl.amount) FILTER (WHERE jl.direction = 'CREDIT'), 0) AS gross_revenue, \
+                COALESCE(SUM(jl.amount) FILTER (WHERE jl.direction = 'DEBIT'), 0) AS refunded \
+         FROM core_schema.journal_lines jl \
+         JOIN core_schema.ledger_accounts la ON la.account_id = jl.account_id \
+         JOIN core_schema.journal_entries je ON je.entry_id = jl.entry_id \
+         LEFT JOIN core_schema.transactions t ON t.transaction_id = je.transaction_id \
+         WHERE la.category = 'FEE_INCOME' \
+           AND ($1::varchar IS NULL OR jl.currency_code = $1) \
+           AND ($2::timestamptz IS NULL OR jl.posted_at >= $2) \
+           AND ($3::timestamptz IS NULL OR jl.posted_at < $3) \
+         GROUP BY 1, 2 \
+         ORDER BY 1, 2",
+    )
+    .bind::<Nullable<Varchar>, _>(currency)
+    .bind::<Nullable<Timestamptz>, _>(from)
+    .bind::<Nullable<Timestamptz>, _>(to)
+    .load(conn)?;
+
+    let rows = raw.into_iter().map(|row| {
+        let gross_revenue = bigdecimal_to_decimal(row.gross_revenue);
+        let refunded = bigdecimal_to_decimal(row.refunded);
+        FeeRevenueRow {
+            currency_code: row.currency_code,
+            rail: row.rail,
+            fees_charged: row.fees_charged,
+            gross_revenue,
+            refunded,
+            net_revenue: gross_revenue - refunded,
+        }
+    }).collect();
+    Ok(FeeRevenueReport { from, to, rows })
+}
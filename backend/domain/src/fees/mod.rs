@@ -0,0 +1,14 @@
+// /home/inno/elights_jobes-research/backend/domain/src/fees/mod.rs
+// Fee schedules and charging. `schedule` prices a payment from config (pure); `engine` charges the
+// quoted fee as a linked `Fee` transaction, refunds it when the payment is compensated and reports revenue.
+
+pub mod schedule; // Fee rules (flat / percentage / tiered, min/max) and quoting
+pub mod engine; // Fee transactions, refunds and revenue reporting
+
+pub use schedule::{
+    minor_units, ChargeBearer, FeeCalculation, FeeQuote, FeeQuoteRequest, FeeRail, FeeRule, FeeSchedule, FeeTier,
+};
+pub use engine::{
+    customer_tier, quote_for_user, post_fee, linked_fees, reverse_linked_fees, fee_revenue, FeeRevenueReport,
+    FeeRevenueRow, PARENT_TRANSACTION_KEY,
+};
@@ -0,0 +1,391 @@
+// /home/inno/elights_jobes-research/backend/domain/src/fees/schedule.rs
+// Fee schedule: rules keyed by rail, currency, customer tier and charge bearer, loaded from a JSON file.
+// A rule left unset on a dimension matches any value; the most specific matching rule wins and ties go to
+// the rule listed first, so a schedule reads as "specific exceptions, then catch-all defaults".
+use crate::config::{self, JsonConfig};
+use crate::error::DomainError;
+use crate::models::TransactionType;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Payment rail a fee applies to.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FeeRail {
+    Ach,
+    Wire,
+    Card,
+    Check,
+    Rtgs,
+    Crypto,
+    Internal,
+    Conversion,
+}
+
+impl FeeRail {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeeRail::Ach => "ACH",
+            FeeRail::Wire => "WIRE",
+            FeeRail::Card => "CARD",
+            FeeRail::Check => "CHECK",
+            FeeRail::Rtgs => "RTGS",
+            FeeRail::Crypto => "CRYPTO",
+            FeeRail::Internal => "INTERNAL",
+            FeeRail::Conversion => "CONVERSION",
+        }
+    }
+
+    /// Rail a transaction of `transaction_type` is priced on. Fees themselves are never charged a fee.
+    pub fn for_transaction_type(transaction_type: &TransactionType) -> Option<FeeRail> {
+        use TransactionType::*;
+        Some(match transaction_type {
+            AchCredit | AchDebit | AchInbound => FeeRail::Ach,
+            WireOutbound | WireInbound => FeeRail::Wire,
+            CardAuthorization | CardCapture | CardRefund | CardChargeback => FeeRail::Card,
+            CheckDeposit | CheckWithdrawal => FeeRail::Check,
+            RtgsCreditTransfer | RtgsDirectDebit | RtgsReturn => FeeRail::Rtgs,
+            CryptoBtcSend | CryptoBtcReceive | CryptoXmrSend | CryptoXmrReceive => FeeRail::Crypto,
+            InternalTransfer => FeeRail::Internal,
+            Conversion => FeeRail::Conversion,
+            Fee | InterestCredit | WithholdingTax | RtgsStatusUpdate | Unknown => return None,
+        })
+    }
+}
+
+/// Who pays the charges (SWIFT field 71A).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ChargeBearer {
+    Our, // Sender pays all charges, the beneficiary receives the full amount
+    #[default]
+    Sha, // Shared: sender pays our fee, the beneficiary pays the intermediaries'
+    Ben, // Beneficiary pays: our fee is deducted from the amount sent
+}
+
+impl ChargeBearer {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChargeBearer::Our => "OUR",
+            ChargeBearer::Sha => "SHA",
+            ChargeBearer::Ben => "BEN",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<ChargeBearer> {
+        match value {
+            "OUR" => Some(ChargeBearer::Our),
+            "SHA" => Some(ChargeBearer::Sha),
+            "BEN" => Some(ChargeBearer::Ben),
+            _ => None,
+        }
+    }
+}
+
+/// One band of a tiered fee. Bands are ordered by `up_to`; the last one is open-ended.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FeeTier {
+    pub up_to: Option<Decimal>, // Inclusive upper bound of the amount, None = no limit
+    #[serde(default)]
+    pub flat: Decimal,
+    #[serde(default)]
+    pub percent: Decimal, // 0.25 = 0.25% of the amount
+}
+
+/// How the fee is derived from the payment amount. The band of a tiered fee is chosen by the whole amount.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FeeCalculation {
+    Flat { amount: Decimal },
+    Percentage { percent: Decimal },
+    Tiered { tiers: Vec<FeeTier> },
+}
+
+/// One line of the schedule.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FeeRule {
+    pub id: String, // Stable identifier, stored on the fee transaction
+    pub rail: FeeRail,
+    #[serde(default)]
+    pub currency: Option<String>, // None = any currency
+    #[serde(default)]
+    pub customer_tier: Option<String>, // None = any tier
+    #[serde(default)]
+    pub charge_bearer: Option<ChargeBearer>, // None = any bearer
+    pub calculation: FeeCalculation,
+    #[serde(default)]
+    pub min_fee: Option<Decimal>,
+    #[serde(default)]
+    pub max_fee: Option<Decimal>,
+}
+
+impl FeeRule {
+    fn matches(&self, rail: FeeRail, currency: &str, customer_tier: &str, charge_bearer: ChargeBearer) -> bool {
+        self.rail == rail
+            && self.currency.as_deref().map_or(true, |c| c.eq_ignore_ascii_case(currency))
+            && self.customer_tier.as_deref().map_or(true, |t| t.eq_ignore_ascii_case(customer_tier))
+            && self.charge_bearer.map_or(true, |b| b == charge_bearer)
+    }
+
+    /// Number of dimensions pinned by the rule.
+    fn specificity(&self) -> usize {
+        [self.currency.is_some(), self.customer_tier.is_some(), self.charge_bearer.is_some()]
+            .iter()
+            .filter(|pinned| **pinned)
+            .count()
+    }
+
+    /// Fee for `amount`, clamped to the rule's minimum/maximum and rounded to the currency's minor unit.
+    pub fn compute(&self, amount: Decimal, currency: &str) -> Decimal {
+        let hundred = Decimal::ONE_HUNDRED;
+        let raw = match &self.calculation {
+            FeeCalculation::Flat { amount: flat } => *flat,
+            FeeCalculation::Percentage { percent } => amount * *percent / hundred,
+            FeeCalculation::Tiered { tiers } => tiers
+                .iter()
+                .find(|tier| tier.up_to.map_or(true, |limit| amount <= limit))
+                .map(|tier| tier.flat + amount * tier.percent / hundred)
+                .unwrap_or(Decimal::ZERO),
+        };
+        let mut fee = raw;
+        if let Some(min_fee) = self.min_fee {
+            fee = fee.max(min_fee);
+        }
+        if let Some(max_fee) = self.max_fee {
+            fee = fee.min(max_fee);
+        }
+        fee.round_dp(minor_units(currency))
+    }
+}
+
+/// Decimal places fees are rounded to.
+pub fn minor_units(currency: &str) -> u32 {
+    match currency.to_uppercase().as_str() {
+        "BTC" | "XMR" | "ETH" => 8,
+        "JPY" | "KRW" | "VND" | "CLP" | "ISK" => 0,
+        "BHD" | "KWD" | "OMR" | "JOD" | "TND" => 3,
+        _ => 2,
+    }
+}
+
+/// The full set of fee rules in force.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct FeeSchedule {
+    #[serde(default)]
+    pub version: Option<String>, // Free text, logged when the schedule is loaded
+    pub rules: Vec<FeeRule>,
+}
+
+impl FeeSchedule {
+    /// Most specific rule matching the payment, first listed on a tie.
+    pub fn find_rule(
+        &self,
+        rail: FeeRail,
+        currency: &str,
+        customer_tier: &str,
+        charge_bearer: ChargeBearer,
+    ) -> Option<&FeeRule> {
+        let mut best: Option<&FeeRule> = None;
+        for rule in self.rules.iter().filter(|r| r.matches(rail, currency, customer_tier, charge_bearer)) {
+            if best.map_or(true, |b| rule.specificity() > b.specificity()) {
+                best = Some(rule);
+            }
+        }
+        best
+    }
+
+    /// Prices a payment. Without a matching rule the payment is free.
+    pub fn quote(&self, request: &FeeQuoteRequest) -> Result<FeeQuote, DomainError> {
+        if request.amount <= Decimal::ZERO {
+            return Err(DomainError::Validation("Amount must be positive".to_string()));
+        }
+        let rule = self.find_rule(request.rail, &request.currency, &request.customer_tier, request.charge_bearer);
+        let fee_amount = rule.map(|r| r.compute(request.amount, &request.currency)).unwrap_or(Decimal::ZERO);
+
+        // BEN: the fee comes out of the amount sent; OUR/SHA: it is charged on top
+        let (total_debit, net_amount) = match request.charge_bearer {
+            ChargeBearer::Ben => {
+                if fee_amount >= request.amount {
+                    return Err(DomainError::Validation(format!(
+                        "Fee {} {} would consume the whole amount", fee_amount, request.currency
+                    )));
+                }
+                (request.amount, request.amount - fee_amount)
+            }
+            ChargeBearer::Our | ChargeBearer::Sha => (request.amount + fee_amount, request.amount),
+        };
+
+        Ok(FeeQuote {
+            rule_id: rule.map(|r| r.id.clone()),
+            rail: request.rail,
+            currency: request.currency.clone(),
+            amount: request.amount,
+            fee_amount,
+            charge_bearer: request.charge_bearer,
+            total_debit,
+            net_amount,
+        })
+    }
+}
+
+impl JsonConfig for FeeSchedule {
+    const NAME: &'static str = "fee schedule";
+
+    /// Rejects schedules that would price inconsistently: duplicate ids, negative amounts,
+    /// min above max, or tier bands out of order.
+    fn validate(&self) -> Result<(), DomainError> {
+        let mut ids = HashSet::new();
+        for rule in &self.rules {
+            let invalid = |reason: &str| DomainError::Configuration(format!("Fee rule '{}': {}", rule.id, reason));
+            if rule.id.trim().is_empty() {
+                return Err(DomainError::Configuration("Fee rule with an empty id".to_string()));
+            }
+            if !ids.insert(rule.id.as_str()) {
+                return Err(invalid("duplicate id"));
+            }
+            if rule.min_fee.map_or(false, |m| m < Decimal::ZERO) || rule.max_fee.map_or(false, |m| m < Decimal::ZERO) {
+                return Err(invalid("min_fee/max_fee must not be negative"));
+            }
+            if let (Some(min_fee), Some(max_fee)) = (rule.min_fee, rule.max_fee) {
+                if min_fee > max_fee {
+                    return Err(invalid("min_fee is above max_fee"));
+                }
+            }
+            match &rule.calculation {
+                FeeCalculation::Flat { amount } if *amount < Decimal::ZERO => return Err(invalid("negative flat fee")),
+                FeeCalculation::Percentage { percent } if *percent < Decimal::ZERO || *percent > Decimal::ONE_HUNDRED => {
+                    return Err(invalid("percent must be between 0 and 100"));
+                }
+                FeeCalculation::Tiered { tiers } => {
+                    if tiers.is_empty() {
+                        return Err(invalid("tiered fee without tiers"));
+                    }
+                    let mut previous: Option<Decimal> = None;
+                    for (index, tier) in tiers.iter().enumerate() {
+                        if tier.flat < Decimal::ZERO || tier.percent < Decimal::ZERO {
+                            return Err(invalid("negative tier fee"));
+                        }
+                        match tier.up_to {
+                            Some(limit) => {
+                                if previous.map_or(false, |p| limit <= p) {
+                                    return Err(invalid("tier bounds must increase"));
+                                }
+                                previous = Some(limit);
+                            }
+                            None if index + 1 != tiers.len() => return Err(invalid("only the last tier may be open-ended")),
+                            None => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn summary(&self) -> String {
+        config::versioned_summary(self.version.as_deref(), self.rules.len(), "rules")
+    }
+}
+
+/// What is being priced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeQuoteRequest {
+    pub rail: FeeRail,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal, // Amount the customer asked to send
+    pub currency: String,
+    pub customer_tier: String,
+    pub charge_bearer: ChargeBearer,
+}
+
+/// Price of a payment, before it is submitted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FeeQuote {
+    pub rule_id: Option<String>, // None when no rule matched (no fee)
+    pub rail: FeeRail,
+    pub currency: String,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal, // As requested
+    #[serde(with = "rust_decimal::serde::str")]
+    pub fee_amount: Decimal,
+    pub charge_bearer: ChargeBearer,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub total_debit: Decimal, // Leaves the sender's wallet, fee included
+    #[serde(with = "rust_decimal::serde::str")]
+    pub net_amount: Decimal, // Principal actually sent to the beneficiary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    const SCHEDULE: &str = r#"{
+        "version": "test",
+        "rules": [
+            { "id": "wire-usd-premium", "rail": "WIRE", "currency": "USD", "customer_tier": "PREMIUM",
+              "calculation": { "type": "FLAT", "amount": "5" } },
+            { "id": "wire-default", "rail": "WIRE",
+              "calculation": { "type": "PERCENTAGE", "percent": "0.1" }, "min_fee": "15", "max_fee": "50" },
+            { "id": "ach-tiered", "rail": "ACH",
+              "calculation": { "type": "TIERED", "tiers": [
+                  { "up_to": "1000", "flat": "0.25" },
+                  { "up_to": "10000", "flat": "1", "percent": "0.01" },
+                  { "up_to": null, "flat": "5" }
+              ] } }
+        ]
+    }"#;
+
+    fn request(rail: FeeRail, amount: Decimal, tier: &str, bearer: ChargeBearer) -> FeeQuoteRequest {
+        FeeQuoteRequest { rail, amount, currency: "USD".to_string(), customer_tier: tier.to_string(), charge_bearer: bearer }
+    }
+
+    #[test]
+    fn test_rule_selection_and_min_max() {
+        let schedule = config::parse_json::<FeeSchedule>(SCHEDULE).unwrap();
+
+        let premium = schedule.quote(&request(FeeRail::Wire, dec!(20000), "PREMIUM", ChargeBearer::Sha)).unwrap();
+        assert_eq!(premium.rule_id.as_deref(), Some("wire-usd-premium"));
+        assert_eq!(premium.fee_amount, dec!(5));
+
+        let small = schedule.quote(&request(FeeRail::Wire, dec!(100), "STANDARD", ChargeBearer::Sha)).unwrap();
+        assert_eq!(small.fee_amount, dec!(15)); // 0.10 raised to the minimum
+        let large = schedule.quote(&request(FeeRail::Wire, dec!(1000000), "STANDARD", ChargeBearer::Sha)).unwrap();
+        assert_eq!(large.fee_amount, dec!(50)); // 1000 capped at the maximum
+
+        let free = schedule.quote(&request(FeeRail::Internal, dec!(100), "STANDARD", ChargeBearer::Sha)).unwrap();
+        assert_eq!(free.rule_id, None);
+        assert_eq!(free.fee_amount, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_tiered_fee() {
+        let schedule = config::parse_json::<FeeSchedule>(SCHEDULE).unwrap();
+        let fee = |amount| schedule.quote(&request(FeeRail::Ach, amount, "STANDARD", ChargeBearer::Sha)).unwrap().fee_amount;
+        assert_eq!(fee(dec!(1000)), dec!(0.25));
+        assert_eq!(fee(dec!(5000)), dec!(1.50));
+        assert_eq!(fee(dec!(50000)), dec!(5));
+    }
+
+    #[test]
+    fn test_charge_bearer_totals() {
+        let schedule = config::parse_json::<FeeSchedule>(SCHEDULE).unwrap();
+        let our = schedule.quote(&request(FeeRail::Wire, dec!(1000), "STANDARD", ChargeBearer::Our)).unwrap();
+        assert_eq!((our.total_debit, our.net_amount), (dec!(1015), dec!(1000)));
+        let ben = schedule.quote(&request(FeeRail::Wire, dec!(1000), "STANDARD", ChargeBearer::Ben)).unwrap();
+        assert_eq!((ben.total_debit, ben.net_amount), (dec!(1000), dec!(985)));
+        assert!(schedule.quote(&request(FeeRail::Wire, dec!(10), "STANDARD", ChargeBearer::Ben)).is_err());
+    }
+
+    #[test]
+    fn test_invalid_schedules_rejected() {
+        let unordered = r#"{ "rules": [ { "id": "t", "rail": "ACH", "calculation": { "type": "TIERED", "tiers": [
+            { "up_to": "100", "flat": "1" }, { "up_to": "50", "flat": "2" } ] } } ] }"#;
+        assert!(config::parse_json::<FeeSchedule>(unordered).is_err());
+        let min_above_max = r#"{ "rules": [ { "id": "m", "rail": "WIRE", "min_fee": "10", "max_fee": "5",
+            "calculation": { "type": "FLAT", "amount": "1" } } ] }"#;
+        assert!(config::parse_json::<FeeSchedule>(min_above_max).is_err());
+    }
+}
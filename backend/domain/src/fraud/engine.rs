@@ -0,0 +1,68 @@
+// /home/inno/elights_jobes-research/backend/domain/src/fraud/engine.rs
+// The rule set in force, reloaded from its file while the service runs. Callers take the current rules once per
+// payment (`current`), so a reload never changes the rules in the middle of an evaluation; a file that no longer
+// parses or validates is reported and the rules in force are kept.
+use super::rules::FraudRuleSet;
+use crate::error::DomainError;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+struct Loaded {
+    rules: Arc<FraudRuleSet>,
+    modified: Option<SystemTime>, // Modification time of the file the rules were read from
+}
+
+/// Hot-reloadable fraud rules.
+pub struct FraudRules {
+    path: Option<PathBuf>, // None for a fixed rule set
+    loaded: RwLock<Loaded>,
+}
+
+fn modified_at(path: &Path) -> Result<SystemTime, DomainError> {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map_err(|e| DomainError::Configuration(format!("Cannot stat fraud rule file {}: {}", path.display(), e)))
+}
+
+impl FraudRules {
+    /// Loads the rule file; it is watched by `reload_if_changed` from then on.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, DomainError> {
+        let path = path.as_ref().to_path_buf();
+        let modified = modified_at(&path)?;
+        let rules = FraudRuleSet::load_from_file(&path)?;
+        Ok(FraudRules { path: Some(path), loaded: RwLock::new(Loaded { rules: Arc::new(rules), modified: Some(modified) }) })
+    }
+
+    /// A rule set that is never reloaded.
+    pub fn fixed(rules: FraudRuleSet) -> Self {
+        FraudRules { path: None, loaded: RwLock::new(Loaded { rules: Arc::new(rules), modified: None }) }
+    }
+
+    /// The rules in force.
+    pub fn current(&self) -> Arc<FraudRuleSet> {
+        self.loaded.read().unwrap_or_else(|poisoned| poisoned.into_inner()).rules.clone()
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Re-reads the rule file if it changed since it was last read. Returns whether new rules are in force;
+    /// when the new file is invalid the error is returned and the previous rules stay in force.
+    pub fn reload_if_changed(&self) -> Result<bool, DomainError> {
+        let Some(path) = &self.path else { return Ok(false) };
+        let modified = modified_at(path)?;
+        let last = self.loaded.read().unwrap_or_else(|poisoned| poisoned.into_inner()).modified;
+        if last == Some(modified) {
+            return Ok(false);
+        }
+        let result = FraudRuleSet::load_from_file(path);
+        let mut loaded = self.loaded.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        loaded.modified = Some(modified); // An invalid file is reported once, not on every poll
+        let rules = result?;
+        log::info!("Fraud rules {} replaced by {}", loaded.rules.version, rules.version);
+        loaded.rules = Arc::new(rules);
+        Ok(true)
+    }
+}
@@ -0,0 +1,254 @@
+// /home/inno/elights_jobes-research/backend/domain/src/fraud/features.rs
+// Features the fraud rules are written against, named `<namespace>.<feature>`: the payment itself
+// (`transaction.*`), the wallet it is paid from (`wallet.*`), its owner (`user.*`), the device and network the
+// request came from (`device.*`, including what the local IP intelligence knows about its address) and the
+// payer's recent activity (`history.*`). A feature that cannot be known for a payment (no device for a scheduled
+// payment, no country for a crypto address) is simply absent.
+use crate::beneficiaries;
+use crate::error::DomainError;
+use crate::ip_intel::{self, IpInfo};
+use crate::models::{Transaction, TransactionStatus, User, Wallet};
+use crate::utils::bigdecimal_to_decimal;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use uuid::Uuid;
+
+/// Feature namespaces a rule may refer to.
+pub const FEATURE_NAMESPACES: [&str; 5] = ["transaction", "wallet", "user", "device", "history"];
+
+/// A feature value. Amounts and counts are numbers, codes and identifiers text.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum FeatureValue {
+    Bool(bool),
+    Number(Decimal),
+    Text(String),
+}
+
+impl From<bool> for FeatureValue {
+    fn from(value: bool) -> Self {
+        FeatureValue::Bool(value)
+    }
+}
+
+impl From<Decimal> for FeatureValue {
+    fn from(value: Decimal) -> Self {
+        FeatureValue::Number(value)
+    }
+}
+
+impl From<i64> for FeatureValue {
+    fn from(value: i64) -> Self {
+        FeatureValue::Number(Decimal::from(value))
+    }
+}
+
+impl From<&str> for FeatureValue {
+    fn from(value: &str) -> Self {
+        FeatureValue::Text(value.to_string())
+    }
+}
+
+impl From<String> for FeatureValue {
+    fn from(value: String) -> Self {
+        FeatureValue::Text(value)
+    }
+}
+
+/// Feature values of one payment, by name.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(transparent)]
+pub struct FraudFeatures(BTreeMap<String, FeatureValue>);
+
+impl FraudFeatures {
+    pub fn new() -> Self {
+        FraudFeatures::default()
+    }
+
+    pub fn set(&mut self, name: &str, value: impl Into<FeatureValue>) {
+        self.0.insert(name.to_string(), value.into());
+    }
+
+    /// Sets the feature only when its value is known.
+    pub fn set_opt<V: Into<FeatureValue>>(&mut self, name: &str, value: Option<V>) {
+        if let Some(value) = value {
+            self.set(name, value);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&FeatureValue> {
+        self.0.get(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
+    }
+}
+
+/// Where an API request came from, as seen by the API layer.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct DeviceContext {
+    pub ip: Option<IpAddr>,
+    pub user_agent: Option<String>,
+    pub device_id: Option<String>, // Client-supplied device identifier (X-Device-Id)
+    #[serde(default)]
+    pub network: Option<IpInfo>, // IP intelligence on `ip`, when looked up
+}
+
+/// Contextual information for fraud detection.
+#[derive(Debug)]
+pub struct FraudDetectionContext<'a> {
+    pub transaction: &'a Transaction,
+    pub source_wallet: Option<&'a Wallet>,
+    pub user: Option<&'a User>,
+    pub device: Option<&'a DeviceContext>, // None for payments not requested over the API (schedules)
+    pub destination_country: Option<&'a str>, // ISO 3166-1 alpha-2, when the rail tells
+    pub destination_address: Option<&'a str>, // Crypto withdrawals
+}
+
+fn age_days(since: DateTime<Utc>, now: DateTime<Utc>) -> i64 {
+    (now - since).num_days().max(0)
+}
+
+/// Distances and speeds are compared as whole numbers.
+fn whole(value: f64) -> Option<Decimal> {
+    Decimal::from_f64_retain(value.round())
+}
+
+/// Whether the user has been evaluated before with this device feature value.
+fn seen_before(conn: &mut PgConnection, user_id: Uuid, feature: &str, value: &str) -> Result<bool, DomainError> {
+    use crate::schema::fraud_evaluations::dsl as fe;
+    use diesel::sql_types::{Bool, Text};
+    Ok(diesel::select(diesel::dsl::exists(
+        fe::fraud_evaluations
+            .filter(fe::user_id.eq(user_id))
+            .filter(diesel::dsl::sql::<Bool>("features->>").bind::<Text, _>(feature.to_string()).sql(" = ").bind::<Text, _>(value.to_string())),
+    ))
+    .get_result(conn)?)
+}
+
+/// Gathers the features of a Pending payment. Call inside the DB transaction that created it.
+pub fn collect_features(
+    conn: &mut PgConnection,
+    context: &FraudDetectionContext<'_>,
+    now: DateTime<Utc>,
+) -> Result<FraudFeatures, DomainError> {
+    use crate::schema::transactions::dsl as t;
+
+    let tx = context.transaction;
+    let mut features = FraudFeatures::new();
+
+    // --- transaction.* ---
+    features.set("transaction.amount", tx.amount);
+    features.set("transaction.currency", tx.currency_code.as_str());
+    features.set("transaction.type", tx.transaction_type.as_str());
+    features.set("transaction.internal", tx.credit_wallet_id.is_some());
+    features.set_opt("transaction.destination_country", context.destination_country);
+    features.set_opt("transaction.destination_address", context.destination_address);
+
+    // --- wallet.* ---
+    if let Some(wallet) = context.source_wallet {
+        features.set("wallet.type", wallet.wallet_type.as_str());
+        features.set("wallet.currency", wallet.currency_code.as_str());
+        features.set("wallet.balance", wallet.balance);
+        features.set("wallet.age_days", age_days(wallet.created_at, now));
+    }
+
+    // --- user.* ---
+    let user_id = context.user.map(|u| u.user_id).or(context.source_wallet.map(|w| w.user_id));
+    if let Some(user) = context.user {
+        features.set("user.kyc_tier", user.kyc_tier.as_str());
+        features.set("user.fee_tier", user.fee_tier.as_str());
+        features.set("user.account_age_days", age_days(user.created_at, now));
+        features.set_opt("user.country", user.country_code.clone());
+    }
+
+    // --- device.* ---
+    if let Some(device) = context.device {
+        let ip = device.ip.map(|ip| ip.to_string());
+        if let (Some(ip), Some(user_id)) = (&ip, user_id) {
+            features.set("device.new_ip", !seen_before(conn, user_id, "device.ip", ip)?);
+        }
+        if let (Some(device_id), Some(user_id)) = (&device.device_id, user_id) {
+            features.set("device.new_device", !seen_before(conn, user_id, "device.id", device_id)?);
+        }
+        features.set_opt("device.ip", ip);
+        features.set_opt("device.ip_version", device.ip.map(|ip| if ip.is_ipv4() { "v4" } else { "v6" }));
+        features.set_opt("device.user_agent", device.user_agent.clone());
+        features.set_opt("device.id", device.device_id.clone());
+
+        if let Some(network) = &device.network {
+            features.set_opt("device.ip_country", network.country.clone());
+            features.set_opt("device.ip_asn", network.asn.map(i64::from));
+            features.set_opt("device.ip_as_org", network.as_org.clone());
+            features.set("device.ip_tor", network.tor);
+            features.set("device.ip_vpn", network.vpn);
+            features.set("device.ip_hosting", network.hosting);
+            features.set("device.ip_risk", i64::from(network.risk));
+            if let (Some(declared), Some(seen)) = (context.user.and_then(|u| u.country_code.as_deref()), &network.country) {
+                features.set("device.ip_country_mismatch", !declared.eq_ignore_ascii_case(seen));
+            }
+            // Where the request comes from against where the customer last logged in from
+            if let (Some(here), Some(user_id)) = (network.location(), user_id) {
+                if let Some(login) = ip_intel::last_located_login(conn, user_id)? {
+                    if let Some(from) = login.location() {
+                        let trip = ip_intel::travel(from, login.created_at, here, now);
+                        features.set_opt("device.login_distance_km", whole(trip.distance_km));
+                        features.set_opt("device.travel_speed_kmh", whole(trip.speed_kmh));
+                        features.set("device.impossible_travel", trip.impossible);
+                    }
+                }
+            }
+        }
+    }
+
+    // --- history.* (outbound activity of the paying wallet, this payment excluded) ---
+    if let Some(wallet_id) = tx.debit_wallet_id {
+        let recent: Vec<(BigDecimal, String, DateTime<Utc>)> = t::transactions
+            .filter(t::debit_wallet_id.eq(wallet_id))
+            .filter(t::transaction_id.ne(tx.transaction_id))
+            .filter(t::created_at.ge(now - Duration::hours(24)))
+            .select((t::amount, t::status, t::created_at))
+            .load(conn)?;
+        let failed = [TransactionStatus::Failed.to_string(), TransactionStatus::Cancelled.to_string()];
+        let hour_ago = now - Duration::hours(1);
+        features.set("history.tx_count_1h", recent.iter().filter(|(_, _, at)| *at >= hour_ago).count() as i64);
+        features.set("history.tx_count_24h", recent.len() as i64);
+        features.set(
+            "history.amount_24h",
+            recent.iter().filter(|(_, status, _)| !failed.contains(status)).map(|(amount, _, _)| bigdecimal_to_decimal(amount.clone())).sum::<Decimal>(),
+        );
+        features.set("history.failed_count_24h", recent.iter().filter(|(_, status, _)| failed.contains(status)).count() as i64);
+
+        let paid_before = diesel::select(diesel::dsl::exists(
+            t::transactions
+                .filter(t::debit_wallet_id.eq(wallet_id))
+                .filter(t::transaction_id.ne(tx.transaction_id))
+                .filter(t::status.eq_any(vec![TransactionStatus::Settled.to_string(), TransactionStatus::Completed.to_string()])),
+        ))
+        .get_result::<bool>(conn)?;
+        features.set("history.first_payment", !paid_before);
+    }
+    features.set("history.new_beneficiary", beneficiaries::is_new_beneficiary(conn, tx, now)?);
+    if let Some(user_id) = user_id {
+        features.set(
+            "history.impossible_logins_24h",
+            ip_intel::impossible_travel_logins_since(conn, user_id, now - Duration::hours(24))?,
+        );
+    }
+
+    Ok(features)
+}
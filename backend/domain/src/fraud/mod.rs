@@ -0,0 +1,17 @@
+// /home/inno/elights_jobes-research/backend/domain/src/fraud/mod.rs
+// Fraud rule engine: declarative rules over transaction, wallet, user, device and history features, loaded from
+// a TOML/YAML file that is reloaded while the service runs. Outbound payments are allowed, held for analyst
+// review or blocked; every evaluation and the rules that fired (shadow rules included) are recorded for tuning.
+
+pub mod features; // Feature names and values, gathering them for a payment
+pub mod rules; // Rule file format, validation and evaluation
+pub mod engine; // Hot-reloaded rule set in force
+pub mod reviews; // Screening payments, recording evaluations and rule hits, analyst review, replays
+
+pub use engine::FraudRules;
+pub use features::{collect_features, DeviceContext, FeatureValue, FraudDetectionContext, FraudFeatures};
+pub use reviews::{
+    clear_review, confirm_review, evaluation_for_transaction, get_evaluation, list_evaluations, replay_rules,
+    rule_hit_stats, screen_payment, EvaluationWithHits, FraudCheck, ReplaySummary, RuleHitStats,
+};
+pub use rules::{CompareOp, Condition, FiredRule, FraudAction, FraudRule, FraudRuleSet, RuleSetMode, RuleSetOutcome, RuleValue};
@@ -0,0 +1,390 @@
+// /home/inno/elights_jobes-research/backend/domain/src/fraud/reviews.rs
+// Applying the fraud rules to outbound payments. Every screened payment gets an evaluation recording the rule set
+// version, the features, the score, the decision and each rule that fired (shadow rules included). A payment the
+// rules send to review is parked in RequiresAction before anything is held, charged or queued, until an analyst
+// other than the initiator clears it (it resumes) or confirms it (it is cancelled); a blocked payment fails.
+use super::features::{collect_features, FraudDetectionContext, FraudFeatures};
+use super::rules::{FraudAction, FraudRuleSet};
+use crate::approvals::{self, ApprovalPolicySet, PendingDispatch};
+use crate::error::DomainError;
+use crate::models::{
+    AuditOutcome, AuditTargetType, FraudEvaluation, FraudReviewStatus, FraudRuleHit, NewFraudEvaluation,
+    NewFraudRuleHit, Transaction, TransactionStatus,
+};
+use crate::payments::state_machine::{self, TransitionUpdate};
+use crate::security::audit;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+const ACTOR: &str = "FRAUD_RULES";
+
+/// Result of screening a Pending payment.
+#[derive(Debug)]
+pub enum FraudCheck {
+    /// Go on; `metadata` is to be merged into the payment when it is held.
+    Proceed { metadata: serde_json::Value },
+    /// Sent to review (RequiresAction) or blocked (Failed); nothing more is to be done with it.
+    Stopped(Transaction),
+}
+
+fn to_json<T: Serialize>(value: &T, what: &str) -> Result<serde_json::Value, DomainError> {
+    serde_json::to_value(value).map_err(|e| DomainError::Internal(format!("Failed to serialize {}: {}", what, e)))
+}
+
+/// Screens a Pending outbound payment with the rules in force. Call inside the DB transaction that created it.
+pub fn screen_payment(
+    conn: &mut PgConnection,
+    rules: &FraudRuleSet,
+    context: &FraudDetectionContext<'_>,
+    initiator_user_id: Uuid,
+    dispatch: &PendingDispatch,
+    now: DateTime<Utc>,
+) -> Result<FraudCheck, DomainError> {
+    let transaction = context.transaction;
+    let features = collect_features(conn, context, now)?;
+    let outcome = rules.evaluate(&features);
+    let applied = outcome.applied_decision();
+
+    let review = applied == FraudAction::Review;
+    let evaluation: FraudEvaluation = diesel::insert_into(crate::schema::fraud_evaluations::table)
+        .values(&NewFraudEvaluation {
+            transaction_id: transaction.transaction_id,
+            user_id: initiator_user_id,
+            rule_set_version: &outcome.version,
+            mode: outcome.mode.as_str(),
+            score: outcome.score as i32,
+            shadow_score: outcome.shadow_score as i32,
+            decision: outcome.decision.as_str(),
+            shadow_decision: outcome.shadow_decision.as_str(),
+            applied_decision: applied.as_str(),
+            features: features.to_json(),
+            review_status: review.then_some(FraudReviewStatus::Open.as_str()),
+            pending_dispatch: if review { Some(to_json(dispatch, "pending dispatch")?) } else { None },
+        })
+        .get_result(conn)?;
+    let hits: Vec<NewFraudRuleHit> = outcome
+        .fired
+        .iter()
+        .map(|hit| NewFraudRuleHit {
+            evaluation_id: evaluation.evaluation_id,
+            transaction_id: transaction.transaction_id,
+            rule_id: &hit.rule_id,
+            rule_set_version: &outcome.version,
+            score: hit.score as i32,
+            action: hit.action.map(|a| a.as_str()),
+            shadow: hit.shadow,
+        })
+        .collect();
+    if !hits.is_empty() {
+        diesel::insert_into(crate::schema::fraud_rule_hits::table).values(&hits).execute(conn)?;
+    }
+    log::debug!(
+        "Fraud rules {} on payment {}: score {}, decision {}, applied {}, {} rule(s) fired",
+        outcome.version, transaction.transaction_id, outcome.score, outcome.decision.as_str(), applied.as_str(), outcome.fired.len()
+    );
+
+    // Only what the customer may see: no rules, scores or features on the payment itself
+    let (status, action) = match applied {
+        FraudAction::Allow => {
+            return Ok(FraudCheck::Proceed { metadata: json!({"fraud": {"evaluation_id": evaluation.evaluation_id}}) });
+        }
+        FraudAction::Review => (TransactionStatus::RequiresAction, "FRAUD_REVIEW_OPENED"),
+        FraudAction::Block => (TransactionStatus::Failed, "FRAUD_RULES_BLOCKED"),
+    };
+    let update = TransitionUpdate {
+        metadata: Some(json!({"fraud": {"evaluation_id": evaluation.evaluation_id, "decision": applied.as_str(),
+            "status": evaluation.review_status, "screened_at": now}})),
+        ..Default::default()
+    };
+    let transaction = state_machine::apply_transition(conn, transaction, status, update, ACTOR)?;
+
+    audit::log_db_audit_event(
+        conn,
+        Some(initiator_user_id),
+        ACTOR,
+        action,
+        Some(AuditTargetType::FraudEvaluation),
+        Some(&evaluation.evaluation_id.to_string()),
+        AuditOutcome::Success,
+        Some(json!({"transaction_id": transaction.transaction_id, "rule_set_version": outcome.version, "score": outcome.score,
+            "rules": outcome.fired.iter().filter(|hit| !hit.shadow).map(|hit| &hit.rule_id).collect::<Vec<_>>(),
+            "amount": transaction.amount.to_string(), "currency": transaction.currency_code})),
+        None,
+    )?;
+    log::warn!(
+        "Payment {} {} by fraud rules {} (score {}), evaluation {}",
+        transaction.transaction_id,
+        if review { "sent to review" } else { "blocked" },
+        outcome.version,
+        outcome.score,
+        evaluation.evaluation_id
+    );
+    Ok(FraudCheck::Stopped(transaction))
+}
+
+fn load_for_update(conn: &mut PgConnection, evaluation_id: Uuid) -> Result<FraudEvaluation, DomainError> {
+    use crate::schema::fraud_evaluations::dsl as fe;
+    fe::fraud_evaluations
+        .find(evaluation_id)
+        .for_update()
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("Fraud evaluation {} not found", evaluation_id)))
+}
+
+/// Records the review on an open evaluation and returns it with its still-parked payment.
+fn review(
+    conn: &mut PgConnection,
+    evaluation_id: Uuid,
+    new_status: FraudReviewStatus,
+    reviewer_id: Uuid,
+    comment: &str,
+    now: DateTime<Utc>,
+) -> Result<(FraudEvaluation, Transaction), DomainError> {
+    use crate::schema::fraud_evaluations::dsl as fe;
+    use crate::schema::transactions::dsl as t;
+
+    let evaluation = load_for_update(conn, evaluation_id)?;
+    if evaluation.review_status.as_deref() != Some(FraudReviewStatus::Open.as_str()) {
+        return Err(DomainError::Validation(format!(
+            "Fraud evaluation {} has no open review ({})", evaluation_id, evaluation.review_status.as_deref().unwrap_or("none")
+        )));
+    }
+    if evaluation.user_id == reviewer_id {
+        return Err(DomainError::Authorization("A fraud review cannot be decided by the payment's initiator".to_string()));
+    }
+    if comment.trim().is_empty() {
+        return Err(DomainError::Validation("A review comment is required".to_string()));
+    }
+    let transaction: Transaction = t::transactions.find(evaluation.transaction_id).for_update().first(conn)?;
+    if transaction.status != TransactionStatus::RequiresAction.to_string() {
+        return Err(DomainError::Validation(format!(
+            "Payment {} is {}, no longer waiting for fraud review", transaction.transaction_id, transaction.status
+        )));
+    }
+    let evaluation: FraudEvaluation = diesel::update(fe::fraud_evaluations.find(evaluation_id))
+        .set((
+            fe::review_status.eq(Some(new_status.as_str())),
+            fe::reviewed_by.eq(Some(reviewer_id)),
+            fe::review_comment.eq(Some(comment.trim())),
+            fe::reviewed_at.eq(Some(now)),
+        ))
+        .get_result(conn)?;
+    Ok((evaluation, transaction))
+}
+
+/// Clears a payment held for review and resumes it: it waits for approval if `approval_policies` require one,
+/// otherwise it is held, charged and queued at once (failing if the wallet no longer covers it).
+pub fn clear_review(
+    conn: &mut PgConnection,
+    evaluation_id: Uuid,
+    reviewer_id: Uuid,
+    comment: &str,
+    approval_policies: Option<&ApprovalPolicySet>,
+    now: DateTime<Utc>,
+) -> Result<FraudEvaluation, DomainError> {
+    conn.transaction(|conn| {
+        let (evaluation, transaction) = review(conn, evaluation_id, FraudReviewStatus::Cleared, reviewer_id, comment, now)?;
+        let dispatch: PendingDispatch = evaluation
+            .pending_dispatch
+            .clone()
+            .and_then(|d| serde_json::from_value(d).ok())
+            .ok_or_else(|| DomainError::Internal(format!("Fraud evaluation {} has no valid pending dispatch", evaluation_id)))?;
+        let metadata = json!({"fraud": {"evaluation_id": evaluation.evaluation_id, "status": evaluation.review_status, "cleared_at": now}});
+
+        let requirement = match approval_policies {
+            Some(policies) => approvals::requirement_for_dispatch(conn, policies, &transaction, &dispatch, now)?,
+            None => None,
+        };
+        let next_step = match requirement {
+            Some(requirement) => {
+                let update = TransitionUpdate { metadata: Some(metadata), ..Default::default() };
+                let transaction = state_machine::apply_transition(conn, &transaction, TransactionStatus::RequiresAction, update, ACTOR)?;
+                approvals::request_approval(conn, &transaction, evaluation.user_id, &requirement, &dispatch, now)?;
+                "AWAITING_APPROVAL"
+            }
+            None => {
+                approvals::workflow::dispatch_held_payment(conn, &transaction, &dispatch, metadata, ACTOR)?;
+                "RELEASED"
+            }
+        };
+
+        audit::log_db_audit_event(
+            conn, Some(reviewer_id), &reviewer_id.to_string(), "FRAUD_REVIEW_CLEARED", Some(AuditTargetType::FraudEvaluation),
+            Some(&evaluation_id.to_string()), AuditOutcome::Success,
+            Some(json!({"transaction_id": evaluation.transaction_id, "comment": evaluation.review_comment, "next_step": next_step})),
+            None,
+        )?;
+        log::info!("Fraud review {} cleared by {}, payment {} {}", evaluation_id, reviewer_id, evaluation.transaction_id, next_step);
+        Ok(evaluation)
+    })
+}
+
+/// Confirms fraud: the payment is cancelled (nothing was held or charged) and its limit usage released.
+pub fn confirm_review(
+    conn: &mut PgConnection,
+    evaluation_id: Uuid,
+    reviewer_id: Uuid,
+    comment: &str,
+    now: DateTime<Utc>,
+) -> Result<FraudEvaluation, DomainError> {
+    conn.transaction(|conn| {
+        let (evaluation, transaction) = review(conn, evaluation_id, FraudReviewStatus::Confirmed, reviewer_id, comment, now)?;
+        let update = TransitionUpdate {
+            metadata: Some(json!({"fraud": {"evaluation_id": evaluation.evaluation_id, "status": evaluation.review_status, "confirmed_at": now}})),
+            ..Default::default()
+        };
+        state_machine::apply_transition(conn, &transaction, TransactionStatus::Cancelled, update, ACTOR)?;
+
+        audit::log_db_audit_event(
+            conn, Some(reviewer_id), &reviewer_id.to_string(), "FRAUD_CONFIRMED", Some(AuditTargetType::FraudEvaluation),
+            Some(&evaluation_id.to_string()), AuditOutcome::Success,
+            Some(json!({"transaction_id": evaluation.transaction_id, "comment": evaluation.review_comment, "score": evaluation.score})),
+            None,
+        )?;
+        log::warn!("Fraud review {} confirmed by {}, payment {} cancelled", evaluation_id, reviewer_id, evaluation.transaction_id);
+        Ok(evaluation)
+    })
+}
+
+/// An evaluation with the rules that fired in it.
+#[derive(Debug, Serialize)]
+pub struct EvaluationWithHits {
+    #[serde(flatten)]
+    pub evaluation: FraudEvaluation,
+    pub hits: Vec<FraudRuleHit>,
+}
+
+pub fn get_evaluation(conn: &mut PgConnection, evaluation_id: Uuid) -> Result<EvaluationWithHits, DomainError> {
+    use crate::schema::fraud_evaluations::dsl as fe;
+    use crate::schema::fraud_rule_hits::dsl as h;
+    let evaluation: FraudEvaluation = fe::fraud_evaluations
+        .find(evaluation_id)
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("Fraud evaluation {} not found", evaluation_id)))?;
+    let hits = h::fraud_rule_hits.filter(h::evaluation_id.eq(evaluation_id)).order(h::score.desc()).load(conn)?;
+    Ok(EvaluationWithHits { evaluation, hits })
+}
+
+/// The evaluation of a payment, if it was screened.
+pub fn evaluation_for_transaction(conn: &mut PgConnection, transaction_id: Uuid) -> Result<EvaluationWithHits, DomainError> {
+    use crate::schema::fraud_evaluations::dsl as fe;
+    let evaluation_id: Uuid = fe::fraud_evaluations
+        .filter(fe::transaction_id.eq(transaction_id))
+        .select(fe::evaluation_id)
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("Payment {} was not screened by the fraud rules", transaction_id)))?;
+    get_evaluation(conn, evaluation_id)
+}
+
+/// Evaluations newest first, optionally by review status or by decision (applied decision).
+pub fn list_evaluations(
+    conn: &mut PgConnection,
+    review_status: Option<FraudReviewStatus>,
+    decision: Option<FraudAction>,
+) -> Result<Vec<FraudEvaluation>, DomainError> {
+    use crate::schema::fraud_evaluations::dsl as fe;
+    let mut query = fe::fraud_evaluations.into_boxed();
+    if let Some(status) = review_status {
+        query = query.filter(fe::review_status.eq(status.as_str()));
+    }
+    if let Some(decision) = decision {
+        query = query.filter(fe::applied_decision.eq(decision.as_str()));
+    }
+    Ok(query.order(fe::created_at.desc()).limit(500).load(conn)?)
+}
+
+/// How one rule fired over a period, for tuning.
+#[derive(Debug, Serialize, Default, Clone, PartialEq)]
+pub struct RuleHitStats {
+    pub rule_id: String,
+    pub hits: i64, // Times the rule fired live
+    pub shadow_hits: i64, // Times it fired as a shadow rule
+    pub reviewed: i64, // Hits on payments an analyst reviewed
+    pub confirmed: i64, // ... of which were confirmed as fraud
+    pub cleared: i64, // ... of which were cleared
+    pub versions: Vec<String>, // Rule set versions it fired under
+}
+
+/// Per-rule hit counts in [from, to), with the review outcomes of the payments they fired on.
+pub fn rule_hit_stats(conn: &mut PgConnection, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<RuleHitStats>, DomainError> {
+    use crate::schema::fraud_evaluations::dsl as fe;
+    use crate::schema::fraud_rule_hits::dsl as h;
+    let rows: Vec<(String, String, bool, Option<String>)> = h::fraud_rule_hits
+        .inner_join(fe::fraud_evaluations)
+        .filter(h::created_at.ge(from).and(h::created_at.lt(to)))
+        .select((h::rule_id, h::rule_set_version, h::shadow, fe::review_status))
+        .load(conn)?;
+
+    let mut stats: BTreeMap<String, RuleHitStats> = BTreeMap::new();
+    for (rule_id, version, shadow, review_status) in rows {
+        let entry = stats.entry(rule_id.clone()).or_insert_with(|| RuleHitStats { rule_id, ..Default::default() });
+        if shadow {
+            entry.shadow_hits += 1;
+        } else {
+            entry.hits += 1;
+        }
+        match review_status.as_deref().and_then(FraudReviewStatus::parse) {
+            Some(FraudReviewStatus::Confirmed) => {
+                entry.reviewed += 1;
+                entry.confirmed += 1;
+            }
+            Some(FraudReviewStatus::Cleared) => {
+                entry.reviewed += 1;
+                entry.cleared += 1;
+            }
+            _ => {}
+        }
+        if !entry.versions.contains(&version) {
+            entry.versions.push(version);
+        }
+    }
+    Ok(stats.into_values().collect())
+}
+
+/// Outcome of running a candidate rule set over the features recorded for past payments.
+#[derive(Debug, Serialize, Default, Clone, PartialEq)]
+pub struct ReplaySummary {
+    pub version: String,
+    pub evaluated: usize,
+    pub decisions: BTreeMap<String, usize>, // Candidate decision -> payments
+    pub changed: usize, // Payments whose decision differs from the one recorded
+    pub rule_hits: BTreeMap<String, usize>, // Rule -> payments it fired on (shadow rules included)
+}
+
+/// Replays `candidate` over the evaluations recorded in [from, to), so rule changes can be tried before they go live.
+pub fn replay_rules(
+    conn: &mut PgConnection,
+    candidate: &FraudRuleSet,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<ReplaySummary, DomainError> {
+    use crate::schema::fraud_evaluations::dsl as fe;
+    let recorded: Vec<(String, serde_json::Value)> = fe::fraud_evaluations
+        .filter(fe::created_at.ge(from).and(fe::created_at.lt(to)))
+        .select((fe::decision, fe::features))
+        .order(fe::created_at.asc())
+        .limit(50_000)
+        .load(conn)?;
+
+    let mut summary = ReplaySummary { version: candidate.version.clone(), ..Default::default() };
+    for (decision, features) in recorded {
+        let Ok(features) = serde_json::from_value::<FraudFeatures>(features) else { continue };
+        let outcome = candidate.evaluate(&features);
+        summary.evaluated += 1;
+        *summary.decisions.entry(outcome.decision.as_str().to_string()).or_default() += 1;
+        if outcome.decision.as_str() != decision {
+            summary.changed += 1;
+        }
+        for hit in &outcome.fired {
+            *summary.rule_hits.entry(hit.rule_id.clone()).or_default() += 1;
+        }
+    }
+    Ok(summary)
+}
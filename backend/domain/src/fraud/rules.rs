@@ -0,0 +1,471 @@
+// /home/inno/elights_jobes-research/backend/domain/src/fraud/rules.rs
+// Declarative fraud rules, loaded from a TOML (or YAML) file. Each rule is a condition over named features,
+// the score it adds when it fires and optionally an action of its own. The scores of the live rules that fire
+// are added up and compared with the review and block thresholds; the decision is the most severe of that and
+// the fired rules' own actions. Shadow rules are evaluated and recorded but never change the decision, and a
+// rule set in SHADOW mode records its decision without applying it, so new rules can be tuned on live traffic.
+use super::features::{FeatureValue, FraudFeatures, FEATURE_NAMESPACES};
+use crate::error::DomainError;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// What happens to a payment. Ordered by severity.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FraudAction {
+    #[default]
+    Allow, // Proceeds
+    Review, // Held in RequiresAction until an analyst clears it
+    Block, // Refused, the payment fails
+}
+
+impl FraudAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FraudAction::Allow => "ALLOW",
+            FraudAction::Review => "REVIEW",
+            FraudAction::Block => "BLOCK",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<FraudAction> {
+        match value {
+            "ALLOW" => Some(FraudAction::Allow),
+            "REVIEW" => Some(FraudAction::Review),
+            "BLOCK" => Some(FraudAction::Block),
+            _ => None,
+        }
+    }
+}
+
+/// Whether a rule set's decisions are applied.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RuleSetMode {
+    #[default]
+    Enforce, // Decisions are applied to payments
+    Shadow, // Decisions are only recorded, every payment is allowed
+}
+
+impl RuleSetMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleSetMode::Enforce => "ENFORCE",
+            RuleSetMode::Shadow => "SHADOW",
+        }
+    }
+}
+
+/// Comparison of a feature with a value.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    In,      // Equal to one of a list
+    NotIn,   // Equal to none of a list
+    Contains, // Text contains, case-insensitively
+    Exists,  // Feature is known (no value)
+    Missing, // Feature is not known (no value)
+}
+
+impl CompareOp {
+    fn needs_value(&self) -> bool {
+        !matches!(self, CompareOp::Exists | CompareOp::Missing)
+    }
+}
+
+/// A value written in a rule.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum RuleValue {
+    Bool(bool),
+    Number(Decimal),
+    Text(String),
+    List(Vec<RuleValue>),
+}
+
+/// A condition tree: `all`/`any`/`not` over comparisons `{feature, op, value}`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Condition {
+    All { all: Vec<Condition> },
+    Any { any: Vec<Condition> },
+    Not { not: Box<Condition> },
+    Compare {
+        feature: String,
+        op: CompareOp,
+        #[serde(default)]
+        value: Option<RuleValue>,
+    },
+}
+
+fn equals(feature: &FeatureValue, value: &RuleValue) -> bool {
+    match (feature, value) {
+        (FeatureValue::Bool(a), RuleValue::Bool(b)) => a == b,
+        (FeatureValue::Number(a), RuleValue::Number(b)) => a == b,
+        (FeatureValue::Text(a), RuleValue::Text(b)) => a.eq_ignore_ascii_case(b),
+        (FeatureValue::Text(a), RuleValue::Number(b)) => a.parse::<Decimal>().is_ok_and(|a| a == *b), // Numeric-looking codes
+        _ => false,
+    }
+}
+
+impl Condition {
+    /// Whether the condition holds. A comparison on a feature the payment does not have is false
+    /// (`missing` aside), so `ne`/`not_in` only fire on known values.
+    pub fn matches(&self, features: &FraudFeatures) -> bool {
+        match self {
+            Condition::All { all } => all.iter().all(|c| c.matches(features)),
+            Condition::Any { any } => any.iter().any(|c| c.matches(features)),
+            Condition::Not { not } => !not.matches(features),
+            Condition::Compare { feature, op, value } => {
+                let actual = features.get(feature);
+                let (actual, value) = match (op, actual, value) {
+                    (CompareOp::Exists, actual, _) => return actual.is_some(),
+                    (CompareOp::Missing, actual, _) => return actual.is_none(),
+                    (_, Some(actual), Some(value)) => (actual, value),
+                    _ => return false,
+                };
+                match op {
+                    CompareOp::Eq => equals(actual, value),
+                    CompareOp::Ne => !equals(actual, value),
+                    CompareOp::In | CompareOp::NotIn => {
+                        let found = matches!(value, RuleValue::List(items) if items.iter().any(|item| equals(actual, item)));
+                        found == (*op == CompareOp::In)
+                    }
+                    CompareOp::Contains => match (actual, value) {
+                        (FeatureValue::Text(a), RuleValue::Text(b)) => a.to_lowercase().contains(&b.to_lowercase()),
+                        _ => false,
+                    },
+                    CompareOp::Gt | CompareOp::Gte | CompareOp::Lt | CompareOp::Lte => match (actual, value) {
+                        (FeatureValue::Number(a), RuleValue::Number(b)) => match op {
+                            CompareOp::Gt => a > b,
+                            CompareOp::Gte => a >= b,
+                            CompareOp::Lt => a < b,
+                            _ => a <= b,
+                        },
+                        _ => false,
+                    },
+                    CompareOp::Exists | CompareOp::Missing => unreachable!("handled above"),
+                }
+            }
+        }
+    }
+
+    fn validate(&self, rule_id: &str) -> Result<(), String> {
+        match self {
+            Condition::All { all: conditions } | Condition::Any { any: conditions } => {
+                if conditions.is_empty() {
+                    return Err(format!("rule {} has an empty all/any", rule_id));
+                }
+                conditions.iter().try_for_each(|c| c.validate(rule_id))
+            }
+            Condition::Not { not } => not.validate(rule_id),
+            Condition::Compare { feature, op, value } => {
+                let known = feature
+                    .split_once('.')
+                    .is_some_and(|(namespace, name)| FEATURE_NAMESPACES.contains(&namespace) && !name.is_empty());
+                if !known {
+                    return Err(format!("rule {} refers to unknown feature '{}'", rule_id, feature));
+                }
+                match (op, value) {
+                    (op, None) if op.needs_value() => Err(format!("rule {}: {:?} on {} needs a value", rule_id, op, feature)),
+                    (op, Some(_)) if !op.needs_value() => Err(format!("rule {}: {:?} on {} takes no value", rule_id, op, feature)),
+                    (CompareOp::In | CompareOp::NotIn, Some(value)) if !matches!(value, RuleValue::List(_)) => {
+                        Err(format!("rule {}: {:?} on {} needs a list", rule_id, op, feature))
+                    }
+                    (CompareOp::Gt | CompareOp::Gte | CompareOp::Lt | CompareOp::Lte, Some(value)) if !matches!(value, RuleValue::Number(_)) => {
+                        Err(format!("rule {}: {:?} on {} needs a number", rule_id, op, feature))
+                    }
+                    _ => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// One fraud rule.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FraudRule {
+    pub id: String, // Stable key, recorded on every hit
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub shadow: bool, // Evaluated and recorded, never affects the decision
+    pub when: Condition,
+    #[serde(default)]
+    pub score: u32, // Added to the payment's score when the rule fires
+    #[serde(default)]
+    pub action: Option<FraudAction>, // Applied when the rule fires, whatever the score
+}
+
+/// A rule set: thresholds and rules, as loaded from one file.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FraudRuleSet {
+    pub version: String, // Recorded on every evaluation
+    #[serde(default)]
+    pub mode: RuleSetMode,
+    pub review_score: u32, // Score from which a payment is held for review
+    pub block_score: u32, // Score from which a payment is refused
+    #[serde(default)]
+    pub rules: Vec<FraudRule>,
+}
+
+/// A rule that fired.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FiredRule {
+    pub rule_id: String,
+    pub score: u32,
+    pub action: Option<FraudAction>,
+    pub shadow: bool,
+}
+
+/// Outcome of a rule set over one payment's features.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RuleSetOutcome {
+    pub version: String,
+    pub mode: RuleSetMode,
+    pub score: u32, // Live rules only
+    pub decision: FraudAction, // From the live rules
+    pub shadow_score: u32, // Had the shadow rules been live too
+    pub shadow_decision: FraudAction,
+    pub fired: Vec<FiredRule>,
+}
+
+impl RuleSetOutcome {
+    /// What is done to the payment: nothing in SHADOW mode.
+    pub fn applied_decision(&self) -> FraudAction {
+        match self.mode {
+            RuleSetMode::Enforce => self.decision,
+            RuleSetMode::Shadow => FraudAction::Allow,
+        }
+    }
+
+    /// Human-readable reasons, one per live rule that fired.
+    pub fn reasons(&self, rules: &FraudRuleSet) -> Vec<String> {
+        self.fired
+            .iter()
+            .filter(|hit| !hit.shadow)
+            .map(|hit| match rules.rule(&hit.rule_id).map(|r| r.description.as_str()).filter(|d| !d.is_empty()) {
+                Some(description) => format!("{}: {}", hit.rule_id, description),
+                None => hit.rule_id.clone(),
+            })
+            .collect()
+    }
+}
+
+impl FraudRuleSet {
+    /// Parses and validates a rule set from its TOML form.
+    pub fn from_toml_str(toml: &str) -> Result<Self, DomainError> {
+        let rules: FraudRuleSet =
+            toml::from_str(toml).map_err(|e| DomainError::Configuration(format!("Invalid fraud rule file: {}", e)))?;
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    /// Parses and validates a rule set from its YAML form.
+    pub fn from_yaml_str(yaml: &str) -> Result<Self, DomainError> {
+        let rules: FraudRuleSet = serde_yaml::from_str(yaml)
+            .map_err(|e| DomainError::Configuration(format!("Invalid fraud rule file: {}", e)))?;
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    /// Reads a rule set from a `.toml`, `.yaml` or `.yml` file.
+    pub fn load_from_file(path: impl AsRef<Path>) -> Result<Self, DomainError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| DomainError::Configuration(format!("Cannot read fraud rule file {}: {}", path.display(), e)))?;
+        let rules = match path.extension().and_then(|e| e.to_str()) {
+            Some("yaml") | Some("yml") => Self::from_yaml_str(&content)?,
+            _ => Self::from_toml_str(&content)?,
+        };
+        log::info!(
+            "Loaded fraud rules {} ({} rules, {} enabled, mode {}) from {}",
+            rules.version,
+            rules.rules.len(),
+            rules.rules.iter().filter(|r| r.enabled).count(),
+            rules.mode.as_str(),
+            path.display()
+        );
+        Ok(rules)
+    }
+
+    pub fn validate(&self) -> Result<(), DomainError> {
+        let invalid = |reason: String| DomainError::Configuration(format!("Fraud rules: {}", reason));
+        if self.version.trim().is_empty() {
+            return Err(invalid("version is required".to_string()));
+        }
+        if self.review_score == 0 || self.block_score < self.review_score {
+            return Err(invalid("review_score must be positive and block_score at least review_score".to_string()));
+        }
+        let mut ids = HashSet::new();
+        for rule in &self.rules {
+            if rule.id.trim().is_empty() || !ids.insert(rule.id.as_str()) {
+                return Err(invalid(format!("rule id '{}' is empty or duplicated", rule.id)));
+            }
+            if rule.score == 0 && rule.action.is_none() {
+                return Err(invalid(format!("rule {} has neither a score nor an action", rule.id)));
+            }
+            rule.when.validate(&rule.id).map_err(invalid)?;
+        }
+        Ok(())
+    }
+
+    pub fn rule(&self, rule_id: &str) -> Option<&FraudRule> {
+        self.rules.iter().find(|r| r.id == rule_id)
+    }
+
+    fn decide(&self, score: u32, actions: impl Iterator<Item = FraudAction>) -> FraudAction {
+        let by_score = if score >= self.block_score {
+            FraudAction::Block
+        } else if score >= self.review_score {
+            FraudAction::Review
+        } else {
+            FraudAction::Allow
+        };
+        actions.fold(by_score, FraudAction::max)
+    }
+
+    /// Runs the enabled rules over a payment's features.
+    pub fn evaluate(&self, features: &FraudFeatures) -> RuleSetOutcome {
+        let fired: Vec<FiredRule> = self
+            .rules
+            .iter()
+            .filter(|rule| rule.enabled && rule.when.matches(features))
+            .map(|rule| FiredRule { rule_id: rule.id.clone(), score: rule.score, action: rule.action, shadow: rule.shadow })
+            .collect();
+        let live = || fired.iter().filter(|hit| !hit.shadow);
+        let score = live().map(|hit| hit.score).sum();
+        let shadow_score = fired.iter().map(|hit| hit.score).sum();
+        RuleSetOutcome {
+            version: self.version.clone(),
+            mode: self.mode,
+            score,
+            decision: self.decide(score, live().filter_map(|hit| hit.action)),
+            shadow_score,
+            shadow_decision: self.decide(shadow_score, fired.iter().filter_map(|hit| hit.action)),
+            fired,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    const RULES: &str = r#"
+        version = "test-1"
+        review_score = 50
+        block_score = 100
+
+        [[rules]]
+        id = "high_value"
+        description = "Large payment"
+        score = 50
+        when = { feature = "transaction.amount", op = "gte", value = 10000 }
+
+        [[rules]]
+        id = "new_beneficiary_burst"
+        score = 40
+        [rules.when]
+        all = [
+            { feature = "history.new_beneficiary", op = "eq", value = true },
+            { feature = "history.tx_count_1h", op = "gt", value = 3 },
+        ]
+
+        [[rules]]
+        id = "risky_country"
+        action = "BLOCK"
+        when = { feature = "transaction.destination_country", op = "in", value = ["KP", "IR"] }
+
+        [[rules]]
+        id = "new_device_candidate"
+        shadow = true
+        score = 60
+        when = { feature = "device.new_device", op = "eq", value = true }
+    "#;
+
+    fn features(amount: Decimal) -> FraudFeatures {
+        let mut features = FraudFeatures::new();
+        features.set("transaction.amount", amount);
+        features.set("history.new_beneficiary", true);
+        features.set("history.tx_count_1h", 5i64);
+        features
+    }
+
+    #[test]
+    fn scores_add_up_to_the_thresholds() {
+        let rules = FraudRuleSet::from_toml_str(RULES).unwrap();
+        let outcome = rules.evaluate(&features(dec!(500)));
+        assert_eq!(outcome.score, 40);
+        assert_eq!(outcome.decision, FraudAction::Allow);
+
+        let outcome = rules.evaluate(&features(dec!(12000)));
+        assert_eq!(outcome.score, 90);
+        assert_eq!(outcome.decision, FraudAction::Review);
+        assert_eq!(outcome.reasons(&rules), vec!["high_value: Large payment".to_string(), "new_beneficiary_burst".to_string()]);
+    }
+
+    #[test]
+    fn rule_action_overrides_a_low_score() {
+        let rules = FraudRuleSet::from_toml_str(RULES).unwrap();
+        let mut features = FraudFeatures::new();
+        features.set("transaction.destination_country", "kp");
+        let outcome = rules.evaluate(&features);
+        assert_eq!(outcome.score, 0);
+        assert_eq!(outcome.decision, FraudAction::Block);
+
+        // Absent features never satisfy a comparison
+        let outcome = rules.evaluate(&FraudFeatures::new());
+        assert!(outcome.fired.is_empty());
+    }
+
+    #[test]
+    fn shadow_rules_and_shadow_mode_do_not_change_what_is_applied() {
+        let mut rules = FraudRuleSet::from_toml_str(RULES).unwrap();
+        let mut features = features(dec!(500));
+        features.set("device.new_device", true);
+        let outcome = rules.evaluate(&features);
+        assert_eq!((outcome.score, outcome.decision), (40, FraudAction::Allow));
+        assert_eq!((outcome.shadow_score, outcome.shadow_decision), (100, FraudAction::Block));
+        assert!(outcome.fired.iter().any(|hit| hit.rule_id == "new_device_candidate" && hit.shadow));
+
+        rules.mode = RuleSetMode::Shadow;
+        let mut features = FraudFeatures::new();
+        features.set("transaction.amount", dec!(20000));
+        let outcome = rules.evaluate(&features);
+        assert_eq!(outcome.decision, FraudAction::Review);
+        assert_eq!(outcome.applied_decision(), FraudAction::Allow);
+    }
+
+    #[test]
+    fn yaml_and_invalid_rules() {
+        let yaml = "version: y1\nreview_score: 10\nblock_score: 20\nrules:\n  - id: crypto\n    score: 10\n    when:\n      any:\n        - {feature: transaction.type, op: contains, value: crypto}\n        - not: {feature: wallet.type, op: exists}\n";
+        let rules = FraudRuleSet::from_yaml_str(yaml).unwrap();
+        let mut features = FraudFeatures::new();
+        features.set("transaction.type", "CryptoBtcSend");
+        features.set("wallet.type", "CRYPTO");
+        assert_eq!(rules.evaluate(&features).decision, FraudAction::Review);
+
+        let unknown_feature = RULES.replace("history.tx_count_1h", "histroy.tx_count_1h");
+        assert!(FraudRuleSet::from_toml_str(&unknown_feature).is_err());
+        let list_expected = RULES.replace(r#"op = "in", value = ["KP", "IR"]"#, r#"op = "in", value = "KP""#);
+        assert!(FraudRuleSet::from_toml_str(&list_expected).is_err());
+        let unbounded = RULES.replace("block_score = 100", "block_score = 10");
+        assert!(FraudRuleSet::from_toml_str(&unbounded).is_err());
+    }
+}
@@ -0,0 +1,351 @@
+// /home/inno/elights_jobes-research/backend/domain/src/interest/accrual.rs
+// Daily accrual. Interest for a day is earned on the wallet's value-dated end-of-day balance: a movement
+// counts from its value date (an inbound payment's `value_date`, otherwise the day it was posted to the
+// ledger), so a transaction booked today with an earlier value date changes the balance of past days.
+// Accrual rows are recomputed, not patched: each run starts from the earliest day that can have changed
+// (a backdated movement or a backdated rate version) and overwrites every day from there on.
+// Daily amounts are kept unrounded; rounding happens once, at capitalization.
+use super::day_count::DayCountConvention;
+use super::rates::{self, RateTerms};
+use crate::error::DomainError;
+use crate::ledger;
+use crate::models::{InterestAccrual, InterestWalletState, NewInterestAccrual, NewInterestWalletState, Wallet};
+use crate::utils::bigdecimal_to_decimal;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Date, Numeric, Uuid as SqlUuid};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Interest earned on one day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyAccrual {
+    pub date: NaiveDate,
+    pub balance: Decimal, // Value-dated end-of-day balance
+    pub annual_rate_percent: Decimal, // Effective (blended) rate on the balance
+    pub day_count: DayCountConvention,
+    pub year_fraction: Decimal,
+    pub amount: Decimal,
+    pub rate_id: Uuid,
+}
+
+/// Accrues `[from, through]` from the balance at the start of `from` and the net movements per value date.
+/// Days without terms in force accrue nothing and get no row.
+pub fn accrue_days(
+    opening_balance: Decimal,
+    movements: &BTreeMap<NaiveDate, Decimal>,
+    terms: &[RateTerms],
+    from: NaiveDate,
+    through: NaiveDate,
+) -> Vec<DailyAccrual> {
+    let mut balance = opening_balance;
+    let mut accruals = Vec::new();
+    let mut date = from;
+    while date <= through {
+        balance += movements.get(&date).copied().unwrap_or(Decimal::ZERO);
+        if let Some(terms) = rates::terms_on(terms, date) {
+            let year_fraction = terms.day_count.year_fraction_for_day(date);
+            accruals.push(DailyAccrual {
+                date,
+                balance,
+                annual_rate_percent: terms.effective_rate_percent(balance),
+                day_count: terms.day_count,
+                year_fraction,
+                amount: terms.annual_interest(balance) * year_fraction,
+                rate_id: terms.rate_id,
+            });
+        }
+        date += Duration::days(1);
+    }
+    accruals
+}
+
+/// Outcome of accruing one wallet.
+#[derive(Debug, Serialize, Clone)]
+pub struct WalletAccrualRun {
+    pub wallet_id: Uuid,
+    pub recomputed_from: Option<NaiveDate>, // None = nothing to do
+    pub accrued_through: Option<NaiveDate>,
+    pub days_written: usize,
+}
+
+#[derive(QueryableByName)]
+struct ValueDatedSqlRow {
+    #[diesel(sql_type = Date)]
+    value_date: NaiveDate,
+    #[diesel(sql_type = Numeric)]
+    net: BigDecimal,
+}
+
+#[derive(QueryableByName)]
+struct OpeningSqlRow {
+    #[diesel(sql_type = Numeric)]
+    balance: BigDecimal,
+}
+
+#[derive(QueryableByName)]
+struct NewLinesSqlRow {
+    #[diesel(sql_type = BigInt)]
+    max_line_id: i64,
+    #[diesel(sql_type = diesel::sql_types::Nullable<Date>)]
+    earliest_value_date: Option<NaiveDate>,
+}
+
+// Value date of a wallet journal line; wallet accounts are credit-normal, so credits add to the balance
+const VALUE_DATED_LINES: &str = "\
+    SELECT COALESCE((t.metadata->>'value_date')::date, (t.metadata->'inbound'->>'value_date')::date, \
+                    jl.posted_at::date) AS value_date, \
+           jl.line_id, \
+           CASE WHEN jl.direction = 'CREDIT' THEN jl.amount ELSE -jl.amount END AS signed_amount \
+    FROM core_schema.journal_lines jl \
+    JOIN core_schema.journal_entries je ON je.entry_id = jl.entry_id \
+    LEFT JOIN core_schema.transactions t ON t.transaction_id = je.transaction_id \
+    WHERE jl.account_id = $1";
+
+fn opening_balance(conn: &mut PgConnection, account_id: Uuid, from: NaiveDate) -> Result<Decimal, DomainError> {
+    let row: OpeningSqlRow = diesel::sql_query(format!(
+        "SELECT COALESCE(SUM(signed_amount), 0) AS balance FROM ({}) v WHERE v.value_date < $2",
+        VALUE_DATED_LINES
+    ))
+    .bind::<SqlUuid, _>(account_id)
+    .bind::<Date, _>(from)
+    .get_result(conn)?;
+    Ok(bigdecimal_to_decimal(row.balance))
+}
+
+fn movements(
+    conn: &mut PgConnection,
+    account_id: Uuid,
+    from: NaiveDate,
+    through: NaiveDate,
+) -> Result<BTreeMap<NaiveDate, Decimal>, DomainError> {
+    let rows: Vec<ValueDatedSqlRow> = diesel::sql_query(format!(
+        "SELECT v.value_date, SUM(v.signed_amount) AS net FROM ({}) v \
+         WHERE v.value_date BETWEEN $2 AND $3 GROUP BY v.value_date",
+        VALUE_DATED_LINES
+    ))
+    .bind::<SqlUuid, _>(account_id)
+    .bind::<Date, _>(from)
+    .bind::<Date, _>(through)
+    .load(conn)?;
+    Ok(rows.into_iter().map(|row| (row.value_date, bigdecimal_to_decimal(row.net))).collect())
+}
+
+/// Highest line id on the account and the earliest value date among lines after `after_line_id`.
+fn new_lines(conn: &mut PgConnection, account_id: Uuid, after_line_id: i64) -> Result<NewLinesSqlRow, DomainError> {
+    let row = diesel::sql_query(format!(
+        "SELECT COALESCE(MAX(v.line_id), $2) AS max_line_id, MIN(v.value_date) AS earliest_value_date \
+         FROM ({}) v WHERE v.line_id > $2",
+        VALUE_DATED_LINES
+    ))
+    .bind::<SqlUuid, _>(account_id)
+    .bind::<BigInt, _>(after_line_id)
+    .get_result(conn)?;
+    Ok(row)
+}
+
+/// Brings the wallet's accruals up to date through `through` (normally yesterday), recomputing earlier
+/// days touched by movements or rate versions recorded since the last run.
+pub fn accrue_wallet(
+    conn: &mut PgConnection,
+    wallet: &Wallet,
+    terms: &[RateTerms],
+    through: NaiveDate,
+    now: DateTime<Utc>,
+) -> Result<WalletAccrualRun, DomainError> {
+    use crate::schema::interest_accruals::dsl as ia;
+    use crate::schema::interest_rate_versions::dsl as irv;
+    use crate::schema::interest_wallet_states::dsl as iws;
+
+    let account = ledger::wallet_account(conn, wallet.wallet_id)?;
+    conn.transaction(|conn| {
+        diesel::insert_into(iws::interest_wallet_states)
+            .values(&NewInterestWalletState { wallet_id: wallet.wallet_id, accrued_through: None, last_line_id: 0, last_run_at: None })
+            .on_conflict(iws::wallet_id)
+            .do_nothing()
+            .execute(conn)?;
+        let state: InterestWalletState = iws::interest_wallet_states
+            .find(wallet.wallet_id)
+            .for_update()
+            .first(conn)?;
+
+        let Some(first_effective) = terms.iter().map(|terms| terms.effective_from).min() else {
+            return Ok(WalletAccrualRun { wallet_id: wallet.wallet_id, recomputed_from: None, accrued_through: state.accrued_through, days_written: 0 });
+        };
+        let accrual_start = wallet.created_at.date_naive().max(first_effective);
+        // Never shrink the accrued window: a backdated change must be carried through every accrued day
+        let through = state.accrued_through.map_or(through, |date| date.max(through));
+
+        let lines = new_lines(conn, account.account_id, state.last_line_id)?;
+        // Versions recorded since the last run may reach back before it
+        let backdated_rate: Option<NaiveDate> = match state.last_run_at {
+            Some(last_run_at) => irv::interest_rate_versions
+                .filter(irv::wallet_type.eq(&wallet.wallet_type))
+                .filter(irv::currency_code.eq(&wallet.currency_code))
+                .filter(irv::created_at.gt(last_run_at))
+                .select(diesel::dsl::min(irv::effective_from))
+                .first(conn)?,
+            None => None,
+        };
+        let resume = state.accrued_through.map_or(accrual_start, |date| date + Duration::days(1));
+        let from = [Some(resume), lines.earliest_value_date, backdated_rate]
+            .into_iter()
+            .flatten()
+            .min()
+            .unwrap_or(resume)
+            .max(accrual_start);
+
+        let mut days_written = 0;
+        if from <= through {
+            let opening = opening_balance(conn, account.account_id, from)?;
+            let movements = movements(conn, account.account_id, from, through)?;
+            let accruals = accrue_days(opening, &movements, terms, from, through);
+            // Days in the window that no longer have terms (e.g. a version moved later) lose their rows
+            diesel::delete(ia::interest_accruals.filter(ia::wallet_id.eq(wallet.wallet_id)).filter(ia::accrual_date.between(from, through)))
+                .execute(conn)?;
+            for accrual in &accruals {
+                let new_accrual = NewInterestAccrual {
+                    wallet_id: wallet.wallet_id,
+                    accrual_date: accrual.date,
+                    balance: accrual.balance,
+                    annual_rate_percent: accrual.annual_rate_percent,
+                    day_count: accrual.day_count.as_str(),
+                    year_fraction: accrual.year_fraction,
+                    amount: accrual.amount,
+                    rate_id: Some(accrual.rate_id),
+                    computed_at: now,
+                };
+                diesel::insert_into(ia::interest_accruals).values(&new_accrual).execute(conn)?;
+            }
+            days_written = accruals.len();
+        }
+
+        let accrued_through = Some(through).filter(|date| *date >= accrual_start);
+        diesel::update(iws::interest_wallet_states.find(wallet.wallet_id))
+            .set((
+                iws::accrued_through.eq(accrued_through),
+                iws::last_line_id.eq(lines.max_line_id),
+                iws::last_run_at.eq(Some(now)),
+            ))
+            .execute(conn)?;
+        if from <= through {
+            log::debug!("Accrued interest for wallet {} from {} through {} ({} days)", wallet.wallet_id, from, through, days_written);
+        }
+        Ok(WalletAccrualRun {
+            wallet_id: wallet.wallet_id,
+            recomputed_from: (from <= through).then_some(from),
+            accrued_through,
+            days_written,
+        })
+    })
+}
+
+/// Wallets with interest terms configured for their type and currency.
+pub fn interest_bearing_wallets(conn: &mut PgConnection) -> Result<Vec<Wallet>, DomainError> {
+    use crate::schema::interest_rate_versions::dsl as irv;
+    use crate::schema::wallets::dsl as w;
+
+    let wallets = w::wallets
+        .filter(diesel::dsl::exists(
+            irv::interest_rate_versions
+                .filter(irv::wallet_type.eq(w::wallet_type))
+                .filter(irv::currency_code.eq(w::currency_code)),
+        ))
+        .order(w::created_at.asc())
+        .load(conn)?;
+    Ok(wallets)
+}
+
+/// Accrues every interest-bearing wallet through `through`. A failing wallet is logged and skipped.
+pub fn accrue_all(conn: &mut PgConnection, through: NaiveDate, now: DateTime<Utc>) -> Result<Vec<WalletAccrualRun>, DomainError> {
+    let mut terms_cache: BTreeMap<(String, String), Vec<RateTerms>> = BTreeMap::new();
+    let mut runs = Vec::new();
+    for wallet in interest_bearing_wallets(conn)? {
+        let key = (wallet.wallet_type.clone(), wallet.currency_code.clone());
+        if !terms_cache.contains_key(&key) {
+            let terms = rates::load_terms(conn, &wallet.wallet_type, &wallet.currency_code)?;
+            terms_cache.insert(key.clone(), terms);
+        }
+        match accrue_wallet(conn, &wallet, &terms_cache[&key], through, now) {
+            Ok(run) => runs.push(run),
+            Err(e) => log::error!("Interest accrual failed for wallet {}: {}", wallet.wallet_id, e),
+        }
+    }
+    Ok(runs)
+}
+
+/// Accrual rows of a wallet in `[from, to]`, oldest first.
+pub fn list_accruals(
+    conn: &mut PgConnection,
+    wallet_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<InterestAccrual>, DomainError> {
+    use crate::schema::interest_accruals::dsl as ia;
+    let accruals = ia::interest_accruals
+        .filter(ia::wallet_id.eq(wallet_id))
+        .filter(ia::accrual_date.between(from, to))
+        .order(ia::accrual_date.asc())
+        .load(conn)?;
+    Ok(accruals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::rates::RateTier;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    fn date(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, d).unwrap()
+    }
+
+    fn flat(effective_from: NaiveDate, rate_percent: Decimal, created_hour: u32) -> RateTerms {
+        RateTerms {
+            rate_id: Uuid::new_v4(),
+            effective_from,
+            day_count: DayCountConvention::Act360,
+            tiers: vec![RateTier { up_to: None, rate_percent }],
+            withholding_tax_percent: Decimal::ZERO,
+            created_at: Utc.with_ymd_and_hms(2025, 1, 1, created_hour, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_value_dated_movements_apply_from_their_day() {
+        let terms = vec![flat(date(1), dec!(3.6), 0)];
+        let movements = BTreeMap::from([(date(2), dec!(1000)), (date(4), dec!(-400))]);
+        let accruals = accrue_days(dec!(0), &movements, &terms, date(1), date(4));
+        let balances: Vec<Decimal> = accruals.iter().map(|a| a.balance).collect();
+        assert_eq!(balances, vec![dec!(0), dec!(1000), dec!(1000), dec!(600)]);
+        // 1000 * 3.6% / 360 = 0.1 per day
+        assert_eq!(accruals[1].amount, dec!(0.1));
+        assert_eq!(accruals[3].amount, dec!(0.06));
+    }
+
+    #[test]
+    fn test_backdated_rate_change_applies_from_its_effective_date() {
+        let mut terms = vec![flat(date(1), dec!(3.6), 0)];
+        let movements = BTreeMap::new();
+        let before: Decimal = accrue_days(dec!(1000), &movements, &terms, date(1), date(10)).iter().map(|a| a.amount).sum();
+        assert_eq!(before, dec!(1.0));
+
+        terms.push(flat(date(6), dec!(7.2), 1));
+        let after = accrue_days(dec!(1000), &movements, &terms, date(1), date(10));
+        assert_eq!(after.iter().map(|a| a.amount).sum::<Decimal>(), dec!(1.5));
+        assert_eq!(after[4].annual_rate_percent, dec!(3.6));
+        assert_eq!(after[5].annual_rate_percent, dec!(7.2));
+    }
+
+    #[test]
+    fn test_no_accrual_before_terms() {
+        let terms = vec![flat(date(5), dec!(3.6), 0)];
+        let accruals = accrue_days(dec!(1000), &BTreeMap::new(), &terms, date(1), date(6));
+        assert_eq!(accruals.len(), 2);
+        assert_eq!(accruals[0].date, date(5));
+    }
+}
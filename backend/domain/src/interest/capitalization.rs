@@ -0,0 +1,279 @@
+// /home/inno/elights_jobes-research/backend/domain/src/interest/capitalization.rs
+// Monthly capitalization. At month end the wallet's accruals are rounded once and credited as an
+// `InterestCredit` transaction (Dr INTEREST_EXPENSE / Cr wallet); tax withheld at the rate in force on
+// the last day of the period is taken back as a separate `WithholdingTax` transaction
+// (Dr wallet / Cr WITHHOLDING_TAX), so the gross amount and the tax both appear on the statement.
+// The amount credited is everything accrued up to the period end less what was credited before, so
+// days recomputed after they were capitalized (backdated rates or value dates) are settled in the next
+// posting. A downward correction is never debited; it is absorbed by later interest.
+use super::accrual;
+use super::rates::{self, RateTerms};
+use crate::error::DomainError;
+use crate::fees::minor_units;
+use crate::models::{
+    AuditOutcome, AuditTargetType, InterestPosting, InterestWalletState, NewInterestPosting, NewTransaction, Transaction,
+    TransactionStatus, TransactionType, Wallet,
+};
+use crate::payments::state_machine::{self, TransitionUpdate};
+use crate::security::audit;
+use crate::utils::bigdecimal_to_decimal;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::Serialize;
+use serde_json::json;
+use uuid::Uuid;
+
+/// Amounts of one capitalization.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct CapitalizationAmounts {
+    #[serde(with = "rust_decimal::serde::str")]
+    pub gross: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub tax: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub net: Decimal,
+}
+
+/// Gross interest due for the period given everything accrued so far and what was already credited.
+/// Accrued interest is rounded down to the currency's minor unit; tax is rounded to the nearest unit.
+pub fn capitalization_amounts(
+    accrued_to_date: Decimal,
+    previously_credited: Decimal,
+    withholding_tax_percent: Decimal,
+    currency: &str,
+) -> CapitalizationAmounts {
+    let units = minor_units(currency);
+    let gross = (accrued_to_date.round_dp_with_strategy(units, RoundingStrategy::ToZero) - previously_credited).max(Decimal::ZERO);
+    let tax = (gross * withholding_tax_percent / Decimal::ONE_HUNDRED).round_dp(units);
+    CapitalizationAmounts { gross, tax, net: gross - tax }
+}
+
+/// First and last day of the month before the one containing `date`.
+pub fn previous_month(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let first_of_month = date.with_day(1).unwrap_or(date);
+    let period_end = first_of_month - Duration::days(1);
+    (period_end.with_day(1).unwrap_or(period_end), period_end)
+}
+
+fn post_interest_transaction(
+    conn: &mut PgConnection,
+    wallet: &Wallet,
+    transaction_type: TransactionType,
+    amount: Decimal,
+    description: &str,
+    metadata: serde_json::Value,
+    actor: &str,
+) -> Result<Transaction, DomainError> {
+    let (debit_wallet_id, credit_wallet_id) = match transaction_type {
+        TransactionType::WithholdingTax => (Some(wallet.wallet_id), None), // Credited to WITHHOLDING_TAX by the ledger postings
+        _ => (None, Some(wallet.wallet_id)), // Debited to INTEREST_EXPENSE by the ledger postings
+    };
+    let new_tx = NewTransaction {
+        transaction_id: None,
+        debit_wallet_id,
+        credit_wallet_id,
+        transaction_type: transaction_type.to_string().as_str(),
+        status: TransactionStatus::Pending.to_string().as_str(),
+        amount,
+        currency_code: &wallet.currency_code,
+        description: Some(description),
+        external_ref_id: None,
+        metadata: Some(metadata),
+        scheduled_run_id: None,
+    };
+    let transaction: Transaction = diesel::insert_into(crate::schema::transactions::table)
+        .values(&new_tx)
+        .get_result(conn)?;
+    state_machine::apply_transition(conn, &transaction, TransactionStatus::Completed, TransitionUpdate::default(), actor)
+}
+
+/// Capitalizes `[period_start, period_end]` for one wallet. Returns the existing posting if the period
+/// was already capitalized, and `None` while accruals do not reach the period end yet.
+pub fn capitalize_wallet(
+    conn: &mut PgConnection,
+    wallet: &Wallet,
+    terms: &[RateTerms],
+    period_start: NaiveDate,
+    period_end: NaiveDate,
+    actor: &str,
+) -> Result<Option<InterestPosting>, DomainError> {
+    use crate::schema::interest_accruals::dsl as ia;
+    use crate::schema::interest_postings::dsl as ip;
+    use crate::schema::interest_wallet_states::dsl as iws;
+
+    conn.transaction(|conn| {
+        // Serializes with accrual runs on the same wallet
+        let Some(state) = iws::interest_wallet_states
+            .find(wallet.wallet_id)
+            .for_update()
+            .first::<InterestWalletState>(conn)
+            .optional()?
+        else {
+            return Ok(None);
+        };
+        let existing: Option<InterestPosting> = ip::interest_postings
+            .filter(ip::wallet_id.eq(wallet.wallet_id))
+            .filter(ip::period_end.eq(period_end))
+            .first(conn)
+            .optional()?;
+        if existing.is_some() {
+            return Ok(existing);
+        }
+        if state.accrued_through.map_or(true, |date| date < period_end) {
+            return Ok(None);
+        }
+
+        let accrued: Option<BigDecimal> = ia::interest_accruals
+            .filter(ia::wallet_id.eq(wallet.wallet_id))
+            .filter(ia::accrual_date.le(period_end))
+            .select(diesel::dsl::sum(ia::amount))
+            .first(conn)?;
+        let credited: Option<BigDecimal> = ip::interest_postings
+            .filter(ip::wallet_id.eq(wallet.wallet_id))
+            .select(diesel::dsl::sum(ip::gross_amount))
+            .first(conn)?;
+        let accrued_to_date = accrued.map(bigdecimal_to_decimal).unwrap_or(Decimal::ZERO);
+        let withholding = rates::terms_on(terms, period_end).map_or(Decimal::ZERO, |terms| terms.withholding_tax_percent);
+        let amounts = capitalization_amounts(
+            accrued_to_date,
+            credited.map(bigdecimal_to_decimal).unwrap_or(Decimal::ZERO),
+            withholding,
+            &wallet.currency_code,
+        );
+
+        let metadata = json!({ "interest": { "period_start": period_start, "period_end": period_end } });
+        let interest_tx = if amounts.gross > Decimal::ZERO {
+            let description = format!("Interest {} to {}", period_start, period_end);
+            Some(post_interest_transaction(conn, wallet, TransactionType::InterestCredit, amounts.gross, &description, metadata.clone(), actor)?)
+        } else {
+            None
+        };
+        let tax_tx = if amounts.tax > Decimal::ZERO {
+            let description = format!("Tax withheld ({}%) on interest {} to {}", withholding.normalize(), period_start, period_end);
+            Some(post_interest_transaction(conn, wallet, TransactionType::WithholdingTax, amounts.tax, &description, metadata, actor)?)
+        } else {
+            None
+        };
+
+        let posting: InterestPosting = diesel::insert_into(ip::interest_postings)
+            .values(&NewInterestPosting {
+                wallet_id: wallet.wallet_id,
+                period_start,
+                period_end,
+                accrued_to_date,
+                gross_amount: amounts.gross,
+                tax_amount: amounts.tax,
+                net_amount: amounts.net,
+                currency_code: &wallet.currency_code,
+                interest_transaction_id: interest_tx.as_ref().map(|tx| tx.transaction_id),
+                tax_transaction_id: tax_tx.as_ref().map(|tx| tx.transaction_id),
+            })
+            .get_result(conn)?;
+        if let Some(interest_tx) = &interest_tx {
+            audit::log_db_audit_event(
+                conn, None, actor, "INTEREST_CAPITALIZED", Some(AuditTargetType::Transaction),
+                Some(&interest_tx.transaction_id.to_string()), AuditOutcome::Success,
+                Some(json!({"wallet_id": wallet.wallet_id, "period_end": period_end, "gross": amounts.gross.to_string(),
+                    "tax": amounts.tax.to_string(), "net": amounts.net.to_string()})),
+                None,
+            )?;
+            log::info!(
+                "Capitalized interest {} {} (tax {}) for wallet {} period ending {}",
+                amounts.gross, wallet.currency_code, amounts.tax, wallet.wallet_id, period_end
+            );
+        }
+        Ok(Some(posting))
+    })
+}
+
+/// Capitalizes the period for every interest-bearing wallet accrued through its end.
+/// A failing wallet is logged and skipped; it is retried on the next run.
+pub fn capitalize_all(
+    conn: &mut PgConnection,
+    period_start: NaiveDate,
+    period_end: NaiveDate,
+    actor: &str,
+) -> Result<Vec<InterestPosting>, DomainError> {
+    let mut postings = Vec::new();
+    let mut terms_cache: std::collections::BTreeMap<(String, String), Vec<RateTerms>> = Default::default();
+    for wallet in accrual::interest_bearing_wallets(conn)? {
+        let key = (wallet.wallet_type.clone(), wallet.currency_code.clone());
+        if !terms_cache.contains_key(&key) {
+            let terms = rates::load_terms(conn, &wallet.wallet_type, &wallet.currency_code)?;
+            terms_cache.insert(key.clone(), terms);
+        }
+        match capitalize_wallet(conn, &wallet, &terms_cache[&key], period_start, period_end, actor) {
+            Ok(Some(posting)) => postings.push(posting),
+            Ok(None) => log::debug!("Wallet {} not accrued through {} yet; capitalization deferred", wallet.wallet_id, period_end),
+            Err(e) => log::error!("Interest capitalization failed for wallet {}: {}", wallet.wallet_id, e),
+        }
+    }
+    Ok(postings)
+}
+
+/// Result of bringing interest up to date.
+#[derive(Debug, Serialize, Clone)]
+pub struct InterestRunSummary {
+    pub accrued_through: NaiveDate,
+    pub wallets_accrued: usize,
+    pub days_written: usize,
+    pub period_end: NaiveDate, // Capitalization period checked
+    pub postings: Vec<InterestPosting>, // Created or already present for the period
+}
+
+/// Accrues every wallet through yesterday, then capitalizes the previous month. Safe to repeat.
+pub fn run_interest(conn: &mut PgConnection, now: DateTime<Utc>, actor: &str) -> Result<InterestRunSummary, DomainError> {
+    let through = now.date_naive() - Duration::days(1);
+    let runs = accrual::accrue_all(conn, through, now)?;
+    let (period_start, period_end) = previous_month(now.date_naive());
+    let postings = capitalize_all(conn, period_start, period_end, actor)?;
+    Ok(InterestRunSummary {
+        accrued_through: through,
+        wallets_accrued: runs.iter().filter(|run| run.recomputed_from.is_some()).count(),
+        days_written: runs.iter().map(|run| run.days_written).sum(),
+        period_end,
+        postings,
+    })
+}
+
+/// Capitalizations of a wallet, newest first.
+pub fn list_postings(conn: &mut PgConnection, wallet_id: Uuid) -> Result<Vec<InterestPosting>, DomainError> {
+    use crate::schema::interest_postings::dsl as ip;
+    let postings = ip::interest_postings
+        .filter(ip::wallet_id.eq(wallet_id))
+        .order(ip::period_end.desc())
+        .load(conn)?;
+    Ok(postings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_amounts_round_once_and_withhold_tax() {
+        let amounts = capitalization_amounts(dec!(12.345678), Decimal::ZERO, dec!(25), "USD");
+        assert_eq!(amounts, CapitalizationAmounts { gross: dec!(12.34), tax: dec!(3.08), net: dec!(9.26) });
+        assert_eq!(capitalization_amounts(dec!(1000.9), Decimal::ZERO, Decimal::ZERO, "JPY").gross, dec!(1000));
+    }
+
+    #[test]
+    fn test_amounts_settle_recomputed_days() {
+        // March credited 12.34; a backdated increase lifts everything accrued through April to 30.00
+        assert_eq!(capitalization_amounts(dec!(30.004), dec!(12.34), Decimal::ZERO, "EUR").gross, dec!(17.66));
+        // A backdated decrease larger than April's interest credits nothing and is absorbed later
+        let absorbed = capitalization_amounts(dec!(10.00), dec!(12.34), dec!(25), "EUR");
+        assert_eq!(absorbed, CapitalizationAmounts { gross: Decimal::ZERO, tax: Decimal::ZERO, net: Decimal::ZERO });
+    }
+
+    #[test]
+    fn test_previous_month() {
+        let (start, end) = previous_month(NaiveDate::from_ymd_opt(2025, 3, 15).unwrap());
+        assert_eq!(start, NaiveDate::from_ymd_opt(2025, 2, 1).unwrap());
+        assert_eq!(end, NaiveDate::from_ymd_opt(2025, 2, 28).unwrap());
+        assert_eq!(previous_month(NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()).1, NaiveDate::from_ymd_opt(2024, 12, 31).unwrap());
+    }
+}
@@ -0,0 +1,104 @@
+// /home/inno/elights_jobes-research/backend/domain/src/interest/day_count.rs
+// Day-count conventions: the fraction of a year that one calendar day of accrual represents.
+use chrono::{Datelike, Duration, NaiveDate};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DayCountConvention {
+    #[serde(rename = "ACT_360")]
+    Act360, // Actual days / 360
+    #[serde(rename = "ACT_365")]
+    Act365, // Actual days / 365 (fixed, also in leap years)
+    #[serde(rename = "30_360")]
+    Thirty360, // 30/360 US bond basis: every month counts as 30 days
+}
+
+impl DayCountConvention {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DayCountConvention::Act360 => "ACT_360",
+            DayCountConvention::Act365 => "ACT_365",
+            DayCountConvention::Thirty360 => "30_360",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<DayCountConvention> {
+        match value {
+            "ACT_360" => Some(DayCountConvention::Act360),
+            "ACT_365" => Some(DayCountConvention::Act365),
+            "30_360" => Some(DayCountConvention::Thirty360),
+            _ => None,
+        }
+    }
+
+    /// Days between `start` and `end` under the convention.
+    pub fn days_between(&self, start: NaiveDate, end: NaiveDate) -> i64 {
+        match self {
+            DayCountConvention::Act360 | DayCountConvention::Act365 => (end - start).num_days(),
+            DayCountConvention::Thirty360 => {
+                let d1 = start.day().min(30) as i64;
+                let d2 = if d1 == 30 { end.day().min(30) as i64 } else { end.day() as i64 };
+                360 * (end.year() - start.year()) as i64
+                    + 30 * (end.month() as i64 - start.month() as i64)
+                    + (d2 - d1)
+            }
+        }
+    }
+
+    pub fn days_in_year(&self) -> i64 {
+        match self {
+            DayCountConvention::Act365 => 365,
+            DayCountConvention::Act360 | DayCountConvention::Thirty360 => 360,
+        }
+    }
+
+    /// Year fraction accrued for holding a balance over the day `date` (from `date` to the next day).
+    /// Under 30/360 one day counts for 0 to 3 days (the 30th of a 31-day month counts nothing, the last
+    /// day of February the rest of the month), so a full month always sums to 30/360.
+    pub fn year_fraction_for_day(&self, date: NaiveDate) -> Decimal {
+        let days = self.days_between(date, date + Duration::days(1));
+        Decimal::from(days) / Decimal::from(self.days_in_year())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        for convention in [DayCountConvention::Act360, DayCountConvention::Act365, DayCountConvention::Thirty360] {
+            assert_eq!(DayCountConvention::parse(convention.as_str()), Some(convention));
+        }
+        assert_eq!(DayCountConvention::parse("ACT_ACT"), None);
+    }
+
+    #[test]
+    fn test_actual_conventions() {
+        assert_eq!(DayCountConvention::Act360.year_fraction_for_day(date(2025, 1, 31)), dec!(1) / dec!(360));
+        assert_eq!(DayCountConvention::Act365.year_fraction_for_day(date(2024, 2, 29)), dec!(1) / dec!(365));
+        assert_eq!(DayCountConvention::Act365.days_between(date(2024, 1, 1), date(2025, 1, 1)), 366);
+    }
+
+    #[test]
+    fn test_thirty_360_months_count_thirty_days() {
+        let convention = DayCountConvention::Thirty360;
+        assert_eq!(convention.days_between(date(2025, 1, 30), date(2025, 1, 31)), 0);
+        assert_eq!(convention.days_between(date(2025, 1, 31), date(2025, 2, 1)), 1);
+        assert_eq!(convention.days_between(date(2025, 2, 28), date(2025, 3, 1)), 3);
+        assert_eq!(convention.days_between(date(2025, 1, 15), date(2025, 7, 15)), 180);
+
+        for (month, days) in [(1u32, 31u32), (2, 28), (4, 30)] {
+            let total: i64 = (1..=days)
+                .map(|day| convention.days_between(date(2025, month, day), date(2025, month, day) + Duration::days(1)))
+                .sum();
+            assert_eq!(total, 30, "month {}", month);
+        }
+    }
+}
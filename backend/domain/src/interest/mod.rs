@@ -0,0 +1,17 @@
+// /home/inno/elights_jobes-research/backend/domain/src/interest/mod.rs
+// Interest on savings-type wallets. Terms are effective-dated versions per wallet type and currency
+// (`rates`); interest accrues daily on the value-dated balance under the version's day-count convention
+// (`accrual`) and is capitalized monthly, net of withholding tax, as its own transactions (`capitalization`).
+
+pub mod day_count; // ACT/360, ACT/365 and 30/360 year fractions
+pub mod rates; // Tiered rate versions and the terms in force on a day
+pub mod accrual; // Daily accrual with recomputation of backdated days
+pub mod capitalization; // Monthly InterestCredit / WithholdingTax postings
+
+pub use day_count::DayCountConvention;
+pub use rates::{create_rate_version, list_rate_versions, load_terms, terms_on, CreateRateVersion, RateTerms, RateTier};
+pub use accrual::{accrue_all, accrue_days, accrue_wallet, interest_bearing_wallets, list_accruals, DailyAccrual, WalletAccrualRun};
+pub use capitalization::{
+    capitalization_amounts, capitalize_all, capitalize_wallet, list_postings, previous_month, run_interest,
+    CapitalizationAmounts, InterestRunSummary,
+};
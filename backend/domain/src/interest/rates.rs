@@ -0,0 +1,261 @@
+// /home/inno/elights_jobes-research/backend/domain/src/interest/rates.rs
+// Interest terms per wallet type and currency. Terms are versioned and never edited: a rate change is a
+// new version with its own `effective_from`, which may lie in the past (a backdated change). The terms
+// for a day are those of the version with the latest `effective_from` on or before it; of two versions
+// with the same date the later-created one wins.
+use super::day_count::DayCountConvention;
+use crate::error::DomainError;
+use crate::models::{AuditOutcome, AuditTargetType, InterestRateVersion, NewInterestRateVersion, WalletType};
+use crate::security::audit;
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+/// One band of a tiered rate. Bands are ordered by `up_to`; the last one is open-ended.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RateTier {
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub up_to: Option<Decimal>, // Inclusive upper bound of the band, None = no limit
+    #[serde(with = "rust_decimal::serde::str")]
+    pub rate_percent: Decimal, // Annual rate, 2.5 = 2.5% p.a.
+}
+
+/// Parsed terms of one rate version.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateTerms {
+    pub rate_id: Uuid,
+    pub effective_from: NaiveDate,
+    pub day_count: DayCountConvention,
+    pub tiers: Vec<RateTier>,
+    pub withholding_tax_percent: Decimal,
+    pub created_at: DateTime<Utc>,
+}
+
+pub fn validate_tiers(tiers: &[RateTier]) -> Result<(), DomainError> {
+    let Some(last) = tiers.last() else {
+        return Err(DomainError::Validation("At least one rate tier is required".to_string()));
+    };
+    if last.up_to.is_some() {
+        return Err(DomainError::Validation("The last rate tier must be open-ended (no 'up_to')".to_string()));
+    }
+    let mut previous = Decimal::ZERO;
+    for tier in tiers {
+        if tier.rate_percent < Decimal::ZERO {
+            return Err(DomainError::Validation("Interest rates cannot be negative".to_string()));
+        }
+        if let Some(up_to) = tier.up_to {
+            if up_to <= previous {
+                return Err(DomainError::Validation("Rate tier bounds must be positive and ascending".to_string()));
+            }
+            previous = up_to;
+        } else if !std::ptr::eq(tier, last) {
+            return Err(DomainError::Validation("Only the last rate tier may be open-ended".to_string()));
+        }
+    }
+    Ok(())
+}
+
+impl RateTerms {
+    pub fn from_version(version: &InterestRateVersion) -> Result<RateTerms, DomainError> {
+        let day_count = DayCountConvention::parse(&version.day_count).ok_or_else(|| {
+            DomainError::Internal(format!("Rate version {} has unknown day count '{}'", version.rate_id, version.day_count))
+        })?;
+        let tiers: Vec<RateTier> = serde_json::from_value(version.tiers.clone())
+            .map_err(|e| DomainError::Internal(format!("Rate version {} has malformed tiers: {}", version.rate_id, e)))?;
+        Ok(RateTerms {
+            rate_id: version.rate_id,
+            effective_from: version.effective_from,
+            day_count,
+            tiers,
+            withholding_tax_percent: version.withholding_tax_percent,
+            created_at: version.created_at,
+        })
+    }
+
+    /// Interest for a full year on `balance`, each band at its own rate. Overdrawn balances earn nothing.
+    pub fn annual_interest(&self, balance: Decimal) -> Decimal {
+        let mut interest = Decimal::ZERO;
+        let mut lower = Decimal::ZERO;
+        for tier in &self.tiers {
+            if balance <= lower {
+                break;
+            }
+            let upper = tier.up_to.map_or(balance, |up_to| up_to.min(balance));
+            interest += (upper - lower) * tier.rate_percent / Decimal::ONE_HUNDRED;
+            lower = upper;
+        }
+        interest
+    }
+
+    /// The single rate that yields `annual_interest` on the whole balance (for statements).
+    pub fn effective_rate_percent(&self, balance: Decimal) -> Decimal {
+        if balance <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+        self.annual_interest(balance) * Decimal::ONE_HUNDRED / balance
+    }
+}
+
+/// Terms in force on `date`. `terms` may be in any order.
+pub fn terms_on(terms: &[RateTerms], date: NaiveDate) -> Option<&RateTerms> {
+    terms.iter()
+        .filter(|terms| terms.effective_from <= date)
+        .max_by(|a, b| a.effective_from.cmp(&b.effective_from).then(a.created_at.cmp(&b.created_at)))
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CreateRateVersion {
+    pub wallet_type: String,
+    pub currency_code: String,
+    pub effective_from: NaiveDate,
+    pub day_count: DayCountConvention,
+    pub tiers: Vec<RateTier>,
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub withholding_tax_percent: Option<Decimal>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Records a new rate version. A backdated `effective_from` is picked up by the next accrual run,
+/// which recomputes the affected days.
+pub fn create_rate_version(
+    conn: &mut PgConnection,
+    request: &CreateRateVersion,
+    created_by: Option<Uuid>,
+    actor: &str,
+) -> Result<InterestRateVersion, DomainError> {
+    use crate::schema::interest_rate_versions::dsl as irv;
+
+    let wallet_type = WalletType::parse(&request.wallet_type)
+        .filter(WalletType::is_fiat)
+        .ok_or_else(|| DomainError::Validation(format!("'{}' is not an interest-bearing wallet type", request.wallet_type)))?;
+    let currency = request.currency_code.trim().to_uppercase();
+    if currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(DomainError::Validation(format!("Invalid currency code '{}'", request.currency_code)));
+    }
+    validate_tiers(&request.tiers)?;
+    let withholding_tax_percent = request.withholding_tax_percent.unwrap_or(Decimal::ZERO);
+    if withholding_tax_percent < Decimal::ZERO || withholding_tax_percent > Decimal::ONE_HUNDRED {
+        return Err(DomainError::Validation("Withholding tax must be between 0 and 100 percent".to_string()));
+    }
+    let tiers = serde_json::to_value(&request.tiers)
+        .map_err(|e| DomainError::Internal(format!("Failed to serialize rate tiers: {}", e)))?;
+
+    conn.transaction(|conn| {
+        let version: InterestRateVersion = diesel::insert_into(irv::interest_rate_versions)
+            .values(&NewInterestRateVersion {
+                wallet_type: wallet_type.as_str(),
+                currency_code: &currency,
+                effective_from: request.effective_from,
+                day_count: request.day_count.as_str(),
+                tiers,
+                withholding_tax_percent,
+                note: request.note.as_deref(),
+                created_by,
+            })
+            .get_result(conn)?;
+        audit::log_db_audit_event(
+            conn, created_by, actor, "INTEREST_RATE_VERSION_CREATE", Some(AuditTargetType::InterestRate),
+            Some(&version.rate_id.to_string()), AuditOutcome::Success,
+            Some(json!({"wallet_type": version.wallet_type, "currency": version.currency_code,
+                "effective_from": version.effective_from, "day_count": version.day_count, "tiers": version.tiers})),
+            None,
+        )?;
+        log::info!(
+            "Interest rate version {} for {} {} effective {} created by {}",
+            version.rate_id, version.wallet_type, version.currency_code, version.effective_from, actor
+        );
+        Ok(version)
+    })
+}
+
+/// Rate versions, newest effective date first, optionally for one wallet type and/or currency.
+pub fn list_rate_versions(
+    conn: &mut PgConnection,
+    wallet_type: Option<&str>,
+    currency: Option<&str>,
+) -> Result<Vec<InterestRateVersion>, DomainError> {
+    use crate::schema::interest_rate_versions::dsl as irv;
+
+    let mut query = irv::interest_rate_versions.into_boxed();
+    if let Some(wallet_type) = wallet_type {
+        query = query.filter(irv::wallet_type.eq(wallet_type.to_string()));
+    }
+    if let Some(currency) = currency {
+        query = query.filter(irv::currency_code.eq(currency.to_uppercase()));
+    }
+    let versions = query
+        .order((irv::effective_from.desc(), irv::created_at.desc()))
+        .load(conn)?;
+    Ok(versions)
+}
+
+/// Parsed terms of every version for a wallet type and currency.
+pub fn load_terms(conn: &mut PgConnection, wallet_type: &str, currency: &str) -> Result<Vec<RateTerms>, DomainError> {
+    list_rate_versions(conn, Some(wallet_type), Some(currency))?
+        .iter()
+        .map(RateTerms::from_version)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use rust_decimal_macros::dec;
+
+    fn terms(effective_from: NaiveDate, created_offset_hours: i64, tiers: Vec<RateTier>) -> RateTerms {
+        RateTerms {
+            rate_id: Uuid::new_v4(),
+            effective_from,
+            day_count: DayCountConvention::Act365,
+            tiers,
+            withholding_tax_percent: Decimal::ZERO,
+            created_at: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap() + Duration::hours(created_offset_hours),
+        }
+    }
+
+    fn tier(up_to: Option<Decimal>, rate_percent: Decimal) -> RateTier {
+        RateTier { up_to, rate_percent }
+    }
+
+    #[test]
+    fn test_banded_interest() {
+        let tiered = terms(
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(), 0,
+            vec![tier(Some(dec!(10000)), dec!(1)), tier(Some(dec!(50000)), dec!(2)), tier(None, dec!(3))],
+        );
+        assert_eq!(tiered.annual_interest(dec!(5000)), dec!(50));
+        // 10,000 at 1% + 40,000 at 2% + 10,000 at 3%
+        assert_eq!(tiered.annual_interest(dec!(60000)), dec!(1200));
+        assert_eq!(tiered.annual_interest(dec!(-100)), Decimal::ZERO);
+        assert_eq!(tiered.effective_rate_percent(dec!(60000)), dec!(2));
+    }
+
+    #[test]
+    fn test_tier_validation() {
+        assert!(validate_tiers(&[tier(None, dec!(1.5))]).is_ok());
+        assert!(validate_tiers(&[]).is_err());
+        assert!(validate_tiers(&[tier(Some(dec!(100)), dec!(1))]).is_err());
+        assert!(validate_tiers(&[tier(Some(dec!(100)), dec!(1)), tier(Some(dec!(50)), dec!(1)), tier(None, dec!(1))]).is_err());
+        assert!(validate_tiers(&[tier(None, dec!(1)), tier(None, dec!(2))]).is_err());
+        assert!(validate_tiers(&[tier(None, dec!(-0.5))]).is_err());
+    }
+
+    #[test]
+    fn test_terms_on_prefers_latest_effective_then_latest_created() {
+        let jan = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let mar = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let versions = vec![
+            terms(jan, 0, vec![tier(None, dec!(1))]),
+            terms(mar, 1, vec![tier(None, dec!(2))]),
+            terms(mar, 5, vec![tier(None, dec!(2.5))]), // Correction of the March change
+        ];
+        assert!(terms_on(&versions, jan - Duration::days(1)).is_none());
+        assert_eq!(terms_on(&versions, mar - Duration::days(1)).unwrap().tiers[0].rate_percent, dec!(1));
+        assert_eq!(terms_on(&versions, mar).unwrap().tiers[0].rate_percent, dec!(2.5));
+    }
+}
@@ -0,0 +1,87 @@
+// /home/inno/elights_jobes-research/backend/domain/src/ip_intel/lists.rs
+// Local address lists: Tor exit nodes (e.g. the Tor Project's bulk exit list), VPN provider ranges and
+// hosting/data centre ranges. One address or CIDR range per line; `#` starts a comment.
+use crate::error::DomainError;
+use ipnetwork::IpNetwork;
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::path::Path;
+
+/// Addresses and ranges read from a list file.
+#[derive(Debug, Clone, Default)]
+pub struct IpList {
+    addresses: HashSet<IpAddr>, // Single addresses (most Tor exit lists)
+    ranges: Vec<IpNetwork>,
+}
+
+impl IpList {
+    /// Parses a list; `name` is only used in error messages.
+    pub fn parse(text: &str, name: &str) -> Result<Self, DomainError> {
+        let mut list = IpList::default();
+        for (number, line) in text.lines().enumerate() {
+            let entry = line.split('#').next().unwrap_or_default().trim();
+            if entry.is_empty() {
+                continue;
+            }
+            if let Ok(address) = entry.parse::<IpAddr>() {
+                list.addresses.insert(address.to_canonical());
+                continue;
+            }
+            let range = entry.parse::<IpNetwork>().map_err(|e| {
+                DomainError::Configuration(format!("{} line {}: invalid address or range '{}': {}", name, number + 1, entry, e))
+            })?;
+            list.ranges.push(range);
+        }
+        Ok(list)
+    }
+
+    /// Reads a list file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, DomainError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| DomainError::Configuration(format!("Cannot read IP list {}: {}", path.display(), e)))?;
+        Self::parse(&text, &path.display().to_string())
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical(); // ::ffff:a.b.c.d is matched as a.b.c.d
+        self.addresses.contains(&ip) || self.ranges.iter().any(|range| range.contains(ip))
+    }
+
+    /// Number of addresses and ranges listed.
+    pub fn len(&self) -> usize {
+        self.addresses.len() + self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_addresses_and_ranges() {
+        let list = IpList::parse(
+            "# Tor exits, 2025-04-20\n198.51.100.7\n203.0.113.0/24   # hosting\n\n2001:db8:beef::/48\n",
+            "test list",
+        )
+        .unwrap();
+        assert_eq!(list.len(), 3);
+        assert!(list.contains("198.51.100.7".parse().unwrap()));
+        assert!(!list.contains("198.51.100.8".parse().unwrap()));
+        assert!(list.contains("203.0.113.200".parse().unwrap()));
+        assert!(list.contains("::ffff:203.0.113.9".parse().unwrap()));
+        assert!(list.contains("2001:db8:beef:1::1".parse().unwrap()));
+        assert!(!list.contains("2001:db8:cafe::1".parse().unwrap()));
+    }
+
+    #[test]
+    fn rejects_invalid_lines() {
+        let err = IpList::parse("198.51.100.7\nnot-an-address\n", "vpn.txt").unwrap_err();
+        assert!(err.to_string().contains("vpn.txt line 2"), "{}", err);
+        assert!(IpList::parse("203.0.113.0/33", "vpn.txt").is_err());
+    }
+}
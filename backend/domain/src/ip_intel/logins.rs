@@ -0,0 +1,125 @@
+// /home/inno/elights_jobes-research/backend/domain/src/ip_intel/logins.rs
+// Successful logins with what the local databases said about their address. Each located login is compared with
+// the customer's previous located login; impossible travel between the two is recorded on the login and audited,
+// and the fraud rules see it on the customer's next payments (`history.impossible_logins_24h`).
+use super::travel::{self, GeoPoint};
+use crate::error::DomainError;
+use crate::fraud::DeviceContext;
+use crate::models::{AuditOutcome, AuditTargetType, LoginEvent, NewLoginEvent};
+use crate::security::audit;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde_json::json;
+use uuid::Uuid;
+
+const ACTOR: &str = "IP_INTELLIGENCE";
+
+impl LoginEvent {
+    pub fn location(&self) -> Option<GeoPoint> {
+        Some(GeoPoint::new(self.latitude?, self.longitude?))
+    }
+}
+
+/// The customer's latest login with a known location, if any.
+pub fn last_located_login(conn: &mut PgConnection, user_id: Uuid) -> Result<Option<LoginEvent>, DomainError> {
+    use crate::schema::login_events::dsl as l;
+    Ok(l::login_events
+        .filter(l::user_id.eq(user_id))
+        .filter(l::latitude.is_not_null())
+        .filter(l::longitude.is_not_null())
+        .order(l::created_at.desc())
+        .select(LoginEvent::as_select())
+        .first(conn)
+        .optional()?)
+}
+
+/// Records a successful login. `device.network` is the lookup of the client address (see `IpIntelligence`).
+pub fn record_login(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    device: &DeviceContext,
+    now: DateTime<Utc>,
+) -> Result<LoginEvent, DomainError> {
+    use crate::schema::login_events::dsl as l;
+
+    conn.transaction(|conn| {
+        let network = device.network.clone().unwrap_or_default();
+        let here = network.location();
+        let previous = match here {
+            Some(_) => last_located_login(conn, user_id)?,
+            None => None,
+        };
+        let trip = match (&previous, here) {
+            (Some(previous), Some(here)) => previous.location().map(|from| travel::travel(from, previous.created_at, here, now)),
+            _ => None,
+        };
+
+        let login = diesel::insert_into(l::login_events)
+            .values(&NewLoginEvent {
+                user_id,
+                ip_address: device.ip.map(|ip| ip.to_string()),
+                user_agent: device.user_agent.as_deref(),
+                device_id: device.device_id.as_deref(),
+                country_code: network.country.as_deref(),
+                latitude: network.latitude,
+                longitude: network.longitude,
+                asn: network.asn.map(i64::from),
+                as_org: network.as_org.as_deref(),
+                tor: network.tor,
+                vpn: network.vpn,
+                hosting: network.hosting,
+                ip_risk: network.risk as i32,
+                previous_login_id: previous.as_ref().map(|p| p.login_id),
+                distance_km: trip.map(|t| t.distance_km),
+                travel_speed_kmh: trip.map(|t| t.speed_kmh),
+                impossible_travel: trip.is_some_and(|t| t.impossible),
+            })
+            .returning(LoginEvent::as_returning())
+            .get_result(conn)?;
+
+        if let (true, Some(previous), Some(trip)) = (login.impossible_travel, &previous, trip) {
+            log::warn!(
+                "Impossible travel for user {}: {} ({:?}) to {} ({:?}), {:.0} km in {:.1} h",
+                user_id, previous.ip_address.as_deref().unwrap_or("?"), previous.country_code,
+                login.ip_address.as_deref().unwrap_or("?"), login.country_code, trip.distance_km, trip.hours
+            );
+            audit::log_db_audit_event(
+                conn,
+                Some(user_id),
+                ACTOR,
+                "LOGIN_IMPOSSIBLE_TRAVEL",
+                Some(AuditTargetType::User),
+                Some(&user_id.to_string()),
+                AuditOutcome::Success,
+                Some(json!({"login_id": login.login_id, "previous_login_id": previous.login_id,
+                    "from": {"ip": previous.ip_address, "country": previous.country_code, "at": previous.created_at},
+                    "to": {"ip": login.ip_address, "country": login.country_code, "at": login.created_at},
+                    "distance_km": trip.distance_km.round(), "speed_kmh": trip.speed_kmh.round()})),
+                None,
+            )?;
+        }
+        Ok(login)
+    })
+}
+
+/// A customer's logins, newest first.
+pub fn list_logins(conn: &mut PgConnection, user_id: Uuid, limit: i64) -> Result<Vec<LoginEvent>, DomainError> {
+    use crate::schema::login_events::dsl as l;
+    Ok(l::login_events
+        .filter(l::user_id.eq(user_id))
+        .order(l::created_at.desc())
+        .limit(limit)
+        .select(LoginEvent::as_select())
+        .load(conn)?)
+}
+
+/// Logins of the customer since `since` flagged as impossible travel.
+pub fn impossible_travel_logins_since(conn: &mut PgConnection, user_id: Uuid, since: DateTime<Utc>) -> Result<i64, DomainError> {
+    use crate::schema::login_events::dsl as l;
+    Ok(l::login_events
+        .filter(l::user_id.eq(user_id))
+        .filter(l::impossible_travel.eq(true))
+        .filter(l::created_at.ge(since))
+        .count()
+        .get_result(conn)?)
+}
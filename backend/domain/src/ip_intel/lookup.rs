@@ -0,0 +1,334 @@
+// /home/inno/elights_jobes-research/backend/domain/src/ip_intel/lookup.rs
+// What the local databases know about an IP address: country and location from a GeoIP2/GeoLite2 City (or
+// Country) database, the autonomous system from a GeoLite2 ASN database, and whether the address is a listed
+// Tor exit, VPN or hosting address. Every source is optional; nothing leaves the process.
+use super::lists::IpList;
+use super::travel::GeoPoint;
+use crate::config::{self, JsonConfig};
+use crate::error::DomainError;
+use chrono::{DateTime, Utc};
+use maxminddb::{geoip2, MaxMindDBError, Reader};
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+
+/// Points each network signal adds to an address's risk (capped at 100).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct RiskWeights {
+    pub tor: u32,
+    pub vpn: u32,
+    pub hosting: u32, // Data centres: proxies, bots and rented servers rather than customers
+    pub unlocated: u32, // Public address the country database does not know
+}
+
+impl Default for RiskWeights {
+    fn default() -> Self {
+        RiskWeights { tor: 60, vpn: 30, hosting: 20, unlocated: 10 }
+    }
+}
+
+/// IP intelligence configuration, loaded from a JSON file. Paths are optional: an absent source is not used.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct IpIntelConfig {
+    #[serde(default)]
+    pub version: Option<String>, // Free text, logged when the configuration is loaded
+    #[serde(default)]
+    pub country_db: Option<String>, // MaxMind-format City or Country database (.mmdb); City adds locations
+    #[serde(default)]
+    pub asn_db: Option<String>, // MaxMind-format ASN database (.mmdb)
+    #[serde(default)]
+    pub tor_exit_list: Option<String>,
+    #[serde(default)]
+    pub vpn_list: Option<String>,
+    #[serde(default)]
+    pub hosting_list: Option<String>,
+    #[serde(default)]
+    pub risk_weights: RiskWeights,
+}
+
+impl JsonConfig for IpIntelConfig {
+    const NAME: &'static str = "IP intelligence configuration";
+
+    fn validate(&self) -> Result<(), DomainError> {
+        let weights = &self.risk_weights;
+        for (name, weight) in [("tor", weights.tor), ("vpn", weights.vpn), ("hosting", weights.hosting), ("unlocated", weights.unlocated)] {
+            if weight > 100 {
+                return Err(DomainError::Configuration(format!("risk_weights.{} must be between 0 and 100", name)));
+            }
+        }
+        let paths = [&self.country_db, &self.asn_db, &self.tor_exit_list, &self.vpn_list, &self.hosting_list];
+        if paths.iter().any(|path| path.as_deref().is_some_and(|p| p.trim().is_empty())) {
+            return Err(DomainError::Configuration("IP intelligence source paths must not be empty".to_string()));
+        }
+        Ok(())
+    }
+
+    fn summary(&self) -> String {
+        self.version.as_deref().unwrap_or("unversioned").to_string()
+    }
+}
+
+/// What is known about one address.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct IpInfo {
+    pub country: Option<String>, // ISO 3166-1 alpha-2
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub accuracy_km: Option<u16>, // Radius the location is good to
+    pub asn: Option<u32>,
+    pub as_org: Option<String>,
+    pub tor: bool,
+    pub vpn: bool,
+    pub hosting: bool,
+    pub private: bool, // Loopback, private or link-local: nothing to look up (internal callers, tests)
+    pub risk: u32, // 0-100 from the configured weights
+}
+
+impl IpInfo {
+    pub fn location(&self) -> Option<GeoPoint> {
+        Some(GeoPoint::new(self.latitude?, self.longitude?))
+    }
+}
+
+/// A database in use, as reported to administrators.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct DatabaseInfo {
+    pub path: String,
+    pub database_type: String,
+    pub built_at: Option<DateTime<Utc>>,
+}
+
+/// The sources in use.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct IpIntelStatus {
+    pub version: Option<String>,
+    pub country_db: Option<DatabaseInfo>,
+    pub asn_db: Option<DatabaseInfo>,
+    pub tor_exits: usize,
+    pub vpn_ranges: usize,
+    pub hosting_ranges: usize,
+    pub risk_weights: RiskWeights,
+}
+
+struct Database {
+    path: String,
+    reader: Reader<Vec<u8>>,
+}
+
+impl Database {
+    fn open(path: &str) -> Result<Self, DomainError> {
+        let reader = Reader::open_readfile(path)
+            .map_err(|e| DomainError::Configuration(format!("Cannot open MaxMind database {}: {}", path, e)))?;
+        log::info!("Opened {} database {} (built {})", reader.metadata.database_type, path, reader.metadata.build_epoch);
+        Ok(Database { path: path.to_string(), reader })
+    }
+
+    fn info(&self) -> DatabaseInfo {
+        DatabaseInfo {
+            path: self.path.clone(),
+            database_type: self.reader.metadata.database_type.clone(),
+            built_at: DateTime::from_timestamp(self.reader.metadata.build_epoch as i64, 0),
+        }
+    }
+
+    /// The record for an address; an address the database does not cover is not an error.
+    fn lookup<'de, T: Deserialize<'de>>(&'de self, ip: IpAddr) -> Option<T> {
+        match self.reader.lookup::<T>(ip) {
+            Ok(record) => Some(record),
+            Err(MaxMindDBError::AddressNotFoundError(_)) => None,
+            Err(e) => {
+                log::warn!("Lookup of {} in {} failed: {}", ip, self.path, e);
+                None
+            }
+        }
+    }
+}
+
+/// Local IP intelligence, loaded once at startup.
+pub struct IpIntelligence {
+    version: Option<String>,
+    country_db: Option<Database>,
+    asn_db: Option<Database>,
+    tor_exits: IpList,
+    vpn_ranges: IpList,
+    hosting_ranges: IpList,
+    weights: RiskWeights,
+}
+
+fn is_internal(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => v4.is_loopback() || v4.is_private() || v4.is_link_local() || v4.is_unspecified(),
+        IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            v6.is_loopback() || v6.is_unspecified() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+impl IpIntelligence {
+    /// Opens the databases and reads the lists named in the configuration.
+    pub fn load(config: &IpIntelConfig) -> Result<Self, DomainError> {
+        config.validate()?;
+        let list = |path: &Option<String>| path.as_deref().map(IpList::load).transpose().map(Option::unwrap_or_default);
+        let intel = IpIntelligence {
+            version: config.version.clone(),
+            country_db: config.country_db.as_deref().map(Database::open).transpose()?,
+            asn_db: config.asn_db.as_deref().map(Database::open).transpose()?,
+            tor_exits: list(&config.tor_exit_list)?,
+            vpn_ranges: list(&config.vpn_list)?,
+            hosting_ranges: list(&config.hosting_list)?,
+            weights: config.risk_weights.clone(),
+        };
+        log::info!(
+            "IP intelligence ready: {} Tor exits, {} VPN and {} hosting entries",
+            intel.tor_exits.len(),
+            intel.vpn_ranges.len(),
+            intel.hosting_ranges.len()
+        );
+        Ok(intel)
+    }
+
+    /// No sources: every address is unknown and carries no risk.
+    pub fn disabled() -> Self {
+        IpIntelligence {
+            version: None,
+            country_db: None,
+            asn_db: None,
+            tor_exits: IpList::default(),
+            vpn_ranges: IpList::default(),
+            hosting_ranges: IpList::default(),
+            weights: RiskWeights::default(),
+        }
+    }
+
+    /// Replaces the Tor exit, VPN and hosting lists.
+    pub fn with_lists(mut self, tor_exits: IpList, vpn_ranges: IpList, hosting_ranges: IpList) -> Self {
+        self.tor_exits = tor_exits;
+        self.vpn_ranges = vpn_ranges;
+        self.hosting_ranges = hosting_ranges;
+        self
+    }
+
+    pub fn status(&self) -> IpIntelStatus {
+        IpIntelStatus {
+            version: self.version.clone(),
+            country_db: self.country_db.as_ref().map(Database::info),
+            asn_db: self.asn_db.as_ref().map(Database::info),
+            tor_exits: self.tor_exits.len(),
+            vpn_ranges: self.vpn_ranges.len(),
+            hosting_ranges: self.hosting_ranges.len(),
+            risk_weights: self.weights.clone(),
+        }
+    }
+
+    /// Everything known about an address, with its network risk.
+    pub fn lookup(&self, ip: IpAddr) -> IpInfo {
+        let ip = ip.to_canonical();
+        if is_internal(ip) {
+            return IpInfo { private: true, ..Default::default() };
+        }
+        let mut info = IpInfo {
+            tor: self.tor_exits.contains(ip),
+            vpn: self.vpn_ranges.contains(ip),
+            hosting: self.hosting_ranges.contains(ip),
+            ..Default::default()
+        };
+        if let Some(city) = self.country_db.as_ref().and_then(|db| db.lookup::<geoip2::City>(ip)) {
+            // Where the address is used; fall back to where the block is registered
+            info.country = city
+                .country
+                .and_then(|c| c.iso_code)
+                .or_else(|| city.registered_country.and_then(|c| c.iso_code))
+                .map(str::to_uppercase);
+            if let Some(location) = city.location {
+                info.latitude = location.latitude;
+                info.longitude = location.longitude;
+                info.accuracy_km = location.accuracy_radius;
+            }
+        }
+        if let Some(asn) = self.asn_db.as_ref().and_then(|db| db.lookup::<geoip2::Asn>(ip)) {
+            info.asn = asn.autonomous_system_number;
+            info.as_org = asn.autonomous_system_organization.map(str::to_string);
+        }
+
+        let weights = &self.weights;
+        let unlocated = self.country_db.is_some() && info.country.is_none();
+        let risk = [(info.tor, weights.tor), (info.vpn, weights.vpn), (info.hosting, weights.hosting), (unlocated, weights.unlocated)]
+            .iter()
+            .filter(|(signal, _)| *signal)
+            .map(|(_, weight)| weight)
+            .sum::<u32>();
+        info.risk = risk.min(100);
+        info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/ip-intel-test.mmdb");
+
+    fn intel() -> IpIntelligence {
+        let config = IpIntelConfig { country_db: Some(FIXTURE.to_string()), asn_db: Some(FIXTURE.to_string()), ..Default::default() };
+        IpIntelligence::load(&config).unwrap().with_lists(
+            IpList::parse("81.2.69.160\n", "tor").unwrap(),
+            IpList::parse("203.0.113.0/24\n", "vpn").unwrap(),
+            IpList::parse("2001:db8:1::/48\n", "hosting").unwrap(),
+        )
+    }
+
+    #[test]
+    fn looks_up_country_location_and_asn() {
+        let intel = intel();
+        let info = intel.lookup("89.160.20.112".parse().unwrap());
+        assert_eq!(info.country.as_deref(), Some("SE"));
+        assert_eq!(info.asn, Some(29518));
+        assert_eq!(info.as_org.as_deref(), Some("Bredband2 AB"));
+        assert!(info.location().is_some());
+        assert_eq!(info.risk, 0);
+
+        // IPv6, and IPv4 written as an IPv4-mapped IPv6 address
+        let v6 = intel.lookup("2001:db8:1::25".parse().unwrap());
+        assert_eq!((v6.country.as_deref(), v6.asn, v6.hosting), (Some("DE"), Some(64500), true));
+        assert_eq!(v6.risk, 20);
+        assert_eq!(intel.lookup("::ffff:216.160.83.56".parse().unwrap()).country.as_deref(), Some("US"));
+
+        let status = intel.status();
+        assert_eq!(status.country_db.unwrap().database_type, "Elights-IP-Intel-Test");
+        assert_eq!((status.tor_exits, status.vpn_ranges, status.hosting_ranges), (1, 1, 1));
+    }
+
+    #[test]
+    fn scores_network_signals() {
+        let intel = intel();
+        let tor = intel.lookup("81.2.69.160".parse().unwrap());
+        assert!(tor.tor && tor.country.as_deref() == Some("GB"));
+        assert_eq!(tor.risk, 60);
+
+        let vpn = intel.lookup("203.0.113.10".parse().unwrap());
+        assert!(vpn.vpn);
+        assert_eq!(vpn.country.as_deref(), Some("NL"));
+        assert!(vpn.location().is_none()); // Country-only record
+        assert_eq!(vpn.risk, 30);
+
+        let unknown = intel.lookup("198.51.100.1".parse().unwrap());
+        assert_eq!((unknown.country, unknown.asn, unknown.risk), (None, None, 10));
+
+        let internal = intel.lookup("10.1.2.3".parse().unwrap());
+        assert!(internal.private);
+        assert_eq!(internal.risk, 0);
+
+        let disabled = IpIntelligence::disabled().lookup("198.51.100.1".parse().unwrap());
+        assert_eq!(disabled, IpInfo::default());
+    }
+
+    #[test]
+    fn validates_configuration() {
+        let config = config::parse_json::<IpIntelConfig>(r#"{"country_db": "GeoLite2-City.mmdb", "risk_weights": {"tor": 80}}"#).unwrap();
+        assert_eq!(config.risk_weights, RiskWeights { tor: 80, ..RiskWeights::default() });
+        assert!(config::parse_json::<IpIntelConfig>(r#"{"risk_weights": {"vpn": 150}}"#).is_err());
+        assert!(config::parse_json::<IpIntelConfig>(r#"{"asn_db": " "}"#).is_err());
+        assert!(IpIntelligence::load(&IpIntelConfig { country_db: Some("missing.mmdb".to_string()), ..Default::default() }).is_err());
+    }
+}
@@ -0,0 +1,13 @@
+// /home/inno/elights_jobes-research/backend/domain/src/ip_intel/mod.rs
+// Offline IP intelligence for fraud scoring: country, location and autonomous system from local MaxMind-format
+// databases, Tor exit / VPN / hosting lists, and impossible travel between a customer's consecutive logins.
+
+pub mod lists; // Tor exit, VPN and hosting address lists
+pub mod lookup; // Configuration, .mmdb lookups and network risk of an address
+pub mod travel; // Great-circle distance and impossible travel between two sightings
+pub mod logins; // Geolocated logins, impossible travel between consecutive logins
+
+pub use lists::IpList;
+pub use logins::{impossible_travel_logins_since, last_located_login, list_logins, record_login};
+pub use lookup::{DatabaseInfo, IpInfo, IpIntelConfig, IpIntelStatus, IpIntelligence, RiskWeights};
+pub use travel::{travel, GeoPoint, Travel, MAX_TRAVEL_SPEED_KMH, MIN_TRAVEL_DISTANCE_KM};
@@ -0,0 +1,91 @@
+// /home/inno/elights_jobes-research/backend/domain/src/ip_intel/travel.rs
+// Impossible travel: two sightings of the same customer too far apart for the time between them. City-level
+// geolocation is tens of kilometres off (more for mobile and satellite networks), so short hops never count.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+/// Faster than this between two sightings is not travel: a long-haul airliner cruises at about 900 km/h.
+pub const MAX_TRAVEL_SPEED_KMH: f64 = 1000.0;
+/// Sightings closer than this are never impossible travel, whatever the time between them.
+pub const MIN_TRAVEL_DISTANCE_KM: f64 = 500.0;
+
+/// A geolocated position.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct GeoPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl GeoPoint {
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        GeoPoint { latitude, longitude }
+    }
+
+    /// Great-circle (haversine) distance.
+    pub fn distance_km(&self, other: &GeoPoint) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.longitude - self.longitude).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+    }
+}
+
+/// Movement between two sightings.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+pub struct Travel {
+    pub distance_km: f64,
+    pub hours: f64,
+    pub speed_kmh: f64,
+    pub impossible: bool,
+}
+
+/// Movement from `from` (seen at `from_at`) to `to` (seen at `to_at`). Sightings less than a minute apart are
+/// treated as a minute apart, so two places at once give a finite (and impossible) speed.
+pub fn travel(from: GeoPoint, from_at: DateTime<Utc>, to: GeoPoint, to_at: DateTime<Utc>) -> Travel {
+    let distance_km = from.distance_km(&to);
+    let hours = ((to_at - from_at).num_seconds().abs().max(60) as f64) / 3600.0;
+    let speed_kmh = distance_km / hours;
+    Travel {
+        distance_km,
+        hours,
+        speed_kmh,
+        impossible: distance_km >= MIN_TRAVEL_DISTANCE_KM && speed_kmh > MAX_TRAVEL_SPEED_KMH,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    const LONDON: GeoPoint = GeoPoint { latitude: 51.5142, longitude: -0.0931 };
+    const STOCKHOLM: GeoPoint = GeoPoint { latitude: 59.3293, longitude: 18.0686 };
+    const SEATTLE: GeoPoint = GeoPoint { latitude: 47.6062, longitude: -122.3321 };
+
+    #[test]
+    fn distances() {
+        assert!((LONDON.distance_km(&STOCKHOLM) - 1435.0).abs() < 10.0);
+        assert!((LONDON.distance_km(&SEATTLE) - 7700.0).abs() < 50.0);
+        assert_eq!(LONDON.distance_km(&LONDON), 0.0);
+    }
+
+    #[test]
+    fn flags_impossible_travel_only() {
+        let now = Utc::now();
+        // London, then Seattle three hours later: no flight does that
+        let hop = travel(LONDON, now - Duration::hours(3), SEATTLE, now);
+        assert!(hop.impossible);
+        assert!(hop.speed_kmh > 2000.0);
+        // The same trip over a day is a flight
+        assert!(!travel(LONDON, now - Duration::hours(24), SEATTLE, now).impossible);
+        // Nearby fixes at the same moment are geolocation noise, not travel
+        let near = GeoPoint::new(51.75, -1.25); // Oxford
+        assert!(!travel(LONDON, now, near, now).impossible);
+        // Two distant places at once
+        let same_time = travel(LONDON, now, STOCKHOLM, now);
+        assert!(same_time.impossible);
+        assert_eq!(same_time.hours, 1.0 / 60.0);
+    }
+}
@@ -0,0 +1,62 @@
+// /home/inno/elights_jobes-research/backend/domain/src/ledger/accounts.rs
+use crate::error::DomainError;
+use crate::models::{LedgerAccount, LedgerAccountCategory, NewLedgerAccount, Wallet};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+fn get_or_create(conn: &mut PgConnection, new_account: NewLedgerAccount<'_>) -> Result<LedgerAccount, DomainError> {
+    use crate::schema::ledger_accounts::dsl as la;
+
+    // Accounts are created lazily; the unique account_code makes concurrent creation safe
+    diesel::insert_into(la::ledger_accounts)
+        .values(&new_account)
+        .on_conflict(la::account_code)
+        .do_nothing()
+        .execute(conn)?;
+    let account = la::ledger_accounts
+        .filter(la::account_code.eq(new_account.account_code))
+        .first(conn)?;
+    Ok(account)
+}
+
+/// Ledger account backing a customer wallet (created on first use).
+pub fn wallet_account(conn: &mut PgConnection, wallet_id: Uuid) -> Result<LedgerAccount, DomainError> {
+    use crate::schema::ledger_accounts::dsl as la;
+
+    if let Some(account) = la::ledger_accounts.filter(la::wallet_id.eq(wallet_id)).first(conn).optional()? {
+        return Ok(account);
+    }
+    let wallet: Wallet = crate::schema::wallets::table
+        .find(wallet_id)
+        .first(conn)
+        .map_err(|e| DomainError::NotFound(format!("Wallet {} not found for ledger account: {}", wallet_id, e)))?;
+
+    let category = LedgerAccountCategory::CustomerWallet;
+    let code = format!("WALLET:{}", wallet_id);
+    get_or_create(conn, NewLedgerAccount {
+        account_code: &code,
+        category: category.as_str(),
+        normal_balance: category.normal_balance().as_str(),
+        currency_code: &wallet.currency_code,
+        wallet_id: Some(wallet_id),
+    })
+}
+
+/// Institution-level account for `category` in `currency` (e.g. NOSTRO:USD).
+pub fn system_account(
+    conn: &mut PgConnection,
+    category: LedgerAccountCategory,
+    currency: &str,
+) -> Result<LedgerAccount, DomainError> {
+    if category == LedgerAccountCategory::CustomerWallet {
+        return Err(DomainError::Internal("Customer wallet accounts are keyed by wallet, use wallet_account".to_string()));
+    }
+    let code = format!("{}:{}", category.as_str(), currency);
+    get_or_create(conn, NewLedgerAccount {
+        account_code: &code,
+        category: category.as_str(),
+        normal_balance: category.normal_balance().as_str(),
+        currency_code: currency,
+        wallet_id: None,
+    })
+}
@@ -0,0 +1,324 @@
+// /home/inno/elights_jobes-research/backend/domain/src/ledger/holds.rs
+// Holds reserve part of a wallet's ledger balance without posting anything:
+//   available balance = ledger balance - active (unexpired) holds
+// A hold ends by being released (funds free again), converted (the posting took its place) or expiring.
+// Callers run inside a DB transaction; the wallet row is locked so concurrent holds cannot overspend.
+use crate::error::DomainError;
+use crate::models::{HoldStatus, HoldType, NewWalletHold, TransactionType, Wallet, WalletHold};
+use crate::utils::{bigdecimal_to_decimal, decimal_to_bigdecimal};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Card authorizations lapse if never captured.
+pub const CARD_AUTHORIZATION_HOLD_DAYS: i64 = 7;
+/// Deposited checks stay unavailable while they can still bounce.
+pub const CHECK_CLEARING_HOLD_DAYS: i64 = 2;
+
+/// Hold kind used when a transaction of `transaction_type` reserves funds.
+pub fn hold_type_for(transaction_type: &TransactionType) -> HoldType {
+    match transaction_type {
+        TransactionType::CardAuthorization | TransactionType::CardCapture => HoldType::CardAuthorization,
+        TransactionType::CheckDeposit => HoldType::CheckClearing,
+        _ => HoldType::Withdrawal,
+    }
+}
+
+/// Default expiry for a new hold; `None` means it lasts until released or converted.
+pub fn default_expiry(hold_type: HoldType, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    match hold_type {
+        HoldType::CardAuthorization => Some(now + Duration::days(CARD_AUTHORIZATION_HOLD_DAYS)),
+        HoldType::CheckClearing => Some(now + Duration::days(CHECK_CLEARING_HOLD_DAYS)),
+        HoldType::Withdrawal | HoldType::Manual => None,
+    }
+}
+
+/// Holds that reserve funds for spending must fit in the available balance; the others
+/// (uncleared deposits, operational freezes) restrict funds that are already there.
+fn requires_available_funds(hold_type: HoldType) -> bool {
+    matches!(hold_type, HoldType::CardAuthorization | HoldType::Withdrawal)
+}
+
+/// Input for `place_hold`.
+#[derive(Debug, Clone)]
+pub struct PlaceHold<'a> {
+    pub wallet_id: Uuid,
+    pub transaction_id: Option<Uuid>,
+    pub hold_type: HoldType,
+    pub amount: Decimal,
+    pub reason: Option<&'a str>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Ledger and available balance of one wallet.
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletBalances {
+    pub wallet_id: Uuid,
+    pub currency_code: String,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub ledger_balance: Decimal, // Posted funds (wallets.balance, cache of the ledger account)
+    #[serde(with = "rust_decimal::serde::str")]
+    pub held_amount: Decimal, // Sum of active holds
+    #[serde(with = "rust_decimal::serde::str")]
+    pub available_balance: Decimal, // What can be spent now
+}
+
+fn lock_wallet(conn: &mut PgConnection, wallet_id: Uuid) -> Result<Wallet, DomainError> {
+    crate::schema::wallets::table
+        .find(wallet_id)
+        .for_update()
+        .first(conn)
+        .map_err(|e| DomainError::NotFound(format!("Wallet {} not found or lock failed: {}", wallet_id, e)))
+}
+
+fn active_hold_total(conn: &mut PgConnection, wallet_id: Uuid, now: DateTime<Utc>) -> Result<Decimal, DomainError> {
+    use crate::schema::wallet_holds::dsl as wh;
+    use diesel::dsl::sum;
+
+    let total: Option<BigDecimal> = wh::wallet_holds
+        .filter(wh::wallet_id.eq(wallet_id))
+        .filter(wh::status.eq(HoldStatus::Active.as_str()))
+        .filter(wh::expires_at.is_null().or(wh::expires_at.gt(now)))
+        .select(sum(wh::amount))
+        .first(conn)?;
+    Ok(total.map(bigdecimal_to_decimal).unwrap_or(Decimal::ZERO))
+}
+
+fn balances_for(conn: &mut PgConnection, wallet: &Wallet) -> Result<WalletBalances, DomainError> {
+    let held_amount = active_hold_total(conn, wallet.wallet_id, Utc::now())?;
+    Ok(WalletBalances {
+        wallet_id: wallet.wallet_id,
+        currency_code: wallet.currency_code.clone(),
+        ledger_balance: wallet.balance,
+        held_amount,
+        available_balance: wallet.balance - held_amount,
+    })
+}
+
+/// Ledger and available balance of `wallet_id` (read-only, no lock).
+pub fn wallet_balances(conn: &mut PgConnection, wallet_id: Uuid) -> Result<WalletBalances, DomainError> {
+    let wallet: Wallet = crate::schema::wallets::table
+        .find(wallet_id)
+        .first(conn)
+        .map_err(|e| DomainError::NotFound(format!("Wallet {} not found: {}", wallet_id, e)))?;
+    balances_for(conn, &wallet)
+}
+
+/// Locks the wallet and fails with `InsufficientFunds` unless `amount` fits in its available balance.
+pub fn ensure_available(conn: &mut PgConnection, wallet_id: Uuid, amount: Decimal) -> Result<WalletBalances, DomainError> {
+    let wallet = lock_wallet(conn, wallet_id)?;
+    let balances = balances_for(conn, &wallet)?;
+    if balances.available_balance < amount {
+        log::warn!("Wallet {}: {} requested, {} available", wallet_id, amount, balances.available_balance);
+        return Err(DomainError::InsufficientFunds(wallet_id));
+    }
+    Ok(balances)
+}
+
+/// Active hold of `hold_type` owned by a transaction, if any.
+pub fn active_hold_for_transaction(
+    conn: &mut PgConnection,
+    transaction_id: Uuid,
+    hold_type: HoldType,
+) -> Result<Option<WalletHold>, DomainError> {
+    use crate::schema::wallet_holds::dsl as wh;
+    Ok(wh::wallet_holds
+        .filter(wh::transaction_id.eq(transaction_id))
+        .filter(wh::hold_type.eq(hold_type.as_str()))
+        .filter(wh::status.eq(HoldStatus::Active.as_str()))
+        .first(conn)
+        .optional()?)
+}
+
+/// Reserves `amount` on a wallet. Placing the same (transaction, type) hold again returns the existing one.
+pub fn place_hold(conn: &mut PgConnection, request: PlaceHold<'_>) -> Result<WalletHold, DomainError> {
+    if request.amount <= Decimal::ZERO {
+        return Err(DomainError::Validation("Hold amount must be positive".to_string()));
+    }
+    if let Some(transaction_id) = request.transaction_id {
+        if let Some(existing) = active_hold_for_transaction(conn, transaction_id, request.hold_type)? {
+            return Ok(existing);
+        }
+    }
+
+    let wallet = lock_wallet(conn, request.wallet_id)?;
+    if requires_available_funds(request.hold_type) {
+        let balances = balances_for(conn, &wallet)?;
+        if balances.available_balance < request.amount {
+            return Err(DomainError::InsufficientFunds(request.wallet_id));
+        }
+    }
+
+    let hold: WalletHold = diesel::insert_into(crate::schema::wallet_holds::table)
+        .values(&NewWalletHold {
+            wallet_id: request.wallet_id,
+            transaction_id: request.transaction_id,
+            hold_type: request.hold_type.as_str(),
+            amount: request.amount,
+            currency_code: &wallet.currency_code,
+            status: HoldStatus::Active.as_str(),
+            reason: request.reason,
+            expires_at: request.expires_at,
+        })
+        .get_result(conn)?;
+    log::info!("Placed {} hold {} of {} {} on wallet {}", hold.hold_type, hold.hold_id, hold.amount, hold.currency_code, hold.wallet_id);
+    Ok(hold)
+}
+
+fn resolve_hold(
+    conn: &mut PgConnection,
+    hold_id: Uuid,
+    status: HoldStatus,
+    reason: Option<&str>,
+) -> Result<Option<WalletHold>, DomainError> {
+    use crate::schema::wallet_holds::dsl as wh;
+
+    let active: Option<WalletHold> = wh::wallet_holds
+        .find(hold_id)
+        .filter(wh::status.eq(HoldStatus::Active.as_str()))
+        .for_update()
+        .first(conn)
+        .optional()?;
+    let Some(hold) = active else {
+        log::debug!("Hold {} is not active, nothing to {}", hold_id, status.as_str());
+        return Ok(None);
+    };
+
+    let resolved: WalletHold = diesel::update(wh::wallet_holds.find(hold_id))
+        .set((
+            wh::status.eq(status.as_str()),
+            wh::resolved_at.eq(Some(Utc::now())),
+            wh::reason.eq(reason.map(str::to_string).or(hold.reason)),
+        ))
+        .get_result(conn)?;
+    log::info!("Hold {} on wallet {} {}", resolved.hold_id, resolved.wallet_id, resolved.status);
+    Ok(Some(resolved))
+}
+
+/// Frees the held funds. Returns `None` if the hold was no longer active.
+pub fn release_hold(conn: &mut PgConnection, hold_id: Uuid, reason: Option<&str>) -> Result<Option<WalletHold>, DomainError> {
+    resolve_hold(conn, hold_id, HoldStatus::Released, reason)
+}
+
+/// Marks the hold as replaced by a ledger posting of `posted_amount` (may be less than held, e.g. partial capture).
+pub fn convert_hold(conn: &mut PgConnection, hold: &WalletHold, posted_amount: Decimal) -> Result<Option<WalletHold>, DomainError> {
+    if posted_amount > hold.amount {
+        return Err(DomainError::Validation(format!(
+            "Cannot post {} against hold {} of {}", posted_amount, hold.hold_id, hold.amount
+        )));
+    }
+    let reason = format!("Converted to posting of {}", posted_amount);
+    resolve_hold(conn, hold.hold_id, HoldStatus::Converted, Some(&reason))
+}
+
+/// Takes `amount` off an active hold that reserves funds for several payments (a payout batch). With a
+/// `transaction_id` the amount moves to a new hold of the same type owned by that transaction, so the funds
+/// stay reserved without a second availability check; without one it is freed for an immediate posting.
+/// A hold drawn down to zero is marked converted. Returns what is left of the hold (`None` once used up).
+pub fn draw_down_hold(
+    conn: &mut PgConnection,
+    hold_id: Uuid,
+    amount: Decimal,
+    transaction_id: Option<Uuid>,
+) -> Result<Option<WalletHold>, DomainError> {
+    use crate::schema::wallet_holds::dsl as wh;
+
+    let hold: WalletHold = wh::wallet_holds
+        .find(hold_id)
+        .filter(wh::status.eq(HoldStatus::Active.as_str()))
+        .for_update()
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::Validation(format!("Hold {} is not active", hold_id)))?;
+    if amount <= Decimal::ZERO || amount > hold.amount {
+        return Err(DomainError::Validation(format!("Cannot draw {} from hold {} of {}", amount, hold_id, hold.amount)));
+    }
+
+    if let Some(transaction_id) = transaction_id {
+        let reason = format!("Drawn from hold {}", hold_id);
+        let carved: WalletHold = diesel::insert_into(crate::schema::wallet_holds::table)
+            .values(&NewWalletHold {
+                wallet_id: hold.wallet_id,
+                transaction_id: Some(transaction_id),
+                hold_type: &hold.hold_type,
+                amount,
+                currency_code: &hold.currency_code,
+                status: HoldStatus::Active.as_str(),
+                reason: Some(&reason),
+                expires_at: hold.expires_at,
+            })
+            .get_result(conn)?;
+        log::info!("Moved {} {} from hold {} to hold {} of transaction {}", amount, hold.currency_code, hold_id, carved.hold_id, transaction_id);
+    }
+
+    let remaining = hold.amount - amount;
+    if remaining.is_zero() {
+        resolve_hold(conn, hold_id, HoldStatus::Converted, Some("Fully drawn down"))?;
+        return Ok(None);
+    }
+    let hold = diesel::update(wh::wallet_holds.find(hold_id))
+        .set(wh::amount.eq(decimal_to_bigdecimal(remaining)))
+        .get_result(conn)?;
+    Ok(Some(hold))
+}
+
+/// Marks lapsed holds as EXPIRED. They already stopped counting at `expires_at`; this keeps the table tidy.
+pub fn expire_holds(conn: &mut PgConnection, now: DateTime<Utc>) -> Result<usize, DomainError> {
+    use crate::schema::wallet_holds::dsl as wh;
+    let expired = diesel::update(
+        wh::wallet_holds
+            .filter(wh::status.eq(HoldStatus::Active.as_str()))
+            .filter(wh::expires_at.le(now)),
+    )
+    .set((wh::status.eq(HoldStatus::Expired.as_str()), wh::resolved_at.eq(Some(now))))
+    .execute(conn)?;
+    Ok(expired)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn hold(status: HoldStatus, expires_at: Option<DateTime<Utc>>) -> WalletHold {
+        WalletHold {
+            hold_id: Uuid::new_v4(),
+            wallet_id: Uuid::new_v4(),
+            transaction_id: None,
+            hold_type: HoldType::CardAuthorization.as_str().to_string(),
+            amount: dec!(10),
+            currency_code: "USD".to_string(),
+            status: status.as_str().to_string(),
+            reason: None,
+            expires_at,
+            resolved_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_hold_activity_and_expiry() {
+        let now = Utc::now();
+        assert!(hold(HoldStatus::Active, None).is_active_at(now));
+        assert!(hold(HoldStatus::Active, Some(now + Duration::hours(1))).is_active_at(now));
+        assert!(!hold(HoldStatus::Active, Some(now - Duration::seconds(1))).is_active_at(now));
+        assert!(!hold(HoldStatus::Released, None).is_active_at(now));
+        assert!(!hold(HoldStatus::Converted, None).is_active_at(now));
+    }
+
+    #[test]
+    fn test_hold_type_defaults() {
+        let now = Utc::now();
+        assert_eq!(hold_type_for(&TransactionType::CardAuthorization), HoldType::CardAuthorization);
+        assert_eq!(hold_type_for(&TransactionType::CryptoXmrSend), HoldType::Withdrawal);
+        assert_eq!(hold_type_for(&TransactionType::CheckDeposit), HoldType::CheckClearing);
+        assert_eq!(default_expiry(HoldType::Withdrawal, now), None);
+        assert_eq!(default_expiry(HoldType::CardAuthorization, now), Some(now + Duration::days(CARD_AUTHORIZATION_HOLD_DAYS)));
+        assert!(requires_available_funds(HoldType::Withdrawal));
+        assert!(!requires_available_funds(HoldType::CheckClearing));
+    }
+}
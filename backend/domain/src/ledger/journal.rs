@@ -0,0 +1,401 @@
+// /home/inno/elights_jobes-research/backend/domain/src/ledger/journal.rs
+// Journal posting. An entry is a set of lines whose debits equal its credits per currency; the
+// database re-checks this with a deferred constraint trigger and rejects updates/deletes of posted rows.
+use crate::error::DomainError;
+use crate::models::{EntryDirection, JournalEntry, LedgerAccount, LedgerAccountCategory, NewJournalEntry, NewJournalLine};
+use crate::utils::{bigdecimal_to_decimal, decimal_to_bigdecimal};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{Nullable, Numeric, Timestamptz, Uuid as DieselUuid, Varchar};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// One side of a journal entry.
+#[derive(Debug, Clone)]
+pub struct PostingLine {
+    pub account: LedgerAccount,
+    pub direction: EntryDirection,
+    pub amount: Decimal, // Positive; currency is the account's currency
+}
+
+impl PostingLine {
+    pub fn debit(account: LedgerAccount, amount: Decimal) -> Self {
+        PostingLine { account, direction: EntryDirection::Debit, amount }
+    }
+
+    pub fn credit(account: LedgerAccount, amount: Decimal) -> Self {
+        PostingLine { account, direction: EntryDirection::Credit, amount }
+    }
+
+    /// Same amount on the same account, opposite side.
+    pub fn mirrored(&self) -> Self {
+        PostingLine { account: self.account.clone(), direction: self.direction.opposite(), amount: self.amount }
+    }
+}
+
+/// Everything needed to post one journal entry.
+#[derive(Debug, Clone)]
+pub struct JournalEntrySpec<'a> {
+    pub transaction_id: Option<Uuid>,
+    pub entry_type: &'a str, // HOLD, POST, RELEASE, REVERSE, ...
+    pub reference: &'a str, // Unique; re-posting the same reference is a no-op
+    pub description: Option<&'a str>,
+    pub lines: Vec<PostingLine>,
+    pub allow_negative_wallets: bool, // Reversals may overdraw a wallet that already spent the funds
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PostOutcome {
+    Posted(JournalEntry),
+    AlreadyPosted(JournalEntry), // Same reference was posted earlier, nothing changed
+}
+
+impl PostOutcome {
+    pub fn entry(&self) -> &JournalEntry {
+        match self {
+            PostOutcome::Posted(entry) | PostOutcome::AlreadyPosted(entry) => entry,
+        }
+    }
+}
+
+/// Checks an entry before it is written: at least two lines, positive amounts, debits == credits per currency.
+pub fn validate_balanced(lines: &[PostingLine]) -> Result<(), DomainError> {
+    if lines.len() < 2 {
+        return Err(DomainError::Validation("A journal entry needs at least two lines".to_string()));
+    }
+    let mut net: BTreeMap<&str, Decimal> = BTreeMap::new();
+    for line in lines {
+        if line.amount <= Decimal::ZERO {
+            return Err(DomainError::Validation(format!(
+                "Journal line amount must be positive (account {})", line.account.account_code
+            )));
+        }
+        let signed = match line.direction {
+            EntryDirection::Debit => line.amount,
+            EntryDirection::Credit => -line.amount,
+        };
+        *net.entry(line.account.currency_code.as_str()).or_insert(Decimal::ZERO) += signed;
+    }
+    if let Some((currency, diff)) = net.iter().find(|(_, diff)| !diff.is_zero()) {
+        return Err(DomainError::Validation(format!(
+            "Unbalanced journal entry: {} debits exceed credits by {}", currency, diff
+        )));
+    }
+    Ok(())
+}
+
+/// Net change of each customer wallet in the entry (credits increase the wallet, debits decrease it).
+fn wallet_deltas(lines: &[PostingLine]) -> BTreeMap<Uuid, Decimal> {
+    let mut deltas = BTreeMap::new();
+    for line in lines {
+        if line.account.category != LedgerAccountCategory::CustomerWallet.as_str() {
+            continue;
+        }
+        if let Some(wallet_id) = line.account.wallet_id {
+            let signed = match line.direction {
+                EntryDirection::Credit => line.amount,
+                EntryDirection::Debit => -line.amount,
+            };
+            *deltas.entry(wallet_id).or_insert(Decimal::ZERO) += signed;
+        }
+    }
+    deltas
+}
+
+/// Keeps `wallets.balance` (a cache of the wallet account balance) in step with the posted lines.
+fn apply_wallet_deltas(conn: &mut PgConnection, deltas: &BTreeMap<Uuid, Decimal>, allow_negative: bool) -> Result<(), DomainError> {
+    use crate::schema::wallets::dsl as w;
+
+    for (wallet_id, delta) in deltas {
+        if delta.is_zero() {
+            continue;
+        }
+        let change = decimal_to_bigdecimal(delta.abs());
+        let updated = if *delta > Decimal::ZERO {
+            diesel::update(w::wallets.find(*wallet_id))
+                .set(w::balance.eq(w::balance + change))
+                .execute(conn)?
+        } else if allow_negative {
+            diesel::update(w::wallets.find(*wallet_id))
+                .set(w::balance.eq(w::balance - change))
+                .execute(conn)?
+        } else {
+            // Guarded debit: refuses to take the balance negative
+            diesel::update(w::wallets.find(*wallet_id).filter(w::balance.ge(change.clone())))
+                .set(w::balance.eq(w::balance - change))
+                .execute(conn)?
+        };
+        if updated == 0 {
+            return Err(DomainError::InsufficientFunds(*wallet_id));
+        }
+    }
+    Ok(())
+}
+
+fn find_entry(conn: &mut PgConnection, reference: &str) -> Result<Option<JournalEntry>, DomainError> {
+    use crate::schema::journal_entries::dsl as je;
+    Ok(je::journal_entries.filter(je::reference.eq(reference)).first(conn).optional()?)
+}
+
+pub fn entry_exists(conn: &mut PgConnection, reference: &str) -> Result<bool, DomainError> {
+    Ok(find_entry(conn, reference)?.is_some())
+}
+
+/// Posts a balanced entry and updates the wallet balance cache. Must run inside a DB transaction:
+/// the balance check is deferred to commit and the cache update must not outlive a rolled-back entry.
+pub fn post_entry(conn: &mut PgConnection, spec: JournalEntrySpec<'_>) -> Result<PostOutcome, DomainError> {
+    use crate::schema::journal_entries::dsl as je;
+
+    validate_balanced(&spec.lines)?;
+
+    let inserted: Option<JournalEntry> = diesel::insert_into(je::journal_entries)
+        .values(&NewJournalEntry {
+            transaction_id: spec.transaction_id,
+            entry_type: spec.entry_type,
+            reference: spec.reference,
+            description: spec.description,
+        })
+        .on_conflict(je::reference)
+        .do_nothing()
+        .get_result(conn)
+        .optional()?;
+
+    let entry = match inserted {
+        Some(entry) => entry,
+        None => {
+            let existing = find_entry(conn, spec.reference)?
+                .ok_or_else(|| DomainError::Internal(format!("Journal entry {} vanished after conflict", spec.reference)))?;
+            log::debug!("Journal entry {} already posted", spec.reference);
+            return Ok(PostOutcome::AlreadyPosted(existing));
+        }
+    };
+
+    let new_lines: Vec<NewJournalLine> = spec.lines.iter().map(|line| NewJournalLine {
+        entry_id: entry.entry_id,
+        account_id: line.account.account_id,
+        direction: line.direction.as_str(),
+        amount: line.amount,
+        currency_code: &line.account.currency_code,
+    }).collect();
+    diesel::insert_into(crate::schema::journal_lines::table)
+        .values(&new_lines)
+        .execute(conn)?;
+
+    apply_wallet_deltas(conn, &wallet_deltas(&spec.lines), spec.allow_negative_wallets)?;
+
+    log::info!("Posted journal entry {} ({}, {} lines)", entry.reference, entry.entry_type, new_lines.len());
+    Ok(PostOutcome::Posted(entry))
+}
+
+/// Lines of a posted entry with the sides swapped, for building its reversal.
+pub fn mirror_entry_lines(conn: &mut PgConnection, reference: &str) -> Result<Vec<PostingLine>, DomainError> {
+    use crate::schema::journal_lines::dsl as jl;
+    use crate::schema::ledger_accounts::dsl as la;
+
+    let Some(entry) = find_entry(conn, reference)? else {
+        return Ok(Vec::new());
+    };
+    let rows: Vec<(String, BigDecimal, LedgerAccount)> = jl::journal_lines
+        .inner_join(la::ledger_accounts)
+        .filter(jl::entry_id.eq(entry.entry_id))
+        .order(jl::line_id.asc())
+        .select((jl::direction, jl::amount, LedgerAccount::as_select()))
+        .load(conn)?;
+
+    rows.into_iter().map(|(direction, amount, account)| {
+        let direction = EntryDirection::parse(&direction)
+            .ok_or_else(|| DomainError::Internal(format!("Unknown journal line direction '{}'", direction)))?;
+        Ok(PostingLine { account, direction, amount: bigdecimal_to_decimal(amount) }.mirrored())
+    }).collect()
+}
+
+/// Balance of an account on its normal side (e.g. a wallet's balance is credits minus debits).
+pub fn account_balance(conn: &mut PgConnection, account: &LedgerAccount) -> Result<Decimal, DomainError> {
+    use crate::schema::journal_lines::dsl as jl;
+    use diesel::dsl::sum;
+
+    let mut totals = (Decimal::ZERO, Decimal::ZERO);
+    for direction in [EntryDirection::Debit, EntryDirection::Credit] {
+        let total: Option<BigDecimal> = jl::journal_lines
+            .filter(jl::account_id.eq(account.account_id))
+            .filter(jl::direction.eq(direction.as_str()))
+            .select(sum(jl::amount))
+            .first(conn)?;
+        let total = total.map(bigdecimal_to_decimal).unwrap_or(Decimal::ZERO);
+        match direction {
+            EntryDirection::Debit => totals.0 = total,
+            EntryDirection::Credit => totals.1 = total,
+        }
+    }
+    let (debits, credits) = totals;
+    Ok(match EntryDirection::parse(&account.normal_balance) {
+        Some(EntryDirection::Debit) => debits - credits,
+        _ => credits - debits,
+    })
+}
+
+/// Per-currency debit and credit totals.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrialBalanceRow {
+    pub currency_code: String,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub total_debits: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub total_credits: Decimal,
+    pub balanced: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrialBalance {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub rows: Vec<TrialBalanceRow>,
+    pub balanced: bool, // True when every currency balances
+}
+
+#[derive(QueryableByName)]
+struct TrialBalanceSqlRow {
+    #[diesel(sql_type = Varchar)]
+    currency_code: String,
+    #[diesel(sql_type = Numeric)]
+    total_debits: BigDecimal,
+    #[diesel(sql_type = Numeric)]
+    total_credits: BigDecimal,
+}
+
+/// Sums all journal lines posted in `[from, to)`, optionally for one currency.
+pub fn trial_balance(
+    conn: &mut PgConnection,
+    currency: Option<&str>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Result<TrialBalance, DomainError> {
+    let raw: Vec<TrialBalanceSqlRow> = diesel::sql_query(
+        "SELECT currency_code, \
+                COALESCE(SUM(amount) FILTER (WHERE direction = 'DEBIT'), 0) AS total_debits, \
+                COALESCE(SUM(amount) FILTER (WHERE direction = 'CREDIT'), 0) AS total_credits \
+         FROM core_schema.journal_lines \
+         WHERE ($1::varchar IS NULL OR currency_code = $1) \
+           AND ($2::timestamptz IS NULL OR posted_at >= $2) \
+           AND ($3::timestamptz IS NULL OR posted_at < $3) \
+         GROUP BY currency_code \
+         ORDER BY currency_code",
+    )
+    .bind::<Nullable<Varchar>, _>(currency)
+    .bind::<Nullable<Timestamptz>, _>(from)
+    .bind::<Nullable<Timestamptz>, _>(to)
+    .load(conn)?;
+
+    let rows: Vec<TrialBalanceRow> = raw.into_iter().map(|row| {
+        let total_debits = bigdecimal_to_decimal(row.total_debits);
+        let total_credits = bigdecimal_to_decimal(row.total_credits);
+        TrialBalanceRow { currency_code: row.currency_code, total_debits, total_credits, balanced: total_debits == total_credits }
+    }).collect();
+
+    let balanced = rows.iter().all(|row| row.balanced);
+    Ok(TrialBalance { from, to, rows, balanced })
+}
+
+/// A wallet whose cached balance differs from its ledger account.
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletBalanceDrift {
+    pub wallet_id: Uuid,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub cached_balance: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub ledger_balance: Decimal,
+}
+
+#[derive(QueryableByName)]
+struct WalletDriftSqlRow {
+    #[diesel(sql_type = DieselUuid)]
+    wallet_id: Uuid,
+    #[diesel(sql_type = Numeric)]
+    cached_balance: BigDecimal,
+    #[diesel(sql_type = Numeric)]
+    ledger_balance: BigDecimal,
+}
+
+/// Lists wallets whose `balance` column no longer matches the ledger (should always be empty).
+pub fn wallet_balance_drift(conn: &mut PgConnection) -> Result<Vec<WalletBalanceDrift>, DomainError> {
+    let raw: Vec<WalletDriftSqlRow> = diesel::sql_query(
+        "SELECT w.wallet_id, w.balance AS cached_balance, \
+                COALESCE(SUM(CASE WHEN l.direction = 'CREDIT' THEN l.amount ELSE -l.amount END), 0) AS ledger_balance \
+         FROM core_schema.wallets w \
+         LEFT JOIN core_schema.ledger_accounts a ON a.wallet_id = w.wallet_id \
+         LEFT JOIN core_schema.journal_lines l ON l.account_id = a.account_id \
+         GROUP BY w.wallet_id, w.balance \
+         HAVING w.balance <> COALESCE(SUM(CASE WHEN l.direction = 'CREDIT' THEN l.amount ELSE -l.amount END), 0)",
+    )
+    .load(conn)?;
+
+    Ok(raw.into_iter().map(|row| WalletBalanceDrift {
+        wallet_id: row.wallet_id,
+        cached_balance: bigdecimal_to_decimal(row.cached_balance),
+        ledger_balance: bigdecimal_to_decimal(row.ledger_balance),
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn account(code: &str, category: LedgerAccountCategory, currency: &str) -> LedgerAccount {
+        LedgerAccount {
+            account_id: Uuid::new_v4(),
+            account_code: code.to_string(),
+            category: category.as_str().to_string(),
+            normal_balance: category.normal_balance().as_str().to_string(),
+            currency_code: currency.to_string(),
+            wallet_id: (category == LedgerAccountCategory::CustomerWallet).then(Uuid::new_v4),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_validate_balanced_per_currency() {
+        let wallet = account("WALLET:1", LedgerAccountCategory::CustomerWallet, "USD");
+        let suspense = account("SUSPENSE:USD", LedgerAccountCategory::Suspense, "USD");
+        let nostro_eur = account("NOSTRO:EUR", LedgerAccountCategory::Nostro, "EUR");
+
+        assert!(validate_balanced(&[
+            PostingLine::debit(wallet.clone(), dec!(10.50)),
+            PostingLine::credit(suspense.clone(), dec!(10.50)),
+        ]).is_ok());
+
+        // Same total, different currencies: not balanced
+        assert!(validate_balanced(&[
+            PostingLine::debit(wallet.clone(), dec!(10)),
+            PostingLine::credit(nostro_eur, dec!(10)),
+        ]).is_err());
+
+        assert!(validate_balanced(&[
+            PostingLine::debit(wallet.clone(), dec!(10)),
+            PostingLine::credit(suspense.clone(), dec!(9.99)),
+        ]).is_err());
+        assert!(validate_balanced(&[PostingLine::debit(wallet.clone(), dec!(10))]).is_err());
+        assert!(validate_balanced(&[
+            PostingLine::debit(wallet, dec!(0)),
+            PostingLine::credit(suspense, dec!(0)),
+        ]).is_err());
+    }
+
+    #[test]
+    fn test_wallet_deltas_and_mirror() {
+        let wallet = account("WALLET:1", LedgerAccountCategory::CustomerWallet, "USD");
+        let suspense = account("SUSPENSE:USD", LedgerAccountCategory::Suspense, "USD");
+        let hold = vec![PostingLine::debit(wallet.clone(), dec!(25)), PostingLine::credit(suspense, dec!(25))];
+
+        let deltas = wallet_deltas(&hold);
+        assert_eq!(deltas.get(&wallet.wallet_id.unwrap()), Some(&dec!(-25)));
+        assert_eq!(deltas.len(), 1); // Suspense is not a wallet
+
+        let release: Vec<PostingLine> = hold.iter().map(PostingLine::mirrored).collect();
+        assert!(validate_balanced(&release).is_ok());
+        assert_eq!(wallet_deltas(&release).get(&wallet.wallet_id.unwrap()), Some(&dec!(25)));
+    }
+}
@@ -0,0 +1,19 @@
+// /home/inno/elights_jobes-research/backend/domain/src/ledger/mod.rs
+// Double-entry ledger. All balance changes go through `journal::post_entry`, which writes balanced
+// debit/credit lines and keeps the `wallets.balance` cache in step with the customer wallet accounts.
+// Funds that are reserved but not yet moved are tracked as holds, which only affect the available balance.
+
+pub mod accounts; // Ledger account lookup/creation (customer wallets, suspense, nostro, fees, FX)
+pub mod holds; // Reservations: available balance = ledger balance - active holds
+pub mod journal; // Posting, reversal and trial balance
+pub mod postings; // Journal entries produced by transaction status transitions
+
+pub use accounts::{wallet_account, system_account};
+pub use journal::{
+    post_entry, mirror_entry_lines, entry_exists, account_balance, trial_balance, wallet_balance_drift,
+    validate_balanced, JournalEntrySpec, PostingLine, PostOutcome, TrialBalance, TrialBalanceRow, WalletBalanceDrift,
+};
+pub use holds::{
+    place_hold, release_hold, convert_hold, draw_down_hold, expire_holds, wallet_balances, ensure_available, PlaceHold, WalletBalances,
+};
+pub use postings::post_transaction_effect;
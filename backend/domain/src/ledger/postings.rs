@@ -0,0 +1,144 @@
+// /home/inno/elights_jobes-research/backend/domain/src/ledger/postings.rs
+// Ledger side of the financial effects of transaction status transitions.
+//
+//   HOLD    - hold on the debit wallet (no journal entry: the ledger balance is unchanged,
+//             the available balance drops)
+//   POST    - converts the hold if there is one, then Dr debit wallet or NOSTRO / Cr credit wallet or NOSTRO
+//             (FEE_INCOME for fee transactions, FX_POSITION for conversion legs, INTEREST_EXPENSE for
+//             credited interest, WITHHOLDING_TAX for tax withheld from it);
+//             a deposited check is credited with a CHECK_CLEARING hold until it can no longer bounce
+//   RELEASE - releases the hold
+//   REVERSE - the POST lines mirrored (and any clearing hold released)
+//
+// Entry references are "<transaction_id>:<EFFECT>", so each effect posts at most once per transaction.
+use super::accounts::{system_account, wallet_account};
+use super::holds::{self, PlaceHold};
+use super::journal::{mirror_entry_lines, post_entry, JournalEntrySpec, PostingLine};
+use crate::error::DomainError;
+use crate::models::{HoldType, LedgerAccount, LedgerAccountCategory, Transaction, TransactionType};
+use crate::payments::state_machine::FinancialEffect;
+use chrono::Utc;
+use diesel::prelude::*;
+
+fn reference(transaction: &Transaction, effect: FinancialEffect) -> String {
+    format!("{}:{}", transaction.transaction_id, effect.as_str())
+}
+
+fn post(
+    conn: &mut PgConnection,
+    transaction: &Transaction,
+    effect: FinancialEffect,
+    lines: Vec<PostingLine>,
+    allow_negative_wallets: bool,
+) -> Result<(), DomainError> {
+    let reference = reference(transaction, effect);
+    let description = format!("{} {}", transaction.transaction_type, effect.as_str());
+    post_entry(conn, JournalEntrySpec {
+        transaction_id: Some(transaction.transaction_id),
+        entry_type: effect.as_str(),
+        reference: &reference,
+        description: Some(&description),
+        lines,
+        allow_negative_wallets,
+    })?;
+    Ok(())
+}
+
+fn nostro(conn: &mut PgConnection, transaction: &Transaction) -> Result<LedgerAccount, DomainError> {
+    system_account(conn, LedgerAccountCategory::Nostro, &transaction.currency_code)
+}
+
+fn fx_position(conn: &mut PgConnection, transaction: &Transaction) -> Result<LedgerAccount, DomainError> {
+    system_account(conn, LedgerAccountCategory::FxPosition, &transaction.currency_code)
+}
+
+/// Applies `effect` of `transaction` to holds and the journal. External legs (no wallet id) go to the NOSTRO account.
+pub fn post_transaction_effect(
+    conn: &mut PgConnection,
+    transaction: &Transaction,
+    effect: FinancialEffect,
+) -> Result<(), DomainError> {
+    let amount = transaction.amount;
+    let tx_type = TransactionType::parse(&transaction.transaction_type).unwrap_or(TransactionType::Unknown);
+    let hold_type = holds::hold_type_for(&tx_type);
+
+    match effect {
+        FinancialEffect::None => Ok(()),
+        FinancialEffect::Hold => {
+            let Some(wallet_id) = transaction.debit_wallet_id else {
+                return Ok(()); // Nothing internal to reserve
+            };
+            let reason = format!("{} {}", transaction.transaction_type, transaction.transaction_id);
+            holds::place_hold(conn, PlaceHold {
+                wallet_id,
+                transaction_id: Some(transaction.transaction_id),
+                hold_type,
+                amount,
+                reason: Some(&reason),
+                expires_at: holds::default_expiry(hold_type, Utc::now()),
+            })?;
+            Ok(())
+        }
+        FinancialEffect::Post => {
+            if transaction.debit_wallet_id.is_none() && transaction.credit_wallet_id.is_none() {
+                return Ok(()); // External to external, nothing on our books
+            }
+            let debit_account = match transaction.debit_wallet_id {
+                Some(wallet_id) => {
+                    // Held funds were reserved already; otherwise the debit must fit in the available balance
+                    match holds::active_hold_for_transaction(conn, transaction.transaction_id, hold_type)? {
+                        Some(hold) => { holds::convert_hold(conn, &hold, amount)?; }
+                        None => { holds::ensure_available(conn, wallet_id, amount)?; }
+                    }
+                    wallet_account(conn, wallet_id)?
+                }
+                None if tx_type == TransactionType::Conversion => fx_position(conn, transaction)?,
+                None if tx_type == TransactionType::InterestCredit => {
+                    system_account(conn, LedgerAccountCategory::InterestExpense, &transaction.currency_code)?
+                }
+                None => nostro(conn, transaction)?,
+            };
+            let credit_account = match (transaction.credit_wallet_id, &tx_type) {
+                (Some(wallet_id), _) => wallet_account(conn, wallet_id)?,
+                (None, TransactionType::Fee) => system_account(conn, LedgerAccountCategory::FeeIncome, &transaction.currency_code)?,
+                (None, TransactionType::Conversion) => fx_position(conn, transaction)?,
+                (None, TransactionType::WithholdingTax) => {
+                    system_account(conn, LedgerAccountCategory::WithholdingTax, &transaction.currency_code)?
+                }
+                (None, _) => nostro(conn, transaction)?,
+            };
+            let lines = vec![PostingLine::debit(debit_account, amount), PostingLine::credit(credit_account, amount)];
+            post(conn, transaction, effect, lines, false)?;
+
+            if let (TransactionType::CheckDeposit, Some(wallet_id)) = (&tx_type, transaction.credit_wallet_id) {
+                // Credited but returnable: keep it out of the available balance until it clears
+                holds::place_hold(conn, PlaceHold {
+                    wallet_id,
+                    transaction_id: Some(transaction.transaction_id),
+                    hold_type: HoldType::CheckClearing,
+                    amount,
+                    reason: Some("Deposited check clearing"),
+                    expires_at: holds::default_expiry(HoldType::CheckClearing, Utc::now()),
+                })?;
+            }
+            Ok(())
+        }
+        FinancialEffect::Release => {
+            if let Some(hold) = holds::active_hold_for_transaction(conn, transaction.transaction_id, hold_type)? {
+                holds::release_hold(conn, hold.hold_id, Some("Payment did not complete"))?;
+            }
+            Ok(())
+        }
+        FinancialEffect::Reverse => {
+            if let Some(hold) = holds::active_hold_for_transaction(conn, transaction.transaction_id, HoldType::CheckClearing)? {
+                holds::release_hold(conn, hold.hold_id, Some("Check returned"))?;
+            }
+            let lines = mirror_entry_lines(conn, &reference(transaction, FinancialEffect::Post))?;
+            if lines.is_empty() {
+                return Ok(());
+            }
+            // The recipient may already have spent the funds: a reversal is allowed to overdraw
+            post(conn, transaction, effect, lines, true)
+        }
+    }
+}
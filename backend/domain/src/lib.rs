@@ -7,12 +7,28 @@
 // extern crate diesel_migrations;
 
 pub mod models;
+pub mod ledger; // Double-entry journal, source of truth for balances
+pub mod fees; // Fee schedules, fee charging and fee revenue
 pub mod payments;
+pub mod approvals; // Maker-checker approval policies and decisions on outbound payments
+pub mod beneficiaries; // Saved payees with verification and cooling-off limits
+pub mod webhooks; // Provider webhooks: signature verification, replay protection, normalized status events
+pub mod reconciliation; // Nostro statement reconciliation, breaks and break reports
+pub mod treasury; // End-of-day nostro positions, liquidity projections and signed position reports
+pub mod interest; // Daily interest accrual, tiered rate versions and monthly capitalization
+pub mod limits; // Per-transaction and rolling daily/weekly/monthly limits with admin-approved temporary increases
+pub mod sanctions; // OFAC/EU/UN watchlist screening of outbound wires and crypto withdrawals, compliance review cases
+pub mod aml; // Behavioural AML monitoring over transactions, investigation cases and SAR summaries
+pub mod ctr; // Currency Transaction Reports: daily cash aggregation, FinCEN batch XML filings and exemptions
+pub mod travel_rule; // FATF Travel Rule data exchange for crypto withdrawals
+pub mod fraud; // Declarative, hot-reloaded fraud rules over outbound payments, analyst review of flagged payments
+pub mod ip_intel; // Offline GeoIP/ASN lookups, Tor/VPN/hosting lists and impossible travel between logins
 pub mod crypto;
 pub mod security;
 pub mod services;
 pub mod error;
 pub mod utils; // General utilities for domain logic
+pub mod config; // Loading and validation of the JSON configuration files
 
 // Re-export key types and error for easier access from other crates
 pub use error::DomainError;
@@ -0,0 +1,304 @@
+// /home/inno/elights_jobes-research/backend/domain/src/limits/counters.rs
+// Usage counters. Each windowed rule keeps one row per subject and currency; a payment locks the rows it
+// counts against, sums what was consumed within the rolling window and records its own usage in its DB
+// transaction, so two concurrent payments cannot both squeeze under a limit. A usage stops counting once
+// it is older than the window, or when it is released because the payment failed, was cancelled or expired.
+use super::overrides;
+use super::policy::{self, LimitFacts, LimitPeriod, LimitPolicy, LimitRule, LimitTerms};
+use crate::error::DomainError;
+use crate::fees::FeeRail;
+use crate::models::{
+    LimitCounter, LimitSubjectType, LimitUsage, NewLimitCounter, NewLimitUsage, TransactionType, User, Wallet,
+};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use uuid::Uuid;
+
+/// A payment as seen by the limits engine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LimitContext {
+    pub user_id: Uuid,
+    pub wallet_id: Uuid,
+    pub wallet_type: String,
+    pub kyc_tier: String,
+    pub rail: Option<FeeRail>,
+    pub currency: String,
+    pub amount: Decimal,
+}
+
+impl LimitContext {
+    pub fn facts(&self) -> LimitFacts<'_> {
+        LimitFacts {
+            rail: self.rail,
+            currency: &self.currency,
+            kyc_tier: &self.kyc_tier,
+            wallet_type: &self.wallet_type,
+            amount: self.amount,
+        }
+    }
+
+    pub fn subject_id(&self, subject: LimitSubjectType) -> Uuid {
+        match subject {
+            LimitSubjectType::User => self.user_id,
+            LimitSubjectType::Wallet => self.wallet_id,
+        }
+    }
+}
+
+/// Limit context of an outbound payment of `amount` from `wallet` by `user_id`.
+pub fn payment_context(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    wallet: &Wallet,
+    payment_type: &TransactionType,
+    amount: Decimal,
+) -> Result<LimitContext, DomainError> {
+    let user: User = crate::schema::users::table
+        .find(user_id)
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("User {} not found", user_id)))?;
+    Ok(LimitContext {
+        user_id,
+        wallet_id: wallet.wallet_id,
+        wallet_type: wallet.wallet_type.clone(),
+        kyc_tier: user.kyc_tier,
+        rail: FeeRail::for_transaction_type(payment_type),
+        currency: wallet.currency_code.clone(),
+        amount,
+    })
+}
+
+/// The terms in force for `rule` and the subject: an approved increase replaces the rule's values.
+fn terms_for(conn: &mut PgConnection, rule: &LimitRule, subject_id: Uuid, now: DateTime<Utc>) -> Result<LimitTerms, DomainError> {
+    let terms = rule.terms();
+    Ok(match overrides::active_override(conn, &rule.id, rule.subject, subject_id, now)? {
+        Some(increase) => LimitTerms {
+            max_amount: increase.max_amount.or(terms.max_amount),
+            max_count: increase.max_count.map(|count| count.max(0) as u32).or(terms.max_count),
+        },
+        None => terms,
+    })
+}
+
+/// Locks (creating if needed) the counter for `rule`, the subject and the currency.
+fn lock_counter(conn: &mut PgConnection, rule: &LimitRule, subject_id: Uuid, currency: &str) -> Result<LimitCounter, DomainError> {
+    use crate::schema::limit_counters::dsl as lc;
+    diesel::insert_into(lc::limit_counters)
+        .values(&NewLimitCounter { rule_id: &rule.id, subject_type: rule.subject.as_str(), subject_id, currency_code: currency })
+        .on_conflict((lc::rule_id, lc::subject_type, lc::subject_id, lc::currency_code))
+        .do_nothing()
+        .execute(conn)?;
+    let counter = lc::limit_counters
+        .filter(lc::rule_id.eq(&rule.id))
+        .filter(lc::subject_type.eq(rule.subject.as_str()))
+        .filter(lc::subject_id.eq(subject_id))
+        .filter(lc::currency_code.eq(currency))
+        .for_update()
+        .first(conn)?;
+    Ok(counter)
+}
+
+/// What a counter's payments add up to within a rolling window.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct WindowUsage {
+    amount: Decimal,
+    count: u32,
+    oldest: Option<DateTime<Utc>>, // When the oldest counted payment was made
+}
+
+impl WindowUsage {
+    /// Unreleased usages made after `start`.
+    fn of(usages: &[LimitUsage], start: DateTime<Utc>) -> Self {
+        usages.iter()
+            .filter(|usage| usage.released_at.is_none() && usage.created_at > start)
+            .fold(WindowUsage::default(), |total, usage| WindowUsage {
+                amount: total.amount + usage.amount,
+                count: total.count + 1,
+                oldest: Some(total.oldest.map_or(usage.created_at, |oldest| oldest.min(usage.created_at))),
+            })
+    }
+
+    /// When the oldest counted payment leaves the window and frees up its share of the limit.
+    fn frees_up_at(&self, period: LimitPeriod) -> Option<DateTime<Utc>> {
+        self.oldest.zip(period.length()).map(|(oldest, length)| oldest + length)
+    }
+}
+
+/// Usage of `counter` in the rolling window of `period` ending at `now`.
+fn window_usage(conn: &mut PgConnection, counter_id: Uuid, period: LimitPeriod, now: DateTime<Utc>) -> Result<WindowUsage, DomainError> {
+    use crate::schema::limit_usages::dsl as lu;
+    let Some((start, _)) = period.window(now) else {
+        return Ok(WindowUsage::default());
+    };
+    let usages: Vec<LimitUsage> = lu::limit_usages
+        .filter(lu::counter_id.eq(counter_id))
+        .filter(lu::released_at.is_null())
+        .filter(lu::created_at.gt(start))
+        .load(conn)?;
+    Ok(WindowUsage::of(&usages, start))
+}
+
+/// Checks the payment against every matching rule and records it against the windowed counters.
+/// Must run in the DB transaction that creates `transaction_id`; on a breach nothing is consumed
+/// once that transaction rolls back, and the error says which limit and when usage starts freeing up.
+pub fn check_and_consume(
+    conn: &mut PgConnection,
+    limit_policy: &LimitPolicy,
+    context: &LimitContext,
+    transaction_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<Vec<LimitUsage>, DomainError> {
+    let mut usages = Vec::new();
+    for rule in limit_policy.matching(&context.facts()) {
+        let subject_id = context.subject_id(rule.subject);
+        let terms = terms_for(conn, rule, subject_id, now)?;
+        if rule.period.length().is_none() {
+            policy::check_limit(rule, terms, &context.currency, Decimal::ZERO, 0, context.amount, None)?;
+            continue;
+        }
+        let counter = lock_counter(conn, rule, subject_id, &context.currency)?;
+        let used = window_usage(conn, counter.counter_id, rule.period, now)?;
+        if let Err(e) = policy::check_limit(
+            rule, terms, &context.currency, used.amount, used.count, context.amount, used.frees_up_at(rule.period),
+        ) {
+            log::info!("Payment {} by user {} refused: {}", transaction_id, context.user_id, e);
+            return Err(e);
+        }
+        let usage: LimitUsage = diesel::insert_into(crate::schema::limit_usages::table)
+            .values(&NewLimitUsage { counter_id: counter.counter_id, transaction_id, amount: context.amount, created_at: now })
+            .get_result(conn)?;
+        usages.push(usage);
+    }
+    Ok(usages)
+}
+
+/// Gives back what `transaction_id` consumed (payment failed, cancelled or expired). Idempotent.
+pub fn release_usage(conn: &mut PgConnection, transaction_id: Uuid, now: DateTime<Utc>) -> Result<usize, DomainError> {
+    use crate::schema::limit_counters::dsl as lc;
+    use crate::schema::limit_usages::dsl as lu;
+    let usages: Vec<LimitUsage> = lu::limit_usages
+        .filter(lu::transaction_id.eq(transaction_id))
+        .filter(lu::released_at.is_null())
+        .for_update()
+        .load(conn)?;
+    for usage in &usages {
+        // Same lock order as `check_and_consume`: a payment summing the window sees the release or not at all
+        let _counter: LimitCounter = lc::limit_counters.find(usage.counter_id).for_update().first(conn)?;
+        diesel::update(lu::limit_usages.find(usage.usage_id))
+            .set(lu::released_at.eq(Some(now)))
+            .execute(conn)?;
+    }
+    if !usages.is_empty() {
+        log::info!("Released {} limit usage(s) of payment {}", usages.len(), transaction_id);
+    }
+    Ok(usages.len())
+}
+
+/// Where a subject stands against one windowed rule.
+#[derive(Debug, Serialize, Clone)]
+pub struct LimitStatus {
+    pub rule_id: String,
+    pub period: LimitPeriod,
+    pub subject_type: LimitSubjectType,
+    pub subject_id: Uuid,
+    pub currency: String,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub max_amount: Option<Decimal>,
+    pub max_count: Option<u32>,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub used_amount: Decimal,
+    pub used_count: u32,
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub remaining_amount: Option<Decimal>,
+    pub resets_at: DateTime<Utc>, // When the oldest counted payment leaves the rolling window
+    pub increased: bool, // An approved temporary increase is in force
+}
+
+/// Usage within the rolling windows ending at `now` of the user and the given wallets, for rules still
+/// in the policy. Counters with nothing in their window are left out.
+pub fn current_usage(
+    conn: &mut PgConnection,
+    limit_policy: &LimitPolicy,
+    user_id: Uuid,
+    wallet_ids: &[Uuid],
+    now: DateTime<Utc>,
+) -> Result<Vec<LimitStatus>, DomainError> {
+    use crate::schema::limit_counters::dsl as lc;
+    let counters: Vec<LimitCounter> = lc::limit_counters
+        .filter(
+            lc::subject_type.eq(LimitSubjectType::User.as_str()).and(lc::subject_id.eq(user_id))
+                .or(lc::subject_type.eq(LimitSubjectType::Wallet.as_str()).and(lc::subject_id.eq_any(wallet_ids))),
+        )
+        .order((lc::rule_id.asc(), lc::currency_code.asc()))
+        .load(conn)?;
+
+    let mut statuses = Vec::new();
+    for counter in counters {
+        let Some(rule) = limit_policy.rule(&counter.rule_id) else {
+            continue; // Rule removed from the policy since
+        };
+        let used = window_usage(conn, counter.counter_id, rule.period, now)?;
+        let Some(resets_at) = used.frees_up_at(rule.period) else {
+            continue; // Nothing counted in the window
+        };
+        let terms = terms_for(conn, rule, counter.subject_id, now)?;
+        statuses.push(LimitStatus {
+            rule_id: rule.id.clone(),
+            period: rule.period,
+            subject_type: rule.subject,
+            subject_id: counter.subject_id,
+            currency: counter.currency_code,
+            max_amount: terms.max_amount,
+            max_count: terms.max_count,
+            used_amount: used.amount,
+            used_count: used.count,
+            remaining_amount: terms.max_amount.map(|max| (max - used.amount).max(Decimal::ZERO)),
+            resets_at,
+            increased: terms != rule.terms(),
+        });
+    }
+    Ok(statuses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use rust_decimal_macros::dec;
+
+    fn usage(amount: Decimal, created_at: DateTime<Utc>, released: bool) -> LimitUsage {
+        LimitUsage {
+            usage_id: Uuid::new_v4(),
+            counter_id: Uuid::nil(),
+            transaction_id: Uuid::new_v4(),
+            amount,
+            released_at: released.then_some(created_at),
+            created_at,
+        }
+    }
+
+    #[test]
+    fn test_rolling_window_usage() {
+        // Just after midnight: yesterday evening's payments still count against the daily limit
+        let now = Utc.with_ymd_and_hms(2026, 1, 1, 0, 30, 0).unwrap();
+        let (start, _) = LimitPeriod::Daily.window(now).unwrap();
+        let usages = [
+            usage(dec!(700), Utc.with_ymd_and_hms(2025, 12, 31, 23, 50, 0).unwrap(), false),
+            usage(dec!(200), Utc.with_ymd_and_hms(2025, 12, 31, 9, 0, 0).unwrap(), false),
+            usage(dec!(900), Utc.with_ymd_and_hms(2025, 12, 31, 10, 0, 0).unwrap(), true), // Payment failed
+            usage(dec!(500), start, false), // Exactly a day old: out of the window
+        ];
+        let used = WindowUsage::of(&usages, start);
+        assert_eq!((used.amount, used.count), (dec!(900), 2));
+        assert_eq!(used.oldest, Some(Utc.with_ymd_and_hms(2025, 12, 31, 9, 0, 0).unwrap()));
+        assert_eq!(used.frees_up_at(LimitPeriod::Daily), Some(Utc.with_ymd_and_hms(2026, 1, 1, 9, 0, 0).unwrap()));
+
+        // A day later nothing is left of them
+        let (start, _) = LimitPeriod::Daily.window(now + Duration::days(1)).unwrap();
+        assert_eq!(WindowUsage::of(&usages, start), WindowUsage::default());
+        assert_eq!(WindowUsage::default().frees_up_at(LimitPeriod::Daily), None);
+    }
+}
@@ -0,0 +1,14 @@
+// /home/inno/elights_jobes-research/backend/domain/src/limits/mod.rs
+// Transaction limits: per-transaction, daily, weekly and monthly caps by rail, currency, KYC tier and
+// wallet type (policy), rolling usage counters in Postgres (counters), and admin-approved temporary
+// increases (overrides).
+pub mod counters;
+pub mod overrides;
+pub mod policy;
+
+pub use counters::{check_and_consume, current_usage, payment_context, release_usage, LimitContext, LimitStatus};
+pub use overrides::{
+    active_override, approve_increase, get_override, list_overrides, reject_increase, request_increase, revoke_increase,
+    LimitIncreaseRequest,
+};
+pub use policy::{check_limit, LimitFacts, LimitPeriod, LimitPolicy, LimitRule, LimitTerms};
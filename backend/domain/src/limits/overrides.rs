@@ -0,0 +1,240 @@
+// /home/inno/elights_jobes-research/backend/domain/src/limits/overrides.rs
+// Temporary limit increases. Anyone may ask for one on their own user or wallet; it only takes effect
+// once an admin other than the requester approves it, and every step is recorded in the audit log.
+use super::policy::LimitPolicy;
+use crate::error::DomainError;
+use crate::models::{AuditOutcome, AuditTargetType, LimitOverride, LimitOverrideStatus, LimitSubjectType, NewLimitOverride};
+use crate::security::audit;
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+/// Longest a temporary increase may last.
+pub const MAX_OVERRIDE_DAYS: i64 = 90;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct LimitIncreaseRequest {
+    pub rule_id: String,
+    pub subject_id: Uuid, // User or wallet, per the rule's subject
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub max_amount: Option<Decimal>,
+    #[serde(default)]
+    pub max_count: Option<u32>,
+    #[serde(default)]
+    pub valid_from: Option<DateTime<Utc>>, // Defaults to now
+    pub valid_until: DateTime<Utc>,
+    pub reason: String,
+}
+
+/// The approved increase in force for the rule and subject at `now`, latest approved first.
+pub fn active_override(
+    conn: &mut PgConnection,
+    rule_id: &str,
+    subject: LimitSubjectType,
+    subject_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<Option<LimitOverride>, DomainError> {
+    use crate::schema::limit_overrides::dsl as lo;
+    Ok(lo::limit_overrides
+        .filter(lo::rule_id.eq(rule_id))
+        .filter(lo::subject_type.eq(subject.as_str()))
+        .filter(lo::subject_id.eq(subject_id))
+        .filter(lo::status.eq(LimitOverrideStatus::Approved.as_str()))
+        .filter(lo::valid_from.le(now))
+        .filter(lo::valid_until.gt(now))
+        .order(lo::decided_at.desc())
+        .first(conn)
+        .optional()?)
+}
+
+/// Records a pending increase of one rule for one subject. The requested values must raise the limit.
+pub fn request_increase(
+    conn: &mut PgConnection,
+    limit_policy: &LimitPolicy,
+    request: &LimitIncreaseRequest,
+    requested_by: Uuid,
+    actor: &str,
+    now: DateTime<Utc>,
+) -> Result<LimitOverride, DomainError> {
+    let rule = limit_policy
+        .rule(&request.rule_id)
+        .ok_or_else(|| DomainError::NotFound(format!("Limit rule '{}' not found", request.rule_id)))?;
+    if request.max_amount.is_none() && request.max_count.is_none() {
+        return Err(DomainError::Validation("An increase needs a new max_amount and/or max_count".to_string()));
+    }
+    let raises_amount = match (request.max_amount, rule.max_amount) {
+        (Some(requested), Some(current)) => requested > current,
+        (Some(_), None) => false, // Rule has no amount cap to raise
+        (None, _) => true,
+    };
+    let raises_count = match (request.max_count, rule.max_count) {
+        (Some(requested), Some(current)) => requested > current,
+        (Some(_), None) => false,
+        (None, _) => true,
+    };
+    if !raises_amount || !raises_count {
+        return Err(DomainError::Validation(format!("Requested values do not raise limit '{}'", rule.id)));
+    }
+    let valid_from = request.valid_from.unwrap_or(now).max(now);
+    if request.valid_until <= valid_from {
+        return Err(DomainError::Validation("valid_until must be after valid_from and now".to_string()));
+    }
+    if request.valid_until - valid_from > Duration::days(MAX_OVERRIDE_DAYS) {
+        return Err(DomainError::Validation(format!("A temporary increase may last at most {} days", MAX_OVERRIDE_DAYS)));
+    }
+    if request.reason.trim().is_empty() {
+        return Err(DomainError::Validation("A reason is required".to_string()));
+    }
+
+    conn.transaction(|conn| {
+        let increase: LimitOverride = diesel::insert_into(crate::schema::limit_overrides::table)
+            .values(&NewLimitOverride {
+                rule_id: &rule.id,
+                subject_type: rule.subject.as_str(),
+                subject_id: request.subject_id,
+                max_amount: request.max_amount,
+                max_count: request.max_count.map(|count| count.min(i32::MAX as u32) as i32),
+                valid_from,
+                valid_until: request.valid_until,
+                reason: request.reason.trim(),
+                requested_by,
+            })
+            .get_result(conn)?;
+        audit::log_db_audit_event(
+            conn, Some(requested_by), actor, "LIMIT_INCREASE_REQUEST", Some(AuditTargetType::TransactionLimit),
+            Some(&increase.override_id.to_string()), AuditOutcome::Success,
+            Some(json!({"rule_id": increase.rule_id, "subject_type": increase.subject_type, "subject_id": increase.subject_id,
+                "max_amount": increase.max_amount.map(|a| a.to_string()), "max_count": increase.max_count,
+                "valid_from": increase.valid_from, "valid_until": increase.valid_until, "reason": increase.reason})),
+            None,
+        )?;
+        log::info!("Limit increase {} on rule {} requested by {}", increase.override_id, increase.rule_id, actor);
+        Ok(increase)
+    })
+}
+
+fn load_for_update(conn: &mut PgConnection, override_id: Uuid) -> Result<LimitOverride, DomainError> {
+    use crate::schema::limit_overrides::dsl as lo;
+    lo::limit_overrides
+        .find(override_id)
+        .for_update()
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("Limit increase {} not found", override_id)))
+}
+
+/// Approves, rejects or revokes an increase. Pending increases are decided by an admin who did not
+/// request them; only approved ones can be revoked.
+fn set_status(
+    conn: &mut PgConnection,
+    override_id: Uuid,
+    new_status: LimitOverrideStatus,
+    admin_id: Uuid,
+    actor: &str,
+    comment: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<LimitOverride, DomainError> {
+    use crate::schema::limit_overrides::dsl as lo;
+    conn.transaction(|conn| {
+        let increase = load_for_update(conn, override_id)?;
+        let expected = match new_status {
+            LimitOverrideStatus::Revoked => LimitOverrideStatus::Approved,
+            _ => LimitOverrideStatus::Pending,
+        };
+        if increase.status != expected.as_str() {
+            return Err(DomainError::Validation(format!(
+                "Limit increase {} is {}, cannot be set to {}", override_id, increase.status, new_status.as_str()
+            )));
+        }
+        if new_status == LimitOverrideStatus::Approved && increase.requested_by == admin_id {
+            return Err(DomainError::Authorization("A limit increase cannot be approved by its requester".to_string()));
+        }
+        if new_status == LimitOverrideStatus::Approved && increase.valid_until <= now {
+            return Err(DomainError::Validation(format!("Limit increase {} has already lapsed", override_id)));
+        }
+        let updated: LimitOverride = diesel::update(lo::limit_overrides.find(override_id))
+            .set((
+                lo::status.eq(new_status.as_str()),
+                lo::decided_by.eq(Some(admin_id)),
+                lo::decision_comment.eq(comment),
+                lo::decided_at.eq(Some(now)),
+            ))
+            .get_result(conn)?;
+        let action = match new_status {
+            LimitOverrideStatus::Approved => "LIMIT_INCREASE_APPROVE",
+            LimitOverrideStatus::Rejected => "LIMIT_INCREASE_REJECT",
+            _ => "LIMIT_INCREASE_REVOKE",
+        };
+        audit::log_db_audit_event(
+            conn, Some(admin_id), actor, action, Some(AuditTargetType::TransactionLimit),
+            Some(&override_id.to_string()), AuditOutcome::Success,
+            Some(json!({"rule_id": updated.rule_id, "subject_type": updated.subject_type, "subject_id": updated.subject_id,
+                "requested_by": updated.requested_by, "comment": comment})),
+            None,
+        )?;
+        log::info!("Limit increase {} set to {} by {}", override_id, updated.status, actor);
+        Ok(updated)
+    })
+}
+
+pub fn approve_increase(
+    conn: &mut PgConnection,
+    override_id: Uuid,
+    admin_id: Uuid,
+    actor: &str,
+    comment: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<LimitOverride, DomainError> {
+    set_status(conn, override_id, LimitOverrideStatus::Approved, admin_id, actor, comment, now)
+}
+
+pub fn reject_increase(
+    conn: &mut PgConnection,
+    override_id: Uuid,
+    admin_id: Uuid,
+    actor: &str,
+    comment: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<LimitOverride, DomainError> {
+    set_status(conn, override_id, LimitOverrideStatus::Rejected, admin_id, actor, comment, now)
+}
+
+pub fn revoke_increase(
+    conn: &mut PgConnection,
+    override_id: Uuid,
+    admin_id: Uuid,
+    actor: &str,
+    comment: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<LimitOverride, DomainError> {
+    set_status(conn, override_id, LimitOverrideStatus::Revoked, admin_id, actor, comment, now)
+}
+
+pub fn get_override(conn: &mut PgConnection, override_id: Uuid) -> Result<LimitOverride, DomainError> {
+    use crate::schema::limit_overrides::dsl as lo;
+    lo::limit_overrides
+        .find(override_id)
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("Limit increase {} not found", override_id)))
+}
+
+/// Increases newest first, optionally by status and/or subject.
+pub fn list_overrides(
+    conn: &mut PgConnection,
+    status: Option<LimitOverrideStatus>,
+    subject_ids: Option<&[Uuid]>,
+) -> Result<Vec<LimitOverride>, DomainError> {
+    use crate::schema::limit_overrides::dsl as lo;
+    let mut query = lo::limit_overrides.into_boxed();
+    if let Some(status) = status {
+        query = query.filter(lo::status.eq(status.as_str()));
+    }
+    if let Some(subject_ids) = subject_ids {
+        query = query.filter(lo::subject_id.eq_any(subject_ids.to_vec()));
+    }
+    Ok(query.order(lo::created_at.desc()).limit(500).load(conn)?)
+}
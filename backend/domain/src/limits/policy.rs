@@ -0,0 +1,269 @@
+// /home/inno/elights_jobes-research/backend/domain/src/limits/policy.rs
+// Transaction limit rules, loaded from a JSON file. A rule caps the amount and/or number of outbound
+// payments per transaction or per rolling window (the last 24 hours, 7 days or 30 days before the payment),
+// counted per customer or per wallet. Every criterion set on a rule must match (unset = any); every
+// matching rule applies, so the tightest one decides.
+use crate::config::{self, JsonConfig};
+use crate::error::DomainError;
+use crate::fees::FeeRail;
+use crate::models::{LimitSubjectType, WalletType};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LimitPeriod {
+    PerTransaction,
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl LimitPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LimitPeriod::PerTransaction => "PER_TRANSACTION",
+            LimitPeriod::Daily => "DAILY",
+            LimitPeriod::Weekly => "WEEKLY",
+            LimitPeriod::Monthly => "MONTHLY",
+        }
+    }
+
+    /// Length of the rolling window; None for per-transaction limits. A month is 30 days.
+    pub fn length(&self) -> Option<Duration> {
+        match self {
+            LimitPeriod::PerTransaction => None,
+            LimitPeriod::Daily => Some(Duration::hours(24)),
+            LimitPeriod::Weekly => Some(Duration::days(7)),
+            LimitPeriod::Monthly => Some(Duration::days(30)),
+        }
+    }
+
+    /// The rolling window ending at `now` as `(start, now]`: usage older than `now - period` no longer
+    /// counts, so a limit cannot be used twice over around a midnight or month boundary.
+    pub fn window(&self, now: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        self.length().map(|length| (now - length, now))
+    }
+}
+
+/// One limit rule.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LimitRule {
+    pub id: String,
+    #[serde(default)]
+    pub subject: LimitSubjectType, // USER = all of the customer's wallets, WALLET = each wallet separately
+    pub period: LimitPeriod,
+    #[serde(default)]
+    pub rails: Vec<FeeRail>, // Empty = any rail
+    #[serde(default)]
+    pub currency: Option<String>, // None = any currency, counted per currency
+    #[serde(default)]
+    pub kyc_tiers: Vec<String>, // Empty = any tier
+    #[serde(default)]
+    pub wallet_types: Vec<String>, // e.g. FIAT_USD; empty = any wallet
+    #[serde(default)]
+    pub max_amount: Option<Decimal>, // Total in the window (or of one payment), in the payment currency
+    #[serde(default)]
+    pub max_count: Option<u32>, // Payments in the window
+}
+
+/// What a rule is matched against.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LimitFacts<'a> {
+    pub rail: Option<FeeRail>,
+    pub currency: &'a str,
+    pub kyc_tier: &'a str,
+    pub wallet_type: &'a str,
+    pub amount: Decimal,
+}
+
+/// Limits in force for one subject: the rule's, or those of an approved temporary increase.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LimitTerms {
+    pub max_amount: Option<Decimal>,
+    pub max_count: Option<u32>,
+}
+
+impl LimitRule {
+    pub fn matches(&self, facts: &LimitFacts) -> bool {
+        (self.rails.is_empty() || facts.rail.is_some_and(|rail| self.rails.contains(&rail)))
+            && self.currency.as_deref().map_or(true, |c| c.eq_ignore_ascii_case(facts.currency))
+            && (self.kyc_tiers.is_empty() || self.kyc_tiers.iter().any(|t| t.eq_ignore_ascii_case(facts.kyc_tier)))
+            && (self.wallet_types.is_empty() || self.wallet_types.iter().any(|w| w.eq_ignore_ascii_case(facts.wallet_type)))
+    }
+
+    pub fn terms(&self) -> LimitTerms {
+        LimitTerms { max_amount: self.max_amount, max_count: self.max_count }
+    }
+}
+
+/// Checks a payment of `amount` against `terms` given what the window has used so far. `resets_at` is when
+/// the oldest payment in the window leaves it.
+pub fn check_limit(
+    rule: &LimitRule,
+    terms: LimitTerms,
+    currency: &str,
+    used_amount: Decimal,
+    used_count: u32,
+    amount: Decimal,
+    resets_at: Option<DateTime<Utc>>,
+) -> Result<(), DomainError> {
+    let resets = resets_at.map_or_else(String::new, |at| format!("; usage starts freeing up at {}", at.to_rfc3339()));
+    let exceeded = |detail: String| DomainError::LimitExceeded { limit_id: rule.id.clone(), detail, resets_at };
+    if let Some(max_amount) = terms.max_amount {
+        if used_amount + amount > max_amount {
+            return Err(exceeded(match rule.period {
+                LimitPeriod::PerTransaction => format!("payments are limited to {} {} each", max_amount, currency),
+                period => format!(
+                    "{} limit of {} {}, {} used, {} requested{}",
+                    period.as_str().to_lowercase(), max_amount, currency, used_amount, amount, resets
+                ),
+            }));
+        }
+    }
+    if let Some(max_count) = terms.max_count {
+        if used_count + 1 > max_count {
+            return Err(exceeded(format!(
+                "{} limit of {} payments reached{}", rule.period.as_str().to_lowercase(), max_count, resets
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// The full set of limit rules in force. An empty policy limits nothing.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct LimitPolicy {
+    #[serde(default)]
+    pub version: Option<String>, // Free text, logged when the policy is loaded
+    #[serde(default)]
+    pub rules: Vec<LimitRule>,
+}
+
+impl LimitPolicy {
+    pub fn rule(&self, id: &str) -> Option<&LimitRule> {
+        self.rules.iter().find(|rule| rule.id == id)
+    }
+
+    /// Every rule that applies to the payment.
+    pub fn matching<'p>(&'p self, facts: &LimitFacts) -> Vec<&'p LimitRule> {
+        self.rules.iter().filter(|rule| rule.matches(facts)).collect()
+    }
+}
+
+impl JsonConfig for LimitPolicy {
+    const NAME: &'static str = "limits policy";
+
+    /// Rejects rules that limit nothing or are ambiguous.
+    fn validate(&self) -> Result<(), DomainError> {
+        let mut ids = HashSet::new();
+        for rule in &self.rules {
+            let invalid = |reason: &str| DomainError::Configuration(format!("Limit rule '{}': {}", rule.id, reason));
+            if rule.id.trim().is_empty() {
+                return Err(DomainError::Configuration("Limit rule with an empty id".to_string()));
+            }
+            if !ids.insert(rule.id.as_str()) {
+                return Err(invalid("duplicate id"));
+            }
+            if rule.max_amount.is_none() && rule.max_count.is_none() {
+                return Err(invalid("needs max_amount and/or max_count"));
+            }
+            if rule.max_amount.is_some_and(|max| max < Decimal::ZERO) {
+                return Err(invalid("max_amount cannot be negative"));
+            }
+            if rule.period == LimitPeriod::PerTransaction && rule.max_count.is_some() {
+                return Err(invalid("max_count needs a DAILY, WEEKLY or MONTHLY period"));
+            }
+            if let Some(unknown) = rule.wallet_types.iter().find(|w| WalletType::parse(&w.to_uppercase()).is_none()) {
+                return Err(invalid(&format!("unknown wallet type '{}'", unknown)));
+            }
+        }
+        Ok(())
+    }
+
+    fn summary(&self) -> String {
+        config::versioned_summary(self.version.as_deref(), self.rules.len(), "rules")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    fn policy() -> LimitPolicy {
+        config::parse_json::<LimitPolicy>(r#"{
+            "version": "test",
+            "rules": [
+                { "id": "basic-single", "period": "PER_TRANSACTION", "kyc_tiers": ["BASIC"], "max_amount": "1000" },
+                { "id": "basic-daily", "period": "DAILY", "kyc_tiers": ["BASIC"], "max_amount": "2500", "max_count": 5 },
+                { "id": "wire-monthly-usd", "period": "MONTHLY", "rails": ["WIRE"], "currency": "USD", "max_amount": "50000" },
+                { "id": "savings-wallet-weekly", "subject": "WALLET", "period": "WEEKLY", "wallet_types": ["SAVINGS_USD"], "max_count": 2 }
+            ]
+        }"#).unwrap()
+    }
+
+    fn facts(rail: FeeRail, kyc_tier: &'static str, wallet_type: &'static str) -> LimitFacts<'static> {
+        LimitFacts { rail: Some(rail), currency: "USD", kyc_tier, wallet_type, amount: dec!(100) }
+    }
+
+    #[test]
+    fn test_matching_rules() {
+        let policy = policy();
+        let ids = |facts: &LimitFacts| policy.matching(facts).iter().map(|r| r.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&facts(FeeRail::Ach, "BASIC", "FIAT_USD")), vec!["basic-single", "basic-daily"]);
+        assert_eq!(ids(&facts(FeeRail::Wire, "verified", "SAVINGS_USD")), vec!["wire-monthly-usd", "savings-wallet-weekly"]);
+        assert!(ids(&facts(FeeRail::Ach, "VERIFIED", "FIAT_USD")).is_empty());
+    }
+
+    #[test]
+    fn test_windows() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 1, 0, 30, 0).unwrap(); // Just past midnight and the month boundary
+        assert_eq!(LimitPeriod::PerTransaction.window(now), None);
+        let (start, end) = LimitPeriod::Daily.window(now).unwrap();
+        assert_eq!((start, end), (Utc.with_ymd_and_hms(2025, 12, 31, 0, 30, 0).unwrap(), now));
+        let (start, end) = LimitPeriod::Weekly.window(now).unwrap();
+        assert_eq!((start, end), (Utc.with_ymd_and_hms(2025, 12, 25, 0, 30, 0).unwrap(), now));
+        let (start, end) = LimitPeriod::Monthly.window(now).unwrap();
+        assert_eq!((start, end), (Utc.with_ymd_and_hms(2025, 12, 2, 0, 30, 0).unwrap(), now));
+    }
+
+    #[test]
+    fn test_check_limit_reports_reset_time() {
+        let policy = policy();
+        let daily = policy.rule("basic-daily").unwrap();
+        let resets_at = Utc.with_ymd_and_hms(2025, 4, 23, 0, 0, 0).unwrap();
+        assert!(check_limit(daily, daily.terms(), "USD", dec!(2000), 2, dec!(500), Some(resets_at)).is_ok());
+
+        let err = check_limit(daily, daily.terms(), "USD", dec!(2000), 2, dec!(500.01), Some(resets_at)).unwrap_err();
+        match &err {
+            DomainError::LimitExceeded { limit_id, resets_at: at, .. } => {
+                assert_eq!(limit_id, "basic-daily");
+                assert_eq!(*at, Some(resets_at));
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+        assert!(err.to_string().contains("freeing up at 2025-04-23T00:00:00+00:00"));
+        assert!(check_limit(daily, daily.terms(), "USD", dec!(0), 5, dec!(1), Some(resets_at)).is_err());
+
+        // An approved increase replaces the rule's terms
+        let raised = LimitTerms { max_amount: Some(dec!(10000)), max_count: Some(10) };
+        assert!(check_limit(daily, raised, "USD", dec!(2000), 5, dec!(5000), Some(resets_at)).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        for json in [
+            r#"{"rules": [{"id": "a", "period": "DAILY"}]}"#,
+            r#"{"rules": [{"id": "a", "period": "DAILY", "max_count": 1}, {"id": "a", "period": "DAILY", "max_count": 2}]}"#,
+            r#"{"rules": [{"id": "a", "period": "PER_TRANSACTION", "max_count": 1}]}"#,
+            r#"{"rules": [{"id": "a", "period": "DAILY", "max_amount": "-1"}]}"#,
+            r#"{"rules": [{"id": "a", "period": "DAILY", "max_count": 1, "wallet_types": ["CHEQUING"]}]}"#,
+        ] {
+            assert!(config::parse_json::<LimitPolicy>(json).is_err(), "{} should be rejected", json);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/aml.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{Int4, Uuid as DieselUuid, Nullable, Varchar, Text, Jsonb, Timestamptz}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use serde_json::Value as JsonValue;
+
+table! {
+    core_schema.aml_cases (case_id) {
+        case_id -> DieselUuid,
+        user_id -> DieselUuid,
+        status -> Varchar,
+        risk_score -> Int4,
+        alert_count -> Int4,
+        assigned_to -> Nullable<DieselUuid>,
+        resolution_comment -> Nullable<Text>,
+        sar_reference -> Nullable<Varchar>,
+        closed_by -> Nullable<DieselUuid>,
+        closed_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    core_schema.aml_alerts (alert_id) {
+        alert_id -> DieselUuid,
+        case_id -> DieselUuid,
+        user_id -> DieselUuid,
+        scenario_id -> Varchar,
+        severity -> Varchar,
+        transaction_ids -> Jsonb,
+        window_start -> Timestamptz,
+        window_end -> Timestamptz,
+        summary -> Text,
+        facts -> Jsonb,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    core_schema.aml_monitor_state (monitor_id) {
+        monitor_id -> Varchar,
+        last_updated_at -> Timestamptz,
+        last_transaction_id -> DieselUuid,
+        updated_at -> Timestamptz,
+    }
+}
+
+/// Where an AML investigation stands.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AmlCaseStatus {
+    Open,          // New alerts, not picked up yet
+    Investigating, // Assigned to an investigator
+    Closed,        // No suspicious activity found
+    SarFiled,      // Suspicious activity report filed
+}
+
+impl AmlCaseStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AmlCaseStatus::Open => "OPEN",
+            AmlCaseStatus::Investigating => "INVESTIGATING",
+            AmlCaseStatus::Closed => "CLOSED",
+            AmlCaseStatus::SarFiled => "SAR_FILED",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<AmlCaseStatus> {
+        match value {
+            "OPEN" => Some(AmlCaseStatus::Open),
+            "INVESTIGATING" => Some(AmlCaseStatus::Investigating),
+            "CLOSED" => Some(AmlCaseStatus::Closed),
+            "SAR_FILED" => Some(AmlCaseStatus::SarFiled),
+            _ => None,
+        }
+    }
+
+    /// Cases that still collect new alerts.
+    pub fn is_active(&self) -> bool {
+        matches!(self, AmlCaseStatus::Open | AmlCaseStatus::Investigating)
+    }
+}
+
+/// All alerts on one customer until the investigation is closed.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = aml_cases, primary_key(case_id))]
+pub struct AmlCase {
+    pub case_id: Uuid,
+    pub user_id: Uuid,
+    pub status: String, // Map to AmlCaseStatus
+    pub risk_score: i32,
+    pub alert_count: i32,
+    pub assigned_to: Option<Uuid>,
+    pub resolution_comment: Option<String>,
+    pub sar_reference: Option<String>,
+    pub closed_by: Option<Uuid>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = aml_cases)]
+pub struct NewAmlCase<'a> {
+    pub user_id: Uuid,
+    pub status: &'a str,
+}
+
+/// One pattern detected by a monitoring scenario.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = aml_alerts, primary_key(alert_id))]
+pub struct AmlAlert {
+    pub alert_id: Uuid,
+    pub case_id: Uuid,
+    pub user_id: Uuid,
+    pub scenario_id: String,
+    pub severity: String, // Map to aml::AlertSeverity
+    pub transaction_ids: JsonValue, // Array of transaction UUIDs
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub summary: String,
+    pub facts: JsonValue,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = aml_alerts)]
+pub struct NewAmlAlert<'a> {
+    pub case_id: Uuid,
+    pub user_id: Uuid,
+    pub scenario_id: &'a str,
+    pub severity: &'a str,
+    pub transaction_ids: JsonValue,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub summary: &'a str,
+    pub facts: JsonValue,
+}
+
+/// Position of the streaming monitor.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = aml_monitor_state, primary_key(monitor_id))]
+pub struct AmlMonitorState {
+    pub monitor_id: String,
+    pub last_updated_at: DateTime<Utc>,
+    pub last_transaction_id: Uuid,
+    pub updated_at: DateTime<Utc>,
+}
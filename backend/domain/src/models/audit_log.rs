@@ -40,6 +40,20 @@ pub enum AuditTargetType {
     Transaction,
     System,
     Config,
+    PaymentSchedule,
+    PayoutBatch,
+    Beneficiary,
+    InboundSuspense,
+    WebhookEvent,
+    Reconciliation,
+    EodPositionSnapshot,
+    InterestRate,
+    TransactionLimit,
+    SanctionsCase,
+    AmlCase,
+    CtrReport,
+    TravelRuleTransfer,
+    FraudEvaluation,
     // Add others as needed
 }
 // TODO: Implement ToSql/FromSql for AuditTargetType if using DbEnum
@@ -0,0 +1,111 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/beneficiary.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{Uuid as DieselUuid, Nullable, Varchar, Timestamptz}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+table! {
+    core_schema.beneficiaries (beneficiary_id) {
+        beneficiary_id -> DieselUuid,
+        user_id -> DieselUuid,
+        nickname -> Varchar,
+        beneficiary_type -> Varchar,
+        identifier_hash -> Varchar,
+        masked_identifier -> Varchar,
+        bank_identifier -> Nullable<Varchar>,
+        crypto_network -> Nullable<Varchar>,
+        country_code -> Nullable<Varchar>,
+        holder_name -> Nullable<Varchar>,
+        status -> Varchar,
+        verified_at -> Nullable<Timestamptz>,
+        cooling_off_until -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+/// How a saved payee is reached.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum BeneficiaryType {
+    Ach,    // US routing + account number
+    Iban,   // IBAN (BIC optional)
+    Wire,   // BIC + non-IBAN account number
+    Crypto, // Network + address
+}
+
+impl BeneficiaryType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BeneficiaryType::Ach => "ACH",
+            BeneficiaryType::Iban => "IBAN",
+            BeneficiaryType::Wire => "WIRE",
+            BeneficiaryType::Crypto => "CRYPTO",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<BeneficiaryType> {
+        match value {
+            "ACH" => Some(BeneficiaryType::Ach),
+            "IBAN" => Some(BeneficiaryType::Iban),
+            "WIRE" => Some(BeneficiaryType::Wire),
+            "CRYPTO" => Some(BeneficiaryType::Crypto),
+            _ => None,
+        }
+    }
+}
+
+/// Lifecycle of a saved payee. Only ACTIVE beneficiaries can be paid.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum BeneficiaryStatus {
+    PendingVerification, // New, or payment details changed since the last verification
+    Active,
+    Disabled, // Removed by the customer; kept for payment history
+}
+
+impl BeneficiaryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BeneficiaryStatus::PendingVerification => "PENDING_VERIFICATION",
+            BeneficiaryStatus::Active => "ACTIVE",
+            BeneficiaryStatus::Disabled => "DISABLED",
+        }
+    }
+}
+
+/// A customer's saved payee. The account identifier itself is never stored.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = beneficiaries, primary_key(beneficiary_id))]
+pub struct Beneficiary {
+    pub beneficiary_id: Uuid,
+    pub user_id: Uuid,
+    pub nickname: String,
+    pub beneficiary_type: String, // Map to BeneficiaryType
+    #[serde(skip_serializing)] // Matching key only, no use to API clients
+    pub identifier_hash: String,
+    pub masked_identifier: String,
+    pub bank_identifier: Option<String>,
+    pub crypto_network: Option<String>,
+    pub country_code: Option<String>,
+    pub holder_name: Option<String>,
+    pub status: String, // Map to BeneficiaryStatus
+    pub verified_at: Option<DateTime<Utc>>,
+    pub cooling_off_until: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = beneficiaries)]
+pub struct NewBeneficiary<'a> {
+    pub user_id: Uuid,
+    pub nickname: &'a str,
+    pub beneficiary_type: &'a str,
+    pub identifier_hash: &'a str,
+    pub masked_identifier: &'a str,
+    pub bank_identifier: Option<&'a str>,
+    pub crypto_network: Option<&'a str>,
+    pub country_code: Option<&'a str>,
+    pub holder_name: Option<&'a str>,
+    pub status: &'a str,
+}
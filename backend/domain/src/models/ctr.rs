@@ -0,0 +1,260 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/ctr.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{Date, Int4, Uuid as DieselUuid, Nullable, Varchar, Numeric as DieselNumeric, Text, Jsonb, Timestamptz}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+use rust_decimal::Decimal;
+use bigdecimal::BigDecimal;
+use serde_json::Value as JsonValue;
+
+table! {
+    core_schema.ctr_exemptions (exemption_id) {
+        exemption_id -> DieselUuid,
+        user_id -> DieselUuid,
+        exemption_type -> Varchar,
+        reason -> Text,
+        effective_from -> Date,
+        expires_on -> Nullable<Date>,
+        created_by -> DieselUuid,
+        revoked_by -> Nullable<DieselUuid>,
+        revoked_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    core_schema.ctr_batches (batch_id) {
+        batch_id -> DieselUuid,
+        file_name -> Varchar,
+        xml -> Text,
+        report_count -> Int4,
+        total_amount -> DieselNumeric,
+        status -> Varchar,
+        created_by -> DieselUuid,
+        submitted_at -> Nullable<Timestamptz>,
+        acknowledged_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    core_schema.ctr_reports (report_id) {
+        report_id -> DieselUuid,
+        user_id -> DieselUuid,
+        business_date -> Date,
+        currency_code -> Varchar,
+        cash_in_total -> DieselNumeric,
+        cash_out_total -> DieselNumeric,
+        details -> Jsonb,
+        transaction_ids -> Jsonb,
+        wallet_ids -> Jsonb,
+        status -> Varchar,
+        exemption_id -> Nullable<DieselUuid>,
+        batch_id -> Nullable<DieselUuid>,
+        batch_seq_num -> Nullable<Int4>,
+        bsa_id -> Nullable<Varchar>,
+        rejection_reason -> Nullable<Text>,
+        filed_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+/// Where a CTR stands.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CtrReportStatus {
+    Pending,  // Over the threshold, waiting for the next batch
+    Exempt,   // Person was exempt on the business day: recorded, not filed
+    Batched,  // In a generated batch file not submitted yet
+    Filed,    // Batch submitted to FinCEN, waiting for the acknowledgement
+    Accepted, // Acknowledged with a BSA ID
+    Rejected, // Acknowledged with errors: goes into the next batch again
+}
+
+impl CtrReportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CtrReportStatus::Pending => "PENDING",
+            CtrReportStatus::Exempt => "EXEMPT",
+            CtrReportStatus::Batched => "BATCHED",
+            CtrReportStatus::Filed => "FILED",
+            CtrReportStatus::Accepted => "ACCEPTED",
+            CtrReportStatus::Rejected => "REJECTED",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<CtrReportStatus> {
+        match value {
+            "PENDING" => Some(CtrReportStatus::Pending),
+            "EXEMPT" => Some(CtrReportStatus::Exempt),
+            "BATCHED" => Some(CtrReportStatus::Batched),
+            "FILED" => Some(CtrReportStatus::Filed),
+            "ACCEPTED" => Some(CtrReportStatus::Accepted),
+            "REJECTED" => Some(CtrReportStatus::Rejected),
+            _ => None,
+        }
+    }
+}
+
+/// Where a CTR batch file stands.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CtrBatchStatus {
+    Generated,    // File built, reports locked into it
+    Submitted,    // Uploaded to BSA E-Filing
+    Acknowledged, // Acknowledgement processed
+}
+
+impl CtrBatchStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CtrBatchStatus::Generated => "GENERATED",
+            CtrBatchStatus::Submitted => "SUBMITTED",
+            CtrBatchStatus::Acknowledged => "ACKNOWLEDGED",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<CtrBatchStatus> {
+        match value {
+            "GENERATED" => Some(CtrBatchStatus::Generated),
+            "SUBMITTED" => Some(CtrBatchStatus::Submitted),
+            "ACKNOWLEDGED" => Some(CtrBatchStatus::Acknowledged),
+            _ => None,
+        }
+    }
+}
+
+/// Exempt person categories (31 CFR 1020.315).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CtrExemptionType {
+    PhaseI,  // Banks, government agencies, listed companies and their subsidiaries
+    PhaseII, // Non-listed businesses and payroll customers
+}
+
+impl CtrExemptionType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CtrExemptionType::PhaseI => "PHASE_I",
+            CtrExemptionType::PhaseII => "PHASE_II",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<CtrExemptionType> {
+        match value {
+            "PHASE_I" => Some(CtrExemptionType::PhaseI),
+            "PHASE_II" => Some(CtrExemptionType::PhaseII),
+            _ => None,
+        }
+    }
+}
+
+/// A customer exempted from CTR filing.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = ctr_exemptions, primary_key(exemption_id))]
+pub struct CtrExemption {
+    pub exemption_id: Uuid,
+    pub user_id: Uuid,
+    pub exemption_type: String, // Map to CtrExemptionType
+    pub reason: String,
+    pub effective_from: NaiveDate,
+    pub expires_on: Option<NaiveDate>,
+    pub created_by: Uuid,
+    pub revoked_by: Option<Uuid>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = ctr_exemptions)]
+pub struct NewCtrExemption<'a> {
+    pub user_id: Uuid,
+    pub exemption_type: &'a str,
+    pub reason: &'a str,
+    pub effective_from: NaiveDate,
+    pub expires_on: Option<NaiveDate>,
+    pub created_by: Uuid,
+}
+
+/// One CTR batch file.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = ctr_batches, primary_key(batch_id))]
+pub struct CtrBatch {
+    pub batch_id: Uuid,
+    pub file_name: String,
+    #[serde(skip_serializing)] // Downloaded separately
+    pub xml: String,
+    pub report_count: i32,
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub total_amount: Decimal,
+    pub status: String, // Map to CtrBatchStatus
+    pub created_by: Uuid,
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = ctr_batches)]
+pub struct NewCtrBatch<'a> {
+    pub batch_id: Uuid,
+    pub file_name: &'a str,
+    pub xml: &'a str,
+    pub report_count: i32,
+    #[diesel(serialize_as = BigDecimal)]
+    pub total_amount: Decimal,
+    pub status: &'a str,
+    pub created_by: Uuid,
+}
+
+/// Cash activity of one person on one business day over the reporting threshold.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = ctr_reports, primary_key(report_id))]
+pub struct CtrReport {
+    pub report_id: Uuid,
+    pub user_id: Uuid,
+    pub business_date: NaiveDate,
+    pub currency_code: String,
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub cash_in_total: Decimal,
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub cash_out_total: Decimal,
+    pub details: JsonValue, // Array of ctr::CashDetail
+    pub transaction_ids: JsonValue, // Array of transaction UUIDs
+    pub wallet_ids: JsonValue, // Array of wallet UUIDs
+    pub status: String, // Map to CtrReportStatus
+    pub exemption_id: Option<Uuid>,
+    pub batch_id: Option<Uuid>,
+    pub batch_seq_num: Option<i32>,
+    pub bsa_id: Option<String>,
+    pub rejection_reason: Option<String>,
+    pub filed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = ctr_reports)]
+pub struct NewCtrReport<'a> {
+    pub user_id: Uuid,
+    pub business_date: NaiveDate,
+    pub currency_code: &'a str,
+    #[diesel(serialize_as = BigDecimal)]
+    pub cash_in_total: Decimal,
+    #[diesel(serialize_as = BigDecimal)]
+    pub cash_out_total: Decimal,
+    pub details: JsonValue,
+    pub transaction_ids: JsonValue,
+    pub wallet_ids: JsonValue,
+    pub status: &'a str,
+    pub exemption_id: Option<Uuid>,
+}
@@ -0,0 +1,68 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/eod_position.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{Date, Int4, Uuid as DieselUuid, Varchar, Text, Jsonb, Timestamptz}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+use serde_json::Value as JsonValue;
+
+table! {
+    core_schema.eod_position_snapshots (snapshot_id) {
+        snapshot_id -> DieselUuid,
+        business_date -> Date,
+        projection_date -> Date,
+        report -> Jsonb,
+        payload -> Text,
+        payload_sha256 -> Varchar,
+        signature_algorithm -> Varchar,
+        signature -> Text,
+        signing_key_id -> Varchar,
+        signing_public_key -> Varchar,
+        account_count -> Int4,
+        shortfall_count -> Int4,
+        generated_by -> Varchar,
+        generated_at -> Timestamptz,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+/// A stored, signed end-of-day nostro position report (one per business day).
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = eod_position_snapshots, primary_key(snapshot_id))]
+pub struct EodPositionSnapshot {
+    pub snapshot_id: Uuid,
+    pub business_date: NaiveDate,
+    pub projection_date: NaiveDate,
+    pub report: JsonValue, // EodPositionReport
+    pub payload: String, // The exact bytes that were signed
+    pub payload_sha256: String,
+    pub signature_algorithm: String,
+    pub signature: String,
+    pub signing_key_id: String,
+    pub signing_public_key: String,
+    pub account_count: i32,
+    pub shortfall_count: i32,
+    pub generated_by: String,
+    pub generated_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, AsChangeset, Clone)]
+#[diesel(table_name = eod_position_snapshots)]
+pub struct NewEodPositionSnapshot<'a> {
+    pub business_date: NaiveDate,
+    pub projection_date: NaiveDate,
+    pub report: JsonValue,
+    pub payload: &'a str,
+    pub payload_sha256: &'a str,
+    pub signature_algorithm: &'a str,
+    pub signature: &'a str,
+    pub signing_key_id: &'a str,
+    pub signing_public_key: &'a str,
+    pub account_count: i32,
+    pub shortfall_count: i32,
+    pub generated_by: &'a str,
+    pub generated_at: DateTime<Utc>,
+}
@@ -0,0 +1,140 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/fraud.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{Int4, Uuid as DieselUuid, Nullable, Varchar, Text, Jsonb, Timestamptz, Bool}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use serde_json::Value as JsonValue;
+
+table! {
+    core_schema.fraud_evaluations (evaluation_id) {
+        evaluation_id -> DieselUuid,
+        transaction_id -> DieselUuid,
+        user_id -> DieselUuid,
+        rule_set_version -> Varchar,
+        mode -> Varchar,
+        score -> Int4,
+        shadow_score -> Int4,
+        decision -> Varchar,
+        shadow_decision -> Varchar,
+        applied_decision -> Varchar,
+        features -> Jsonb,
+        review_status -> Nullable<Varchar>,
+        pending_dispatch -> Nullable<Jsonb>,
+        reviewed_by -> Nullable<DieselUuid>,
+        review_comment -> Nullable<Text>,
+        reviewed_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    core_schema.fraud_rule_hits (hit_id) {
+        hit_id -> DieselUuid,
+        evaluation_id -> DieselUuid,
+        transaction_id -> DieselUuid,
+        rule_id -> Varchar,
+        rule_set_version -> Varchar,
+        score -> Int4,
+        action -> Nullable<Varchar>,
+        shadow -> Bool,
+        created_at -> Timestamptz,
+    }
+}
+
+/// Where the analyst review of a payment sent to review by the fraud rules stands.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum FraudReviewStatus {
+    Open,      // Payment held in RequiresAction, waiting for an analyst
+    Cleared,   // Legitimate, payment resumed
+    Confirmed, // Fraud, payment cancelled
+}
+
+impl FraudReviewStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FraudReviewStatus::Open => "OPEN",
+            FraudReviewStatus::Cleared => "CLEARED",
+            FraudReviewStatus::Confirmed => "CONFIRMED",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<FraudReviewStatus> {
+        match value {
+            "OPEN" => Some(FraudReviewStatus::Open),
+            "CLEARED" => Some(FraudReviewStatus::Cleared),
+            "CONFIRMED" => Some(FraudReviewStatus::Confirmed),
+            _ => None,
+        }
+    }
+}
+
+/// The fraud rules' verdict on one outbound payment.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = fraud_evaluations, primary_key(evaluation_id))]
+pub struct FraudEvaluation {
+    pub evaluation_id: Uuid,
+    pub transaction_id: Uuid,
+    pub user_id: Uuid,
+    pub rule_set_version: String,
+    pub mode: String, // ENFORCE or SHADOW
+    pub score: i32,
+    pub shadow_score: i32,
+    pub decision: String, // Map to fraud::FraudAction
+    pub shadow_decision: String,
+    pub applied_decision: String,
+    pub features: JsonValue, // Feature name -> value
+    pub review_status: Option<String>, // Map to FraudReviewStatus
+    #[serde(skip_serializing)]
+    pub pending_dispatch: Option<JsonValue>, // approvals::PendingDispatch
+    pub reviewed_by: Option<Uuid>,
+    pub review_comment: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = fraud_evaluations)]
+pub struct NewFraudEvaluation<'a> {
+    pub transaction_id: Uuid,
+    pub user_id: Uuid,
+    pub rule_set_version: &'a str,
+    pub mode: &'a str,
+    pub score: i32,
+    pub shadow_score: i32,
+    pub decision: &'a str,
+    pub shadow_decision: &'a str,
+    pub applied_decision: &'a str,
+    pub features: JsonValue,
+    pub review_status: Option<&'a str>,
+    pub pending_dispatch: Option<JsonValue>,
+}
+
+/// One rule that fired in an evaluation.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = fraud_rule_hits, primary_key(hit_id))]
+pub struct FraudRuleHit {
+    pub hit_id: Uuid,
+    pub evaluation_id: Uuid,
+    pub transaction_id: Uuid,
+    pub rule_id: String,
+    pub rule_set_version: String,
+    pub score: i32,
+    pub action: Option<String>,
+    pub shadow: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = fraud_rule_hits)]
+pub struct NewFraudRuleHit<'a> {
+    pub evaluation_id: Uuid,
+    pub transaction_id: Uuid,
+    pub rule_id: &'a str,
+    pub rule_set_version: &'a str,
+    pub score: i32,
+    pub action: Option<&'a str>,
+    pub shadow: bool,
+}
@@ -0,0 +1,114 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/fx_quote.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{Int4, Uuid as DieselUuid, Nullable, Varchar, Numeric as DieselNumeric, Timestamptz}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use rust_decimal::Decimal;
+use bigdecimal::BigDecimal;
+
+table! {
+    core_schema.fx_quotes (quote_id) {
+        quote_id -> DieselUuid,
+        user_id -> DieselUuid,
+        source_wallet_id -> DieselUuid,
+        target_wallet_id -> DieselUuid,
+        from_currency -> Varchar,
+        to_currency -> Varchar,
+        source_amount -> DieselNumeric,
+        mid_rate -> DieselNumeric,
+        spread_bps -> Int4,
+        applied_rate -> DieselNumeric,
+        target_amount -> DieselNumeric,
+        fee_amount -> DieselNumeric,
+        fee_rule_id -> Nullable<Varchar>,
+        rate_source -> Nullable<Varchar>,
+        status -> Varchar,
+        expires_at -> Timestamptz,
+        executed_at -> Nullable<Timestamptz>,
+        debit_transaction_id -> Nullable<DieselUuid>,
+        credit_transaction_id -> Nullable<DieselUuid>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+/// Lifecycle of a locked quote. Only an OPEN quote before its expiry can be executed.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum FxQuoteStatus {
+    Open,
+    Executed, // Both conversion legs booked
+    Expired,  // Not executed in time
+}
+
+impl FxQuoteStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FxQuoteStatus::Open => "OPEN",
+            FxQuoteStatus::Executed => "EXECUTED",
+            FxQuoteStatus::Expired => "EXPIRED",
+        }
+    }
+}
+
+/// A rate locked for one conversion between two wallets of the same user.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = fx_quotes, primary_key(quote_id))]
+pub struct FxQuote {
+    pub quote_id: Uuid,
+    pub user_id: Uuid,
+    pub source_wallet_id: Uuid,
+    pub target_wallet_id: Uuid,
+    pub from_currency: String,
+    pub to_currency: String,
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub source_amount: Decimal, // Debited from the source wallet (plus fee_amount)
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub mid_rate: Decimal,
+    pub spread_bps: i32,
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub applied_rate: Decimal, // Rate the customer gets, spread included
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub target_amount: Decimal, // Credited to the target wallet
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub fee_amount: Decimal, // In from_currency
+    pub fee_rule_id: Option<String>,
+    pub rate_source: Option<String>,
+    pub status: String, // Map to FxQuoteStatus
+    pub expires_at: DateTime<Utc>,
+    pub executed_at: Option<DateTime<Utc>>,
+    pub debit_transaction_id: Option<Uuid>,
+    pub credit_transaction_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = fx_quotes)]
+pub struct NewFxQuote<'a> {
+    pub user_id: Uuid,
+    pub source_wallet_id: Uuid,
+    pub target_wallet_id: Uuid,
+    pub from_currency: &'a str,
+    pub to_currency: &'a str,
+    #[diesel(serialize_as = BigDecimal)]
+    pub source_amount: Decimal,
+    #[diesel(serialize_as = BigDecimal)]
+    pub mid_rate: Decimal,
+    pub spread_bps: i32,
+    #[diesel(serialize_as = BigDecimal)]
+    pub applied_rate: Decimal,
+    #[diesel(serialize_as = BigDecimal)]
+    pub target_amount: Decimal,
+    #[diesel(serialize_as = BigDecimal)]
+    pub fee_amount: Decimal,
+    pub fee_rule_id: Option<&'a str>,
+    pub rate_source: Option<&'a str>,
+    pub status: &'a str,
+    pub expires_at: DateTime<Utc>,
+}
@@ -0,0 +1,84 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/idempotency_key.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{BigInt, Int4, Uuid as DieselUuid, Nullable, Varchar, Jsonb, Timestamptz}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use serde_json::Value as JsonValue;
+
+table! {
+    core_schema.idempotency_keys (key_id) {
+        key_id -> BigInt,
+        idempotency_key -> Varchar,
+        user_id -> Nullable<DieselUuid>,
+        scope -> Varchar,
+        request_fingerprint -> Varchar,
+        status -> Varchar,
+        response_status_code -> Nullable<Int4>,
+        response_body -> Nullable<Jsonb>,
+        transaction_id -> Nullable<DieselUuid>,
+        locked_until -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        expires_at -> Timestamptz,
+    }
+}
+
+/// Lifecycle of an idempotency key record.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum IdempotencyKeyStatus {
+    InProgress, // First request is still executing
+    Completed,  // Response stored, replays return it
+}
+
+impl IdempotencyKeyStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IdempotencyKeyStatus::InProgress => "IN_PROGRESS",
+            IdempotencyKeyStatus::Completed => "COMPLETED",
+        }
+    }
+}
+
+/// Represents a stored idempotency key and (once completed) the response it produced.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = idempotency_keys, primary_key(key_id))]
+pub struct IdempotencyKey {
+    pub key_id: i64,
+    pub idempotency_key: String,
+    pub user_id: Option<Uuid>, // None for unauthenticated endpoints
+    pub scope: String, // Endpoint identifier, e.g. PAYMENT_INITIATE
+    pub request_fingerprint: String, // SHA-256 hex of the request body
+    pub status: String, // Map to IdempotencyKeyStatus
+    pub response_status_code: Option<i32>,
+    pub response_body: Option<JsonValue>,
+    pub transaction_id: Option<Uuid>, // Transaction created by the original request
+    pub locked_until: Option<DateTime<Utc>>, // Lease of the request working on an IN_PROGRESS key
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Represents data needed to claim a new idempotency key.
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = idempotency_keys)]
+pub struct NewIdempotencyKey<'a> {
+    pub idempotency_key: &'a str,
+    pub user_id: Option<Uuid>,
+    pub scope: &'a str,
+    pub request_fingerprint: &'a str,
+    pub status: &'a str,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+    // key_id, created_at, updated_at defaulted by DB
+}
+
+/// Represents the stored outcome written once the original request finishes.
+#[derive(Debug, AsChangeset, Clone)]
+#[diesel(table_name = idempotency_keys)]
+pub struct CompleteIdempotencyKey<'a> {
+    pub status: &'a str,
+    pub response_status_code: Option<i32>,
+    pub response_body: Option<JsonValue>,
+    pub transaction_id: Option<Uuid>,
+}
@@ -0,0 +1,127 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/inbound.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{Uuid as DieselUuid, Nullable, Varchar, Numeric as DieselNumeric, Text, Jsonb, Timestamptz}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use rust_decimal::Decimal;
+use bigdecimal::BigDecimal;
+use serde_json::Value as JsonValue;
+
+table! {
+    core_schema.virtual_accounts (virtual_account_id) {
+        virtual_account_id -> DieselUuid,
+        wallet_id -> DieselUuid,
+        account_number -> Varchar,
+        status -> Varchar,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    core_schema.inbound_suspense (suspense_id) {
+        suspense_id -> DieselUuid,
+        transaction_id -> DieselUuid,
+        rail -> Varchar,
+        amount -> DieselNumeric,
+        currency_code -> Varchar,
+        reason -> Text,
+        notice -> Jsonb,
+        status -> Varchar,
+        resolved_wallet_id -> Nullable<DieselUuid>,
+        resolved_by -> Nullable<DieselUuid>,
+        resolution_note -> Nullable<Text>,
+        resolved_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum VirtualAccountStatus {
+    Active,
+    Closed, // Credits to it go to suspense
+}
+
+impl VirtualAccountStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VirtualAccountStatus::Active => "ACTIVE",
+            VirtualAccountStatus::Closed => "CLOSED",
+        }
+    }
+}
+
+/// Lifecycle of an unmatched inbound payment.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SuspenseStatus {
+    Open,     // Funds held in SUSPENSE awaiting a decision
+    Assigned, // Credited to a wallet by operations
+    Returned, // Sent back to the originator
+}
+
+impl SuspenseStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SuspenseStatus::Open => "OPEN",
+            SuspenseStatus::Assigned => "ASSIGNED",
+            SuspenseStatus::Returned => "RETURNED",
+        }
+    }
+}
+
+/// An account number issued by us that routes inbound ACH/wire credits to a wallet.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = virtual_accounts, primary_key(virtual_account_id))]
+pub struct VirtualAccount {
+    pub virtual_account_id: Uuid,
+    pub wallet_id: Uuid,
+    pub account_number: String,
+    pub status: String, // Map to VirtualAccountStatus
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = virtual_accounts)]
+pub struct NewVirtualAccount<'a> {
+    pub wallet_id: Uuid,
+    pub account_number: &'a str,
+    pub status: &'a str,
+}
+
+/// An inbound payment that could not be credited to a wallet.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = inbound_suspense, primary_key(suspense_id))]
+pub struct InboundSuspenseItem {
+    pub suspense_id: Uuid,
+    pub transaction_id: Uuid,
+    pub rail: String, // Map to payments::inbound::InboundRail
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+    pub currency_code: String,
+    pub reason: String,
+    pub notice: JsonValue,
+    pub status: String, // Map to SuspenseStatus
+    pub resolved_wallet_id: Option<Uuid>,
+    pub resolved_by: Option<Uuid>,
+    pub resolution_note: Option<String>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = inbound_suspense)]
+pub struct NewInboundSuspenseItem<'a> {
+    pub transaction_id: Uuid,
+    pub rail: &'a str,
+    #[diesel(serialize_as = BigDecimal)]
+    pub amount: Decimal,
+    pub currency_code: &'a str,
+    pub reason: &'a str,
+    pub notice: JsonValue,
+    pub status: &'a str,
+}
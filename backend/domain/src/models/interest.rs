@@ -0,0 +1,207 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/interest.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{Date, Int8, Uuid as DieselUuid, Nullable, Varchar, Numeric as DieselNumeric, Text, Jsonb, Timestamptz}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+use rust_decimal::Decimal;
+use bigdecimal::BigDecimal;
+use serde_json::Value as JsonValue;
+
+table! {
+    core_schema.interest_rate_versions (rate_id) {
+        rate_id -> DieselUuid,
+        wallet_type -> Varchar,
+        currency_code -> Varchar,
+        effective_from -> Date,
+        day_count -> Varchar,
+        tiers -> Jsonb,
+        withholding_tax_percent -> DieselNumeric,
+        note -> Nullable<Text>,
+        created_by -> Nullable<DieselUuid>,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    core_schema.interest_wallet_states (wallet_id) {
+        wallet_id -> DieselUuid,
+        accrued_through -> Nullable<Date>,
+        last_line_id -> Int8,
+        last_run_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    core_schema.interest_accruals (accrual_id) {
+        accrual_id -> DieselUuid,
+        wallet_id -> DieselUuid,
+        accrual_date -> Date,
+        balance -> DieselNumeric,
+        annual_rate_percent -> DieselNumeric,
+        day_count -> Varchar,
+        year_fraction -> DieselNumeric,
+        amount -> DieselNumeric,
+        rate_id -> Nullable<DieselUuid>,
+        computed_at -> Timestamptz,
+    }
+}
+
+table! {
+    core_schema.interest_postings (posting_id) {
+        posting_id -> DieselUuid,
+        wallet_id -> DieselUuid,
+        period_start -> Date,
+        period_end -> Date,
+        accrued_to_date -> DieselNumeric,
+        gross_amount -> DieselNumeric,
+        tax_amount -> DieselNumeric,
+        net_amount -> DieselNumeric,
+        currency_code -> Varchar,
+        interest_transaction_id -> Nullable<DieselUuid>,
+        tax_transaction_id -> Nullable<DieselUuid>,
+        created_at -> Timestamptz,
+    }
+}
+
+/// A version of a wallet type's interest terms, in force from `effective_from` until a later version.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = interest_rate_versions, primary_key(rate_id))]
+pub struct InterestRateVersion {
+    pub rate_id: Uuid,
+    pub wallet_type: String, // Map to WalletType
+    pub currency_code: String,
+    pub effective_from: NaiveDate,
+    pub day_count: String, // Map to DayCountConvention
+    pub tiers: JsonValue, // Vec<RateTier>
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub withholding_tax_percent: Decimal,
+    pub note: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = interest_rate_versions)]
+pub struct NewInterestRateVersion<'a> {
+    pub wallet_type: &'a str,
+    pub currency_code: &'a str,
+    pub effective_from: NaiveDate,
+    pub day_count: &'a str,
+    pub tiers: JsonValue,
+    #[diesel(serialize_as = BigDecimal)]
+    pub withholding_tax_percent: Decimal,
+    pub note: Option<&'a str>,
+    pub created_by: Option<Uuid>,
+}
+
+/// How far a wallet's interest has been accrued.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = interest_wallet_states, primary_key(wallet_id))]
+pub struct InterestWalletState {
+    pub wallet_id: Uuid,
+    pub accrued_through: Option<NaiveDate>,
+    pub last_line_id: i64,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, AsChangeset, Clone)]
+#[diesel(table_name = interest_wallet_states)]
+pub struct NewInterestWalletState {
+    pub wallet_id: Uuid,
+    pub accrued_through: Option<NaiveDate>,
+    pub last_line_id: i64,
+    pub last_run_at: Option<DateTime<Utc>>,
+}
+
+/// Interest accrued on one wallet for one day.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = interest_accruals, primary_key(accrual_id))]
+pub struct InterestAccrual {
+    pub accrual_id: Uuid,
+    pub wallet_id: Uuid,
+    pub accrual_date: NaiveDate,
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub balance: Decimal,
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub annual_rate_percent: Decimal,
+    pub day_count: String,
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub year_fraction: Decimal,
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+    pub rate_id: Option<Uuid>,
+    pub computed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, AsChangeset, Clone)]
+#[diesel(table_name = interest_accruals)]
+pub struct NewInterestAccrual<'a> {
+    pub wallet_id: Uuid,
+    pub accrual_date: NaiveDate,
+    #[diesel(serialize_as = BigDecimal)]
+    pub balance: Decimal,
+    #[diesel(serialize_as = BigDecimal)]
+    pub annual_rate_percent: Decimal,
+    pub day_count: &'a str,
+    #[diesel(serialize_as = BigDecimal)]
+    pub year_fraction: Decimal,
+    #[diesel(serialize_as = BigDecimal)]
+    pub amount: Decimal,
+    pub rate_id: Option<Uuid>,
+    pub computed_at: DateTime<Utc>,
+}
+
+/// Interest capitalized into a wallet for a period.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = interest_postings, primary_key(posting_id))]
+pub struct InterestPosting {
+    pub posting_id: Uuid,
+    pub wallet_id: Uuid,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub accrued_to_date: Decimal,
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub gross_amount: Decimal,
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub tax_amount: Decimal,
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub net_amount: Decimal,
+    pub currency_code: String,
+    pub interest_transaction_id: Option<Uuid>,
+    pub tax_transaction_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = interest_postings)]
+pub struct NewInterestPosting<'a> {
+    pub wallet_id: Uuid,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    #[diesel(serialize_as = BigDecimal)]
+    pub accrued_to_date: Decimal,
+    #[diesel(serialize_as = BigDecimal)]
+    pub gross_amount: Decimal,
+    #[diesel(serialize_as = BigDecimal)]
+    pub tax_amount: Decimal,
+    #[diesel(serialize_as = BigDecimal)]
+    pub net_amount: Decimal,
+    pub currency_code: &'a str,
+    pub interest_transaction_id: Option<Uuid>,
+    pub tax_transaction_id: Option<Uuid>,
+}
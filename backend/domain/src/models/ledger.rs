@@ -0,0 +1,186 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/ledger.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{BigInt, Uuid as DieselUuid, Nullable, Varchar, Numeric as DieselNumeric, Text, Timestamptz}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use rust_decimal::Decimal;
+use bigdecimal::BigDecimal;
+
+table! {
+    core_schema.ledger_accounts (account_id) {
+        account_id -> DieselUuid,
+        account_code -> Varchar,
+        category -> Varchar,
+        normal_balance -> Varchar,
+        currency_code -> Varchar,
+        wallet_id -> Nullable<DieselUuid>,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    core_schema.journal_entries (entry_id) {
+        entry_id -> DieselUuid,
+        transaction_id -> Nullable<DieselUuid>,
+        entry_type -> Varchar,
+        reference -> Varchar,
+        description -> Nullable<Text>,
+        posted_at -> Timestamptz,
+    }
+}
+
+table! {
+    core_schema.journal_lines (line_id) {
+        line_id -> BigInt,
+        entry_id -> DieselUuid,
+        account_id -> DieselUuid,
+        direction -> Varchar,
+        amount -> DieselNumeric,
+        currency_code -> Varchar,
+        posted_at -> Timestamptz,
+    }
+}
+
+/// Side of a journal line.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum EntryDirection {
+    Debit,
+    Credit,
+}
+
+impl EntryDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntryDirection::Debit => "DEBIT",
+            EntryDirection::Credit => "CREDIT",
+        }
+    }
+
+    pub fn opposite(&self) -> EntryDirection {
+        match self {
+            EntryDirection::Debit => EntryDirection::Credit,
+            EntryDirection::Credit => EntryDirection::Debit,
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<EntryDirection> {
+        match value {
+            "DEBIT" => Some(EntryDirection::Debit),
+            "CREDIT" => Some(EntryDirection::Credit),
+            _ => None,
+        }
+    }
+}
+
+/// Kind of ledger account. Customer wallets are liabilities of the institution (credit-normal).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum LedgerAccountCategory {
+    CustomerWallet, // One per wallet
+    Suspense,       // Funds in flight (held for an outbound payment) or received but not yet applied to a wallet
+    Nostro,         // Our account at the bank / custodian for the currency
+    FeeIncome,      // Fees earned
+    FxPosition,     // Currency position taken by conversions
+    OpeningBalance, // Counterpart of balances migrated from wallets.balance
+    InterestExpense, // Interest paid to customers
+    WithholdingTax, // Tax withheld from customer interest, owed to the tax authority
+}
+
+impl LedgerAccountCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LedgerAccountCategory::CustomerWallet => "CUSTOMER_WALLET",
+            LedgerAccountCategory::Suspense => "SUSPENSE",
+            LedgerAccountCategory::Nostro => "NOSTRO",
+            LedgerAccountCategory::FeeIncome => "FEE_INCOME",
+            LedgerAccountCategory::FxPosition => "FX_POSITION",
+            LedgerAccountCategory::OpeningBalance => "OPENING_BALANCE",
+            LedgerAccountCategory::InterestExpense => "INTEREST_EXPENSE",
+            LedgerAccountCategory::WithholdingTax => "WITHHOLDING_TAX",
+        }
+    }
+
+    /// Side on which the account's balance grows.
+    pub fn normal_balance(&self) -> EntryDirection {
+        match self {
+            LedgerAccountCategory::Suspense
+            | LedgerAccountCategory::Nostro
+            | LedgerAccountCategory::InterestExpense => EntryDirection::Debit,
+            LedgerAccountCategory::CustomerWallet
+            | LedgerAccountCategory::FeeIncome
+            | LedgerAccountCategory::FxPosition
+            | LedgerAccountCategory::OpeningBalance
+            | LedgerAccountCategory::WithholdingTax => EntryDirection::Credit,
+        }
+    }
+}
+
+/// Represents an account in the general ledger.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = ledger_accounts, primary_key(account_id))]
+pub struct LedgerAccount {
+    pub account_id: Uuid,
+    pub account_code: String, // e.g. WALLET:<wallet_id>, NOSTRO:USD
+    pub category: String, // Map to LedgerAccountCategory
+    pub normal_balance: String, // DEBIT or CREDIT
+    pub currency_code: String,
+    pub wallet_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = ledger_accounts)]
+pub struct NewLedgerAccount<'a> {
+    pub account_code: &'a str,
+    pub category: &'a str,
+    pub normal_balance: &'a str,
+    pub currency_code: &'a str,
+    pub wallet_id: Option<Uuid>,
+}
+
+/// Header of a balanced set of journal lines.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = journal_entries, primary_key(entry_id))]
+pub struct JournalEntry {
+    pub entry_id: Uuid,
+    pub transaction_id: Option<Uuid>,
+    pub entry_type: String, // HOLD, POST, RELEASE, REVERSE, FEE, FX, ADJUSTMENT...
+    pub reference: String, // Unique, makes posting idempotent
+    pub description: Option<String>,
+    pub posted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = journal_entries)]
+pub struct NewJournalEntry<'a> {
+    pub transaction_id: Option<Uuid>,
+    pub entry_type: &'a str,
+    pub reference: &'a str,
+    pub description: Option<&'a str>,
+}
+
+/// One debit or credit against a ledger account.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = journal_lines, primary_key(line_id))]
+pub struct JournalLine {
+    pub line_id: i64,
+    pub entry_id: Uuid,
+    pub account_id: Uuid,
+    pub direction: String, // DEBIT or CREDIT
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal, // Always positive
+    pub currency_code: String,
+    pub posted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = journal_lines)]
+pub struct NewJournalLine<'a> {
+    pub entry_id: Uuid,
+    pub account_id: Uuid,
+    pub direction: &'a str,
+    #[diesel(serialize_as = BigDecimal)]
+    pub amount: Decimal,
+    pub currency_code: &'a str,
+}
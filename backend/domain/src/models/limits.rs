@@ -0,0 +1,185 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/limits.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{Int4, Uuid as DieselUuid, Nullable, Varchar, Numeric as DieselNumeric, Text, Timestamptz}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use rust_decimal::Decimal;
+use bigdecimal::BigDecimal;
+
+table! {
+    core_schema.limit_counters (counter_id) {
+        counter_id -> DieselUuid,
+        rule_id -> Varchar,
+        subject_type -> Varchar,
+        subject_id -> DieselUuid,
+        currency_code -> Varchar,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    core_schema.limit_usages (usage_id) {
+        usage_id -> DieselUuid,
+        counter_id -> DieselUuid,
+        transaction_id -> DieselUuid,
+        amount -> DieselNumeric,
+        released_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    core_schema.limit_overrides (override_id) {
+        override_id -> DieselUuid,
+        rule_id -> Varchar,
+        subject_type -> Varchar,
+        subject_id -> DieselUuid,
+        max_amount -> Nullable<DieselNumeric>,
+        max_count -> Nullable<Int4>,
+        valid_from -> Timestamptz,
+        valid_until -> Timestamptz,
+        reason -> Text,
+        status -> Varchar,
+        requested_by -> DieselUuid,
+        decided_by -> Nullable<DieselUuid>,
+        decision_comment -> Nullable<Text>,
+        decided_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+/// Whose usage a limit counts: the customer across all wallets, or a single wallet.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LimitSubjectType {
+    #[default]
+    User,
+    Wallet,
+}
+
+impl LimitSubjectType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LimitSubjectType::User => "USER",
+            LimitSubjectType::Wallet => "WALLET",
+        }
+    }
+}
+
+/// Where a temporary limit increase stands.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum LimitOverrideStatus {
+    Pending,  // Waiting for an admin
+    Approved, // Applies between valid_from and valid_until
+    Rejected,
+    Revoked, // Withdrawn by an admin after approval
+}
+
+impl LimitOverrideStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LimitOverrideStatus::Pending => "PENDING",
+            LimitOverrideStatus::Approved => "APPROVED",
+            LimitOverrideStatus::Rejected => "REJECTED",
+            LimitOverrideStatus::Revoked => "REVOKED",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<LimitOverrideStatus> {
+        match value {
+            "PENDING" => Some(LimitOverrideStatus::Pending),
+            "APPROVED" => Some(LimitOverrideStatus::Approved),
+            "REJECTED" => Some(LimitOverrideStatus::Rejected),
+            "REVOKED" => Some(LimitOverrideStatus::Revoked),
+            _ => None,
+        }
+    }
+}
+
+/// Lock row of one limit rule for one subject and currency; usage is summed from its `LimitUsage`s.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = limit_counters, primary_key(counter_id))]
+pub struct LimitCounter {
+    pub counter_id: Uuid,
+    pub rule_id: String,
+    pub subject_type: String, // Map to LimitSubjectType
+    pub subject_id: Uuid,
+    pub currency_code: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = limit_counters)]
+pub struct NewLimitCounter<'a> {
+    pub rule_id: &'a str,
+    pub subject_type: &'a str,
+    pub subject_id: Uuid,
+    pub currency_code: &'a str,
+}
+
+/// What one payment consumed from a counter.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = limit_usages, primary_key(usage_id))]
+pub struct LimitUsage {
+    pub usage_id: Uuid,
+    pub counter_id: Uuid,
+    pub transaction_id: Uuid,
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+    pub released_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = limit_usages)]
+pub struct NewLimitUsage {
+    pub counter_id: Uuid,
+    pub transaction_id: Uuid,
+    #[diesel(serialize_as = BigDecimal)]
+    pub amount: Decimal,
+    pub created_at: DateTime<Utc>, // The payment's time, from which the rolling windows count it
+}
+
+/// A temporary increase of one limit rule for one subject.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = limit_overrides, primary_key(override_id))]
+pub struct LimitOverride {
+    pub override_id: Uuid,
+    pub rule_id: String,
+    pub subject_type: String, // Map to LimitSubjectType
+    pub subject_id: Uuid,
+    #[diesel(deserialize_as = Option<BigDecimal>)]
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub max_amount: Option<Decimal>,
+    pub max_count: Option<i32>,
+    pub valid_from: DateTime<Utc>,
+    pub valid_until: DateTime<Utc>,
+    pub reason: String,
+    pub status: String, // Map to LimitOverrideStatus
+    pub requested_by: Uuid,
+    pub decided_by: Option<Uuid>,
+    pub decision_comment: Option<String>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = limit_overrides)]
+pub struct NewLimitOverride<'a> {
+    pub rule_id: &'a str,
+    pub subject_type: &'a str,
+    pub subject_id: Uuid,
+    #[diesel(serialize_as = Option<BigDecimal>)]
+    pub max_amount: Option<Decimal>,
+    pub max_count: Option<i32>,
+    pub valid_from: DateTime<Utc>,
+    pub valid_until: DateTime<Utc>,
+    pub reason: &'a str,
+    pub requested_by: Uuid,
+}
@@ -0,0 +1,77 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/login_event.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{Int4, Int8, Float8, Uuid as DieselUuid, Nullable, Varchar, Text, Timestamptz, Bool}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+table! {
+    core_schema.login_events (login_id) {
+        login_id -> DieselUuid,
+        user_id -> DieselUuid,
+        ip_address -> Nullable<Varchar>,
+        user_agent -> Nullable<Text>,
+        device_id -> Nullable<Varchar>,
+        country_code -> Nullable<Varchar>,
+        latitude -> Nullable<Float8>,
+        longitude -> Nullable<Float8>,
+        asn -> Nullable<Int8>,
+        as_org -> Nullable<Varchar>,
+        tor -> Bool,
+        vpn -> Bool,
+        hosting -> Bool,
+        ip_risk -> Int4,
+        previous_login_id -> Nullable<DieselUuid>,
+        distance_km -> Nullable<Float8>,
+        travel_speed_kmh -> Nullable<Float8>,
+        impossible_travel -> Bool,
+        created_at -> Timestamptz,
+    }
+}
+
+/// A successful login, geolocated from its IP address.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = login_events, primary_key(login_id))]
+pub struct LoginEvent {
+    pub login_id: Uuid,
+    pub user_id: Uuid,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub device_id: Option<String>,
+    pub country_code: Option<String>, // From the GeoIP database
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub asn: Option<i64>,
+    pub as_org: Option<String>,
+    pub tor: bool,
+    pub vpn: bool,
+    pub hosting: bool,
+    pub ip_risk: i32,
+    pub previous_login_id: Option<Uuid>, // Last located login this one was compared with
+    pub distance_km: Option<f64>,
+    pub travel_speed_kmh: Option<f64>,
+    pub impossible_travel: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = login_events)]
+pub struct NewLoginEvent<'a> {
+    pub user_id: Uuid,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<&'a str>,
+    pub device_id: Option<&'a str>,
+    pub country_code: Option<&'a str>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub asn: Option<i64>,
+    pub as_org: Option<&'a str>,
+    pub tor: bool,
+    pub vpn: bool,
+    pub hosting: bool,
+    pub ip_risk: i32,
+    pub previous_login_id: Option<Uuid>,
+    pub distance_km: Option<f64>,
+    pub travel_speed_kmh: Option<f64>,
+    pub impossible_travel: bool,
+}
@@ -3,12 +3,81 @@ pub mod user;
 pub mod wallet; // Renamed from account
 pub mod transaction;
 pub mod audit_log; // Added audit log model
+pub mod idempotency_key; // Stored Idempotency-Key requests/responses
+pub mod payment_outbox; // Transactional outbox + saga step log for outbound payments
+pub mod ledger; // Double-entry ledger accounts, journal entries and lines
+pub mod wallet_hold; // Reservations against wallet balances (available vs ledger balance)
+pub mod fx_quote; // Locked FX quotes for wallet-to-wallet conversions
+pub mod payment_schedule; // Standing orders and their occurrences
+pub mod payout_batch; // Bulk payout files and their rows
+pub mod payment_approval; // Maker-checker approvals of outbound payments
+pub mod beneficiary; // Saved payees (hashed identifiers, verification, cooling-off)
+pub mod inbound; // Virtual account numbers and the suspense queue for unmatched inbound payments
+pub mod webhook_event; // Verified provider webhooks (raw payloads, seen-event store)
+pub mod reconciliation; // Bank statement lines, reconciliation matches and breaks
+pub mod eod_position; // Signed end-of-day nostro position snapshots
+pub mod interest; // Interest rate versions, daily accruals and capitalization postings
+pub mod limits; // Transaction limit counters, consumed usage and temporary increases
+pub mod sanctions; // Payments stopped by sanctions screening, pending compliance review
+pub mod aml; // AML monitoring alerts, investigation cases and the streaming monitor position
+pub mod ctr; // Currency Transaction Reports, their batch files and exempt persons
+pub mod travel_rule; // FATF Travel Rule data of crypto withdrawals and its exchange with counterparty VASPs
+pub mod fraud; // Fraud rule evaluations of outbound payments, the rules that fired and analyst reviews
+pub mod login_event; // Geolocated logins, checked for impossible travel
 
 // Re-export main models and enums for easier access
 pub use user::{User, NewUser, UpdateUser};
 pub use wallet::{Wallet, NewWallet, UpdateWallet, WalletType, WalletStatus};
 pub use transaction::{
     Transaction, NewTransaction, UpdateTransaction, TransactionType, TransactionStatus,
-    PaymentDetails, CardDetails, AchDetails, WireDetails, CheckDetails, CryptoDetails
+    PaymentDetails, CardDetails, AchDetails, WireDetails, CheckDetails, CryptoDetails,
+    TransactionStateTransition, NewTransactionStateTransition,
 };
-pub use audit_log::{AuditLog, NewAuditLog, AuditOutcome, AuditTargetType};
\ No newline at end of file
+pub use audit_log::{AuditLog, NewAuditLog, AuditOutcome, AuditTargetType};
+pub use idempotency_key::{IdempotencyKey, NewIdempotencyKey, CompleteIdempotencyKey, IdempotencyKeyStatus};
+pub use payment_outbox::{
+    PaymentOutbox, NewPaymentOutbox, PaymentSagaStep, NewPaymentSagaStep, OutboxOperation, OutboxStatus
+};
+pub use ledger::{
+    LedgerAccount, NewLedgerAccount, LedgerAccountCategory, JournalEntry, NewJournalEntry, JournalLine, NewJournalLine,
+    EntryDirection
+};
+pub use wallet_hold::{WalletHold, NewWalletHold, HoldType, HoldStatus};
+pub use fx_quote::{FxQuote, NewFxQuote, FxQuoteStatus};
+pub use payment_schedule::{
+    PaymentSchedule, NewPaymentSchedule, PaymentScheduleRun, NewPaymentScheduleRun, ScheduleStatus, ScheduleRunStatus
+};
+pub use payout_batch::{
+    PayoutBatch, NewPayoutBatch, PayoutBatchItem, NewPayoutBatchItem, PayoutBatchStatus, AchFileStatus, PayoutItemStatus
+};
+pub use payment_approval::{
+    PaymentApproval, NewPaymentApproval, PaymentApprovalDecision, NewPaymentApprovalDecision, ApprovalStatus, ApprovalDecision
+};
+pub use beneficiary::{Beneficiary, NewBeneficiary, BeneficiaryType, BeneficiaryStatus};
+pub use inbound::{
+    VirtualAccount, NewVirtualAccount, VirtualAccountStatus, InboundSuspenseItem, NewInboundSuspenseItem, SuspenseStatus
+};
+pub use webhook_event::{WebhookEvent, NewWebhookEvent, WebhookEventStatus};
+pub use reconciliation::{
+    BankStatementLine, NewBankStatementLine, ReconciliationMatch, NewReconciliationMatch, ReconciliationMatchTransaction,
+    ReconciliationException, NewReconciliationException, StatementLineStatus, MatchKind, MatchRule, ExceptionType,
+    ExceptionStatus, ExceptionResolution
+};
+pub use eod_position::{EodPositionSnapshot, NewEodPositionSnapshot};
+pub use interest::{
+    InterestRateVersion, NewInterestRateVersion, InterestWalletState, NewInterestWalletState, InterestAccrual,
+    NewInterestAccrual, InterestPosting, NewInterestPosting
+};
+pub use limits::{
+    LimitCounter, NewLimitCounter, LimitUsage, NewLimitUsage, LimitOverride, NewLimitOverride, LimitOverrideStatus,
+    LimitSubjectType
+};
+pub use sanctions::{SanctionsCase, NewSanctionsCase, SanctionsCaseStatus};
+pub use aml::{AmlCase, NewAmlCase, AmlAlert, NewAmlAlert, AmlMonitorState, AmlCaseStatus};
+pub use ctr::{
+    CtrReport, NewCtrReport, CtrReportStatus, CtrBatch, NewCtrBatch, CtrBatchStatus, CtrExemption, NewCtrExemption,
+    CtrExemptionType
+};
+pub use travel_rule::{TravelRuleTransfer, NewTravelRuleTransfer, TravelRuleStatus};
+pub use fraud::{FraudEvaluation, NewFraudEvaluation, FraudRuleHit, NewFraudRuleHit, FraudReviewStatus};
+pub use login_event::{LoginEvent, NewLoginEvent};
@@ -0,0 +1,129 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/payment_approval.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{Int4, Uuid as DieselUuid, Nullable, Varchar, Text, Jsonb, Timestamptz}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use serde_json::Value as JsonValue;
+
+table! {
+    core_schema.payment_approvals (approval_id) {
+        approval_id -> DieselUuid,
+        transaction_id -> DieselUuid,
+        initiator_user_id -> DieselUuid,
+        status -> Varchar,
+        required_approvals -> Int4,
+        approval_count -> Int4,
+        approver_roles -> Jsonb,
+        policy_ids -> Jsonb,
+        pending_dispatch -> Jsonb,
+        expires_at -> Timestamptz,
+        decided_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    core_schema.payment_approval_decisions (decision_id) {
+        decision_id -> DieselUuid,
+        approval_id -> DieselUuid,
+        approver_user_id -> DieselUuid,
+        approver_role -> Varchar,
+        decision -> Varchar,
+        comment -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+/// Where a payment stands in the maker-checker workflow.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalStatus {
+    Pending,  // Waiting for approvers, transaction in RequiresAction
+    Approved, // Enough approvals, payment released to its rail
+    Rejected, // An approver said no, transaction cancelled
+    Expired,  // Window lapsed without enough approvals
+}
+
+impl ApprovalStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalStatus::Pending => "PENDING",
+            ApprovalStatus::Approved => "APPROVED",
+            ApprovalStatus::Rejected => "REJECTED",
+            ApprovalStatus::Expired => "EXPIRED",
+        }
+    }
+}
+
+/// An approver's decision.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalDecision {
+    Approve,
+    Reject,
+}
+
+impl ApprovalDecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalDecision::Approve => "APPROVE",
+            ApprovalDecision::Reject => "REJECT",
+        }
+    }
+}
+
+/// Approval requirement attached to one outbound payment.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = payment_approvals, primary_key(approval_id))]
+pub struct PaymentApproval {
+    pub approval_id: Uuid,
+    pub transaction_id: Uuid,
+    pub initiator_user_id: Uuid,
+    pub status: String, // Map to ApprovalStatus
+    pub required_approvals: i32,
+    pub approval_count: i32,
+    pub approver_roles: JsonValue, // Array of role names
+    pub policy_ids: JsonValue, // Array of matched policy ids
+    #[serde(skip_serializing)] // Beneficiary account details stay out of API responses
+    pub pending_dispatch: JsonValue, // approvals::PendingDispatch
+    pub expires_at: DateTime<Utc>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = payment_approvals)]
+pub struct NewPaymentApproval<'a> {
+    pub transaction_id: Uuid,
+    pub initiator_user_id: Uuid,
+    pub status: &'a str,
+    pub required_approvals: i32,
+    pub approver_roles: JsonValue,
+    pub policy_ids: JsonValue,
+    pub pending_dispatch: JsonValue,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// One approver's decision on a payment.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = payment_approval_decisions, primary_key(decision_id))]
+pub struct PaymentApprovalDecision {
+    pub decision_id: Uuid,
+    pub approval_id: Uuid,
+    pub approver_user_id: Uuid,
+    pub approver_role: String,
+    pub decision: String, // Map to ApprovalDecision
+    pub comment: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = payment_approval_decisions)]
+pub struct NewPaymentApprovalDecision<'a> {
+    pub approval_id: Uuid,
+    pub approver_user_id: Uuid,
+    pub approver_role: &'a str,
+    pub decision: &'a str,
+    pub comment: Option<&'a str>,
+}
@@ -0,0 +1,140 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/payment_outbox.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{BigInt, Int4, Uuid as DieselUuid, Nullable, Varchar, Text, Jsonb, Timestamptz}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use serde_json::Value as JsonValue;
+
+table! {
+    core_schema.payment_outbox (outbox_id) {
+        outbox_id -> DieselUuid,
+        transaction_id -> DieselUuid,
+        operation -> Varchar,
+        payload -> Jsonb,
+        status -> Varchar,
+        attempts -> Int4,
+        max_attempts -> Int4,
+        next_attempt_at -> Timestamptz,
+        locked_until -> Nullable<Timestamptz>,
+        dispatch_key -> Varchar,
+        external_ref -> Nullable<Varchar>,
+        last_error -> Nullable<Text>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    core_schema.payment_saga_steps (step_id) {
+        step_id -> BigInt,
+        outbox_id -> DieselUuid,
+        step -> Varchar,
+        outcome -> Varchar,
+        details -> Nullable<Jsonb>,
+        created_at -> Timestamptz,
+    }
+}
+
+/// External rail an outbox entry is dispatched to.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OutboxOperation {
+    AchCredit,
+    WireOutbound,
+    CryptoSend,
+}
+
+impl OutboxOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutboxOperation::AchCredit => "ACH_CREDIT",
+            OutboxOperation::WireOutbound => "WIRE_OUTBOUND",
+            OutboxOperation::CryptoSend => "CRYPTO_SEND",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<OutboxOperation> {
+        match value {
+            "ACH_CREDIT" => Some(OutboxOperation::AchCredit),
+            "WIRE_OUTBOUND" => Some(OutboxOperation::WireOutbound),
+            "CRYPTO_SEND" => Some(OutboxOperation::CryptoSend),
+            _ => None,
+        }
+    }
+}
+
+/// Lifecycle of an outbox entry.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OutboxStatus {
+    Pending,        // Hold committed, waiting for (re)dispatch
+    Dispatching,    // Leased by a worker; an expired lease means the worker died mid-call
+    Submitted,      // Rail accepted the payment
+    Compensated,    // Dispatch failed, hold released
+    RequiresReview, // Rail outcome unknown after all retries, not safe to reverse automatically
+}
+
+impl OutboxStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutboxStatus::Pending => "PENDING",
+            OutboxStatus::Dispatching => "DISPATCHING",
+            OutboxStatus::Submitted => "SUBMITTED",
+            OutboxStatus::Compensated => "COMPENSATED",
+            OutboxStatus::RequiresReview => "REQUIRES_REVIEW",
+        }
+    }
+}
+
+/// Represents a queued outbound payment dispatch.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = payment_outbox, primary_key(outbox_id))]
+pub struct PaymentOutbox {
+    pub outbox_id: Uuid,
+    pub transaction_id: Uuid, // Transaction whose hold this saga owns
+    pub operation: String, // Map to OutboxOperation
+    pub payload: JsonValue, // Dispatcher input (destination details etc.)
+    pub status: String, // Map to OutboxStatus
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub dispatch_key: String, // Same key on every attempt so the rail can de-duplicate
+    pub external_ref: Option<String>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Represents data needed to enqueue a dispatch.
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = payment_outbox)]
+pub struct NewPaymentOutbox<'a> {
+    pub transaction_id: Uuid,
+    pub operation: &'a str,
+    pub payload: JsonValue,
+    pub status: &'a str,
+    pub max_attempts: i32,
+    pub dispatch_key: &'a str,
+    // outbox_id, attempts, next_attempt_at, timestamps defaulted by DB
+}
+
+/// Represents one recorded saga step.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = payment_saga_steps, primary_key(step_id))]
+pub struct PaymentSagaStep {
+    pub step_id: i64,
+    pub outbox_id: Uuid,
+    pub step: String, // HOLD, DISPATCH, RETRY_SCHEDULED, SUBMITTED, COMPENSATE, REVIEW
+    pub outcome: String, // SUCCESS, FAILURE
+    pub details: Option<JsonValue>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = payment_saga_steps)]
+pub struct NewPaymentSagaStep<'a> {
+    pub outbox_id: Uuid,
+    pub step: &'a str,
+    pub outcome: &'a str,
+    pub details: Option<JsonValue>,
+}
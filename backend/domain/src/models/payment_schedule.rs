@@ -0,0 +1,181 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/payment_schedule.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{Date, Int4, Uuid as DieselUuid, Nullable, Varchar, Numeric as DieselNumeric, Text, Jsonb, Timestamptz}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+use rust_decimal::Decimal;
+use bigdecimal::BigDecimal;
+use serde_json::Value as JsonValue;
+
+table! {
+    core_schema.payment_schedules (schedule_id) {
+        schedule_id -> DieselUuid,
+        user_id -> DieselUuid,
+        source_wallet_id -> DieselUuid,
+        payment_type -> Text,
+        amount -> DieselNumeric,
+        currency_code -> Varchar,
+        charge_bearer -> Varchar,
+        payment_details -> Jsonb,
+        description -> Nullable<Text>,
+        recurrence -> Jsonb,
+        start_date -> Date,
+        end_date -> Nullable<Date>,
+        max_occurrences -> Nullable<Int4>,
+        business_day_convention -> Varchar,
+        missed_run_policy -> Varchar,
+        status -> Varchar,
+        next_occurrence -> Nullable<Date>,
+        next_run_date -> Nullable<Date>,
+        occurrences_count -> Int4,
+        last_run_at -> Nullable<Timestamptz>,
+        last_error -> Nullable<Text>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    core_schema.payment_schedule_runs (run_id) {
+        run_id -> DieselUuid,
+        schedule_id -> DieselUuid,
+        occurrence_date -> Date,
+        run_date -> Date,
+        status -> Varchar,
+        transaction_id -> Nullable<DieselUuid>,
+        error_message -> Nullable<Text>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+/// Lifecycle of a standing order.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleStatus {
+    Active,
+    Paused,    // Occurrences falling due while paused are skipped
+    Cancelled, // By the customer, final
+    Completed, // End date or occurrence limit reached
+}
+
+impl ScheduleStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScheduleStatus::Active => "ACTIVE",
+            ScheduleStatus::Paused => "PAUSED",
+            ScheduleStatus::Cancelled => "CANCELLED",
+            ScheduleStatus::Completed => "COMPLETED",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<ScheduleStatus> {
+        match value {
+            "ACTIVE" => Some(ScheduleStatus::Active),
+            "PAUSED" => Some(ScheduleStatus::Paused),
+            "CANCELLED" => Some(ScheduleStatus::Cancelled),
+            "COMPLETED" => Some(ScheduleStatus::Completed),
+            _ => None,
+        }
+    }
+}
+
+/// Outcome of one occurrence.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleRunStatus {
+    Pending,   // Claimed, payment being submitted
+    Submitted, // Payment created (see transaction_id)
+    Failed,    // Payment rejected (insufficient funds, validation...)
+    Skipped,   // Skipped by the customer or missed while the scheduler was down
+}
+
+impl ScheduleRunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScheduleRunStatus::Pending => "PENDING",
+            ScheduleRunStatus::Submitted => "SUBMITTED",
+            ScheduleRunStatus::Failed => "FAILED",
+            ScheduleRunStatus::Skipped => "SKIPPED",
+        }
+    }
+}
+
+/// A recurring (or single future-dated) payment.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = payment_schedules, primary_key(schedule_id))]
+pub struct PaymentSchedule {
+    pub schedule_id: Uuid,
+    pub user_id: Uuid,
+    pub source_wallet_id: Uuid,
+    pub payment_type: String, // Map to TransactionType
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+    pub currency_code: String,
+    pub charge_bearer: String, // OUR, SHA, BEN
+    pub payment_details: JsonValue, // ScheduledPaymentDetails
+    pub description: Option<String>,
+    pub recurrence: JsonValue, // RecurrenceRule
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+    pub max_occurrences: Option<i32>,
+    pub business_day_convention: String, // Map to BusinessDayConvention
+    pub missed_run_policy: String, // Map to MissedRunPolicy
+    pub status: String, // Map to ScheduleStatus
+    pub next_occurrence: Option<NaiveDate>,
+    pub next_run_date: Option<NaiveDate>,
+    pub occurrences_count: i32,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = payment_schedules)]
+pub struct NewPaymentSchedule<'a> {
+    pub user_id: Uuid,
+    pub source_wallet_id: Uuid,
+    pub payment_type: &'a str,
+    #[diesel(serialize_as = BigDecimal)]
+    pub amount: Decimal,
+    pub currency_code: &'a str,
+    pub charge_bearer: &'a str,
+    pub payment_details: JsonValue,
+    pub description: Option<&'a str>,
+    pub recurrence: JsonValue,
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+    pub max_occurrences: Option<i32>,
+    pub business_day_convention: &'a str,
+    pub missed_run_policy: &'a str,
+    pub status: &'a str,
+    pub next_occurrence: Option<NaiveDate>,
+    pub next_run_date: Option<NaiveDate>,
+}
+
+/// One occurrence of a schedule and what happened to it.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = payment_schedule_runs, primary_key(run_id))]
+pub struct PaymentScheduleRun {
+    pub run_id: Uuid,
+    pub schedule_id: Uuid,
+    pub occurrence_date: NaiveDate,
+    pub run_date: NaiveDate,
+    pub status: String, // Map to ScheduleRunStatus
+    pub transaction_id: Option<Uuid>,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = payment_schedule_runs)]
+pub struct NewPaymentScheduleRun<'a> {
+    pub schedule_id: Uuid,
+    pub occurrence_date: NaiveDate,
+    pub run_date: NaiveDate,
+    pub status: &'a str,
+    pub transaction_id: Option<Uuid>,
+    pub error_message: Option<&'a str>,
+}
@@ -0,0 +1,203 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/payout_batch.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{Int4, Uuid as DieselUuid, Nullable, Varchar, Numeric as DieselNumeric, Text, Jsonb, Timestamptz}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use rust_decimal::Decimal;
+use bigdecimal::BigDecimal;
+use serde_json::Value as JsonValue;
+
+table! {
+    core_schema.payout_batches (batch_id) {
+        batch_id -> DieselUuid,
+        user_id -> DieselUuid,
+        source_wallet_id -> DieselUuid,
+        currency_code -> Varchar,
+        file_format -> Varchar,
+        file_name -> Nullable<Text>,
+        status -> Varchar,
+        total_rows -> Int4,
+        accepted_count -> Int4,
+        rejected_count -> Int4,
+        total_amount -> DieselNumeric,
+        total_fees -> DieselNumeric,
+        settled_count -> Int4,
+        failed_count -> Int4,
+        returned_count -> Int4,
+        ach_status -> Varchar,
+        ach_file_ref -> Nullable<Text>,
+        ach_attempts -> Int4,
+        ach_next_attempt_at -> Nullable<Timestamptz>,
+        ach_locked_until -> Nullable<Timestamptz>,
+        last_error -> Nullable<Text>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    core_schema.payout_batch_items (item_id) {
+        item_id -> DieselUuid,
+        batch_id -> DieselUuid,
+        row_number -> Int4,
+        payment_type -> Nullable<Text>,
+        amount -> Nullable<Text>,
+        beneficiary_name -> Nullable<Text>,
+        reference -> Nullable<Text>,
+        status -> Varchar,
+        errors -> Jsonb,
+        payment_details -> Nullable<Jsonb>,
+        transaction_id -> Nullable<DieselUuid>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+/// Overall state of a batch, derived from its items' transactions.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PayoutBatchStatus {
+    Processing,          // Some items not final yet
+    Completed,           // Every accepted item settled
+    CompletedWithErrors, // Every item final, some failed or were returned
+    Rejected,            // No row passed validation, nothing was reserved
+}
+
+impl PayoutBatchStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PayoutBatchStatus::Processing => "PROCESSING",
+            PayoutBatchStatus::Completed => "COMPLETED",
+            PayoutBatchStatus::CompletedWithErrors => "COMPLETED_WITH_ERRORS",
+            PayoutBatchStatus::Rejected => "REJECTED",
+        }
+    }
+}
+
+/// Progress of the batch's single NACHA file (NONE when the batch has no ACH rows).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum AchFileStatus {
+    None,
+    Pending,        // Waiting for the dispatcher
+    Dispatching,    // Leased by a worker
+    Submitted,      // File accepted by the ODFI
+    Failed,         // Rejected; every ACH item was failed and released
+    RequiresReview, // Outcome unknown after repeated errors
+}
+
+impl AchFileStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AchFileStatus::None => "NONE",
+            AchFileStatus::Pending => "PENDING",
+            AchFileStatus::Dispatching => "DISPATCHING",
+            AchFileStatus::Submitted => "SUBMITTED",
+            AchFileStatus::Failed => "FAILED",
+            AchFileStatus::RequiresReview => "REQUIRES_REVIEW",
+        }
+    }
+}
+
+/// Validation outcome of one row.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PayoutItemStatus {
+    Accepted, // Became a transaction (see transaction_id)
+    Rejected, // See errors
+}
+
+impl PayoutItemStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PayoutItemStatus::Accepted => "ACCEPTED",
+            PayoutItemStatus::Rejected => "REJECTED",
+        }
+    }
+}
+
+/// An uploaded payout file.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = payout_batches, primary_key(batch_id))]
+pub struct PayoutBatch {
+    pub batch_id: Uuid,
+    pub user_id: Uuid,
+    pub source_wallet_id: Uuid,
+    pub currency_code: String,
+    pub file_format: String, // CSV, JSON
+    pub file_name: Option<String>,
+    pub status: String, // Map to PayoutBatchStatus
+    pub total_rows: i32,
+    pub accepted_count: i32,
+    pub rejected_count: i32,
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub total_amount: Decimal,
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub total_fees: Decimal,
+    pub settled_count: i32,
+    pub failed_count: i32,
+    pub returned_count: i32,
+    pub ach_status: String, // Map to AchFileStatus
+    pub ach_file_ref: Option<String>,
+    pub ach_attempts: i32,
+    pub ach_next_attempt_at: Option<DateTime<Utc>>,
+    pub ach_locked_until: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = payout_batches)]
+pub struct NewPayoutBatch<'a> {
+    pub user_id: Uuid,
+    pub source_wallet_id: Uuid,
+    pub currency_code: &'a str,
+    pub file_format: &'a str,
+    pub file_name: Option<&'a str>,
+    pub status: &'a str,
+    pub total_rows: i32,
+    pub accepted_count: i32,
+    pub rejected_count: i32,
+    #[diesel(serialize_as = BigDecimal)]
+    pub total_amount: Decimal,
+    #[diesel(serialize_as = BigDecimal)]
+    pub total_fees: Decimal,
+    pub ach_status: &'a str,
+    pub ach_next_attempt_at: Option<DateTime<Utc>>,
+}
+
+/// One row of an uploaded payout file.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = payout_batch_items, primary_key(item_id))]
+pub struct PayoutBatchItem {
+    pub item_id: Uuid,
+    pub batch_id: Uuid,
+    pub row_number: i32,
+    pub payment_type: Option<String>, // Map to TransactionType
+    pub amount: Option<String>, // As written in the file
+    pub beneficiary_name: Option<String>,
+    pub reference: Option<String>,
+    pub status: String, // Map to PayoutItemStatus
+    pub errors: JsonValue, // Array of messages
+    #[serde(skip_serializing)] // Account numbers stay out of API responses
+    pub payment_details: Option<JsonValue>,
+    pub transaction_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = payout_batch_items)]
+pub struct NewPayoutBatchItem<'a> {
+    pub batch_id: Uuid,
+    pub row_number: i32,
+    pub payment_type: Option<&'a str>,
+    pub amount: Option<&'a str>,
+    pub beneficiary_name: Option<&'a str>,
+    pub reference: Option<&'a str>,
+    pub status: &'a str,
+    pub errors: JsonValue,
+    pub payment_details: Option<JsonValue>,
+    pub transaction_id: Option<Uuid>,
+}
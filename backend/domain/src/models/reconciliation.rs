@@ -0,0 +1,341 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/reconciliation.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{Date, Uuid as DieselUuid, Nullable, Varchar, Numeric as DieselNumeric, Text, Jsonb, Timestamptz}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+use rust_decimal::Decimal;
+use bigdecimal::BigDecimal;
+use serde_json::Value as JsonValue;
+
+table! {
+    core_schema.bank_statement_lines (line_id) {
+        line_id -> DieselUuid,
+        nostro_account -> Varchar,
+        bank_transaction_id -> Varchar,
+        booked_at -> Timestamptz,
+        value_date -> Nullable<Date>,
+        amount -> DieselNumeric,
+        currency_code -> Varchar,
+        reference -> Nullable<Varchar>,
+        uetr -> Nullable<Varchar>,
+        description -> Text,
+        counterparty_name -> Nullable<Varchar>,
+        raw -> Jsonb,
+        status -> Varchar,
+        match_id -> Nullable<DieselUuid>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    core_schema.reconciliation_matches (match_id) {
+        match_id -> DieselUuid,
+        nostro_account -> Varchar,
+        match_kind -> Varchar,
+        match_rule -> Varchar,
+        statement_amount -> DieselNumeric,
+        transaction_amount -> DieselNumeric,
+        currency_code -> Varchar,
+        matched_by -> Nullable<DieselUuid>,
+        note -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    core_schema.reconciliation_match_transactions (match_id, transaction_id) {
+        match_id -> DieselUuid,
+        transaction_id -> DieselUuid,
+    }
+}
+
+table! {
+    core_schema.reconciliation_exceptions (exception_id) {
+        exception_id -> DieselUuid,
+        nostro_account -> Varchar,
+        exception_type -> Varchar,
+        line_id -> Nullable<DieselUuid>,
+        transaction_id -> Nullable<DieselUuid>,
+        match_id -> Nullable<DieselUuid>,
+        business_date -> Date,
+        expected_amount -> Nullable<DieselNumeric>,
+        actual_amount -> Nullable<DieselNumeric>,
+        currency_code -> Varchar,
+        status -> Varchar,
+        resolution -> Nullable<Varchar>,
+        resolution_note -> Nullable<Text>,
+        resolved_by -> Nullable<DieselUuid>,
+        resolved_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum StatementLineStatus {
+    Unmatched, // Pulled, not reconciled yet
+    Matched,   // Part of a reconciliation match
+    Exception, // Could not be matched automatically; an exception is open for it
+}
+
+impl StatementLineStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StatementLineStatus::Unmatched => "UNMATCHED",
+            StatementLineStatus::Matched => "MATCHED",
+            StatementLineStatus::Exception => "EXCEPTION",
+        }
+    }
+}
+
+/// Shape of a match, seen from the statement: one line against one or several transactions, or
+/// several lines against one transaction.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MatchKind {
+    OneToOne,
+    OneToMany, // One statement line, several transactions (e.g. a batch booked as one debit)
+    ManyToOne, // Several statement lines, one transaction (e.g. a payment booked in parts)
+}
+
+impl MatchKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MatchKind::OneToOne => "ONE_TO_ONE",
+            MatchKind::OneToMany => "ONE_TO_MANY",
+            MatchKind::ManyToOne => "MANY_TO_ONE",
+        }
+    }
+
+    pub fn for_counts(lines: usize, transactions: usize) -> MatchKind {
+        match (lines, transactions) {
+            (1, 1) => MatchKind::OneToOne,
+            (1, _) => MatchKind::OneToMany,
+            _ => MatchKind::ManyToOne,
+        }
+    }
+}
+
+/// Which rule produced a match, strongest first.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MatchRule {
+    Uetr,       // SWIFT gpi UETR on both sides
+    Reference,  // Our transaction id / rail reference quoted on the statement
+    AmountDate, // Same signed amount within the account's date window, and the only candidate
+    Manual,     // Matched by operations
+}
+
+impl MatchRule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MatchRule::Uetr => "UETR",
+            MatchRule::Reference => "REFERENCE",
+            MatchRule::AmountDate => "AMOUNT_DATE",
+            MatchRule::Manual => "MANUAL",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ExceptionType {
+    UnmatchedStatementLine, // Money moved at the bank that we cannot tie to a transaction
+    UnmatchedTransaction,   // A payment we sent/expected that never appeared on the statement
+    AmountMismatch,         // Matched by reference/UETR but the amounts differ (fees deducted, partial credit)
+}
+
+impl ExceptionType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExceptionType::UnmatchedStatementLine => "UNMATCHED_STATEMENT_LINE",
+            ExceptionType::UnmatchedTransaction => "UNMATCHED_TRANSACTION",
+            ExceptionType::AmountMismatch => "AMOUNT_MISMATCH",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<ExceptionType> {
+        match value {
+            "UNMATCHED_STATEMENT_LINE" => Some(ExceptionType::UnmatchedStatementLine),
+            "UNMATCHED_TRANSACTION" => Some(ExceptionType::UnmatchedTransaction),
+            "AMOUNT_MISMATCH" => Some(ExceptionType::AmountMismatch),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ExceptionStatus {
+    Open,
+    Resolved,
+}
+
+impl ExceptionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExceptionStatus::Open => "OPEN",
+            ExceptionStatus::Resolved => "RESOLVED",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<ExceptionStatus> {
+        match value {
+            "OPEN" => Some(ExceptionStatus::Open),
+            "RESOLVED" => Some(ExceptionStatus::Resolved),
+            _ => None,
+        }
+    }
+}
+
+/// How a break was closed.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ExceptionResolution {
+    AutoMatched,     // A later run found the other side
+    MatchedManually, // Tied to the other side by hand
+    Accepted,        // Amount difference accepted (e.g. correspondent fees); the transaction settles
+    WrittenOff,      // Difference or unexplained entry booked off outside this system
+    Ignored,         // Not a break (e.g. bank-internal entry)
+}
+
+impl ExceptionResolution {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExceptionResolution::AutoMatched => "AUTO_MATCHED",
+            ExceptionResolution::MatchedManually => "MATCHED_MANUALLY",
+            ExceptionResolution::Accepted => "ACCEPTED",
+            ExceptionResolution::WrittenOff => "WRITTEN_OFF",
+            ExceptionResolution::Ignored => "IGNORED",
+        }
+    }
+}
+
+/// An entry from a nostro account statement.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = bank_statement_lines, primary_key(line_id))]
+pub struct BankStatementLine {
+    pub line_id: Uuid,
+    pub nostro_account: String,
+    pub bank_transaction_id: String,
+    pub booked_at: DateTime<Utc>,
+    pub value_date: Option<NaiveDate>,
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal, // Credits positive, debits negative
+    pub currency_code: String,
+    pub reference: Option<String>,
+    pub uetr: Option<String>,
+    pub description: String,
+    pub counterparty_name: Option<String>,
+    pub raw: JsonValue,
+    pub status: String, // Map to StatementLineStatus
+    pub match_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = bank_statement_lines)]
+pub struct NewBankStatementLine<'a> {
+    pub nostro_account: &'a str,
+    pub bank_transaction_id: &'a str,
+    pub booked_at: DateTime<Utc>,
+    pub value_date: Option<NaiveDate>,
+    #[diesel(serialize_as = BigDecimal)]
+    pub amount: Decimal,
+    pub currency_code: &'a str,
+    pub reference: Option<&'a str>,
+    pub uetr: Option<&'a str>,
+    pub description: &'a str,
+    pub counterparty_name: Option<&'a str>,
+    pub raw: JsonValue,
+    pub status: &'a str,
+}
+
+/// Statement lines and transactions agreed to be the same money movement.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = reconciliation_matches, primary_key(match_id))]
+pub struct ReconciliationMatch {
+    pub match_id: Uuid,
+    pub nostro_account: String,
+    pub match_kind: String, // Map to MatchKind
+    pub match_rule: String, // Map to MatchRule
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub statement_amount: Decimal,
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub transaction_amount: Decimal,
+    pub currency_code: String,
+    pub matched_by: Option<Uuid>,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = reconciliation_matches)]
+pub struct NewReconciliationMatch<'a> {
+    pub nostro_account: &'a str,
+    pub match_kind: &'a str,
+    pub match_rule: &'a str,
+    #[diesel(serialize_as = BigDecimal)]
+    pub statement_amount: Decimal,
+    #[diesel(serialize_as = BigDecimal)]
+    pub transaction_amount: Decimal,
+    pub currency_code: &'a str,
+    pub matched_by: Option<Uuid>,
+    pub note: Option<&'a str>,
+}
+
+#[derive(Debug, Insertable, Queryable, Clone, Copy, PartialEq)]
+#[diesel(table_name = reconciliation_match_transactions)]
+pub struct ReconciliationMatchTransaction {
+    pub match_id: Uuid,
+    pub transaction_id: Uuid,
+}
+
+/// A reconciliation break.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = reconciliation_exceptions, primary_key(exception_id))]
+pub struct ReconciliationException {
+    pub exception_id: Uuid,
+    pub nostro_account: String,
+    pub exception_type: String, // Map to ExceptionType
+    pub line_id: Option<Uuid>,
+    pub transaction_id: Option<Uuid>,
+    pub match_id: Option<Uuid>,
+    pub business_date: NaiveDate,
+    #[diesel(deserialize_as = Option<BigDecimal>)]
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub expected_amount: Option<Decimal>,
+    #[diesel(deserialize_as = Option<BigDecimal>)]
+    #[serde(with = "rust_decimal::serde::str_option")]
+    pub actual_amount: Option<Decimal>,
+    pub currency_code: String,
+    pub status: String, // Map to ExceptionStatus
+    pub resolution: Option<String>, // Map to ExceptionResolution
+    pub resolution_note: Option<String>,
+    pub resolved_by: Option<Uuid>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = reconciliation_exceptions)]
+pub struct NewReconciliationException<'a> {
+    pub nostro_account: &'a str,
+    pub exception_type: &'a str,
+    pub line_id: Option<Uuid>,
+    pub transaction_id: Option<Uuid>,
+    pub match_id: Option<Uuid>,
+    pub business_date: NaiveDate,
+    #[diesel(serialize_as = Option<BigDecimal>)]
+    pub expected_amount: Option<Decimal>,
+    #[diesel(serialize_as = Option<BigDecimal>)]
+    pub actual_amount: Option<Decimal>,
+    pub currency_code: &'a str,
+    pub status: &'a str,
+}
@@ -0,0 +1,84 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/sanctions.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{Uuid as DieselUuid, Nullable, Varchar, Text, Jsonb, Timestamptz}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use serde_json::Value as JsonValue;
+
+table! {
+    core_schema.sanctions_cases (case_id) {
+        case_id -> DieselUuid,
+        transaction_id -> DieselUuid,
+        initiator_user_id -> DieselUuid,
+        status -> Varchar,
+        parties -> Jsonb,
+        hits -> Jsonb,
+        lists -> Jsonb,
+        pending_dispatch -> Jsonb,
+        reviewed_by -> Nullable<DieselUuid>,
+        review_comment -> Nullable<Text>,
+        reviewed_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+/// Where a sanctions screening case stands.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SanctionsCaseStatus {
+    Open,      // Payment stopped in RequiresAction, waiting for compliance
+    Cleared,   // False positive, payment resumed
+    Confirmed, // True hit, payment cancelled
+}
+
+impl SanctionsCaseStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SanctionsCaseStatus::Open => "OPEN",
+            SanctionsCaseStatus::Cleared => "CLEARED",
+            SanctionsCaseStatus::Confirmed => "CONFIRMED",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<SanctionsCaseStatus> {
+        match value {
+            "OPEN" => Some(SanctionsCaseStatus::Open),
+            "CLEARED" => Some(SanctionsCaseStatus::Cleared),
+            "CONFIRMED" => Some(SanctionsCaseStatus::Confirmed),
+            _ => None,
+        }
+    }
+}
+
+/// An outbound payment stopped by a watchlist hit.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = sanctions_cases, primary_key(case_id))]
+pub struct SanctionsCase {
+    pub case_id: Uuid,
+    pub transaction_id: Uuid,
+    pub initiator_user_id: Uuid,
+    pub status: String, // Map to SanctionsCaseStatus
+    pub parties: JsonValue, // Array of sanctions::ScreeningParty
+    pub hits: JsonValue, // Array of sanctions::ScreeningHit
+    pub lists: JsonValue, // Array of sanctions::LoadedList
+    #[serde(skip_serializing)] // Beneficiary account details stay out of API responses
+    pub pending_dispatch: JsonValue, // approvals::PendingDispatch
+    pub reviewed_by: Option<Uuid>,
+    pub review_comment: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = sanctions_cases)]
+pub struct NewSanctionsCase<'a> {
+    pub transaction_id: Uuid,
+    pub initiator_user_id: Uuid,
+    pub status: &'a str,
+    pub parties: JsonValue,
+    pub hits: JsonValue,
+    pub lists: JsonValue,
+    pub pending_dispatch: JsonValue,
+}
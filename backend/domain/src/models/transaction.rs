@@ -27,15 +27,29 @@ table! {
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
         settlement_at -> Nullable<Timestamptz>,
+        scheduled_run_id -> Nullable<DieselUuid>,
     }
 }
 
 
+table! {
+    core_schema.transaction_state_transitions (transition_id) {
+        transition_id -> BigInt,
+        transaction_id -> DieselUuid,
+        from_status -> Nullable<Varchar>,
+        to_status -> Varchar,
+        financial_effect -> Varchar,
+        actor -> Varchar,
+        created_at -> Timestamptz,
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, diesel_derive_enum::DbEnum)]
 #[ExistingTypePath = "core_schema.sql_types::TransactionType"] // Assuming TYPE transaction_type
 pub enum TransactionType {
     AchCredit,
     AchDebit,
+    AchInbound, // ACH credit originated elsewhere and received into one of our accounts
     WireOutbound,
     WireInbound,
     CardAuthorization,
@@ -51,6 +65,8 @@ pub enum TransactionType {
     InternalTransfer,
     Conversion, // Added for currency conversions
     Fee, // Added for representing fees
+    InterestCredit, // Interest capitalized into a wallet
+    WithholdingTax, // Tax withheld from credited interest
     RtgsCreditTransfer, // Specific for TARGET2/RTGS ISO 20022 pacs.008
     RtgsDirectDebit, // Specific for TARGET2/RTGS ISO 20022 pacs.003 (less common for RTGS)
     RtgsReturn, // Specific for TARGET2/RTGS ISO 20022 pacs.004
@@ -65,6 +81,7 @@ pub enum TransactionStatus {
     Processing,       // Actively being processed (e.g., sent to bank)
     RequiresAction,   // Needs user input (e.g., 2FA, clarification)
     Authorized,       // Card payment authorized, awaiting capture
+    Capturing,        // Card capture sent to the gateway, awaiting its answer
     Submitted,        // Submitted to network (ACH, Wire, Crypto broadcast)
     Settled,          // Final settlement confirmed (RTGS, some ACH)
     Completed,        // Successfully processed and funds delivered/credited
@@ -75,6 +92,90 @@ pub enum TransactionStatus {
     Expired,          // Timed out (e.g., crypto invoice)
 }
 
+impl TransactionType {
+    /// Parses the stored (variant name) representation.
+    pub fn parse(value: &str) -> Option<TransactionType> {
+        use TransactionType::*;
+        Some(match value {
+            "AchCredit" => AchCredit,
+            "AchDebit" => AchDebit,
+            "AchInbound" => AchInbound,
+            "WireOutbound" => WireOutbound,
+            "WireInbound" => WireInbound,
+            "CardAuthorization" => CardAuthorization,
+            "CardCapture" => CardCapture,
+            "CardRefund" => CardRefund,
+            "CardChargeback" => CardChargeback,
+            "CheckDeposit" => CheckDeposit,
+            "CheckWithdrawal" => CheckWithdrawal,
+            "CryptoBtcSend" => CryptoBtcSend,
+            "CryptoBtcReceive" => CryptoBtcReceive,
+            "CryptoXmrSend" => CryptoXmrSend,
+            "CryptoXmrReceive" => CryptoXmrReceive,
+            "InternalTransfer" => InternalTransfer,
+            "Conversion" => Conversion,
+            "Fee" => Fee,
+            "InterestCredit" => InterestCredit,
+            "WithholdingTax" => WithholdingTax,
+            "RtgsCreditTransfer" => RtgsCreditTransfer,
+            "RtgsDirectDebit" => RtgsDirectDebit,
+            "RtgsReturn" => RtgsReturn,
+            "RtgsStatusUpdate" => RtgsStatusUpdate,
+            "Unknown" => Unknown,
+            _ => return None,
+        })
+    }
+}
+
+impl TransactionStatus {
+    /// Parses the stored (variant name) representation.
+    pub fn parse(value: &str) -> Option<TransactionStatus> {
+        use TransactionStatus::*;
+        Some(match value {
+            "Pending" => Pending,
+            "Processing" => Processing,
+            "RequiresAction" => RequiresAction,
+            "Authorized" => Authorized,
+            "Capturing" => Capturing,
+            "Submitted" => Submitted,
+            "Settled" => Settled,
+            "Completed" => Completed,
+            "Failed" => Failed,
+            "Cancelled" => Cancelled,
+            "Returned" => Returned,
+            "Chargeback" => Chargeback,
+            "Expired" => Expired,
+            _ => return None,
+        })
+    }
+
+    /// The stored representation (variant name), as read back by `parse`.
+    pub fn as_str(&self) -> &'static str {
+        use TransactionStatus::*;
+        match self {
+            Pending => "Pending",
+            Processing => "Processing",
+            RequiresAction => "RequiresAction",
+            Authorized => "Authorized",
+            Capturing => "Capturing",
+            Submitted => "Submitted",
+            Settled => "Settled",
+            Completed => "Completed",
+            Failed => "Failed",
+            Cancelled => "Cancelled",
+            Returned => "Returned",
+            Chargeback => "Chargeback",
+            Expired => "Expired",
+        }
+    }
+}
+
+impl std::fmt::Display for TransactionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Represents a financial transaction in the system.
 #[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
 #[diesel(table_name = transactions, primary_key(transaction_id))]
@@ -94,6 +195,7 @@ pub struct Transaction {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub settlement_at: Option<DateTime<Utc>>, // Track final settlement time
+    pub scheduled_run_id: Option<Uuid>, // Payment schedule run that made this payment
 }
 
 
@@ -112,6 +214,7 @@ pub struct NewTransaction<'a> {
     pub description: Option<&'a str>,
     pub external_ref_id: Option<&'a str>,
     pub metadata: Option<JsonValue>,
+    pub scheduled_run_id: Option<Uuid>, // Set only by the payment scheduler
     // created_at, updated_at defaulted by DB
     // settlement_at is set later
 }
@@ -139,21 +242,38 @@ pub struct CardDetails {
     // Add other relevant non-sensitive card details
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct AchDetails {
+    pub routing_number: String, // Receiving DFI ABA routing number (9 digits)
+    pub account_number: String, // Receiver's account at the RDFI
+    #[serde(default)]
+    pub account_holder_name: Option<String>, // Receiver name on the entry detail record
+    #[serde(default)]
     pub trace_number: Option<String>, // NACHA trace number
+    #[serde(default)]
     pub return_code: Option<String>, // If returned
+    #[serde(default)]
     pub company_id: Option<String>, // SEC code specific IDs
+    #[serde(default)]
     pub effective_entry_date: Option<chrono::NaiveDate>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct WireDetails {
+    pub swift_bic: String, // Beneficiary bank
+    pub account_number: String, // IBAN or local account number
+    pub beneficiary_name: String,
+    #[serde(default)]
     pub uetr: Option<String>, // Unique End-to-end Transaction Reference (SWIFT gpi)
+    #[serde(default)]
     pub swift_messages: Option<Vec<String>>, // Store raw MT/MX messages if needed (or refs)
+    #[serde(default)]
     pub intermediary_banks: Option<Vec<BankIdentifier>>, // List of intermediary banks
+    #[serde(default)]
     pub purpose_code: Option<String>,
+    #[serde(default)]
     pub remittance_info: Option<String>, // Structured or unstructured remittance
+    #[serde(default)]
     pub charge_details: Option<String>, // BEN, OUR, SHA
 }
 
@@ -190,4 +310,27 @@ pub enum PaymentDetails {
     Check(CheckDetails),
     Crypto(CryptoDetails),
     // Add others if needed
-}
\ No newline at end of file
+}
+
+/// Represents one recorded status transition and the financial effect it applied.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = transaction_state_transitions, primary_key(transition_id))]
+pub struct TransactionStateTransition {
+    pub transition_id: i64,
+    pub transaction_id: Uuid,
+    pub from_status: Option<String>, // None for the effect applied at creation
+    pub to_status: String,
+    pub financial_effect: String, // Map to payments::state_machine::FinancialEffect
+    pub actor: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = transaction_state_transitions)]
+pub struct NewTransactionStateTransition<'a> {
+    pub transaction_id: Uuid,
+    pub from_status: Option<&'a str>,
+    pub to_status: &'a str,
+    pub financial_effect: &'a str,
+    pub actor: &'a str,
+}
@@ -0,0 +1,120 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/travel_rule.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{Int4, Uuid as DieselUuid, Nullable, Varchar, Text, Jsonb, Timestamptz}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use serde_json::Value as JsonValue;
+
+table! {
+    core_schema.travel_rule_transfers (transfer_id) {
+        transfer_id -> DieselUuid,
+        transaction_id -> DieselUuid,
+        initiator_user_id -> DieselUuid,
+        status -> Varchar,
+        destination_address -> Text,
+        beneficiary_vasp_id -> Nullable<Varchar>,
+        protocol -> Nullable<Varchar>,
+        ivms101 -> Jsonb,
+        missing_fields -> Jsonb,
+        pending_dispatch -> Nullable<Jsonb>,
+        counterparty_reference -> Nullable<Varchar>,
+        counterparty_response -> Nullable<Jsonb>,
+        attempts -> Int4,
+        last_error -> Nullable<Text>,
+        next_attempt_at -> Nullable<Timestamptz>,
+        expires_at -> Nullable<Timestamptz>,
+        sent_at -> Nullable<Timestamptz>,
+        completed_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+/// Where the Travel Rule exchange of a withdrawal stands.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TravelRuleStatus {
+    AwaitingData, // Withdrawal held until the customer supplies the missing data
+    Ready,        // Data complete, to be sent to the beneficiary VASP
+    Sent,         // Sent, the beneficiary VASP has not decided yet
+    Accepted,     // Beneficiary VASP accepted, withdrawal released
+    Rejected,     // Beneficiary VASP refused (or is unknown), withdrawal cancelled
+    Unhosted,     // Self-hosted destination wallet: data recorded, nothing to exchange
+    Expired,      // Hold window lapsed, withdrawal expired
+}
+
+impl TravelRuleStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TravelRuleStatus::AwaitingData => "AWAITING_DATA",
+            TravelRuleStatus::Ready => "READY",
+            TravelRuleStatus::Sent => "SENT",
+            TravelRuleStatus::Accepted => "ACCEPTED",
+            TravelRuleStatus::Rejected => "REJECTED",
+            TravelRuleStatus::Unhosted => "UNHOSTED",
+            TravelRuleStatus::Expired => "EXPIRED",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<TravelRuleStatus> {
+        match value {
+            "AWAITING_DATA" => Some(TravelRuleStatus::AwaitingData),
+            "READY" => Some(TravelRuleStatus::Ready),
+            "SENT" => Some(TravelRuleStatus::Sent),
+            "ACCEPTED" => Some(TravelRuleStatus::Accepted),
+            "REJECTED" => Some(TravelRuleStatus::Rejected),
+            "UNHOSTED" => Some(TravelRuleStatus::Unhosted),
+            "EXPIRED" => Some(TravelRuleStatus::Expired),
+            _ => None,
+        }
+    }
+
+    /// Whether the withdrawal is still held in RequiresAction.
+    pub fn is_holding(&self) -> bool {
+        matches!(self, TravelRuleStatus::AwaitingData | TravelRuleStatus::Ready | TravelRuleStatus::Sent)
+    }
+}
+
+/// Travel Rule data of one crypto withdrawal and its exchange with the beneficiary VASP.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = travel_rule_transfers, primary_key(transfer_id))]
+pub struct TravelRuleTransfer {
+    pub transfer_id: Uuid,
+    pub transaction_id: Uuid,
+    pub initiator_user_id: Uuid,
+    pub status: String, // Map to TravelRuleStatus
+    pub destination_address: String,
+    pub beneficiary_vasp_id: Option<String>,
+    pub protocol: Option<String>,
+    pub ivms101: JsonValue, // travel_rule::IdentityPayload
+    pub missing_fields: JsonValue, // Array of data element names
+    #[serde(skip_serializing)]
+    pub pending_dispatch: Option<JsonValue>, // approvals::PendingDispatch
+    pub counterparty_reference: Option<String>,
+    pub counterparty_response: Option<JsonValue>,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = travel_rule_transfers)]
+pub struct NewTravelRuleTransfer<'a> {
+    pub transaction_id: Uuid,
+    pub initiator_user_id: Uuid,
+    pub status: &'a str,
+    pub destination_address: &'a str,
+    pub beneficiary_vasp_id: Option<&'a str>,
+    pub protocol: Option<&'a str>,
+    pub ivms101: JsonValue,
+    pub missing_fields: JsonValue,
+    pub pending_dispatch: Option<JsonValue>,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
@@ -19,6 +19,9 @@ table! {
         password_hash -> Text,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        fee_tier -> Varchar,
+        kyc_tier -> Varchar,
+        country_code -> Nullable<Varchar>,
     }
 }
 
@@ -33,6 +36,9 @@ pub struct User {
     pub password_hash: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub fee_tier: String, // Pricing tier for fee schedules (STANDARD by default)
+    pub kyc_tier: String, // Verification level, matched against transaction limit rules (BASIC by default)
+    pub country_code: Option<String>, // Declared country of residence (ISO 3166-1 alpha-2), compared with GeoIP
 }
 
 /// Represents data needed to create a new user.
@@ -42,6 +48,7 @@ pub struct NewUser<'a> {
     pub username: &'a str,
     pub email: &'a str,
     pub password_hash: &'a str,
+    pub country_code: Option<&'a str>,
     // user_id, created_at, updated_at are defaulted by DB
 }
 
@@ -51,6 +58,9 @@ pub struct NewUser<'a> {
 pub struct UpdateUser<'a> {
     pub email: Option<&'a str>,
     pub password_hash: Option<&'a str>,
+    pub fee_tier: Option<&'a str>,
+    pub kyc_tier: Option<&'a str>,
+    pub country_code: Option<&'a str>,
     // username is likely not updatable
     // updated_at is handled by trigger
 }
\ No newline at end of file
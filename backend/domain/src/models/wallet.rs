@@ -37,11 +37,43 @@ table! {
 pub enum WalletType {
     FiatUsd,
     FiatEur,
+    SavingsUsd, // Interest-bearing
+    SavingsEur, // Interest-bearing
     CryptoBtc,
     CryptoXmr,
     // Add other specific types
 }
 
+impl WalletType {
+    /// Stored representation (`wallets.wallet_type`).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WalletType::FiatUsd => "FIAT_USD",
+            WalletType::FiatEur => "FIAT_EUR",
+            WalletType::SavingsUsd => "SAVINGS_USD",
+            WalletType::SavingsEur => "SAVINGS_EUR",
+            WalletType::CryptoBtc => "CRYPTO_BTC",
+            WalletType::CryptoXmr => "CRYPTO_XMR",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<WalletType> {
+        match value {
+            "FIAT_USD" => Some(WalletType::FiatUsd),
+            "FIAT_EUR" => Some(WalletType::FiatEur),
+            "SAVINGS_USD" => Some(WalletType::SavingsUsd),
+            "SAVINGS_EUR" => Some(WalletType::SavingsEur),
+            "CRYPTO_BTC" => Some(WalletType::CryptoBtc),
+            "CRYPTO_XMR" => Some(WalletType::CryptoXmr),
+            _ => None,
+        }
+    }
+
+    pub fn is_fiat(&self) -> bool {
+        !matches!(self, WalletType::CryptoBtc | WalletType::CryptoXmr)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, diesel_derive_enum::DbEnum)]
 #[ExistingTypePath = "core_schema.sql_types::WalletStatus"] // Assuming you create TYPE wallet_status
 pub enum WalletStatus {
@@ -115,8 +147,7 @@ pub struct NewWallet<'a> {
 #[derive(Debug, Deserialize, AsChangeset, Clone)]
 #[diesel(table_name = wallets)]
 pub struct UpdateWallet<'a> {
-    #[diesel(serialize_as = Option<BigDecimal>)] // Map update value correctly
-    pub balance: Option<Decimal>, // Only update balance if provided
+    // balance is not updatable here: it is a cache of the wallet's ledger account, changed only by journal postings
     pub bank_name: Option<&'a str>,
     pub account_number_hash: Option<&'a str>,
     pub iban_hash: Option<&'a str>,
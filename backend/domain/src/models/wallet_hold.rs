@@ -0,0 +1,116 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/wallet_hold.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{Uuid as DieselUuid, Nullable, Varchar, Numeric as DieselNumeric, Text, Timestamptz}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use rust_decimal::Decimal;
+use bigdecimal::BigDecimal;
+
+table! {
+    core_schema.wallet_holds (hold_id) {
+        hold_id -> DieselUuid,
+        wallet_id -> DieselUuid,
+        transaction_id -> Nullable<DieselUuid>,
+        hold_type -> Varchar,
+        amount -> DieselNumeric,
+        currency_code -> Varchar,
+        status -> Varchar,
+        reason -> Nullable<Text>,
+        expires_at -> Nullable<Timestamptz>,
+        resolved_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+/// Why funds are reserved.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum HoldType {
+    CardAuthorization, // Authorized, not yet captured
+    Withdrawal,        // Outbound payment in flight
+    CheckClearing,     // Deposited check credited but still returnable
+    Manual,            // Placed by operations (disputes, investigations)
+}
+
+impl HoldType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HoldType::CardAuthorization => "CARD_AUTHORIZATION",
+            HoldType::Withdrawal => "WITHDRAWAL",
+            HoldType::CheckClearing => "CHECK_CLEARING",
+            HoldType::Manual => "MANUAL",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<HoldType> {
+        match value {
+            "CARD_AUTHORIZATION" => Some(HoldType::CardAuthorization),
+            "WITHDRAWAL" => Some(HoldType::Withdrawal),
+            "CHECK_CLEARING" => Some(HoldType::CheckClearing),
+            "MANUAL" => Some(HoldType::Manual),
+            _ => None,
+        }
+    }
+}
+
+/// Lifecycle of a hold. Only ACTIVE, unexpired holds reduce the available balance.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum HoldStatus {
+    Active,
+    Released,  // Funds given back (failed/cancelled payment, check cleared)
+    Converted, // Became a ledger posting (capture, settlement)
+    Expired,   // Lapsed (e.g. card authorization never captured)
+}
+
+impl HoldStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HoldStatus::Active => "ACTIVE",
+            HoldStatus::Released => "RELEASED",
+            HoldStatus::Converted => "CONVERTED",
+            HoldStatus::Expired => "EXPIRED",
+        }
+    }
+}
+
+/// Represents a reservation against a wallet's ledger balance.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = wallet_holds, primary_key(hold_id))]
+pub struct WalletHold {
+    pub hold_id: Uuid,
+    pub wallet_id: Uuid,
+    pub transaction_id: Option<Uuid>,
+    pub hold_type: String, // Map to HoldType
+    #[diesel(deserialize_as = BigDecimal)]
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+    pub currency_code: String,
+    pub status: String, // Map to HoldStatus
+    pub reason: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl WalletHold {
+    /// True while the hold still reduces the available balance.
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        self.status == HoldStatus::Active.as_str() && self.expires_at.map_or(true, |expiry| expiry > now)
+    }
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = wallet_holds)]
+pub struct NewWalletHold<'a> {
+    pub wallet_id: Uuid,
+    pub transaction_id: Option<Uuid>,
+    pub hold_type: &'a str,
+    #[diesel(serialize_as = BigDecimal)]
+    pub amount: Decimal,
+    pub currency_code: &'a str,
+    pub status: &'a str,
+    pub reason: Option<&'a str>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,87 @@
+// /home/inno/elights_jobes-research/backend/domain/src/models/webhook_event.rs
+use diesel::prelude::*;
+use diesel::{table, sql_types::{Uuid as DieselUuid, Nullable, Varchar, Text, Jsonb, Int4, Timestamptz}};
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use serde_json::Value as JsonValue;
+
+table! {
+    core_schema.webhook_events (event_id) {
+        event_id -> DieselUuid,
+        provider -> Varchar,
+        provider_event_id -> Varchar,
+        event_type -> Varchar,
+        raw_payload -> Text,
+        signature_headers -> Jsonb,
+        status -> Varchar,
+        attempts -> Int4,
+        last_error -> Nullable<Text>,
+        results -> Nullable<Jsonb>,
+        received_at -> Timestamptz,
+        processed_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+/// Processing state of a stored webhook.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEventStatus {
+    Received,   // Stored, not processed yet
+    Processing, // Claimed by one request while it applies the event (reclaimable once the claim is stale)
+    Processed,  // Applied; a replayed delivery is only acknowledged
+    Failed,    // Last attempt errored; a redelivery or a reprocess request retries it
+}
+
+impl WebhookEventStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEventStatus::Received => "RECEIVED",
+            WebhookEventStatus::Processing => "PROCESSING",
+            WebhookEventStatus::Processed => "PROCESSED",
+            WebhookEventStatus::Failed => "FAILED",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<WebhookEventStatus> {
+        match value {
+            "RECEIVED" => Some(WebhookEventStatus::Received),
+            "PROCESSING" => Some(WebhookEventStatus::Processing),
+            "PROCESSED" => Some(WebhookEventStatus::Processed),
+            "FAILED" => Some(WebhookEventStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// A verified provider webhook, kept with its raw payload.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, Selectable, Clone, PartialEq)]
+#[diesel(table_name = webhook_events, primary_key(event_id))]
+pub struct WebhookEvent {
+    pub event_id: Uuid,
+    pub provider: String,
+    pub provider_event_id: String,
+    pub event_type: String,
+    pub raw_payload: String,
+    pub signature_headers: JsonValue,
+    pub status: String, // Map to WebhookEventStatus
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub results: Option<JsonValue>,
+    pub received_at: DateTime<Utc>,
+    pub processed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, Clone)]
+#[diesel(table_name = webhook_events)]
+pub struct NewWebhookEvent<'a> {
+    pub provider: &'a str,
+    pub provider_event_id: &'a str,
+    pub event_type: &'a str,
+    pub raw_payload: &'a str,
+    pub signature_headers: JsonValue,
+    pub status: &'a str,
+}
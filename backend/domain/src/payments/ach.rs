@@ -3,10 +3,20 @@ use diesel::prelude::*;
 use crate::models::{Transaction, NewTransaction, Wallet, TransactionType, TransactionStatus, AchDetails, UpdateTransaction};
 use crate::error::DomainError;
 use crate::payments::validator::{validate_ach_details, ValidationContext};
+use crate::payments::state_machine::{self, TransitionUpdate};
 use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use chrono::{DateTime, NaiveDate, Utc};
 use uuid::Uuid;
 
-// Placeholder structure for NACHA file data
+/// Length of every NACHA record.
+pub const NACHA_RECORD_LENGTH: usize = 94;
+/// Records per block; the file is padded with all-9 records to a multiple of this.
+pub const NACHA_BLOCKING_FACTOR: usize = 10;
+/// Largest amount an entry detail record can carry (10 digits of cents).
+pub const NACHA_MAX_ENTRY_CENTS: u64 = 9_999_999_999;
+
+// Records of a NACHA file (one string per 94-character record)
 #[derive(Debug)]
 pub struct NachaFile {
     pub file_header: String,
@@ -36,6 +46,212 @@ pub fn generate_ach_file(transactions: Vec<&Transaction>) -> Result<NachaFile, D
     })
 }
 
+impl NachaFile {
+    /// All records in file order, padded to whole blocks, newline separated.
+    pub fn to_file_string(&self) -> String {
+        let mut records: Vec<&str> = vec![&self.file_header];
+        // Single-batch files: header, entries, control
+        for (header, control) in self.batch_headers.iter().zip(&self.batch_controls) {
+            records.push(header);
+            records.extend(self.entries.iter().map(String::as_str));
+            records.push(control);
+        }
+        records.push(&self.file_control);
+        let padding = "9".repeat(NACHA_RECORD_LENGTH);
+        while records.len() % NACHA_BLOCKING_FACTOR != 0 {
+            records.push(&padding);
+        }
+        let mut contents = records.join("\n");
+        contents.push('\n');
+        contents
+    }
+}
+
+/// Our origination agreement with the ODFI, stamped on every file we send.
+#[derive(Debug, Clone)]
+pub struct NachaOriginator {
+    pub odfi_routing_number: String, // Immediate destination (9 digits); its first 8 digits prefix our trace numbers
+    pub odfi_name: String, // Immediate destination name
+    pub company_id: String, // Immediate origin / company identification (10 chars, usually "1" + EIN)
+    pub company_name: String, // Shown to receivers on their statements
+}
+
+/// One PPD credit entry of a batch.
+#[derive(Debug, Clone)]
+pub struct NachaCreditEntry<'a> {
+    pub routing_number: &'a str, // RDFI routing number (9 digits, checksum already validated)
+    pub account_number: &'a str,
+    pub amount: Decimal,
+    pub individual_id: &'a str, // Our reference for the receiver (15 chars max)
+    pub individual_name: &'a str,
+    pub trace_sequence: u32, // Last 7 digits of the trace number, unique within the file
+}
+
+/// Alphanumeric NACHA field: upper-case printable ASCII, left-justified, space-padded, truncated.
+fn alpha_field(value: &str, width: usize) -> String {
+    let cleaned: String = value.chars()
+        .map(|c| if c.is_ascii_graphic() || c == ' ' { c.to_ascii_uppercase() } else { ' ' })
+        .take(width)
+        .collect();
+    format!("{:<width$}", cleaned, width = width)
+}
+
+/// Numeric NACHA field: right-justified, zero-padded. Fails when the value does not fit.
+fn numeric_field(value: u64, width: usize, name: &str) -> Result<String, DomainError> {
+    let formatted = format!("{:0width$}", value, width = width);
+    if formatted.len() > width {
+        return Err(DomainError::Validation(format!("NACHA {} {} exceeds {} digits", name, value, width)));
+    }
+    Ok(formatted)
+}
+
+fn entry_cents(amount: Decimal) -> Result<u64, DomainError> {
+    let cents = amount * Decimal::ONE_HUNDRED;
+    if amount <= Decimal::ZERO || !cents.fract().is_zero() {
+        return Err(DomainError::Validation(format!("ACH amount {} must be positive with at most 2 decimals", amount)));
+    }
+    cents.to_u64()
+        .filter(|cents| *cents <= NACHA_MAX_ENTRY_CENTS)
+        .ok_or_else(|| DomainError::Validation(format!("ACH amount {} exceeds the entry maximum", amount)))
+}
+
+fn routing_digits(routing_number: &str) -> Result<&str, DomainError> {
+    if routing_number.len() != 9 || !routing_number.chars().all(|c| c.is_ascii_digit()) {
+        return Err(DomainError::Validation(format!("Invalid routing number {}", routing_number)));
+    }
+    Ok(routing_number)
+}
+
+/// Trace number of an entry: ODFI identification (8 digits) + sequence (7 digits).
+pub fn nacha_trace_number(originator: &NachaOriginator, trace_sequence: u32) -> String {
+    format!("{}{:07}", &originator.odfi_routing_number[..8.min(originator.odfi_routing_number.len())], trace_sequence % 10_000_000)
+}
+
+/// Builds a single-batch PPD credit file (service class 220, transaction code 22 - checking credit).
+/// `file_id_modifier` (A-Z, 0-9) distinguishes files sent on the same day.
+pub fn build_credit_file(
+    originator: &NachaOriginator,
+    entries: &[NachaCreditEntry],
+    entry_description: &str,
+    effective_entry_date: NaiveDate,
+    created_at: DateTime<Utc>,
+    file_id_modifier: char,
+) -> Result<NachaFile, DomainError> {
+    if entries.is_empty() {
+        return Err(DomainError::Validation("No entries provided for ACH file generation".to_string()));
+    }
+    if !file_id_modifier.is_ascii_uppercase() && !file_id_modifier.is_ascii_digit() {
+        return Err(DomainError::Validation(format!("Invalid file ID modifier {}", file_id_modifier)));
+    }
+    let odfi = routing_digits(&originator.odfi_routing_number)?;
+    let odfi_id = &odfi[..8];
+    const SERVICE_CLASS: &str = "220"; // Credits only
+    const BATCH_NUMBER: u64 = 1;
+
+    // --- 1: File Header ---
+    let file_header = format!(
+        "101 {}{}{}{}{}094101{}{}{}",
+        odfi,
+        alpha_field(&originator.company_id, 10),
+        created_at.format("%y%m%d"),
+        created_at.format("%H%M"),
+        file_id_modifier,
+        alpha_field(&originator.odfi_name, 23),
+        alpha_field(&originator.company_name, 23),
+        alpha_field("", 8), // Reference code
+    );
+
+    // --- 5: Batch Header ---
+    let batch_header = format!(
+        "5{}{}{}{}PPD{}{}{}{}1{}{}",
+        SERVICE_CLASS,
+        alpha_field(&originator.company_name, 16),
+        alpha_field("", 20), // Company discretionary data
+        alpha_field(&originator.company_id, 10),
+        alpha_field(entry_description, 10),
+        alpha_field("", 6), // Company descriptive date
+        effective_entry_date.format("%y%m%d"),
+        alpha_field("", 3), // Settlement date, filled in by the ACH operator
+        odfi_id,
+        numeric_field(BATCH_NUMBER, 7, "batch number")?,
+    );
+
+    // --- 6: Entry Details ---
+    let mut records = Vec::with_capacity(entries.len());
+    let mut entry_hash: u64 = 0;
+    let mut total_credit: u64 = 0;
+    for entry in entries {
+        let rdfi = routing_digits(entry.routing_number)?;
+        let cents = entry_cents(entry.amount)?;
+        entry_hash += rdfi[..8].parse::<u64>().unwrap_or(0);
+        total_credit += cents;
+        if entry.account_number.len() > 17 {
+            return Err(DomainError::Validation(format!("ACH account number {} is longer than 17 characters", entry.account_number)));
+        }
+        records.push(format!(
+            "622{}{}{}{}{}{}0{}",
+            rdfi, // RDFI identification (8) + check digit (1)
+            alpha_field(entry.account_number, 17),
+            numeric_field(cents, 10, "amount")?,
+            alpha_field(entry.individual_id, 15),
+            alpha_field(entry.individual_name, 22),
+            alpha_field("", 2), // Discretionary data
+            nacha_trace_number(originator, entry.trace_sequence), // Addenda indicator 0 precedes it
+        ));
+    }
+    let entry_hash = entry_hash % 10_000_000_000; // Rightmost 10 digits
+    let entry_count = entries.len() as u64;
+
+    // --- 8: Batch Control ---
+    let batch_control = format!(
+        "8{}{}{}{}{}{}{}{}{}{}",
+        SERVICE_CLASS,
+        numeric_field(entry_count, 6, "entry count")?,
+        numeric_field(entry_hash, 10, "entry hash")?,
+        numeric_field(0, 12, "total debit")?,
+        numeric_field(total_credit, 12, "total credit")?,
+        alpha_field(&originator.company_id, 10),
+        alpha_field("", 19), // Message authentication code
+        alpha_field("", 6), // Reserved
+        odfi_id,
+        numeric_field(BATCH_NUMBER, 7, "batch number")?,
+    );
+
+    // --- 9: File Control ---
+    let record_count = entries.len() + 4; // File header/control + batch header/control
+    let block_count = record_count.div_ceil(NACHA_BLOCKING_FACTOR) as u64;
+    let file_control = format!(
+        "9{}{}{}{}{}{}{}",
+        numeric_field(1, 6, "batch count")?,
+        numeric_field(block_count, 6, "block count")?,
+        numeric_field(entry_count, 8, "entry count")?,
+        numeric_field(entry_hash, 10, "entry hash")?,
+        numeric_field(0, 12, "total debit")?,
+        numeric_field(total_credit, 12, "total credit")?,
+        alpha_field("", 39), // Reserved
+    );
+
+    Ok(NachaFile {
+        file_header,
+        batch_headers: vec![batch_header],
+        entries: records,
+        batch_controls: vec![batch_control],
+        file_control,
+    })
+}
+
+/// Transmits a NACHA file to the ODFI and returns the file reference it acknowledged.
+/// Resending the same `file_name` is recognised by the ODFI as a duplicate.
+pub async fn submit_nacha_file(file: &NachaFile, file_name: &str) -> Result<String, DomainError> {
+    let contents = file.to_file_string();
+    if contents.lines().any(|record| record.len() != NACHA_RECORD_LENGTH) {
+        return Err(DomainError::Validation(format!("NACHA file {} has malformed records", file_name)));
+    }
+    // TODO: Upload via the ODFI's SFTP drop and wait for the acknowledgement file.
+    log::info!("Submitting NACHA file {} ({} entries, {} bytes)", file_name, file.entries.len(), contents.len());
+    Ok(file_name.to_string())
+}
+
 
 /// Processes an outbound ACH debit (pulling funds from an external account).
 pub async fn process_ach_debit(
@@ -66,6 +282,7 @@ pub async fn process_ach_debit(
         description: Some(description),
         external_ref_id: None, // Will be set later (e.g., trace number)
         metadata: metadata.clone(), // Clone if needed later
+        scheduled_run_id: None,
     };
 
     let mut transaction: Transaction = diesel::insert_into(crate::schema::transactions::table)
@@ -127,6 +344,7 @@ pub async fn process_ach_credit(
         description: Some(description),
         external_ref_id: None,
         metadata: metadata.clone(),
+        scheduled_run_id: None,
     };
 
     let mut transaction: Transaction = diesel::insert_into(crate::schema::transactions::table)
@@ -152,6 +370,23 @@ pub async fn process_ach_credit(
     Ok(transaction)
 }
 
+/// Submits the entry detail record for an already-debited outbound ACH credit.
+/// Used by the outbox worker; returns the trace number used as external reference.
+pub async fn submit_ach_credit_entry(
+    transaction: &Transaction,
+    destination_external_details: &AchDetails,
+) -> Result<String, DomainError> {
+    let context = ValidationContext { currency: &transaction.currency_code };
+    validate_ach_details(destination_external_details, &context)?;
+
+    // Trace number derived from the transaction, so a resubmitted entry is detectable as a duplicate
+    let trace_number = format!("{:015}", transaction.transaction_id.as_u128() % 1_000_000_000_000_000);
+    let nacha = generate_ach_file(vec![transaction])?;
+    // TODO: Batch and submit NachaFile via ODFI.
+    log::info!("Submitting ACH Credit Entry: {} (trace {})", nacha.entries.join(","), trace_number);
+    Ok(trace_number)
+}
+
 /// Handles incoming ACH return files/notifications.
 pub async fn handle_ach_return(
     conn: &mut PgConnection,
@@ -162,30 +397,90 @@ pub async fn handle_ach_return(
     log::warn!("Handling ACH Return for Tx: {} Code: {} Reason: {}",
         original_transaction_id, return_code, return_reason);
 
-    // 1. Find the original transaction
-    let mut transaction: Transaction = crate::schema::transactions::table
-        .find(original_transaction_id)
-        .first(conn)
-        .map_err(|e| DomainError::NotFound(format!("Original ACH transaction {} not found: {}", original_transaction_id, e)))?;
-
-    // 2. Update transaction status to Returned
-    // TODO: Update metadata with return code and reason.
-    let update_status = UpdateTransaction {
-        status: Some(TransactionStatus::Returned.to_string().as_str()),
-        // TODO: Add return_code/reason to metadata update if needed
-        external_ref_id: None,
-        metadata: None,
-        settlement_at: None,
-    };
-    transaction = diesel::update(crate::schema::transactions::table.find(transaction.transaction_id))
-        .set(&update_status)
-        .get_result(conn)?;
+    conn.transaction(|conn| {
+        // 1. Find and lock the original transaction
+        let transaction: Transaction = crate::schema::transactions::table
+            .find(original_transaction_id)
+            .for_update()
+            .first(conn)
+            .map_err(|e| DomainError::NotFound(format!("Original ACH transaction {} not found: {}", original_transaction_id, e)))?;
 
-    // 3. Handle financial reversal if necessary
-    // If it was an ACH Credit, funds might need to be returned to the source wallet.
-    // If it was an ACH Debit, the credit to the internal wallet might need reversal.
-    // TODO: Implement reversal logic carefully, considering idempotency.
+        // 2. Move to Returned; the state machine releases/reverses the funds exactly once
+        let update = TransitionUpdate {
+            metadata: Some(serde_json::json!({"ach_return": {"code": return_code, "reason": return_reason}})),
+            ..Default::default()
+        };
+        state_machine::apply_transition(conn, &transaction, TransactionStatus::Returned, update, "ACH_RETURN")?;
+        Ok::<_, DomainError>(())
+    })?;
 
     log::info!("Updated transaction {} status to Returned", original_transaction_id);
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    fn originator() -> NachaOriginator {
+        NachaOriginator {
+            odfi_routing_number: "021000021".to_string(),
+            odfi_name: "Example ODFI".to_string(),
+            company_id: "1234567890".to_string(),
+            company_name: "Elights Payouts".to_string(),
+        }
+    }
+
+    fn entry<'a>(routing_number: &'a str, amount: Decimal, trace_sequence: u32) -> NachaCreditEntry<'a> {
+        NachaCreditEntry {
+            routing_number,
+            account_number: "000123456789",
+            amount,
+            individual_id: "ROW-1",
+            individual_name: "Jane Doe",
+            trace_sequence,
+        }
+    }
+
+    fn build(entries: &[NachaCreditEntry]) -> NachaFile {
+        let created_at = Utc.with_ymd_and_hms(2025, 4, 21, 9, 30, 0).unwrap();
+        let effective = NaiveDate::from_ymd_opt(2025, 4, 22).unwrap();
+        build_credit_file(&originator(), entries, "PAYROLL", effective, created_at, 'A').unwrap()
+    }
+
+    #[test]
+    fn test_credit_file_records_are_94_chars_and_blocked() {
+        let file = build(&[entry("021000021", dec!(10.50), 1), entry("011000015", dec!(2500), 2)]);
+        let contents = file.to_file_string();
+        let records: Vec<&str> = contents.lines().collect();
+        assert_eq!(records.len(), 10); // 6 records padded to one block
+        assert!(records.iter().all(|r| r.len() == NACHA_RECORD_LENGTH));
+        assert!(records[0].starts_with("101 021000021"));
+        assert!(records[1].starts_with("5220"));
+        assert_eq!(&records[2][..12], "622021000021");
+        assert_eq!(records[6], "9".repeat(NACHA_RECORD_LENGTH));
+    }
+
+    #[test]
+    fn test_control_totals_and_trace_numbers() {
+        let file = build(&[entry("021000021", dec!(10.50), 1), entry("011000015", dec!(2500), 2)]);
+        assert_eq!(&file.entries[0][29..39], "0000001050");
+        assert_eq!(&file.entries[1][79..94], "021000020000002");
+        // Entry hash: 02100002 + 01100001
+        assert_eq!(&file.batch_controls[0][4..20], "0000020003200003");
+        assert_eq!(&file.batch_controls[0][32..44], "000000251050");
+        assert_eq!(&file.file_control[1..21], "00000100000100000002");
+    }
+
+    #[test]
+    fn test_rejects_amounts_that_do_not_fit_an_entry() {
+        let created_at = Utc.with_ymd_and_hms(2025, 4, 21, 9, 30, 0).unwrap();
+        let effective = NaiveDate::from_ymd_opt(2025, 4, 22).unwrap();
+        for amount in [dec!(0), dec!(1.005), dec!(100000000)] {
+            let result = build_credit_file(&originator(), &[entry("021000021", amount, 1)], "PAYROLL", effective, created_at, 'A');
+            assert!(matches!(result, Err(DomainError::Validation(_))), "{} should be rejected", amount);
+        }
+    }
+}
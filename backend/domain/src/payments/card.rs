@@ -1,34 +1,49 @@
 // /home/inno/elights_jobes-research/backend/domain/src/payments/card.rs
 use diesel::prelude::*;
-use crate::models::{Transaction, NewTransaction, Wallet, TransactionType, TransactionStatus, CardDetails as TxCardDetails, UpdateTransaction};
+use crate::models::{Transaction, NewTransaction, Wallet, TransactionType, TransactionStatus, CardDetails as TxCardDetails};
 use crate::error::DomainError;
 use crate::payments::validator::{validate_card_details, ValidationContext};
 use crate::payments::gateway::{PaymentGateway, PaymentGatewayRequest, PaymentGatewayResponse, PaymentIntent, PaymentMethodDetails}; // Use gateway trait
+use crate::payments::state_machine::{apply_transition, TransitionUpdate};
+use crate::ledger;
+use crate::services::idempotency::derive_gateway_idempotency_key;
+use chrono::{Duration, Utc};
 use rust_decimal::Decimal;
 use uuid::Uuid;
 
+/// A capture claim older than this is presumed abandoned and may be retried.
+pub const CAPTURE_LEASE_SECONDS: i64 = 120;
+
 // Note: Live card processing requires strict adherence to PCI DSS compliance standards.
 // Sensitive card data (full PAN, CVV) should NOT be stored in your database.
 // Typically, you only store the last 4 digits, expiry, card type, and a token from the payment gateway.
 
 /// Processes a card payment authorization.
 /// This contacts the payment gateway to authorize the amount on the cardholder's account.
+/// When the card is funded by an internal wallet, a successful authorization places a hold on it.
 pub async fn process_card_authorization(
     conn: &mut PgConnection,
     gateway: &dyn PaymentGateway, // Inject gateway implementation
     initiating_user_id: Uuid,
+    funding_wallet_id: Option<Uuid>, // Internal wallet behind the card, None for external cardholders
     wallet_to_charge_token: &str, // Gateway's token representing the card (NOT the raw card number)
     amount: Decimal,
     currency: &str, // ISO 4217
     description: &str,
     metadata: Option<serde_json::Value>,
+    idempotency_key: Option<&str>, // Client Idempotency-Key, forwarded to the gateway
 ) -> Result<Transaction, DomainError> {
     log::info!("Processing Card Authorization for amount {} {}", amount, currency);
 
+    // Fail fast if the funding wallet cannot cover the authorization (the hold re-checks under lock)
+    if let Some(wallet_id) = funding_wallet_id {
+        ledger::ensure_available(conn, wallet_id, amount)?;
+    }
+
     // 1. Create initial transaction record (Status: Pending/RequiresAction)
     let new_tx = NewTransaction {
         transaction_id: None,
-        debit_wallet_id: None, // External cardholder
+        debit_wallet_id: funding_wallet_id, // Held on authorization, debited on capture
         credit_wallet_id: None, // Funds not yet moved
         transaction_type: TransactionType::CardAuthorization.to_string().as_str(),
         status: TransactionStatus::Pending.to_string().as_str(),
@@ -37,8 +52,9 @@ pub async fn process_card_authorization(
         description: Some(description),
         external_ref_id: None, // Gateway reference will be added later
         metadata: metadata.clone(),
+        scheduled_run_id: None,
     };
-    let mut transaction: Transaction = diesel::insert_into(crate::schema::transactions::table)
+    let transaction: Transaction = diesel::insert_into(crate::schema::transactions::table)
         .values(&new_tx)
         .get_result(conn)?;
 
@@ -51,6 +67,11 @@ pub async fn process_card_authorization(
         description: Some(description.to_string()),
         customer_id: Some(initiating_user_id.to_string()), // Example customer ref
         metadata: metadata.clone(),
+        card_bin_country: metadata.as_ref()
+            .and_then(|m| m.get("card_bin_country"))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_uppercase()),
+        idempotency_key: Some(derive_gateway_idempotency_key(idempotency_key, transaction.transaction_id, "AUTH")),
     };
 
     // 3. Call the payment gateway
@@ -85,15 +106,9 @@ pub async fn process_card_authorization(
         // Store error message in transaction record? Or just log?
     }
 
-    let update_tx = UpdateTransaction {
-        status: Some(final_status.to_string().as_str()),
-        external_ref_id: external_ref,
-        metadata: Some(updated_metadata),
-        settlement_at: None,
-    };
-    transaction = diesel::update(crate::schema::transactions::table.find(transaction.transaction_id))
-        .set(&update_tx)
-        .get_result(conn)?;
+    // Pending -> Authorized places the hold on the funding wallet; Pending -> Failed has no effect
+    let update = TransitionUpdate { external_ref, settlement_at: None, metadata: Some(updated_metadata) };
+    let transaction = apply_transition(conn, &transaction, final_status.clone(), update, "CARD_GATEWAY")?;
 
     if final_status == TransactionStatus::Failed {
         // Return specific error if auth failed
@@ -107,41 +122,26 @@ pub async fn process_card_authorization(
 
 
 /// Processes a card payment capture.
-/// Captures funds previously authorized.
+/// Captures funds previously authorized, once: a partial capture posts the captured amount and frees the rest of
+/// the hold, closing the authorization. The authorization is claimed (Authorized -> Capturing) before the gateway
+/// is called, so concurrent captures cannot both reach it; the gateway key is derived from the authorization, so a
+/// retried capture (including one taking over a claim older than `CAPTURE_LEASE_SECONDS`) is deduplicated.
 pub async fn process_card_capture(
     conn: &mut PgConnection,
     gateway: &dyn PaymentGateway, // Inject gateway implementation
     authorization_transaction_id: Uuid, // ID of the original authorization transaction
     capture_amount: Option<Decimal>, // Optional: Capture less than authorized amount
+    idempotency_key: Option<&str>, // Client key of the capture request, if any
 ) -> Result<Transaction, DomainError> {
     log::info!("Processing Card Capture for Auth Tx ID: {}", authorization_transaction_id);
 
-    // 1. Find the original authorization transaction
-    let auth_tx: Transaction = crate::schema::transactions::table
-        .find(authorization_transaction_id)
-        .first(conn)
-        .map_err(|_| DomainError::NotFound(format!("Authorization transaction {} not found", authorization_transaction_id)))?;
-
-    // 2. Validate status and amount
-    if auth_tx.status != TransactionStatus::Authorized.to_string() {
-        return Err(DomainError::Validation("Cannot capture transaction that is not Authorized".to_string()));
-    }
-    let amount_to_capture = capture_amount.unwrap_or(auth_tx.amount);
-    if amount_to_capture <= Decimal::ZERO || amount_to_capture > auth_tx.amount {
-        return Err(DomainError::Validation("Invalid capture amount".to_string()));
-    }
+    // 1-2. Claim the authorization under a row lock, validating status and amount
+    let (auth_tx, amount_to_capture) = conn.transaction(|conn| claim_for_capture(conn, authorization_transaction_id, capture_amount))?;
     let gateway_ref = auth_tx.external_ref_id.as_deref()
         .ok_or_else(|| DomainError::Validation("Missing gateway reference on authorization transaction".to_string()))?;
 
-    // 3. Create Capture Transaction record (linked to Auth) - Optional, depends on model
-    // Or update the existing auth transaction status to Processing/Completed directly.
-    // For simplicity, let's update the existing one here. Mark as Processing first.
-    let mut transaction = diesel::update(crate::schema::transactions::table.find(auth_tx.transaction_id))
-         .set(crate::schema::transactions::status.eq(TransactionStatus::Processing.to_string()))
-         .get_result::<Transaction>(conn)?;
-
-
-    // 4. Prepare gateway request
+    // 3. Prepare gateway request
+    let capture_id = derive_gateway_idempotency_key(idempotency_key, auth_tx.transaction_id, "CAPTURE");
     let request = PaymentGatewayRequest {
         amount: amount_to_capture,
         currency: auth_tx.currency_code.clone(),
@@ -150,50 +150,110 @@ pub async fn process_card_capture(
         description: Some(format!("Capture for Auth {}", authorization_transaction_id)),
         customer_id: None, // Usually not needed for capture
         metadata: None, // Or pass specific capture metadata
+        card_bin_country: None, // Captures follow the authorizing gateway
+        idempotency_key: Some(capture_id.clone()),
     };
 
-    // 5. Call the payment gateway
+    // 4. Call the payment gateway; an error leaves the claim to lapse so the capture can be retried
     let gateway_response = gateway.submit_payment(request).await?;
 
-    // 6. Update transaction status based on response
-    let final_status;
-     let mut updated_metadata = transaction.metadata.clone().unwrap_or_else(|| serde_json::json!({}));
+    // 5. Update transaction status based on response
+     let mut updated_metadata = auth_tx.metadata.clone().unwrap_or_else(|| serde_json::json!({}));
      if let Some(details) = gateway_response.details {
          if let serde_json::Value::Object(mut map) = updated_metadata {
              map.insert("gateway_capture_details".to_string(), details);
              updated_metadata = serde_json::Value::Object(map);
          }
      }
+     if let serde_json::Value::Object(ref mut map) = updated_metadata {
+         map.insert("capture_id".to_string(), serde_json::json!(capture_id));
+     }
 
-    if gateway_response.success {
-         log::info!("Card capture successful. Gateway Ref: {}", gateway_response.gateway_transaction_id);
-         final_status = TransactionStatus::Completed; // Assume capture means funds moved
-         // TODO: Credit the appropriate internal wallet based on the original transaction context
-         // update_wallet_balance(conn, credit_wallet_id, amount_to_capture)?;
-    } else {
+    if !gateway_response.success {
          log::error!("Card capture failed. Gateway Ref: {}, Reason: {:?}",
              gateway_response.gateway_transaction_id, gateway_response.error_message);
-         final_status = TransactionStatus::Failed;
-         // Optionally revert status back from Processing if capture fails? Or keep as Failed.
+         // Capturing -> Authorized (hold kept) so the capture can be retried before the authorization lapses
+         conn.transaction(|conn| {
+             let auth_tx = lock_transaction(conn, auth_tx.transaction_id)?;
+             let update = TransitionUpdate { metadata: Some(updated_metadata), ..Default::default() };
+             apply_transition(conn, &auth_tx, TransactionStatus::Authorized, update, "CARD_GATEWAY")
+         })?;
+         return Err(DomainError::CardProcessing(
+             gateway_response.error_message.unwrap_or_else(|| "Capture failed".to_string())
+         ));
     }
+    log::info!("Card capture successful. Gateway Ref: {}", gateway_response.gateway_transaction_id);
+
+    conn.transaction(|conn| {
+        // Partial capture: the transaction is posted for the captured amount, the rest of the hold is freed
+        let mut auth_tx = lock_transaction(conn, auth_tx.transaction_id)?;
+        if amount_to_capture < auth_tx.amount {
+            if let serde_json::Value::Object(ref mut map) = updated_metadata {
+                map.insert("authorized_amount".to_string(), serde_json::json!(auth_tx.amount.to_string()));
+            }
+            auth_tx = diesel::update(crate::schema::transactions::table.find(auth_tx.transaction_id))
+                .set(crate::schema::transactions::amount.eq(crate::utils::decimal_to_bigdecimal(amount_to_capture)))
+                .get_result(conn)?;
+        }
 
-    let update_tx = UpdateTransaction {
-        status: Some(final_status.to_string().as_str()),
-        external_ref_id: Some(gateway_response.gateway_transaction_id.as_str()), // Capture might have new ref
-        metadata: Some(updated_metadata),
-        settlement_at: if final_status == TransactionStatus::Completed { Some(Utc::now()) } else { None }, // Approximate settlement
-    };
-    transaction = diesel::update(crate::schema::transactions::table.find(transaction.transaction_id))
-        .set(&update_tx)
-        .get_result(conn)?;
+        // Capturing -> Completed converts the hold into the ledger posting
+        let update = TransitionUpdate {
+            external_ref: Some(gateway_response.gateway_transaction_id.as_str()), // Capture might have new ref
+            settlement_at: Some(Utc::now()), // Approximate settlement
+            metadata: Some(updated_metadata),
+        };
+        apply_transition(conn, &auth_tx, TransactionStatus::Completed, update, "CARD_GATEWAY")
+    })
+}
 
-     if final_status == TransactionStatus::Failed {
-        return Err(DomainError::CardProcessing(
-            gateway_response.error_message.unwrap_or_else(|| "Capture failed".to_string())
-        ));
+fn lock_transaction(conn: &mut PgConnection, transaction_id: Uuid) -> Result<Transaction, DomainError> {
+    crate::schema::transactions::table
+        .find(transaction_id)
+        .for_update()
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("Authorization transaction {} not found", transaction_id)))
+}
+
+/// Moves an Authorized card transaction to Capturing and returns it with the amount to capture. A capture
+/// claim older than `CAPTURE_LEASE_SECONDS` (the capturing process died) is taken over for the same amount.
+fn claim_for_capture(
+    conn: &mut PgConnection,
+    authorization_transaction_id: Uuid,
+    capture_amount: Option<Decimal>,
+) -> Result<(Transaction, Decimal), DomainError> {
+    let auth_tx = lock_transaction(conn, authorization_transaction_id)?;
+    let in_flight = auth_tx.metadata.as_ref()
+        .and_then(|m| m.get("capture_amount"))
+        .and_then(|v| v.as_str())
+        .and_then(|v| v.parse::<Decimal>().ok());
+
+    let stale = Utc::now() - Duration::seconds(CAPTURE_LEASE_SECONDS);
+    if auth_tx.status == TransactionStatus::Capturing.as_str() && auth_tx.updated_at >= stale {
+        return Err(DomainError::IdempotencyConflict("A capture of this authorization is already in progress".to_string()));
+    }
+    if auth_tx.status != TransactionStatus::Authorized.as_str() && auth_tx.status != TransactionStatus::Capturing.as_str() {
+        return Err(DomainError::Validation("Cannot capture transaction that is not Authorized".to_string()));
     }
 
-    Ok(transaction)
+    let amount_to_capture = capture_amount.unwrap_or(auth_tx.amount);
+    if amount_to_capture <= Decimal::ZERO || amount_to_capture > auth_tx.amount {
+        return Err(DomainError::Validation("Invalid capture amount".to_string()));
+    }
+    if let Some(in_flight) = in_flight.filter(|_| auth_tx.status == TransactionStatus::Capturing.as_str()) {
+        // The abandoned request may have reached the gateway; a retry replays it under the same key
+        if in_flight != amount_to_capture {
+            return Err(DomainError::Validation(format!("An interrupted capture of {} must be retried for the same amount", in_flight)));
+        }
+    }
+
+    // Re-claiming a stale capture rewrites the metadata, which refreshes updated_at and so the lease
+    let update = TransitionUpdate {
+        metadata: Some(serde_json::json!({ "capture_amount": amount_to_capture.to_string() })),
+        ..Default::default()
+    };
+    let claimed = apply_transition(conn, &auth_tx, TransactionStatus::Capturing, update, "CARD_GATEWAY")?;
+    Ok((claimed, amount_to_capture))
 }
 
 /// Processes a card payment refund.
@@ -236,13 +296,14 @@ pub async fn process_card_refund(
         description: Some(reason.unwrap_or("Card Refund")),
         external_ref_id: None, // Will get gateway ref for refund
         metadata: Some(serde_json::json!({ "original_transaction_id": capture_transaction_id })),
+        scheduled_run_id: None,
     };
-    let mut transaction: Transaction = diesel::insert_into(crate::schema::transactions::table)
+    let transaction: Transaction = diesel::insert_into(crate::schema::transactions::table)
         .values(&new_tx)
         .get_result(conn)?;
 
-    // TODO: Implement Debit from internal wallet (part of DB transaction)
-    // update_wallet_balance(conn, capture_tx.credit_wallet_id.unwrap(), -amount_to_refund)?;
+    // Hold the refund on the merchant wallet before calling the gateway (Pending -> Processing)
+    let transaction = apply_transition(conn, &transaction, TransactionStatus::Processing, TransitionUpdate::default(), "CARD_GATEWAY")?;
 
     // 4. Prepare gateway request
      let request = PaymentGatewayRequest {
@@ -253,13 +314,14 @@ pub async fn process_card_refund(
         description: Some(reason.unwrap_or("Refund").to_string()),
         customer_id: None,
         metadata: None,
+        card_bin_country: None, // Refunds follow the capturing gateway
+        idempotency_key: Some(derive_gateway_idempotency_key(None, transaction.transaction_id, "REFUND")),
     };
 
     // 5. Call the payment gateway
     let gateway_response = gateway.submit_payment(request).await?;
 
     // 6. Update refund transaction status
-    let final_status;
     let mut updated_metadata = transaction.metadata.clone().unwrap_or_else(|| serde_json::json!({}));
      if let Some(details) = gateway_response.details {
          if let serde_json::Value::Object(mut map) = updated_metadata {
@@ -268,32 +330,22 @@ pub async fn process_card_refund(
          }
      }
 
-    if gateway_response.success {
-         log::info!("Card refund successful. Gateway Ref: {}", gateway_response.gateway_transaction_id);
-         final_status = TransactionStatus::Completed;
-    } else {
+    let external_ref = gateway_response.gateway_transaction_id.clone();
+    if !gateway_response.success {
          log::error!("Card refund failed. Gateway Ref: {}, Reason: {:?}",
              gateway_response.gateway_transaction_id, gateway_response.error_message);
-         final_status = TransactionStatus::Failed;
-         // TODO: Handle refund failure - Re-credit internal wallet?
-         // update_wallet_balance(conn, capture_tx.credit_wallet_id.unwrap(), amount_to_refund)?; // Reversal
+         // Processing -> Failed releases the hold
+         let update = TransitionUpdate { external_ref: Some(&external_ref), settlement_at: None, metadata: Some(updated_metadata) };
+         apply_transition(conn, &transaction, TransactionStatus::Failed, update, "CARD_GATEWAY")?;
+         return Err(DomainError::CardProcessing(
+             gateway_response.error_message.unwrap_or_else(|| "Refund failed".to_string())
+         ));
     }
+    log::info!("Card refund successful. Gateway Ref: {}", gateway_response.gateway_transaction_id);
 
-     let update_tx = UpdateTransaction {
-        status: Some(final_status.to_string().as_str()),
-        external_ref_id: Some(gateway_response.gateway_transaction_id.as_str()),
-        metadata: Some(updated_metadata),
-        settlement_at: if final_status == TransactionStatus::Completed { Some(Utc::now()) } else { None }, // Approximate settlement
-    };
-    transaction = diesel::update(crate::schema::transactions::table.find(transaction.transaction_id))
-        .set(&update_tx)
-        .get_result(conn)?;
-
-     if final_status == TransactionStatus::Failed {
-        return Err(DomainError::CardProcessing(
-            gateway_response.error_message.unwrap_or_else(|| "Refund failed".to_string())
-        ));
-    }
-
-    Ok(transaction)
-}
\ No newline at end of file
+    // Processing -> Submitted -> Completed converts the hold into the ledger posting
+    let update = TransitionUpdate { external_ref: Some(&external_ref), settlement_at: None, metadata: Some(updated_metadata) };
+    let transaction = apply_transition(conn, &transaction, TransactionStatus::Submitted, update, "CARD_GATEWAY")?;
+    let update = TransitionUpdate { settlement_at: Some(Utc::now()), ..Default::default() }; // Approximate settlement
+    apply_transition(conn, &transaction, TransactionStatus::Completed, update, "CARD_GATEWAY")
+}
@@ -3,6 +3,7 @@ use diesel::prelude::*;
 use crate::models::{Transaction, NewTransaction, Wallet, TransactionType, TransactionStatus, CheckDetails, UpdateTransaction};
 use crate::error::DomainError;
 use crate::payments::validator::{validate_check_details, ValidationContext};
+use crate::payments::state_machine::{apply_transition, TransitionUpdate};
 use rust_decimal::Decimal;
 use uuid::Uuid;
 
@@ -38,8 +39,9 @@ pub async fn process_check_deposit(
         description: Some("Check Deposit".to_string()),
         external_ref_id: None, // Clearinghouse reference later
         metadata: metadata.clone(), // Store check details in metadata?
+        scheduled_run_id: None,
     };
-    let mut transaction: Transaction = diesel::insert_into(crate::schema::transactions::table)
+    let transaction: Transaction = diesel::insert_into(crate::schema::transactions::table)
         .values(&new_tx)
         .get_result(conn)?;
 
@@ -55,12 +57,11 @@ pub async fn process_check_deposit(
     log::info!("Submitting ICL for check deposit {}...", transaction.transaction_id);
 
     // 6. Update transaction status (e.g., Submitted)
-     transaction = diesel::update(crate::schema::transactions::table.find(transaction.transaction_id))
-        .set(crate::schema::transactions::status.eq(TransactionStatus::Submitted.to_string()))
-        .get_result(conn)?;
+    let transaction = apply_transition(conn, &transaction, TransactionStatus::Submitted, TransitionUpdate::default(), "CHECK_DEPOSIT")?;
 
-    // Note: Actual crediting often happens after clearing, potentially days later,
-    // and checks can be returned. Need webhook/callback or polling mechanism for updates.
+    // Note: Crediting happens when the clearinghouse settles the item (Submitted -> Settled). The credit
+    // arrives with a CHECK_CLEARING hold, so the funds show in the ledger balance but not the available
+    // balance until the hold lapses; a return before that reverses the credit and drops the hold.
 
     Ok(transaction)
 }
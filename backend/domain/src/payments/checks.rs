@@ -0,0 +1,291 @@
+// /home/inno/elights_jobes-research/backend/domain/src/payments/checks.rs
+// Checks an outbound payment goes through before its funds are held and its external leg is queued:
+// saved-payee rules, transaction limits, sanctions screening, fraud rules and approvals. Single payments
+// (`PaymentProcessor`), payout batch rows and crypto withdrawals share them; a check without its policy is skipped.
+use crate::approvals::{self, ApprovalPolicySet, PaymentFacts, PendingDispatch};
+use crate::beneficiaries::{self, BeneficiaryIdentity, CheckedPayee, CoolingOffLimits};
+use crate::error::DomainError;
+use crate::fees::FeeRail;
+use crate::fraud::{self, DeviceContext, FraudCheck, FraudDetectionContext, FraudRuleSet};
+use crate::limits::{self, LimitPolicy};
+use crate::models::{AchDetails, Transaction, TransactionType, User, Wallet, WireDetails};
+use crate::sanctions::{self, SanctionsScreener, ScreeningHit, ScreeningParty};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+/// Policies in force for outbound payments; `None` skips the check.
+#[derive(Clone, Copy, Default)]
+pub struct OutboundChecks<'a> {
+    pub approval_policies: Option<&'a ApprovalPolicySet>, // Maker-checker approvals
+    pub beneficiary_limits: Option<&'a CoolingOffLimits>, // Saved payees must be verified, cooling-off limits
+    pub limit_policy: Option<&'a LimitPolicy>, // Per-transaction and rolling limits
+    pub sanctions_screener: Option<&'a SanctionsScreener>, // Watchlist screening of wire and crypto parties
+    pub fraud_rules: Option<&'a FraudRuleSet>, // Fraud scoring
+}
+
+/// The payment being checked.
+pub struct OutboundPayment<'p> {
+    pub user_id: Uuid, // Initiator
+    pub source_wallet: &'p Wallet,
+    pub payment_type: &'p TransactionType,
+    pub amount: Decimal, // As requested, before a beneficiary-borne fee comes out of it
+    pub currency: &'p str,
+    pub ach_details: Option<&'p AchDetails>,
+    pub wire_details: Option<&'p WireDetails>,
+    pub crypto_address: Option<&'p str>,
+    pub device: Option<&'p DeviceContext>, // None for scheduled and batch payments
+}
+
+impl OutboundPayment<'_> {
+    /// Parties screened against the watchlists (wire parties, crypto destination; none for ACH).
+    pub fn screening_parties(&self) -> Vec<ScreeningParty> {
+        sanctions::parties_for_payment(self.payment_type, self.wire_details, self.crypto_address)
+    }
+}
+
+/// Result of `OutboundChecks::run`.
+pub enum CheckOutcome {
+    /// Parked in RequiresAction (compliance case, fraud review, approval) or Failed (blocked by fraud rules).
+    /// Nothing is held, charged or queued; `dispatch` is replayed when the payment is released.
+    Stopped(Transaction),
+    /// Hold and queue the payment, merging `metadata` (the fraud evaluation) when it moves to Processing.
+    Proceed { metadata: Option<serde_json::Value> },
+}
+
+impl<'a> OutboundChecks<'a> {
+    /// Matches the payee to the payer's saved beneficiaries (verified, within cooling-off limits; unsaved payees
+    /// always within them). Made before the transaction exists; the payee returned is recorded on it
+    /// (`CheckedPayee::metadata`).
+    pub fn check_payee(&self, conn: &mut PgConnection, payment: &OutboundPayment, now: DateTime<Utc>) -> Result<Option<CheckedPayee>, DomainError> {
+        let Some(limits) = self.beneficiary_limits else {
+            return Ok(None);
+        };
+        let payee = BeneficiaryIdentity::for_payment(payment.payment_type, payment.ach_details, payment.wire_details, payment.crypto_address)?;
+        beneficiaries::check_payment(conn, payment.user_id, payee.as_ref(), payment.amount, limits, now)
+    }
+
+    /// Checks the payment against the transaction limits and counts its requested amount in the rolling
+    /// windows. A breach fails with `LimitExceeded`; the usage is released when the payment fails, is
+    /// cancelled or expires (`state_machine::apply_transition`).
+    pub fn consume_limits(&self, conn: &mut PgConnection, payment: &OutboundPayment, transaction: &Transaction, now: DateTime<Utc>) -> Result<(), DomainError> {
+        let Some(policy) = self.limit_policy else {
+            return Ok(());
+        };
+        let context = limits::payment_context(conn, payment.user_id, payment.source_wallet, payment.payment_type, payment.amount)?;
+        limits::check_and_consume(conn, policy, &context, transaction.transaction_id, now)?;
+        Ok(())
+    }
+
+    /// The payment's parties and their watchlist hits, when there is at least one hit.
+    pub fn sanctions_hits(&self, payment: &OutboundPayment) -> Option<(Vec<ScreeningParty>, Vec<ScreeningHit>)> {
+        let screener = self.sanctions_screener?;
+        let parties = payment.screening_parties();
+        let hits = screener.screen(&parties);
+        (!hits.is_empty()).then_some((parties, hits))
+    }
+
+    /// Stops a payment with screening hits: opens a compliance case and moves the payment to RequiresAction.
+    /// Returns the stopped payment, `None` when nothing was hit.
+    pub fn screen_sanctions(
+        &self,
+        conn: &mut PgConnection,
+        payment: &OutboundPayment,
+        transaction: &Transaction,
+        dispatch: &PendingDispatch,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Transaction>, DomainError> {
+        let (Some(screener), Some((parties, hits))) = (self.sanctions_screener, self.sanctions_hits(payment)) else {
+            return Ok(None);
+        };
+        let stopped = sanctions::open_case(conn, transaction, payment.user_id, &parties, &hits, screener.lists(), dispatch, now)?;
+        Ok(Some(stopped))
+    }
+
+    /// Parks a payment matching an approval policy until it is approved (maker-checker). Returns the parked
+    /// payment, `None` when no approval is needed.
+    pub fn require_approval(
+        &self,
+        conn: &mut PgConnection,
+        payment: &OutboundPayment,
+        transaction: &Transaction,
+        dispatch: &PendingDispatch,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Transaction>, DomainError> {
+        let Some(policies) = self.approval_policies else {
+            return Ok(None);
+        };
+        let new_beneficiary = policies.uses_new_beneficiary()
+            && beneficiaries::is_new_beneficiary(conn, transaction, now)?;
+        let facts = PaymentFacts {
+            rail: FeeRail::for_transaction_type(payment.payment_type),
+            amount: payment.amount,
+            currency: payment.currency,
+            destination_country: approvals::destination_country(payment.payment_type, payment.ach_details, payment.wire_details),
+            new_beneficiary,
+        };
+        if let Some(requirement) = policies.evaluate(&facts)? {
+            let stopped = approvals::request_approval(conn, transaction, payment.user_id, &requirement, dispatch, now)?;
+            return Ok(Some(stopped));
+        }
+        Ok(None)
+    }
+
+    /// Runs the checks on the Pending `transaction`, in order: limits (a breach fails with `LimitExceeded`
+    /// and rolls the payment back), sanctions, fraud, approvals. The first one that stops the payment wins.
+    pub fn run(
+        &self,
+        conn: &mut PgConnection,
+        payment: &OutboundPayment,
+        transaction: &Transaction,
+        dispatch: &PendingDispatch,
+        now: DateTime<Utc>,
+    ) -> Result<CheckOutcome, DomainError> {
+        self.consume_limits(conn, payment, transaction, now)?;
+
+        // Sanctions screening: a hit stops the payment for compliance review, before approvals
+        if let Some(stopped) = self.screen_sanctions(conn, payment, transaction, dispatch, now)? {
+            return Ok(CheckOutcome::Stopped(stopped));
+        }
+
+        // Fraud rules: a flagged payment waits for an analyst, a blocked one fails; every evaluation is recorded
+        let mut fraud_metadata = None;
+        if let Some(rules) = self.fraud_rules {
+            let user: Option<User> = crate::schema::users::table.find(payment.user_id).first(conn).optional()?;
+            let destination_country = approvals::destination_country(payment.payment_type, payment.ach_details, payment.wire_details);
+            let context = FraudDetectionContext {
+                transaction,
+                source_wallet: Some(payment.source_wallet),
+                user: user.as_ref(),
+                device: payment.device,
+                destination_country: destination_country.as_deref(),
+                destination_address: payment.crypto_address,
+            };
+            match fraud::screen_payment(conn, rules, &context, payment.user_id, dispatch, now)? {
+                FraudCheck::Stopped(stopped) => return Ok(CheckOutcome::Stopped(stopped)),
+                FraudCheck::Proceed { metadata } => fraud_metadata = Some(metadata),
+            }
+        }
+
+        // Second pair of eyes: matching payments wait for approval before anything is held or queued
+        if let Some(stopped) = self.require_approval(conn, payment, transaction, dispatch, now)? {
+            return Ok(CheckOutcome::Stopped(stopped));
+        }
+
+        Ok(CheckOutcome::Proceed { metadata: fraud_metadata })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payments::payout_batch::{validate_row, RawPayoutRow};
+    use crate::sanctions::{HitKind, WatchlistEntry, WatchlistSource};
+    use rust_decimal_macros::dec;
+
+    const SANCTIONED_ADDRESS: &str = "0x7F367cc41522ce07553e823bf3be79a889debe1b";
+
+    fn screener() -> SanctionsScreener {
+        let entries = vec![
+            (
+                WatchlistSource::EuConsolidated,
+                WatchlistEntry {
+                    entry_id: "13".to_string(),
+                    entity_type: "person".to_string(),
+                    names: vec!["Vladimir Vladimirovich PUTIN".to_string()],
+                    ..Default::default()
+                },
+            ),
+            (
+                WatchlistSource::OfacSdn,
+                WatchlistEntry {
+                    entry_id: "9001".to_string(),
+                    names: vec!["Ivan PETROV".to_string()],
+                    crypto_addresses: vec![SANCTIONED_ADDRESS.to_string()],
+                    ..Default::default()
+                },
+            ),
+        ];
+        SanctionsScreener::from_entries(entries, 0.90, &[])
+    }
+
+    fn wallet(currency: &str) -> Wallet {
+        Wallet {
+            wallet_id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            wallet_type: "Fiat".to_string(),
+            currency_code: currency.to_string(),
+            balance: dec!(10000),
+            bank_name: None,
+            account_number_hash: None,
+            iban_hash: None,
+            bic_swift: None,
+            routing_number_hash: None,
+            address: None,
+            address_index: None,
+            status: "Active".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn payment<'p>(wallet: &'p Wallet, payment_type: &'p TransactionType, amount: Decimal) -> OutboundPayment<'p> {
+        OutboundPayment {
+            user_id: wallet.user_id,
+            source_wallet: wallet,
+            payment_type,
+            amount,
+            currency: &wallet.currency_code,
+            ach_details: None,
+            wire_details: None,
+            crypto_address: None,
+            device: None,
+        }
+    }
+
+    #[test]
+    fn test_payout_batch_rows_are_screened() {
+        let screener = screener();
+        let checks = OutboundChecks { sanctions_screener: Some(&screener), ..Default::default() };
+        let wallet = wallet("EUR");
+
+        let row = RawPayoutRow {
+            payment_type: Some("WIRE".to_string()),
+            amount: Some("2500".to_string()),
+            beneficiary_name: Some("PUTIN, Vladimir".to_string()),
+            account_number: Some("DE89370400440532013000".to_string()),
+            swift_bic: Some("DEUTDEFF".to_string()),
+            ..Default::default()
+        };
+        let instruction = validate_row(&row, "EUR").unwrap();
+        let mut wire = payment(&wallet, &instruction.payment_type, instruction.amount);
+        wire.wire_details = instruction.wire_details.as_ref();
+        let (_, hits) = checks.sanctions_hits(&wire).expect("sanctioned beneficiary");
+        assert_eq!(hits[0].entry_id.as_deref(), Some("13"));
+
+        let mut clean = instruction.wire_details.clone().unwrap();
+        clean.beneficiary_name = "Acme GmbH".to_string();
+        wire.wire_details = Some(&clean);
+        assert!(checks.sanctions_hits(&wire).is_none());
+        assert!(OutboundChecks::default().sanctions_hits(&wire).is_none()); // Screening disabled
+    }
+
+    #[test]
+    fn test_crypto_withdrawals_are_screened() {
+        let screener = screener();
+        let checks = OutboundChecks { sanctions_screener: Some(&screener), ..Default::default() };
+        let wallet = wallet("BTC");
+        let send = TransactionType::CryptoBtcSend;
+
+        let mut withdrawal = payment(&wallet, &send, dec!(0.5));
+        withdrawal.crypto_address = Some(SANCTIONED_ADDRESS);
+        let (parties, hits) = checks.sanctions_hits(&withdrawal).expect("sanctioned address");
+        assert_eq!(parties.len(), 1);
+        assert_eq!(hits[0].kind, HitKind::CryptoAddress);
+        assert_eq!(hits[0].entry_id.as_deref(), Some("9001"));
+
+        withdrawal.crypto_address = Some("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq");
+        assert!(checks.sanctions_hits(&withdrawal).is_none());
+    }
+}
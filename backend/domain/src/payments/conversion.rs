@@ -0,0 +1,305 @@
+// /home/inno/elights_jobes-research/backend/domain/src/payments/conversion.rs
+// Currency conversion between two wallets of the same user, in two steps:
+// 1. `lock_quote` fixes the customer rate (mid rate minus our spread) and the amounts for a short time.
+// 2. `execute_quote` books the conversion atomically as two linked Conversion transactions:
+//      SELL leg: Dr source wallet / Cr FX_POSITION:<from>
+//      BUY leg:  Dr FX_POSITION:<to> / Cr target wallet
+//    plus the conversion fee from the fee schedule, charged to the source wallet.
+// An expired quote is rejected; the customer has to lock a new rate.
+use crate::error::DomainError;
+use crate::fees::{self, minor_units, ChargeBearer, FeeQuote, FeeRail, FeeSchedule};
+use crate::ledger;
+use crate::models::{
+    AuditOutcome, AuditTargetType, FxQuote, FxQuoteStatus, NewFxQuote, NewTransaction, Transaction, TransactionStatus,
+    TransactionType, Wallet, WalletStatus,
+};
+use crate::payments::state_machine::{self, TransitionUpdate};
+use crate::security::audit;
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::Serialize;
+use serde_json::json;
+use uuid::Uuid;
+
+/// How long a locked rate stays valid when the caller does not say.
+pub const DEFAULT_QUOTE_TTL_SECONDS: i64 = 30;
+/// Upper bound on the spread, guards against a misconfigured margin (10%).
+pub const MAX_SPREAD_BPS: i32 = 1_000;
+
+const BPS_PER_UNIT: i64 = 10_000;
+
+/// Customer rate: the mid rate less `spread_bps` basis points.
+pub fn apply_spread(mid_rate: Decimal, spread_bps: i32) -> Decimal {
+    mid_rate * (Decimal::from(BPS_PER_UNIT - spread_bps as i64) / Decimal::from(BPS_PER_UNIT))
+}
+
+/// Amount credited for `amount` at `rate`, rounded down to the target currency's minor unit.
+pub fn converted_amount(amount: Decimal, rate: Decimal, to_currency: &str) -> Decimal {
+    (amount * rate).round_dp_with_strategy(minor_units(to_currency), RoundingStrategy::ToZero)
+}
+
+/// Input for `lock_quote`. The mid rate comes from the rate service, which is async and lives outside the domain.
+#[derive(Debug, Clone)]
+pub struct LockQuote<'a> {
+    pub user_id: Uuid,
+    pub source_wallet_id: Uuid,
+    pub target_wallet_id: Uuid,
+    pub source_amount: Decimal, // In the source wallet's currency, before the fee
+    pub mid_rate: Decimal, // 1 unit of source currency in target currency
+    pub spread_bps: i32,
+    pub ttl: Duration,
+    pub rate_source: Option<&'a str>,
+    pub fee_schedule: Option<&'a FeeSchedule>,
+}
+
+/// The booked conversion.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversionOutcome {
+    pub quote: FxQuote,
+    pub debit_leg: Transaction, // SELL, source currency
+    pub credit_leg: Transaction, // BUY, target currency
+    pub fee: Option<Transaction>,
+}
+
+/// Loads a wallet of `user_id` that can take part in a conversion.
+fn owned_active_wallet(conn: &mut PgConnection, wallet_id: Uuid, user_id: Uuid) -> Result<Wallet, DomainError> {
+    use crate::schema::wallets::dsl as w;
+    let wallet: Wallet = w::wallets
+        .filter(w::wallet_id.eq(wallet_id))
+        .filter(w::user_id.eq(user_id)) // Conversions only move funds between the caller's own wallets
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("Wallet {} not found", wallet_id)))?;
+    if wallet.status != WalletStatus::Active.to_string() {
+        return Err(DomainError::Validation(format!("Wallet {} is not active", wallet_id)));
+    }
+    Ok(wallet)
+}
+
+/// Locks a rate for converting `source_amount` from the source wallet into the target wallet.
+pub fn lock_quote(conn: &mut PgConnection, request: LockQuote<'_>) -> Result<FxQuote, DomainError> {
+    if request.source_amount <= Decimal::ZERO {
+        return Err(DomainError::Validation("Amount must be positive".to_string()));
+    }
+    if request.mid_rate <= Decimal::ZERO {
+        return Err(DomainError::ExternalService(format!("Unusable FX rate {}", request.mid_rate)));
+    }
+    if !(0..=MAX_SPREAD_BPS).contains(&request.spread_bps) {
+        return Err(DomainError::Configuration(format!("FX spread {} bps out of range", request.spread_bps)));
+    }
+    if request.ttl <= Duration::zero() {
+        return Err(DomainError::Configuration("FX quote lifetime must be positive".to_string()));
+    }
+    if request.source_wallet_id == request.target_wallet_id {
+        return Err(DomainError::Validation("Source and target wallet must differ".to_string()));
+    }
+
+    conn.transaction(|conn| {
+        let source = owned_active_wallet(conn, request.source_wallet_id, request.user_id)?;
+        let target = owned_active_wallet(conn, request.target_wallet_id, request.user_id)?;
+        if source.currency_code == target.currency_code {
+            return Err(DomainError::Validation(format!("Both wallets hold {}, nothing to convert", source.currency_code)));
+        }
+
+        let applied_rate = apply_spread(request.mid_rate, request.spread_bps);
+        let target_amount = converted_amount(request.source_amount, applied_rate, &target.currency_code);
+        if target_amount <= Decimal::ZERO {
+            return Err(DomainError::Validation("Amount too small to convert".to_string()));
+        }
+
+        // Conversion fee, paid on top in the source currency
+        let fee_quote = match request.fee_schedule {
+            Some(schedule) => fees::quote_for_user(
+                conn, schedule, request.user_id, &TransactionType::Conversion,
+                request.source_amount, &source.currency_code, ChargeBearer::Our,
+            )?,
+            None => None,
+        };
+        let fee_amount = fee_quote.as_ref().map(|q| q.fee_amount).unwrap_or(Decimal::ZERO);
+
+        // Fail early rather than at execution if the funds are not there now
+        ledger::ensure_available(conn, source.wallet_id, request.source_amount + fee_amount)?;
+
+        let new_quote = NewFxQuote {
+            user_id: request.user_id,
+            source_wallet_id: source.wallet_id,
+            target_wallet_id: target.wallet_id,
+            from_currency: &source.currency_code,
+            to_currency: &target.currency_code,
+            source_amount: request.source_amount,
+            mid_rate: request.mid_rate,
+            spread_bps: request.spread_bps,
+            applied_rate,
+            target_amount,
+            fee_amount,
+            fee_rule_id: fee_quote.as_ref().and_then(|q| q.rule_id.as_deref()),
+            rate_source: request.rate_source,
+            status: FxQuoteStatus::Open.as_str(),
+            expires_at: Utc::now() + request.ttl,
+        };
+        let quote: FxQuote = diesel::insert_into(crate::schema::fx_quotes::table)
+            .values(&new_quote)
+            .get_result(conn)?;
+        log::info!("Locked FX quote {}: {} {} -> {} {} at {} (mid {}, spread {} bps), expires {}",
+            quote.quote_id, quote.source_amount, quote.from_currency, quote.target_amount, quote.to_currency,
+            quote.applied_rate, quote.mid_rate, quote.spread_bps, quote.expires_at);
+        Ok(quote)
+    })
+}
+
+fn insert_leg(
+    conn: &mut PgConnection,
+    quote: &FxQuote,
+    leg: &str,
+    debit_wallet_id: Option<Uuid>,
+    credit_wallet_id: Option<Uuid>,
+    amount: Decimal,
+    currency: &str,
+    linked_transaction_id: Option<Uuid>,
+) -> Result<Transaction, DomainError> {
+    let description = format!("FX {} {} -> {} ({})", leg, quote.from_currency, quote.to_currency, quote.quote_id);
+    let new_tx = NewTransaction {
+        transaction_id: None,
+        debit_wallet_id,
+        credit_wallet_id,
+        transaction_type: TransactionType::Conversion.to_string().as_str(),
+        status: TransactionStatus::Pending.to_string().as_str(),
+        amount,
+        currency_code: currency,
+        description: Some(&description),
+        external_ref_id: None,
+        metadata: Some(json!({
+            "fx_quote_id": quote.quote_id,
+            "leg": leg,
+            "linked_transaction_id": linked_transaction_id,
+            "mid_rate": quote.mid_rate.to_string(),
+            "applied_rate": quote.applied_rate.to_string(),
+            "spread_bps": quote.spread_bps,
+        })),
+        scheduled_run_id: None,
+    };
+    let tx: Transaction = diesel::insert_into(crate::schema::transactions::table)
+        .values(&new_tx)
+        .get_result(conn)?;
+    Ok(tx)
+}
+
+/// Books a locked quote. Both legs, the fee and the quote update commit together or not at all.
+pub fn execute_quote(
+    conn: &mut PgConnection,
+    quote_id: Uuid,
+    user_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<ConversionOutcome, DomainError> {
+    conn.transaction(|conn| {
+        use crate::schema::fx_quotes::dsl as q;
+
+        let quote: FxQuote = q::fx_quotes
+            .find(quote_id)
+            .for_update() // Two concurrent executions of one quote serialize here
+            .first(conn)
+            .optional()?
+            .filter(|quote: &FxQuote| quote.user_id == user_id)
+            .ok_or_else(|| DomainError::NotFound(format!("FX quote {} not found", quote_id)))?;
+
+        if quote.status == FxQuoteStatus::Executed.as_str() {
+            return Err(DomainError::Validation(format!("FX quote {} was already executed", quote_id)));
+        }
+        if quote.status == FxQuoteStatus::Expired.as_str() || now >= quote.expires_at {
+            return Err(DomainError::QuoteExpired(quote_id));
+        }
+
+        // Re-check the wallets: they may have been frozen since the quote was locked
+        owned_active_wallet(conn, quote.source_wallet_id, user_id)?;
+        owned_active_wallet(conn, quote.target_wallet_id, user_id)?;
+        ledger::ensure_available(conn, quote.source_wallet_id, quote.source_amount + quote.fee_amount)?;
+
+        let sell = insert_leg(conn, &quote, "SELL", Some(quote.source_wallet_id), None, quote.source_amount, &quote.from_currency, None)?;
+        let buy = insert_leg(conn, &quote, "BUY", None, Some(quote.target_wallet_id), quote.target_amount, &quote.to_currency, Some(sell.transaction_id))?;
+        let sell = state_machine::apply_transition(conn, &sell, TransactionStatus::Completed, TransitionUpdate {
+            metadata: Some(json!({"linked_transaction_id": buy.transaction_id})),
+            ..Default::default()
+        }, "FX_CONVERSION")?;
+        let buy = state_machine::apply_transition(conn, &buy, TransactionStatus::Completed, TransitionUpdate::default(), "FX_CONVERSION")?;
+
+        let fee = if quote.fee_amount > Decimal::ZERO {
+            let fee_quote = FeeQuote {
+                rule_id: quote.fee_rule_id.clone(),
+                rail: FeeRail::Conversion,
+                currency: quote.from_currency.clone(),
+                amount: quote.source_amount,
+                fee_amount: quote.fee_amount,
+                charge_bearer: ChargeBearer::Our,
+                total_debit: quote.source_amount + quote.fee_amount,
+                net_amount: quote.source_amount,
+            };
+            fees::post_fee(conn, &sell, &fee_quote, "FX_CONVERSION")?
+        } else {
+            None
+        };
+
+        let quote: FxQuote = diesel::update(q::fx_quotes.find(quote_id))
+            .set((
+                q::status.eq(FxQuoteStatus::Executed.as_str()),
+                q::executed_at.eq(Some(now)),
+                q::debit_transaction_id.eq(Some(sell.transaction_id)),
+                q::credit_transaction_id.eq(Some(buy.transaction_id)),
+            ))
+            .get_result(conn)?;
+
+        audit::log_db_audit_event(
+            conn,
+            Some(user_id),
+            &user_id.to_string(),
+            "EXECUTE_FX_CONVERSION",
+            Some(AuditTargetType::Transaction),
+            Some(&sell.transaction_id.to_string()),
+            AuditOutcome::Success,
+            Some(json!({
+                "fx_quote_id": quote_id,
+                "sell": {"amount": quote.source_amount.to_string(), "currency": quote.from_currency},
+                "buy": {"amount": quote.target_amount.to_string(), "currency": quote.to_currency, "transaction_id": buy.transaction_id},
+                "applied_rate": quote.applied_rate.to_string(),
+                "fee_amount": quote.fee_amount.to_string(),
+            })),
+            None,
+        )?;
+        log::info!("Executed FX quote {}: SELL {} / BUY {}", quote_id, sell.transaction_id, buy.transaction_id);
+
+        Ok(ConversionOutcome { quote, debit_leg: sell, credit_leg: buy, fee })
+    })
+}
+
+/// Marks OPEN quotes past their expiry as EXPIRED. Returns how many were updated.
+pub fn expire_quotes(conn: &mut PgConnection, now: DateTime<Utc>) -> Result<usize, DomainError> {
+    use crate::schema::fx_quotes::dsl as q;
+    let count = diesel::update(
+        q::fx_quotes
+            .filter(q::status.eq(FxQuoteStatus::Open.as_str()))
+            .filter(q::expires_at.le(now)),
+    )
+    .set(q::status.eq(FxQuoteStatus::Expired.as_str()))
+    .execute(conn)?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_spread_reduces_customer_rate() {
+        assert_eq!(apply_spread(dec!(1.10), 0), dec!(1.10));
+        assert_eq!(apply_spread(dec!(1.10), 50), dec!(1.09450)); // 1.10 * 0.995
+        assert_eq!(apply_spread(dec!(50000), 100), dec!(49500));
+    }
+
+    #[test]
+    fn test_converted_amount_rounds_down_to_minor_units() {
+        assert_eq!(converted_amount(dec!(100), dec!(1.09456), "USD"), dec!(109.45));
+        assert_eq!(converted_amount(dec!(1000), dec!(0.000020001), "BTC"), dec!(0.02000100));
+        assert_eq!(converted_amount(dec!(10), dec!(151.789), "JPY"), dec!(1517));
+    }
+}
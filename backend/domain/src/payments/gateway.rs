@@ -24,6 +24,8 @@ pub enum GatewayError {
     Timeout,
     #[error("Internal gateway error: {0}")]
     InternalError(String),
+    #[error("Operation not supported by gateway: {0}")]
+    NotSupported(String),
 }
 
 // Details of the payment method provided to the gateway
@@ -65,8 +67,9 @@ pub struct PaymentGatewayRequest {
     pub description: Option<String>,
     pub customer_id: Option<String>, // Gateway's customer identifier
     pub metadata: Option<serde_json::Value>, // Pass-through metadata
-    // Add fields for idempotency keys, return URLs, etc.
-    // idempotency_key: Option<String>,
+    pub card_bin_country: Option<String>, // ISO 3166 alpha-2 country of the card issuer (from BIN), used for routing
+    pub idempotency_key: Option<String>, // Forwarded to the provider so retries are not charged twice
+    // Add fields for return URLs, etc.
 }
 
 /// Response structure received from the payment gateway.
@@ -96,6 +99,16 @@ pub trait PaymentGateway: Send + Sync { // Ensure Send+Sync for multi-threaded e
         gateway_transaction_id: &str,
     ) -> Result<PaymentGatewayResponse, GatewayError>;
 
+    /// Looks up a payment by the idempotency key it was submitted with.
+    /// `Ok(None)` means the gateway confirms no payment exists for the key, i.e. an attempt
+    /// that failed in transit never went through. Used by the routing gateway before failing over.
+    async fn find_by_idempotency_key(
+        &self,
+        _idempotency_key: &str,
+    ) -> Result<Option<PaymentGatewayResponse>, GatewayError> {
+        Err(GatewayError::NotSupported("Lookup by idempotency key".to_string()))
+    }
+
     // Add other methods if needed (e.g., create customer, manage payment methods)
     // async fn create_customer(&self, ...) -> Result<..., GatewayError>;
     // async fn tokenize_card(&self, ...) -> Result<String, GatewayError>;
@@ -103,77 +116,5 @@ pub trait PaymentGateway: Send + Sync { // Ensure Send+Sync for multi-threaded e
 
 
 // --- Mock Implementation for Testing ---
-
-#[derive(Debug, Clone, Default)]
-pub struct MockPaymentGateway {
-    pub should_succeed: bool,
-    pub simulate_delay_ms: Option<u64>,
-}
-
-#[async_trait]
-impl PaymentGateway for MockPaymentGateway {
-    async fn submit_payment(
-        &self,
-        request: PaymentGatewayRequest,
-    ) -> Result<PaymentGatewayResponse, GatewayError> {
-        if let Some(delay) = self.simulate_delay_ms {
-            tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
-        }
-
-        let gateway_id = format!("MOCK_{}_{}",
-            match request.intent {
-                PaymentIntent::Authorize => "AUTH",
-                PaymentIntent::Capture => "CAP",
-                PaymentIntent::AuthorizeAndCapture => "SALE",
-                PaymentIntent::Refund => "REF",
-                PaymentIntent::Validate => "VAL",
-            },
-            rand::random::<u32>()
-        );
-
-        if self.should_succeed {
-            Ok(PaymentGatewayResponse {
-                success: true,
-                gateway_transaction_id: gateway_id,
-                status: Some(match request.intent { // Simulate typical success statuses
-                    PaymentIntent::Authorize => "Authorized".to_string(),
-                    PaymentIntent::Capture | PaymentIntent::AuthorizeAndCapture => "Succeeded".to_string(),
-                    PaymentIntent::Refund => "Succeeded".to_string(),
-                    PaymentIntent::Validate => "Validated".to_string(),
-                }),
-                error_code: None,
-                error_message: None,
-                details: Some(json!({"mock": true, "intent": request.intent})),
-            })
-        } else {
-            Ok(PaymentGatewayResponse {
-                success: false,
-                gateway_transaction_id: gateway_id,
-                status: Some("Failed".to_string()),
-                error_code: Some("mock_fail_code".to_string()),
-                error_message: Some("Mock Gateway Failure".to_string()),
-                details: Some(json!({"mock": true, "intent": request.intent})),
-            })
-            // Or return Err(GatewayError::RequestRejected{...}) for failure simulation
-        }
-    }
-
-     async fn get_transaction_status(
-        &self,
-        gateway_transaction_id: &str,
-    ) -> Result<PaymentGatewayResponse, GatewayError> {
-         if let Some(delay) = self.simulate_delay_ms {
-            tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
-        }
-        // Simple mock: Assume if ID exists, it succeeded previously
-         Ok(PaymentGatewayResponse {
-            success: true,
-            gateway_transaction_id: gateway_transaction_id.to_string(),
-            status: Some("Succeeded".to_string()), // Assume succeeded if found
-            error_code: None,
-            error_message: None,
-            details: Some(json!({"mock": true, "status_check": true})),
-        })
-    }
-}
-use serde_json::json; // Ensure this is imported
\ No newline at end of file
+// Scenario-driven mock lives in its own module; re-exported here for existing imports.
+pub use crate::payments::mock_gateway::{MockPaymentGateway, MockScenario};
@@ -46,7 +46,7 @@ pub fn generate_random_ach_details() -> AchDetails {
     let account_len = rng.gen_range(8..=17);
     let account_number = (0..account_len).map(|_| rng.gen_range(0..=9).to_string()).collect();
 
-    AchDetails { routing_number, account_number }
+    AchDetails { routing_number, account_number, ..Default::default() }
 }
 
 /// Generates random (but plausible) Wire details (IBAN/BIC).
@@ -0,0 +1,679 @@
+// /home/inno/elights_jobes-research/backend/domain/src/payments/inbound.rs
+// Inbound payments from every rail (ACH credits, wires, crypto receipts) arrive as one normalized
+// `InboundNotice` and go through `process_inbound`:
+// 1. Dedupe on (transaction type, rail reference). A re-sent notice never credits twice; a confirmation
+//    of an earlier unconfirmed notice completes it.
+// 2. Find the beneficiary wallet: crypto address, else virtual account number, else hashed account
+//    number, else hashed IBAN. The wallet must be active and hold the notice currency.
+// 3. Matched: the transaction completes (Dr NOSTRO / Cr wallet) once the funds are confirmed.
+//    Unmatched: the transaction waits in RequiresAction with an inbound_suspense row and, once confirmed,
+//    the funds are booked Dr NOSTRO / Cr SUSPENSE until operations assign them to a wallet or return them.
+use crate::beneficiaries::identity::mask;
+use crate::error::DomainError;
+use crate::ledger::{self, JournalEntrySpec, PostingLine};
+use crate::models::{
+    AuditOutcome, AuditTargetType, InboundSuspenseItem, LedgerAccountCategory, NewInboundSuspenseItem, NewTransaction,
+    NewVirtualAccount, SuspenseStatus, Transaction, TransactionStatus, TransactionType, VirtualAccount,
+    VirtualAccountStatus, Wallet, WalletStatus,
+};
+use crate::payments::state_machine::{self, TransitionUpdate};
+use crate::security::{audit, hashing};
+use chrono::{NaiveDate, Utc};
+use diesel::prelude::*;
+use rand::Rng;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use uuid::Uuid;
+
+const ACTOR: &str = "INBOUND_PROCESSOR";
+/// Virtual account numbers are 12 digits, inside the 17 characters NACHA allows.
+const VIRTUAL_ACCOUNT_DIGITS: u32 = 12;
+const VIRTUAL_ACCOUNT_ATTEMPTS: usize = 5;
+
+/// Rail an inbound payment arrived on.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum InboundRail {
+    Ach,
+    Wire,
+    CryptoBtc,
+    CryptoXmr,
+}
+
+impl InboundRail {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InboundRail::Ach => "ACH",
+            InboundRail::Wire => "WIRE",
+            InboundRail::CryptoBtc => "CRYPTO_BTC",
+            InboundRail::CryptoXmr => "CRYPTO_XMR",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<InboundRail> {
+        match value {
+            "ACH" => Some(InboundRail::Ach),
+            "WIRE" => Some(InboundRail::Wire),
+            "CRYPTO_BTC" => Some(InboundRail::CryptoBtc),
+            "CRYPTO_XMR" => Some(InboundRail::CryptoXmr),
+            _ => None,
+        }
+    }
+
+    pub fn transaction_type(&self) -> TransactionType {
+        match self {
+            InboundRail::Ach => TransactionType::AchInbound,
+            InboundRail::Wire => TransactionType::WireInbound,
+            InboundRail::CryptoBtc => TransactionType::CryptoBtcReceive,
+            InboundRail::CryptoXmr => TransactionType::CryptoXmrReceive,
+        }
+    }
+
+    fn is_crypto(&self) -> bool {
+        matches!(self, InboundRail::CryptoBtc | InboundRail::CryptoXmr)
+    }
+}
+
+fn confirmed_by_default() -> bool {
+    true
+}
+
+/// A received payment, as reported by any rail adapter (NACHA return file parser, MT103/pacs.008
+/// parser, node/BTCPay webhook).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InboundNotice {
+    pub rail: InboundRail,
+    pub external_reference: String, // ACH trace number, wire UETR, crypto tx hash (with output index)
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+    pub currency: String,
+    // Beneficiary, as addressed by the originator
+    #[serde(default)]
+    pub account_number: Option<String>, // ACH/wire account number (ours or a virtual account)
+    #[serde(default)]
+    pub iban: Option<String>,
+    #[serde(default)]
+    pub crypto_address: Option<String>,
+    // Originator
+    #[serde(default)]
+    pub originator_name: Option<String>,
+    #[serde(default)]
+    pub originator_account: Option<String>,
+    #[serde(default)]
+    pub originator_bank: Option<String>, // Routing number or BIC
+    #[serde(default)]
+    pub remittance_info: Option<String>,
+    #[serde(default)]
+    pub value_date: Option<NaiveDate>,
+    #[serde(default = "confirmed_by_default")]
+    pub confirmed: bool, // False while funds are not final (e.g. crypto below the confirmation threshold)
+}
+
+/// Account numbers compared without spacing, dashes or case.
+pub fn normalize_account(value: &str) -> String {
+    value.chars().filter(|c| !c.is_whitespace() && *c != '-').collect::<String>().to_uppercase()
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+impl InboundNotice {
+    pub fn validate(&self) -> Result<(), DomainError> {
+        if self.external_reference.trim().is_empty() {
+            return Err(DomainError::Validation("Inbound notice needs the rail reference".to_string()));
+        }
+        if self.amount <= Decimal::ZERO {
+            return Err(DomainError::Validation("Inbound amount must be positive".to_string()));
+        }
+        if self.currency.trim().is_empty() {
+            return Err(DomainError::Validation("Inbound notice needs a currency".to_string()));
+        }
+        Ok(())
+    }
+
+    /// The notice as stored in metadata and the suspense queue: account numbers masked.
+    pub fn redacted(&self) -> JsonValue {
+        json!({
+            "rail": self.rail.as_str(),
+            "external_reference": self.external_reference.trim(),
+            "amount": self.amount.to_string(),
+            "currency": self.currency_code(),
+            "account_number": non_empty(&self.account_number).map(|a| mask(&normalize_account(a))),
+            "iban": non_empty(&self.iban).map(|i| mask(&normalize_account(i))),
+            "crypto_address": non_empty(&self.crypto_address), // Our own deposit address, not customer data
+            "originator_name": non_empty(&self.originator_name),
+            "originator_account": non_empty(&self.originator_account).map(|a| mask(&normalize_account(a))),
+            "originator_bank": non_empty(&self.originator_bank),
+            "remittance_info": non_empty(&self.remittance_info),
+            "value_date": self.value_date,
+            "confirmed": self.confirmed,
+        })
+    }
+
+    fn currency_code(&self) -> String {
+        self.currency.trim().to_uppercase()
+    }
+}
+
+/// What happened to a notice.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "disposition", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum InboundDisposition {
+    Credited,
+    AwaitingConfirmation, // Matched, credited once the rail confirms the funds
+    Suspense { suspense_id: Uuid, reason: String },
+    Duplicate, // Already processed, nothing changed
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct InboundOutcome {
+    pub transaction: Transaction,
+    #[serde(flatten)]
+    pub disposition: InboundDisposition,
+}
+
+/// A suspense item after operations resolved it.
+#[derive(Debug, Serialize, Clone)]
+pub struct SuspenseResolution {
+    pub item: InboundSuspenseItem,
+    pub transaction: Transaction,
+}
+
+/// What a repeated notice for an existing transaction does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Redelivery {
+    Duplicate,
+    Complete, // Matched and awaiting confirmation, now confirmed
+    BookSuspense, // Unmatched and awaiting confirmation, now confirmed
+}
+
+fn redelivery_action(status: &TransactionStatus, confirmed: bool, suspense_booked: bool) -> Redelivery {
+    match (status, confirmed) {
+        (TransactionStatus::Processing, true) => Redelivery::Complete,
+        (TransactionStatus::RequiresAction, true) if !suspense_booked => Redelivery::BookSuspense,
+        _ => Redelivery::Duplicate,
+    }
+}
+
+enum WalletMatch {
+    Matched { wallet_id: Uuid, matched_by: &'static str },
+    Unmatched(String),
+}
+
+fn check_wallet(wallet: Option<Wallet>, currency: &str, matched_by: &'static str) -> Option<WalletMatch> {
+    let wallet = wallet?;
+    Some(if wallet.status != WalletStatus::Active.to_string() {
+        WalletMatch::Unmatched(format!("Wallet matched by {} is not active", matched_by))
+    } else if !wallet.currency_code.eq_ignore_ascii_case(currency) {
+        WalletMatch::Unmatched(format!("Wallet matched by {} holds {}, not {}", matched_by, wallet.currency_code, currency))
+    } else {
+        WalletMatch::Matched { wallet_id: wallet.wallet_id, matched_by }
+    })
+}
+
+/// Finds the wallet a notice is addressed to.
+fn find_beneficiary_wallet(conn: &mut PgConnection, notice: &InboundNotice) -> Result<WalletMatch, DomainError> {
+    use crate::schema::virtual_accounts::dsl as va;
+    use crate::schema::wallets::dsl as w;
+
+    let currency = notice.currency_code();
+    if notice.rail.is_crypto() {
+        let Some(address) = non_empty(&notice.crypto_address) else {
+            return Ok(WalletMatch::Unmatched("No receiving address on the notice".to_string()));
+        };
+        let wallet: Option<Wallet> = w::wallets.filter(w::address.eq(address)).first(conn).optional()?;
+        return Ok(check_wallet(wallet, &currency, "ADDRESS")
+            .unwrap_or_else(|| WalletMatch::Unmatched("Receiving address is not one of ours".to_string())));
+    }
+
+    let account_number = non_empty(&notice.account_number).map(normalize_account);
+    let iban = non_empty(&notice.iban).map(normalize_account);
+    if account_number.is_none() && iban.is_none() {
+        return Ok(WalletMatch::Unmatched("No beneficiary account on the notice".to_string()));
+    }
+
+    if let Some(account_number) = &account_number {
+        let virtual_account: Option<(VirtualAccount, Wallet)> = va::virtual_accounts
+            .inner_join(w::wallets)
+            .filter(va::account_number.eq(account_number))
+            .select((VirtualAccount::as_select(), Wallet::as_select()))
+            .first(conn)
+            .optional()?;
+        if let Some((virtual_account, wallet)) = virtual_account {
+            if virtual_account.status != VirtualAccountStatus::Active.as_str() {
+                return Ok(WalletMatch::Unmatched("Virtual account is closed".to_string()));
+            }
+            if let Some(found) = check_wallet(Some(wallet), &currency, "VIRTUAL_ACCOUNT") {
+                return Ok(found);
+            }
+        }
+        let hash = hashing::hash_sensitive_data(account_number)?;
+        let wallet: Option<Wallet> = w::wallets.filter(w::account_number_hash.eq(&hash)).first(conn).optional()?;
+        if let Some(found) = check_wallet(wallet, &currency, "ACCOUNT_NUMBER") {
+            return Ok(found);
+        }
+    }
+    if let Some(iban) = &iban {
+        let hash = hashing::hash_sensitive_data(iban)?;
+        let wallet: Option<Wallet> = w::wallets.filter(w::iban_hash.eq(&hash)).first(conn).optional()?;
+        if let Some(found) = check_wallet(wallet, &currency, "IBAN") {
+            return Ok(found);
+        }
+    }
+    Ok(WalletMatch::Unmatched("Beneficiary account is not one of ours".to_string()))
+}
+
+fn suspense_reference(transaction_id: Uuid) -> String {
+    format!("{}:SUSPENSE", transaction_id)
+}
+
+/// Books received but unapplied funds: Dr NOSTRO / Cr SUSPENSE.
+fn book_suspense(conn: &mut PgConnection, transaction: &Transaction) -> Result<(), DomainError> {
+    let nostro = ledger::system_account(conn, LedgerAccountCategory::Nostro, &transaction.currency_code)?;
+    let suspense = ledger::system_account(conn, LedgerAccountCategory::Suspense, &transaction.currency_code)?;
+    let reference = suspense_reference(transaction.transaction_id);
+    let description = format!("{} unapplied", transaction.transaction_type);
+    ledger::post_entry(conn, JournalEntrySpec {
+        transaction_id: Some(transaction.transaction_id),
+        entry_type: "SUSPENSE",
+        reference: &reference,
+        description: Some(&description),
+        lines: vec![PostingLine::debit(nostro, transaction.amount), PostingLine::credit(suspense, transaction.amount)],
+        allow_negative_wallets: false,
+    })?;
+    Ok(())
+}
+
+/// Takes booked funds back out of SUSPENSE (for assignment or return): the SUSPENSE entry mirrored.
+fn unbook_suspense(conn: &mut PgConnection, transaction: &Transaction, entry_type: &str) -> Result<(), DomainError> {
+    let lines = ledger::mirror_entry_lines(conn, &suspense_reference(transaction.transaction_id))?;
+    let reference = format!("{}:{}", transaction.transaction_id, entry_type);
+    let description = format!("{} {}", transaction.transaction_type, entry_type);
+    ledger::post_entry(conn, JournalEntrySpec {
+        transaction_id: Some(transaction.transaction_id),
+        entry_type,
+        reference: &reference,
+        description: Some(&description),
+        lines,
+        allow_negative_wallets: false,
+    })?;
+    Ok(())
+}
+
+fn audit_inbound(
+    conn: &mut PgConnection,
+    user_id: Option<Uuid>,
+    actor: &str,
+    action: &str,
+    target_type: AuditTargetType,
+    target_id: Uuid,
+    details: JsonValue,
+) -> Result<(), DomainError> {
+    audit::log_db_audit_event(
+        conn, user_id, actor, action, Some(target_type), Some(&target_id.to_string()), AuditOutcome::Success, Some(details), None,
+    )
+}
+
+/// Credits, parks or ignores (duplicate) a received payment. Runs in one DB transaction.
+pub fn process_inbound(conn: &mut PgConnection, notice: &InboundNotice) -> Result<InboundOutcome, DomainError> {
+    notice.validate()?;
+    let tx_type = notice.rail.transaction_type();
+    let reference = notice.external_reference.trim();
+    let currency = notice.currency_code();
+
+    conn.transaction(|conn| {
+        use crate::schema::transactions::dsl as t;
+
+        let existing: Option<Transaction> = t::transactions
+            .filter(t::transaction_type.eq(tx_type.to_string()))
+            .filter(t::external_ref_id.eq(reference))
+            .for_update() // Concurrent deliveries of one notice serialize here
+            .first(conn)
+            .optional()?;
+        if let Some(existing) = existing {
+            return redeliver(conn, existing, notice);
+        }
+
+        let found = find_beneficiary_wallet(conn, notice)?;
+        let (credit_wallet_id, matched_by) = match &found {
+            WalletMatch::Matched { wallet_id, matched_by } => (Some(*wallet_id), Some(*matched_by)),
+            WalletMatch::Unmatched(_) => (None, None),
+        };
+        let mut inbound = notice.redacted();
+        inbound["matched_by"] = json!(matched_by);
+        let description = non_empty(&notice.remittance_info)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("Inbound {} payment", notice.rail.as_str()));
+
+        // Inserted without a status effect; the unique (type, reference) index backs up the lookup above
+        let transaction: Transaction = diesel::insert_into(t::transactions)
+            .values(&NewTransaction {
+                transaction_id: None,
+                debit_wallet_id: None, // External originator
+                credit_wallet_id,
+                transaction_type: tx_type.to_string().as_str(),
+                status: TransactionStatus::Pending.to_string().as_str(),
+                amount: notice.amount,
+                currency_code: &currency,
+                description: Some(&description),
+                external_ref_id: Some(reference),
+                metadata: Some(json!({ "inbound": inbound })),
+                scheduled_run_id: None,
+            })
+            .get_result(conn)?;
+
+        let outcome = match found {
+            WalletMatch::Matched { wallet_id, .. } => {
+                let (to, disposition) = if notice.confirmed {
+                    (TransactionStatus::Completed, InboundDisposition::Credited)
+                } else {
+                    (TransactionStatus::Processing, InboundDisposition::AwaitingConfirmation)
+                };
+                let settlement_at = notice.confirmed.then(Utc::now);
+                let update = TransitionUpdate { settlement_at, ..Default::default() };
+                let transaction = state_machine::apply_transition(conn, &transaction, to, update, ACTOR)?;
+                log::info!("Inbound {} {} {} {} for wallet {}: {:?}",
+                    notice.rail.as_str(), reference, transaction.amount, transaction.currency_code, wallet_id, disposition);
+                InboundOutcome { transaction, disposition }
+            }
+            WalletMatch::Unmatched(reason) => {
+                let update = TransitionUpdate { metadata: Some(json!({ "suspense_reason": reason })), ..Default::default() };
+                let transaction = state_machine::apply_transition(conn, &transaction, TransactionStatus::RequiresAction, update, ACTOR)?;
+                let item: InboundSuspenseItem = diesel::insert_into(crate::schema::inbound_suspense::table)
+                    .values(&NewInboundSuspenseItem {
+                        transaction_id: transaction.transaction_id,
+                        rail: notice.rail.as_str(),
+                        amount: transaction.amount,
+                        currency_code: &transaction.currency_code,
+                        reason: &reason,
+                        notice: notice.redacted(),
+                        status: SuspenseStatus::Open.as_str(),
+                    })
+                    .get_result(conn)?;
+                if notice.confirmed {
+                    book_suspense(conn, &transaction)?;
+                }
+                log::warn!("Inbound {} {} {} {} sent to suspense {}: {}",
+                    notice.rail.as_str(), reference, transaction.amount, transaction.currency_code, item.suspense_id, reason);
+                InboundOutcome { transaction, disposition: InboundDisposition::Suspense { suspense_id: item.suspense_id, reason } }
+            }
+        };
+
+        audit_inbound(conn, None, ACTOR, "RECEIVE_INBOUND_PAYMENT", AuditTargetType::Transaction, outcome.transaction.transaction_id,
+            json!({"rail": notice.rail.as_str(), "reference": reference, "amount": notice.amount.to_string(),
+                "currency": currency, "confirmed": notice.confirmed, "result": outcome.disposition}))?;
+        Ok(outcome)
+    })
+}
+
+/// A notice for a reference we already have: confirm it, or report the duplicate.
+fn redeliver(conn: &mut PgConnection, existing: Transaction, notice: &InboundNotice) -> Result<InboundOutcome, DomainError> {
+    if existing.amount != notice.amount || !existing.currency_code.eq_ignore_ascii_case(&notice.currency) {
+        return Err(DomainError::Validation(format!(
+            "Inbound reference {} was already received as {} {}", notice.external_reference.trim(), existing.amount, existing.currency_code)));
+    }
+    let status = TransactionStatus::parse(&existing.status)
+        .ok_or_else(|| DomainError::Internal(format!("Unknown status '{}' on {}", existing.status, existing.transaction_id)))?;
+    let suspense_booked = ledger::entry_exists(conn, &suspense_reference(existing.transaction_id))?;
+
+    match redelivery_action(&status, notice.confirmed, suspense_booked) {
+        Redelivery::Duplicate => {
+            log::info!("Duplicate inbound notice {} for Tx {}", notice.external_reference.trim(), existing.transaction_id);
+            Ok(InboundOutcome { transaction: existing, disposition: InboundDisposition::Duplicate })
+        }
+        Redelivery::Complete => {
+            let update = TransitionUpdate { settlement_at: Some(Utc::now()), ..Default::default() };
+            let transaction = state_machine::apply_transition(conn, &existing, TransactionStatus::Completed, update, ACTOR)?;
+            audit_inbound(conn, None, ACTOR, "CONFIRM_INBOUND_PAYMENT", AuditTargetType::Transaction, transaction.transaction_id,
+                json!({"reference": notice.external_reference.trim()}))?;
+            Ok(InboundOutcome { transaction, disposition: InboundDisposition::Credited })
+        }
+        Redelivery::BookSuspense => {
+            use crate::schema::inbound_suspense::dsl as s;
+            let item: InboundSuspenseItem = s::inbound_suspense
+                .filter(s::transaction_id.eq(existing.transaction_id))
+                .first(conn)
+                .optional()?
+                .ok_or_else(|| DomainError::Internal(format!("Tx {} awaits action but has no suspense item", existing.transaction_id)))?;
+            if item.status == SuspenseStatus::Open.as_str() {
+                book_suspense(conn, &existing)?;
+                audit_inbound(conn, None, ACTOR, "CONFIRM_INBOUND_PAYMENT", AuditTargetType::InboundSuspense, item.suspense_id,
+                    json!({"reference": notice.external_reference.trim(), "transaction_id": existing.transaction_id}))?;
+            }
+            let reason = item.reason.clone();
+            Ok(InboundOutcome { transaction: existing, disposition: InboundDisposition::Suspense { suspense_id: item.suspense_id, reason } })
+        }
+    }
+}
+
+/// Locks an OPEN suspense item and its transaction.
+fn open_item_for_update(conn: &mut PgConnection, suspense_id: Uuid) -> Result<(InboundSuspenseItem, Transaction), DomainError> {
+    use crate::schema::inbound_suspense::dsl as s;
+
+    let item: InboundSuspenseItem = s::inbound_suspense
+        .find(suspense_id)
+        .for_update()
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("Suspense item {} not found", suspense_id)))?;
+    if item.status != SuspenseStatus::Open.as_str() {
+        return Err(DomainError::Validation(format!("Suspense item {} is already {}", suspense_id, item.status)));
+    }
+    let transaction: Transaction = crate::schema::transactions::table
+        .find(item.transaction_id)
+        .for_update()
+        .first(conn)?;
+    Ok((item, transaction))
+}
+
+fn resolve_item(
+    conn: &mut PgConnection,
+    item: &InboundSuspenseItem,
+    status: SuspenseStatus,
+    wallet_id: Option<Uuid>,
+    resolved_by: Uuid,
+    note: Option<&str>,
+) -> Result<InboundSuspenseItem, DomainError> {
+    use crate::schema::inbound_suspense::dsl as s;
+    Ok(diesel::update(s::inbound_suspense.find(item.suspense_id))
+        .set((
+            s::status.eq(status.as_str()),
+            s::resolved_wallet_id.eq(wallet_id),
+            s::resolved_by.eq(Some(resolved_by)),
+            s::resolution_note.eq(note),
+            s::resolved_at.eq(Some(Utc::now())),
+        ))
+        .get_result(conn)?)
+}
+
+/// Credits an unmatched payment to `wallet_id`: Dr SUSPENSE / Cr NOSTRO, then the usual Dr NOSTRO / Cr wallet.
+pub fn assign_suspense(
+    conn: &mut PgConnection,
+    suspense_id: Uuid,
+    wallet_id: Uuid,
+    resolved_by: Uuid,
+    actor: &str,
+    note: Option<&str>,
+) -> Result<SuspenseResolution, DomainError> {
+    conn.transaction(|conn| {
+        let (item, transaction) = open_item_for_update(conn, suspense_id)?;
+        if !ledger::entry_exists(conn, &suspense_reference(transaction.transaction_id))? {
+            return Err(DomainError::Validation("Funds are not confirmed yet and cannot be assigned".to_string()));
+        }
+        let wallet: Wallet = crate::schema::wallets::table
+            .find(wallet_id)
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| DomainError::NotFound(format!("Wallet {} not found", wallet_id)))?;
+        if let Some(WalletMatch::Unmatched(reason)) = check_wallet(Some(wallet), &item.currency_code, "ASSIGNMENT") {
+            return Err(DomainError::Validation(reason));
+        }
+
+        unbook_suspense(conn, &transaction, "SUSPENSE_RELEASE")?;
+        let transaction: Transaction = diesel::update(crate::schema::transactions::table.find(transaction.transaction_id))
+            .set(crate::schema::transactions::credit_wallet_id.eq(Some(wallet_id)))
+            .get_result(conn)?;
+        let update = TransitionUpdate {
+            metadata: Some(json!({ "suspense": { "suspense_id": suspense_id, "assigned_by": resolved_by } })),
+            ..Default::default()
+        };
+        let transaction = state_machine::apply_transition(conn, &transaction, TransactionStatus::Pending, update, actor)?;
+        let update = TransitionUpdate { settlement_at: Some(Utc::now()), ..Default::default() };
+        let transaction = state_machine::apply_transition(conn, &transaction, TransactionStatus::Completed, update, actor)?;
+
+        let item = resolve_item(conn, &item, SuspenseStatus::Assigned, Some(wallet_id), resolved_by, note)?;
+        audit_inbound(conn, Some(resolved_by), actor, "ASSIGN_INBOUND_SUSPENSE", AuditTargetType::InboundSuspense, suspense_id,
+            json!({"transaction_id": transaction.transaction_id, "wallet_id": wallet_id, "note": note}))?;
+        log::info!("Suspense item {} assigned to wallet {} by {}", suspense_id, wallet_id, actor);
+        Ok(SuspenseResolution { item, transaction })
+    })
+}
+
+/// Gives an unmatched payment back to the originator: the SUSPENSE booking is reversed and the
+/// transaction marked Returned.
+pub fn return_suspense(
+    conn: &mut PgConnection,
+    suspense_id: Uuid,
+    resolved_by: Uuid,
+    actor: &str,
+    note: Option<&str>,
+) -> Result<SuspenseResolution, DomainError> {
+    conn.transaction(|conn| {
+        let (item, transaction) = open_item_for_update(conn, suspense_id)?;
+        if ledger::entry_exists(conn, &suspense_reference(transaction.transaction_id))? {
+            unbook_suspense(conn, &transaction, "SUSPENSE_RETURN")?;
+        }
+        // TODO: Originate the return on the rail (ACH R03/R04 return entry, MT103 return, crypto refund).
+        let update = TransitionUpdate {
+            metadata: Some(json!({ "suspense": { "suspense_id": suspense_id, "returned_by": resolved_by } })),
+            ..Default::default()
+        };
+        let transaction = state_machine::apply_transition(conn, &transaction, TransactionStatus::Returned, update, actor)?;
+
+        let item = resolve_item(conn, &item, SuspenseStatus::Returned, None, resolved_by, note)?;
+        audit_inbound(conn, Some(resolved_by), actor, "RETURN_INBOUND_SUSPENSE", AuditTargetType::InboundSuspense, suspense_id,
+            json!({"transaction_id": transaction.transaction_id, "note": note}))?;
+        log::info!("Suspense item {} returned by {}", suspense_id, actor);
+        Ok(SuspenseResolution { item, transaction })
+    })
+}
+
+/// Unresolved suspense items, oldest first.
+pub fn list_open_suspense(conn: &mut PgConnection) -> Result<Vec<InboundSuspenseItem>, DomainError> {
+    use crate::schema::inbound_suspense::dsl as s;
+    Ok(s::inbound_suspense
+        .filter(s::status.eq(SuspenseStatus::Open.as_str()))
+        .order(s::created_at.asc())
+        .load(conn)?)
+}
+
+fn random_account_number() -> String {
+    let mut rng = rand::thread_rng();
+    let first = rng.gen_range(1..=9); // No leading zero, some bank systems drop it
+    let rest: String = (1..VIRTUAL_ACCOUNT_DIGITS).map(|_| char::from(b'0' + rng.gen_range(0..10u8))).collect();
+    format!("{}{}", first, rest)
+}
+
+/// Issues a new virtual account number for a wallet of `user_id`.
+pub fn open_virtual_account(conn: &mut PgConnection, wallet_id: Uuid, user_id: Uuid) -> Result<VirtualAccount, DomainError> {
+    use crate::schema::virtual_accounts::dsl as va;
+    use crate::schema::wallets::dsl as w;
+
+    conn.transaction(|conn| {
+        let wallet: Wallet = w::wallets
+            .filter(w::wallet_id.eq(wallet_id))
+            .filter(w::user_id.eq(user_id))
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| DomainError::NotFound(format!("Wallet {} not found", wallet_id)))?;
+        if wallet.status != WalletStatus::Active.to_string() {
+            return Err(DomainError::Validation(format!("Wallet {} is not active", wallet_id)));
+        }
+
+        for _ in 0..VIRTUAL_ACCOUNT_ATTEMPTS {
+            let account_number = random_account_number();
+            let created: Option<VirtualAccount> = diesel::insert_into(va::virtual_accounts)
+                .values(&NewVirtualAccount {
+                    wallet_id,
+                    account_number: &account_number,
+                    status: VirtualAccountStatus::Active.as_str(),
+                })
+                .on_conflict(va::account_number)
+                .do_nothing()
+                .get_result(conn)
+                .optional()?;
+            if let Some(account) = created {
+                audit_inbound(conn, Some(user_id), &user_id.to_string(), "OPEN_VIRTUAL_ACCOUNT", AuditTargetType::Wallet, wallet_id,
+                    json!({"virtual_account_id": account.virtual_account_id, "account_number": mask(&account.account_number)}))?;
+                log::info!("Opened virtual account {} for wallet {}", account.virtual_account_id, wallet_id);
+                return Ok(account);
+            }
+        }
+        Err(DomainError::Internal("Could not allocate a unique virtual account number".to_string()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn notice() -> InboundNotice {
+        serde_json::from_value(json!({
+            "rail": "WIRE",
+            "external_reference": "eb6305c9-1f7f-49de-aed0-16487c27b42d",
+            "amount": "1250.00",
+            "currency": "eur",
+            "iban": "DE89 3704 0044 0532 0130 00",
+            "originator_account": "GB29NWBK60161331926819",
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_rail_mapping() {
+        assert_eq!(InboundRail::Ach.transaction_type(), TransactionType::AchInbound);
+        assert_eq!(InboundRail::CryptoXmr.transaction_type(), TransactionType::CryptoXmrReceive);
+        for rail in [InboundRail::Ach, InboundRail::Wire, InboundRail::CryptoBtc, InboundRail::CryptoXmr] {
+            assert_eq!(InboundRail::parse(rail.as_str()), Some(rail));
+        }
+    }
+
+    #[test]
+    fn test_notice_defaults_and_redaction() {
+        let notice = notice();
+        assert!(notice.confirmed);
+        assert!(notice.validate().is_ok());
+        let stored = notice.redacted();
+        assert_eq!(stored["iban"], "****3000");
+        assert_eq!(stored["originator_account"], "****6819");
+        assert_eq!(stored["currency"], "EUR");
+        assert!(!stored.to_string().contains("37040044"));
+
+        let mut zero = notice.clone();
+        zero.amount = dec!(0);
+        assert!(zero.validate().is_err());
+        let mut unreferenced = notice;
+        unreferenced.external_reference = "  ".to_string();
+        assert!(unreferenced.validate().is_err());
+    }
+
+    #[test]
+    fn test_redelivery() {
+        use TransactionStatus::*;
+        assert_eq!(redelivery_action(&Processing, true, false), Redelivery::Complete);
+        assert_eq!(redelivery_action(&Processing, false, false), Redelivery::Duplicate);
+        assert_eq!(redelivery_action(&RequiresAction, true, false), Redelivery::BookSuspense);
+        assert_eq!(redelivery_action(&RequiresAction, true, true), Redelivery::Duplicate);
+        assert_eq!(redelivery_action(&Completed, true, false), Redelivery::Duplicate);
+    }
+
+    #[test]
+    fn test_account_normalization_and_generation() {
+        assert_eq!(normalize_account(" de89-3704 0044 "), "DE8937040044");
+        let number = random_account_number();
+        assert_eq!(number.len(), VIRTUAL_ACCOUNT_DIGITS as usize);
+        assert!(number.chars().all(|c| c.is_ascii_digit()) && !number.starts_with('0'));
+    }
+}
@@ -0,0 +1,489 @@
+// /home/inno/elights_jobes-research/backend/domain/src/payments/mock_gateway.rs
+// Scenario-driven mock gateway, modelled on processor test cards (magic tokens and amounts).
+//
+// Test tokens (PaymentMethodDetails::CardToken):
+//   tok_visa / any other token          -> approved
+//   tok_chargeDeclined                  -> decline, code "do_not_honor"
+//   tok_chargeDeclinedInsufficientFunds -> decline, code "insufficient_funds"
+//   tok_chargeDeclinedExpiredCard       -> decline, code "expired_card"
+//   tok_chargeDeclinedIncorrectCvc      -> decline, code "incorrect_cvc"
+//   tok_chargeDeclinedFraudulent        -> decline, code "fraudulent"
+//   tok_threeDSecureRequired            -> 3DS authentication required
+//   tok_networkError                    -> NetworkError, nothing recorded
+//   tok_timeoutBeforeCharge             -> Timeout, nothing recorded
+//   tok_timeoutAfterCharge              -> Timeout, but the charge WAS recorded
+//   tok_asyncSettlement                 -> accepted as Pending, settles on later status polls
+//
+// Magic amounts (any token):
+//   2000.00 - 2999.99 -> decline with the integer part as processor code
+//                        (2001 insufficient funds, 2004 expired card, 2010 CVV, 2014 fraud)
+//   3000.00           -> NetworkError before the charge
+//   4001.00           -> 3DS required
+//   4002.00           -> Timeout after the charge
+//   4003.00           -> asynchronous settlement
+use crate::payments::gateway::{
+    GatewayError, PaymentGateway, PaymentGatewayRequest, PaymentGatewayResponse, PaymentIntent, PaymentMethodDetails,
+};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Outcome the mock produces for a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockScenario {
+    Approve,
+    Decline { code: String, message: String },
+    ThreeDsRequired,
+    NetworkErrorBeforeCharge,
+    TimeoutBeforeCharge,
+    TimeoutAfterCharge,
+    AsyncSettlement,
+}
+
+impl MockScenario {
+    /// Resolves the scenario from the request's test token or magic amount.
+    /// Follow-up requests (capture/refund by reference) always approve unless the stored state forbids it.
+    pub fn for_request(request: &PaymentGatewayRequest) -> MockScenario {
+        if let PaymentMethodDetails::CardToken(token) = &request.payment_method {
+            if let Some(scenario) = Self::for_token(token) {
+                return scenario;
+            }
+        }
+        if matches!(request.payment_method, PaymentMethodDetails::GatewayReference(_)) {
+            return MockScenario::Approve;
+        }
+        Self::for_amount(request.amount).unwrap_or(MockScenario::Approve)
+    }
+
+    fn for_token(token: &str) -> Option<MockScenario> {
+        let decline = |code: &str, message: &str| MockScenario::Decline { code: code.to_string(), message: message.to_string() };
+        Some(match token {
+            "tok_chargeDeclined" => decline("do_not_honor", "Your card was declined."),
+            "tok_chargeDeclinedInsufficientFunds" => decline("insufficient_funds", "Your card has insufficient funds."),
+            "tok_chargeDeclinedExpiredCard" => decline("expired_card", "Your card has expired."),
+            "tok_chargeDeclinedIncorrectCvc" => decline("incorrect_cvc", "Your card's security code is incorrect."),
+            "tok_chargeDeclinedFraudulent" => decline("fraudulent", "Your card was declined."),
+            "tok_threeDSecureRequired" => MockScenario::ThreeDsRequired,
+            "tok_networkError" => MockScenario::NetworkErrorBeforeCharge,
+            "tok_timeoutBeforeCharge" => MockScenario::TimeoutBeforeCharge,
+            "tok_timeoutAfterCharge" => MockScenario::TimeoutAfterCharge,
+            "tok_asyncSettlement" => MockScenario::AsyncSettlement,
+            _ => return None,
+        })
+    }
+
+    fn for_amount(amount: Decimal) -> Option<MockScenario> {
+        let whole = amount.trunc();
+        if amount >= Decimal::new(2000, 0) && amount < Decimal::new(3000, 0) {
+            let code = whole.to_string();
+            let message = match code.as_str() {
+                "2001" => "Insufficient Funds",
+                "2004" => "Expired Card",
+                "2010" => "Card Issuer Declined CVV",
+                "2014" => "Processor Declined - Fraud Suspected",
+                _ => "Do Not Honor",
+            };
+            return Some(MockScenario::Decline { code, message: message.to_string() });
+        }
+        if amount.fract() != Decimal::ZERO {
+            return None;
+        }
+        match whole.to_string().as_str() {
+            "3000" => Some(MockScenario::NetworkErrorBeforeCharge),
+            "4001" => Some(MockScenario::ThreeDsRequired),
+            "4002" => Some(MockScenario::TimeoutAfterCharge),
+            "4003" => Some(MockScenario::AsyncSettlement),
+            _ => None,
+        }
+    }
+}
+
+/// In-memory record of a payment the mock has accepted.
+#[derive(Debug, Clone)]
+struct MockCharge {
+    intent: PaymentIntent,
+    currency: String,
+    authorized_amount: Decimal,
+    captured_amount: Decimal,
+    refunded_amount: Decimal,
+    status: String, // Authorized, Succeeded, Pending, Failed, Refunded, RequiresAction
+    pending_polls_remaining: u32, // For async settlement
+    parent_id: Option<String>, // Original charge for captures/refunds
+    response: PaymentGatewayResponse, // Replayed for duplicate idempotency keys
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    charges: HashMap<String, MockCharge>,
+    by_idempotency_key: HashMap<String, String>, // key -> gateway transaction id
+    next_id: u64,
+}
+
+/// Mock gateway with scenario engine and in-memory state. Clones share state.
+#[derive(Debug, Clone, Default)]
+pub struct MockPaymentGateway {
+    pub should_succeed: bool, // Outcome for requests that do not hit a scenario token/amount
+    pub simulate_delay_ms: Option<u64>,
+    pub async_settlement_polls: u32, // Status polls before an async payment settles (0 = first poll)
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockPaymentGateway {
+    /// Mock that approves everything not covered by a scenario.
+    pub fn new() -> Self {
+        MockPaymentGateway { should_succeed: true, async_settlement_polls: 1, ..Default::default() }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Forces a pending (async) payment to its final status, as a settlement webhook would.
+    pub fn settle_pending(&self, gateway_transaction_id: &str, succeeded: bool) -> Result<(), GatewayError> {
+        let mut state = self.state();
+        let charge = state.charges.get_mut(gateway_transaction_id)
+            .ok_or_else(|| not_found(gateway_transaction_id))?;
+        charge.status = if succeeded { "Succeeded" } else { "Failed" }.to_string();
+        charge.pending_polls_remaining = 0;
+        Ok(())
+    }
+
+    /// Number of payments currently recorded (for assertions in tests).
+    pub fn recorded_count(&self) -> usize {
+        self.state().charges.len()
+    }
+
+    async fn delay(&self) {
+        if let Some(delay) = self.simulate_delay_ms {
+            tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
+        }
+    }
+
+    fn next_gateway_id(state: &mut MockState, intent: &PaymentIntent) -> String {
+        state.next_id += 1;
+        format!("MOCK_{}_{:06}", intent_code(intent), state.next_id)
+    }
+
+    /// Records an accepted payment and returns the response stored for it.
+    fn record(
+        state: &mut MockState,
+        request: &PaymentGatewayRequest,
+        status: &str,
+        success: bool,
+        error: Option<(&str, &str)>,
+        pending_polls: u32,
+        parent_id: Option<String>,
+    ) -> PaymentGatewayResponse {
+        let id = Self::next_gateway_id(state, &request.intent);
+        let response = PaymentGatewayResponse {
+            success,
+            gateway_transaction_id: id.clone(),
+            status: Some(status.to_string()),
+            error_code: error.map(|(code, _)| code.to_string()),
+            error_message: error.map(|(_, msg)| msg.to_string()),
+            details: Some(json!({"mock": true, "intent": request.intent, "idempotency_key": request.idempotency_key})),
+        };
+        let captured = if matches!(request.intent, PaymentIntent::AuthorizeAndCapture) && success { request.amount } else { Decimal::ZERO };
+        state.charges.insert(id.clone(), MockCharge {
+            intent: request.intent.clone(),
+            currency: request.currency.clone(),
+            authorized_amount: request.amount,
+            captured_amount: captured,
+            refunded_amount: Decimal::ZERO,
+            status: status.to_string(),
+            pending_polls_remaining: pending_polls,
+            parent_id,
+            response: response.clone(),
+        });
+        if let Some(key) = &request.idempotency_key {
+            state.by_idempotency_key.insert(key.clone(), id);
+        }
+        response
+    }
+
+    /// Applies a capture or refund against an earlier payment held in memory.
+    fn apply_follow_up(&self, state: &mut MockState, request: &PaymentGatewayRequest, reference: &str) -> Result<PaymentGatewayResponse, GatewayError> {
+        let original = state.charges.get(reference).cloned().ok_or_else(|| not_found(reference))?;
+        if original.currency != request.currency {
+            return Err(rejected("currency_mismatch", "Currency does not match the original payment"));
+        }
+
+        match request.intent {
+            PaymentIntent::Capture => {
+                if original.status != "Authorized" {
+                    return Err(rejected("charge_not_capturable", &format!("Payment is {}, not Authorized", original.status)));
+                }
+                if request.amount > original.authorized_amount {
+                    return Err(rejected("amount_too_large", "Capture exceeds authorized amount"));
+                }
+                let response = Self::record(state, request, "Succeeded", true, None, 0, Some(reference.to_string()));
+                if let Some(parent) = state.charges.get_mut(reference) {
+                    // One capture per authorization: a partial capture releases the rest
+                    parent.captured_amount = request.amount;
+                    parent.status = "Succeeded".to_string();
+                }
+                Ok(response)
+            }
+            PaymentIntent::Refund => {
+                if !matches!(original.status.as_str(), "Succeeded" | "Refunded") {
+                    return Err(rejected("charge_not_refundable", &format!("Payment is {}, cannot refund", original.status)));
+                }
+                if request.amount > original.captured_amount - original.refunded_amount {
+                    return Err(rejected("amount_too_large", "Refund exceeds captured amount"));
+                }
+                let response = Self::record(state, request, "Succeeded", true, None, 0, Some(reference.to_string()));
+                if let Some(parent) = state.charges.get_mut(reference) {
+                    parent.refunded_amount += request.amount;
+                    if parent.refunded_amount == parent.captured_amount {
+                        parent.status = "Refunded".to_string();
+                    }
+                }
+                Ok(response)
+            }
+            _ => Err(rejected("invalid_request", "Only capture/refund accept a gateway reference")),
+        }
+    }
+}
+
+fn intent_code(intent: &PaymentIntent) -> &'static str {
+    match intent {
+        PaymentIntent::Authorize => "AUTH",
+        PaymentIntent::Capture => "CAP",
+        PaymentIntent::AuthorizeAndCapture => "SALE",
+        PaymentIntent::Refund => "REF",
+        PaymentIntent::Validate => "VAL",
+    }
+}
+
+fn not_found(reference: &str) -> GatewayError {
+    GatewayError::RequestRejected {
+        status: Some(404),
+        code: Some("resource_missing".to_string()),
+        message: Some(format!("No such payment: {}", reference)),
+    }
+}
+
+fn rejected(code: &str, message: &str) -> GatewayError {
+    GatewayError::RequestRejected { status: Some(400), code: Some(code.to_string()), message: Some(message.to_string()) }
+}
+
+#[async_trait]
+impl PaymentGateway for MockPaymentGateway {
+    async fn submit_payment(
+        &self,
+        request: PaymentGatewayRequest,
+    ) -> Result<PaymentGatewayResponse, GatewayError> {
+        self.delay().await;
+        let mut state = self.state();
+
+        // Duplicate key: return the original result, as real processors do
+        if let Some(key) = &request.idempotency_key {
+            if let Some(existing) = state.by_idempotency_key.get(key).and_then(|id| state.charges.get(id)) {
+                return Ok(existing.response.clone());
+            }
+        }
+
+        if let PaymentMethodDetails::GatewayReference(reference) = &request.payment_method {
+            return self.apply_follow_up(&mut state, &request, reference);
+        }
+
+        let success_status = match request.intent {
+            PaymentIntent::Authorize => "Authorized",
+            PaymentIntent::Validate => "Validated",
+            _ => "Succeeded",
+        };
+
+        match MockScenario::for_request(&request) {
+            MockScenario::Approve if self.should_succeed => {
+                Ok(Self::record(&mut state, &request, success_status, true, None, 0, None))
+            }
+            MockScenario::Approve => {
+                Ok(Self::record(&mut state, &request, "Failed", false, Some(("mock_fail_code", "Mock Gateway Failure")), 0, None))
+            }
+            MockScenario::Decline { code, message } => {
+                Ok(Self::record(&mut state, &request, "Failed", false, Some((&code, &message)), 0, None))
+            }
+            MockScenario::ThreeDsRequired => {
+                Ok(Self::record(&mut state, &request, "RequiresAction", false,
+                    Some(("authentication_required", "3D Secure authentication required")), 0, None))
+            }
+            MockScenario::NetworkErrorBeforeCharge => {
+                Err(GatewayError::NetworkError("Mock connection reset before request was sent".to_string()))
+            }
+            MockScenario::TimeoutBeforeCharge => Err(GatewayError::Timeout),
+            MockScenario::TimeoutAfterCharge => {
+                // The charge goes through, the caller just never hears about it
+                Self::record(&mut state, &request, success_status, true, None, 0, None);
+                Err(GatewayError::Timeout)
+            }
+            MockScenario::AsyncSettlement => {
+                let polls = self.async_settlement_polls;
+                Ok(Self::record(&mut state, &request, "Pending", true, None, polls, None))
+            }
+        }
+    }
+
+    async fn get_transaction_status(
+        &self,
+        gateway_transaction_id: &str,
+    ) -> Result<PaymentGatewayResponse, GatewayError> {
+        self.delay().await;
+        let mut state = self.state();
+        let charge = state.charges.get_mut(gateway_transaction_id)
+            .ok_or_else(|| not_found(gateway_transaction_id))?;
+
+        if charge.status == "Pending" {
+            if charge.pending_polls_remaining == 0 {
+                charge.status = "Succeeded".to_string();
+                charge.captured_amount = charge.authorized_amount;
+            } else {
+                charge.pending_polls_remaining -= 1;
+            }
+        }
+
+        Ok(PaymentGatewayResponse {
+            success: charge.status != "Failed",
+            gateway_transaction_id: gateway_transaction_id.to_string(),
+            status: Some(charge.status.clone()),
+            error_code: charge.response.error_code.clone(),
+            error_message: charge.response.error_message.clone(),
+            details: Some(json!({
+                "mock": true,
+                "status_check": true,
+                "intent": charge.intent,
+                "captured_amount": charge.captured_amount.to_string(),
+                "refunded_amount": charge.refunded_amount.to_string(),
+                "parent_id": charge.parent_id,
+            })),
+        })
+    }
+
+    async fn find_by_idempotency_key(
+        &self,
+        idempotency_key: &str,
+    ) -> Result<Option<PaymentGatewayResponse>, GatewayError> {
+        let state = self.state();
+        Ok(state.by_idempotency_key.get(idempotency_key)
+            .and_then(|id| state.charges.get(id))
+            .map(|charge| charge.response.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn request(token: &str, amount: Decimal, intent: PaymentIntent, key: Option<&str>) -> PaymentGatewayRequest {
+        PaymentGatewayRequest {
+            amount,
+            currency: "USD".to_string(),
+            payment_method: PaymentMethodDetails::CardToken(token.to_string()),
+            intent,
+            description: None,
+            customer_id: None,
+            metadata: None,
+            card_bin_country: None,
+            idempotency_key: key.map(str::to_string),
+        }
+    }
+
+    fn follow_up(reference: &str, amount: Decimal, intent: PaymentIntent) -> PaymentGatewayRequest {
+        let mut req = request("", amount, intent, None);
+        req.payment_method = PaymentMethodDetails::GatewayReference(reference.to_string());
+        req
+    }
+
+    #[test]
+    fn test_scenario_resolution() {
+        assert_eq!(MockScenario::for_request(&request("tok_visa", dec!(10), PaymentIntent::Authorize, None)), MockScenario::Approve);
+        assert_eq!(
+            MockScenario::for_request(&request("tok_visa", dec!(2001), PaymentIntent::Authorize, None)),
+            MockScenario::Decline { code: "2001".to_string(), message: "Insufficient Funds".to_string() }
+        );
+        assert_eq!(MockScenario::for_request(&request("tok_visa", dec!(4001), PaymentIntent::Authorize, None)), MockScenario::ThreeDsRequired);
+        assert_eq!(MockScenario::for_request(&request("tok_visa", dec!(4001.50), PaymentIntent::Authorize, None)), MockScenario::Approve);
+        assert_eq!(MockScenario::for_request(&request("tok_timeoutAfterCharge", dec!(10), PaymentIntent::Authorize, None)), MockScenario::TimeoutAfterCharge);
+    }
+
+    #[tokio::test]
+    async fn test_authorize_capture_refund_flow() {
+        let gateway = MockPaymentGateway::new();
+        let auth = gateway.submit_payment(request("tok_visa", dec!(100), PaymentIntent::Authorize, None)).await.unwrap();
+        assert_eq!(auth.status.as_deref(), Some("Authorized"));
+
+        // Cannot capture more than authorized
+        assert!(gateway.submit_payment(follow_up(&auth.gateway_transaction_id, dec!(150), PaymentIntent::Capture)).await.is_err());
+        gateway.submit_payment(follow_up(&auth.gateway_transaction_id, dec!(100), PaymentIntent::Capture)).await.unwrap();
+
+        gateway.submit_payment(follow_up(&auth.gateway_transaction_id, dec!(60), PaymentIntent::Refund)).await.unwrap();
+        assert!(gateway.submit_payment(follow_up(&auth.gateway_transaction_id, dec!(60), PaymentIntent::Refund)).await.is_err());
+
+        // Unknown references are rejected
+        assert!(gateway.submit_payment(follow_up("MOCK_AUTH_999999", dec!(1), PaymentIntent::Capture)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_partial_capture_closes_the_authorization() {
+        let gateway = MockPaymentGateway::new();
+        let auth = gateway.submit_payment(request("tok_visa", dec!(100), PaymentIntent::Authorize, None)).await.unwrap();
+        let reference = auth.gateway_transaction_id.clone();
+
+        gateway.submit_payment(follow_up(&reference, dec!(40), PaymentIntent::Capture)).await.unwrap();
+        let status = gateway.get_transaction_status(&reference).await.unwrap();
+        assert_eq!(status.status.as_deref(), Some("Succeeded"));
+
+        // The uncaptured 60 was released: a second capture is refused, refunds are bounded by the 40 captured
+        let second = gateway.submit_payment(follow_up(&reference, dec!(60), PaymentIntent::Capture)).await;
+        assert!(matches!(second, Err(GatewayError::RequestRejected { code: Some(ref code), .. }) if code == "charge_not_capturable"));
+        assert!(gateway.submit_payment(follow_up(&reference, dec!(41), PaymentIntent::Refund)).await.is_err());
+        gateway.submit_payment(follow_up(&reference, dec!(40), PaymentIntent::Refund)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_retried_capture_replays_the_first() {
+        let gateway = MockPaymentGateway::new();
+        let auth = gateway.submit_payment(request("tok_visa", dec!(100), PaymentIntent::Authorize, None)).await.unwrap();
+        let mut capture = follow_up(&auth.gateway_transaction_id, dec!(40), PaymentIntent::Capture);
+        capture.idempotency_key = Some("auth-1:CAPTURE".to_string());
+
+        let first = gateway.submit_payment(capture.clone()).await.unwrap();
+        let retried = gateway.submit_payment(capture).await.unwrap();
+        assert_eq!(first.gateway_transaction_id, retried.gateway_transaction_id);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_after_charge_is_discoverable() {
+        let gateway = MockPaymentGateway::new();
+        let result = gateway.submit_payment(request("tok_timeoutAfterCharge", dec!(25), PaymentIntent::AuthorizeAndCapture, Some("k-1"))).await;
+        assert!(matches!(result, Err(GatewayError::Timeout)));
+        assert!(gateway.find_by_idempotency_key("k-1").await.unwrap().is_some());
+
+        let result = gateway.submit_payment(request("tok_timeoutBeforeCharge", dec!(25), PaymentIntent::AuthorizeAndCapture, Some("k-2"))).await;
+        assert!(matches!(result, Err(GatewayError::Timeout)));
+        assert!(gateway.find_by_idempotency_key("k-2").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_async_settlement_changes_status_later() {
+        let gateway = MockPaymentGateway::new();
+        let pending = gateway.submit_payment(request("tok_asyncSettlement", dec!(40), PaymentIntent::AuthorizeAndCapture, None)).await.unwrap();
+        assert_eq!(pending.status.as_deref(), Some("Pending"));
+
+        let first_poll = gateway.get_transaction_status(&pending.gateway_transaction_id).await.unwrap();
+        assert_eq!(first_poll.status.as_deref(), Some("Pending"));
+        let second_poll = gateway.get_transaction_status(&pending.gateway_transaction_id).await.unwrap();
+        assert_eq!(second_poll.status.as_deref(), Some("Succeeded"));
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_idempotency_key_replays() {
+        let gateway = MockPaymentGateway::new();
+        let first = gateway.submit_payment(request("tok_visa", dec!(10), PaymentIntent::Authorize, Some("dup"))).await.unwrap();
+        let second = gateway.submit_payment(request("tok_visa", dec!(10), PaymentIntent::Authorize, Some("dup"))).await.unwrap();
+        assert_eq!(first.gateway_transaction_id, second.gateway_transaction_id);
+        assert_eq!(gateway.recorded_count(), 1);
+    }
+}
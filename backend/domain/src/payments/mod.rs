@@ -15,10 +15,20 @@ pub mod rtgs; // RTGS interaction logic/concepts
 pub mod validator; // Validation functions for payment details
 pub mod generator; // Generation of random data for testing/dev
 pub mod payment_processor; // Central payment orchestration service
+pub mod checks; // Payee, limit, sanctions, fraud and approval checks shared by outbound payment paths
 pub mod gateway; // Trait/interface for external payment gateways (cards, etc.)
+pub mod routing_gateway; // Multi-gateway routing/failover implementing PaymentGateway
+pub mod mock_gateway; // Scenario-driven mock gateway (test tokens, magic amounts)
+pub mod outbox; // Transactional outbox/saga: crash-safe dispatch of outbound payments
+pub mod state_machine; // Allowed TransactionStatus transitions and their financial effects
+pub mod conversion; // Locked FX quotes and two-leg conversions between a user's wallets
+pub mod recurrence; // Calendar rules, business-day conventions and missed-run planning for schedules
+pub mod scheduler; // Standing orders (future-dated / recurring payments) and the worker that pays them
+pub mod payout_batch; // Bulk payout files: per-row validation, one funds reservation, NACHA batch dispatch
+pub mod inbound; // Received payments from any rail: dedupe, wallet matching, crediting or suspense
 
 // Re-export key structs and functions for easier access from core-api or other modules
-pub use ach::{process_ach_debit, process_ach_credit, generate_ach_file}; // Example exports
+pub use ach::{process_ach_debit, process_ach_credit, generate_ach_file, build_credit_file, NachaOriginator}; // Example exports
 pub use card::{process_card_authorization, process_card_capture, process_card_refund};
 pub use check::process_check_deposit;
 pub use wire::{process_wire_transfer_outbound, process_wire_transfer_inbound};
@@ -30,4 +40,23 @@ pub use iso20022::{build_pacs_008, parse_camt_053}; // Example exports
 pub use swift_mt::format_mt103;
 pub use rtgs::{initiate_rtgs_payment, check_rtgs_settlement};
 pub use gateway::{PaymentGateway, MockPaymentGateway}; // Export gateway trait and mock
+pub use routing_gateway::{
+    RoutingPaymentGateway, RoutedGateway, RoutingRule, RoutingConfig, GatewayCost, GatewayKind, GatewayProviderConfig,
+    GatewayRoutingConfig,
+};
+pub use outbox::{OutboxWorker, OutboundDispatcher, OutboxPayload, DispatchError, RailDispatcher};
+pub use state_machine::{FinancialEffect, apply_transition, validate_transition};
+pub use conversion::{lock_quote, execute_quote, expire_quotes, LockQuote, ConversionOutcome};
+pub use recurrence::{BusinessCalendar, BusinessDayConvention, Frequency, MissedRunPolicy, RecurrenceRule};
+pub use scheduler::{
+    create_schedule, list_schedules, get_schedule, pause_schedule, resume_schedule, skip_next_occurrence, cancel_schedule,
+    CreateSchedule, ScheduledPaymentDetails, ScheduledPaymentWorker, ScheduleWithRuns,
+};
+pub use payout_batch::{
+    create_batch, list_batches, get_batch, validate_row, CreatePayoutBatch, PayoutBatchReport, PayoutBatchWorker, PayoutFileFormat,
+};
+pub use inbound::{
+    process_inbound, assign_suspense, return_suspense, list_open_suspense, open_virtual_account,
+    InboundNotice, InboundRail, InboundOutcome, InboundDisposition, SuspenseResolution,
+};
 pub use payment_processor::PaymentProcessor; // Export the orchestrator
\ No newline at end of file
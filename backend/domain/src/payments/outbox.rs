@@ -0,0 +1,510 @@
+// /home/inno/elights_jobes-research/backend/domain/src/payments/outbox.rs
+// Transactional outbox / saga for outbound payments.
+//
+// 1. `enqueue_outbound` is called in the SAME DB transaction as the wallet hold (the transaction's
+//    Pending -> Processing transition), so a committed hold always has an outbox row and vice versa.
+// 2. `OutboxWorker::run_once` leases due rows, calls the rail via an `OutboundDispatcher` and
+//    resolves the row: Submitted, retry later, or compensated (hold released, fees refunded).
+// 3. A worker that crashes mid-dispatch leaves its lease to expire; the next run picks the row up
+//    again and re-dispatches with the same `dispatch_key`, or parks it for review when the rail
+//    cannot de-duplicate.
+use crate::error::DomainError;
+use crate::fees;
+use crate::models::{
+    AchDetails, AuditOutcome, AuditTargetType, NewPaymentOutbox, NewPaymentSagaStep, OutboxOperation, OutboxStatus,
+    PaymentOutbox, Transaction, TransactionStatus, WireDetails,
+};
+use crate::payments::{ach, wire};
+use crate::payments::state_machine::{self, TransitionUpdate};
+use crate::sanctions::SanctionsScreener;
+use crate::security::audit;
+use crate::services::idempotency::derive_gateway_idempotency_key;
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub const DEFAULT_MAX_ATTEMPTS: i32 = 5;
+/// How long a worker owns a row while dispatching. Must exceed the slowest rail call.
+pub const DISPATCH_LEASE_SECONDS: i64 = 120;
+pub const RETRY_BASE_DELAY_SECONDS: i64 = 30;
+pub const RETRY_MAX_DELAY_SECONDS: i64 = 3600;
+
+/// Everything a dispatcher needs to execute the external leg, stored as the outbox payload.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct OutboxPayload {
+    pub ach_details: Option<AchDetails>,
+    pub wire_details: Option<WireDetails>,
+    pub use_iso20022: bool,
+    pub crypto_address: Option<String>,
+    pub crypto_payment_id: Option<String>, // Monero payment ID
+}
+
+/// Failure reported by a dispatcher. The variant decides what the saga does next.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DispatchError {
+    /// Rail refused the payment, nothing moved: release the hold now.
+    Rejected(String),
+    /// Payment was not sent (rail down, not configured): retry, reverse once attempts run out.
+    Unavailable(String),
+    /// Payment may have been executed (timeout, lost response): retry with the same key, never reverse blindly.
+    Ambiguous(String),
+}
+
+impl std::fmt::Display for DispatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DispatchError::Rejected(msg) => write!(f, "Rejected: {}", msg),
+            DispatchError::Unavailable(msg) => write!(f, "Unavailable: {}", msg),
+            DispatchError::Ambiguous(msg) => write!(f, "Ambiguous: {}", msg),
+        }
+    }
+}
+
+/// Executes the external leg of an outbound payment (ACH, Wire, crypto).
+#[async_trait]
+pub trait OutboundDispatcher: Send + Sync {
+    /// Sends the payment and returns the rail's reference. Must present `entry.dispatch_key` to the rail.
+    async fn dispatch(
+        &self,
+        entry: &PaymentOutbox,
+        transaction: &Transaction,
+        payload: &OutboxPayload,
+    ) -> Result<String, DispatchError>;
+
+    /// Whether re-sending with the same `dispatch_key` is safe (the rail de-duplicates).
+    /// When false, an interrupted or ambiguous dispatch is parked for review instead of retried.
+    fn deduplicates(&self, operation: OutboxOperation) -> bool;
+}
+
+/// How a dispatch attempt was settled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DispatchResolution {
+    Submitted(String),
+    RetryScheduled,
+    Compensated,
+    RequiresReview,
+    AlreadyResolved, // Another worker resolved the row first (e.g. our lease expired)
+}
+
+/// A leased outbox row. `recovered` is set when the row was taken over from an expired lease.
+#[derive(Debug, Clone)]
+pub struct ClaimedEntry {
+    pub entry: PaymentOutbox,
+    pub recovered: bool,
+}
+
+/// Backoff before the next attempt: base * 2^(attempts-1), capped.
+pub fn retry_delay(attempts: i32) -> Duration {
+    let exponent = attempts.clamp(1, 16) as u32 - 1;
+    let seconds = RETRY_BASE_DELAY_SECONDS.saturating_mul(1i64 << exponent);
+    Duration::seconds(seconds.min(RETRY_MAX_DELAY_SECONDS))
+}
+
+/// Appends a step to the saga log.
+pub fn record_step(
+    conn: &mut PgConnection,
+    outbox_id: Uuid,
+    step: &str,
+    success: bool,
+    details: Option<serde_json::Value>,
+) -> Result<(), DomainError> {
+    let new_step = NewPaymentSagaStep {
+        outbox_id,
+        step,
+        outcome: if success { "SUCCESS" } else { "FAILURE" },
+        details,
+    };
+    diesel::insert_into(crate::schema::payment_saga_steps::table)
+        .values(&new_step)
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Queues the external leg of `transaction`. Call inside the DB transaction that moved it to Processing
+/// (posting the HOLD), so a failed dispatch releases exactly the funds that were held.
+pub fn enqueue_outbound(
+    conn: &mut PgConnection,
+    transaction: &Transaction,
+    operation: OutboxOperation,
+    payload: &OutboxPayload,
+    client_idempotency_key: Option<&str>,
+) -> Result<PaymentOutbox, DomainError> {
+    let payload_json = serde_json::to_value(payload)
+        .map_err(|e| DomainError::Internal(format!("Failed to serialize outbox payload: {}", e)))?;
+    let dispatch_key = derive_gateway_idempotency_key(client_idempotency_key, transaction.transaction_id, "DISPATCH");
+
+    let new_entry = NewPaymentOutbox {
+        transaction_id: transaction.transaction_id,
+        operation: operation.as_str(),
+        payload: payload_json,
+        status: OutboxStatus::Pending.as_str(),
+        max_attempts: DEFAULT_MAX_ATTEMPTS,
+        dispatch_key: &dispatch_key,
+    };
+    let entry: PaymentOutbox = diesel::insert_into(crate::schema::payment_outbox::table)
+        .values(&new_entry)
+        .get_result(conn)?;
+
+    record_step(conn, entry.outbox_id, "HOLD", true, Some(json!({
+        "wallet_id": transaction.debit_wallet_id,
+        "amount": transaction.amount.to_string(),
+        "currency": transaction.currency_code,
+    })))?;
+    log::info!("Queued {} dispatch {} for Tx {}", operation.as_str(), entry.outbox_id, transaction.transaction_id);
+    Ok(entry)
+}
+
+/// Leases up to `limit` due rows: pending rows whose retry time has come, and rows whose lease expired.
+/// `SKIP LOCKED` lets several workers run side by side without picking the same row. A due row that has
+/// used up `max_attempts` (e.g. every worker that took it died mid-dispatch) is parked for review, not leased.
+pub fn claim_due_entries(conn: &mut PgConnection, limit: i64) -> Result<Vec<ClaimedEntry>, DomainError> {
+    use crate::schema::payment_outbox::dsl as po;
+
+    conn.transaction(|conn| {
+        let now = Utc::now();
+        let due: Vec<PaymentOutbox> = po::payment_outbox
+            .filter(
+                po::status.eq(OutboxStatus::Pending.as_str()).and(po::next_attempt_at.le(now))
+                    .or(po::status.eq(OutboxStatus::Dispatching.as_str()).and(po::locked_until.lt(now))),
+            )
+            .order(po::next_attempt_at.asc())
+            .limit(limit)
+            .for_update()
+            .skip_locked()
+            .load(conn)?;
+
+        let mut claimed = Vec::with_capacity(due.len());
+        for entry in due {
+            if entry.attempts >= entry.max_attempts {
+                let reason = format!("No outcome recorded after {} of {} attempts", entry.attempts, entry.max_attempts);
+                park_for_review(conn, &entry, &reason)?;
+                continue;
+            }
+            let recovered = entry.status == OutboxStatus::Dispatching.as_str();
+            if recovered {
+                log::warn!("Recovering outbox entry {} (Tx {}) after expired lease", entry.outbox_id, entry.transaction_id);
+            }
+            let leased: PaymentOutbox = diesel::update(po::payment_outbox.find(entry.outbox_id))
+                .set((
+                    po::status.eq(OutboxStatus::Dispatching.as_str()),
+                    po::locked_until.eq(Some(now + Duration::seconds(DISPATCH_LEASE_SECONDS))),
+                    po::attempts.eq(po::attempts + 1),
+                ))
+                .get_result(conn)?;
+            claimed.push(ClaimedEntry { entry: leased, recovered });
+        }
+        Ok(claimed)
+    })
+}
+
+/// Applies the outcome of a dispatch attempt. Runs in one DB transaction under a row lock, so each
+/// resolution (and in particular the compensating credit) happens at most once.
+pub fn resolve_dispatch(
+    conn: &mut PgConnection,
+    outbox_id: Uuid,
+    result: Result<String, DispatchError>,
+    rail_deduplicates: bool,
+) -> Result<DispatchResolution, DomainError> {
+    use crate::schema::payment_outbox::dsl as po;
+
+    conn.transaction(|conn| {
+        let entry: PaymentOutbox = po::payment_outbox.find(outbox_id).for_update().first(conn)?;
+        if entry.status != OutboxStatus::Dispatching.as_str() {
+            log::warn!("Outbox entry {} already resolved as {}, ignoring late result", outbox_id, entry.status);
+            return Ok(DispatchResolution::AlreadyResolved);
+        }
+
+        match result {
+            Ok(external_ref) => {
+                mark_submitted(conn, &entry, &external_ref)?;
+                Ok(DispatchResolution::Submitted(external_ref))
+            }
+            Err(DispatchError::Rejected(reason)) => {
+                compensate(conn, &entry, &reason)?;
+                Ok(DispatchResolution::Compensated)
+            }
+            Err(DispatchError::Unavailable(reason)) => {
+                if entry.attempts >= entry.max_attempts {
+                    compensate(conn, &entry, &format!("Gave up after {} attempts: {}", entry.attempts, reason))?;
+                    Ok(DispatchResolution::Compensated)
+                } else {
+                    schedule_retry(conn, &entry, &reason)?;
+                    Ok(DispatchResolution::RetryScheduled)
+                }
+            }
+            Err(DispatchError::Ambiguous(reason)) => {
+                if !rail_deduplicates || entry.attempts >= entry.max_attempts {
+                    park_for_review(conn, &entry, &reason)?;
+                    Ok(DispatchResolution::RequiresReview)
+                } else {
+                    schedule_retry(conn, &entry, &reason)?;
+                    Ok(DispatchResolution::RetryScheduled)
+                }
+            }
+        }
+    })
+}
+
+fn mark_submitted(conn: &mut PgConnection, entry: &PaymentOutbox, external_ref: &str) -> Result<(), DomainError> {
+    use crate::schema::payment_outbox::dsl as po;
+    use crate::schema::transactions::dsl as t;
+
+    diesel::update(po::payment_outbox.find(entry.outbox_id))
+        .set((
+            po::status.eq(OutboxStatus::Submitted.as_str()),
+            po::external_ref.eq(Some(external_ref)),
+            po::locked_until.eq(None::<chrono::DateTime<Utc>>),
+            po::last_error.eq(None::<String>),
+        ))
+        .execute(conn)?;
+    let transaction: Transaction = t::transactions.find(entry.transaction_id).for_update().first(conn)?;
+    let update = TransitionUpdate { external_ref: Some(external_ref), ..Default::default() };
+    state_machine::apply_transition(conn, &transaction, TransactionStatus::Submitted, update, "OUTBOX_WORKER")?;
+    record_step(conn, entry.outbox_id, "SUBMITTED", true, Some(json!({"external_ref": external_ref, "attempt": entry.attempts})))?;
+    log::info!("Outbox entry {} submitted, Tx {} ref {}", entry.outbox_id, entry.transaction_id, external_ref);
+    Ok(())
+}
+
+fn schedule_retry(conn: &mut PgConnection, entry: &PaymentOutbox, reason: &str) -> Result<(), DomainError> {
+    use crate::schema::payment_outbox::dsl as po;
+
+    let next_attempt_at = Utc::now() + retry_delay(entry.attempts);
+    diesel::update(po::payment_outbox.find(entry.outbox_id))
+        .set((
+            po::status.eq(OutboxStatus::Pending.as_str()),
+            po::next_attempt_at.eq(next_attempt_at),
+            po::locked_until.eq(None::<chrono::DateTime<Utc>>),
+            po::last_error.eq(Some(reason)),
+        ))
+        .execute(conn)?;
+    record_step(conn, entry.outbox_id, "RETRY_SCHEDULED", false, Some(json!({
+        "attempt": entry.attempts, "error": reason, "next_attempt_at": next_attempt_at,
+    })))?;
+    log::warn!("Dispatch of outbox entry {} failed (attempt {}), retrying at {}: {}", entry.outbox_id, entry.attempts, next_attempt_at, reason);
+    Ok(())
+}
+
+fn park_for_review(conn: &mut PgConnection, entry: &PaymentOutbox, reason: &str) -> Result<(), DomainError> {
+    use crate::schema::payment_outbox::dsl as po;
+
+    diesel::update(po::payment_outbox.find(entry.outbox_id))
+        .set((
+            po::status.eq(OutboxStatus::RequiresReview.as_str()),
+            po::locked_until.eq(None::<chrono::DateTime<Utc>>),
+            po::last_error.eq(Some(reason)),
+        ))
+        .execute(conn)?;
+    record_step(conn, entry.outbox_id, "REVIEW", false, Some(json!({"attempt": entry.attempts, "error": reason})))?;
+    audit::log_db_audit_event(
+        conn,
+        None,
+        "OUTBOX_WORKER",
+        "OUTBOUND_PAYMENT_REQUIRES_REVIEW",
+        Some(AuditTargetType::Transaction),
+        Some(&entry.transaction_id.to_string()),
+        AuditOutcome::Failure,
+        Some(json!({"outbox_id": entry.outbox_id, "operation": entry.operation})),
+        Some(reason),
+    )?;
+    log::error!("Outbox entry {} (Tx {}) needs manual review, rail outcome unknown: {}", entry.outbox_id, entry.transaction_id, reason);
+    Ok(())
+}
+
+/// Compensating action: fails the transaction, which releases the hold on the wallet, and refunds its fees.
+fn compensate(conn: &mut PgConnection, entry: &PaymentOutbox, reason: &str) -> Result<(), DomainError> {
+    use crate::schema::payment_outbox::dsl as po;
+    use crate::schema::transactions::dsl as t;
+
+    let transaction: Transaction = t::transactions.find(entry.transaction_id).for_update().first(conn)?;
+    let update = TransitionUpdate {
+        metadata: Some(json!({"compensation": {
+            "reason": reason,
+            "reversed_amount": transaction.amount.to_string(),
+            "reversed_at": Utc::now(),
+        }})),
+        ..Default::default()
+    };
+    state_machine::apply_transition(conn, &transaction, TransactionStatus::Failed, update, "OUTBOX_WORKER")?;
+    // Nothing was sent, so the fee charged for it is refunded too
+    let fees_refunded = fees::reverse_linked_fees(conn, transaction.transaction_id, reason, "OUTBOX_WORKER")?;
+
+    diesel::update(po::payment_outbox.find(entry.outbox_id))
+        .set((
+            po::status.eq(OutboxStatus::Compensated.as_str()),
+            po::locked_until.eq(None::<chrono::DateTime<Utc>>),
+            po::last_error.eq(Some(reason)),
+        ))
+        .execute(conn)?;
+    record_step(conn, entry.outbox_id, "COMPENSATE", true, Some(json!({
+        "wallet_id": transaction.debit_wallet_id, "amount": transaction.amount.to_string(), "reason": reason,
+        "fees_refunded": fees_refunded,
+    })))?;
+    audit::log_db_audit_event(
+        conn,
+        None,
+        "OUTBOX_WORKER",
+        "REVERSE_OUTBOUND_PAYMENT",
+        Some(AuditTargetType::Transaction),
+        Some(&entry.transaction_id.to_string()),
+        AuditOutcome::Success,
+        Some(json!({"outbox_id": entry.outbox_id, "amount": transaction.amount.to_string(), "reason": reason})),
+        None,
+    )?;
+    log::warn!("Released hold for Tx {} ({} {}): {}", transaction.transaction_id, transaction.amount, transaction.currency_code, reason);
+    Ok(())
+}
+
+/// Drains the outbox. Run periodically; safe to run in several processes at once.
+pub struct OutboxWorker<'a> {
+    dispatcher: &'a dyn OutboundDispatcher,
+    batch_size: i64,
+}
+
+impl<'a> OutboxWorker<'a> {
+    pub fn new(dispatcher: &'a dyn OutboundDispatcher) -> Self {
+        OutboxWorker { dispatcher, batch_size: 20 }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: i64) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Processes one batch of due entries. Returns how many entries were handled.
+    pub async fn run_once(&self, conn: &mut PgConnection) -> Result<usize, DomainError> {
+        let claimed = claim_due_entries(conn, self.batch_size)?;
+        let count = claimed.len();
+        for claim in claimed {
+            if let Err(e) = self.process_entry(conn, claim).await {
+                // Row keeps its lease and is picked up again once it expires
+                log::error!("Outbox processing error: {}", e);
+            }
+        }
+        Ok(count)
+    }
+
+    async fn process_entry(&self, conn: &mut PgConnection, claim: ClaimedEntry) -> Result<DispatchResolution, DomainError> {
+        let entry = claim.entry;
+        let Some(operation) = OutboxOperation::parse(&entry.operation) else {
+            return resolve_dispatch(conn, entry.outbox_id, Err(DispatchError::Rejected(format!("Unknown operation {}", entry.operation))), false);
+        };
+        let deduplicates = self.dispatcher.deduplicates(operation);
+
+        // A previous worker died mid-call: only re-send if the rail will recognise the duplicate
+        if claim.recovered && !deduplicates {
+            return resolve_dispatch(conn, entry.outbox_id,
+                Err(DispatchError::Ambiguous("Worker interrupted during dispatch".to_string())), false);
+        }
+
+        let payload: OutboxPayload = match serde_json::from_value(entry.payload.clone()) {
+            Ok(payload) => payload,
+            Err(e) => {
+                return resolve_dispatch(conn, entry.outbox_id, Err(DispatchError::Rejected(format!("Invalid payload: {}", e))), deduplicates);
+            }
+        };
+        let transaction: Transaction = crate::schema::transactions::table.find(entry.transaction_id).first(conn)?;
+
+        record_step(conn, entry.outbox_id, "DISPATCH", true, Some(json!({"attempt": entry.attempts, "recovered": claim.recovered})))?;
+        let result = self.dispatcher.dispatch(&entry, &transaction, &payload).await;
+        let resolution = resolve_dispatch(conn, entry.outbox_id, result, deduplicates)?;
+        log::info!("Outbox entry {} (Tx {}) resolved: {:?}", entry.outbox_id, entry.transaction_id, resolution);
+        Ok(resolution)
+    }
+}
+
+/// Default dispatcher for fiat rails; crypto sends are delegated to an injected dispatcher
+/// (the API layer owns the wallet RPC clients). With a screener, built wire messages are screened
+/// before submission and a hit rejects the entry.
+#[derive(Default, Clone)]
+pub struct RailDispatcher {
+    crypto: Option<Arc<dyn OutboundDispatcher>>,
+    sanctions_screener: Option<Arc<SanctionsScreener>>,
+}
+
+impl RailDispatcher {
+    pub fn new() -> Self {
+        RailDispatcher::default()
+    }
+
+    pub fn with_crypto_dispatcher(mut self, crypto: Arc<dyn OutboundDispatcher>) -> Self {
+        self.crypto = Some(crypto);
+        self
+    }
+
+    pub fn with_sanctions_screener(mut self, screener: Arc<SanctionsScreener>) -> Self {
+        self.sanctions_screener = Some(screener);
+        self
+    }
+}
+
+/// Maps a rail error onto the saga: input problems are definitive, anything else may have reached the rail.
+fn classify_rail_error(err: DomainError) -> DispatchError {
+    match err {
+        DomainError::Validation(msg) | DomainError::NotSupported(msg) => DispatchError::Rejected(msg),
+        other => DispatchError::Ambiguous(other.to_string()),
+    }
+}
+
+#[async_trait]
+impl OutboundDispatcher for RailDispatcher {
+    async fn dispatch(
+        &self,
+        entry: &PaymentOutbox,
+        transaction: &Transaction,
+        payload: &OutboxPayload,
+    ) -> Result<String, DispatchError> {
+        match OutboxOperation::parse(&entry.operation) {
+            Some(OutboxOperation::AchCredit) => {
+                let details = payload.ach_details.as_ref()
+                    .ok_or_else(|| DispatchError::Rejected("Missing ACH details".to_string()))?;
+                ach::submit_ach_credit_entry(transaction, details).await.map_err(classify_rail_error)
+            }
+            Some(OutboxOperation::WireOutbound) => {
+                let details = payload.wire_details.as_ref()
+                    .ok_or_else(|| DispatchError::Rejected("Missing Wire details".to_string()))?;
+                wire::submit_outbound_wire(transaction, details, payload.use_iso20022, self.sanctions_screener.as_deref())
+                    .await.map_err(classify_rail_error)
+            }
+            Some(OutboxOperation::CryptoSend) => match &self.crypto {
+                Some(crypto) => crypto.dispatch(entry, transaction, payload).await,
+                None => Err(DispatchError::Unavailable("No crypto dispatcher configured".to_string())),
+            },
+            None => Err(DispatchError::Rejected(format!("Unknown operation {}", entry.operation))),
+        }
+    }
+
+    fn deduplicates(&self, operation: OutboxOperation) -> bool {
+        match operation {
+            // The UETR is derived from the transaction, so the wire network recognises a resubmission
+            OutboxOperation::WireOutbound => true,
+            // An ODFI file is not deduplicated on trace numbers: a replayed submit could send the entry twice
+            OutboxOperation::AchCredit => false,
+            OutboxOperation::CryptoSend => self.crypto.as_ref().map(|c| c.deduplicates(operation)).unwrap_or(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_backs_off_and_caps() {
+        assert_eq!(retry_delay(1), Duration::seconds(RETRY_BASE_DELAY_SECONDS));
+        assert_eq!(retry_delay(2), Duration::seconds(RETRY_BASE_DELAY_SECONDS * 2));
+        assert_eq!(retry_delay(4), Duration::seconds(RETRY_BASE_DELAY_SECONDS * 8));
+        assert_eq!(retry_delay(40), Duration::seconds(RETRY_MAX_DELAY_SECONDS));
+        assert_eq!(retry_delay(0), Duration::seconds(RETRY_BASE_DELAY_SECONDS));
+    }
+
+    #[test]
+    fn test_rail_errors_are_classified_conservatively() {
+        assert!(matches!(classify_rail_error(DomainError::Validation("bad IBAN".into())), DispatchError::Rejected(_)));
+        assert!(matches!(classify_rail_error(DomainError::WireTransfer("timeout".into())), DispatchError::Ambiguous(_)));
+        assert!(matches!(classify_rail_error(DomainError::NetworkError("reset".into())), DispatchError::Ambiguous(_)));
+    }
+}
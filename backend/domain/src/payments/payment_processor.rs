@@ -1,19 +1,32 @@
 // /home/inno/elights_jobes-research/backend/domain/src/payments/payment_processor.rs
 use diesel::prelude::*;
 use crate::models::{
-    Transaction, TransactionType, TransactionStatus, Wallet, User,
-    AchDetails, WireDetails, CardDetails, CheckDetails, PaymentDetails, UpdateTransaction, NewTransaction
+    Transaction, TransactionType, TransactionStatus, Wallet,
+    AchDetails, WireDetails, CardDetails, CheckDetails, PaymentDetails, UpdateTransaction, NewTransaction,
+    AuditOutcome, AuditTargetType, OutboxOperation, WebhookEvent,
 };
 use crate::error::DomainError;
 use crate::payments::{
     ach, card, check, wire, rtgs, validator, // Import specific payment modules
     gateway::{PaymentGateway}, // Import gateway trait
+    outbox::{self, OutboxPayload}, // Outbox queueing for external legs
+    inbound::{self, InboundNotice, InboundOutcome}, // Received payments from any rail
+    state_machine::{self, TransitionUpdate}, // Status transition validation + balance effects
+    checks::{CheckOutcome, OutboundChecks, OutboundPayment}, // Payee, limit, sanctions, fraud and approval checks
 };
-use crate::services::fraud_detection; // Import fraud detection
+use crate::ledger; // Available balance checks
+use crate::fees::{self, ChargeBearer, FeeSchedule}; // Fee quoting and charging
+use crate::approvals::{ApprovalPolicySet, PendingDispatch}; // Maker-checker approvals
+use crate::beneficiaries::{CheckedPayee, CoolingOffLimits}; // Saved payees
+use crate::limits::LimitPolicy; // Per-transaction and rolling limits
+use crate::sanctions::SanctionsScreener; // Watchlist screening of payment parties
+use crate::webhooks::{self, WebhookDispatch, WebhookProvider}; // Provider status webhooks
+use crate::fraud::{DeviceContext, FraudRuleSet}; // Fraud rules
 use crate::security::audit; // Import audit logging
 use rust_decimal::Decimal;
 use uuid::Uuid;
 use serde_json::json;
+use chrono::{DateTime, Utc};
 
 /// Structure holding dependencies for payment processing.
 pub struct PaymentProcessor<'a> {
@@ -21,7 +34,11 @@ pub struct PaymentProcessor<'a> {
     db_connection: &'a mut PgConnection,
     // Inject the specific payment gateway implementation being used
     card_gateway: &'a dyn PaymentGateway,
-    // Add other dependencies like fraud service config, rate service client etc.
+    // Fee schedule in force; without one no fees are charged
+    fee_schedule: Option<&'a FeeSchedule>,
+    // Beneficiary, limit, sanctions, fraud and approval checks in force; each is skipped without its policy
+    checks: OutboundChecks<'a>,
+    // Add other dependencies like rate service client etc.
 }
 
 // Represents a generic payment request
@@ -42,8 +59,23 @@ pub struct PaymentRequest<'a> {
     // Common fields
     pub description: &'a str,
     pub metadata: Option<serde_json::Value>,
+    pub idempotency_key: Option<&'a str>, // Client Idempotency-Key, passed through to gateways
+    pub charge_bearer: ChargeBearer, // Who pays our fee (OUR/SHA on top of the amount, BEN out of it)
+    pub device: Option<&'a DeviceContext>, // Where the request came from (None for scheduled payments)
+    pub scheduled_run_id: Option<Uuid>, // Schedule run making this payment (None for API requests)
 }
 
+/// Adds `key` to the client's metadata object (replacing metadata that is not an object).
+fn insert_metadata(metadata: &mut Option<serde_json::Value>, key: &str, value: serde_json::Value) {
+    match metadata.as_mut().and_then(|m| m.as_object_mut()) {
+        Some(object) => { object.insert(key.to_string(), value); }
+        None => {
+            let mut object = serde_json::Map::new();
+            object.insert(key.to_string(), value);
+            *metadata = Some(serde_json::Value::Object(object));
+        }
+    }
+}
 
 impl<'a> PaymentProcessor<'a> {
     /// Creates a new PaymentProcessor instance.
@@ -51,10 +83,58 @@ impl<'a> PaymentProcessor<'a> {
         db_connection: &'a mut PgConnection,
         card_gateway: &'a dyn PaymentGateway,
     ) -> Self {
-        PaymentProcessor { db_connection, card_gateway }
+        PaymentProcessor { db_connection, card_gateway, fee_schedule: None, checks: OutboundChecks::default() }
+    }
+
+    /// Charges fees from `fee_schedule` on the payments this processor initiates.
+    pub fn with_fee_schedule(mut self, fee_schedule: &'a FeeSchedule) -> Self {
+        self.fee_schedule = Some(fee_schedule);
+        self
+    }
+
+    /// Parks payments matching `approval_policies` in RequiresAction until they are approved.
+    pub fn with_approval_policies(mut self, approval_policies: &'a ApprovalPolicySet) -> Self {
+        self.checks.approval_policies = Some(approval_policies);
+        self
+    }
+
+    /// Matches payees to the payer's saved beneficiaries: they must be verified, and new ones stay
+    /// within `beneficiary_limits` until their cooling-off period ends.
+    pub fn with_beneficiary_limits(mut self, beneficiary_limits: &'a CoolingOffLimits) -> Self {
+        self.checks.beneficiary_limits = Some(beneficiary_limits);
+        self
+    }
+
+    /// Checks payments against `limit_policy` and counts them in the rolling limit windows.
+    pub fn with_limit_policy(mut self, limit_policy: &'a LimitPolicy) -> Self {
+        self.checks.limit_policy = Some(limit_policy);
+        self
+    }
+
+    /// Screens wire and crypto withdrawal parties; payments with hits wait in RequiresAction for compliance.
+    pub fn with_sanctions_screener(mut self, sanctions_screener: &'a SanctionsScreener) -> Self {
+        self.checks.sanctions_screener = Some(sanctions_screener);
+        self
+    }
+
+    /// Scores payments with `fraud_rules`; flagged ones wait in RequiresAction for an analyst, blocked ones fail.
+    pub fn with_fraud_rules(mut self, fraud_rules: &'a FraudRuleSet) -> Self {
+        self.checks.fraud_rules = Some(fraud_rules);
+        self
     }
 
     /// Processes an outbound payment request.
+    /// The wallet debit, the fee and the outbox entry are committed in one DB transaction; the rail call is made
+    /// afterwards by the outbox worker (`payments::outbox`), which reverses the debit and refunds the fee if
+    /// dispatch fails. With a BEN charge bearer the fee is taken out of `amount` and the rest is sent.
+    /// A payment matching an approval policy is returned in RequiresAction instead: nothing is held, charged
+    /// or queued until `approvals::approve_payment` releases it.
+    /// Payments over a transaction limit are refused with `DomainError::LimitExceeded`; accepted ones count
+    /// against the rolling windows until they fail, are cancelled or expire.
+    /// Wires and crypto withdrawals with sanctions screening hits are returned in RequiresAction with a
+    /// compliance case (`sanctions::clear_case` / `sanctions::confirm_case`), before any approval is requested.
+    /// Payments the fraud rules send to review are returned in RequiresAction too (`fraud::clear_review` /
+    /// `fraud::confirm_review`); payments they block are returned Failed, with nothing held or charged.
     pub async fn process_outbound_payment(
         &mut self,
         request: PaymentRequest<'a>,
@@ -62,268 +142,226 @@ impl<'a> PaymentProcessor<'a> {
         log::info!("Processing outbound payment request. User: {}, Type: {:?}, Amount: {} {}",
             request.initiating_user_id, request.payment_type, request.amount, request.currency);
 
-        // Use a database transaction for atomicity
-        self.db_connection.transaction(|conn| {
-            // Box the future to handle different async blocks within transaction
-            Box::pin(async move {
-                // --- 1. Initial Validation & Wallet Checks ---
-                if request.amount <= Decimal::ZERO {
-                    return Err(DomainError::Validation("Amount must be positive".to_string()));
-                }
-                // Validate currency code (basic check)
-                if iso_4217::CurrencyCode::try_from(request.currency).is_err() {
-                    return Err(DomainError::Validation(format!("Invalid currency code: {}", request.currency)));
-                }
-
-                let source_wallet_id = request.source_wallet_id
-                    .ok_or(DomainError::Validation("Source wallet ID required for outbound payment".to_string()))?;
-
-                // Find source wallet, check status and currency, lock row for update
-                let source_wallet: Wallet = crate::schema::wallets::table
-                    .find(source_wallet_id)
-                    .for_update() // Lock the row
-                    .first(conn)
-                    .map_err(|e| DomainError::NotFound(format!("Source wallet {} not found or lock failed: {}", source_wallet_id, e)))?;
-
-                if source_wallet.status != crate::models::WalletStatus::Active.to_string() { // Compare string if enum not mapped
-                     return Err(DomainError::Validation(format!("Source wallet {} is not active", source_wallet_id)));
-                 }
-                 if source_wallet.currency_code != request.currency {
-                     return Err(DomainError::Validation(format!("Source wallet currency ({}) does not match transaction currency ({})", source_wallet.currency_code, request.currency)));
-                 }
-
-                // --- 2. Check Funds ---
-                if source_wallet.balance < request.amount {
-                    return Err(DomainError::InsufficientFunds(source_wallet_id));
-                }
-
-                // --- 3. Create Initial Transaction Record ---
-                 let new_tx = NewTransaction {
-                    transaction_id: None, // Let DB generate UUID
-                    debit_wallet_id: Some(source_wallet_id),
-                    credit_wallet_id: request.destination_wallet_id, // Can be None for external
-                    transaction_type: request.payment_type.to_string().as_str(),
-                    status: TransactionStatus::Pending.to_string().as_str(),
-                    amount: request.amount,
-                    currency_code: request.currency,
-                    description: Some(request.description),
-                    external_ref_id: None,
-                    metadata: request.metadata.clone(), // Clone metadata
-                };
-
-                 let mut transaction: Transaction = diesel::insert_into(crate::schema::transactions::table)
-                    .values(&new_tx)
-                    .get_result(conn)?;
-
-                // --- 4. Debit Source Wallet Immediately ---
-                // This ensures funds are reserved before calling external systems.
-                let new_balance = source_wallet.balance - request.amount;
-                diesel::update(crate::schema::wallets::table.find(source_wallet_id))
-                    .set(crate::schema::wallets::balance.eq(decimal_to_bigdecimal(new_balance))) // Use BigDecimal for Diesel
-                    .execute(conn)?;
-                log::info!("Debited {} {} from wallet {}", request.amount, request.currency, source_wallet_id);
-
-
-                // --- 5. Perform Fraud Check (Conceptual) ---
-                // let is_fraudulent = fraud_detection::detect_fraud(&transaction)?; // Pass immutable borrow
-                // if is_fraudulent {
-                //      // Rollback debit, mark transaction as failed/requires_action
-                //      diesel::update(crate::schema::wallets::table.find(source_wallet_id))
-                //          .set(crate::schema::wallets::balance.eq(decimal_to_bigdecimal(source_wallet.balance))) // Revert balance
-                //          .execute(conn)?;
-                //      transaction = diesel::update(crate::schema::transactions::table.find(transaction.transaction_id))
-                //          .set(crate::schema::transactions::status.eq(TransactionStatus::Failed.to_string())) // Or RequiresAction
-                //          .get_result(conn)?;
-                //      return Err(DomainError::PaymentProcessing("Fraud detected".to_string()));
-                // }
-
-
-                // --- 6. Route to Specific Payment Logic ---
-                // Note: External calls (await) cannot be made directly inside diesel::transaction closure
-                // Strategy: Perform DB changes, commit, then make external call, then update DB again in new transaction.
-                // Or, use transaction manager pattern if library supports it.
-                // For simplicity here, assume external calls are done AFTER this DB transaction commits.
-
-                // Update status before potential commit
-                 transaction = diesel::update(crate::schema::transactions::table.find(transaction.transaction_id))
-                         .set(crate::schema::transactions::status.eq(TransactionStatus::Processing.to_string())) // Mark as processing
-                         .get_result(conn)?;
-
-
-                // --- 7. Log Audit Event ---
-                 audit::log_db_audit_event(
-                     conn,
-                     Some(request.initiating_user_id),
-                     &request.initiating_user_id.to_string(), // Actor ID
-                     "INITIATE_OUTBOUND_PAYMENT",
-                     Some("TRANSACTION"),
-                     Some(&transaction.transaction_id.to_string()),
-                     AuditOutcome::Success,
-                     Some(json!({"amount": request.amount.to_string(), "currency": request.currency, "type": request.payment_type.to_string()})),
-                     None // No error message for success
-                 )?;
-
-
-                Ok(transaction) // Return the transaction state BEFORE external calls
-            }) // End Box::pin(async move)
-        })?; // End db_connection.transaction
-
-
-        // --- 7. Make External Calls (AFTER DB Transaction Commit) ---
-        // We use the 'transaction' state returned from the DB transaction.
-        let processing_result: Result<String, DomainError> = match request.payment_type {
+        // --- 1. Resolve the external leg before touching any balance ---
+        let (operation, payload) = match request.payment_type {
             TransactionType::AchCredit => {
                 let details = request.ach_details
                     .ok_or(DomainError::Validation("Missing ACH details for credit".to_string()))?;
-                // ACH processing might return immediately or later via webhook
-                // For now, assume it just needs submission details.
-                // TODO: Call ACH service/gateway here (e.g., submit to batch). Requires external call.
-                 ach::process_ach_credit(self.db_connection, request.initiating_user_id, request.source_wallet_id.unwrap(), details, request.amount, request.description, request.metadata).await?;
-                Ok("ACH Credit Submitted".to_string()) // Placeholder result
-            },
-             TransactionType::WireOutbound => {
+                (OutboxOperation::AchCredit, OutboxPayload { ach_details: Some(details.clone()), ..Default::default() })
+            }
+            TransactionType::WireOutbound => {
                 let details = request.wire_details
                     .ok_or(DomainError::Validation("Missing Wire details for outbound".to_string()))?;
-                // TODO: Call Wire service/gateway here. Requires external call.
-                 wire::process_wire_transfer_outbound(self.db_connection, request.initiating_user_id, request.source_wallet_id.unwrap(), details, request.amount, request.currency, request.description, true, request.metadata).await?;
-                 Ok("Wire Transfer Submitted".to_string()) // Placeholder result
-            },
-             TransactionType::CardAuthorization => {
-                 // Card Auth involves external call but doesn't usually move funds yet
-                 // The debit might have been premature above - card flows differ.
-                 // Revisit the flow: create Tx, call gateway, update Tx & MAYBE debit wallet later on capture.
-                 // For now, return error indicating flow needs adjustment.
-                 return Err(DomainError::NotSupported("Card Authorization flow needs adjustment in processor".to_string()));
-             },
-             TransactionType::CryptoBtcSend | TransactionType::CryptoXmrSend => {
-                  let address = request.crypto_address
-                      .ok_or(DomainError::Validation("Missing destination crypto address".to_string()))?;
-                  // TODO: Call cryptography_exchange service here. Requires external call.
-                  // crypto_exchange_client.send_crypto(wallet_id, address, amount, currency).await?;
-                  Ok(format!("{:?} Submitted", request.payment_type))
-             }
-            // Add other outbound types (CheckWithdrawal?)
-            _ => Err(DomainError::NotSupported(format!("Outbound processing not supported for type: {:?}", request.payment_type)))
+                (OutboxOperation::WireOutbound, OutboxPayload { wire_details: Some(details.clone()), use_iso20022: true, ..Default::default() })
+            }
+            TransactionType::CryptoBtcSend | TransactionType::CryptoXmrSend => {
+                let address = request.crypto_address
+                    .ok_or(DomainError::Validation("Missing destination crypto address".to_string()))?;
+                (OutboxOperation::CryptoSend, OutboxPayload { crypto_address: Some(address.to_string()), ..Default::default() })
+            }
+            TransactionType::CardAuthorization => {
+                // Card flows authorize first and only move funds on capture, they do not go through the outbox
+                return Err(DomainError::NotSupported("Card Authorization flow needs adjustment in processor".to_string()));
+            }
+            _ => return Err(DomainError::NotSupported(format!("Outbound processing not supported for type: {:?}", request.payment_type))),
         };
 
-        // --- 8. Final DB Update (New Transaction) ---
-        // Update status based on external call result (e.g., Submitted, Failed)
-         self.db_connection.transaction(|conn| {
-            Box::pin(async move {
-                let final_status;
-                let external_ref;
-                match processing_result {
-                     Ok(ext_ref) => {
-                         final_status = TransactionStatus::Submitted;
-                         external_ref = Some(ext_ref);
-                         log::info!("External call successful for Tx: {}", transaction.transaction_id);
-                     }
-                     Err(e) => {
-                         final_status = TransactionStatus::Failed;
-                         external_ref = None;
-                         log::error!("External call failed for Tx: {}: {}", transaction.transaction_id, e);
-                         // TODO: CRITICAL - Need to re-credit the source wallet if debit occurred in step 4!
-                         // This requires careful state management and potentially a separate reversal transaction.
-                         // update_wallet_balance(conn, source_wallet_id, request.amount)?; // Re-credit
-                     }
-                 };
-
-                let update_tx = UpdateTransaction {
-                    status: Some(final_status.to_string().as_str()),
-                    external_ref_id: external_ref.as_deref(),
-                    metadata: None, // Keep existing metadata unless updated
-                    settlement_at: None,
-                };
-                 let updated_tx = diesel::update(crate::schema::transactions::table.find(transaction.transaction_id))
-                    .set(&update_tx)
-                    .get_result(conn)?;
-
-                Ok(updated_tx)
-            }) // End Box::pin
-        }) // End transaction
+        // --- 2. Debit + fee + outbox row, atomically ---
+        let fee_schedule = self.fee_schedule;
+        let checks = self.checks;
+        self.db_connection.transaction(|conn| {
+            // Initial Validation & Wallet Checks
+            if request.amount <= Decimal::ZERO {
+                return Err(DomainError::Validation("Amount must be positive".to_string()));
+            }
+            // Validate currency code (basic check)
+            if iso_4217::CurrencyCode::try_from(request.currency).is_err() {
+                return Err(DomainError::Validation(format!("Invalid currency code: {}", request.currency)));
+            }
+
+            let source_wallet_id = request.source_wallet_id
+                .ok_or(DomainError::Validation("Source wallet ID required for outbound payment".to_string()))?;
+
+            // Find source wallet, check status and currency, lock row for update
+            let source_wallet: Wallet = crate::schema::wallets::table
+                .find(source_wallet_id)
+                .for_update() // Lock the row
+                .first(conn)
+                .map_err(|e| DomainError::NotFound(format!("Source wallet {} not found or lock failed: {}", source_wallet_id, e)))?;
+
+            if source_wallet.status != crate::models::WalletStatus::Active.to_string() { // Compare string if enum not mapped
+                return Err(DomainError::Validation(format!("Source wallet {} is not active", source_wallet_id)));
+            }
+            if source_wallet.currency_code != request.currency {
+                return Err(DomainError::Validation(format!("Source wallet currency ({}) does not match transaction currency ({})", source_wallet.currency_code, request.currency)));
+            }
+
+            // Payee checks (verified, cooling-off limits); the payment is linked to its payee
+            let payment = OutboundPayment {
+                user_id: request.initiating_user_id,
+                source_wallet: &source_wallet,
+                payment_type: &request.payment_type,
+                amount: request.amount,
+                currency: request.currency,
+                ach_details: request.ach_details,
+                wire_details: request.wire_details,
+                crypto_address: request.crypto_address,
+                device: request.device,
+            };
+            let payee = checks.check_payee(conn, &payment, Utc::now())?;
+
+            // Price the payment at the customer's tier
+            let fee_quote = match fee_schedule {
+                Some(schedule) => fees::quote_for_user(
+                    conn, schedule, request.initiating_user_id, &request.payment_type,
+                    request.amount, request.currency, request.charge_bearer,
+                )?,
+                None => None,
+            };
+            let (principal, total_debit) = fee_quote.as_ref()
+                .map(|q| (q.net_amount, q.total_debit))
+                .unwrap_or((request.amount, request.amount));
+
+            // Check Funds against the available balance (ledger balance minus active holds), fee included
+            ledger::ensure_available(conn, source_wallet_id, total_debit)?;
+
+            let mut metadata = request.metadata.clone();
+            if let Some(quote) = &fee_quote {
+                let fee_info = json!({"amount": quote.fee_amount.to_string(), "rule_id": quote.rule_id, "charge_bearer": quote.charge_bearer.as_str(), "requested_amount": quote.amount.to_string()});
+                insert_metadata(&mut metadata, "fee", fee_info);
+            }
+            for (key, value) in payee.iter().flat_map(CheckedPayee::metadata) {
+                insert_metadata(&mut metadata, key, value);
+            }
+
+            // Create Transaction Record (Pending; moved to Processing below, which holds the funds)
+            let new_tx = NewTransaction {
+                transaction_id: None, // Let DB generate UUID
+                debit_wallet_id: Some(source_wallet_id),
+                credit_wallet_id: request.destination_wallet_id, // Can be None for external
+                transaction_type: request.payment_type.to_string().as_str(),
+                status: TransactionStatus::Pending.to_string().as_str(),
+                amount: principal, // Net of the fee when the beneficiary bears it
+                currency_code: request.currency,
+                description: Some(request.description),
+                external_ref_id: None,
+                metadata, // Client metadata plus the fee charged and the payee paid
+                scheduled_run_id: request.scheduled_run_id,
+            };
+            let transaction: Transaction = diesel::insert_into(crate::schema::transactions::table)
+                .values(&new_tx)
+                .get_result(conn)?;
+
+            // Limits, sanctions, fraud rules and approvals: a stopped payment is returned with nothing held or queued
+            let dispatch = PendingDispatch {
+                operation,
+                payload: payload.clone(),
+                fee_quote: fee_quote.clone(),
+                idempotency_key: request.idempotency_key.map(str::to_string),
+            };
+            let fraud_metadata = match checks.run(conn, &payment, &transaction, &dispatch, Utc::now())? {
+                CheckOutcome::Stopped(transaction) => return Ok(transaction),
+                CheckOutcome::Proceed { metadata } => metadata,
+            };
+
+            // Hold the funds before the external call (a wallet hold; the journal is only posted on settlement)
+            let update = TransitionUpdate { metadata: fraud_metadata, ..Default::default() };
+            let transaction = state_machine::apply_transition(conn, &transaction, TransactionStatus::Processing, update, "PAYMENT_PROCESSOR")?;
+            log::info!("Held {} {} on wallet {}", principal, request.currency, source_wallet_id);
+
+            // Charge the fee as a linked Fee transaction (refunded by the outbox if the payment is never sent)
+            let fee_tx = match &fee_quote {
+                Some(quote) => fees::post_fee(conn, &transaction, quote, "PAYMENT_PROCESSOR")?,
+                None => None,
+            };
+
+            // Queue the external leg in the same commit
+            let entry = outbox::enqueue_outbound(conn, &transaction, operation, &payload, request.idempotency_key)?;
+
+            // Log Audit Event
+            audit::log_db_audit_event(
+                conn,
+                Some(request.initiating_user_id),
+                &request.initiating_user_id.to_string(), // Actor ID
+                "INITIATE_OUTBOUND_PAYMENT",
+                Some(AuditTargetType::Transaction),
+                Some(&transaction.transaction_id.to_string()),
+                AuditOutcome::Success,
+                Some(json!({"amount": principal.to_string(), "currency": request.currency, "type": request.payment_type.to_string(), "outbox_id": entry.outbox_id,
+                    "fee_amount": fee_quote.as_ref().map(|q| q.fee_amount.to_string()), "fee_transaction_id": fee_tx.as_ref().map(|t| t.transaction_id)})),
+                None // No error message for success
+            )?;
+
+            Ok(transaction)
+        })
     }
 
 
     /// Handles updates for a payment (e.g., from webhooks, settlement confirmations).
+    /// The move is checked against `state_machine`; illegal transitions fail with
+    /// `DomainError::InvalidStateTransition` and the transition's balance effect is applied exactly once.
     pub async fn update_payment_status(
         &mut self,
         transaction_id: Uuid,
         new_status: TransactionStatus,
         external_ref: Option<&str>,
         settlement_time: Option<DateTime<Utc>>,
-        metadata_update: Option<serde_json::Value>, // For adding failure reasons, etc.
+        metadata_update: Option<serde_json::Value>, // Merged into existing metadata (failure reasons, etc.)
     ) -> Result<Transaction, DomainError> {
          log::info!("Updating status for Tx: {} to {:?}", transaction_id, new_status);
 
          self.db_connection.transaction(|conn| {
-             Box::pin(async move {
-                 let mut tx: Transaction = crate::schema::transactions::table
-                    .find(transaction_id)
-                    .for_update() // Lock row
-                    .first(conn)
-                    .map_err(|e| DomainError::NotFound(format!("Transaction {} not found for update: {}", transaction_id, e)))?;
-
-                 // TODO: Add state transition validation (e.g., cannot move from Completed to Pending)
-
-                 let update_data = UpdateTransaction {
-                     status: Some(new_status.to_string().as_str()),
-                     external_ref_id: external_ref,
-                     settlement_at: settlement_time,
-                     metadata: metadata_update, // Replace or merge metadata logic needed
-                 };
-
-                let updated_tx = diesel::update(crate::schema::transactions::table.find(transaction_id))
-                     .set(&update_data)
-                     .get_result(conn)?;
-
-                // --- Handle Financial Impact of Status Change ---
-                 match new_status {
-                     TransactionStatus::Completed | TransactionStatus::Settled => {
-                         // If crediting an internal wallet, update balance
-                         if let Some(wallet_id) = updated_tx.credit_wallet_id {
-                            // TODO: Implement atomic balance update for credit side
-                            // update_wallet_balance(conn, wallet_id, updated_tx.amount)?;
-                             log::info!("Credited wallet {} for completed Tx {}", wallet_id, transaction_id);
-                         }
-                     }
-                     TransactionStatus::Failed | TransactionStatus::Returned | TransactionStatus::Cancelled => {
-                          // If debiting an internal wallet, need to revert the debit
-                          if let Some(wallet_id) = updated_tx.debit_wallet_id {
-                              // Check if it was actually debited (status might have been Pending before failure)
-                              // This requires careful state tracking or checking previous status.
-                              // TODO: Implement atomic balance update for debit reversal.
-                              // update_wallet_balance(conn, wallet_id, updated_tx.amount)?; // Re-credit
-                              log::warn!("Reversal needed for failed/returned Tx {} debit from wallet {}", transaction_id, wallet_id);
-                          }
-                     }
-                     _ => {} // Other statuses might not have immediate financial impact
-                 }
-
-                // --- Log Audit Event ---
-                 audit::log_db_audit_event(
-                     conn,
-                     None, // UserID might not be available for webhook updates
-                     "SYSTEM", // Actor
-                     "UPDATE_PAYMENT_STATUS",
-                     Some("TRANSACTION"),
-                     Some(&transaction_id.to_string()),
-                     AuditOutcome::Success, // Assuming DB update worked
-                     Some(json!({"new_status": new_status.to_string(), "external_ref": external_ref})),
-                     None
-                 )?;
-
-
-                 Ok(updated_tx)
-             }) // End Box::pin
+             let tx: Transaction = crate::schema::transactions::table
+                .find(transaction_id)
+                .for_update() // Lock row, transitions on one transaction are serialized
+                .first(conn)
+                .map_err(|e| DomainError::NotFound(format!("Transaction {} not found for update: {}", transaction_id, e)))?;
+             let previous_status = tx.status.clone();
+
+             let update = TransitionUpdate {
+                 external_ref,
+                 settlement_at: settlement_time,
+                 metadata: metadata_update,
+             };
+             let updated_tx = state_machine::apply_transition(conn, &tx, new_status.clone(), update, "SYSTEM")?;
+
+             // --- Log Audit Event ---
+             audit::log_db_audit_event(
+                 conn,
+                 None, // UserID might not be available for webhook updates
+                 "SYSTEM", // Actor
+                 "UPDATE_PAYMENT_STATUS",
+                 Some(AuditTargetType::Transaction),
+                 Some(&transaction_id.to_string()),
+                 AuditOutcome::Success,
+                 Some(json!({"previous_status": previous_status, "new_status": updated_tx.status, "external_ref": external_ref})),
+                 None
+             )?;
+
+             Ok(updated_tx)
          }) // End transaction
     }
 
-    // Add methods for handling inbound payments, processing notifications etc.
-}
+    /// Handles a payment received on any rail (ACH credit, wire, crypto receipt): dedupes it by rail
+    /// reference, credits the beneficiary wallet or parks the funds in the suspense queue.
+    pub fn process_inbound_payment(&mut self, notice: &InboundNotice) -> Result<InboundOutcome, DomainError> {
+        log::info!("Processing inbound {} payment {} for {} {}",
+            notice.rail.as_str(), notice.external_reference, notice.amount, notice.currency);
+        inbound::process_inbound(self.db_connection, notice)
+    }
+
+    /// Applies a stored provider webhook (see `webhooks::dispatcher`): each status event it carries goes
+    /// through `update_payment_status`.
+    pub async fn handle_webhook_event(
+        &mut self,
+        provider: &dyn WebhookProvider,
+        event: &WebhookEvent,
+    ) -> Result<WebhookDispatch, DomainError> {
+        webhooks::dispatch(self, provider, event).await
+    }
 
-// Helper function to convert Decimal to BigDecimal (required by Diesel for Numeric)
-fn decimal_to_bigdecimal(d: Decimal) -> BigDecimal {
-     use std::str::FromStr;
-     BigDecimal::from_str(&d.to_string()).unwrap_or_default()
-     // TODO: Handle potential conversion errors more gracefully
-}
\ No newline at end of file
+    /// The processor's connection, for collaborators that look up rows between status updates.
+    pub(crate) fn connection(&mut self) -> &mut PgConnection {
+        self.db_connection
+    }
+}
@@ -0,0 +1,949 @@
+// /home/inno/elights_jobes-research/backend/domain/src/payments/payout_batch.rs
+// Bulk payouts: one uploaded CSV/JSON file of ACH credits and outbound wires.
+//
+// `create_batch` validates every row, reports the rejected ones, places one hold for the whole file (fees
+// included) and books the accepted rows in the same DB transaction. Each row goes through the same checks as
+// a single payment (`payments::checks`) and draws its share from the batch hold into its own held
+// transaction; rows the checks park or block keep nothing, and what is left of the batch hold is released.
+// Wires are queued on the outbox one by one; the ACH rows wait for the `PayoutBatchWorker`, which sends
+// them to the ODFI as a single NACHA file (rows released later from review go out on their own through the
+// outbox). Batch counts are refreshed from the items' transactions whenever the batch is read.
+use crate::error::DomainError;
+use crate::approvals::PendingDispatch;
+use crate::beneficiaries::CheckedPayee;
+use crate::fees::{self, minor_units, ChargeBearer, FeeQuote, FeeSchedule};
+use crate::ledger::{self, PlaceHold};
+use crate::models::{
+    AchDetails, AchFileStatus, AuditOutcome, AuditTargetType, HoldType, NewPayoutBatch, NewPayoutBatchItem, NewTransaction,
+    OutboxOperation, PayoutBatch, PayoutBatchItem, PayoutBatchStatus, PayoutItemStatus, Transaction, TransactionStatus,
+    TransactionType, Wallet, WalletStatus, WireDetails,
+};
+use crate::payments::ach::{self, NachaCreditEntry, NachaOriginator};
+use crate::payments::checks::{CheckOutcome, OutboundChecks, OutboundPayment};
+use crate::payments::outbox::{self, OutboxPayload, DEFAULT_MAX_ATTEMPTS, DISPATCH_LEASE_SECONDS};
+use crate::payments::state_machine::{self, TransitionUpdate};
+use crate::payments::validator::{self, ValidationContext};
+use crate::security::audit;
+use crate::utils::decimal_to_bigdecimal;
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Largest file accepted in one upload.
+pub const MAX_BATCH_ROWS: usize = 5000;
+/// Metadata key linking a payout's transaction to its batch.
+pub const PAYOUT_BATCH_KEY: &str = "payout_batch_id";
+
+const ACTOR: &str = "PAYOUT_BATCH";
+
+/// Format of an uploaded payout file.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PayoutFileFormat {
+    Csv,  // Header row with the `RawPayoutRow` field names
+    Json, // Array of `RawPayoutRow` objects
+}
+
+impl PayoutFileFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PayoutFileFormat::Csv => "CSV",
+            PayoutFileFormat::Json => "JSON",
+        }
+    }
+}
+
+/// One row as written in the file. Every field is optional so that a bad row is reported, not the whole file.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct RawPayoutRow {
+    #[serde(default)]
+    pub payment_type: Option<String>, // ACH or WIRE
+    #[serde(default)]
+    pub amount: Option<String>, // In the source wallet's currency
+    #[serde(default)]
+    pub beneficiary_name: Option<String>,
+    #[serde(default)]
+    pub account_number: Option<String>, // ACH account or IBAN
+    #[serde(default)]
+    pub routing_number: Option<String>, // ACH only
+    #[serde(default)]
+    pub swift_bic: Option<String>, // WIRE only
+    #[serde(default)]
+    pub reference: Option<String>, // Shown to the beneficiary / payroll employee ID
+}
+
+/// A parsed row, or why it could not be read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedRow {
+    pub row_number: i32, // 1-based, header excluded
+    pub row: Result<RawPayoutRow, String>,
+}
+
+/// A row that passed validation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayoutInstruction {
+    pub payment_type: TransactionType, // AchCredit or WireOutbound
+    pub amount: Decimal,
+    pub ach_details: Option<AchDetails>,
+    pub wire_details: Option<WireDetails>,
+}
+
+fn check_row_count(count: usize) -> Result<(), DomainError> {
+    if count == 0 {
+        return Err(DomainError::Validation("Payout file contains no rows".to_string()));
+    }
+    if count > MAX_BATCH_ROWS {
+        return Err(DomainError::Validation(format!("Payout file has {} rows, the maximum is {}", count, MAX_BATCH_ROWS)));
+    }
+    Ok(())
+}
+
+/// Reads a CSV payout file. Headers are matched case-insensitively; unknown columns are ignored.
+pub fn parse_csv(content: &[u8]) -> Result<Vec<ParsedRow>, DomainError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(content);
+    let headers = reader.headers()
+        .map_err(|e| DomainError::Validation(format!("Unreadable CSV header: {}", e)))?
+        .iter()
+        .map(|h| h.to_lowercase().replace([' ', '-'], "_"))
+        .collect::<csv::StringRecord>();
+
+    let rows: Vec<ParsedRow> = reader.records()
+        .enumerate()
+        .map(|(index, record)| ParsedRow {
+            row_number: index as i32 + 1,
+            row: record
+                .and_then(|record| record.deserialize::<RawPayoutRow>(Some(&headers)))
+                .map_err(|e| format!("Unreadable row: {}", e)),
+        })
+        .collect();
+    check_row_count(rows.len())?;
+    Ok(rows)
+}
+
+/// Reads a JSON payout file (an array of row objects). Numbers are read as written (`10.50` or `"10.50"`).
+pub fn parse_json(content: &[u8]) -> Result<Vec<ParsedRow>, DomainError> {
+    let values: Vec<serde_json::Value> = serde_json::from_slice(content)
+        .map_err(|e| DomainError::Validation(format!("Payout file must be a JSON array of rows: {}", e)))?;
+    check_row_count(values.len())?;
+    Ok(values.into_iter()
+        .enumerate()
+        .map(|(index, mut value)| {
+            if let Some(fields) = value.as_object_mut() {
+                for field in fields.values_mut() {
+                    if let serde_json::Value::Number(number) = field {
+                        *field = serde_json::Value::String(number.to_string());
+                    }
+                }
+            }
+            ParsedRow {
+                row_number: index as i32 + 1,
+                row: serde_json::from_value::<RawPayoutRow>(value).map_err(|e| format!("Unreadable row: {}", e)),
+            }
+        })
+        .collect())
+}
+
+pub fn parse_file(format: PayoutFileFormat, content: &[u8]) -> Result<Vec<ParsedRow>, DomainError> {
+    match format {
+        PayoutFileFormat::Csv => parse_csv(content),
+        PayoutFileFormat::Json => parse_json(content),
+    }
+}
+
+/// Trimmed value of an optional cell, None when blank.
+fn cell(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// Validates one row for a wallet in `currency`. Returns every problem found, not only the first.
+pub fn validate_row(row: &RawPayoutRow, currency: &str) -> Result<PayoutInstruction, Vec<String>> {
+    let mut errors = Vec::new();
+
+    let payment_type = match cell(&row.payment_type).map(str::to_uppercase).as_deref() {
+        Some("ACH" | "ACH_CREDIT") => Some(TransactionType::AchCredit),
+        Some("WIRE" | "WIRE_OUTBOUND") => Some(TransactionType::WireOutbound),
+        Some(other) => {
+            errors.push(format!("Unsupported payment type '{}' (expected ACH or WIRE)", other));
+            None
+        }
+        None => {
+            errors.push("payment_type is required".to_string());
+            None
+        }
+    };
+
+    let amount = match cell(&row.amount).map(Decimal::from_str) {
+        Some(Ok(amount)) if amount <= Decimal::ZERO => {
+            errors.push("Amount must be positive".to_string());
+            None
+        }
+        Some(Ok(amount)) if amount.normalize().scale() > minor_units(currency) => {
+            errors.push(format!("Amount {} has more than {} decimals for {}", amount, minor_units(currency), currency));
+            None
+        }
+        Some(Ok(amount)) => Some(amount),
+        Some(Err(_)) => {
+            errors.push(format!("Invalid amount '{}'", cell(&row.amount).unwrap_or_default()));
+            None
+        }
+        None => {
+            errors.push("amount is required".to_string());
+            None
+        }
+    };
+
+    let beneficiary_name = cell(&row.beneficiary_name).unwrap_or_default().to_string();
+    if beneficiary_name.is_empty() {
+        errors.push("beneficiary_name is required".to_string());
+    }
+    let account_number = cell(&row.account_number).unwrap_or_default().to_string();
+
+    let (ach_details, wire_details) = match payment_type {
+        Some(TransactionType::AchCredit) => {
+            if !currency.eq_ignore_ascii_case("USD") {
+                errors.push(format!("ACH payouts must be in USD, the source wallet holds {}", currency));
+            }
+            let details = AchDetails {
+                routing_number: cell(&row.routing_number).unwrap_or_default().to_string(),
+                account_number,
+                account_holder_name: Some(beneficiary_name),
+                ..Default::default()
+            };
+            (Some(details), None)
+        }
+        Some(_) => {
+            let details = WireDetails {
+                swift_bic: cell(&row.swift_bic).unwrap_or_default().to_uppercase(),
+                account_number: account_number.replace(' ', ""),
+                beneficiary_name,
+                remittance_info: cell(&row.reference).map(str::to_string),
+                ..Default::default()
+            };
+            (None, Some(details))
+        }
+        None => (None, None),
+    };
+
+    if let Some(payment_type) = &payment_type {
+        let context = ValidationContext { currency };
+        if let Err(e) = validator::validate_payment_details(payment_type, ach_details.as_ref(), wire_details.as_ref(), None, None, &context) {
+            errors.push(match e {
+                DomainError::Validation(msg) => msg,
+                other => other.to_string(),
+            });
+        }
+    }
+
+    match (payment_type, amount) {
+        (Some(payment_type), Some(amount)) if errors.is_empty() => Ok(PayoutInstruction { payment_type, amount, ach_details, wire_details }),
+        _ => Err(errors),
+    }
+}
+
+/// Batch status implied by the item counts.
+pub fn derive_batch_status(accepted: i32, settled: i32, failed: i32, returned: i32) -> PayoutBatchStatus {
+    if accepted == 0 {
+        PayoutBatchStatus::Rejected
+    } else if settled + failed + returned < accepted {
+        PayoutBatchStatus::Processing
+    } else if failed + returned == 0 {
+        PayoutBatchStatus::Completed
+    } else {
+        PayoutBatchStatus::CompletedWithErrors
+    }
+}
+
+/// Input for `create_batch`.
+#[derive(Debug, Clone)]
+pub struct CreatePayoutBatch<'a> {
+    pub user_id: Uuid,
+    pub source_wallet_id: Uuid,
+    pub format: PayoutFileFormat,
+    pub file_name: Option<&'a str>,
+    pub content: &'a [u8],
+    pub charge_bearer: ChargeBearer, // Applies to every row
+}
+
+/// A batch with its per-row report, in file order.
+#[derive(Debug, Clone, Serialize)]
+pub struct PayoutBatchReport {
+    pub batch: PayoutBatch,
+    pub items: Vec<PayoutBatchItem>,
+}
+
+/// A row that passed validation, priced.
+struct AcceptedRow {
+    instruction: PayoutInstruction,
+    fee_quote: Option<FeeQuote>,
+}
+
+impl AcceptedRow {
+    fn principal(&self) -> Decimal {
+        self.fee_quote.as_ref().map(|q| q.net_amount).unwrap_or(self.instruction.amount)
+    }
+
+    fn total_debit(&self) -> Decimal {
+        self.fee_quote.as_ref().map(|q| q.total_debit).unwrap_or(self.instruction.amount)
+    }
+
+    fn fee_amount(&self) -> Decimal {
+        self.fee_quote.as_ref().map(|q| q.fee_amount).unwrap_or(Decimal::ZERO)
+    }
+}
+
+/// Validates an uploaded file and books its valid rows. The funds for all valid rows (fees included) are
+/// held at once, otherwise nothing is booked. Rows that fail validation, or that a payee or limit check
+/// refuses, are reported on the batch and do not block the others. Rows stopped by sanctions, fraud or
+/// approval checks are booked in RequiresAction (or Failed) and leave the batch when released.
+pub fn create_batch(
+    conn: &mut PgConnection,
+    request: CreatePayoutBatch,
+    fee_schedule: Option<&FeeSchedule>,
+    checks: OutboundChecks,
+) -> Result<PayoutBatchReport, DomainError> {
+    let parsed = parse_file(request.format, request.content)?;
+
+    conn.transaction(|conn| {
+        use crate::schema::wallets::dsl as w;
+        let wallet: Wallet = w::wallets
+            .filter(w::wallet_id.eq(request.source_wallet_id))
+            .filter(w::user_id.eq(request.user_id)) // Authorization check
+            .for_update()
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| DomainError::NotFound(format!("Wallet {} not found", request.source_wallet_id)))?;
+        if wallet.status != WalletStatus::Active.to_string() {
+            return Err(DomainError::Validation(format!("Source wallet {} is not active", wallet.wallet_id)));
+        }
+        let currency = wallet.currency_code.as_str();
+
+        // --- 1. Validate and price every row ---
+        let mut outcomes: Vec<(&ParsedRow, Result<AcceptedRow, Vec<String>>)> = Vec::with_capacity(parsed.len());
+        for parsed_row in &parsed {
+            let outcome = match &parsed_row.row {
+                Err(e) => Err(vec![e.clone()]),
+                Ok(raw) => match validate_row(raw, currency) {
+                    Err(errors) => Err(errors),
+                    Ok(instruction) => {
+                        let fee_quote = match fee_schedule {
+                            Some(schedule) => fees::quote_for_user(
+                                conn, schedule, request.user_id, &instruction.payment_type,
+                                instruction.amount, currency, request.charge_bearer,
+                            )?,
+                            None => None,
+                        };
+                        match &fee_quote {
+                            Some(quote) if quote.net_amount <= Decimal::ZERO => Err(vec![format!("Amount {} does not cover the {} fee", instruction.amount, quote.fee_amount)]),
+                            _ => Ok(AcceptedRow { instruction, fee_quote }),
+                        }
+                    }
+                },
+            };
+            outcomes.push((parsed_row, outcome));
+        }
+
+        let accepted: Vec<&AcceptedRow> = outcomes.iter().filter_map(|(_, o)| o.as_ref().ok()).collect();
+        let total_amount: Decimal = accepted.iter().map(|row| row.principal()).sum();
+        let total_fees: Decimal = accepted.iter().map(|row| row.fee_amount()).sum();
+        let total_debit: Decimal = accepted.iter().map(|row| row.total_debit()).sum();
+        let has_ach = accepted.iter().any(|row| row.instruction.payment_type == TransactionType::AchCredit);
+
+        let accepted_count = accepted.len() as i32;
+        let batch: PayoutBatch = diesel::insert_into(crate::schema::payout_batches::table)
+            .values(&NewPayoutBatch {
+                user_id: request.user_id,
+                source_wallet_id: wallet.wallet_id,
+                currency_code: currency,
+                file_format: request.format.as_str(),
+                file_name: request.file_name,
+                status: derive_batch_status(accepted_count, 0, 0, 0).as_str(),
+                total_rows: parsed.len() as i32,
+                accepted_count,
+                rejected_count: parsed.len() as i32 - accepted_count,
+                total_amount,
+                total_fees,
+                ach_status: if has_ach { AchFileStatus::Pending.as_str() } else { AchFileStatus::None.as_str() },
+                ach_next_attempt_at: has_ach.then(Utc::now),
+            })
+            .get_result(conn)?;
+
+        // --- 2. One hold for the whole file, rows draw their share from it ---
+        let funding_hold = if accepted.is_empty() {
+            None
+        } else {
+            let reason = format!("Payout batch {}", batch.batch_id);
+            let hold = ledger::place_hold(conn, PlaceHold {
+                wallet_id: wallet.wallet_id,
+                transaction_id: None, // Rows get their own holds as they are booked
+                hold_type: HoldType::Withdrawal,
+                amount: total_debit,
+                reason: Some(&reason),
+                expires_at: None,
+            })?;
+            Some(hold.hold_id)
+        };
+
+        // --- 3. Check and book the accepted rows, record every row ---
+        let mut items = Vec::with_capacity(outcomes.len());
+        let mut booked: Vec<&AcceptedRow> = Vec::with_capacity(accepted.len());
+        for (parsed_row, outcome) in &outcomes {
+            let raw = parsed_row.row.as_ref().ok();
+            let booking = match outcome {
+                Ok(row) => {
+                    let hold_id = funding_hold.ok_or_else(|| DomainError::Internal("Accepted row without a batch hold".to_string()))?;
+                    // Savepoint: a row refused by its checks leaves no trace and the batch hold untouched
+                    match conn.transaction(|conn| book_row(conn, &batch, &wallet, hold_id, checks, parsed_row.row_number, row, raw)) {
+                        Ok(transaction) => Ok((row, transaction)),
+                        Err(e @ (DomainError::Validation(_) | DomainError::LimitExceeded { .. })) => Err(vec![e.to_string()]),
+                        Err(e) => return Err(e),
+                    }
+                }
+                Err(errors) => Err(errors.clone()),
+            };
+            let (status, errors, payment_details, transaction_id) = match &booking {
+                Ok((row, transaction)) => {
+                    booked.push(row);
+                    let details = match &row.instruction.ach_details {
+                        Some(ach_details) => serde_json::to_value(ach_details),
+                        None => serde_json::to_value(&row.instruction.wire_details),
+                    }
+                    .map_err(|e| DomainError::Internal(format!("Failed to serialize payment details: {}", e)))?;
+                    (PayoutItemStatus::Accepted, json!([]), Some(details), Some(transaction.transaction_id))
+                }
+                Err(errors) => (PayoutItemStatus::Rejected, json!(errors), None, None),
+            };
+            let payment_type = match outcome {
+                Ok(row) => Some(row.instruction.payment_type.to_string()),
+                Err(_) => raw.and_then(|r| cell(&r.payment_type)).map(str::to_string),
+            };
+            let item: PayoutBatchItem = diesel::insert_into(crate::schema::payout_batch_items::table)
+                .values(&NewPayoutBatchItem {
+                    batch_id: batch.batch_id,
+                    row_number: parsed_row.row_number,
+                    payment_type: payment_type.as_deref(),
+                    amount: raw.and_then(|r| cell(&r.amount)),
+                    beneficiary_name: raw.and_then(|r| cell(&r.beneficiary_name)),
+                    reference: raw.and_then(|r| cell(&r.reference)),
+                    status: status.as_str(),
+                    errors,
+                    payment_details,
+                    transaction_id,
+                })
+                .get_result(conn)?;
+            items.push(item);
+        }
+
+        // Shares of rows that were refused or stopped are not reserved any longer
+        if let Some(hold_id) = funding_hold {
+            ledger::release_hold(conn, hold_id, Some("Rows refused or stopped by payment checks"))?;
+        }
+        let batch = if booked.len() < accepted.len() { record_refusals(conn, batch, &booked)? } else { batch };
+        let total_debit: Decimal = booked.iter().map(|row| row.total_debit()).sum();
+
+        audit::log_db_audit_event(
+            conn,
+            Some(request.user_id),
+            &request.user_id.to_string(),
+            "CREATE_PAYOUT_BATCH",
+            Some(AuditTargetType::PayoutBatch),
+            Some(&batch.batch_id.to_string()),
+            if batch.accepted_count > 0 { AuditOutcome::Success } else { AuditOutcome::Failure },
+            Some(json!({"format": batch.file_format, "file_name": batch.file_name, "total_rows": batch.total_rows,
+                "accepted": batch.accepted_count, "rejected": batch.rejected_count, "total_amount": batch.total_amount.to_string(),
+                "total_fees": batch.total_fees.to_string(), "currency": batch.currency_code, "funding_hold_id": funding_hold})),
+            None,
+        )?;
+        log::info!("Payout batch {} from user {}: {} of {} rows accepted, {} {} reserved",
+            batch.batch_id, request.user_id, batch.accepted_count, batch.total_rows, total_debit, batch.currency_code);
+        Ok(PayoutBatchReport { batch, items })
+    })
+}
+
+/// Updates the batch's counts and totals after payment checks refused some of its valid rows.
+fn record_refusals(conn: &mut PgConnection, batch: PayoutBatch, booked: &[&AcceptedRow]) -> Result<PayoutBatch, DomainError> {
+    use crate::schema::payout_batches::dsl as pb;
+    let accepted_count = booked.len() as i32;
+    let has_ach = booked.iter().any(|row| row.instruction.payment_type == TransactionType::AchCredit);
+    let batch = diesel::update(pb::payout_batches.find(batch.batch_id))
+        .set((
+            pb::accepted_count.eq(accepted_count),
+            pb::rejected_count.eq(batch.total_rows - accepted_count),
+            pb::total_amount.eq(decimal_to_bigdecimal(booked.iter().map(|row| row.principal()).sum())),
+            pb::total_fees.eq(decimal_to_bigdecimal(booked.iter().map(|row| row.fee_amount()).sum())),
+            pb::status.eq(derive_batch_status(accepted_count, 0, 0, 0).as_str()),
+            pb::ach_status.eq(if has_ach { AchFileStatus::Pending.as_str() } else { AchFileStatus::None.as_str() }),
+            pb::ach_next_attempt_at.eq(if has_ach { batch.ach_next_attempt_at } else { None }),
+        ))
+        .get_result(conn)?;
+    Ok(batch)
+}
+
+/// Creates the transaction for one accepted row and runs the payment checks on it. A row that passes
+/// moves its share of the batch hold to its own hold, charges its fee and, for wires, is queued on the
+/// outbox. A row the checks stop is returned in RequiresAction or Failed, holding nothing.
+#[allow(clippy::too_many_arguments)]
+fn book_row(
+    conn: &mut PgConnection,
+    batch: &PayoutBatch,
+    wallet: &Wallet,
+    funding_hold_id: Uuid,
+    checks: OutboundChecks,
+    row_number: i32,
+    row: &AcceptedRow,
+    raw: Option<&RawPayoutRow>,
+) -> Result<Transaction, DomainError> {
+    let instruction = &row.instruction;
+    let now = Utc::now();
+    let payment = OutboundPayment {
+        user_id: batch.user_id,
+        source_wallet: wallet,
+        payment_type: &instruction.payment_type,
+        amount: instruction.amount,
+        currency: &batch.currency_code,
+        ach_details: instruction.ach_details.as_ref(),
+        wire_details: instruction.wire_details.as_ref(),
+        crypto_address: None,
+        device: None, // Uploaded file, the upload request is not the payment's device
+    };
+    let payee = checks.check_payee(conn, &payment, now)?;
+
+    let mut metadata = json!({PAYOUT_BATCH_KEY: batch.batch_id, "row_number": row_number});
+    if let Some(quote) = &row.fee_quote {
+        metadata["fee"] = json!({"amount": quote.fee_amount.to_string(), "rule_id": quote.rule_id,
+            "charge_bearer": quote.charge_bearer.as_str(), "requested_amount": quote.amount.to_string()});
+    }
+    for (key, value) in payee.iter().flat_map(CheckedPayee::metadata) {
+        metadata[key] = value;
+    }
+    let description = raw.and_then(|r| cell(&r.reference)).map(str::to_string)
+        .unwrap_or_else(|| format!("Payout batch {} row {}", batch.batch_id, row_number));
+    let transaction_type = instruction.payment_type.to_string();
+    let pending = TransactionStatus::Pending.to_string();
+
+    let transaction: Transaction = diesel::insert_into(crate::schema::transactions::table)
+        .values(&NewTransaction {
+            transaction_id: None, // Let DB generate UUID
+            debit_wallet_id: Some(batch.source_wallet_id),
+            credit_wallet_id: None, // External beneficiary
+            transaction_type: &transaction_type,
+            status: &pending,
+            amount: row.principal(),
+            currency_code: &batch.currency_code,
+            description: Some(&description),
+            external_ref_id: None,
+            metadata: Some(metadata),
+            scheduled_run_id: None,
+        })
+        .get_result(conn)?;
+
+    // Limits, sanctions, fraud rules and approvals, as for a single payment. A stopped row is released
+    // on its own: ACH rows then leave through the outbox, not the batch's NACHA file
+    let (operation, payload) = match &instruction.wire_details {
+        Some(wire_details) => (OutboxOperation::WireOutbound, OutboxPayload { wire_details: Some(wire_details.clone()), use_iso20022: true, ..Default::default() }),
+        None => (OutboxOperation::AchCredit, OutboxPayload { ach_details: instruction.ach_details.clone(), ..Default::default() }),
+    };
+    let key = format!("payout:{}:{}", batch.batch_id, row_number);
+    let dispatch = PendingDispatch {
+        operation,
+        payload: payload.clone(),
+        fee_quote: row.fee_quote.clone(),
+        idempotency_key: Some(key.clone()),
+    };
+    let fraud_metadata = match checks.run(conn, &payment, &transaction, &dispatch, now)? {
+        CheckOutcome::Stopped(transaction) => return Ok(transaction),
+        CheckOutcome::Proceed { metadata } => metadata,
+    };
+
+    // Move the row's share of the batch hold to the row (Processing then finds its hold in place),
+    // and free the fee share for the fee posting
+    ledger::draw_down_hold(conn, funding_hold_id, row.principal(), Some(transaction.transaction_id))?;
+    let update = TransitionUpdate { metadata: fraud_metadata, ..Default::default() };
+    let transaction = state_machine::apply_transition(conn, &transaction, TransactionStatus::Processing, update, ACTOR)?;
+    if let Some(quote) = &row.fee_quote {
+        let fee_share = row.total_debit() - row.principal();
+        if fee_share > Decimal::ZERO {
+            ledger::draw_down_hold(conn, funding_hold_id, fee_share, None)?;
+        }
+        fees::post_fee(conn, &transaction, quote, ACTOR)?;
+    }
+
+    // ACH rows leave together in the batch's NACHA file; wires go out individually
+    if operation == OutboxOperation::WireOutbound {
+        outbox::enqueue_outbound(conn, &transaction, operation, &payload, Some(&key))?;
+    }
+    Ok(transaction)
+}
+
+/// Recomputes the settled/failed/returned counts from the items' transactions and stores them if they moved.
+fn refresh_batch(conn: &mut PgConnection, batch: PayoutBatch) -> Result<PayoutBatch, DomainError> {
+    use crate::schema::payout_batch_items::dsl as i;
+    use crate::schema::payout_batches::dsl as pb;
+    use crate::schema::transactions::dsl as t;
+    if batch.status == PayoutBatchStatus::Rejected.as_str() {
+        return Ok(batch); // Nothing was booked
+    }
+
+    let statuses: Vec<String> = i::payout_batch_items
+        .inner_join(t::transactions)
+        .filter(i::batch_id.eq(batch.batch_id))
+        .select(t::status)
+        .load(conn)?;
+    let (mut settled, mut failed, mut returned) = (0, 0, 0);
+    for status in statuses.iter().filter_map(|s| TransactionStatus::parse(s)) {
+        match status {
+            TransactionStatus::Settled | TransactionStatus::Completed => settled += 1,
+            TransactionStatus::Failed | TransactionStatus::Cancelled => failed += 1,
+            TransactionStatus::Returned => returned += 1,
+            _ => {}
+        }
+    }
+    let status = derive_batch_status(batch.accepted_count, settled, failed, returned);
+    if (settled, failed, returned) == (batch.settled_count, batch.failed_count, batch.returned_count) && status.as_str() == batch.status {
+        return Ok(batch);
+    }
+    let batch = diesel::update(pb::payout_batches.find(batch.batch_id))
+        .set((
+            pb::settled_count.eq(settled),
+            pb::failed_count.eq(failed),
+            pb::returned_count.eq(returned),
+            pb::status.eq(status.as_str()),
+        ))
+        .get_result(conn)?;
+    Ok(batch)
+}
+
+/// The caller's batches, newest first, with up-to-date counts.
+pub fn list_batches(conn: &mut PgConnection, user_id: Uuid) -> Result<Vec<PayoutBatch>, DomainError> {
+    use crate::schema::payout_batches::dsl as pb;
+    let batches: Vec<PayoutBatch> = pb::payout_batches
+        .filter(pb::user_id.eq(user_id))
+        .order(pb::created_at.desc())
+        .load(conn)?;
+    batches.into_iter().map(|batch| refresh_batch(conn, batch)).collect()
+}
+
+/// One of the caller's batches with its per-row report.
+pub fn get_batch(conn: &mut PgConnection, batch_id: Uuid, user_id: Uuid) -> Result<PayoutBatchReport, DomainError> {
+    use crate::schema::payout_batch_items::dsl as i;
+    use crate::schema::payout_batches::dsl as pb;
+    let batch: PayoutBatch = pb::payout_batches
+        .filter(pb::batch_id.eq(batch_id))
+        .filter(pb::user_id.eq(user_id)) // Authorization check
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("Payout batch {} not found", batch_id)))?;
+    let batch = refresh_batch(conn, batch)?;
+    let items = i::payout_batch_items
+        .filter(i::batch_id.eq(batch_id))
+        .order(i::row_number.asc())
+        .load(conn)?;
+    Ok(PayoutBatchReport { batch, items })
+}
+
+/// Sends the ACH rows of each batch to the ODFI as one NACHA file. Run periodically (see core-api
+/// `services::outbox_worker`); safe to run in several processes at once.
+pub struct PayoutBatchWorker<'a> {
+    originator: &'a NachaOriginator,
+    batch_size: i64,
+}
+
+impl<'a> PayoutBatchWorker<'a> {
+    pub fn new(originator: &'a NachaOriginator) -> Self {
+        PayoutBatchWorker { originator, batch_size: 10 }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: i64) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Dispatches the due NACHA files. Returns how many batches were handled.
+    pub async fn run_once(&self, conn: &mut PgConnection) -> Result<usize, DomainError> {
+        let claimed = self.claim_due_batches(conn)?;
+        let count = claimed.len();
+        for (batch, recovered) in claimed {
+            if let Err(e) = self.dispatch_batch(conn, &batch, recovered).await {
+                // Batch keeps its lease and is picked up again once it expires
+                log::error!("Payout batch {} NACHA dispatch error: {}", batch.batch_id, e);
+            }
+        }
+        Ok(count)
+    }
+
+    /// Leases batches whose file is due, or whose previous worker's lease expired (`recovered`).
+    fn claim_due_batches(&self, conn: &mut PgConnection) -> Result<Vec<(PayoutBatch, bool)>, DomainError> {
+        use crate::schema::payout_batches::dsl as pb;
+        conn.transaction(|conn| {
+            let now = Utc::now();
+            let due: Vec<PayoutBatch> = pb::payout_batches
+                .filter(
+                    pb::ach_status.eq(AchFileStatus::Pending.as_str())
+                        .and(pb::ach_next_attempt_at.le(now).or(pb::ach_next_attempt_at.is_null()))
+                        .or(pb::ach_status.eq(AchFileStatus::Dispatching.as_str()).and(pb::ach_locked_until.lt(now))),
+                )
+                .order(pb::created_at.asc())
+                .limit(self.batch_size)
+                .for_update()
+                .skip_locked()
+                .load(conn)?;
+
+            let mut claimed = Vec::with_capacity(due.len());
+            for batch in due {
+                let recovered = batch.ach_status == AchFileStatus::Dispatching.as_str();
+                let batch: PayoutBatch = diesel::update(pb::payout_batches.find(batch.batch_id))
+                    .set((
+                        pb::ach_status.eq(AchFileStatus::Dispatching.as_str()),
+                        pb::ach_attempts.eq(batch.ach_attempts + 1),
+                        pb::ach_locked_until.eq(Some(now + Duration::seconds(DISPATCH_LEASE_SECONDS))),
+                    ))
+                    .get_result(conn)?;
+                claimed.push((batch, recovered));
+            }
+            Ok(claimed)
+        })
+    }
+
+    async fn dispatch_batch(&self, conn: &mut PgConnection, batch: &PayoutBatch, recovered: bool) -> Result<(), DomainError> {
+        // The ODFI may already have the file: never send it twice blindly
+        if recovered {
+            return park_batch(conn, batch, "Worker interrupted during NACHA dispatch");
+        }
+
+        let entries = ach_entries(conn, batch.batch_id)?;
+        if entries.is_empty() {
+            return finish_batch(conn, batch, &[], Ok(String::new()));
+        }
+        let file = {
+            let records: Vec<NachaCreditEntry> = entries.iter()
+                .map(|(item, transaction, details)| NachaCreditEntry {
+                    routing_number: &details.routing_number,
+                    account_number: &details.account_number,
+                    amount: transaction.amount,
+                    individual_id: item.reference.as_deref().unwrap_or(""),
+                    individual_name: item.beneficiary_name.as_deref().unwrap_or(""),
+                    trace_sequence: item.row_number as u32,
+                })
+                .collect();
+            let now = Utc::now();
+            ach::build_credit_file(self.originator, &records, "PAYOUT", now.date_naive(), now, 'A')
+        };
+        let result = match file {
+            Ok(file) => ach::submit_nacha_file(&file, &format!("payout-{}.ach", batch.batch_id)).await,
+            Err(e) => Err(e),
+        };
+        let traces: Vec<(Uuid, String)> = entries.iter()
+            .map(|(item, transaction, _)| (transaction.transaction_id, ach::nacha_trace_number(self.originator, item.row_number as u32)))
+            .collect();
+        finish_batch(conn, batch, &traces, result)
+    }
+}
+
+/// ACH items of a batch whose transaction is still held, with their transaction and account details.
+/// Rows released from review are queued on the outbox instead and are left out of the file.
+fn ach_entries(conn: &mut PgConnection, batch_id: Uuid) -> Result<Vec<(PayoutBatchItem, Transaction, AchDetails)>, DomainError> {
+    use crate::schema::payment_outbox::dsl as po;
+    use crate::schema::payout_batch_items::dsl as i;
+    use crate::schema::transactions::dsl as t;
+    let rows: Vec<(PayoutBatchItem, Transaction)> = i::payout_batch_items
+        .inner_join(t::transactions)
+        .filter(i::batch_id.eq(batch_id))
+        .filter(t::transaction_type.eq(TransactionType::AchCredit.to_string()))
+        .filter(t::status.eq(TransactionStatus::Processing.to_string()))
+        .filter(diesel::dsl::not(diesel::dsl::exists(po::payment_outbox.filter(po::transaction_id.eq(t::transaction_id)))))
+        .order(i::row_number.asc())
+        .load(conn)?;
+    rows.into_iter()
+        .map(|(item, transaction)| {
+            let details: AchDetails = item.payment_details.clone()
+                .and_then(|value| serde_json::from_value(value).ok())
+                .ok_or_else(|| DomainError::Internal(format!("Payout item {} has no ACH details", item.item_id)))?;
+            Ok((item, transaction, details))
+        })
+        .collect()
+}
+
+/// Records the outcome of a NACHA dispatch on the batch and its ACH transactions.
+fn finish_batch(
+    conn: &mut PgConnection,
+    batch: &PayoutBatch,
+    traces: &[(Uuid, String)],
+    result: Result<String, DomainError>,
+) -> Result<(), DomainError> {
+    use crate::schema::payout_batches::dsl as pb;
+    use crate::schema::transactions::dsl as t;
+    match result {
+        Ok(file_ref) => conn.transaction(|conn| {
+            for (transaction_id, trace_number) in traces {
+                let transaction: Transaction = t::transactions.find(*transaction_id).for_update().first(conn)?;
+                let update = TransitionUpdate { external_ref: Some(trace_number.as_str()), ..Default::default() };
+                state_machine::apply_transition(conn, &transaction, TransactionStatus::Submitted, update, ACTOR)?;
+            }
+            diesel::update(pb::payout_batches.find(batch.batch_id))
+                .set((
+                    pb::ach_status.eq(AchFileStatus::Submitted.as_str()),
+                    pb::ach_file_ref.eq(Some(file_ref.as_str())),
+                    pb::ach_locked_until.eq(None::<DateTime<Utc>>),
+                    pb::last_error.eq(None::<String>),
+                ))
+                .execute(conn)?;
+            audit_dispatch(conn, batch, "SUBMIT_PAYOUT_NACHA_FILE", AuditOutcome::Success, json!({"file_ref": file_ref, "entries": traces.len()}), None)?;
+            log::info!("Payout batch {}: NACHA file {} submitted with {} entries", batch.batch_id, file_ref, traces.len());
+            Ok(())
+        }),
+        // The ODFI refused the file, nothing was sent: release every held row and refund its fee
+        Err(DomainError::Validation(reason) | DomainError::NotSupported(reason)) => conn.transaction(|conn| {
+            for (transaction_id, _) in traces {
+                let transaction: Transaction = t::transactions.find(*transaction_id).for_update().first(conn)?;
+                let update = TransitionUpdate { metadata: Some(json!({"compensation": {"reason": reason, "reversed_at": Utc::now()}})), ..Default::default() };
+                state_machine::apply_transition(conn, &transaction, TransactionStatus::Failed, update, ACTOR)?;
+                fees::reverse_linked_fees(conn, transaction.transaction_id, &reason, ACTOR)?;
+            }
+            diesel::update(pb::payout_batches.find(batch.batch_id))
+                .set((
+                    pb::ach_status.eq(AchFileStatus::Failed.as_str()),
+                    pb::ach_locked_until.eq(None::<DateTime<Utc>>),
+                    pb::last_error.eq(Some(reason.as_str())),
+                ))
+                .execute(conn)?;
+            audit_dispatch(conn, batch, "REJECT_PAYOUT_NACHA_FILE", AuditOutcome::Failure, json!({"entries": traces.len()}), Some(&reason))?;
+            log::warn!("Payout batch {}: NACHA file rejected, {} entries released: {}", batch.batch_id, traces.len(), reason);
+            Ok(())
+        }),
+        Err(e) if batch.ach_attempts >= DEFAULT_MAX_ATTEMPTS => park_batch(conn, batch, &e.to_string()),
+        Err(e) => {
+            let next_attempt_at = Utc::now() + outbox::retry_delay(batch.ach_attempts);
+            diesel::update(pb::payout_batches.find(batch.batch_id))
+                .set((
+                    pb::ach_status.eq(AchFileStatus::Pending.as_str()),
+                    pb::ach_next_attempt_at.eq(Some(next_attempt_at)),
+                    pb::ach_locked_until.eq(None::<DateTime<Utc>>),
+                    pb::last_error.eq(Some(e.to_string())),
+                ))
+                .execute(conn)?;
+            log::warn!("Payout batch {}: NACHA dispatch failed (attempt {}), retrying at {}: {}", batch.batch_id, batch.ach_attempts, next_attempt_at, e);
+            Ok(())
+        }
+    }
+}
+
+/// Outcome unknown: the held rows stay held until an operator confirms what the ODFI received.
+fn park_batch(conn: &mut PgConnection, batch: &PayoutBatch, reason: &str) -> Result<(), DomainError> {
+    use crate::schema::payout_batches::dsl as pb;
+    conn.transaction(|conn| {
+        diesel::update(pb::payout_batches.find(batch.batch_id))
+            .set((
+                pb::ach_status.eq(AchFileStatus::RequiresReview.as_str()),
+                pb::ach_locked_until.eq(None::<DateTime<Utc>>),
+                pb::last_error.eq(Some(reason)),
+            ))
+            .execute(conn)?;
+        audit_dispatch(conn, batch, "PAYOUT_NACHA_FILE_REQUIRES_REVIEW", AuditOutcome::Failure, json!({"attempts": batch.ach_attempts}), Some(reason))?;
+        log::error!("Payout batch {} needs manual review, NACHA outcome unknown: {}", batch.batch_id, reason);
+        Ok(())
+    })
+}
+
+fn audit_dispatch(
+    conn: &mut PgConnection,
+    batch: &PayoutBatch,
+    action: &str,
+    outcome: AuditOutcome,
+    details: serde_json::Value,
+    error_message: Option<&str>,
+) -> Result<(), DomainError> {
+    audit::log_db_audit_event(
+        conn,
+        Some(batch.user_id),
+        ACTOR,
+        action,
+        Some(AuditTargetType::PayoutBatch),
+        Some(&batch.batch_id.to_string()),
+        outcome,
+        Some(details),
+        error_message,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn ach_row(amount: &str) -> RawPayoutRow {
+        RawPayoutRow {
+            payment_type: Some("ACH".to_string()),
+            amount: Some(amount.to_string()),
+            beneficiary_name: Some("Jane Doe".to_string()),
+            account_number: Some("000123456789".to_string()),
+            routing_number: Some("021000021".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_csv_rows_are_numbered_and_bad_rows_reported() {
+        let csv = b"Payment Type,Amount,Beneficiary Name,Account Number,Routing Number,SWIFT BIC,Reference\n\
+            ACH,125.00,Jane Doe,000123456789,021000021,,EMP-1\n\
+            WIRE,900,Acme GmbH,DE89370400440532013000,,DEUTDEFF,INV-7\n";
+        let rows = parse_csv(csv).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].row_number, 1);
+        let first = rows[0].row.as_ref().unwrap();
+        assert_eq!(first.amount.as_deref(), Some("125.00"));
+        assert_eq!(first.reference.as_deref(), Some("EMP-1"));
+        assert_eq!(rows[1].row.as_ref().unwrap().swift_bic.as_deref(), Some("DEUTDEFF"));
+    }
+
+    #[test]
+    fn test_json_accepts_numeric_amounts_and_rejects_non_arrays() {
+        let rows = parse_json(br#"[{"payment_type": "ACH", "amount": 10.5, "routing_number": "021000021"}, 7]"#).unwrap();
+        assert_eq!(rows[0].row.as_ref().unwrap().amount.as_deref(), Some("10.5"));
+        assert!(rows[1].row.is_err());
+        assert!(parse_json(br#"{"payment_type": "ACH"}"#).is_err());
+        assert!(parse_json(b"[]").is_err());
+    }
+
+    #[test]
+    fn test_valid_rows_become_instructions() {
+        let instruction = validate_row(&ach_row("125.00"), "USD").unwrap();
+        assert_eq!(instruction.payment_type, TransactionType::AchCredit);
+        assert_eq!(instruction.amount, dec!(125.00));
+        assert_eq!(instruction.ach_details.unwrap().account_holder_name.as_deref(), Some("Jane Doe"));
+
+        let wire = RawPayoutRow {
+            payment_type: Some("wire".to_string()),
+            amount: Some("900".to_string()),
+            beneficiary_name: Some("Acme GmbH".to_string()),
+            account_number: Some("DE89 3704 0044 0532 0130 00".to_string()),
+            swift_bic: Some("deutdeff".to_string()),
+            ..Default::default()
+        };
+        let details = validate_row(&wire, "EUR").unwrap().wire_details.unwrap();
+        assert_eq!(details.swift_bic, "DEUTDEFF");
+        assert_eq!(details.account_number, "DE89370400440532013000");
+    }
+
+    #[test]
+    fn test_row_errors_are_collected() {
+        let row = RawPayoutRow { routing_number: Some("123456789".to_string()), ..ach_row("12.345") };
+        let errors = validate_row(&row, "USD").unwrap_err();
+        assert_eq!(errors.len(), 2, "{:?}", errors); // Amount precision + routing checksum
+        assert!(validate_row(&ach_row("10"), "EUR").is_err()); // ACH is USD only
+        assert!(validate_row(&ach_row("-1"), "USD").is_err());
+        assert_eq!(validate_row(&RawPayoutRow::default(), "USD").unwrap_err().len(), 3);
+    }
+
+    #[test]
+    fn test_batch_status_from_counts() {
+        assert_eq!(derive_batch_status(0, 0, 0, 0), PayoutBatchStatus::Rejected);
+        assert_eq!(derive_batch_status(3, 2, 0, 0), PayoutBatchStatus::Processing);
+        assert_eq!(derive_batch_status(3, 3, 0, 0), PayoutBatchStatus::Completed);
+        assert_eq!(derive_batch_status(3, 1, 1, 1), PayoutBatchStatus::CompletedWithErrors);
+    }
+}
@@ -0,0 +1,448 @@
+// /home/inno/elights_jobes-research/backend/domain/src/payments/recurrence.rs
+// Calendar rules for scheduled payments (pure date arithmetic, no DB access).
+//
+// A schedule has nominal occurrence dates generated by its `RecurrenceRule` from the start date; each one
+// is paid on its run date, the nominal date moved to a business day by the `BusinessDayConvention`.
+// `plan_due` decides, for a given day, which occurrences to pay and which were missed (scheduler downtime).
+use crate::error::DomainError;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Upper bound on occurrences examined in one planning pass (about three years of daily runs).
+pub const MAX_CATCH_UP_OCCURRENCES: usize = 1_100;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Frequency {
+    Once, // Single future-dated payment on the start date
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+fn default_interval() -> u32 {
+    1
+}
+
+/// When a schedule recurs, e.g. every 2 weeks on Monday and Thursday, or monthly on the last day (31).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    #[serde(default = "default_interval")]
+    pub interval: u32, // Every N days/weeks/months/years
+    #[serde(default)]
+    pub weekdays: Vec<Weekday>, // Weekly only; empty = the start date's weekday
+    #[serde(default)]
+    pub day_of_month: Option<u32>, // Monthly only (1-31, clamped to the month's last day); None = the start date's day
+}
+
+impl RecurrenceRule {
+    pub fn validate(&self) -> Result<(), DomainError> {
+        if self.interval == 0 || self.interval > 999 {
+            return Err(DomainError::Validation("Recurrence interval must be between 1 and 999".to_string()));
+        }
+        if !self.weekdays.is_empty() && self.frequency != Frequency::Weekly {
+            return Err(DomainError::Validation("Weekdays only apply to weekly schedules".to_string()));
+        }
+        match self.day_of_month {
+            Some(_) if self.frequency != Frequency::Monthly => {
+                Err(DomainError::Validation("Day of month only applies to monthly schedules".to_string()))
+            }
+            Some(day) if !(1..=31).contains(&day) => {
+                Err(DomainError::Validation("Day of month must be between 1 and 31".to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// First nominal date strictly after `after` (and not before `start`).
+    pub fn next_after(&self, start: NaiveDate, after: NaiveDate) -> Option<NaiveDate> {
+        let interval = self.interval.max(1) as i64;
+        match self.frequency {
+            Frequency::Once => (start > after).then_some(start),
+            Frequency::Daily => Some(next_in_step(start, after, interval)),
+            Frequency::Weekly if self.weekdays.is_empty() => Some(next_in_step(start, after, 7 * interval)),
+            Frequency::Weekly => {
+                let start_week = week_start(start);
+                let mut candidate = if after < start { start } else { after + Duration::days(1) };
+                // Within 'interval' weeks plus one there is always a matching day
+                for _ in 0..(7 * (interval + 1)) {
+                    let weeks_from_start = (week_start(candidate) - start_week).num_days() / 7;
+                    if weeks_from_start % interval == 0 && self.weekdays.contains(&candidate.weekday()) {
+                        return Some(candidate);
+                    }
+                    candidate = candidate + Duration::days(1);
+                }
+                None
+            }
+            Frequency::Monthly => {
+                let day = self.day_of_month.unwrap_or(start.day());
+                next_in_months(start, after, interval, day)
+            }
+            Frequency::Yearly => next_in_months(start, after, 12 * interval, start.day()),
+        }
+    }
+
+    /// First nominal date on or after `start`.
+    pub fn first_occurrence(&self, start: NaiveDate) -> Option<NaiveDate> {
+        self.next_after(start, start - Duration::days(1))
+    }
+}
+
+fn next_in_step(start: NaiveDate, after: NaiveDate, step_days: i64) -> NaiveDate {
+    if after < start {
+        return start;
+    }
+    let steps = (after - start).num_days() / step_days + 1;
+    start + Duration::days(steps * step_days)
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .map(|first_of_next| (first_of_next - Duration::days(1)).day())
+        .unwrap_or(28)
+}
+
+/// `day` of the month `offset` months after `start`'s month, clamped to that month's length.
+fn month_date(start: NaiveDate, offset: i64, day: u32) -> Option<NaiveDate> {
+    let total = start.year() as i64 * 12 + start.month0() as i64 + offset;
+    let year = (total / 12) as i32;
+    let month = (total % 12) as u32 + 1;
+    NaiveDate::from_ymd_opt(year, month, day.min(days_in_month(year, month)))
+}
+
+fn next_in_months(start: NaiveDate, after: NaiveDate, step_months: i64, day: u32) -> Option<NaiveDate> {
+    let months_elapsed = if after < start {
+        0
+    } else {
+        (after.year() as i64 - start.year() as i64) * 12 + after.month() as i64 - start.month() as i64
+    };
+    let mut k = (months_elapsed / step_months - 1).max(0);
+    for _ in 0..4 {
+        let candidate = month_date(start, k * step_months, day)?;
+        if candidate >= start && candidate > after {
+            return Some(candidate);
+        }
+        k += 1;
+    }
+    None
+}
+
+/// How a nominal date falling on a weekend or holiday is moved.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BusinessDayConvention {
+    None, // Pay on the nominal date regardless
+    #[default]
+    Following, // Next business day
+    ModifiedFollowing, // Next business day unless that is in the next month, then the previous one
+    Preceding, // Previous business day
+}
+
+impl BusinessDayConvention {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BusinessDayConvention::None => "NONE",
+            BusinessDayConvention::Following => "FOLLOWING",
+            BusinessDayConvention::ModifiedFollowing => "MODIFIED_FOLLOWING",
+            BusinessDayConvention::Preceding => "PRECEDING",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<BusinessDayConvention> {
+        match value {
+            "NONE" => Some(BusinessDayConvention::None),
+            "FOLLOWING" => Some(BusinessDayConvention::Following),
+            "MODIFIED_FOLLOWING" => Some(BusinessDayConvention::ModifiedFollowing),
+            "PRECEDING" => Some(BusinessDayConvention::Preceding),
+            _ => None,
+        }
+    }
+}
+
+/// What to do with occurrences whose run date passed while the scheduler was not running.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MissedRunPolicy {
+    #[default]
+    RunOnce, // Pay the latest missed occurrence once, skip the older ones
+    RunAll, // Pay every missed occurrence
+    Skip, // Only pay occurrences due today
+}
+
+impl MissedRunPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MissedRunPolicy::RunOnce => "RUN_ONCE",
+            MissedRunPolicy::RunAll => "RUN_ALL",
+            MissedRunPolicy::Skip => "SKIP",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<MissedRunPolicy> {
+        match value {
+            "RUN_ONCE" => Some(MissedRunPolicy::RunOnce),
+            "RUN_ALL" => Some(MissedRunPolicy::RunAll),
+            "SKIP" => Some(MissedRunPolicy::Skip),
+            _ => None,
+        }
+    }
+}
+
+/// Non-business days: weekends plus configured holidays.
+#[derive(Debug, Clone, Default)]
+pub struct BusinessCalendar {
+    holidays: BTreeSet<NaiveDate>,
+}
+
+impl BusinessCalendar {
+    pub fn new(holidays: impl IntoIterator<Item = NaiveDate>) -> Self {
+        BusinessCalendar { holidays: holidays.into_iter().collect() }
+    }
+
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.holidays.contains(&date)
+    }
+
+    fn roll(&self, mut date: NaiveDate, step: i64) -> NaiveDate {
+        while !self.is_business_day(date) {
+            date = date + Duration::days(step);
+        }
+        date
+    }
+
+    /// Run date for a nominal date.
+    pub fn adjust(&self, date: NaiveDate, convention: BusinessDayConvention) -> NaiveDate {
+        match convention {
+            BusinessDayConvention::None => date,
+            BusinessDayConvention::Following => self.roll(date, 1),
+            BusinessDayConvention::Preceding => self.roll(date, -1),
+            BusinessDayConvention::ModifiedFollowing => {
+                let following = self.roll(date, 1);
+                if following.month() == date.month() { following } else { self.roll(date, -1) }
+            }
+        }
+    }
+}
+
+/// End conditions of a schedule.
+#[derive(Debug, Clone, Copy)]
+pub struct ScheduleLimits {
+    pub end_date: Option<NaiveDate>, // Last nominal date, inclusive
+    pub max_occurrences: Option<i32>,
+}
+
+impl ScheduleLimits {
+    pub fn allows(&self, nominal: NaiveDate, occurrences_so_far: i32) -> bool {
+        self.end_date.map_or(true, |end| nominal <= end)
+            && self.max_occurrences.map_or(true, |max| occurrences_so_far < max)
+    }
+}
+
+/// Everything about a schedule that decides its dates.
+#[derive(Debug, Clone, Copy)]
+pub struct ScheduleTerms<'a> {
+    pub rule: &'a RecurrenceRule,
+    pub start: NaiveDate,
+    pub limits: ScheduleLimits,
+    pub convention: BusinessDayConvention,
+}
+
+impl ScheduleTerms<'_> {
+    /// First occurrence of the schedule, None when the end conditions exclude it.
+    pub fn first(&self, calendar: &BusinessCalendar) -> Option<Occurrence> {
+        self.rule.first_occurrence(self.start)
+            .filter(|date| self.limits.allows(*date, 0))
+            .map(|date| Occurrence { nominal: date, run_date: calendar.adjust(date, self.convention) })
+    }
+}
+
+/// One occurrence with its run date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Occurrence {
+    pub nominal: NaiveDate,
+    pub run_date: NaiveDate,
+}
+
+/// What the scheduler should do for a schedule today.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DuePlan {
+    pub pay: Vec<Occurrence>,
+    pub skip: Vec<Occurrence>, // Missed and not paid per the policy
+    pub next: Option<Occurrence>, // First occurrence not yet due, None when the schedule is finished
+}
+
+/// Splits the occurrences from `next_occurrence` up to `today` into paid and missed ones.
+/// An occurrence is on time when its run date is `today`; earlier run dates were missed.
+pub fn plan_due(
+    terms: &ScheduleTerms,
+    calendar: &BusinessCalendar,
+    next_occurrence: Option<NaiveDate>,
+    occurrences_so_far: i32,
+    policy: MissedRunPolicy,
+    today: NaiveDate,
+) -> DuePlan {
+    let mut due = Vec::new();
+    let mut count = occurrences_so_far;
+    let mut nominal = next_occurrence;
+    let mut next = None;
+
+    while let Some(date) = nominal {
+        if !terms.limits.allows(date, count) {
+            break;
+        }
+        let occurrence = Occurrence { nominal: date, run_date: calendar.adjust(date, terms.convention) };
+        if occurrence.run_date > today || due.len() >= MAX_CATCH_UP_OCCURRENCES {
+            next = Some(occurrence);
+            break;
+        }
+        due.push(occurrence);
+        count += 1;
+        nominal = terms.rule.next_after(terms.start, date);
+    }
+
+    let mut plan = DuePlan { next, ..Default::default() };
+    match policy {
+        MissedRunPolicy::RunAll => plan.pay = due,
+        MissedRunPolicy::RunOnce => {
+            if let Some(latest) = due.pop() {
+                plan.pay.push(latest);
+            }
+            plan.skip = due;
+        }
+        MissedRunPolicy::Skip => {
+            let (on_time, missed): (Vec<Occurrence>, Vec<Occurrence>) = due.into_iter().partition(|o| o.run_date == today);
+            plan.pay = on_time;
+            plan.skip = missed;
+        }
+    }
+    plan
+}
+
+/// First occurrence from `next_occurrence` on whose run date is not before `today`, used when a paused
+/// schedule is resumed. Occurrences passed over while paused are dropped without counting.
+pub fn next_not_before(
+    terms: &ScheduleTerms,
+    calendar: &BusinessCalendar,
+    next_occurrence: Option<NaiveDate>,
+    occurrences_so_far: i32,
+    today: NaiveDate,
+) -> Option<Occurrence> {
+    let mut nominal = next_occurrence;
+    while let Some(date) = nominal {
+        if !terms.limits.allows(date, occurrences_so_far) {
+            return None;
+        }
+        let run_date = calendar.adjust(date, terms.convention);
+        if run_date >= today {
+            return Some(Occurrence { nominal: date, run_date });
+        }
+        nominal = terms.rule.next_after(terms.start, date);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn rule(frequency: Frequency, interval: u32) -> RecurrenceRule {
+        RecurrenceRule { frequency, interval, weekdays: vec![], day_of_month: None }
+    }
+
+    #[test]
+    fn test_monthly_clamps_to_month_end() {
+        let monthly = RecurrenceRule { day_of_month: Some(31), ..rule(Frequency::Monthly, 1) };
+        let start = date(2025, 1, 31);
+        assert_eq!(monthly.next_after(start, start), Some(date(2025, 2, 28)));
+        assert_eq!(monthly.next_after(start, date(2025, 2, 28)), Some(date(2025, 3, 31)));
+        assert_eq!(monthly.next_after(start, date(2025, 4, 1)), Some(date(2025, 4, 30)));
+
+        let quarterly = rule(Frequency::Monthly, 3);
+        assert_eq!(quarterly.next_after(date(2025, 1, 15), date(2025, 1, 15)), Some(date(2025, 4, 15)));
+    }
+
+    #[test]
+    fn test_weekly_on_weekdays_every_other_week() {
+        let biweekly = RecurrenceRule { weekdays: vec![Weekday::Mon, Weekday::Thu], ..rule(Frequency::Weekly, 2) };
+        let start = date(2025, 4, 7); // Monday
+        assert_eq!(biweekly.first_occurrence(start), Some(date(2025, 4, 7)));
+        assert_eq!(biweekly.next_after(start, date(2025, 4, 7)), Some(date(2025, 4, 10)));
+        assert_eq!(biweekly.next_after(start, date(2025, 4, 10)), Some(date(2025, 4, 21))); // Skips the off week
+    }
+
+    #[test]
+    fn test_once_and_daily() {
+        let once = rule(Frequency::Once, 1);
+        assert_eq!(once.first_occurrence(date(2025, 5, 1)), Some(date(2025, 5, 1)));
+        assert_eq!(once.next_after(date(2025, 5, 1), date(2025, 5, 1)), None);
+        let every_third_day = rule(Frequency::Daily, 3);
+        assert_eq!(every_third_day.next_after(date(2025, 5, 1), date(2025, 5, 5)), Some(date(2025, 5, 7)));
+    }
+
+    #[test]
+    fn test_business_day_conventions() {
+        let calendar = BusinessCalendar::new([date(2025, 5, 26)]); // Memorial Day (Monday)
+        let saturday = date(2025, 5, 24);
+        assert_eq!(calendar.adjust(saturday, BusinessDayConvention::Following), date(2025, 5, 27));
+        assert_eq!(calendar.adjust(saturday, BusinessDayConvention::Preceding), date(2025, 5, 23));
+        assert_eq!(calendar.adjust(saturday, BusinessDayConvention::None), saturday);
+        // Saturday 31 May: following business day is in June, so roll back instead
+        assert_eq!(calendar.adjust(date(2025, 5, 31), BusinessDayConvention::ModifiedFollowing), date(2025, 5, 30));
+    }
+
+    #[test]
+    fn test_missed_runs_after_downtime() {
+        let weekly = rule(Frequency::Weekly, 1);
+        let start = date(2025, 4, 7);
+        let calendar = BusinessCalendar::default();
+        let limits = ScheduleLimits { end_date: None, max_occurrences: None };
+        let terms = ScheduleTerms { rule: &weekly, start, limits, convention: BusinessDayConvention::Following };
+        let plan = |policy, today| plan_due(&terms, &calendar, Some(start), 0, policy, today);
+
+        // Down for two weeks: 7, 14 and 21 April are due on the 21st
+        let run_once = plan(MissedRunPolicy::RunOnce, date(2025, 4, 21));
+        assert_eq!(run_once.pay.iter().map(|o| o.nominal).collect::<Vec<_>>(), vec![date(2025, 4, 21)]);
+        assert_eq!(run_once.skip.len(), 2);
+        assert_eq!(run_once.next.map(|o| o.nominal), Some(date(2025, 4, 28)));
+
+        assert_eq!(plan(MissedRunPolicy::RunAll, date(2025, 4, 21)).pay.len(), 3);
+
+        let skip = plan(MissedRunPolicy::Skip, date(2025, 4, 22));
+        assert!(skip.pay.is_empty());
+        assert_eq!(skip.skip.len(), 3);
+    }
+
+    #[test]
+    fn test_end_conditions() {
+        let daily = rule(Frequency::Daily, 1);
+        let start = date(2025, 4, 1);
+        let calendar = BusinessCalendar::default();
+        let terms = |limits| ScheduleTerms { rule: &daily, start, limits, convention: BusinessDayConvention::None };
+
+        let limited = terms(ScheduleLimits { end_date: None, max_occurrences: Some(2) });
+        let plan = plan_due(&limited, &calendar, Some(start), 0, MissedRunPolicy::RunAll, date(2025, 4, 10));
+        assert_eq!(plan.pay.len(), 2);
+        assert_eq!(plan.next, None); // Finished
+
+        let until = terms(ScheduleLimits { end_date: Some(date(2025, 4, 3)), max_occurrences: None });
+        let plan = plan_due(&until, &calendar, Some(start), 0, MissedRunPolicy::RunAll, date(2025, 4, 2));
+        assert_eq!(plan.pay.len(), 2);
+        assert_eq!(plan.next.map(|o| o.nominal), Some(date(2025, 4, 3)));
+
+        // Resumed on the 10th: occurrences before it are dropped, the end date still applies
+        assert_eq!(next_not_before(&until, &calendar, Some(start), 0, date(2025, 4, 10)), None);
+        assert_eq!(next_not_before(&until, &calendar, Some(start), 0, date(2025, 4, 3)).map(|o| o.nominal), Some(date(2025, 4, 3)));
+    }
+}
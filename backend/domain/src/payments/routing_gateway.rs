@@ -0,0 +1,542 @@
+// /home/inno/elights_jobes-research/backend/domain/src/payments/routing_gateway.rs
+// Smart routing across several card gateways, exposed as a single `PaymentGateway`.
+use crate::config::{self, JsonConfig};
+use crate::error::DomainError;
+use crate::payments::gateway::{
+    GatewayError, MockPaymentGateway, PaymentGateway, PaymentGatewayRequest, PaymentGatewayResponse, PaymentIntent,
+    PaymentMethodDetails,
+};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// Separator between provider name and the provider's own transaction ID in returned references.
+/// Captures, refunds and status checks use the prefix to reach the gateway that owns the payment.
+const REFERENCE_SEPARATOR: &str = "::";
+
+/// Conditions under which a provider is eligible for a request. `None` fields match anything.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RoutingRule {
+    pub currencies: Option<Vec<String>>, // ISO 4217 codes
+    pub bin_countries: Option<Vec<String>>, // ISO 3166 alpha-2 issuer countries
+    pub min_amount: Option<Decimal>,
+    pub max_amount: Option<Decimal>,
+}
+
+impl RoutingRule {
+    /// Returns true if the request satisfies every condition set on this rule.
+    pub fn matches(&self, request: &PaymentGatewayRequest) -> bool {
+        if let Some(currencies) = &self.currencies {
+            if !currencies.iter().any(|c| c.eq_ignore_ascii_case(&request.currency)) {
+                return false;
+            }
+        }
+        if let Some(countries) = &self.bin_countries {
+            match &request.card_bin_country {
+                Some(country) if countries.iter().any(|c| c.eq_ignore_ascii_case(country)) => {}
+                _ => return false, // Unknown BIN country never matches a country-restricted rule
+            }
+        }
+        if self.min_amount.map_or(false, |min| request.amount < min) {
+            return false;
+        }
+        if self.max_amount.map_or(false, |max| request.amount > max) {
+            return false;
+        }
+        true
+    }
+}
+
+/// Processing cost of a provider: fixed fee plus a percentage of the amount.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct GatewayCost {
+    pub fixed_fee: Decimal,
+    pub percentage_fee: Decimal, // e.g. 0.029 for 2.9%
+}
+
+impl GatewayCost {
+    pub fn cost_for(&self, amount: Decimal) -> Decimal {
+        self.fixed_fee + amount * self.percentage_fee
+    }
+}
+
+/// One provider behind the router.
+pub struct RoutedGateway {
+    pub name: String, // Unique, used as the reference prefix
+    pub gateway: Arc<dyn PaymentGateway>,
+    pub rules: Vec<RoutingRule>, // Eligible if ANY rule matches; empty means always eligible
+    pub cost: GatewayCost,
+    pub priority: u32, // Lower wins when scores tie
+}
+
+/// Tuning for provider selection.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RoutingConfig {
+    pub success_window_size: usize, // Number of recent attempts tracked per provider
+    pub success_rate_weight: Decimal, // Weight of the rolling success rate in the score
+    pub cost_weight: Decimal, // Weight of the (relative) cost in the score
+    pub max_failover_attempts: usize, // Secondary providers tried after the first
+}
+
+impl Default for RoutingConfig {
+    fn default() -> Self {
+        RoutingConfig {
+            success_window_size: 100,
+            success_rate_weight: Decimal::ONE,
+            cost_weight: Decimal::new(5, 1), // 0.5
+            max_failover_attempts: 1,
+        }
+    }
+}
+
+/// Rolling record of recent attempt outcomes for one provider.
+#[derive(Debug, Default)]
+struct SuccessWindow {
+    outcomes: VecDeque<bool>,
+}
+
+impl SuccessWindow {
+    fn record(&mut self, success: bool, window_size: usize) {
+        self.outcomes.push_back(success);
+        while self.outcomes.len() > window_size {
+            self.outcomes.pop_front();
+        }
+    }
+
+    /// Laplace-smoothed success rate, so a new provider starts at 0.5 rather than 0 or 1.
+    fn rate(&self) -> Decimal {
+        let successes = self.outcomes.iter().filter(|s| **s).count();
+        Decimal::from(successes as u64 + 1) / Decimal::from(self.outcomes.len() as u64 + 2)
+    }
+}
+
+/// A `PaymentGateway` that picks a provider per request and fails over on safe-to-retry errors.
+pub struct RoutingPaymentGateway {
+    providers: Vec<RoutedGateway>,
+    config: RoutingConfig,
+    stats: Mutex<HashMap<String, SuccessWindow>>,
+}
+
+impl RoutingPaymentGateway {
+    /// Creates an empty router; add providers with `with_provider`.
+    pub fn new(config: RoutingConfig) -> Self {
+        RoutingPaymentGateway { providers: Vec::new(), config, stats: Mutex::new(HashMap::new()) }
+    }
+
+    /// Registers a provider. Names must be unique and must not contain the reference separator.
+    pub fn with_provider(mut self, provider: RoutedGateway) -> Result<Self, GatewayError> {
+        if provider.name.is_empty() || provider.name.contains(REFERENCE_SEPARATOR) {
+            return Err(GatewayError::ConfigurationError(format!("Invalid gateway name '{}'", provider.name)));
+        }
+        if self.providers.iter().any(|p| p.name == provider.name) {
+            return Err(GatewayError::ConfigurationError(format!("Duplicate gateway name '{}'", provider.name)));
+        }
+        self.providers.push(provider);
+        Ok(self)
+    }
+
+    /// Current rolling success rate of a provider (smoothed).
+    pub fn success_rate(&self, provider_name: &str) -> Decimal {
+        let stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        stats.get(provider_name).map(|w| w.rate()).unwrap_or_else(|| SuccessWindow::default().rate())
+    }
+
+    fn record_outcome(&self, provider_name: &str, success: bool) {
+        let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        stats.entry(provider_name.to_string())
+            .or_default()
+            .record(success, self.config.success_window_size);
+    }
+
+    /// Eligible providers for a new payment, best first.
+    pub fn rank_providers(&self, request: &PaymentGatewayRequest) -> Vec<&RoutedGateway> {
+        let mut eligible: Vec<&RoutedGateway> = self.providers.iter()
+            .filter(|p| p.rules.is_empty() || p.rules.iter().any(|r| r.matches(request)))
+            .collect();
+
+        // Cost is normalised against the most expensive eligible provider
+        let max_cost = eligible.iter()
+            .map(|p| p.cost.cost_for(request.amount))
+            .max()
+            .unwrap_or(Decimal::ZERO);
+
+        let score = |p: &RoutedGateway| -> Decimal {
+            let relative_cost = if max_cost > Decimal::ZERO {
+                p.cost.cost_for(request.amount) / max_cost
+            } else {
+                Decimal::ZERO
+            };
+            self.success_rate(&p.name) * self.config.success_rate_weight - relative_cost * self.config.cost_weight
+        };
+
+        eligible.sort_by(|a, b| score(b).cmp(&score(a)).then(a.priority.cmp(&b.priority)));
+        eligible
+    }
+
+    fn provider(&self, name: &str) -> Result<&RoutedGateway, GatewayError> {
+        self.providers.iter()
+            .find(|p| p.name == name)
+            .ok_or_else(|| GatewayError::ConfigurationError(format!("Unknown gateway '{}'", name)))
+    }
+
+    /// Prefixes the provider reference with the provider name.
+    fn tag_response(provider_name: &str, mut response: PaymentGatewayResponse) -> PaymentGatewayResponse {
+        response.gateway_transaction_id = format!("{}{}{}", provider_name, REFERENCE_SEPARATOR, response.gateway_transaction_id);
+        response
+    }
+
+    /// Splits a tagged reference back into (provider name, provider reference).
+    fn split_reference(reference: &str) -> Result<(&str, &str), GatewayError> {
+        reference.split_once(REFERENCE_SEPARATOR)
+            .ok_or_else(|| GatewayError::InternalError(format!("Reference '{}' was not issued by the routing gateway", reference)))
+    }
+
+    /// Capture/refund must go to the provider holding the original payment.
+    async fn submit_follow_up(&self, mut request: PaymentGatewayRequest, reference: &str) -> Result<PaymentGatewayResponse, GatewayError> {
+        let (provider_name, provider_ref) = Self::split_reference(reference)?;
+        let provider = self.provider(provider_name)?;
+        request.payment_method = PaymentMethodDetails::GatewayReference(provider_ref.to_string());
+        let result = provider.gateway.submit_payment(request).await;
+        self.record_outcome(provider_name, matches!(&result, Ok(r) if r.success));
+        result.map(|r| Self::tag_response(provider_name, r))
+    }
+
+    /// Whether an error leaves the payment in an unknown state that might allow failover.
+    fn is_failover_candidate(error: &GatewayError) -> bool {
+        matches!(error, GatewayError::NetworkError(_) | GatewayError::Timeout)
+    }
+}
+
+/// Gateway implementations a configured provider can use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum GatewayKind {
+    Mock, // Scenario-driven mock (test tokens, magic amounts); the only implementation so far
+}
+
+/// One provider entry of the routing file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GatewayProviderConfig {
+    pub name: String,
+    pub kind: GatewayKind,
+    #[serde(default)]
+    pub rules: Vec<RoutingRule>,
+    #[serde(default)]
+    pub cost: GatewayCost,
+    #[serde(default)]
+    pub priority: u32,
+}
+
+/// Card gateways and how requests are routed between them, loaded from a JSON file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GatewayRoutingConfig {
+    #[serde(default)]
+    pub version: Option<String>, // Free text, logged when the configuration is loaded
+    #[serde(default)]
+    pub routing: RoutingConfig,
+    pub providers: Vec<GatewayProviderConfig>,
+}
+
+impl JsonConfig for GatewayRoutingConfig {
+    const NAME: &'static str = "card gateway routing";
+
+    /// Rejects files the router could not be built from.
+    fn validate(&self) -> Result<(), DomainError> {
+        if self.providers.is_empty() {
+            return Err(DomainError::Configuration("Card gateway routing needs at least one provider".to_string()));
+        }
+        if self.routing.success_window_size == 0 {
+            return Err(DomainError::Configuration("success_window_size must be positive".to_string()));
+        }
+        let mut names = HashSet::new();
+        for provider in &self.providers {
+            if provider.name.is_empty() || provider.name.contains(REFERENCE_SEPARATOR) {
+                return Err(DomainError::Configuration(format!("Invalid gateway name '{}'", provider.name)));
+            }
+            if !names.insert(provider.name.as_str()) {
+                return Err(DomainError::Configuration(format!("Duplicate gateway name '{}'", provider.name)));
+            }
+        }
+        Ok(())
+    }
+
+    fn summary(&self) -> String {
+        config::versioned_summary(self.version.as_deref(), self.providers.len(), "gateways")
+    }
+}
+
+impl RoutingPaymentGateway {
+    /// Builds the router and its providers from the routing file.
+    pub fn from_config(config: &GatewayRoutingConfig) -> Result<Self, GatewayError> {
+        config.providers.iter().try_fold(RoutingPaymentGateway::new(config.routing.clone()), |router, provider| {
+            let gateway: Arc<dyn PaymentGateway> = match provider.kind {
+                GatewayKind::Mock => Arc::new(MockPaymentGateway::new()),
+            };
+            router.with_provider(RoutedGateway {
+                name: provider.name.clone(),
+                gateway,
+                rules: provider.rules.clone(),
+                cost: provider.cost.clone(),
+                priority: provider.priority,
+            })
+        })
+    }
+}
+
+#[async_trait]
+impl PaymentGateway for RoutingPaymentGateway {
+    async fn submit_payment(
+        &self,
+        request: PaymentGatewayRequest,
+    ) -> Result<PaymentGatewayResponse, GatewayError> {
+        // Follow-up operations are pinned to the original provider
+        if let PaymentMethodDetails::GatewayReference(reference) = &request.payment_method {
+            let reference = reference.clone();
+            return self.submit_follow_up(request, &reference).await;
+        }
+        if matches!(request.intent, PaymentIntent::Capture | PaymentIntent::Refund) {
+            return Err(GatewayError::ConfigurationError("Capture/Refund requires a gateway reference".to_string()));
+        }
+
+        let ranked = self.rank_providers(&request);
+        if ranked.is_empty() {
+            return Err(GatewayError::ConfigurationError(format!(
+                "No gateway configured for {} {} (BIN country {:?})",
+                request.amount, request.currency, request.card_bin_country
+            )));
+        }
+
+        let mut last_error = None;
+        for (attempt, provider) in ranked.iter().take(1 + self.config.max_failover_attempts).enumerate() {
+            if attempt > 0 {
+                log::warn!("Failing over card payment to gateway '{}' (attempt {})", provider.name, attempt + 1);
+            }
+            match provider.gateway.submit_payment(request.clone()).await {
+                Ok(response) => {
+                    self.record_outcome(&provider.name, response.success);
+                    return Ok(Self::tag_response(&provider.name, response));
+                }
+                Err(error) => {
+                    self.record_outcome(&provider.name, false);
+                    if !Self::is_failover_candidate(&error) {
+                        return Err(error); // Rejections/config errors are final
+                    }
+
+                    // Only fail over when the provider confirms the attempt never went through
+                    let Some(key) = request.idempotency_key.as_deref() else {
+                        log::error!("Gateway '{}' failed ({}) and no idempotency key was sent; outcome unknown, not failing over", provider.name, error);
+                        return Err(error);
+                    };
+                    match provider.gateway.find_by_idempotency_key(key).await {
+                        Ok(None) => {
+                            log::warn!("Gateway '{}' failed ({}) and confirms no payment for key {}", provider.name, error, key);
+                            last_error = Some(error);
+                        }
+                        Ok(Some(existing)) => {
+                            log::info!("Gateway '{}' processed key {} despite transport error", provider.name, key);
+                            return Ok(Self::tag_response(&provider.name, existing));
+                        }
+                        Err(lookup_error) => {
+                            log::error!("Gateway '{}' failed ({}); lookup for key {} also failed ({}), not failing over",
+                                provider.name, error, key, lookup_error);
+                            return Err(error);
+                        }
+                    }
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or(GatewayError::InternalError("No gateway attempt was made".to_string())))
+    }
+
+    async fn get_transaction_status(
+        &self,
+        gateway_transaction_id: &str,
+    ) -> Result<PaymentGatewayResponse, GatewayError> {
+        let (provider_name, provider_ref) = Self::split_reference(gateway_transaction_id)?;
+        let response = self.provider(provider_name)?.gateway.get_transaction_status(provider_ref).await?;
+        Ok(Self::tag_response(provider_name, response))
+    }
+
+    async fn find_by_idempotency_key(
+        &self,
+        idempotency_key: &str,
+    ) -> Result<Option<PaymentGatewayResponse>, GatewayError> {
+        // Any provider might hold the payment; a single unknown answer makes the overall answer unknown
+        for provider in &self.providers {
+            if let Some(found) = provider.gateway.find_by_idempotency_key(idempotency_key).await? {
+                return Ok(Some(Self::tag_response(&provider.name, found)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Minimal scripted gateway for routing tests.
+    struct ScriptedGateway {
+        result: fn() -> Result<PaymentGatewayResponse, GatewayError>,
+        lookup: fn() -> Result<Option<PaymentGatewayResponse>, GatewayError>,
+        calls: AtomicUsize,
+    }
+
+    impl ScriptedGateway {
+        fn new(
+            result: fn() -> Result<PaymentGatewayResponse, GatewayError>,
+            lookup: fn() -> Result<Option<PaymentGatewayResponse>, GatewayError>,
+        ) -> Arc<Self> {
+            Arc::new(ScriptedGateway { result, lookup, calls: AtomicUsize::new(0) })
+        }
+    }
+
+    fn approved() -> Result<PaymentGatewayResponse, GatewayError> {
+        Ok(PaymentGatewayResponse {
+            success: true,
+            gateway_transaction_id: "tx_1".to_string(),
+            status: Some("Authorized".to_string()),
+            error_code: None,
+            error_message: None,
+            details: None,
+        })
+    }
+    fn timeout() -> Result<PaymentGatewayResponse, GatewayError> { Err(GatewayError::Timeout) }
+    fn not_found() -> Result<Option<PaymentGatewayResponse>, GatewayError> { Ok(None) }
+    fn found() -> Result<Option<PaymentGatewayResponse>, GatewayError> { approved().map(Some) }
+    fn unknown() -> Result<Option<PaymentGatewayResponse>, GatewayError> { Err(GatewayError::Timeout) }
+
+    #[async_trait]
+    impl PaymentGateway for ScriptedGateway {
+        async fn submit_payment(&self, _request: PaymentGatewayRequest) -> Result<PaymentGatewayResponse, GatewayError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            (self.result)()
+        }
+        async fn get_transaction_status(&self, _id: &str) -> Result<PaymentGatewayResponse, GatewayError> {
+            approved()
+        }
+        async fn find_by_idempotency_key(&self, _key: &str) -> Result<Option<PaymentGatewayResponse>, GatewayError> {
+            (self.lookup)()
+        }
+    }
+
+    fn request(currency: &str, amount: Decimal, bin_country: Option<&str>, key: Option<&str>) -> PaymentGatewayRequest {
+        PaymentGatewayRequest {
+            amount,
+            currency: currency.to_string(),
+            payment_method: PaymentMethodDetails::CardToken("tok_test".to_string()),
+            intent: PaymentIntent::Authorize,
+            description: None,
+            customer_id: None,
+            metadata: None,
+            card_bin_country: bin_country.map(str::to_string),
+            idempotency_key: key.map(str::to_string),
+        }
+    }
+
+    fn provider(name: &str, gateway: Arc<ScriptedGateway>, rules: Vec<RoutingRule>, pct: Decimal, priority: u32) -> RoutedGateway {
+        RoutedGateway {
+            name: name.to_string(),
+            gateway,
+            rules,
+            cost: GatewayCost { fixed_fee: dec!(0.30), percentage_fee: pct },
+            priority,
+        }
+    }
+
+    #[test]
+    fn test_rules_filter_by_currency_country_and_amount() {
+        let eu_only = RoutingRule {
+            currencies: Some(vec!["EUR".to_string()]),
+            bin_countries: Some(vec!["DE".to_string(), "FR".to_string()]),
+            min_amount: None,
+            max_amount: Some(dec!(5000)),
+        };
+        assert!(eu_only.matches(&request("EUR", dec!(100), Some("de"), None)));
+        assert!(!eu_only.matches(&request("USD", dec!(100), Some("DE"), None)));
+        assert!(!eu_only.matches(&request("EUR", dec!(100), None, None)));
+        assert!(!eu_only.matches(&request("EUR", dec!(5000.01), Some("FR"), None)));
+    }
+
+    #[test]
+    fn test_cheaper_provider_ranks_first_with_equal_history() {
+        let router = RoutingPaymentGateway::new(RoutingConfig::default())
+            .with_provider(provider("expensive", ScriptedGateway::new(approved, not_found), vec![], dec!(0.035), 0)).unwrap()
+            .with_provider(provider("cheap", ScriptedGateway::new(approved, not_found), vec![], dec!(0.015), 1)).unwrap();
+        let ranked = router.rank_providers(&request("USD", dec!(100), None, None));
+        assert_eq!(ranked[0].name, "cheap");
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_when_primary_confirms_no_charge() {
+        let primary = ScriptedGateway::new(timeout, not_found);
+        let secondary = ScriptedGateway::new(approved, not_found);
+        let router = RoutingPaymentGateway::new(RoutingConfig::default())
+            .with_provider(provider("primary", primary.clone(), vec![], dec!(0.01), 0)).unwrap()
+            .with_provider(provider("secondary", secondary.clone(), vec![], dec!(0.03), 1)).unwrap();
+
+        let response = router.submit_payment(request("USD", dec!(50), None, Some("key-1"))).await.unwrap();
+        assert_eq!(response.gateway_transaction_id, "secondary::tx_1");
+        assert_eq!(secondary.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_no_failover_when_outcome_unknown() {
+        let secondary = ScriptedGateway::new(approved, not_found);
+        let router = RoutingPaymentGateway::new(RoutingConfig::default())
+            .with_provider(provider("primary", ScriptedGateway::new(timeout, unknown), vec![], dec!(0.01), 0)).unwrap()
+            .with_provider(provider("secondary", secondary.clone(), vec![], dec!(0.03), 1)).unwrap();
+
+        assert!(matches!(router.submit_payment(request("USD", dec!(50), None, Some("key-2"))).await, Err(GatewayError::Timeout)));
+        // Without a key the primary cannot be asked at all
+        assert!(router.submit_payment(request("USD", dec!(50), None, None)).await.is_err());
+        assert_eq!(secondary.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_charge_found_after_timeout_is_returned_not_retried() {
+        let secondary = ScriptedGateway::new(approved, not_found);
+        let router = RoutingPaymentGateway::new(RoutingConfig::default())
+            .with_provider(provider("primary", ScriptedGateway::new(timeout, found), vec![], dec!(0.01), 0)).unwrap()
+            .with_provider(provider("secondary", secondary.clone(), vec![], dec!(0.03), 1)).unwrap();
+
+        let response = router.submit_payment(request("USD", dec!(50), None, Some("key-3"))).await.unwrap();
+        assert_eq!(response.gateway_transaction_id, "primary::tx_1");
+        assert_eq!(secondary.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_router_built_from_config_file() {
+        let config = config::parse_json::<GatewayRoutingConfig>(
+            r#"{"version": "2025-05", "routing": {"max_failover_attempts": 2},
+                "providers": [
+                    {"name": "eu", "kind": "MOCK", "rules": [{"currencies": ["EUR"]}], "cost": {"percentage_fee": "0.012"}},
+                    {"name": "global", "kind": "MOCK", "cost": {"fixed_fee": "0.30", "percentage_fee": "0.029"}, "priority": 1}
+                ]}"#,
+        )
+        .unwrap();
+        assert_eq!(config.routing.max_failover_attempts, 2);
+        assert_eq!(config.routing.success_window_size, 100); // Unset tuning keeps its default
+
+        let router = RoutingPaymentGateway::from_config(&config).unwrap();
+        let names = |currency: &str| -> Vec<String> {
+            router.rank_providers(&request(currency, dec!(100), None, None)).iter().map(|p| p.name.clone()).collect()
+        };
+        assert_eq!(names("EUR"), vec!["eu", "global"]);
+        assert_eq!(names("USD"), vec!["global"]);
+
+        assert!(config::parse_json::<GatewayRoutingConfig>(r#"{"providers": []}"#).is_err());
+        assert!(config::parse_json::<GatewayRoutingConfig>(r#"{"providers": [{"name": "a::b", "kind": "MOCK"}]}"#).is_err());
+        assert!(config::parse_json::<GatewayRoutingConfig>(
+            r#"{"providers": [{"name": "a", "kind": "MOCK"}, {"name": "a", "kind": "MOCK"}]}"#
+        ).is_err());
+        assert!(config::parse_json::<GatewayRoutingConfig>(r#"{"providers": [{"name": "a", "kind": "ADYEN"}]}"#).is_err());
+    }
+}
@@ -127,6 +127,7 @@ impl TransactionStatus {
              "Processing" => Ok(TransactionStatus::Processing),
              "RequiresAction" => Ok(TransactionStatus::RequiresAction),
              "Authorized" => Ok(TransactionStatus::Authorized),
+             "Capturing" => Ok(TransactionStatus::Capturing),
              "Submitted" => Ok(TransactionStatus::Submitted),
              "Settled" => Ok(TransactionStatus::Settled),
              "Completed" => Ok(TransactionStatus::Completed),
@@ -0,0 +1,744 @@
+// /home/inno/elights_jobes-research/backend/domain/src/payments/scheduler.rs
+// Standing orders: future-dated and recurring ACH credits / outbound wires.
+//
+// A schedule stores the payment template and its calendar rule (`payments::recurrence`). The
+// `ScheduledPaymentWorker` pays occurrences in two steps:
+// 1. Claim (one DB transaction, schedule row locked): plan what is due, insert one run row per occurrence
+//    (PENDING to pay, SKIPPED for missed ones per the missed-run policy) and advance the schedule.
+// 2. Submit each PENDING run through `PaymentProcessor`. The transaction carries the run id (unique), so a
+//    run left PENDING by a crash is completed from the existing transaction instead of paying twice.
+use crate::approvals::ApprovalPolicySet;
+use crate::beneficiaries::CoolingOffLimits;
+use crate::error::DomainError;
+use crate::fees::{ChargeBearer, FeeSchedule};
+use crate::limits::LimitPolicy;
+use crate::sanctions::SanctionsScreener;
+use crate::fraud::{FraudAction, FraudRules};
+use crate::models::{
+    AchDetails, AuditOutcome, AuditTargetType, NewPaymentSchedule, NewPaymentScheduleRun, PaymentSchedule,
+    PaymentScheduleRun, ScheduleRunStatus, ScheduleStatus, Transaction, TransactionStatus, TransactionType, Wallet, WalletStatus,
+    WireDetails,
+};
+use crate::payments::gateway::PaymentGateway;
+use crate::payments::payment_processor::{PaymentProcessor, PaymentRequest};
+use crate::payments::recurrence::{
+    next_not_before, plan_due, BusinessCalendar, BusinessDayConvention, MissedRunPolicy, Occurrence, RecurrenceRule,
+    ScheduleLimits, ScheduleTerms,
+};
+use crate::payments::validator::{self, ValidationContext};
+use crate::security::audit;
+use chrono::{Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+/// A PENDING run untouched for this long is considered abandoned by a crashed worker.
+pub const RUN_LEASE_SECONDS: i64 = 600;
+
+const ACTOR: &str = "PAYMENT_SCHEDULER";
+
+/// Rail details stored with a schedule and passed to the processor on every occurrence.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ScheduledPaymentDetails {
+    pub ach_details: Option<AchDetails>,
+    pub wire_details: Option<WireDetails>,
+}
+
+/// Input for `create_schedule`.
+#[derive(Debug, Clone)]
+pub struct CreateSchedule<'a> {
+    pub user_id: Uuid,
+    pub source_wallet_id: Uuid,
+    pub payment_type: TransactionType, // AchCredit or WireOutbound
+    pub amount: rust_decimal::Decimal,
+    pub charge_bearer: ChargeBearer,
+    pub details: ScheduledPaymentDetails,
+    pub description: Option<&'a str>,
+    pub recurrence: RecurrenceRule,
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+    pub max_occurrences: Option<i32>,
+    pub business_day_convention: BusinessDayConvention,
+    pub missed_run_policy: MissedRunPolicy,
+}
+
+/// A schedule with its occurrences so far, most recent first.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleWithRuns {
+    pub schedule: PaymentSchedule,
+    pub runs: Vec<PaymentScheduleRun>,
+}
+
+fn recurrence_of(schedule: &PaymentSchedule) -> Result<RecurrenceRule, DomainError> {
+    serde_json::from_value(schedule.recurrence.clone())
+        .map_err(|e| DomainError::Configuration(format!("Schedule {} has an invalid recurrence: {}", schedule.schedule_id, e)))
+}
+
+fn limits_of(schedule: &PaymentSchedule) -> ScheduleLimits {
+    ScheduleLimits { end_date: schedule.end_date, max_occurrences: schedule.max_occurrences }
+}
+
+fn convention_of(schedule: &PaymentSchedule) -> BusinessDayConvention {
+    BusinessDayConvention::parse(&schedule.business_day_convention).unwrap_or_default()
+}
+
+/// Creates a standing order. The first occurrence is on or after `start_date`, which cannot be in the past.
+pub fn create_schedule(
+    conn: &mut PgConnection,
+    request: CreateSchedule,
+    calendar: &BusinessCalendar,
+    today: NaiveDate,
+) -> Result<PaymentSchedule, DomainError> {
+    if !matches!(request.payment_type, TransactionType::AchCredit | TransactionType::WireOutbound) {
+        return Err(DomainError::Validation(format!("Scheduling is not supported for {:?} payments", request.payment_type)));
+    }
+    if request.amount <= rust_decimal::Decimal::ZERO {
+        return Err(DomainError::Validation("Amount must be positive".to_string()));
+    }
+    request.recurrence.validate()?;
+    if request.start_date < today {
+        return Err(DomainError::Validation("Start date cannot be in the past".to_string()));
+    }
+    if request.end_date.is_some_and(|end| end < request.start_date) {
+        return Err(DomainError::Validation("End date cannot be before the start date".to_string()));
+    }
+    if request.max_occurrences.is_some_and(|max| max <= 0) {
+        return Err(DomainError::Validation("Maximum occurrences must be positive".to_string()));
+    }
+
+    let terms = ScheduleTerms {
+        rule: &request.recurrence,
+        start: request.start_date,
+        limits: ScheduleLimits { end_date: request.end_date, max_occurrences: request.max_occurrences },
+        convention: request.business_day_convention,
+    };
+    let first = terms.first(calendar)
+        .ok_or_else(|| DomainError::Validation("Schedule has no occurrence before its end date".to_string()))?;
+
+    conn.transaction(|conn| {
+        use crate::schema::wallets::dsl as w;
+        let wallet: Wallet = w::wallets
+            .filter(w::wallet_id.eq(request.source_wallet_id))
+            .filter(w::user_id.eq(request.user_id)) // Authorization check
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| DomainError::NotFound(format!("Wallet {} not found", request.source_wallet_id)))?;
+        if wallet.status != WalletStatus::Active.to_string() {
+            return Err(DomainError::Validation(format!("Source wallet {} is not active", wallet.wallet_id)));
+        }
+
+        validator::validate_payment_details(
+            &request.payment_type,
+            request.details.ach_details.as_ref(),
+            request.details.wire_details.as_ref(),
+            None,
+            None,
+            &ValidationContext { currency: &wallet.currency_code },
+        )?;
+
+        let payment_type = request.payment_type.to_string();
+        let payment_details = serde_json::to_value(&request.details)
+            .map_err(|e| DomainError::Validation(format!("Invalid payment details: {}", e)))?;
+        let recurrence = serde_json::to_value(&request.recurrence)
+            .map_err(|e| DomainError::Validation(format!("Invalid recurrence: {}", e)))?;
+        let schedule: PaymentSchedule = diesel::insert_into(crate::schema::payment_schedules::table)
+            .values(&NewPaymentSchedule {
+                user_id: request.user_id,
+                source_wallet_id: wallet.wallet_id,
+                payment_type: &payment_type,
+                amount: request.amount,
+                currency_code: &wallet.currency_code, // Paid in the wallet's currency
+                charge_bearer: request.charge_bearer.as_str(),
+                payment_details,
+                description: request.description,
+                recurrence,
+                start_date: request.start_date,
+                end_date: request.end_date,
+                max_occurrences: request.max_occurrences,
+                business_day_convention: request.business_day_convention.as_str(),
+                missed_run_policy: request.missed_run_policy.as_str(),
+                status: ScheduleStatus::Active.as_str(),
+                next_occurrence: Some(first.nominal),
+                next_run_date: Some(first.run_date),
+            })
+            .get_result(conn)?;
+
+        audit::log_db_audit_event(
+            conn,
+            Some(request.user_id),
+            &request.user_id.to_string(),
+            "CREATE_PAYMENT_SCHEDULE",
+            Some(AuditTargetType::PaymentSchedule),
+            Some(&schedule.schedule_id.to_string()),
+            AuditOutcome::Success,
+            Some(json!({"type": schedule.payment_type, "amount": schedule.amount.to_string(), "currency": schedule.currency_code,
+                "recurrence": schedule.recurrence, "next_run_date": schedule.next_run_date})),
+            None,
+        )?;
+        log::info!("Created payment schedule {} for user {}, first run {}", schedule.schedule_id, request.user_id, first.run_date);
+        Ok(schedule)
+    })
+}
+
+/// The caller's schedules, newest first.
+pub fn list_schedules(conn: &mut PgConnection, user_id: Uuid) -> Result<Vec<PaymentSchedule>, DomainError> {
+    use crate::schema::payment_schedules::dsl as ps;
+    let schedules = ps::payment_schedules
+        .filter(ps::user_id.eq(user_id))
+        .order(ps::created_at.desc())
+        .load(conn)?;
+    Ok(schedules)
+}
+
+/// One of the caller's schedules with its run history.
+pub fn get_schedule(conn: &mut PgConnection, schedule_id: Uuid, user_id: Uuid) -> Result<ScheduleWithRuns, DomainError> {
+    use crate::schema::payment_schedule_runs::dsl as r;
+    use crate::schema::payment_schedules::dsl as ps;
+    let schedule: PaymentSchedule = ps::payment_schedules
+        .filter(ps::schedule_id.eq(schedule_id))
+        .filter(ps::user_id.eq(user_id)) // Authorization check
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("Payment schedule {} not found", schedule_id)))?;
+    let runs = r::payment_schedule_runs
+        .filter(r::schedule_id.eq(schedule_id))
+        .order(r::occurrence_date.desc())
+        .load(conn)?;
+    Ok(ScheduleWithRuns { schedule, runs })
+}
+
+/// Locks one of the caller's schedules and checks it is in one of `allowed` statuses.
+fn lock_owned(
+    conn: &mut PgConnection,
+    schedule_id: Uuid,
+    user_id: Uuid,
+    allowed: &[ScheduleStatus],
+) -> Result<PaymentSchedule, DomainError> {
+    use crate::schema::payment_schedules::dsl as ps;
+    let schedule: PaymentSchedule = ps::payment_schedules
+        .filter(ps::schedule_id.eq(schedule_id))
+        .filter(ps::user_id.eq(user_id)) // Authorization check
+        .for_update()
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("Payment schedule {} not found", schedule_id)))?;
+    if !allowed.iter().any(|status| status.as_str() == schedule.status) {
+        return Err(DomainError::Validation(format!("Payment schedule {} is {}", schedule_id, schedule.status)));
+    }
+    Ok(schedule)
+}
+
+fn audit_schedule_change(conn: &mut PgConnection, schedule: &PaymentSchedule, action: &str) -> Result<(), DomainError> {
+    audit::log_db_audit_event(
+        conn,
+        Some(schedule.user_id),
+        &schedule.user_id.to_string(),
+        action,
+        Some(AuditTargetType::PaymentSchedule),
+        Some(&schedule.schedule_id.to_string()),
+        AuditOutcome::Success,
+        Some(json!({"status": schedule.status, "next_occurrence": schedule.next_occurrence, "next_run_date": schedule.next_run_date})),
+        None,
+    )
+}
+
+/// Stops an active schedule. Occurrences falling due while paused are not paid.
+pub fn pause_schedule(conn: &mut PgConnection, schedule_id: Uuid, user_id: Uuid) -> Result<PaymentSchedule, DomainError> {
+    use crate::schema::payment_schedules::dsl as ps;
+    conn.transaction(|conn| {
+        lock_owned(conn, schedule_id, user_id, &[ScheduleStatus::Active])?;
+        let schedule: PaymentSchedule = diesel::update(ps::payment_schedules.find(schedule_id))
+            .set(ps::status.eq(ScheduleStatus::Paused.as_str()))
+            .get_result(conn)?;
+        audit_schedule_change(conn, &schedule, "PAUSE_PAYMENT_SCHEDULE")?;
+        Ok(schedule)
+    })
+}
+
+/// Restarts a paused schedule from its next occurrence due today or later.
+pub fn resume_schedule(
+    conn: &mut PgConnection,
+    schedule_id: Uuid,
+    user_id: Uuid,
+    calendar: &BusinessCalendar,
+    today: NaiveDate,
+) -> Result<PaymentSchedule, DomainError> {
+    use crate::schema::payment_schedules::dsl as ps;
+    conn.transaction(|conn| {
+        let schedule = lock_owned(conn, schedule_id, user_id, &[ScheduleStatus::Paused])?;
+        let rule = recurrence_of(&schedule)?;
+        let terms = ScheduleTerms { rule: &rule, start: schedule.start_date, limits: limits_of(&schedule), convention: convention_of(&schedule) };
+        let next = next_not_before(&terms, calendar, schedule.next_occurrence, schedule.occurrences_count, today);
+        let status = if next.is_some() { ScheduleStatus::Active } else { ScheduleStatus::Completed };
+        let schedule: PaymentSchedule = diesel::update(ps::payment_schedules.find(schedule_id))
+            .set((
+                ps::status.eq(status.as_str()),
+                ps::next_occurrence.eq(next.map(|o| o.nominal)),
+                ps::next_run_date.eq(next.map(|o| o.run_date)),
+            ))
+            .get_result(conn)?;
+        audit_schedule_change(conn, &schedule, "RESUME_PAYMENT_SCHEDULE")?;
+        Ok(schedule)
+    })
+}
+
+/// Skips the next occurrence only; the schedule carries on with the one after.
+pub fn skip_next_occurrence(
+    conn: &mut PgConnection,
+    schedule_id: Uuid,
+    user_id: Uuid,
+    calendar: &BusinessCalendar,
+) -> Result<PaymentSchedule, DomainError> {
+    use crate::schema::payment_schedules::dsl as ps;
+    conn.transaction(|conn| {
+        let schedule = lock_owned(conn, schedule_id, user_id, &[ScheduleStatus::Active, ScheduleStatus::Paused])?;
+        let (Some(nominal), Some(run_date)) = (schedule.next_occurrence, schedule.next_run_date) else {
+            return Err(DomainError::Validation(format!("Payment schedule {} has no upcoming occurrence", schedule_id)));
+        };
+        record_run(conn, schedule_id, Occurrence { nominal, run_date }, ScheduleRunStatus::Skipped, Some("Skipped by customer"))?;
+
+        let rule = recurrence_of(&schedule)?;
+        let count = schedule.occurrences_count + 1;
+        let convention = convention_of(&schedule);
+        let next = rule.next_after(schedule.start_date, nominal)
+            .filter(|date| limits_of(&schedule).allows(*date, count))
+            .map(|date| Occurrence { nominal: date, run_date: calendar.adjust(date, convention) });
+        let status = if next.is_some() { schedule.status.as_str() } else { ScheduleStatus::Completed.as_str() };
+        let schedule: PaymentSchedule = diesel::update(ps::payment_schedules.find(schedule_id))
+            .set((
+                ps::status.eq(status),
+                ps::occurrences_count.eq(count),
+                ps::next_occurrence.eq(next.map(|o| o.nominal)),
+                ps::next_run_date.eq(next.map(|o| o.run_date)),
+            ))
+            .get_result(conn)?;
+        audit_schedule_change(conn, &schedule, "SKIP_PAYMENT_SCHEDULE_OCCURRENCE")?;
+        Ok(schedule)
+    })
+}
+
+/// Ends a schedule for good. Payments already submitted are not affected.
+pub fn cancel_schedule(conn: &mut PgConnection, schedule_id: Uuid, user_id: Uuid) -> Result<PaymentSchedule, DomainError> {
+    use crate::schema::payment_schedules::dsl as ps;
+    conn.transaction(|conn| {
+        lock_owned(conn, schedule_id, user_id, &[ScheduleStatus::Active, ScheduleStatus::Paused])?;
+        let schedule: PaymentSchedule = diesel::update(ps::payment_schedules.find(schedule_id))
+            .set((
+                ps::status.eq(ScheduleStatus::Cancelled.as_str()),
+                ps::next_occurrence.eq(None::<NaiveDate>),
+                ps::next_run_date.eq(None::<NaiveDate>),
+            ))
+            .get_result(conn)?;
+        audit_schedule_change(conn, &schedule, "CANCEL_PAYMENT_SCHEDULE")?;
+        Ok(schedule)
+    })
+}
+
+/// Inserts the run row for an occurrence. Fails if the occurrence already has one.
+fn record_run(
+    conn: &mut PgConnection,
+    schedule_id: Uuid,
+    occurrence: Occurrence,
+    status: ScheduleRunStatus,
+    error_message: Option<&str>,
+) -> Result<PaymentScheduleRun, DomainError> {
+    let run = diesel::insert_into(crate::schema::payment_schedule_runs::table)
+        .values(&NewPaymentScheduleRun {
+            schedule_id,
+            occurrence_date: occurrence.nominal,
+            run_date: occurrence.run_date,
+            status: status.as_str(),
+            transaction_id: None,
+            error_message,
+        })
+        .get_result(conn)?;
+    Ok(run)
+}
+
+/// Transaction already created for a run, if any.
+fn run_transaction(conn: &mut PgConnection, run_id: Uuid) -> Result<Option<Transaction>, DomainError> {
+    use crate::schema::transactions::dsl as t;
+    let transaction = t::transactions.filter(t::scheduled_run_id.eq(run_id)).first(conn).optional()?;
+    Ok(transaction)
+}
+
+/// What happens to a PENDING run abandoned by a crashed worker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AbandonedRun {
+    Resume, // Submit it, or record the payment already made
+    Void,   // Its schedule was paused or cancelled meanwhile and nothing was paid: skip it
+}
+
+/// A schedule is marked COMPLETED when its last occurrence is claimed, so that run is still owed.
+fn abandoned_run_action(schedule_status: &str, payment_made: bool) -> AbandonedRun {
+    let withdrawn = schedule_status == ScheduleStatus::Paused.as_str() || schedule_status == ScheduleStatus::Cancelled.as_str();
+    if withdrawn && !payment_made {
+        AbandonedRun::Void
+    } else {
+        AbandonedRun::Resume
+    }
+}
+
+/// Records an abandoned run of a schedule that is no longer active as skipped.
+fn void_run(conn: &mut PgConnection, schedule: &PaymentSchedule, run: &PaymentScheduleRun) -> Result<(), DomainError> {
+    use crate::schema::payment_schedule_runs::dsl as r;
+    let reason = format!("Schedule {} before the payment was made", schedule.status.to_lowercase());
+    diesel::update(r::payment_schedule_runs.find(run.run_id).filter(r::status.eq(ScheduleRunStatus::Pending.as_str())))
+        .set((r::status.eq(ScheduleRunStatus::Skipped.as_str()), r::error_message.eq(Some(reason.as_str()))))
+        .execute(conn)?;
+    audit::log_db_audit_event(
+        conn,
+        Some(schedule.user_id),
+        ACTOR,
+        "VOID_PAYMENT_SCHEDULE_RUN",
+        Some(AuditTargetType::PaymentSchedule),
+        Some(&schedule.schedule_id.to_string()),
+        AuditOutcome::Success,
+        Some(json!({"run_id": run.run_id, "occurrence_date": run.occurrence_date, "run_date": run.run_date})),
+        Some(&reason),
+    )?;
+    log::warn!("Voided abandoned run {} of schedule {}: {}", run.run_id, schedule.schedule_id, reason);
+    Ok(())
+}
+
+/// Pays the due occurrences of standing orders. Run it periodically (see core-api `services::payment_scheduler`).
+pub struct ScheduledPaymentWorker<'a> {
+    card_gateway: &'a dyn PaymentGateway,
+    fee_schedule: Option<&'a FeeSchedule>,
+    approval_policies: Option<&'a ApprovalPolicySet>,
+    beneficiary_limits: Option<&'a CoolingOffLimits>,
+    limit_policy: Option<&'a LimitPolicy>,
+    sanctions_screener: Option<&'a SanctionsScreener>,
+    fraud_rules: Option<&'a FraudRules>,
+    calendar: &'a BusinessCalendar,
+    batch_size: i64,
+}
+
+impl<'a> ScheduledPaymentWorker<'a> {
+    pub fn new(card_gateway: &'a dyn PaymentGateway, calendar: &'a BusinessCalendar) -> Self {
+        ScheduledPaymentWorker { card_gateway, fee_schedule: None, approval_policies: None, beneficiary_limits: None, limit_policy: None, sanctions_screener: None, fraud_rules: None, calendar, batch_size: 50 }
+    }
+
+    /// Charges fees from `fee_schedule` on the scheduled payments.
+    pub fn with_fee_schedule(mut self, fee_schedule: &'a FeeSchedule) -> Self {
+        self.fee_schedule = Some(fee_schedule);
+        self
+    }
+
+    /// Holds scheduled payments matching `approval_policies` for approval, like one-off payments.
+    pub fn with_approval_policies(mut self, approval_policies: &'a ApprovalPolicySet) -> Self {
+        self.approval_policies = Some(approval_policies);
+        self
+    }
+
+    /// Applies saved-beneficiary checks (verification, cooling-off limits) to the scheduled payments.
+    pub fn with_beneficiary_limits(mut self, beneficiary_limits: &'a CoolingOffLimits) -> Self {
+        self.beneficiary_limits = Some(beneficiary_limits);
+        self
+    }
+
+    /// Counts scheduled payments against the customer's transaction limits, like one-off payments.
+    pub fn with_limit_policy(mut self, limit_policy: &'a LimitPolicy) -> Self {
+        self.limit_policy = Some(limit_policy);
+        self
+    }
+
+    /// Screens scheduled wires and crypto withdrawals against the sanctions lists, like one-off payments.
+    pub fn with_sanctions_screener(mut self, sanctions_screener: &'a SanctionsScreener) -> Self {
+        self.sanctions_screener = Some(sanctions_screener);
+        self
+    }
+
+    /// Scores scheduled payments with the fraud rules in force at each run, like one-off payments.
+    pub fn with_fraud_rules(mut self, fraud_rules: &'a FraudRules) -> Self {
+        self.fraud_rules = Some(fraud_rules);
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: i64) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Claims and submits the occurrences due on `today`. Returns how many runs were submitted or failed.
+    pub async fn run_once(&self, conn: &mut PgConnection, today: NaiveDate) -> Result<usize, DomainError> {
+        let mut runs = self.claim_due_runs(conn, today)?;
+        runs.extend(self.reclaim_abandoned_runs(conn)?);
+        let count = runs.len();
+        for (schedule, run) in runs {
+            if let Err(e) = self.submit_run(conn, &schedule, &run).await {
+                // Left PENDING, retried once the lease lapses
+                log::error!("Scheduled payment run {} (schedule {}) failed: {}", run.run_id, schedule.schedule_id, e);
+            }
+        }
+        Ok(count)
+    }
+
+    /// Step 1: plans the due schedules under a row lock, records their runs and advances them.
+    fn claim_due_runs(&self, conn: &mut PgConnection, today: NaiveDate) -> Result<Vec<(PaymentSchedule, PaymentScheduleRun)>, DomainError> {
+        use crate::schema::payment_schedules::dsl as ps;
+        conn.transaction(|conn| {
+            let due: Vec<PaymentSchedule> = ps::payment_schedules
+                .filter(ps::status.eq(ScheduleStatus::Active.as_str()))
+                .filter(ps::next_run_date.le(today))
+                .order(ps::next_run_date.asc())
+                .limit(self.batch_size)
+                .for_update()
+                .skip_locked()
+                .load(conn)?;
+
+            let mut claimed = Vec::new();
+            for schedule in due {
+                let rule = recurrence_of(&schedule)?;
+                let terms = ScheduleTerms { rule: &rule, start: schedule.start_date, limits: limits_of(&schedule), convention: convention_of(&schedule) };
+                let policy = MissedRunPolicy::parse(&schedule.missed_run_policy).unwrap_or_default();
+                let plan = plan_due(&terms, self.calendar, schedule.next_occurrence, schedule.occurrences_count, policy, today);
+
+                for occurrence in &plan.skip {
+                    record_run(conn, schedule.schedule_id, *occurrence, ScheduleRunStatus::Skipped, Some("Missed while the scheduler was not running"))?;
+                }
+                for occurrence in &plan.pay {
+                    let run = record_run(conn, schedule.schedule_id, *occurrence, ScheduleRunStatus::Pending, None)?;
+                    claimed.push((schedule.clone(), run));
+                }
+                if !plan.skip.is_empty() {
+                    log::warn!("Schedule {}: {} missed occurrence(s) skipped ({})", schedule.schedule_id, plan.skip.len(), policy.as_str());
+                }
+
+                let consumed = (plan.pay.len() + plan.skip.len()) as i32;
+                let status = if plan.next.is_some() { ScheduleStatus::Active } else { ScheduleStatus::Completed };
+                diesel::update(ps::payment_schedules.find(schedule.schedule_id))
+                    .set((
+                        ps::status.eq(status.as_str()),
+                        ps::occurrences_count.eq(schedule.occurrences_count + consumed),
+                        ps::next_occurrence.eq(plan.next.map(|o| o.nominal)),
+                        ps::next_run_date.eq(plan.next.map(|o| o.run_date)),
+                        ps::last_run_at.eq(Some(Utc::now())),
+                    ))
+                    .execute(conn)?;
+            }
+            Ok(claimed)
+        })
+    }
+
+    /// PENDING runs whose worker died before recording the outcome; the lease is renewed for this worker.
+    /// Runs of schedules paused or cancelled since then are voided instead, unless their payment was already made.
+    fn reclaim_abandoned_runs(&self, conn: &mut PgConnection) -> Result<Vec<(PaymentSchedule, PaymentScheduleRun)>, DomainError> {
+        use crate::schema::payment_schedule_runs::dsl as r;
+        use crate::schema::payment_schedules::dsl as ps;
+        conn.transaction(|conn| {
+            let stale: Vec<PaymentScheduleRun> = r::payment_schedule_runs
+                .filter(r::status.eq(ScheduleRunStatus::Pending.as_str()))
+                .filter(r::updated_at.lt(Utc::now() - Duration::seconds(RUN_LEASE_SECONDS)))
+                .limit(self.batch_size)
+                .for_update()
+                .skip_locked()
+                .load(conn)?;
+
+            let mut reclaimed = Vec::with_capacity(stale.len());
+            for run in stale {
+                // Locked like pause/cancel do, so the schedule cannot change status under us
+                let schedule: PaymentSchedule = ps::payment_schedules.find(run.schedule_id).for_update().first(conn)?;
+                let payment_made = run_transaction(conn, run.run_id)?.is_some();
+                if abandoned_run_action(&schedule.status, payment_made) == AbandonedRun::Void {
+                    void_run(conn, &schedule, &run)?;
+                    continue;
+                }
+                log::warn!("Recovering scheduled payment run {} after expired lease", run.run_id);
+                let run: PaymentScheduleRun = diesel::update(r::payment_schedule_runs.find(run.run_id))
+                    .set(r::error_message.eq(Some("Recovered after worker interruption")))
+                    .get_result(conn)?; // Touches updated_at (renews the lease)
+                reclaimed.push((schedule, run));
+            }
+            Ok(reclaimed)
+        })
+    }
+
+    /// Step 2: submits one run through the payment processor and records the outcome.
+    async fn submit_run(&self, conn: &mut PgConnection, schedule: &PaymentSchedule, run: &PaymentScheduleRun) -> Result<(), DomainError> {
+        // A previous attempt may have committed the payment before dying
+        if let Some(transaction) = run_transaction(conn, run.run_id)? {
+            return finish_run(conn, schedule, run, Ok(&transaction));
+        }
+
+        let outcome = self.submit_payment(conn, schedule, run).await;
+        match outcome {
+            Ok(transaction) => finish_run(conn, schedule, run, Ok(&transaction)),
+            // Infrastructure errors leave the run PENDING for a retry
+            Err(e @ (DomainError::DieselError(_) | DomainError::Database(_))) => Err(e),
+            Err(e) => finish_run(conn, schedule, run, Err(&e)),
+        }
+    }
+
+    async fn submit_payment(&self, conn: &mut PgConnection, schedule: &PaymentSchedule, run: &PaymentScheduleRun) -> Result<Transaction, DomainError> {
+        let payment_type = TransactionType::parse(&schedule.payment_type)
+            .ok_or_else(|| DomainError::Configuration(format!("Unknown payment type {}", schedule.payment_type)))?;
+        let details: ScheduledPaymentDetails = serde_json::from_value(schedule.payment_details.clone())
+            .map_err(|e| DomainError::Configuration(format!("Schedule {} has invalid payment details: {}", schedule.schedule_id, e)))?;
+        let idempotency_key = format!("schedule:{}:{}", schedule.schedule_id, run.occurrence_date);
+        let description = schedule.description.clone().unwrap_or_else(|| "Scheduled payment".to_string());
+
+        let mut processor = PaymentProcessor::new(conn, self.card_gateway);
+        if let Some(fee_schedule) = self.fee_schedule {
+            processor = processor.with_fee_schedule(fee_schedule);
+        }
+        if let Some(approval_policies) = self.approval_policies {
+            processor = processor.with_approval_policies(approval_policies);
+        }
+        if let Some(beneficiary_limits) = self.beneficiary_limits {
+            processor = processor.with_beneficiary_limits(beneficiary_limits);
+        }
+        if let Some(limit_policy) = self.limit_policy {
+            processor = processor.with_limit_policy(limit_policy);
+        }
+        if let Some(sanctions_screener) = self.sanctions_screener {
+            processor = processor.with_sanctions_screener(sanctions_screener);
+        }
+        let fraud_rules = self.fraud_rules.map(FraudRules::current);
+        if let Some(fraud_rules) = fraud_rules.as_deref() {
+            processor = processor.with_fraud_rules(fraud_rules);
+        }
+        processor.process_outbound_payment(PaymentRequest {
+            initiating_user_id: schedule.user_id,
+            amount: schedule.amount,
+            currency: &schedule.currency_code,
+            payment_type,
+            source_wallet_id: Some(schedule.source_wallet_id),
+            destination_wallet_id: None,
+            ach_details: details.ach_details.as_ref(),
+            wire_details: details.wire_details.as_ref(),
+            card_token: None,
+            check_details: None,
+            crypto_address: None,
+            description: &description,
+            metadata: Some(json!({
+                "schedule_id": schedule.schedule_id,
+                "occurrence_date": run.occurrence_date,
+            })),
+            idempotency_key: Some(&idempotency_key),
+            charge_bearer: ChargeBearer::parse(&schedule.charge_bearer).unwrap_or_default(),
+            device: None, // Not requested over the API
+            scheduled_run_id: Some(run.run_id),
+        }).await
+    }
+}
+
+/// Why a failed payment failed, from what the step that failed it recorded in its metadata.
+fn failure_reason(transaction: &Transaction) -> String {
+    let text = |pointer: &str| transaction.metadata.as_ref().and_then(|m| m.pointer(pointer)).and_then(|v| v.as_str());
+    // Never sent (outbox compensation, refused batch file) or rejected by the rail afterwards
+    if let Some(reason) = text("/compensation/reason").or_else(|| text("/webhook/reason")) {
+        return format!("Payment failed: {}", reason);
+    }
+    if text("/fraud/decision") == Some(FraudAction::Block.as_str()) {
+        return "Payment declined by fraud rules".to_string();
+    }
+    format!("Payment {} failed", transaction.transaction_id)
+}
+
+/// Records the outcome of a run on the run and its schedule.
+fn finish_run(
+    conn: &mut PgConnection,
+    schedule: &PaymentSchedule,
+    run: &PaymentScheduleRun,
+    outcome: Result<&Transaction, &DomainError>,
+) -> Result<(), DomainError> {
+    use crate::schema::payment_schedule_runs::dsl as r;
+    use crate::schema::payment_schedules::dsl as ps;
+    conn.transaction(|conn| {
+        let (status, transaction_id, error_message) = match outcome {
+            Ok(transaction) if transaction.status == TransactionStatus::Failed.to_string() => {
+                (ScheduleRunStatus::Failed, Some(transaction.transaction_id), Some(failure_reason(transaction)))
+            }
+            Ok(transaction) => (ScheduleRunStatus::Submitted, Some(transaction.transaction_id), None),
+            Err(e) => (ScheduleRunStatus::Failed, None, Some(e.to_string())),
+        };
+        let updated = diesel::update(
+            r::payment_schedule_runs
+                .find(run.run_id)
+                .filter(r::status.eq(ScheduleRunStatus::Pending.as_str())),
+        )
+        .set((
+            r::status.eq(status.as_str()),
+            r::transaction_id.eq(transaction_id),
+            r::error_message.eq(error_message.as_deref()),
+        ))
+        .execute(conn)?;
+        if updated == 0 {
+            return Ok(()); // Another worker recorded it first
+        }
+        diesel::update(ps::payment_schedules.find(schedule.schedule_id))
+            .set(ps::last_error.eq(error_message.as_deref()))
+            .execute(conn)?;
+
+        audit::log_db_audit_event(
+            conn,
+            Some(schedule.user_id),
+            ACTOR,
+            "RUN_PAYMENT_SCHEDULE",
+            Some(AuditTargetType::PaymentSchedule),
+            Some(&schedule.schedule_id.to_string()),
+            if error_message.is_none() { AuditOutcome::Success } else { AuditOutcome::Failure },
+            Some(json!({"run_id": run.run_id, "occurrence_date": run.occurrence_date, "run_date": run.run_date, "transaction_id": transaction_id})),
+            error_message.as_deref(),
+        )?;
+        match &error_message {
+            None => log::info!("Schedule {} occurrence {} submitted as Tx {:?}", schedule.schedule_id, run.occurrence_date, transaction_id),
+            Some(msg) => log::warn!("Schedule {} occurrence {} failed: {}", schedule.schedule_id, run.occurrence_date, msg),
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_abandoned_runs_of_inactive_schedules_are_voided() {
+        assert_eq!(abandoned_run_action(ScheduleStatus::Active.as_str(), false), AbandonedRun::Resume);
+        assert_eq!(abandoned_run_action(ScheduleStatus::Paused.as_str(), false), AbandonedRun::Void);
+        assert_eq!(abandoned_run_action(ScheduleStatus::Cancelled.as_str(), false), AbandonedRun::Void);
+        // Completed by claiming its last occurrence, which was never paid
+        assert_eq!(abandoned_run_action(ScheduleStatus::Completed.as_str(), false), AbandonedRun::Resume);
+        // The payment went out before the worker died: the run is recorded, whatever the schedule became
+        assert_eq!(abandoned_run_action(ScheduleStatus::Cancelled.as_str(), true), AbandonedRun::Resume);
+    }
+
+    fn failed(metadata: serde_json::Value) -> Transaction {
+        let now = Utc::now();
+        Transaction {
+            transaction_id: Uuid::new_v4(),
+            debit_wallet_id: Some(Uuid::new_v4()),
+            credit_wallet_id: None,
+            transaction_type: TransactionType::AchCredit.to_string(),
+            status: TransactionStatus::Failed.to_string(),
+            amount: rust_decimal::Decimal::new(10_000, 2),
+            currency_code: "USD".to_string(),
+            description: None,
+            external_ref_id: None,
+            metadata: Some(metadata),
+            created_at: now,
+            updated_at: now,
+            settlement_at: None,
+            scheduled_run_id: Some(Uuid::new_v4()),
+        }
+    }
+
+    #[test]
+    fn test_failed_run_records_why_the_payment_failed() {
+        let blocked = failed(json!({"fraud": {"decision": "BLOCK", "status": null}}));
+        assert_eq!(failure_reason(&blocked), "Payment declined by fraud rules");
+
+        let compensated = failed(json!({"fraud": {"evaluation_id": Uuid::new_v4()},
+            "compensation": {"reason": "ODFI rejected the file"}}));
+        assert_eq!(failure_reason(&compensated), "Payment failed: ODFI rejected the file");
+
+        let rejected = failed(json!({"webhook": {"provider": "partner_bank", "reason": "Closed account"}}));
+        assert_eq!(failure_reason(&rejected), "Payment failed: Closed account");
+
+        let unexplained = failed(json!({"schedule_id": Uuid::new_v4()}));
+        assert_eq!(failure_reason(&unexplained), format!("Payment {} failed", unexplained.transaction_id));
+    }
+}
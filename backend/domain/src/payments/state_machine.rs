@@ -0,0 +1,298 @@
+// /home/inno/elights_jobes-research/backend/domain/src/payments/state_machine.rs
+// Explicit transaction status transitions per transaction type, and the financial effect of each.
+//
+// Effects (applied to holds and the journal by `ledger::postings`):
+//   HOLD    - reserve funds on the debit wallet (lowers the available balance only)
+//   POST    - final posting: credit the credit wallet (and debit the debit wallet if nothing was held)
+//   RELEASE - return held funds to the debit wallet
+//   REVERSE - undo a posting (returns, chargebacks)
+// Every applied effect is recorded in `transaction_state_transitions`; a unique index on
+// (transaction_id, effect) guarantees each effect hits the ledger at most once.
+use crate::error::DomainError;
+use crate::ledger::post_transaction_effect;
+use crate::models::{NewTransactionStateTransition, Transaction, TransactionStatus, TransactionType};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde_json::Value as JsonValue;
+
+/// Balance impact of a status transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinancialEffect {
+    None,
+    Hold,
+    Post,
+    Release,
+    Reverse,
+}
+
+impl FinancialEffect {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FinancialEffect::None => "NONE",
+            FinancialEffect::Hold => "HOLD",
+            FinancialEffect::Post => "POST",
+            FinancialEffect::Release => "RELEASE",
+            FinancialEffect::Reverse => "REVERSE",
+        }
+    }
+}
+
+/// Groups of transaction types sharing one lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flow {
+    Outbound, // Funds leave an internal wallet to an external rail
+    Inbound,  // Funds arrive from outside into an internal wallet
+    Card,     // Authorize, then capture/settle; chargebacks possible
+    Internal, // Book transfer between internal wallets
+    Passive,  // Informational records, no balance impact
+}
+
+fn flow_for(transaction_type: &TransactionType) -> Flow {
+    use TransactionType::*;
+    match transaction_type {
+        AchCredit | WireOutbound | CryptoBtcSend | CryptoXmrSend | CheckWithdrawal | RtgsCreditTransfer | CardRefund => Flow::Outbound,
+        AchDebit | AchInbound | WireInbound | CheckDeposit | CryptoBtcReceive | CryptoXmrReceive | RtgsDirectDebit | RtgsReturn => Flow::Inbound,
+        CardAuthorization | CardCapture => Flow::Card,
+        InternalTransfer | Conversion | Fee | InterestCredit | WithholdingTax => Flow::Internal,
+        CardChargeback | RtgsStatusUpdate | Unknown => Flow::Passive,
+    }
+}
+
+/// Returns the effect of moving `from` -> `to`, or `None` if the move is not allowed.
+/// Staying in the same status is always allowed and has no effect (repeated webhooks).
+pub fn transition_effect(
+    transaction_type: &TransactionType,
+    from: &TransactionStatus,
+    to: &TransactionStatus,
+) -> Option<FinancialEffect> {
+    use FinancialEffect as E;
+    use TransactionStatus::*;
+
+    if from == to {
+        return Some(E::None);
+    }
+
+    let effect = match (flow_for(transaction_type), from, to) {
+        // --- Outbound: debit is held when processing starts, posted on settlement ---
+        (Flow::Outbound, Pending, Processing) => E::Hold,
+        (Flow::Outbound, Pending, RequiresAction | Cancelled | Failed) => E::None,
+        (Flow::Outbound, RequiresAction, Pending | Cancelled | Failed) => E::None,
+        (Flow::Outbound, RequiresAction, Processing) => E::Hold,
+        (Flow::Outbound, RequiresAction, Expired) => E::None, // Approval window lapsed
+        (Flow::Outbound, Processing, Submitted) => E::None,
+        (Flow::Outbound, Processing, Failed | Cancelled) => E::Release,
+        (Flow::Outbound, Submitted, Settled | Completed) => E::Post,
+        (Flow::Outbound, Submitted, Failed | Returned) => E::Release,
+        (Flow::Outbound, Settled, Completed) => E::None,
+        (Flow::Outbound, Settled | Completed, Returned) => E::Reverse,
+
+        // --- Inbound: nothing to hold, credit posted once funds are final ---
+        (Flow::Inbound, Pending, Processing | Submitted | RequiresAction) => E::None,
+        (Flow::Inbound, Pending, Failed | Cancelled | Expired) => E::None,
+        (Flow::Inbound, RequiresAction, Pending | Processing | Failed | Cancelled) => E::None,
+        (Flow::Inbound, RequiresAction, Returned) => E::None, // Unapplied funds sent back out of suspense
+        (Flow::Inbound, Processing, Submitted | Failed) => E::None,
+        (Flow::Inbound, Pending | Processing | Submitted, Settled | Completed) => E::Post,
+        (Flow::Inbound, Submitted, Failed | Returned) => E::None,
+        (Flow::Inbound, Settled, Completed) => E::None,
+        (Flow::Inbound, Settled | Completed, Returned) => E::Reverse,
+
+        // --- Card: authorization holds, capture posts, chargeback only after funds moved ---
+        (Flow::Card, Pending, Authorized) => E::Hold,
+        (Flow::Card, Pending, RequiresAction | Failed | Cancelled) => E::None,
+        (Flow::Card, RequiresAction, Authorized) => E::Hold,
+        (Flow::Card, RequiresAction, Failed | Cancelled) => E::None,
+        (Flow::Card, Pending, Completed) => E::Post, // Sale (auth + capture in one step)
+        (Flow::Card, Authorized, Completed | Settled) => E::Post,
+        (Flow::Card, Authorized, Capturing) => E::None, // Capture claimed before the gateway is called
+        (Flow::Card, Capturing, Authorized) => E::None, // Gateway refused the capture
+        (Flow::Card, Capturing, Completed | Settled) => E::Post,
+        (Flow::Card, Authorized, Cancelled | Expired | Failed) => E::Release,
+        (Flow::Card, Completed, Settled) => E::None,
+        (Flow::Card, Completed | Settled, Chargeback) => E::Reverse,
+
+        // --- Internal book transfers post in one step ---
+        (Flow::Internal, Pending, Completed) => E::Post,
+        (Flow::Internal, Pending, Processing) => E::None,
+        (Flow::Internal, Processing, Completed) => E::Post,
+        (Flow::Internal, Pending | Processing, Failed | Cancelled) => E::None,
+        (Flow::Internal, Completed, Returned) => E::Reverse,
+
+        // --- Informational records ---
+        (Flow::Passive, Pending, Processing | Completed | Failed | Cancelled) => E::None,
+        (Flow::Passive, Processing, Completed | Failed) => E::None,
+
+        _ => return None,
+    };
+    Some(effect)
+}
+
+/// Validates a transition, returning its effect or a typed `InvalidStateTransition` error.
+pub fn validate_transition(
+    transaction_type: &TransactionType,
+    from: &TransactionStatus,
+    to: &TransactionStatus,
+) -> Result<FinancialEffect, DomainError> {
+    transition_effect(transaction_type, from, to).ok_or_else(|| DomainError::InvalidStateTransition {
+        transaction_type: format!("{:?}", transaction_type),
+        from: from.to_string(),
+        to: to.to_string(),
+    })
+}
+
+/// Optional field changes that accompany a status transition.
+#[derive(Debug, Default, Clone)]
+pub struct TransitionUpdate<'a> {
+    pub external_ref: Option<&'a str>,
+    pub settlement_at: Option<DateTime<Utc>>,
+    pub metadata: Option<JsonValue>, // Merged into existing metadata (object keys overwrite)
+}
+
+fn parse_current(transaction: &Transaction) -> Result<(TransactionType, TransactionStatus), DomainError> {
+    let tx_type = TransactionType::parse(&transaction.transaction_type).ok_or_else(|| {
+        DomainError::Internal(format!("Unknown transaction type '{}' on {}", transaction.transaction_type, transaction.transaction_id))
+    })?;
+    let status = TransactionStatus::parse(&transaction.status).ok_or_else(|| {
+        DomainError::Internal(format!("Unknown status '{}' on {}", transaction.status, transaction.transaction_id))
+    })?;
+    Ok((tx_type, status))
+}
+
+fn merge_metadata(existing: Option<JsonValue>, update: JsonValue) -> JsonValue {
+    match (existing, update) {
+        (Some(JsonValue::Object(mut base)), JsonValue::Object(patch)) => {
+            base.extend(patch);
+            JsonValue::Object(base)
+        }
+        (_, update) => update,
+    }
+}
+
+fn record_transition(
+    conn: &mut PgConnection,
+    transaction: &Transaction,
+    from: Option<&str>,
+    to: &str,
+    effect: FinancialEffect,
+    actor: &str,
+) -> Result<(), DomainError> {
+    let row = NewTransactionStateTransition {
+        transaction_id: transaction.transaction_id,
+        from_status: from,
+        to_status: to,
+        financial_effect: effect.as_str(),
+        actor,
+    };
+    diesel::insert_into(crate::schema::transaction_state_transitions::table)
+        .values(&row)
+        .execute(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::DatabaseError(diesel::result::DatabaseErrorKind::UniqueViolation, _) => {
+                DomainError::InvalidStateTransition {
+                    transaction_type: transaction.transaction_type.clone(),
+                    from: from.unwrap_or("-").to_string(),
+                    to: format!("{} ({} already applied)", to, effect.as_str()),
+                }
+            }
+            other => DomainError::DieselError(other),
+        })?;
+    Ok(())
+}
+
+/// Moves `transaction` to `to`, applying the transition's financial effect exactly once.
+/// The caller must hold a row lock on the transaction (`SELECT ... FOR UPDATE`) and run inside a DB transaction.
+pub fn apply_transition(
+    conn: &mut PgConnection,
+    transaction: &Transaction,
+    to: TransactionStatus,
+    update: TransitionUpdate<'_>,
+    actor: &str,
+) -> Result<Transaction, DomainError> {
+    use crate::schema::transactions::dsl as t;
+
+    let (tx_type, from) = parse_current(transaction)?;
+    let effect = validate_transition(&tx_type, &from, &to)?;
+
+    if from == to && update.external_ref.is_none() && update.settlement_at.is_none() && update.metadata.is_none() {
+        log::debug!("Tx {} already {:?}, nothing to do", transaction.transaction_id, to);
+        return Ok(transaction.clone());
+    }
+
+    if effect != FinancialEffect::None {
+        // Record first: the unique index rejects a second application before balances are touched
+        record_transition(conn, transaction, Some(&transaction.status), to.as_str(), effect, actor)?;
+        post_transaction_effect(conn, transaction, effect)?;
+    } else if from != to {
+        record_transition(conn, transaction, Some(&transaction.status), to.as_str(), effect, actor)?;
+    }
+
+    let metadata = update.metadata.map(|patch| merge_metadata(transaction.metadata.clone(), patch));
+    let updated: Transaction = diesel::update(t::transactions.find(transaction.transaction_id))
+        .set((
+            t::status.eq(to.as_str()),
+            t::external_ref_id.eq(update.external_ref.map(str::to_string).or_else(|| transaction.external_ref_id.clone())),
+            t::settlement_at.eq(update.settlement_at.or(transaction.settlement_at)),
+            t::metadata.eq(metadata.or_else(|| transaction.metadata.clone())),
+        ))
+        .get_result(conn)?;
+
+    // Money that never left gives its limit usage back
+    if from != to && matches!(to, TransactionStatus::Failed | TransactionStatus::Cancelled | TransactionStatus::Expired) {
+        crate::limits::release_usage(conn, transaction.transaction_id, chrono::Utc::now())?;
+    }
+
+    log::info!("Tx {} {:?} -> {:?} (effect {})", transaction.transaction_id, from, to, effect.as_str());
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TransactionStatus::*;
+
+    #[test]
+    fn test_completed_cannot_go_back_to_pending() {
+        for tx_type in [TransactionType::AchCredit, TransactionType::WireInbound, TransactionType::CardAuthorization, TransactionType::InternalTransfer] {
+            assert!(transition_effect(&tx_type, &Completed, &Pending).is_none(), "{:?}", tx_type);
+        }
+        let err = validate_transition(&TransactionType::WireOutbound, &Completed, &Pending).unwrap_err();
+        assert!(matches!(err, DomainError::InvalidStateTransition { .. }));
+    }
+
+    #[test]
+    fn test_card_chargeback_only_after_funds_moved() {
+        let card = TransactionType::CardAuthorization;
+        assert_eq!(transition_effect(&card, &Completed, &Chargeback), Some(FinancialEffect::Reverse));
+        assert_eq!(transition_effect(&card, &Settled, &Chargeback), Some(FinancialEffect::Reverse));
+        assert!(transition_effect(&card, &Authorized, &Chargeback).is_none());
+        assert!(transition_effect(&card, &Pending, &Chargeback).is_none());
+        assert!(transition_effect(&card, &Capturing, &Chargeback).is_none());
+        // Chargeback is not a status for non-card flows
+        assert!(transition_effect(&TransactionType::AchCredit, &Completed, &Chargeback).is_none());
+    }
+
+    #[test]
+    fn test_stored_status_round_trips() {
+        for status in [Pending, Processing, RequiresAction, Authorized, Capturing, Submitted, Settled, Completed, Failed, Cancelled, Returned, Chargeback, Expired] {
+            assert_eq!(TransactionStatus::parse(&status.to_string()), Some(status.clone()));
+            assert_eq!(status.as_str(), format!("{:?}", status)); // Rows written before as_str() stay readable
+        }
+    }
+
+    #[test]
+    fn test_effects_follow_lifecycle() {
+        let ach = TransactionType::AchCredit;
+        assert_eq!(transition_effect(&ach, &Pending, &Processing), Some(FinancialEffect::Hold));
+        assert_eq!(transition_effect(&ach, &Processing, &Failed), Some(FinancialEffect::Release));
+        assert_eq!(transition_effect(&ach, &Submitted, &Completed), Some(FinancialEffect::Post));
+        assert_eq!(transition_effect(&ach, &Completed, &Returned), Some(FinancialEffect::Reverse));
+        assert_eq!(transition_effect(&ach, &Completed, &Completed), Some(FinancialEffect::None));
+        assert!(transition_effect(&ach, &Failed, &Completed).is_none());
+
+        // A capture is claimed first, then posts; a claimed authorization cannot be voided under it
+        let card = TransactionType::CardAuthorization;
+        assert_eq!(transition_effect(&card, &Authorized, &Capturing), Some(FinancialEffect::None));
+        assert_eq!(transition_effect(&card, &Capturing, &Completed), Some(FinancialEffect::Post));
+        assert!(transition_effect(&card, &Capturing, &Cancelled).is_none());
+    }
+}
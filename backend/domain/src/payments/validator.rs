@@ -136,7 +136,7 @@ pub fn validate_check_details(details: &CheckDetails, _context: &ValidationConte
 
 
 /// Validates a SWIFT/BIC code (length and structure).
-fn validate_swift_bic(swift_bic: &str) -> Result<(), DomainError> {
+pub fn validate_swift_bic(swift_bic: &str) -> Result<(), DomainError> {
     let len = swift_bic.len();
     if !(len == 8 || len == 11) {
         return Err(DomainError::Validation(format!("Invalid SWIFT/BIC length: {}", len)));
@@ -175,6 +175,7 @@ fn luhn_check(number: &str) -> bool {
 
 /// Validates liquidity or performs checks against payment system rules (T2, CHIPS, Fedwire)
 /// Placeholder function - real implementation is highly complex.
+/// Customer transaction limits are not checked here: they need usage counters, see `crate::limits`.
 pub fn validate_payment_rules(
     payment_type: &TransactionType,
     currency: &str,
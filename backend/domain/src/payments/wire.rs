@@ -9,6 +9,9 @@ use crate::payments::validator::{validate_wire_details, ValidationContext};
 use crate::payments::iso20022; // Use ISO 20022 module
 use crate::payments::swift_mt; // Use SWIFT MT module
 use crate::payments::rtgs; // Use RTGS module
+use crate::payments::inbound::{self, InboundNotice, InboundOutcome, InboundRail}; // Shared inbound processing
+use crate::beneficiaries::identity::is_valid_iban;
+use crate::sanctions::{self, SanctionsScreener};
 use rust_decimal::Decimal;
 use uuid::Uuid;
 use serde_json::json;
@@ -50,6 +53,7 @@ pub async fn process_wire_transfer_outbound(
         description: Some(description),
         external_ref_id: None, // UETR or Bank Ref later
         metadata: Some(json!({"destination_details": destination_details, "requested_format": if use_iso20022 {"ISO20022"} else {"SWIFT_MT"}})), // Store destination/format
+        scheduled_run_id: None,
     };
     let mut transaction: Transaction = diesel::insert_into(crate::schema::transactions::table)
         .values(&new_tx)
@@ -122,52 +126,82 @@ pub async fn process_wire_transfer_outbound(
     Ok(transaction)
 }
 
+/// Builds and submits the payment message for an already-debited outbound wire transaction.
+/// Used by the outbox worker; returns the UETR used as external reference. With a screener, the parties
+/// of the built message are screened before anything is sent.
+pub async fn submit_outbound_wire(
+    transaction: &Transaction,
+    destination_details: &WireDetails,
+    use_iso20022: bool,
+    screener: Option<&SanctionsScreener>,
+) -> Result<String, DomainError> {
+    let context = ValidationContext { currency: &transaction.currency_code };
+    validate_wire_details(destination_details, &context)?;
+
+    // Reuse the UETR from a previous attempt so a retried submission is recognisable downstream
+    let uetr = destination_details.uetr.clone().unwrap_or_else(|| transaction.transaction_id.to_string());
+    let payment_message = if use_iso20022 {
+        // TODO: Populate pacs.008 details from transaction/destination details
+        let pacs008_details = iso20022::Pacs008Details { /* ... populate ... */ };
+        if let Some(screener) = screener {
+            sanctions::ensure_message_clear(screener, &sanctions::parties_from_pacs008(&pacs008_details))?;
+        }
+        iso20022::build_pacs_008(&pacs008_details, &uetr)?
+    } else {
+        // TODO: Populate MT103 details from transaction/destination details
+        let mt103_details = swift_mt::Mt103Details { /* ... populate ... */ };
+        if let Some(screener) = screener {
+            sanctions::ensure_message_clear(screener, &sanctions::parties_from_mt103(&mt103_details))?;
+        }
+        swift_mt::format_mt103(&mt103_details, &uetr)?
+    };
+
+    let bank_ref = submit_wire_message(&payment_message, use_iso20022).await
+        .map_err(DomainError::WireTransfer)?;
+    log::info!("Wire message for Tx {} submitted. Bank Ref: {}", transaction.transaction_id, bank_ref.as_deref().unwrap_or("-"));
+
+    if rtgs::is_rtgs_destination(&destination_details.swift_bic) { // Placeholder check
+        rtgs::initiate_rtgs_payment(transaction.transaction_id, &payment_message).await?;
+    }
+    Ok(uetr)
+}
+
 /// Processes an incoming Wire transfer notification (e.g., from MT103/pacs.008 received via bank).
+/// Matching, dedupe (by UETR) and crediting are shared with the other rails in `inbound`.
 pub async fn process_wire_transfer_inbound(
     conn: &mut PgConnection,
     parsed_message_details: &WireMessageDetails, // Details parsed from MT103/pacs.008/pacs.009
-) -> Result<Transaction, DomainError> {
+) -> Result<InboundOutcome, DomainError> {
     log::info!("Processing Inbound Wire Transfer. Ref: {}", parsed_message_details.uetr.as_deref().unwrap_or("N/A"));
+    let notice = inbound_notice(parsed_message_details)?;
+    inbound::process_inbound(conn, &notice)
+}
 
-    // 1. Identify destination internal wallet based on beneficiary details in message
-    // TODO: Implement logic to match IBAN/Account Number hash from message to internal wallets.
-    let destination_wallet_id = find_wallet_by_iban_hash(&parsed_message_details.beneficiary_account_iban_hash)
-        .ok_or_else(|| DomainError::NotFound("Destination wallet not found for inbound wire".to_string()))?;
-
-    // 2. Check if transaction already exists (using UETR or other unique refs)
-     let existing_tx = crate::schema::transactions::table
-         .filter(crate::schema::transactions::external_ref_id.eq(&parsed_message_details.uetr))
-         .first::<Transaction>(conn)
-         .optional()?;
-
-     if existing_tx.is_some() {
-          log::warn!("Duplicate inbound wire message received or already processed: {:?}", parsed_message_details.uetr);
-          return Ok(existing_tx.unwrap()); // Idempotency: return existing transaction
-     }
-
-    // 3. Create transaction record
-    let new_tx = NewTransaction {
-         transaction_id: None,
-         debit_wallet_id: None, // External sender
-         credit_wallet_id: Some(destination_wallet_id),
-         transaction_type: TransactionType::WireInbound.to_string().as_str(),
-         status: TransactionStatus::Completed.to_string().as_str(), // Assume funds received if message parsed
-         amount: parsed_message_details.amount,
-         currency_code: &parsed_message_details.currency,
-         description: Some("Inbound Wire Transfer"), // Or use remittance info
-         external_ref_id: parsed_message_details.uetr.as_deref(),
-         metadata: Some(json!(parsed_message_details)), // Store parsed details
+/// Normalized inbound notice for a parsed wire message. Field 59 carries either an IBAN or a plain account number.
+pub fn inbound_notice(details: &WireMessageDetails) -> Result<InboundNotice, DomainError> {
+    let uetr = details.uetr.as_deref().map(str::trim).filter(|u| !u.is_empty())
+        .ok_or_else(|| DomainError::Validation("Inbound wire without UETR cannot be deduplicated".to_string()))?;
+    let account = details.beneficiary_account.trim();
+    let (iban, account_number) = if is_valid_iban(account) {
+        (Some(account.to_string()), None)
+    } else {
+        (None, Some(account.to_string()).filter(|a| !a.is_empty()))
     };
-    let transaction: Transaction = diesel::insert_into(crate::schema::transactions::table)
-        .values(&new_tx)
-        .get_result(conn)?;
-
-    // 4. Credit destination wallet (DB Transaction - potentially combine with insert)
-    // TODO: Implement atomic credit balance logic.
-    // update_wallet_balance(conn, destination_wallet_id, parsed_message_details.amount)?;
-
-    log::info!("Inbound wire {} credited to wallet {}", transaction.transaction_id, destination_wallet_id);
-    Ok(transaction)
+    Ok(InboundNotice {
+        rail: InboundRail::Wire,
+        external_reference: uetr.to_string(),
+        amount: details.amount,
+        currency: details.currency.clone(),
+        account_number,
+        iban,
+        crypto_address: None,
+        originator_name: details.sender_name.clone(),
+        originator_account: details.sender_account.clone(),
+        originator_bank: details.sender_bic.clone(),
+        remittance_info: details.remittance_info.clone(),
+        value_date: details.value_date,
+        confirmed: true, // A received MT103/pacs.008 means the funds are on our nostro
+    })
 }
 
 // --- Helper Structs/Functions ---
@@ -181,7 +215,7 @@ pub struct WireMessageDetails {
     pub sender_account: Option<String>,
     pub receiver_bic: Option<String>,
     pub beneficiary_name: String,
-    pub beneficiary_account_iban_hash: String, // Hash of the account/IBAN
+    pub beneficiary_account: String, // IBAN or account number as sent (field 59); never stored unmasked
     pub amount: Decimal,
     pub currency: String,
     pub value_date: Option<chrono::NaiveDate>,
@@ -195,9 +229,3 @@ async fn submit_wire_message(_message: &str, _is_iso20022: bool) -> Result<Optio
     tokio::time::sleep(tokio::time::Duration::from_millis(150)).await; // Simulate network delay
     Ok(Some(format!("BANK_ACK_{}", rand::random::<u32>()))) // Simulate bank acknowledgement ref
 }
-
-// Placeholder function to find wallet by hashed IBAN/Account#
-fn find_wallet_by_iban_hash(_hash: &str) -> Option<Uuid> {
-    // TODO: Implement DB query
-    Some(Uuid::new_v4()) // Dummy
-}
\ No newline at end of file
@@ -0,0 +1,149 @@
+// /home/inno/elights_jobes-research/backend/domain/src/reconciliation/accounts.rs
+// Nostro accounts to reconcile, loaded from a JSON file: which bank client serves each account,
+// its currency, how loosely statement entries may be matched against our transactions and the minimum
+// balance treasury keeps on it.
+use crate::config::{self, JsonConfig};
+use crate::error::DomainError;
+use crate::fees::FeeRail;
+use crate::models::{Transaction, TransactionType};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+fn default_date_window_days() -> i64 {
+    3
+}
+
+fn default_missing_after_days() -> i64 {
+    5
+}
+
+/// Whether a transaction of `transaction_type` without a wallet on one side moves money at a bank.
+/// Fees, conversion legs and interest have one of our own ledger accounts on the other side instead.
+pub fn touches_nostro(transaction_type: &TransactionType) -> bool {
+    !matches!(
+        transaction_type,
+        TransactionType::Fee | TransactionType::Conversion | TransactionType::InterestCredit | TransactionType::WithholdingTax
+    )
+}
+
+/// One of our accounts at a bank.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NostroAccount {
+    pub code: String, // Our name for it, e.g. "CHASE-USD-OPS"
+    pub bank: String, // Which bank client pulls its statement (e.g. "chase", "deutsche_bank")
+    pub account_id: String, // The bank's identifier for the account
+    pub currency: String,
+    #[serde(default)]
+    pub rails: Vec<FeeRail>, // Rails settling through this account; empty = any rail in the currency
+    #[serde(default = "default_date_window_days")]
+    pub date_window_days: i64, // Max days between our transaction and its booking, for amount/date matches
+    #[serde(default = "default_missing_after_days")]
+    pub missing_after_days: i64, // A transaction not on the statement after this long becomes a break
+    #[serde(default)]
+    pub minimum_balance: Option<Decimal>, // Treasury floor; projected positions below it are shortfalls
+}
+
+impl NostroAccount {
+    /// Whether `transaction` moves money through this account (it has an external leg in our currency).
+    pub fn carries(&self, transaction: &Transaction) -> bool {
+        let tx_type = TransactionType::parse(&transaction.transaction_type).unwrap_or(TransactionType::Unknown);
+        if !touches_nostro(&tx_type) {
+            return false;
+        }
+        let external_leg = transaction.debit_wallet_id.is_none() || transaction.credit_wallet_id.is_none();
+        external_leg && self.carries_rail(&transaction.currency_code, FeeRail::for_transaction_type(&tx_type))
+    }
+
+    /// Whether payments of `currency` on `rail` settle through this account.
+    pub fn carries_rail(&self, currency: &str, rail: Option<FeeRail>) -> bool {
+        currency.eq_ignore_ascii_case(&self.currency)
+            && (self.rails.is_empty() || rail.is_some_and(|rail| self.rails.contains(&rail)))
+    }
+}
+
+/// All nostro accounts under reconciliation. An empty set reconciles nothing.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct NostroAccountSet {
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub accounts: Vec<NostroAccount>,
+}
+
+impl NostroAccountSet {
+    pub fn get(&self, code: &str) -> Option<&NostroAccount> {
+        self.accounts.iter().find(|account| account.code == code)
+    }
+
+    /// The account a payment of `currency` on `rail` is expected to go through: the first that carries it.
+    pub fn carrier_for(&self, currency: &str, rail: Option<FeeRail>) -> Option<&NostroAccount> {
+        self.accounts.iter().find(|account| account.carries_rail(currency, rail))
+    }
+}
+
+impl JsonConfig for NostroAccountSet {
+    const NAME: &'static str = "nostro accounts";
+
+    /// Rejects duplicate codes and windows that would match nothing or never raise a break.
+    fn validate(&self) -> Result<(), DomainError> {
+        let mut codes = HashSet::new();
+        for account in &self.accounts {
+            let invalid = |reason: &str| DomainError::Configuration(format!("Nostro account '{}': {}", account.code, reason));
+            if account.code.trim().is_empty() {
+                return Err(DomainError::Configuration("Nostro account with an empty code".to_string()));
+            }
+            if !codes.insert(account.code.as_str()) {
+                return Err(invalid("duplicate code"));
+            }
+            if account.bank.trim().is_empty() || account.account_id.trim().is_empty() {
+                return Err(invalid("bank and account_id are required"));
+            }
+            if account.currency.len() != 3 {
+                return Err(invalid("currency must be an ISO 4217 code"));
+            }
+            if account.date_window_days < 0 {
+                return Err(invalid("date_window_days cannot be negative"));
+            }
+            if account.missing_after_days < account.date_window_days {
+                return Err(invalid("missing_after_days must be at least date_window_days"));
+            }
+            if account.minimum_balance.is_some_and(|minimum| minimum < Decimal::ZERO) {
+                return Err(invalid("minimum_balance cannot be negative"));
+            }
+        }
+        Ok(())
+    }
+
+    fn summary(&self) -> String {
+        format!("({} accounts)", self.accounts.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_and_validate() {
+        let set = config::parse_json::<NostroAccountSet>(r#"{
+            "accounts": [
+                { "code": "CHASE-USD", "bank": "chase", "account_id": "000123", "currency": "USD", "rails": ["WIRE", "ACH"] },
+                { "code": "DB-EUR", "bank": "deutsche_bank", "account_id": "DE89370400440532013000", "currency": "EUR",
+                  "date_window_days": 1, "missing_after_days": 2 }
+            ]
+        }"#).unwrap();
+        let chase = set.get("CHASE-USD").unwrap();
+        assert_eq!(chase.date_window_days, 3);
+        assert_eq!(chase.missing_after_days, 5);
+        assert_eq!(set.get("DB-EUR").unwrap().date_window_days, 1);
+
+        let duplicate = r#"{ "accounts": [
+            { "code": "A", "bank": "chase", "account_id": "1", "currency": "USD" },
+            { "code": "A", "bank": "chase", "account_id": "2", "currency": "USD" } ] }"#;
+        assert!(config::parse_json::<NostroAccountSet>(duplicate).is_err());
+        let window = r#"{ "accounts": [
+            { "code": "A", "bank": "chase", "account_id": "1", "currency": "USD", "date_window_days": 5, "missing_after_days": 2 } ] }"#;
+        assert!(config::parse_json::<NostroAccountSet>(window).is_err());
+    }
+}
@@ -0,0 +1,596 @@
+// /home/inno/elights_jobes-research/backend/domain/src/reconciliation/engine.rs
+// Reconciliation runs per nostro account: stored statement lines that are not matched yet are paired with
+// transactions that moved money through the account (see `matcher`). A clean match settles its
+// transactions; a match with an amount difference, a line nobody claims within the date window and a
+// transaction missing from the statement for too long each become an exception (a break) for operations.
+use super::accounts::{NostroAccount, NostroAccountSet};
+use super::matcher::{self, LineCandidate, ProposedMatch, TransactionCandidate};
+use super::statement::{self, ImportSummary, StatementSource};
+use crate::error::DomainError;
+use crate::models::{
+    AuditOutcome, AuditTargetType, BankStatementLine, ExceptionResolution, ExceptionStatus, ExceptionType, MatchRule,
+    NewReconciliationException, NewReconciliationMatch, ReconciliationException, ReconciliationMatch,
+    ReconciliationMatchTransaction, StatementLineStatus, Transaction, TransactionStatus, TransactionType,
+};
+use crate::payments::payout_batch::PAYOUT_BATCH_KEY;
+use crate::payments::state_machine::{self, TransitionUpdate};
+use crate::security::audit;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::{json, Value as JsonValue};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+const ACTOR: &str = "reconciliation";
+/// How far back unreconciled transactions are considered.
+const CANDIDATE_LOOKBACK_DAYS: i64 = 60;
+/// Extra days pulled before the break horizon, for entries the bank books late.
+const STATEMENT_OVERLAP_DAYS: i64 = 2;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MatchOutcome {
+    pub matched: ReconciliationMatch,
+    pub settled: Vec<Uuid>, // Transactions moved to Settled by this match
+    pub exception: Option<ReconciliationException>, // Amount difference raised as a break
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ReconciliationRun {
+    pub nostro_account: String,
+    pub import: Option<ImportSummary>,
+    pub lines_considered: usize,
+    pub transactions_considered: usize,
+    pub matches: Vec<MatchOutcome>,
+    pub exceptions_opened: Vec<ReconciliationException>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ExceptionOutcome {
+    pub exception: ReconciliationException,
+    pub settled: Vec<Uuid>,
+}
+
+/// Money into the nostro account is positive: inbound payments have no debit wallet.
+pub fn signed_amount(transaction: &Transaction) -> Decimal {
+    if transaction.debit_wallet_id.is_none() { transaction.amount } else { -transaction.amount }
+}
+
+fn transaction_uetr(transaction: &Transaction) -> Option<String> {
+    transaction.metadata.as_ref()
+        .and_then(|metadata| metadata.pointer("/destination_details/uetr"))
+        .and_then(JsonValue::as_str)
+        .map(str::to_string)
+        // Wires carry the UETR as their rail reference
+        .or_else(|| transaction.external_ref_id.clone().filter(|reference| Uuid::parse_str(reference).is_ok()))
+}
+
+fn line_candidate(line: &BankStatementLine) -> LineCandidate {
+    LineCandidate {
+        id: line.line_id,
+        booked_on: line.value_date.unwrap_or_else(|| line.booked_at.date_naive()),
+        amount: line.amount,
+        uetr: line.uetr.clone(),
+        text: line.reference.iter().cloned().chain(std::iter::once(line.description.clone())).collect(),
+    }
+}
+
+fn transaction_candidate(transaction: &Transaction, batch_references: &HashMap<String, Vec<String>>) -> TransactionCandidate {
+    let mut references = vec![transaction.transaction_id.to_string()];
+    references.extend(transaction.external_ref_id.clone());
+    let batch_id = transaction.metadata.as_ref()
+        .and_then(|metadata| metadata.get(PAYOUT_BATCH_KEY))
+        .and_then(JsonValue::as_str);
+    if let Some(batch_id) = batch_id {
+        references.push(batch_id.to_string());
+        references.extend(batch_references.get(batch_id).cloned().unwrap_or_default());
+    }
+    TransactionCandidate {
+        id: transaction.transaction_id,
+        date: transaction.created_at.date_naive(),
+        amount: signed_amount(transaction),
+        uetr: transaction_uetr(transaction),
+        references,
+    }
+}
+
+/// NACHA file references of the payout batches among `transactions`; banks book a file as one entry.
+fn batch_file_references(conn: &mut PgConnection, transactions: &[Transaction]) -> Result<HashMap<String, Vec<String>>, DomainError> {
+    use crate::schema::payout_batches::dsl as pb;
+    let batch_ids: HashSet<Uuid> = transactions.iter()
+        .filter_map(|transaction| transaction.metadata.as_ref()?.get(PAYOUT_BATCH_KEY)?.as_str()?.parse().ok())
+        .collect();
+    if batch_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let rows: Vec<(Uuid, Option<String>)> = pb::payout_batches
+        .filter(pb::batch_id.eq_any(batch_ids.into_iter().collect::<Vec<_>>()))
+        .select((pb::batch_id, pb::ach_file_ref))
+        .load(conn)?;
+    Ok(rows.into_iter()
+        .filter_map(|(batch_id, file_ref)| Some((batch_id.to_string(), vec![file_ref?])))
+        .collect())
+}
+
+fn unmatched_lines(conn: &mut PgConnection, account: &NostroAccount) -> Result<Vec<BankStatementLine>, DomainError> {
+    use crate::schema::bank_statement_lines::dsl as bsl;
+    Ok(bsl::bank_statement_lines
+        .filter(bsl::nostro_account.eq(&account.code))
+        .filter(bsl::status.ne(StatementLineStatus::Matched.as_str()))
+        .order(bsl::booked_at.asc())
+        .load(conn)?)
+}
+
+/// Transactions through `account` whose money has moved (or is moving) and that are not reconciled yet.
+fn unreconciled_transactions(
+    conn: &mut PgConnection,
+    account: &NostroAccount,
+    now: DateTime<Utc>,
+) -> Result<Vec<Transaction>, DomainError> {
+    use crate::schema::reconciliation_match_transactions::dsl as rmt;
+    use crate::schema::transactions::dsl as t;
+    let transactions: Vec<Transaction> = t::transactions
+        .filter(t::currency_code.eq(&account.currency))
+        .filter(t::status.eq_any(vec![
+            TransactionStatus::Processing.to_string(),
+            TransactionStatus::Submitted.to_string(),
+            TransactionStatus::Settled.to_string(),
+            TransactionStatus::Completed.to_string(),
+        ]))
+        .filter(t::created_at.ge(now - Duration::days(CANDIDATE_LOOKBACK_DAYS)))
+        .filter(diesel::dsl::not(diesel::dsl::exists(
+            rmt::reconciliation_match_transactions.filter(rmt::transaction_id.eq(t::transaction_id)),
+        )))
+        .order(t::created_at.asc())
+        .load(conn)?;
+    Ok(transactions.into_iter().filter(|transaction| account.carries(transaction)).collect())
+}
+
+/// Moves a matched transaction to Settled where its flow has that step. Transactions already
+/// settled or completed (e.g. by a webhook, or inbound credits) are left as they are.
+fn settle_transaction(
+    conn: &mut PgConnection,
+    transaction_id: Uuid,
+    settled_at: DateTime<Utc>,
+    match_id: Uuid,
+    actor: &str,
+) -> Result<bool, DomainError> {
+    use crate::schema::transactions::dsl as t;
+    let transaction: Transaction = t::transactions.find(transaction_id).for_update().first(conn)?;
+    let tx_type = TransactionType::parse(&transaction.transaction_type).unwrap_or(TransactionType::Unknown);
+    let Some(from) = TransactionStatus::parse(&transaction.status) else {
+        return Ok(false);
+    };
+    if from == TransactionStatus::Settled
+        || state_machine::transition_effect(&tx_type, &from, &TransactionStatus::Settled).is_none()
+    {
+        return Ok(false);
+    }
+    let update = TransitionUpdate {
+        settlement_at: Some(settled_at),
+        metadata: Some(json!({ "reconciliation": { "match_id": match_id } })),
+        ..Default::default()
+    };
+    state_machine::apply_transition(conn, &transaction, TransactionStatus::Settled, update, actor)?;
+    Ok(true)
+}
+
+fn open_exception(conn: &mut PgConnection, exception: &NewReconciliationException<'_>) -> Result<ReconciliationException, DomainError> {
+    use crate::schema::reconciliation_exceptions::dsl as re;
+    let exception: ReconciliationException = diesel::insert_into(re::reconciliation_exceptions).values(exception).get_result(conn)?;
+    log::warn!("Reconciliation break {} on {}: {}", exception.exception_id, exception.nostro_account, exception.exception_type);
+    Ok(exception)
+}
+
+/// Closes the open "unmatched" breaks of lines/transactions that have now been matched.
+fn close_unmatched_exceptions(
+    conn: &mut PgConnection,
+    line_ids: &[Uuid],
+    transaction_ids: &[Uuid],
+    resolution: ExceptionResolution,
+    resolved_by: Option<Uuid>,
+    note: Option<&str>,
+) -> Result<usize, DomainError> {
+    use crate::schema::reconciliation_exceptions::dsl as re;
+    Ok(diesel::update(re::reconciliation_exceptions)
+        .filter(re::status.eq(ExceptionStatus::Open.as_str()))
+        .filter(re::exception_type.ne(ExceptionType::AmountMismatch.as_str()))
+        .filter(re::line_id.eq_any(line_ids.to_vec()).or(re::transaction_id.eq_any(transaction_ids.to_vec())))
+        .set((
+            re::status.eq(ExceptionStatus::Resolved.as_str()),
+            re::resolution.eq(Some(resolution.as_str())),
+            re::resolution_note.eq(note),
+            re::resolved_by.eq(resolved_by),
+            re::resolved_at.eq(Some(Utc::now())),
+        ))
+        .execute(conn)?)
+}
+
+/// Stores a match, closes the breaks it explains, and settles its transactions if the amounts agree.
+/// Runs inside the caller's DB transaction; lines and transactions already matched are refused.
+fn record_match(
+    conn: &mut PgConnection,
+    account: &NostroAccount,
+    proposal: &ProposedMatch,
+    matched_by: Option<Uuid>,
+    note: Option<&str>,
+    actor: &str,
+) -> Result<MatchOutcome, DomainError> {
+    use crate::schema::bank_statement_lines::dsl as bsl;
+    use crate::schema::reconciliation_match_transactions::dsl as rmt;
+    use crate::schema::reconciliation_matches::dsl as rm;
+
+    let lines: Vec<BankStatementLine> = bsl::bank_statement_lines
+        .filter(bsl::line_id.eq_any(proposal.lines.clone()))
+        .for_update()
+        .load(conn)?;
+    if lines.len() != proposal.lines.len() || lines.iter().any(|line| line.nostro_account != account.code) {
+        return Err(DomainError::NotFound(format!("Statement lines not found on {}", account.code)));
+    }
+    if let Some(line) = lines.iter().find(|line| line.status == StatementLineStatus::Matched.as_str()) {
+        return Err(DomainError::Validation(format!("Statement line {} is already matched", line.line_id)));
+    }
+    let already_matched: Vec<Uuid> = rmt::reconciliation_match_transactions
+        .filter(rmt::transaction_id.eq_any(proposal.transactions.clone()))
+        .select(rmt::transaction_id)
+        .load(conn)?;
+    if let Some(transaction_id) = already_matched.first() {
+        return Err(DomainError::Validation(format!("Transaction {} is already reconciled", transaction_id)));
+    }
+
+    let matched: ReconciliationMatch = diesel::insert_into(rm::reconciliation_matches)
+        .values(&NewReconciliationMatch {
+            nostro_account: &account.code,
+            match_kind: proposal.kind().as_str(),
+            match_rule: proposal.rule.as_str(),
+            statement_amount: proposal.statement_amount,
+            transaction_amount: proposal.transaction_amount,
+            currency_code: &account.currency,
+            matched_by,
+            note,
+        })
+        .get_result(conn)?;
+    let links: Vec<ReconciliationMatchTransaction> = proposal.transactions.iter()
+        .map(|&transaction_id| ReconciliationMatchTransaction { match_id: matched.match_id, transaction_id })
+        .collect();
+    diesel::insert_into(rmt::reconciliation_match_transactions).values(&links).execute(conn)?;
+    diesel::update(bsl::bank_statement_lines.filter(bsl::line_id.eq_any(proposal.lines.clone())))
+        .set((bsl::status.eq(StatementLineStatus::Matched.as_str()), bsl::match_id.eq(Some(matched.match_id))))
+        .execute(conn)?;
+
+    let resolution = if matched_by.is_some() { ExceptionResolution::MatchedManually } else { ExceptionResolution::AutoMatched };
+    close_unmatched_exceptions(conn, &proposal.lines, &proposal.transactions, resolution, matched_by, note)?;
+
+    let booked_at = lines.iter().map(|line| line.booked_at).max().unwrap_or_else(Utc::now);
+    let mut settled = Vec::new();
+    let exception = if proposal.difference().is_zero() {
+        for &transaction_id in &proposal.transactions {
+            if settle_transaction(conn, transaction_id, booked_at, matched.match_id, actor)? {
+                settled.push(transaction_id);
+            }
+        }
+        None
+    } else {
+        // Settle only once someone has looked at the difference
+        let business_date = lines.iter().map(|line| line.booked_at.date_naive()).min().unwrap_or_else(|| Utc::now().date_naive());
+        Some(open_exception(conn, &NewReconciliationException {
+            nostro_account: &account.code,
+            exception_type: ExceptionType::AmountMismatch.as_str(),
+            line_id: None,
+            transaction_id: match proposal.transactions[..] { [transaction_id] => Some(transaction_id), _ => None },
+            match_id: Some(matched.match_id),
+            business_date,
+            expected_amount: Some(proposal.transaction_amount),
+            actual_amount: Some(proposal.statement_amount),
+            currency_code: &account.currency,
+            status: ExceptionStatus::Open.as_str(),
+        })?)
+    };
+    log::info!("Reconciled {} line(s) with {} transaction(s) on {} ({}, {})",
+        proposal.lines.len(), proposal.transactions.len(), account.code, proposal.rule.as_str(), proposal.kind().as_str());
+    Ok(MatchOutcome { matched, settled, exception })
+}
+
+/// Matches what can be matched on `account` and raises breaks for what is overdue.
+pub fn reconcile_account(conn: &mut PgConnection, account: &NostroAccount, now: DateTime<Utc>) -> Result<ReconciliationRun, DomainError> {
+    use crate::schema::bank_statement_lines::dsl as bsl;
+    use crate::schema::reconciliation_exceptions::dsl as re;
+
+    let lines = unmatched_lines(conn, account)?;
+    let transactions = unreconciled_transactions(conn, account, now)?;
+    let batch_references = batch_file_references(conn, &transactions)?;
+    let line_candidates: Vec<LineCandidate> = lines.iter().map(line_candidate).collect();
+    let transaction_candidates: Vec<TransactionCandidate> = transactions.iter()
+        .map(|transaction| transaction_candidate(transaction, &batch_references))
+        .collect();
+    let proposals = matcher::propose_matches(&line_candidates, &transaction_candidates, account.date_window_days);
+
+    let mut run = ReconciliationRun {
+        nostro_account: account.code.clone(),
+        import: None,
+        lines_considered: lines.len(),
+        transactions_considered: transactions.len(),
+        matches: Vec::new(),
+        exceptions_opened: Vec::new(),
+    };
+    let mut matched_lines = HashSet::new();
+    let mut matched_transactions = HashSet::new();
+    for proposal in &proposals {
+        match conn.transaction(|conn| record_match(conn, account, proposal, None, None, ACTOR)) {
+            Ok(outcome) => {
+                matched_lines.extend(proposal.lines.iter().copied());
+                matched_transactions.extend(proposal.transactions.iter().copied());
+                run.exceptions_opened.extend(outcome.exception.clone());
+                run.matches.push(outcome);
+            }
+            // A manual match or a concurrent run got there first
+            Err(DomainError::Validation(reason)) => log::warn!("Skipping proposed match on {}: {}", account.code, reason),
+            Err(e) => return Err(e),
+        }
+    }
+
+    // Bank entries nobody claimed within the date window
+    let line_cutoff = now - Duration::days(account.date_window_days);
+    for line in lines.iter().filter(|line| {
+        !matched_lines.contains(&line.line_id)
+            && line.status == StatementLineStatus::Unmatched.as_str()
+            && line.booked_at < line_cutoff
+    }) {
+        let exception = conn.transaction(|conn| {
+            diesel::update(bsl::bank_statement_lines.find(line.line_id))
+                .set(bsl::status.eq(StatementLineStatus::Exception.as_str()))
+                .execute(conn)?;
+            open_exception(conn, &NewReconciliationException {
+                nostro_account: &account.code,
+                exception_type: ExceptionType::UnmatchedStatementLine.as_str(),
+                line_id: Some(line.line_id),
+                transaction_id: None,
+                match_id: None,
+                business_date: line.booked_at.date_naive(),
+                expected_amount: None,
+                actual_amount: Some(line.amount),
+                currency_code: &account.currency,
+                status: ExceptionStatus::Open.as_str(),
+            })
+        })?;
+        run.exceptions_opened.push(exception);
+    }
+
+    // Our payments the bank still has not booked. Each is raised once; a closed break stays closed.
+    let overdue: Vec<&Transaction> = transactions.iter()
+        .filter(|transaction| {
+            !matched_transactions.contains(&transaction.transaction_id)
+                && transaction.created_at < now - Duration::days(account.missing_after_days)
+        })
+        .collect();
+    let already_raised: HashSet<Uuid> = re::reconciliation_exceptions
+        .filter(re::exception_type.eq(ExceptionType::UnmatchedTransaction.as_str()))
+        .filter(re::transaction_id.eq_any(overdue.iter().map(|transaction| transaction.transaction_id).collect::<Vec<_>>()))
+        .select(re::transaction_id)
+        .load::<Option<Uuid>>(conn)?
+        .into_iter()
+        .flatten()
+        .collect();
+    for transaction in overdue.into_iter().filter(|transaction| !already_raised.contains(&transaction.transaction_id)) {
+        let exception = open_exception(conn, &NewReconciliationException {
+            nostro_account: &account.code,
+            exception_type: ExceptionType::UnmatchedTransaction.as_str(),
+            line_id: None,
+            transaction_id: Some(transaction.transaction_id),
+            match_id: None,
+            business_date: transaction.created_at.date_naive(),
+            expected_amount: Some(signed_amount(transaction)),
+            actual_amount: None,
+            currency_code: &account.currency,
+            status: ExceptionStatus::Open.as_str(),
+        })?;
+        run.exceptions_opened.push(exception);
+    }
+
+    log::info!("Reconciliation of {}: {} matches, {} new breaks ({} lines, {} transactions considered)",
+        account.code, run.matches.len(), run.exceptions_opened.len(), run.lines_considered, run.transactions_considered);
+    Ok(run)
+}
+
+/// Pulls the recent statement of `account` from `source`, stores it and reconciles the account.
+pub async fn pull_and_reconcile(
+    conn: &mut PgConnection,
+    source: &dyn StatementSource,
+    account: &NostroAccount,
+    now: DateTime<Utc>,
+) -> Result<ReconciliationRun, DomainError> {
+    let from = now - Duration::days(account.missing_after_days + STATEMENT_OVERLAP_DAYS);
+    let entries = source.fetch_statement(account, from, now).await?;
+    let import = statement::import_statement(conn, account, &entries)?;
+    let mut run = reconcile_account(conn, account, now)?;
+    run.import = Some(import);
+    Ok(run)
+}
+
+fn account<'a>(accounts: &'a NostroAccountSet, code: &str) -> Result<&'a NostroAccount, DomainError> {
+    accounts.get(code).ok_or_else(|| DomainError::NotFound(format!("Nostro account '{}' is not configured", code)))
+}
+
+/// Ties statement lines and transactions together by hand.
+#[allow(clippy::too_many_arguments)]
+pub fn match_manually(
+    conn: &mut PgConnection,
+    accounts: &NostroAccountSet,
+    account_code: &str,
+    line_ids: &[Uuid],
+    transaction_ids: &[Uuid],
+    matched_by: Uuid,
+    actor: &str,
+    note: Option<&str>,
+) -> Result<MatchOutcome, DomainError> {
+    use crate::schema::bank_statement_lines::dsl as bsl;
+    use crate::schema::transactions::dsl as t;
+
+    let account = account(accounts, account_code)?;
+    if line_ids.is_empty() || transaction_ids.is_empty() {
+        return Err(DomainError::Validation("A match needs at least one statement line and one transaction".to_string()));
+    }
+    if line_ids.len() > 1 && transaction_ids.len() > 1 {
+        return Err(DomainError::Validation("Match several lines to one transaction, or one line to several".to_string()));
+    }
+    conn.transaction(|conn| {
+        let lines: Vec<BankStatementLine> = bsl::bank_statement_lines.filter(bsl::line_id.eq_any(line_ids.to_vec())).load(conn)?;
+        let transactions: Vec<Transaction> = t::transactions.filter(t::transaction_id.eq_any(transaction_ids.to_vec())).load(conn)?;
+        if transactions.len() != transaction_ids.len() {
+            return Err(DomainError::NotFound("Transaction not found".to_string()));
+        }
+        if let Some(transaction) = transactions.iter().find(|transaction| !account.carries(transaction)) {
+            return Err(DomainError::Validation(format!(
+                "Transaction {} does not move money through {}", transaction.transaction_id, account.code,
+            )));
+        }
+        let proposal = ProposedMatch {
+            lines: line_ids.to_vec(),
+            transactions: transaction_ids.to_vec(),
+            rule: MatchRule::Manual,
+            statement_amount: lines.iter().map(|line| line.amount).sum(),
+            transaction_amount: transactions.iter().map(signed_amount).sum(),
+        };
+        let outcome = record_match(conn, account, &proposal, Some(matched_by), note, actor)?;
+        audit::log_db_audit_event(
+            conn, Some(matched_by), actor, "RECONCILIATION_MANUAL_MATCH", Some(AuditTargetType::Reconciliation),
+            Some(&outcome.matched.match_id.to_string()), AuditOutcome::Success,
+            Some(json!({"nostro_account": account.code, "line_ids": line_ids, "transaction_ids": transaction_ids,
+                "difference": proposal.difference().to_string(), "note": note})),
+            None,
+        )?;
+        Ok(outcome)
+    })
+}
+
+/// Closes a break without matching. Accepting (or writing off) an amount difference settles the
+/// transactions of its match; IGNORED only closes the break.
+pub fn resolve_exception(
+    conn: &mut PgConnection,
+    exception_id: Uuid,
+    resolution: ExceptionResolution,
+    resolved_by: Uuid,
+    actor: &str,
+    note: Option<&str>,
+) -> Result<ExceptionOutcome, DomainError> {
+    use crate::schema::bank_statement_lines::dsl as bsl;
+    use crate::schema::reconciliation_exceptions::dsl as re;
+    use crate::schema::reconciliation_match_transactions::dsl as rmt;
+
+    conn.transaction(|conn| {
+        let exception: ReconciliationException = re::reconciliation_exceptions
+            .find(exception_id)
+            .for_update()
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| DomainError::NotFound(format!("Reconciliation exception {} not found", exception_id)))?;
+        if exception.status != ExceptionStatus::Open.as_str() {
+            return Err(DomainError::Validation(format!("Exception {} is already {}", exception_id, exception.status)));
+        }
+        let exception_type = ExceptionType::parse(&exception.exception_type)
+            .ok_or_else(|| DomainError::Internal(format!("Unknown exception type '{}'", exception.exception_type)))?;
+        match (resolution, exception_type) {
+            (ExceptionResolution::AutoMatched | ExceptionResolution::MatchedManually, _) => {
+                return Err(DomainError::Validation("Use a manual match to tie the break to the other side".to_string()));
+            }
+            (ExceptionResolution::Accepted, ExceptionType::UnmatchedStatementLine | ExceptionType::UnmatchedTransaction) => {
+                return Err(DomainError::Validation("Only an amount difference can be accepted".to_string()));
+            }
+            _ => {}
+        }
+
+        let mut settled = Vec::new();
+        if exception_type == ExceptionType::AmountMismatch
+            && matches!(resolution, ExceptionResolution::Accepted | ExceptionResolution::WrittenOff)
+        {
+            let match_id = exception.match_id
+                .ok_or_else(|| DomainError::Internal(format!("Exception {} has no match", exception_id)))?;
+            let booked_at = bsl::bank_statement_lines
+                .filter(bsl::match_id.eq(Some(match_id)))
+                .select(diesel::dsl::max(bsl::booked_at))
+                .first::<Option<DateTime<Utc>>>(conn)?
+                .unwrap_or_else(Utc::now);
+            let transaction_ids: Vec<Uuid> = rmt::reconciliation_match_transactions
+                .filter(rmt::match_id.eq(match_id))
+                .select(rmt::transaction_id)
+                .load(conn)?;
+            for transaction_id in transaction_ids {
+                if settle_transaction(conn, transaction_id, booked_at, match_id, actor)? {
+                    settled.push(transaction_id);
+                }
+            }
+        }
+
+        let exception: ReconciliationException = diesel::update(re::reconciliation_exceptions.find(exception_id))
+            .set((
+                re::status.eq(ExceptionStatus::Resolved.as_str()),
+                re::resolution.eq(Some(resolution.as_str())),
+                re::resolution_note.eq(note),
+                re::resolved_by.eq(Some(resolved_by)),
+                re::resolved_at.eq(Some(Utc::now())),
+            ))
+            .get_result(conn)?;
+        audit::log_db_audit_event(
+            conn, Some(resolved_by), actor, "RESOLVE_RECONCILIATION_EXCEPTION", Some(AuditTargetType::Reconciliation),
+            Some(&exception_id.to_string()), AuditOutcome::Success,
+            Some(json!({"exception_type": exception.exception_type, "resolution": resolution.as_str(), "settled": settled, "note": note})),
+            None,
+        )?;
+        log::info!("Reconciliation exception {} resolved as {} by {}", exception_id, resolution.as_str(), actor);
+        Ok(ExceptionOutcome { exception, settled })
+    })
+}
+
+pub fn get_exception(conn: &mut PgConnection, exception_id: Uuid) -> Result<ReconciliationException, DomainError> {
+    use crate::schema::reconciliation_exceptions::dsl as re;
+    re::reconciliation_exceptions
+        .find(exception_id)
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("Reconciliation exception {} not found", exception_id)))
+}
+
+/// Breaks, oldest business date first.
+pub fn list_exceptions(
+    conn: &mut PgConnection,
+    account_code: Option<&str>,
+    status: Option<ExceptionStatus>,
+    exception_type: Option<ExceptionType>,
+    limit: i64,
+) -> Result<Vec<ReconciliationException>, DomainError> {
+    use crate::schema::reconciliation_exceptions::dsl as re;
+    let mut query = re::reconciliation_exceptions.into_boxed();
+    if let Some(account_code) = account_code {
+        query = query.filter(re::nostro_account.eq(account_code));
+    }
+    if let Some(status) = status {
+        query = query.filter(re::status.eq(status.as_str()));
+    }
+    if let Some(exception_type) = exception_type {
+        query = query.filter(re::exception_type.eq(exception_type.as_str()));
+    }
+    Ok(query.order((re::business_date.asc(), re::created_at.asc())).limit(limit).load(conn)?)
+}
+
+/// Statement lines of `account_code` booked on `from..=to` (e.g. for picking lines to match by hand).
+pub fn list_statement_lines(
+    conn: &mut PgConnection,
+    account_code: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+    only_unmatched: bool,
+) -> Result<Vec<BankStatementLine>, DomainError> {
+    use crate::schema::bank_statement_lines::dsl as bsl;
+    let start = from.and_hms_opt(0, 0, 0).map(|t| t.and_utc()).unwrap_or_else(Utc::now);
+    let end = (to + Duration::days(1)).and_hms_opt(0, 0, 0).map(|t| t.and_utc()).unwrap_or_else(Utc::now);
+    let mut query = bsl::bank_statement_lines
+        .filter(bsl::nostro_account.eq(account_code))
+        .filter(bsl::booked_at.ge(start))
+        .filter(bsl::booked_at.lt(end))
+        .into_boxed();
+    if only_unmatched {
+        query = query.filter(bsl::status.ne(StatementLineStatus::Matched.as_str()));
+    }
+    Ok(query.order(bsl::booked_at.asc()).load(conn)?)
+}
@@ -0,0 +1,291 @@
+// /home/inno/elights_jobes-research/backend/domain/src/reconciliation/matcher.rs
+// Matching of statement lines against our transactions, strongest evidence first:
+//   1. UETR      - the same SWIFT gpi UETR on both sides
+//   2. REFERENCE - a reference of ours (transaction id, rail reference, payout batch) quoted on the line.
+//                  Lines and transactions linked by references form groups: one line against several
+//                  transactions (a batch booked as one debit) or several lines against one transaction.
+//                  Groups linking several lines to several transactions are ambiguous and left alone.
+//   3. AMOUNT_DATE - same signed amount within the account's date window, where the line and the
+//                  transaction are each other's only candidate.
+// UETR/reference matches are kept even when the amounts differ; the difference is raised as a break.
+// Amounts are signed from the nostro account's point of view: money in positive, money out negative.
+use crate::models::{MatchKind, MatchRule};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// References shorter than this are too likely to appear in unrelated text.
+const MIN_REFERENCE_LEN: usize = 6;
+
+/// A statement line as seen by the matcher.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineCandidate {
+    pub id: Uuid,
+    pub booked_on: NaiveDate,
+    pub amount: Decimal,
+    pub uetr: Option<String>,
+    pub text: Vec<String>, // Reference and description, searched for our references
+}
+
+/// An unreconciled transaction as seen by the matcher.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionCandidate {
+    pub id: Uuid,
+    pub date: NaiveDate,
+    pub amount: Decimal,
+    pub uetr: Option<String>,
+    pub references: Vec<String>, // Transaction id, rail reference, batch references
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProposedMatch {
+    pub lines: Vec<Uuid>,
+    pub transactions: Vec<Uuid>,
+    pub rule: MatchRule,
+    pub statement_amount: Decimal,
+    pub transaction_amount: Decimal,
+}
+
+impl ProposedMatch {
+    pub fn kind(&self) -> MatchKind {
+        MatchKind::for_counts(self.lines.len(), self.transactions.len())
+    }
+
+    /// Bank side minus our side; zero for a clean match.
+    pub fn difference(&self) -> Decimal {
+        self.statement_amount - self.transaction_amount
+    }
+}
+
+/// Uppercase alphanumerics only, so "ref 1234-abcd" and "REF1234ABCD" compare equal.
+pub fn normalize_reference(value: &str) -> String {
+    value.chars().filter(|c| c.is_ascii_alphanumeric()).map(|c| c.to_ascii_uppercase()).collect()
+}
+
+fn quotes(line: &LineCandidate, reference: &str) -> bool {
+    let reference = normalize_reference(reference);
+    reference.len() >= MIN_REFERENCE_LEN && line.text.iter().any(|text| normalize_reference(text).contains(&reference))
+}
+
+struct Pool<'a> {
+    lines: &'a [LineCandidate],
+    transactions: &'a [TransactionCandidate],
+    used_lines: Vec<bool>,
+    used_transactions: Vec<bool>,
+    matches: Vec<ProposedMatch>,
+}
+
+impl<'a> Pool<'a> {
+    fn take(&mut self, lines: &[usize], transactions: &[usize], rule: MatchRule) {
+        for &i in lines {
+            self.used_lines[i] = true;
+        }
+        for &j in transactions {
+            self.used_transactions[j] = true;
+        }
+        self.matches.push(ProposedMatch {
+            lines: lines.iter().map(|&i| self.lines[i].id).collect(),
+            transactions: transactions.iter().map(|&j| self.transactions[j].id).collect(),
+            rule,
+            statement_amount: lines.iter().map(|&i| self.lines[i].amount).sum(),
+            transaction_amount: transactions.iter().map(|&j| self.transactions[j].amount).sum(),
+        });
+    }
+
+    fn open_lines(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.lines.len()).filter(|&i| !self.used_lines[i])
+    }
+
+    fn open_transactions(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.transactions.len()).filter(|&j| !self.used_transactions[j])
+    }
+
+    fn match_by_uetr(&mut self) {
+        for i in 0..self.lines.len() {
+            if self.used_lines[i] {
+                continue;
+            }
+            let Some(uetr) = self.lines[i].uetr.as_deref().map(normalize_reference).filter(|u| !u.is_empty()) else {
+                continue;
+            };
+            let same_uetr = |other: &Option<String>| other.as_deref().map(normalize_reference).as_deref() == Some(uetr.as_str());
+            let transactions: Vec<usize> = self.open_transactions().filter(|&j| same_uetr(&self.transactions[j].uetr)).collect();
+            if transactions.len() != 1 {
+                continue; // A UETR identifies one payment; several candidates means bad data, leave it
+            }
+            let lines: Vec<usize> = self.open_lines().filter(|&k| same_uetr(&self.lines[k].uetr)).collect();
+            self.take(&lines, &transactions, MatchRule::Uetr);
+        }
+    }
+
+    fn match_by_reference(&mut self) {
+        let open_lines: Vec<usize> = self.open_lines().collect();
+        let open_transactions: Vec<usize> = self.open_transactions().collect();
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        for &i in &open_lines {
+            for &j in &open_transactions {
+                if self.transactions[j].references.iter().any(|reference| quotes(&self.lines[i], reference)) {
+                    edges.push((i, j));
+                }
+            }
+        }
+
+        // Connected groups of lines and transactions
+        let mut seen_lines = HashSet::new();
+        for &(start, _) in &edges {
+            if !seen_lines.insert(start) {
+                continue;
+            }
+            let mut lines = vec![start];
+            let mut transactions: Vec<usize> = Vec::new();
+            let mut frontier = vec![start];
+            while let Some(i) = frontier.pop() {
+                for &(_, j) in edges.iter().filter(|(line, _)| *line == i) {
+                    if transactions.contains(&j) {
+                        continue;
+                    }
+                    transactions.push(j);
+                    for &(k, _) in edges.iter().filter(|(_, tx)| *tx == j) {
+                        if seen_lines.insert(k) {
+                            lines.push(k);
+                            frontier.push(k);
+                        }
+                    }
+                }
+            }
+            if lines.len() > 1 && transactions.len() > 1 {
+                continue; // Many-to-many: no way to tell which line pays which transaction
+            }
+            lines.sort_unstable();
+            transactions.sort_unstable();
+            self.take(&lines, &transactions, MatchRule::Reference);
+        }
+    }
+
+    fn match_by_amount_and_date(&mut self, date_window_days: i64) {
+        let within = |line: &LineCandidate, tx: &TransactionCandidate| {
+            line.amount == tx.amount && (line.booked_on - tx.date).num_days().abs() <= date_window_days
+        };
+        let mut pairs = Vec::new();
+        for i in self.open_lines() {
+            let candidates: Vec<usize> = self.open_transactions().filter(|&j| within(&self.lines[i], &self.transactions[j])).collect();
+            let [j] = candidates[..] else { continue };
+            let rivals = self.open_lines().filter(|&k| within(&self.lines[k], &self.transactions[j])).count();
+            if rivals == 1 {
+                pairs.push((i, j));
+            }
+        }
+        for (i, j) in pairs {
+            self.take(&[i], &[j], MatchRule::AmountDate);
+        }
+    }
+}
+
+/// Proposes matches between unmatched statement lines and unreconciled transactions of one account.
+pub fn propose_matches(
+    lines: &[LineCandidate],
+    transactions: &[TransactionCandidate],
+    date_window_days: i64,
+) -> Vec<ProposedMatch> {
+    let mut pool = Pool {
+        lines,
+        transactions,
+        used_lines: vec![false; lines.len()],
+        used_transactions: vec![false; transactions.len()],
+        matches: Vec::new(),
+    };
+    pool.match_by_uetr();
+    pool.match_by_reference();
+    pool.match_by_amount_and_date(date_window_days);
+    pool.matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 4, d).unwrap()
+    }
+
+    fn line(n: u128, booked: u32, amount: Decimal, uetr: Option<&str>, text: &str) -> LineCandidate {
+        LineCandidate { id: Uuid::from_u128(n), booked_on: day(booked), amount, uetr: uetr.map(str::to_string), text: vec![text.to_string()] }
+    }
+
+    fn tx(n: u128, date: u32, amount: Decimal, uetr: Option<&str>, references: &[&str]) -> TransactionCandidate {
+        TransactionCandidate {
+            id: Uuid::from_u128(n), date: day(date), amount, uetr: uetr.map(str::to_string),
+            references: references.iter().map(|r| r.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_uetr_and_reference_matches_keep_differences() {
+        let uetr = "eb6305c9-1f7f-49de-aed0-16487c27b42d";
+        let lines = vec![
+            line(1, 10, dec!(-995.00), Some("EB6305C91F7F49DEAED016487C27B42D"), "WIRE OUT"),
+            line(2, 11, dec!(250.00), None, "ACH CREDIT REF INV-20250410"),
+        ];
+        let transactions = vec![
+            tx(10, 9, dec!(-1000.00), Some(uetr), &[]),
+            tx(11, 11, dec!(250.00), None, &["inv-20250410"]),
+        ];
+        let matches = propose_matches(&lines, &transactions, 2);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].rule, MatchRule::Uetr);
+        assert_eq!(matches[0].difference(), dec!(5.00)); // Correspondent fee taken by the bank
+        assert_eq!(matches[1].rule, MatchRule::Reference);
+        assert_eq!(matches[1].transactions, vec![Uuid::from_u128(11)]);
+        assert_eq!(matches[1].difference(), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_grouped_reference_matches() {
+        // One statement debit for a payout batch, and one payment booked in two parts
+        let lines = vec![
+            line(1, 10, dec!(-300.00), None, "NACHA FILE BATCH-7781"),
+            line(2, 10, dec!(60.00), None, "PART 1 PAYREF-55501"),
+            line(3, 11, dec!(40.00), None, "PART 2 PAYREF-55501"),
+        ];
+        let transactions = vec![
+            tx(10, 10, dec!(-100.00), None, &["BATCH-7781"]),
+            tx(11, 10, dec!(-200.00), None, &["BATCH-7781"]),
+            tx(12, 9, dec!(100.00), None, &["PAYREF-55501"]),
+        ];
+        let matches = propose_matches(&lines, &transactions, 2);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].kind(), MatchKind::OneToMany);
+        assert_eq!(matches[0].difference(), Decimal::ZERO);
+        assert_eq!(matches[1].kind(), MatchKind::ManyToOne);
+        assert_eq!(matches[1].lines, vec![Uuid::from_u128(2), Uuid::from_u128(3)]);
+        assert_eq!(matches[1].statement_amount, dec!(100.00));
+    }
+
+    #[test]
+    fn test_amount_date_requires_a_unique_candidate() {
+        let lines = vec![
+            line(1, 10, dec!(75.00), None, "INCOMING"),
+            line(2, 10, dec!(20.00), None, "INCOMING"),
+            line(3, 10, dec!(20.00), None, "INCOMING"),
+            line(4, 20, dec!(-50.00), None, "OUTGOING"),
+        ];
+        let transactions = vec![
+            tx(10, 9, dec!(75.00), None, &[]),
+            tx(11, 10, dec!(20.00), None, &[]),
+            tx(12, 10, dec!(-50.00), None, &[]), // Outside the window
+        ];
+        let matches = propose_matches(&lines, &transactions, 2);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].rule, MatchRule::AmountDate);
+        assert_eq!(matches[0].lines, vec![Uuid::from_u128(1)]);
+    }
+
+    #[test]
+    fn test_short_references_are_not_matched() {
+        let lines = vec![line(1, 10, dec!(10.00), None, "REF 123 PAYMENT")];
+        let transactions = vec![tx(10, 1, dec!(11.00), None, &["123"])];
+        assert!(propose_matches(&lines, &transactions, 2).is_empty());
+    }
+}
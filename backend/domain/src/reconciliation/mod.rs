@@ -0,0 +1,18 @@
+// /home/inno/elights_jobes-research/backend/domain/src/reconciliation/mod.rs
+// Bank reconciliation: nostro account statements are pulled, matched against our transactions and
+// anything that does not agree is raised as a break for operations to resolve.
+
+pub mod accounts; // Nostro accounts under reconciliation (JSON configuration)
+pub mod statement; // Statement sources and storing pulled statement lines
+pub mod matcher; // UETR / reference / amount-and-date matching, incl. grouped matches
+pub mod engine; // Reconciliation runs, settling matched transactions, manual matches and break resolution
+pub mod report; // Daily break report
+
+pub use accounts::{NostroAccount, NostroAccountSet};
+pub use statement::{import_statement, ImportSummary, StatementEntry, StatementSource};
+pub use matcher::{propose_matches, ProposedMatch};
+pub use engine::{
+    get_exception, list_exceptions, list_statement_lines, match_manually, pull_and_reconcile, reconcile_account,
+    resolve_exception, ExceptionOutcome, MatchOutcome, ReconciliationRun,
+};
+pub use report::{break_report, AccountBreaks, BreakReport};
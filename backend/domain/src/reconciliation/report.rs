@@ -0,0 +1,100 @@
+// /home/inno/elights_jobes-research/backend/domain/src/reconciliation/report.rs
+// Daily break report per nostro account: what was booked on the day, how much of it reconciled,
+// the breaks raised for the day and everything still open as of that day (with its age).
+use super::accounts::NostroAccountSet;
+use crate::error::DomainError;
+use crate::models::{BankStatementLine, ExceptionStatus, ReconciliationException, StatementLineStatus};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct AccountBreaks {
+    pub nostro_account: String,
+    pub currency: String,
+    pub statement_lines: usize, // Booked on the business date
+    pub matched_lines: usize,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub statement_net: Decimal, // Net movement booked on the business date
+    pub new_breaks: Vec<ReconciliationException>, // Raised for the business date
+    pub open_breaks: usize, // Open, for the business date or earlier
+    pub open_by_type: BTreeMap<String, usize>,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub open_amount: Decimal, // Sum of absolute unexplained amounts
+    pub oldest_open: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct BreakReport {
+    pub business_date: NaiveDate,
+    pub generated_at: DateTime<Utc>,
+    pub accounts: Vec<AccountBreaks>,
+}
+
+/// What an open break leaves unexplained: the difference for a mismatch, else the one side we have.
+pub fn unexplained_amount(exception: &ReconciliationException) -> Decimal {
+    let expected = exception.expected_amount.unwrap_or_default();
+    let actual = exception.actual_amount.unwrap_or_default();
+    (actual - expected).abs()
+}
+
+/// Break report for `business_date`, for one account or all configured accounts.
+pub fn break_report(
+    conn: &mut PgConnection,
+    accounts: &NostroAccountSet,
+    account_code: Option<&str>,
+    business_date: NaiveDate,
+) -> Result<BreakReport, DomainError> {
+    use crate::schema::bank_statement_lines::dsl as bsl;
+    use crate::schema::reconciliation_exceptions::dsl as re;
+
+    if let Some(code) = account_code {
+        if accounts.get(code).is_none() {
+            return Err(DomainError::NotFound(format!("Nostro account '{}' is not configured", code)));
+        }
+    }
+    let day_start = business_date.and_hms_opt(0, 0, 0).map(|t| t.and_utc())
+        .ok_or_else(|| DomainError::Validation("Invalid business date".to_string()))?;
+    let day_end = day_start + Duration::days(1);
+
+    let mut report = BreakReport { business_date, generated_at: Utc::now(), accounts: Vec::new() };
+    for account in accounts.accounts.iter().filter(|account| account_code.map_or(true, |code| code == account.code)) {
+        let booked: Vec<BankStatementLine> = bsl::bank_statement_lines
+            .filter(bsl::nostro_account.eq(&account.code))
+            .filter(bsl::booked_at.ge(day_start))
+            .filter(bsl::booked_at.lt(day_end))
+            .load(conn)?;
+
+        let open: Vec<ReconciliationException> = re::reconciliation_exceptions
+            .filter(re::nostro_account.eq(&account.code))
+            .filter(re::business_date.le(business_date))
+            .filter(re::status.eq(ExceptionStatus::Open.as_str()))
+            .order(re::business_date.asc())
+            .load(conn)?;
+        let new_breaks: Vec<ReconciliationException> = re::reconciliation_exceptions
+            .filter(re::nostro_account.eq(&account.code))
+            .filter(re::business_date.eq(business_date))
+            .order(re::created_at.asc())
+            .load(conn)?;
+
+        let mut open_by_type = BTreeMap::new();
+        for exception in &open {
+            *open_by_type.entry(exception.exception_type.clone()).or_insert(0) += 1;
+        }
+        report.accounts.push(AccountBreaks {
+            nostro_account: account.code.clone(),
+            currency: account.currency.clone(),
+            statement_lines: booked.len(),
+            matched_lines: booked.iter().filter(|line| line.status == StatementLineStatus::Matched.as_str()).count(),
+            statement_net: booked.iter().map(|line| line.amount).sum(),
+            new_breaks,
+            open_breaks: open.len(),
+            open_by_type,
+            open_amount: open.iter().map(unexplained_amount).sum(),
+            oldest_open: open.first().map(|exception| exception.business_date),
+        });
+    }
+    Ok(report)
+}
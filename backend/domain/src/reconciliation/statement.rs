@@ -0,0 +1,90 @@
+// /home/inno/elights_jobes-research/backend/domain/src/reconciliation/statement.rs
+// Statement pulls: bank clients live outside the domain, so they are reached through `StatementSource`.
+// Pulls overlap on purpose (late bookings); lines already stored are recognised by the bank's id.
+use super::accounts::NostroAccount;
+use crate::error::DomainError;
+use crate::models::{NewBankStatementLine, StatementLineStatus};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+/// A statement entry as reported by the bank, normalized.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StatementEntry {
+    pub bank_transaction_id: String,
+    pub booked_at: DateTime<Utc>,
+    pub value_date: Option<NaiveDate>,
+    pub amount: Decimal, // Credits to our account positive, debits negative
+    pub currency: String,
+    pub reference: Option<String>, // End-to-end id / client reference
+    pub uetr: Option<String>,
+    pub description: String,
+    pub counterparty_name: Option<String>,
+    pub raw: JsonValue, // The entry as the bank client returned it
+}
+
+/// Where statements come from (the bank clients, behind the API layer).
+#[async_trait]
+pub trait StatementSource: Send + Sync {
+    async fn fetch_statement(
+        &self,
+        account: &NostroAccount,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<StatementEntry>, DomainError>;
+}
+
+#[derive(Debug, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub received: usize,
+    pub inserted: usize,
+    pub already_stored: usize,
+    pub skipped: usize, // Entries in another currency than the account's
+}
+
+/// Stores new statement entries for `account`.
+pub fn import_statement(
+    conn: &mut PgConnection,
+    account: &NostroAccount,
+    entries: &[StatementEntry],
+) -> Result<ImportSummary, DomainError> {
+    use crate::schema::bank_statement_lines::dsl as bsl;
+    let mut summary = ImportSummary { received: entries.len(), ..Default::default() };
+    for entry in entries {
+        if !entry.currency.eq_ignore_ascii_case(&account.currency) {
+            log::warn!("Statement entry {} on {} is in {}, expected {}; skipped",
+                entry.bank_transaction_id, account.code, entry.currency, account.currency);
+            summary.skipped += 1;
+            continue;
+        }
+        let inserted = diesel::insert_into(bsl::bank_statement_lines)
+            .values(&NewBankStatementLine {
+                nostro_account: &account.code,
+                bank_transaction_id: &entry.bank_transaction_id,
+                booked_at: entry.booked_at,
+                value_date: entry.value_date,
+                amount: entry.amount,
+                currency_code: &account.currency,
+                reference: entry.reference.as_deref(),
+                uetr: entry.uetr.as_deref(),
+                description: &entry.description,
+                counterparty_name: entry.counterparty_name.as_deref(),
+                raw: entry.raw.clone(),
+                status: StatementLineStatus::Unmatched.as_str(),
+            })
+            .on_conflict((bsl::nostro_account, bsl::bank_transaction_id))
+            .do_nothing()
+            .execute(conn)?;
+        if inserted == 1 {
+            summary.inserted += 1;
+        } else {
+            summary.already_stored += 1;
+        }
+    }
+    log::info!("Statement import for {}: {} new, {} already stored, {} skipped",
+        account.code, summary.inserted, summary.already_stored, summary.skipped);
+    Ok(summary)
+}
@@ -0,0 +1,210 @@
+// /home/inno/elights_jobes-research/backend/domain/src/sanctions/cases.rs
+// Compliance review of screening hits. A payment with hits is parked in RequiresAction before anything is
+// held, charged or queued, with a case recording the parties, hits and lists it was screened against.
+// A compliance officer other than the initiator either clears the case (false positive: the payment goes
+// on to approvals, if a policy requires them, or straight to its rail) or confirms it (payment cancelled).
+use super::lists::LoadedList;
+use super::screening::{ScreeningHit, ScreeningParty};
+use crate::approvals::{self, ApprovalPolicySet, PendingDispatch};
+use crate::error::DomainError;
+use crate::models::{
+    AuditOutcome, AuditTargetType, NewSanctionsCase, SanctionsCase, SanctionsCaseStatus, Transaction, TransactionStatus,
+};
+use crate::payments::state_machine::{self, TransitionUpdate};
+use crate::security::audit;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde_json::json;
+use uuid::Uuid;
+
+const ACTOR: &str = "SANCTIONS_SCREENING";
+
+fn to_json<T: serde::Serialize>(value: &T, what: &str) -> Result<serde_json::Value, DomainError> {
+    serde_json::to_value(value).map_err(|e| DomainError::Internal(format!("Failed to serialize {}: {}", what, e)))
+}
+
+/// Stops a Pending outbound payment with screening hits. Call inside the DB transaction that created it.
+#[allow(clippy::too_many_arguments)]
+pub fn open_case(
+    conn: &mut PgConnection,
+    transaction: &Transaction,
+    initiator_user_id: Uuid,
+    parties: &[ScreeningParty],
+    hits: &[ScreeningHit],
+    lists: &[LoadedList],
+    dispatch: &PendingDispatch,
+    now: DateTime<Utc>,
+) -> Result<Transaction, DomainError> {
+    let case: SanctionsCase = diesel::insert_into(crate::schema::sanctions_cases::table)
+        .values(&NewSanctionsCase {
+            transaction_id: transaction.transaction_id,
+            initiator_user_id,
+            status: SanctionsCaseStatus::Open.as_str(),
+            parties: to_json(&parties, "screened parties")?,
+            hits: to_json(&hits, "screening hits")?,
+            lists: to_json(&lists, "watchlists")?,
+            pending_dispatch: to_json(dispatch, "pending dispatch")?,
+        })
+        .get_result(conn)?;
+
+    // Only what the customer may see: no list entries or scores on the payment itself
+    let update = TransitionUpdate {
+        metadata: Some(json!({"sanctions": {"case_id": case.case_id, "status": case.status, "screened_at": now}})),
+        ..Default::default()
+    };
+    let transaction = state_machine::apply_transition(conn, transaction, TransactionStatus::RequiresAction, update, ACTOR)?;
+
+    audit::log_db_audit_event(
+        conn,
+        Some(initiator_user_id),
+        ACTOR,
+        "SANCTIONS_SCREENING_HIT",
+        Some(AuditTargetType::SanctionsCase),
+        Some(&case.case_id.to_string()),
+        AuditOutcome::Success,
+        Some(json!({"transaction_id": transaction.transaction_id, "hits": case.hits, "amount": transaction.amount.to_string(),
+            "currency": transaction.currency_code})),
+        None,
+    )?;
+    log::warn!("Payment {} stopped by {} sanctions screening hit(s), case {}", transaction.transaction_id, hits.len(), case.case_id);
+    Ok(transaction)
+}
+
+fn load_for_update(conn: &mut PgConnection, case_id: Uuid) -> Result<SanctionsCase, DomainError> {
+    use crate::schema::sanctions_cases::dsl as sc;
+    sc::sanctions_cases
+        .find(case_id)
+        .for_update()
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("Sanctions case {} not found", case_id)))
+}
+
+/// Records the review on an open case and returns it with its still-parked payment.
+fn review(
+    conn: &mut PgConnection,
+    case_id: Uuid,
+    new_status: SanctionsCaseStatus,
+    reviewer_id: Uuid,
+    comment: &str,
+    now: DateTime<Utc>,
+) -> Result<(SanctionsCase, Transaction), DomainError> {
+    use crate::schema::sanctions_cases::dsl as sc;
+    use crate::schema::transactions::dsl as t;
+
+    let case = load_for_update(conn, case_id)?;
+    if case.status != SanctionsCaseStatus::Open.as_str() {
+        return Err(DomainError::Validation(format!("Sanctions case {} is already {}", case_id, case.status)));
+    }
+    if case.initiator_user_id == reviewer_id {
+        return Err(DomainError::Authorization("A sanctions case cannot be reviewed by the payment's initiator".to_string()));
+    }
+    if comment.trim().is_empty() {
+        return Err(DomainError::Validation("A review comment is required".to_string()));
+    }
+    let transaction: Transaction = t::transactions.find(case.transaction_id).for_update().first(conn)?;
+    if transaction.status != TransactionStatus::RequiresAction.to_string() {
+        return Err(DomainError::Validation(format!(
+            "Payment {} is {}, no longer waiting for sanctions review", transaction.transaction_id, transaction.status
+        )));
+    }
+    let case: SanctionsCase = diesel::update(sc::sanctions_cases.find(case_id))
+        .set((
+            sc::status.eq(new_status.as_str()),
+            sc::reviewed_by.eq(Some(reviewer_id)),
+            sc::review_comment.eq(Some(comment.trim())),
+            sc::reviewed_at.eq(Some(now)),
+        ))
+        .get_result(conn)?;
+    Ok((case, transaction))
+}
+
+/// Clears a false positive and resumes the payment: it waits for approval if `approval_policies` require
+/// one, otherwise it is held, charged and queued at once (failing if the wallet no longer covers it).
+pub fn clear_case(
+    conn: &mut PgConnection,
+    case_id: Uuid,
+    reviewer_id: Uuid,
+    comment: &str,
+    approval_policies: Option<&ApprovalPolicySet>,
+    now: DateTime<Utc>,
+) -> Result<SanctionsCase, DomainError> {
+    conn.transaction(|conn| {
+        let (case, transaction) = review(conn, case_id, SanctionsCaseStatus::Cleared, reviewer_id, comment, now)?;
+        let dispatch: PendingDispatch = serde_json::from_value(case.pending_dispatch.clone())
+            .map_err(|e| DomainError::Internal(format!("Sanctions case {} has an invalid pending dispatch: {}", case_id, e)))?;
+        let metadata = json!({"sanctions": {"case_id": case.case_id, "status": case.status, "cleared_at": now}});
+
+        let requirement = match approval_policies {
+            Some(policies) => approvals::requirement_for_dispatch(conn, policies, &transaction, &dispatch, now)?,
+            None => None,
+        };
+        let next_step = match requirement {
+            Some(requirement) => {
+                let update = TransitionUpdate { metadata: Some(metadata), ..Default::default() };
+                let transaction = state_machine::apply_transition(conn, &transaction, TransactionStatus::RequiresAction, update, ACTOR)?;
+                approvals::request_approval(conn, &transaction, case.initiator_user_id, &requirement, &dispatch, now)?;
+                "AWAITING_APPROVAL"
+            }
+            None => {
+                approvals::workflow::dispatch_held_payment(conn, &transaction, &dispatch, metadata, ACTOR)?;
+                "RELEASED"
+            }
+        };
+
+        audit::log_db_audit_event(
+            conn, Some(reviewer_id), &reviewer_id.to_string(), "SANCTIONS_CASE_CLEARED", Some(AuditTargetType::SanctionsCase),
+            Some(&case_id.to_string()), AuditOutcome::Success,
+            Some(json!({"transaction_id": case.transaction_id, "comment": case.review_comment, "next_step": next_step})),
+            None,
+        )?;
+        log::info!("Sanctions case {} cleared by {}, payment {} {}", case_id, reviewer_id, case.transaction_id, next_step);
+        Ok(case)
+    })
+}
+
+/// Confirms a true hit: the payment is cancelled (nothing was held or charged) and its limit usage released.
+pub fn confirm_case(
+    conn: &mut PgConnection,
+    case_id: Uuid,
+    reviewer_id: Uuid,
+    comment: &str,
+    now: DateTime<Utc>,
+) -> Result<SanctionsCase, DomainError> {
+    conn.transaction(|conn| {
+        let (case, transaction) = review(conn, case_id, SanctionsCaseStatus::Confirmed, reviewer_id, comment, now)?;
+        let update = TransitionUpdate {
+            metadata: Some(json!({"sanctions": {"case_id": case.case_id, "status": case.status, "confirmed_at": now}})),
+            ..Default::default()
+        };
+        state_machine::apply_transition(conn, &transaction, TransactionStatus::Cancelled, update, ACTOR)?;
+
+        audit::log_db_audit_event(
+            conn, Some(reviewer_id), &reviewer_id.to_string(), "SANCTIONS_HIT_CONFIRMED", Some(AuditTargetType::SanctionsCase),
+            Some(&case_id.to_string()), AuditOutcome::Success,
+            Some(json!({"transaction_id": case.transaction_id, "comment": case.review_comment, "hits": case.hits})),
+            None,
+        )?;
+        log::warn!("Sanctions case {} confirmed by {}, payment {} cancelled", case_id, reviewer_id, case.transaction_id);
+        Ok(case)
+    })
+}
+
+pub fn get_case(conn: &mut PgConnection, case_id: Uuid) -> Result<SanctionsCase, DomainError> {
+    use crate::schema::sanctions_cases::dsl as sc;
+    sc::sanctions_cases
+        .find(case_id)
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("Sanctions case {} not found", case_id)))
+}
+
+/// Cases oldest first (the review queue), optionally by status.
+pub fn list_cases(conn: &mut PgConnection, status: Option<SanctionsCaseStatus>) -> Result<Vec<SanctionsCase>, DomainError> {
+    use crate::schema::sanctions_cases::dsl as sc;
+    let mut query = sc::sanctions_cases.into_boxed();
+    if let Some(status) = status {
+        query = query.filter(sc::status.eq(status.as_str()));
+    }
+    Ok(query.order(sc::created_at.asc()).limit(500).load(conn)?)
+}
@@ -0,0 +1,478 @@
+// /home/inno/elights_jobes-research/backend/domain/src/sanctions/lists.rs
+// Watchlist files, read from local copies (downloaded and refreshed outside the service):
+// - OFAC SDN: sdn.xml, or sdn.csv with the optional alt.csv (aliases) and add.csv (addresses),
+// - EU consolidated financial sanctions list: XML or the semicolon-separated CSV,
+// - UN Security Council consolidated list: XML.
+// BICs and digital currency addresses are taken from the OFAC id lists/remarks and EU identifications.
+use crate::error::DomainError;
+use chrono::{DateTime, Utc};
+use roxmltree::{Document, Node};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WatchlistSource {
+    OfacSdn,
+    EuConsolidated,
+    UnConsolidated,
+}
+
+impl WatchlistSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WatchlistSource::OfacSdn => "OFAC_SDN",
+            WatchlistSource::EuConsolidated => "EU_CONSOLIDATED",
+            WatchlistSource::UnConsolidated => "UN_CONSOLIDATED",
+        }
+    }
+}
+
+/// One listed person, entity, vessel... with every name it is known by.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct WatchlistEntry {
+    pub entry_id: String, // OFAC uid / ent_num, EU logicalId, UN DATAID
+    pub entity_type: String, // As the list names it (Individual, Entity, person, enterprise...)
+    pub names: Vec<String>, // Primary name first, then aliases
+    pub programs: Vec<String>,
+    pub countries: Vec<String>, // As the list gives them: ISO codes on the EU list, names on the others
+    pub bics: Vec<String>, // Normalized (uppercase, no spaces)
+    pub crypto_addresses: Vec<String>,
+}
+
+/// A list file named in the sanctions configuration. The format follows the file extension.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ListFile {
+    pub source: WatchlistSource,
+    pub path: String,
+    #[serde(default)]
+    pub alt_path: Option<String>, // OFAC CSV only: alt.csv
+    #[serde(default)]
+    pub add_path: Option<String>, // OFAC CSV only: add.csv
+}
+
+/// What was loaded, recorded on every screening case.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LoadedList {
+    pub source: WatchlistSource,
+    pub path: String,
+    pub entries: usize,
+    pub loaded_at: DateTime<Utc>,
+}
+
+pub fn normalize_bic(bic: &str) -> String {
+    bic.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_uppercase()
+}
+
+fn invalid(source: WatchlistSource, reason: impl std::fmt::Display) -> DomainError {
+    DomainError::Configuration(format!("Invalid {} list: {}", source.as_str(), reason))
+}
+
+fn is(node: &Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name
+}
+
+fn text(node: Node) -> Option<String> {
+    node.text().map(str::trim).filter(|t| !t.is_empty()).map(str::to_string)
+}
+
+fn child_text(node: Node, name: &str) -> Option<String> {
+    node.children().find(|child| is(child, name)).and_then(text)
+}
+
+fn attribute(node: Node, name: &str) -> Option<String> {
+    node.attribute(name).map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+}
+
+fn joined(parts: &[Option<String>]) -> Option<String> {
+    let name = parts.iter().flatten().map(String::as_str).collect::<Vec<_>>().join(" ");
+    (!name.is_empty()).then_some(name)
+}
+
+fn push_unique(values: &mut Vec<String>, value: Option<String>) {
+    if let Some(value) = value {
+        if !values.contains(&value) {
+            values.push(value);
+        }
+    }
+}
+
+/// OFAC SDN XML (sdn.xml).
+pub fn parse_ofac_sdn_xml(xml: &str) -> Result<Vec<WatchlistEntry>, DomainError> {
+    let source = WatchlistSource::OfacSdn;
+    let doc = Document::parse(xml).map_err(|e| invalid(source, e))?;
+    let mut entries = Vec::new();
+    for node in doc.descendants().filter(|n| is(n, "sdnEntry")) {
+        let Some(entry_id) = child_text(node, "uid") else {
+            continue;
+        };
+        let mut entry = WatchlistEntry {
+            entry_id,
+            entity_type: child_text(node, "sdnType").unwrap_or_default(),
+            ..Default::default()
+        };
+        push_unique(&mut entry.names, joined(&[child_text(node, "firstName"), child_text(node, "lastName")]));
+        for aka in node.descendants().filter(|n| is(n, "aka")) {
+            push_unique(&mut entry.names, joined(&[child_text(aka, "firstName"), child_text(aka, "lastName")]));
+        }
+        for program in node.descendants().filter(|n| is(n, "program")) {
+            push_unique(&mut entry.programs, text(program));
+        }
+        for country in node.descendants().filter(|n| is(n, "country")) {
+            push_unique(&mut entry.countries, text(country));
+        }
+        for id in node.descendants().filter(|n| is(n, "id")) {
+            let (Some(id_type), Some(number)) = (child_text(id, "idType"), child_text(id, "idNumber")) else {
+                continue;
+            };
+            if id_type.eq_ignore_ascii_case("SWIFT/BIC") {
+                push_unique(&mut entry.bics, Some(normalize_bic(&number)));
+            } else if id_type.starts_with("Digital Currency Address") {
+                push_unique(&mut entry.crypto_addresses, Some(number));
+            }
+        }
+        if !entry.names.is_empty() {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+fn csv_records(content: &str, delimiter: u8, has_headers: bool) -> Result<(Vec<String>, Vec<csv::StringRecord>), csv::Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(has_headers)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+    let headers = if has_headers {
+        reader.headers()?.iter().map(|h| h.trim_start_matches('\u{feff}').to_lowercase()).collect()
+    } else {
+        Vec::new()
+    };
+    let records = reader.records().collect::<Result<Vec<_>, _>>()?;
+    Ok((headers, records))
+}
+
+/// OFAC CSV cell; "-0-" stands for an empty value.
+fn ofac_field(record: &csv::StringRecord, index: usize) -> Option<String> {
+    record.get(index).map(str::trim).filter(|v| !v.is_empty() && *v != "-0-").map(str::to_string)
+}
+
+/// BICs, digital currency addresses and aliases written in an OFAC remarks cell.
+fn parse_ofac_remarks(remarks: &str, entry: &mut WatchlistEntry) {
+    for item in remarks.split(';').map(str::trim) {
+        if let Some(bic) = item.strip_prefix("SWIFT/BIC") {
+            push_unique(&mut entry.bics, Some(normalize_bic(bic.trim_end_matches('.'))));
+        } else if item.starts_with("Digital Currency Address") {
+            let address = item.split_whitespace().last().map(|a| a.trim_end_matches('.').to_string());
+            push_unique(&mut entry.crypto_addresses, address);
+        } else if let Some(alias) = item.strip_prefix("a.k.a.") {
+            let alias = alias.trim().trim_end_matches('.').trim_matches('\'').trim();
+            push_unique(&mut entry.names, (!alias.is_empty()).then(|| alias.to_string()));
+        }
+    }
+}
+
+/// OFAC SDN CSV: sdn.csv plus, when available, alt.csv (aliases) and add.csv (addresses).
+pub fn parse_ofac_sdn_csv(sdn: &str, alt: Option<&str>, add: Option<&str>) -> Result<Vec<WatchlistEntry>, DomainError> {
+    let source = WatchlistSource::OfacSdn;
+    let (_, records) = csv_records(sdn, b',', false).map_err(|e| invalid(source, e))?;
+    let mut entries: Vec<WatchlistEntry> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for record in &records {
+        let (Some(entry_id), Some(name)) = (ofac_field(record, 0), ofac_field(record, 1)) else {
+            continue; // Trailing end-of-file marker
+        };
+        let mut entry = WatchlistEntry {
+            entry_id: entry_id.clone(),
+            entity_type: ofac_field(record, 2).unwrap_or_else(|| "Entity".to_string()), // Blank for entities
+            names: vec![name],
+            ..Default::default()
+        };
+        for program in ofac_field(record, 3).iter().flat_map(|p| p.split(']')) {
+            push_unique(&mut entry.programs, Some(program.trim().trim_start_matches('[').to_string()).filter(|p| !p.is_empty()));
+        }
+        if let Some(remarks) = ofac_field(record, 11) {
+            parse_ofac_remarks(&remarks, &mut entry);
+        }
+        index.insert(entry_id, entries.len());
+        entries.push(entry);
+    }
+    if let Some(alt) = alt {
+        let (_, records) = csv_records(alt, b',', false).map_err(|e| invalid(source, e))?;
+        for record in &records {
+            if let (Some(entry_id), Some(name)) = (ofac_field(record, 0), ofac_field(record, 3)) {
+                if let Some(&i) = index.get(&entry_id) {
+                    push_unique(&mut entries[i].names, Some(name));
+                }
+            }
+        }
+    }
+    if let Some(add) = add {
+        let (_, records) = csv_records(add, b',', false).map_err(|e| invalid(source, e))?;
+        for record in &records {
+            if let (Some(entry_id), Some(country)) = (ofac_field(record, 0), ofac_field(record, 4)) {
+                if let Some(&i) = index.get(&entry_id) {
+                    push_unique(&mut entries[i].countries, Some(country));
+                }
+            }
+        }
+    }
+    Ok(entries)
+}
+
+fn is_bic_identification(type_code: &str) -> bool {
+    let type_code = type_code.to_lowercase();
+    type_code.contains("bic") || type_code.contains("swift")
+}
+
+fn eu_country(code: Option<String>) -> Option<String> {
+    code.map(|c| c.to_uppercase()).filter(|c| c.len() == 2 && c != "00") // "00" = unknown
+}
+
+/// EU consolidated financial sanctions list, XML.
+pub fn parse_eu_xml(xml: &str) -> Result<Vec<WatchlistEntry>, DomainError> {
+    let source = WatchlistSource::EuConsolidated;
+    let doc = Document::parse(xml).map_err(|e| invalid(source, e))?;
+    let mut entries = Vec::new();
+    for node in doc.descendants().filter(|n| is(n, "sanctionEntity")) {
+        let Some(entry_id) = attribute(node, "logicalId") else {
+            continue;
+        };
+        let mut entry = WatchlistEntry { entry_id, ..Default::default() };
+        for child in node.children().filter(|n| n.is_element()) {
+            match child.tag_name().name() {
+                "subjectType" => entry.entity_type = attribute(child, "code").unwrap_or_default(),
+                "regulation" => push_unique(&mut entry.programs, attribute(child, "programme")),
+                "nameAlias" => {
+                    let whole = attribute(child, "wholeName")
+                        .or_else(|| joined(&[attribute(child, "firstName"), attribute(child, "lastName")]));
+                    push_unique(&mut entry.names, whole);
+                }
+                "citizenship" | "address" => push_unique(&mut entry.countries, eu_country(attribute(child, "countryIso2Code"))),
+                "identification" => {
+                    if attribute(child, "identificationTypeCode").is_some_and(|code| is_bic_identification(&code)) {
+                        push_unique(&mut entry.bics, attribute(child, "number").map(|n| normalize_bic(&n)));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if !entry.names.is_empty() {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+/// EU consolidated financial sanctions list, semicolon-separated CSV (one row per name, address, id...).
+pub fn parse_eu_csv(content: &str) -> Result<Vec<WatchlistEntry>, DomainError> {
+    let source = WatchlistSource::EuConsolidated;
+    let (headers, records) = csv_records(content, b';', true).map_err(|e| invalid(source, e))?;
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let id_column = column("entity_logicalid").ok_or_else(|| invalid(source, "missing Entity_LogicalId column"))?;
+    let name_column = column("namealias_wholename");
+    let type_column = column("entity_subjecttype").or_else(|| column("entity_subjecttype_classificationcode"));
+    let programme_column = column("entity_regulation_programme");
+    let country_columns = [column("citizenship_countryiso2code"), column("address_countryiso2code")];
+    let id_type_column = column("identification_typecode");
+    let id_number_column = column("identification_number");
+
+    let cell = |record: &csv::StringRecord, index: Option<usize>| {
+        index.and_then(|i| record.get(i)).map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+    };
+    let mut entries: Vec<WatchlistEntry> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for record in &records {
+        let Some(entry_id) = cell(record, Some(id_column)) else {
+            continue;
+        };
+        let i = *index.entry(entry_id.clone()).or_insert_with(|| {
+            entries.push(WatchlistEntry { entry_id, ..Default::default() });
+            entries.len() - 1
+        });
+        let entry = &mut entries[i];
+        if entry.entity_type.is_empty() {
+            entry.entity_type = cell(record, type_column).unwrap_or_default();
+        }
+        push_unique(&mut entry.names, cell(record, name_column));
+        push_unique(&mut entry.programs, cell(record, programme_column));
+        for country_column in country_columns {
+            push_unique(&mut entry.countries, eu_country(cell(record, country_column)));
+        }
+        if cell(record, id_type_column).is_some_and(|code| is_bic_identification(&code)) {
+            push_unique(&mut entry.bics, cell(record, id_number_column).map(|n| normalize_bic(&n)));
+        }
+    }
+    entries.retain(|entry| !entry.names.is_empty());
+    Ok(entries)
+}
+
+/// UN Security Council consolidated list, XML (individuals and entities).
+pub fn parse_un_xml(xml: &str) -> Result<Vec<WatchlistEntry>, DomainError> {
+    let source = WatchlistSource::UnConsolidated;
+    let doc = Document::parse(xml).map_err(|e| invalid(source, e))?;
+    let mut entries = Vec::new();
+    for node in doc.descendants().filter(|n| is(n, "INDIVIDUAL") || is(n, "ENTITY")) {
+        let Some(entry_id) = child_text(node, "DATAID") else {
+            continue;
+        };
+        let mut entry = WatchlistEntry { entry_id, entity_type: node.tag_name().name().to_string(), ..Default::default() };
+        let name_parts: Vec<Option<String>> =
+            ["FIRST_NAME", "SECOND_NAME", "THIRD_NAME", "FOURTH_NAME"].iter().map(|part| child_text(node, part)).collect();
+        push_unique(&mut entry.names, joined(&name_parts));
+        push_unique(&mut entry.names, child_text(node, "NAME_ORIGINAL_SCRIPT"));
+        for alias in node.descendants().filter(|n| is(n, "ALIAS_NAME")) {
+            push_unique(&mut entry.names, text(alias));
+        }
+        push_unique(&mut entry.programs, child_text(node, "UN_LIST_TYPE"));
+        for nationality in node.children().filter(|n| is(n, "NATIONALITY")) {
+            for value in nationality.children().filter(|n| is(n, "VALUE")) {
+                push_unique(&mut entry.countries, text(value));
+            }
+        }
+        for address in node.children().filter(|n| is(n, "INDIVIDUAL_ADDRESS") || is(n, "ENTITY_ADDRESS")) {
+            push_unique(&mut entry.countries, child_text(address, "COUNTRY"));
+        }
+        if !entry.names.is_empty() {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+fn read(source: WatchlistSource, path: &str) -> Result<String, DomainError> {
+    std::fs::read_to_string(path).map_err(|e| invalid(source, format!("cannot read {}: {}", path, e)))
+}
+
+/// Reads and parses one configured list file.
+pub fn load_list(file: &ListFile) -> Result<Vec<WatchlistEntry>, DomainError> {
+    let source = file.source;
+    let extension = Path::new(&file.path).extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    let content = read(source, &file.path)?;
+    let entries = match (source, extension.as_str()) {
+        (WatchlistSource::OfacSdn, "xml") => parse_ofac_sdn_xml(&content)?,
+        (WatchlistSource::OfacSdn, "csv") => {
+            let alt = file.alt_path.as_deref().map(|path| read(source, path)).transpose()?;
+            let add = file.add_path.as_deref().map(|path| read(source, path)).transpose()?;
+            parse_ofac_sdn_csv(&content, alt.as_deref(), add.as_deref())?
+        }
+        (WatchlistSource::EuConsolidated, "xml") => parse_eu_xml(&content)?,
+        (WatchlistSource::EuConsolidated, "csv") => parse_eu_csv(&content)?,
+        (WatchlistSource::UnConsolidated, "xml") => parse_un_xml(&content)?,
+        _ => return Err(invalid(source, format!("unsupported file format '{}' ({})", extension, file.path))),
+    };
+    if entries.is_empty() {
+        return Err(invalid(source, format!("{} contains no entries", file.path)));
+    }
+    log::info!("Loaded {} {} entries from {}", entries.len(), source.as_str(), file.path);
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ofac_sdn_xml() {
+        let xml = r#"<?xml version="1.0" standalone="yes"?>
+<sdnList xmlns="http://tempuri.org/sdnList.xsd">
+  <sdnEntry>
+    <uid>306</uid><lastName>BANK MELLI IRAN</lastName><sdnType>Entity</sdnType>
+    <programList><program>IRAN</program><program>SDGT</program></programList>
+    <idList><id><uid>1</uid><idType>SWIFT/BIC</idType><idNumber>MELI IR TH</idNumber></id></idList>
+    <akaList><aka><uid>2</uid><type>a.k.a.</type><lastName>BANK MELLI</lastName></aka></akaList>
+    <addressList><address><uid>3</uid><city>Tehran</city><country>Iran</country></address></addressList>
+  </sdnEntry>
+  <sdnEntry>
+    <uid>9001</uid><firstName>Ivan</firstName><lastName>PETROV</lastName><sdnType>Individual</sdnType>
+    <idList><id><uid>4</uid><idType>Digital Currency Address - XBT</idType><idNumber>1BoatSLRHtKNngkdXEeobR76b53LETtpyT</idNumber></id></idList>
+  </sdnEntry>
+</sdnList>"#;
+        let entries = parse_ofac_sdn_xml(xml).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].names, vec!["BANK MELLI IRAN", "BANK MELLI"]);
+        assert_eq!(entries[0].bics, vec!["MELIIRTH"]);
+        assert_eq!(entries[0].programs, vec!["IRAN", "SDGT"]);
+        assert_eq!(entries[0].countries, vec!["Iran"]);
+        assert_eq!(entries[1].names, vec!["Ivan PETROV"]);
+        assert_eq!(entries[1].crypto_addresses, vec!["1BoatSLRHtKNngkdXEeobR76b53LETtpyT"]);
+    }
+
+    #[test]
+    fn test_parse_ofac_sdn_csv_with_remarks_and_aliases() {
+        let sdn = "36,\"AEROCARIBBEAN AIRLINES\",-0- ,\"CUBA\",-0- ,-0- ,-0- ,-0- ,-0- ,-0- ,-0- ,\"SWIFT/BIC AECUCUHH; a.k.a. 'AERO CARIBE'.\"\n\
+                   9002,\"SOKOLOV, Dmitri\",\"individual\",\"[CYBER2] [RUSSIA-EO14024]\",-0- ,-0- ,-0- ,-0- ,-0- ,-0- ,-0- ,\"Digital Currency Address - ETH 0x7f367cc41522ce07553e823bf3be79a889debe1b.\"\n\u{1a}\n";
+        let alt = "9002,101,\"aka\",\"SOKOLOV, Dmitry\",-0- \n";
+        let add = "36,25,-0- ,\"Havana\",\"Cuba\",-0- \n";
+        let entries = parse_ofac_sdn_csv(sdn, Some(alt), Some(add)).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].entity_type, "Entity");
+        assert_eq!(entries[0].bics, vec!["AECUCUHH"]);
+        assert_eq!(entries[0].names, vec!["AEROCARIBBEAN AIRLINES", "AERO CARIBE"]);
+        assert_eq!(entries[0].countries, vec!["Cuba"]);
+        assert_eq!(entries[1].programs, vec!["CYBER2", "RUSSIA-EO14024"]);
+        assert_eq!(entries[1].names, vec!["SOKOLOV, Dmitri", "SOKOLOV, Dmitry"]);
+        assert_eq!(entries[1].crypto_addresses, vec!["0x7f367cc41522ce07553e823bf3be79a889debe1b"]);
+    }
+
+    #[test]
+    fn test_parse_eu_xml_and_csv() {
+        let xml = r#"<export xmlns="http://eu.europa.ec/fpi/fsd/export">
+  <sanctionEntity logicalId="13" designationDate="2003-05-22">
+    <regulation programme="IRQ"/>
+    <subjectType code="person"/>
+    <nameAlias firstName="Saddam" lastName="Hussein Al-Tikriti" wholeName="Saddam Hussein Al-Tikriti"/>
+    <nameAlias wholeName="Abu Ali"/>
+    <citizenship countryIso2Code="IQ"/>
+    <address countryIso2Code="00"/>
+  </sanctionEntity>
+</export>"#;
+        let entries = parse_eu_xml(xml).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].names, vec!["Saddam Hussein Al-Tikriti", "Abu Ali"]);
+        assert_eq!(entries[0].countries, vec!["IQ"]);
+        assert_eq!(entries[0].programs, vec!["IRQ"]);
+
+        let csv = "Entity_LogicalId;Entity_SubjectType;Entity_Regulation_Programme;NameAlias_WholeName;Address_CountryIso2Code;Identification_TypeCode;Identification_Number\n\
+                   120;enterprise;SYR;Commercial Bank of Syria;SY;;\n\
+                   120;enterprise;SYR;;;swiftbic;CMSY SY DA\n\
+                   121;person;PRK;Kim Yong Chol;KP;;\n";
+        let entries = parse_eu_csv(csv).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].names, vec!["Commercial Bank of Syria"]);
+        assert_eq!(entries[0].bics, vec!["CMSYSYDA"]);
+        assert_eq!(entries[0].countries, vec!["SY"]);
+        assert_eq!(entries[1].entity_type, "person");
+    }
+
+    #[test]
+    fn test_parse_un_xml() {
+        let xml = r#"<CONSOLIDATED_LIST dateGenerated="2025-04-01T00:00:00">
+  <INDIVIDUALS>
+    <INDIVIDUAL>
+      <DATAID>6908555</DATAID><FIRST_NAME>RI</FIRST_NAME><SECOND_NAME>WON HO</SECOND_NAME>
+      <UN_LIST_TYPE>DPRK</UN_LIST_TYPE><NAME_ORIGINAL_SCRIPT>리원호</NAME_ORIGINAL_SCRIPT>
+      <NATIONALITY><VALUE>Democratic People's Republic of Korea</VALUE></NATIONALITY>
+      <INDIVIDUAL_ALIAS><QUALITY>Good</QUALITY><ALIAS_NAME>Ri Won-ho</ALIAS_NAME></INDIVIDUAL_ALIAS>
+    </INDIVIDUAL>
+  </INDIVIDUALS>
+  <ENTITIES>
+    <ENTITY>
+      <DATAID>110404</DATAID><FIRST_NAME>KOREA KWANGSON BANKING CORP.</FIRST_NAME><UN_LIST_TYPE>DPRK</UN_LIST_TYPE>
+      <ENTITY_ALIAS><ALIAS_NAME>KKBC</ALIAS_NAME></ENTITY_ALIAS>
+      <ENTITY_ADDRESS><CITY>Pyongyang</CITY><COUNTRY>Democratic People's Republic of Korea</COUNTRY></ENTITY_ADDRESS>
+    </ENTITY>
+  </ENTITIES>
+</CONSOLIDATED_LIST>"#;
+        let entries = parse_un_xml(xml).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].names, vec!["RI WON HO", "리원호", "Ri Won-ho"]);
+        assert_eq!(entries[0].entity_type, "INDIVIDUAL");
+        assert_eq!(entries[1].names, vec!["KOREA KWANGSON BANKING CORP.", "KKBC"]);
+        assert_eq!(entries[1].countries, vec!["Democratic People's Republic of Korea"]);
+        assert!(parse_un_xml("<CONSOLIDATED_LIST>").is_err());
+    }
+}
@@ -0,0 +1,225 @@
+// /home/inno/elights_jobes-research/backend/domain/src/sanctions/matching.rs
+// Fuzzy name matching. Names are transliterated to ASCII (Cyrillic, Arabic, accented Latin...), lowercased,
+// split into tokens and stripped of titles and legal-form noise, so "ПУТИН, Владимир" and "Vladimir Putin"
+// compare equal. Scores are Jaro-Winkler similarities between 0 and 1. Names are only compared with list
+// names sharing a blocking key: a token prefix or a Soundex-style phonetic code.
+use deunicode::deunicode;
+
+/// Tokens that say nothing about who a party is.
+const NOISE_TOKENS: &[&str] = &[
+    "the", "of", "and", "mr", "mrs", "ms", "dr", "ltd", "llc", "inc", "co", "corp", "plc", "gmbh", "sa", "ag", "jsc",
+    "ojsc", "pjsc", "cjsc", "oao", "zao", "ooo", "limited", "company", "corporation",
+];
+
+/// Transliterated, lowercased name tokens without punctuation or noise. Dots join ("S.A." is "sa").
+pub fn name_tokens(name: &str) -> Vec<String> {
+    let ascii = deunicode(name).to_lowercase().replace('.', "");
+    ascii
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|token| !token.is_empty() && !NOISE_TOKENS.contains(token))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Normalized form of a name: its tokens joined by single spaces.
+pub fn normalize_name(name: &str) -> String {
+    name_tokens(name).join(" ")
+}
+
+/// Jaro similarity of two strings.
+pub fn jaro(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut a_matched = vec![false; a.len()];
+    let mut b_matched = vec![false; b.len()];
+    let mut matches = 0usize;
+    for (i, ca) in a.iter().enumerate() {
+        let end = (i + window + 1).min(b.len());
+        for j in i.saturating_sub(window)..end {
+            if !b_matched[j] && b[j] == *ca {
+                a_matched[i] = true;
+                b_matched[j] = true;
+                matches += 1;
+                break;
+            }
+        }
+    }
+    if matches == 0 {
+        return 0.0;
+    }
+    let mut transpositions = 0usize;
+    let mut k = 0usize;
+    for (i, ca) in a.iter().enumerate() {
+        if a_matched[i] {
+            while !b_matched[k] {
+                k += 1;
+            }
+            if *ca != b[k] {
+                transpositions += 1;
+            }
+            k += 1;
+        }
+    }
+    let m = matches as f64;
+    (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64 / 2.0) / m) / 3.0
+}
+
+/// Jaro-Winkler similarity: Jaro boosted for a common prefix of up to four characters.
+pub fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let jaro = jaro(a, b);
+    let prefix = a.chars().zip(b.chars()).take(4).take_while(|(x, y)| x == y).count();
+    jaro + prefix as f64 * 0.1 * (1.0 - jaro)
+}
+
+/// Similarity of two tokens. Initials and very short tokens only count when identical.
+fn token_score(a: &str, b: &str) -> f64 {
+    if a.len() < 3 || b.len() < 3 {
+        return if a == b { 1.0 } else { 0.0 };
+    }
+    jaro_winkler(a, b)
+}
+
+/// How well the name `a` matches `b`, whatever the token order and however many extra tokens (middle
+/// names, patronymics) the longer one has. A single-token name only matches a single-token name.
+pub fn name_score(a: &[String], b: &[String]) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let sorted = |tokens: &[String]| {
+        let mut tokens = tokens.to_vec();
+        tokens.sort();
+        tokens.join(" ")
+    };
+    let whole = jaro_winkler(&sorted(a), &sorted(b));
+
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if short.len() == 1 && long.len() > 1 {
+        return whole;
+    }
+    // Each token of the shorter name takes its best unused counterpart, weighted by length
+    let mut used = vec![false; long.len()];
+    let mut weighted = 0.0;
+    let mut total = 0.0;
+    for token in short {
+        let best = long
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| !used[*j])
+            .map(|(j, other)| (j, token_score(token, other)))
+            .max_by(|x, y| x.1.total_cmp(&y.1));
+        if let Some((j, score)) = best {
+            used[j] = true;
+            weighted += score * token.len() as f64;
+        }
+        total += token.len() as f64;
+    }
+    whole.max(weighted / total)
+}
+
+/// Soundex class of a letter; vowels, `h` and `y` have none. `w` sounds like `v` in transliterated names
+/// (Wladimir, Wolkov), so it joins `v`'s class instead of being skipped.
+fn phonetic_class(c: char) -> Option<char> {
+    match c {
+        'b' | 'f' | 'p' | 'v' | 'w' => Some('1'),
+        'c' | 'g' | 'j' | 'k' | 'q' | 's' | 'x' | 'z' => Some('2'),
+        'd' | 't' => Some('3'),
+        'l' => Some('4'),
+        'm' | 'n' => Some('5'),
+        'r' => Some('6'),
+        _ => None,
+    }
+}
+
+/// Soundex-style code of a token: the classes of its first four consonant sounds, first letter included,
+/// so spellings of one name that differ in vowels or in letters that sound alike ("poutine" / "putin",
+/// "wladimir" / "vladimir") share a code. Empty for tokens without consonants.
+pub fn phonetic_code(token: &str) -> String {
+    let mut code = String::new();
+    let mut previous = None;
+    for c in token.chars() {
+        let class = phonetic_class(c);
+        if let Some(class) = class {
+            if previous != Some(class) {
+                code.push(class);
+                if code.len() == 4 {
+                    break;
+                }
+            }
+        }
+        if c != 'h' {
+            previous = class; // A vowel separates two letters of the same class, an `h` does not
+        }
+    }
+    code
+}
+
+/// Blocking keys of a name: the first two characters and the phonetic code of each token. Names sharing
+/// no key are not compared.
+pub fn blocking_keys(tokens: &[String]) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::with_capacity(tokens.len() * 2);
+    for token in tokens {
+        keys.push(format!("p:{}", token.chars().take(2).collect::<String>()));
+        let code = phonetic_code(token);
+        if !code.is_empty() {
+            keys.push(format!("s:{}", code));
+        }
+    }
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 0.001
+    }
+
+    #[test]
+    fn test_jaro_winkler_reference_values() {
+        assert!(close(jaro_winkler("martha", "marhta"), 0.961));
+        assert!(close(jaro_winkler("dwayne", "duane"), 0.84));
+        assert!(close(jaro_winkler("dixon", "dicksonx"), 0.813));
+        assert_eq!(jaro_winkler("abc", "abc"), 1.0);
+        assert_eq!(jaro_winkler("abc", "xyz"), 0.0);
+    }
+
+    #[test]
+    fn test_transliteration_and_noise() {
+        assert_eq!(normalize_name("ПУТИН, Владимир"), "putin vladimir");
+        assert_eq!(normalize_name("Société Générale S.A."), "societe generale");
+        assert_eq!(normalize_name("The Mr. John O'Neil LTD"), "john o neil");
+    }
+
+    #[test]
+    fn test_name_score_ignores_order_and_extra_tokens() {
+        let listed = name_tokens("PUTIN, Vladimir Vladimirovich");
+        assert!(name_score(&name_tokens("Vladimir Putin"), &listed) > 0.99);
+        assert!(name_score(&name_tokens("Wladimir Poutine"), &listed) > 0.85);
+        assert!(name_score(&name_tokens("Владимир Путин"), &listed) > 0.99);
+        assert!(name_score(&name_tokens("Maria Gonzalez"), &listed) < 0.7);
+        // A lone first name does not match a full listed name
+        assert!(name_score(&name_tokens("Vladimir"), &listed) < 0.9);
+    }
+
+    #[test]
+    fn test_spelling_variants_share_a_blocking_key() {
+        assert_eq!(phonetic_code("putin"), phonetic_code("poutine"));
+        assert_eq!(phonetic_code("wladimir"), phonetic_code("vladimir"));
+        assert_eq!(phonetic_code("ashcroft"), "2613"); // "s" and "c" across an "h" count once
+        assert_eq!(phonetic_code("aeiou"), "");
+        let listed = blocking_keys(&name_tokens("PUTIN, Vladimir Vladimirovich"));
+        let variant = blocking_keys(&name_tokens("Wladimir Poutine"));
+        assert!(variant.iter().any(|key| listed.contains(key)));
+        assert!(!blocking_keys(&name_tokens("Maria Gonzalez")).iter().any(|key| listed.contains(key)));
+    }
+}
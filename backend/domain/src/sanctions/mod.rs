@@ -0,0 +1,15 @@
+// /home/inno/elights_jobes-research/backend/domain/src/sanctions/mod.rs
+// Sanctions screening: outbound wires and crypto withdrawals are screened against the OFAC SDN, EU
+// consolidated and UN consolidated lists before they are submitted; hits wait for compliance review.
+
+pub mod lists; // Loading OFAC/EU/UN watchlist files (XML, CSV)
+pub mod matching; // Transliteration, tokenization and Jaro-Winkler name scoring
+pub mod screening; // Indexed watchlists, party extraction from wires/MT103/pacs.008, hits
+pub mod cases; // Compliance cases: stopping, clearing and confirming payments with hits
+
+pub use cases::{clear_case, confirm_case, get_case, list_cases, open_case};
+pub use lists::{ListFile, LoadedList, WatchlistEntry, WatchlistSource};
+pub use screening::{
+    ensure_message_clear, parties_for_payment, parties_from_mt103, parties_from_pacs008, parties_from_wire, HitKind, PartyRole,
+    SanctionsConfig, SanctionsScreener, ScreeningHit, ScreeningParty,
+};
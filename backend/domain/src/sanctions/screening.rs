@@ -0,0 +1,580 @@
+// /home/inno/elights_jobes-research/backend/domain/src/sanctions/screening.rs
+// Screening of payment parties against the loaded watchlists. Names match fuzzily (see `matching`), BICs
+// match on their first 8 characters (institution + country + location, any branch), digital currency
+// addresses match exactly. Parties in, or banks from, a sanctioned country are hits too.
+use crate::beneficiaries::identity::is_valid_iban;
+use crate::config::{self, JsonConfig};
+use crate::error::DomainError;
+use crate::models::{TransactionType, WireDetails};
+use crate::payments::iso20022::Pacs008Details;
+use crate::payments::swift_mt::Mt103Details;
+use crate::sanctions::lists::{self, ListFile, LoadedList, WatchlistEntry, WatchlistSource};
+use crate::sanctions::matching;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+fn default_threshold() -> f64 {
+    0.90
+}
+
+/// Sanctions screening configuration, loaded from a JSON file.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SanctionsConfig {
+    #[serde(default)]
+    pub version: Option<String>, // Free text, logged when the configuration is loaded
+    #[serde(default = "default_threshold")]
+    pub name_match_threshold: f64, // Jaro-Winkler score from which a name is a hit
+    #[serde(default)]
+    pub sanctioned_countries: Vec<String>, // ISO 3166 alpha-2, comprehensively sanctioned jurisdictions
+    #[serde(default)]
+    pub lists: Vec<ListFile>,
+}
+
+impl JsonConfig for SanctionsConfig {
+    const NAME: &'static str = "sanctions configuration";
+
+    fn validate(&self) -> Result<(), DomainError> {
+        if !(0.5..=1.0).contains(&self.name_match_threshold) {
+            return Err(DomainError::Configuration("name_match_threshold must be between 0.5 and 1.0".to_string()));
+        }
+        if let Some(code) = self.sanctioned_countries.iter().find(|c| c.len() != 2 || !c.chars().all(|ch| ch.is_ascii_alphabetic())) {
+            return Err(DomainError::Configuration(format!("Invalid sanctioned country code '{}'", code)));
+        }
+        if self.lists.is_empty() {
+            return Err(DomainError::Configuration("Sanctions screening needs at least one list".to_string()));
+        }
+        for file in &self.lists {
+            let csv = file.path.to_lowercase().ends_with(".csv");
+            if file.source == WatchlistSource::UnConsolidated && csv {
+                return Err(DomainError::Configuration(format!("{}: the UN list is read from its XML export", file.path)));
+            }
+            if (file.alt_path.is_some() || file.add_path.is_some()) && !(file.source == WatchlistSource::OfacSdn && csv) {
+                return Err(DomainError::Configuration(format!("{}: alt_path/add_path only apply to the OFAC CSV files", file.path)));
+            }
+        }
+        Ok(())
+    }
+
+    fn summary(&self) -> String {
+        config::versioned_summary(self.version.as_deref(), self.lists.len(), "lists")
+    }
+}
+
+/// The part a screened party plays in the payment.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PartyRole {
+    Debtor,
+    Creditor,
+    DebtorAgent,
+    CreditorAgent,
+    IntermediaryAgent,
+    ReceiverCorrespondent,
+}
+
+/// One party of a payment, as far as it is known.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ScreeningParty {
+    pub role: PartyRole,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub bic: Option<String>,
+    #[serde(default)]
+    pub country: Option<String>, // ISO 3166 alpha-2
+    #[serde(default)]
+    pub crypto_address: Option<String>,
+}
+
+impl ScreeningParty {
+    pub fn new(role: PartyRole) -> Self {
+        ScreeningParty { role, name: None, bic: None, country: None, crypto_address: None }
+    }
+
+    fn with_name(mut self, name: Option<&str>) -> Self {
+        self.name = name.map(str::trim).filter(|n| !n.is_empty()).map(str::to_string);
+        self
+    }
+
+    fn with_bic(mut self, bic: Option<&str>) -> Self {
+        self.bic = bic.map(lists::normalize_bic).filter(|b| !b.is_empty());
+        self
+    }
+
+    fn with_country(mut self, country: Option<&str>) -> Self {
+        self.country = country.map(|c| c.trim().to_uppercase()).filter(|c| !c.is_empty());
+        self
+    }
+
+    fn is_empty(&self) -> bool {
+        self.name.is_none() && self.bic.is_none() && self.country.is_none() && self.crypto_address.is_none()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum HitKind {
+    Name,
+    Bic,
+    Country,
+    CryptoAddress,
+}
+
+/// A party matching a list entry (or a sanctioned country).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ScreeningHit {
+    pub role: PartyRole,
+    pub kind: HitKind,
+    pub value: String, // What the payment said
+    pub source: Option<WatchlistSource>, // None for country hits
+    pub entry_id: Option<String>,
+    pub matched: String, // The listed name, BIC, address or the country code
+    pub score: f64, // 1.0 for exact matches
+}
+
+struct IndexedName {
+    entry: usize,
+    name: String,
+    tokens: Vec<String>,
+}
+
+/// Watchlists loaded into memory and indexed for screening. Built once at startup and shared.
+pub struct SanctionsScreener {
+    entries: Vec<(WatchlistSource, WatchlistEntry)>,
+    names: Vec<IndexedName>,
+    name_index: HashMap<String, Vec<usize>>, // Blocking key -> names
+    bic_index: HashMap<String, Vec<usize>>, // BIC8 -> entries
+    crypto_index: HashMap<String, Vec<usize>>, // Address -> entries
+    threshold: f64,
+    sanctioned_countries: HashSet<String>,
+    lists: Vec<LoadedList>,
+}
+
+/// Case-insensitive address formats (hex, bech32) are compared lowercased; base58 and others as given.
+fn normalize_address(address: &str) -> String {
+    let address = address.trim();
+    let lower = address.to_lowercase();
+    if lower.starts_with("0x") || lower.starts_with("bc1") || lower.starts_with("tb1") {
+        lower
+    } else {
+        address.to_string()
+    }
+}
+
+fn bic8(bic: &str) -> Option<String> {
+    let bic = lists::normalize_bic(bic);
+    (bic.len() == 8 || bic.len() == 11).then(|| bic[..8].to_string())
+}
+
+impl SanctionsScreener {
+    /// Loads every configured list. Any unreadable or empty list fails the whole load.
+    pub fn load(config: &SanctionsConfig) -> Result<Self, DomainError> {
+        config.validate()?;
+        let mut entries = Vec::new();
+        let mut loaded = Vec::new();
+        for file in &config.lists {
+            let list = lists::load_list(file)?;
+            loaded.push(LoadedList { source: file.source, path: file.path.clone(), entries: list.len(), loaded_at: Utc::now() });
+            entries.extend(list.into_iter().map(|entry| (file.source, entry)));
+        }
+        let mut screener = Self::from_entries(entries, config.name_match_threshold, &config.sanctioned_countries);
+        screener.lists = loaded;
+        Ok(screener)
+    }
+
+    pub fn from_entries(entries: Vec<(WatchlistSource, WatchlistEntry)>, threshold: f64, sanctioned_countries: &[String]) -> Self {
+        let mut screener = SanctionsScreener {
+            entries: Vec::new(),
+            names: Vec::new(),
+            name_index: HashMap::new(),
+            bic_index: HashMap::new(),
+            crypto_index: HashMap::new(),
+            threshold,
+            sanctioned_countries: sanctioned_countries.iter().map(|c| c.to_uppercase()).collect(),
+            lists: Vec::new(),
+        };
+        for (i, (_, entry)) in entries.iter().enumerate() {
+            for name in &entry.names {
+                let tokens = matching::name_tokens(name);
+                if tokens.is_empty() {
+                    continue;
+                }
+                for key in matching::blocking_keys(&tokens) {
+                    screener.name_index.entry(key).or_default().push(screener.names.len());
+                }
+                screener.names.push(IndexedName { entry: i, name: name.clone(), tokens });
+            }
+            for bic in entry.bics.iter().filter_map(|b| bic8(b)) {
+                screener.bic_index.entry(bic).or_default().push(i);
+            }
+            for address in &entry.crypto_addresses {
+                screener.crypto_index.entry(normalize_address(address)).or_default().push(i);
+            }
+        }
+        screener.entries = entries;
+        screener
+    }
+
+    /// The list files behind this screener.
+    pub fn lists(&self) -> &[LoadedList] {
+        &self.lists
+    }
+
+    pub fn entry_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn entry_hit(&self, role: PartyRole, kind: HitKind, value: &str, entry: usize, matched: &str, score: f64) -> ScreeningHit {
+        let (source, listed) = &self.entries[entry];
+        ScreeningHit {
+            role,
+            kind,
+            value: value.to_string(),
+            source: Some(*source),
+            entry_id: Some(listed.entry_id.clone()),
+            matched: matched.to_string(),
+            score,
+        }
+    }
+
+    fn country_hit(&self, role: PartyRole, value: &str, country: &str) -> Option<ScreeningHit> {
+        let country = country.to_uppercase();
+        self.sanctioned_countries.contains(&country).then(|| ScreeningHit {
+            role,
+            kind: HitKind::Country,
+            value: value.to_string(),
+            source: None,
+            entry_id: None,
+            matched: country,
+            score: 1.0,
+        })
+    }
+
+    /// Best-scoring listed name per entry, for names at or above the threshold.
+    fn name_hits(&self, party: &ScreeningParty, name: &str) -> Vec<ScreeningHit> {
+        let tokens = matching::name_tokens(name);
+        let candidates: HashSet<usize> = matching::blocking_keys(&tokens)
+            .iter()
+            .filter_map(|key| self.name_index.get(key))
+            .flatten()
+            .copied()
+            .collect();
+        let mut best: HashMap<usize, (f64, &str)> = HashMap::new();
+        for candidate in candidates {
+            let listed = &self.names[candidate];
+            let score = matching::name_score(&tokens, &listed.tokens);
+            if score >= self.threshold && best.get(&listed.entry).map_or(true, |(s, _)| score > *s) {
+                best.insert(listed.entry, (score, listed.name.as_str()));
+            }
+        }
+        best.into_iter()
+            .map(|(entry, (score, matched))| self.entry_hit(party.role, HitKind::Name, name, entry, matched, score))
+            .collect()
+    }
+
+    /// Screens every party; an empty result means the payment is clear.
+    pub fn screen(&self, parties: &[ScreeningParty]) -> Vec<ScreeningHit> {
+        let mut hits = Vec::new();
+        for party in parties {
+            if let Some(name) = &party.name {
+                hits.extend(self.name_hits(party, name));
+            }
+            if let Some(bic) = &party.bic {
+                if let Some(key) = bic8(bic) {
+                    for &entry in self.bic_index.get(&key).into_iter().flatten() {
+                        let listed = self.entries[entry].1.bics.iter().find(|b| bic8(b).as_deref() == Some(key.as_str()));
+                        hits.push(self.entry_hit(party.role, HitKind::Bic, bic, entry, listed.map_or(key.as_str(), String::as_str), 1.0));
+                    }
+                    hits.extend(self.country_hit(party.role, bic, &key[4..6])); // BIC country code
+                }
+            }
+            if let Some(country) = &party.country {
+                hits.extend(self.country_hit(party.role, country, country));
+            }
+            if let Some(address) = &party.crypto_address {
+                for &entry in self.crypto_index.get(&normalize_address(address)).into_iter().flatten() {
+                    hits.push(self.entry_hit(party.role, HitKind::CryptoAddress, address, entry, address.trim(), 1.0));
+                }
+            }
+        }
+        let mut seen = HashSet::new();
+        hits.retain(|hit| seen.insert((hit.role, hit.kind, hit.source, hit.entry_id.clone(), hit.matched.clone())));
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits
+    }
+}
+
+/// Parties of an outbound wire: the beneficiary, its bank and any intermediary banks.
+pub fn parties_from_wire(details: &WireDetails) -> Vec<ScreeningParty> {
+    let account = details.account_number.trim();
+    let iban_country = is_valid_iban(account).then(|| &account[..2]);
+    let mut parties = vec![
+        ScreeningParty::new(PartyRole::Creditor).with_name(Some(&details.beneficiary_name)).with_country(iban_country),
+        ScreeningParty::new(PartyRole::CreditorAgent).with_bic(Some(&details.swift_bic)),
+    ];
+    for bank in details.intermediary_banks.iter().flatten() {
+        parties.push(
+            ScreeningParty::new(PartyRole::IntermediaryAgent)
+                .with_name(bank.name.as_deref())
+                .with_bic(bank.bic_swift.as_deref())
+                .with_country(bank.country_code.as_deref()),
+        );
+    }
+    parties.retain(|party| !party.is_empty());
+    parties
+}
+
+/// Name line of an MT 50F/59F party ("1/NAME"), or the first line of the other options.
+fn mt_name_line(lines: Option<&Vec<String>>) -> Option<&str> {
+    lines.and_then(|lines| lines.first()).map(|line| line.strip_prefix("1/").unwrap_or(line))
+}
+
+/// Parties of an MT103: ordering customer (50a), beneficiary customer (59a) and the banks (54A, 57A).
+pub fn parties_from_mt103(details: &Mt103Details) -> Vec<ScreeningParty> {
+    let mut beneficiary = ScreeningParty::new(PartyRole::Creditor).with_name(mt_name_line(details.beneficiary_address_lines.as_ref()));
+    if details.beneficiary_option == 'A' {
+        beneficiary = beneficiary.with_bic(Some(&details.beneficiary_identifier));
+    }
+    let mut parties = vec![
+        ScreeningParty::new(PartyRole::Debtor).with_name(mt_name_line(details.debtor_address_lines.as_ref())),
+        beneficiary,
+        ScreeningParty::new(PartyRole::CreditorAgent).with_bic(Some(&details.beneficiary_bank_bic)),
+        ScreeningParty::new(PartyRole::ReceiverCorrespondent).with_bic(details.receiver_correspondent_bic.as_deref()),
+    ];
+    parties.retain(|party| !party.is_empty());
+    parties
+}
+
+/// Parties of a pacs.008: debtor and creditor with their address countries, and both agents.
+pub fn parties_from_pacs008(details: &Pacs008Details) -> Vec<ScreeningParty> {
+    let mut parties = vec![
+        ScreeningParty::new(PartyRole::Debtor)
+            .with_name(Some(&details.debtor_name))
+            .with_country(details.debtor_address.as_ref().and_then(|a| a.country.as_deref())),
+        ScreeningParty::new(PartyRole::DebtorAgent).with_bic(Some(&details.debtor_agent_bic)),
+        ScreeningParty::new(PartyRole::CreditorAgent).with_bic(Some(&details.creditor_agent_bic)),
+        ScreeningParty::new(PartyRole::Creditor)
+            .with_name(Some(&details.creditor_name))
+            .with_country(details.creditor_address.as_ref().and_then(|a| a.country.as_deref())),
+    ];
+    parties.retain(|party| !party.is_empty());
+    parties
+}
+
+/// Screens the parties of a built payment message right before it is sent. The message carries parties the
+/// request screening never saw (ordering customer, correspondents), so any hit here blocks the submission.
+pub fn ensure_message_clear(screener: &SanctionsScreener, parties: &[ScreeningParty]) -> Result<(), DomainError> {
+    let hits = screener.screen(parties);
+    if hits.is_empty() {
+        return Ok(());
+    }
+    let summary = hits.iter()
+        .map(|hit| format!("{:?} '{}' matches '{}'", hit.role, hit.value, hit.matched))
+        .collect::<Vec<_>>()
+        .join("; ");
+    Err(DomainError::Validation(format!("Payment message blocked by sanctions screening: {}", summary)))
+}
+
+/// The receiving address of a crypto withdrawal.
+pub fn crypto_party(address: &str) -> ScreeningParty {
+    let mut party = ScreeningParty::new(PartyRole::Creditor);
+    party.crypto_address = Some(address.trim().to_string()).filter(|a| !a.is_empty());
+    party
+}
+
+/// Parties to screen for an outbound payment; empty for rails that are not screened.
+pub fn parties_for_payment(
+    payment_type: &TransactionType,
+    wire_details: Option<&WireDetails>,
+    crypto_address: Option<&str>,
+) -> Vec<ScreeningParty> {
+    match payment_type {
+        TransactionType::WireOutbound => wire_details.map(parties_from_wire).unwrap_or_default(),
+        TransactionType::CryptoBtcSend | TransactionType::CryptoXmrSend => {
+            crypto_address.map(|address| vec![crypto_party(address)]).unwrap_or_default()
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::BankIdentifier;
+
+    fn screener() -> SanctionsScreener {
+        let entries = vec![
+            (
+                WatchlistSource::OfacSdn,
+                WatchlistEntry {
+                    entry_id: "306".to_string(),
+                    entity_type: "Entity".to_string(),
+                    names: vec!["BANK MELLI IRAN".to_string()],
+                    bics: vec!["MELIIRTH".to_string()],
+                    ..Default::default()
+                },
+            ),
+            (
+                WatchlistSource::EuConsolidated,
+                WatchlistEntry {
+                    entry_id: "13".to_string(),
+                    entity_type: "person".to_string(),
+                    names: vec!["Владимир Владимирович ПУТИН".to_string(), "Vladimir Vladimirovich PUTIN".to_string()],
+                    ..Default::default()
+                },
+            ),
+            (
+                WatchlistSource::OfacSdn,
+                WatchlistEntry {
+                    entry_id: "9001".to_string(),
+                    names: vec!["Ivan PETROV".to_string()],
+                    crypto_addresses: vec!["0x7F367cc41522ce07553e823bf3be79a889debe1b".to_string()],
+                    ..Default::default()
+                },
+            ),
+        ];
+        SanctionsScreener::from_entries(entries, 0.90, &["KP".to_string(), "IR".to_string()])
+    }
+
+    fn wire(name: &str, bic: &str) -> WireDetails {
+        WireDetails {
+            swift_bic: bic.to_string(),
+            account_number: "DE89370400440532013000".to_string(),
+            beneficiary_name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_name_hits_across_scripts_and_order() {
+        let screener = screener();
+        let hits = screener.screen(&parties_from_wire(&wire("Putin, Vladimir", "DEUTDEFF")));
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].kind, HitKind::Name);
+        assert_eq!(hits[0].entry_id.as_deref(), Some("13"));
+        assert_eq!(hits[0].role, PartyRole::Creditor);
+
+        let hits = screener.screen(&parties_from_wire(&wire("Wladimir Putin", "DEUTDEFF")));
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].score >= 0.90 && hits[0].score < 1.0);
+
+        assert!(screener.screen(&parties_from_wire(&wire("Vladimir Petrenko", "DEUTDEFF"))).is_empty());
+        assert!(screener.screen(&parties_from_wire(&wire("Acme Trading GmbH", "DEUTDEFF"))).is_empty());
+    }
+
+    #[test]
+    fn test_names_spelled_differently_in_every_token_are_compared() {
+        // No token shares its first two letters with the listed ones: only the phonetic key finds the entry
+        let entry = WatchlistEntry { entry_id: "7".to_string(), names: vec!["PUTIN, VLADIMIR".to_string()], ..Default::default() };
+        let screener = SanctionsScreener::from_entries(vec![(WatchlistSource::OfacSdn, entry)], 0.90, &[]);
+        let hits = screener.screen(&parties_from_wire(&wire("Wladimir Poutine", "DEUTDEFF")));
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].entry_id.as_deref(), Some("7"));
+        assert_eq!(hits[0].matched, "PUTIN, VLADIMIR");
+    }
+
+    #[test]
+    fn test_bic_country_and_crypto_hits() {
+        let screener = screener();
+        let mut details = wire("Acme Trading", "DEUTDEFF");
+        details.intermediary_banks = Some(vec![BankIdentifier {
+            name: None,
+            bic_swift: Some("meli ir th 100".to_string()),
+            clearing_code: None,
+            country_code: None,
+        }]);
+        let hits = screener.screen(&parties_from_wire(&details));
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().all(|h| h.role == PartyRole::IntermediaryAgent));
+        assert!(hits.iter().any(|h| h.kind == HitKind::Bic && h.entry_id.as_deref() == Some("306")));
+        assert!(hits.iter().any(|h| h.kind == HitKind::Country && h.matched == "IR"));
+
+        let mut korea = ScreeningParty::new(PartyRole::Creditor);
+        korea.country = Some("kp".to_string());
+        assert_eq!(screener.screen(&[korea])[0].kind, HitKind::Country);
+
+        let hits = screener.screen(&[crypto_party(" 0x7f367cc41522ce07553e823bf3be79a889debe1b ")]);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].kind, HitKind::CryptoAddress);
+        assert!(screener.screen(&[crypto_party("0x0000000000000000000000000000000000000000")]).is_empty());
+    }
+
+    fn pacs008(debtor: &str, creditor: &str, creditor_agent_bic: &str) -> Pacs008Details {
+        Pacs008Details {
+            message_id: "MSG-1".to_string(),
+            initiating_party_name: "Elights Bank".to_string(),
+            number_of_txs: 1,
+            settlement_method: "INDA".to_string(),
+            instruction_id: None,
+            end_to_end_id: "E2E-1".to_string(),
+            transaction_id: "TX-1".to_string(),
+            currency: "EUR".to_string(),
+            amount: rust_decimal::Decimal::new(250_000, 2),
+            debtor_name: debtor.to_string(),
+            debtor_address: None,
+            debtor_account_iban: Some("DE89370400440532013000".to_string()),
+            debtor_account_other_id: None,
+            debtor_agent_bic: "DEUTDEFF".to_string(),
+            creditor_agent_bic: creditor_agent_bic.to_string(),
+            creditor_name: creditor.to_string(),
+            creditor_address: None,
+            creditor_account_iban: None,
+            creditor_account_other_id: Some("123456789".to_string()),
+            remittance_unstructured: None,
+        }
+    }
+
+    fn mt103(debtor: &str, beneficiary: &str, beneficiary_bank_bic: &str) -> Mt103Details {
+        Mt103Details {
+            sender_reference: "REF-1".to_string(),
+            bank_operation_code: "CRED".to_string(),
+            value_date: chrono::NaiveDate::from_ymd_opt(2025, 4, 1).unwrap(),
+            currency: "USD".to_string(),
+            amount: rust_decimal::Decimal::new(250_000, 2),
+            debtor_option: 'F',
+            debtor_identifier: "/DE89370400440532013000".to_string(),
+            debtor_address_lines: Some(vec![format!("1/{}", debtor)]),
+            receiver_correspondent_bic: None,
+            beneficiary_bank_bic: beneficiary_bank_bic.to_string(),
+            beneficiary_option: 'F',
+            beneficiary_identifier: "/123456789".to_string(),
+            beneficiary_address_lines: Some(vec![format!("1/{}", beneficiary)]),
+            remittance_info_lines: None,
+            details_of_charges: "SHA".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_sanctioned_party_in_built_message_blocks_submission() {
+        let screener = screener();
+        assert!(ensure_message_clear(&screener, &parties_from_pacs008(&pacs008("Acme Trading", "Globex Ltd", "BARCGB22"))).is_ok());
+        assert!(ensure_message_clear(&screener, &parties_from_mt103(&mt103("Acme Trading", "Globex Ltd", "BARCGB22"))).is_ok());
+
+        // The ordering customer only appears in the message, not in the beneficiary details screened at request time
+        let err = ensure_message_clear(&screener, &parties_from_pacs008(&pacs008("Vladimir Putin", "Globex Ltd", "BARCGB22")));
+        assert!(matches!(err, Err(DomainError::Validation(ref msg)) if msg.contains("Debtor")));
+
+        let err = ensure_message_clear(&screener, &parties_from_mt103(&mt103("Acme Trading", "Globex Ltd", "MELIIRTH")));
+        assert!(matches!(err, Err(DomainError::Validation(ref msg)) if msg.contains("CreditorAgent")));
+        let err = ensure_message_clear(&screener, &parties_from_mt103(&mt103("Acme Trading", "Putin, Vladimir", "BARCGB22")));
+        assert!(matches!(err, Err(DomainError::Validation(ref msg)) if msg.contains("Creditor ")));
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let config = config::parse_json::<SanctionsConfig>(
+            r#"{"version": "2025-04", "sanctioned_countries": ["IR", "KP"],
+                "lists": [{"source": "OFAC_SDN", "path": "sdn.csv", "alt_path": "alt.csv"}, {"source": "UN_CONSOLIDATED", "path": "un.xml"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(config.name_match_threshold, 0.90);
+        assert_eq!(config.lists[0].alt_path.as_deref(), Some("alt.csv"));
+
+        assert!(config::parse_json::<SanctionsConfig>(r#"{"lists": []}"#).is_err());
+        assert!(config::parse_json::<SanctionsConfig>(r#"{"lists": [{"source": "UN_CONSOLIDATED", "path": "un.csv"}]}"#).is_err());
+        assert!(config::parse_json::<SanctionsConfig>(r#"{"lists": [{"source": "EU_CONSOLIDATED", "path": "eu.xml", "add_path": "x.csv"}]}"#).is_err());
+        assert!(config::parse_json::<SanctionsConfig>(r#"{"name_match_threshold": 1.5, "lists": [{"source": "OFAC_SDN", "path": "sdn.xml"}]}"#).is_err());
+        assert!(config::parse_json::<SanctionsConfig>(r#"{"sanctioned_countries": ["IRN"], "lists": [{"source": "OFAC_SDN", "path": "sdn.xml"}]}"#).is_err());
+    }
+}
@@ -30,6 +30,20 @@ pub fn log_db_audit_event(
         AuditTargetType::Transaction => "Transaction",
         AuditTargetType::System => "System",
         AuditTargetType::Config => "Config",
+        AuditTargetType::PaymentSchedule => "PaymentSchedule",
+        AuditTargetType::PayoutBatch => "PayoutBatch",
+        AuditTargetType::Beneficiary => "Beneficiary",
+        AuditTargetType::InboundSuspense => "InboundSuspense",
+        AuditTargetType::WebhookEvent => "WebhookEvent",
+        AuditTargetType::Reconciliation => "Reconciliation",
+        AuditTargetType::EodPositionSnapshot => "EodPositionSnapshot",
+        AuditTargetType::InterestRate => "InterestRate",
+        AuditTargetType::TransactionLimit => "TransactionLimit",
+        AuditTargetType::SanctionsCase => "SanctionsCase",
+        AuditTargetType::AmlCase => "AmlCase",
+        AuditTargetType::CtrReport => "CtrReport",
+        AuditTargetType::TravelRuleTransfer => "TravelRuleTransfer",
+        AuditTargetType::FraudEvaluation => "FraudEvaluation",
     });
 
     let new_log = NewAuditLog {
@@ -0,0 +1,269 @@
+// /home/inno/elights_jobes-research/backend/domain/src/services/idempotency.rs
+use crate::error::DomainError;
+use crate::models::{CompleteIdempotencyKey, IdempotencyKey, IdempotencyKeyStatus, NewIdempotencyKey};
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use serde_json::Value as JsonValue;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// How long a stored key (and its response) is honoured before it can be reused.
+pub const IDEMPOTENCY_KEY_TTL_HOURS: i64 = 24;
+/// How long a request may hold an IN_PROGRESS key before a retry presumes it crashed and takes the key over.
+pub const IDEMPOTENCY_LOCK_SECONDS: i64 = 120;
+/// Upper bound on the header value, matches the VARCHAR column size.
+pub const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// Result of claiming an idempotency key at the start of a request.
+#[derive(Debug)]
+pub enum IdempotencyOutcome {
+    /// First time this key is seen: execute the request, then call `complete_idempotent_request`.
+    Proceed(IdempotencyKey),
+    /// Key was already used for an identical request: return the stored response as-is.
+    Replay {
+        status_code: u16,
+        body: JsonValue,
+    },
+}
+
+/// Body field that may carry the key instead of the header; it is not part of the request itself.
+const BODY_KEY_FIELD: &str = "idempotency_key";
+
+/// Computes the SHA-256 (hex) fingerprint of a request body.
+/// `serde_json::Value` keeps object keys sorted, so semantically equal JSON bodies hash the same.
+/// A top-level `idempotency_key` field is left out, so a payload replayed with the key in the header instead of
+/// the body fingerprints the same.
+pub fn fingerprint_request(scope: &str, body: &JsonValue) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(scope.as_bytes());
+    hasher.update(b"\n");
+    match body {
+        JsonValue::Object(map) if map.contains_key(BODY_KEY_FIELD) => {
+            let mut map = map.clone();
+            map.remove(BODY_KEY_FIELD);
+            hasher.update(JsonValue::Object(map).to_string().as_bytes());
+        }
+        _ => hasher.update(body.to_string().as_bytes()),
+    }
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Validates the client supplied key (non-empty, bounded, visible ASCII only).
+pub fn validate_idempotency_key(key: &str) -> Result<(), DomainError> {
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+        return Err(DomainError::Validation(format!(
+            "Idempotency-Key must be between 1 and {} characters", MAX_IDEMPOTENCY_KEY_LEN
+        )));
+    }
+    if !key.chars().all(|c| c.is_ascii_graphic()) {
+        return Err(DomainError::Validation("Idempotency-Key contains invalid characters".to_string()));
+    }
+    Ok(())
+}
+
+/// What a request presenting an already stored key gets.
+#[derive(Debug, PartialEq, Eq)]
+enum StoredKey {
+    Replay,   // Completed: return the stored response
+    TakeOver, // IN_PROGRESS past its lease: the first request died, run this one in its place
+}
+
+fn stored_key_outcome(existing: &IdempotencyKey, request_fingerprint: &str, now: DateTime<Utc>) -> Result<StoredKey, DomainError> {
+    if existing.request_fingerprint != request_fingerprint {
+        log::warn!("Idempotency key {} reused with a different request body (scope {})", existing.idempotency_key, existing.scope);
+        return Err(DomainError::IdempotencyConflict(
+            "Idempotency-Key was already used with a different request".to_string(),
+        ));
+    }
+    if existing.status == IdempotencyKeyStatus::Completed.as_str() {
+        return Ok(StoredKey::Replay);
+    }
+    match existing.locked_until {
+        Some(locked_until) if locked_until > now => Err(DomainError::IdempotencyConflict(
+            "A request with this Idempotency-Key is still being processed".to_string(),
+        )),
+        _ => Ok(StoredKey::TakeOver),
+    }
+}
+
+/// Claims `key` for (`user_id`, `scope`) or returns the stored outcome of a previous identical request.
+/// A key reused with a different fingerprint, or while the first request still holds its lease, is rejected;
+/// an IN_PROGRESS key whose lease (`IDEMPOTENCY_LOCK_SECONDS`) lapsed is taken over by the retry.
+pub fn begin_idempotent_request(
+    conn: &mut PgConnection,
+    user_id: Option<Uuid>,
+    scope: &str,
+    key: &str,
+    request_fingerprint: &str,
+) -> Result<IdempotencyOutcome, DomainError> {
+    use crate::schema::idempotency_keys::dsl as ik;
+
+    validate_idempotency_key(key)?;
+    let now = Utc::now();
+
+    conn.transaction(|conn| {
+        // Expired keys are free to be reused
+        diesel::delete(
+            ik::idempotency_keys
+                .filter(ik::scope.eq(scope))
+                .filter(ik::user_id.is_not_distinct_from(user_id))
+                .filter(ik::idempotency_key.eq(key))
+                .filter(ik::expires_at.lt(now)),
+        )
+        .execute(conn)?;
+
+        let new_key = NewIdempotencyKey {
+            idempotency_key: key,
+            user_id,
+            scope,
+            request_fingerprint,
+            status: IdempotencyKeyStatus::InProgress.as_str(),
+            locked_until: Some(now + Duration::seconds(IDEMPOTENCY_LOCK_SECONDS)),
+            expires_at: now + Duration::hours(IDEMPOTENCY_KEY_TTL_HOURS),
+        };
+        // The unique index on (scope, user, key) makes concurrent first requests race safely
+        let inserted = diesel::insert_into(ik::idempotency_keys)
+            .values(&new_key)
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+
+        let existing: IdempotencyKey = ik::idempotency_keys
+            .filter(ik::scope.eq(scope))
+            .filter(ik::user_id.is_not_distinct_from(user_id))
+            .filter(ik::idempotency_key.eq(key))
+            .for_update()
+            .first(conn)?;
+
+        if inserted == 1 {
+            log::debug!("Claimed idempotency key {} for scope {}", key, scope);
+            return Ok(IdempotencyOutcome::Proceed(existing));
+        }
+
+        if stored_key_outcome(&existing, request_fingerprint, now)? == StoredKey::TakeOver {
+            log::warn!("Taking over idempotency key {} (scope {}) abandoned by an earlier request", key, scope);
+            let existing: IdempotencyKey = diesel::update(ik::idempotency_keys.find(existing.key_id))
+                .set(ik::locked_until.eq(Some(now + Duration::seconds(IDEMPOTENCY_LOCK_SECONDS))))
+                .get_result(conn)?;
+            return Ok(IdempotencyOutcome::Proceed(existing));
+        }
+
+        log::info!("Replaying stored response for idempotency key {} (scope {})", key, scope);
+        Ok(IdempotencyOutcome::Replay {
+            status_code: existing.response_status_code.unwrap_or(200) as u16,
+            body: existing.response_body.unwrap_or(JsonValue::Null),
+        })
+    })
+}
+
+/// Stores the response produced for a claimed key so later replays can return it.
+pub fn complete_idempotent_request(
+    conn: &mut PgConnection,
+    key_id: i64,
+    status_code: u16,
+    body: &JsonValue,
+    transaction_id: Option<Uuid>,
+) -> Result<(), DomainError> {
+    use crate::schema::idempotency_keys::dsl as ik;
+
+    let update = CompleteIdempotencyKey {
+        status: IdempotencyKeyStatus::Completed.as_str(),
+        response_status_code: Some(status_code as i32),
+        response_body: Some(body.clone()),
+        transaction_id,
+    };
+    diesel::update(ik::idempotency_keys.find(key_id))
+        .set(&update)
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Releases a claimed key after a request failed without side effects, so the client may retry it.
+pub fn release_idempotent_request(conn: &mut PgConnection, key_id: i64) -> Result<(), DomainError> {
+    use crate::schema::idempotency_keys::dsl as ik;
+
+    diesel::delete(
+        ik::idempotency_keys
+            .find(key_id)
+            .filter(ik::status.eq(IdempotencyKeyStatus::InProgress.as_str())),
+    )
+    .execute(conn)?;
+    Ok(())
+}
+
+/// Derives the key forwarded to an external gateway for one step of a transaction.
+/// Deterministic, so a retried step presents the same key to the provider.
+pub fn derive_gateway_idempotency_key(client_key: Option<&str>, transaction_id: Uuid, step: &str) -> String {
+    match client_key {
+        Some(key) => format!("{}:{}", key, step),
+        None => format!("{}:{}", transaction_id, step),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_fingerprint_ignores_key_order() {
+        let a = json!({"amount": "10.00", "currency": "USD"});
+        let b: JsonValue = serde_json::from_str(r#"{"currency":"USD","amount":"10.00"}"#).unwrap();
+        assert_eq!(fingerprint_request("PAYMENT_INITIATE", &a), fingerprint_request("PAYMENT_INITIATE", &b));
+        assert_ne!(
+            fingerprint_request("PAYMENT_INITIATE", &a),
+            fingerprint_request("PAYMENT_INITIATE", &json!({"amount": "10.01", "currency": "USD"}))
+        );
+        assert_ne!(fingerprint_request("PAYMENT_INITIATE", &a), fingerprint_request("CRYPTO_WITHDRAWAL", &a));
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_body_key() {
+        let header_key = json!({"amount": "10.00", "currency": "USD", "idempotency_key": null});
+        let body_key = json!({"amount": "10.00", "currency": "USD", "idempotency_key": "order-42"});
+        let bare = json!({"amount": "10.00", "currency": "USD"});
+        assert_eq!(fingerprint_request("PAYMENT_INITIATE", &header_key), fingerprint_request("PAYMENT_INITIATE", &body_key));
+        assert_eq!(fingerprint_request("PAYMENT_INITIATE", &body_key), fingerprint_request("PAYMENT_INITIATE", &bare));
+    }
+
+    fn stored_key(status: IdempotencyKeyStatus, locked_until: Option<DateTime<Utc>>) -> IdempotencyKey {
+        let now = Utc::now();
+        IdempotencyKey {
+            key_id: 1,
+            idempotency_key: "order-42".to_string(),
+            user_id: None,
+            scope: "PAYMENT_INITIATE".to_string(),
+            request_fingerprint: "abc".to_string(),
+            status: status.as_str().to_string(),
+            response_status_code: None,
+            response_body: None,
+            transaction_id: None,
+            locked_until,
+            created_at: now,
+            updated_at: now,
+            expires_at: now + Duration::hours(IDEMPOTENCY_KEY_TTL_HOURS),
+        }
+    }
+
+    #[test]
+    fn test_stale_in_progress_key_is_taken_over() {
+        let now = Utc::now();
+        let running = stored_key(IdempotencyKeyStatus::InProgress, Some(now + Duration::seconds(30)));
+        assert!(matches!(stored_key_outcome(&running, "abc", now), Err(DomainError::IdempotencyConflict(_))));
+
+        // The first request crashed between begin and complete: its lease lapses and a retry runs instead
+        let abandoned = stored_key(IdempotencyKeyStatus::InProgress, Some(now - Duration::seconds(1)));
+        assert_eq!(stored_key_outcome(&abandoned, "abc", now).unwrap(), StoredKey::TakeOver);
+        assert!(stored_key_outcome(&abandoned, "other", now).is_err());
+
+        let completed = stored_key(IdempotencyKeyStatus::Completed, Some(now - Duration::seconds(1)));
+        assert_eq!(stored_key_outcome(&completed, "abc", now).unwrap(), StoredKey::Replay);
+    }
+
+    #[test]
+    fn test_key_validation() {
+        assert!(validate_idempotency_key("3f1c2a9e-order-42").is_ok());
+        assert!(validate_idempotency_key("").is_err());
+        assert!(validate_idempotency_key("has space").is_err());
+        assert!(validate_idempotency_key(&"k".repeat(MAX_IDEMPOTENCY_KEY_LEN + 1)).is_err());
+    }
+}
@@ -2,4 +2,5 @@
 pub mod analytics;
 pub mod fraud_detection;
 pub mod reporting;
+pub mod idempotency; // Idempotency-Key storage and replay
 // Add other domain-level services here (e.g., NotificationService, CurrencyConversionService)
\ No newline at end of file
@@ -0,0 +1,231 @@
+// /home/inno/elights_jobes-research/backend/domain/src/travel_rule/config.rs
+// Travel Rule settings, loaded from a JSON file: the threshold per currency, what happens to a withdrawal with
+// missing data, how long a held withdrawal waits, who we are, and the directory of counterparty VASPs with the
+// protocol each one speaks.
+use super::ivms101::{
+    Address, AddressType, LegalPerson, LegalPersonName, LegalPersonNameId, LegalPersonNameType, NationalIdentification,
+    NationalIdentifierType, Person,
+};
+use crate::config::{self, JsonConfig};
+use crate::error::DomainError;
+use chrono::Duration;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+fn default_hold_hours() -> i64 {
+    72
+}
+
+fn default_poll_interval_secs() -> i64 {
+    60
+}
+
+/// What happens to a withdrawal above the threshold when required data is missing.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MissingDataAction {
+    Block, // Refused outright, nothing is created
+    #[default]
+    Hold, // Held in RequiresAction until the customer supplies the data (or the hold expires)
+}
+
+/// Protocol adapters a counterparty can be reached through.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum VaspProtocol {
+    HttpsJson, // IVMS101 JSON posted to the counterparty's endpoint
+    Loopback, // Local stand-in that answers for the counterparty (sandbox only)
+}
+
+impl VaspProtocol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VaspProtocol::HttpsJson => "HTTPS_JSON",
+            VaspProtocol::Loopback => "LOOPBACK",
+        }
+    }
+}
+
+/// Our own identity as the originating VASP.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct VaspIdentity {
+    pub name: String, // Legal name
+    #[serde(default)]
+    pub lei: Option<String>, // Legal Entity Identifier
+    pub country: String, // ISO 3166-1 alpha-2, country of registration
+    #[serde(default)]
+    pub address_lines: Vec<String>,
+}
+
+/// A VASP we exchange Travel Rule data with.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CounterpartyVasp {
+    pub vasp_id: String, // Our directory key, given by the customer when withdrawing
+    pub name: String,
+    #[serde(default)]
+    pub lei: Option<String>,
+    pub country: String,
+    pub protocol: VaspProtocol,
+    #[serde(default)]
+    pub endpoint: Option<String>, // Base URL, for HTTPS_JSON
+}
+
+/// Travel Rule configuration.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TravelRuleConfig {
+    #[serde(default)]
+    pub version: Option<String>, // Free text, logged when the configuration is loaded
+    #[serde(default)]
+    pub thresholds: BTreeMap<String, Decimal>, // Currency -> amount from which data is required; unlisted: always
+    #[serde(default)]
+    pub on_missing_data: MissingDataAction,
+    #[serde(default = "default_hold_hours")]
+    pub hold_hours: i64, // Held withdrawals expire after this
+    #[serde(default = "default_poll_interval_secs")]
+    pub poll_interval_secs: i64, // How often an undecided exchange is asked again
+    #[serde(default)]
+    pub sandbox: bool, // Allows LOOPBACK counterparties
+    pub originating_vasp: VaspIdentity,
+    #[serde(default)]
+    pub counterparty_vasps: Vec<CounterpartyVasp>,
+}
+
+impl TravelRuleConfig {
+    /// Whether a withdrawal needs Travel Rule data.
+    pub fn applies_to(&self, currency: &str, amount: Decimal) -> bool {
+        self.thresholds.get(currency).map_or(true, |threshold| amount >= *threshold)
+    }
+
+    pub fn counterparty(&self, vasp_id: &str) -> Option<&CounterpartyVasp> {
+        self.counterparty_vasps.iter().find(|vasp| vasp.vasp_id == vasp_id)
+    }
+
+    pub fn hold_window(&self) -> Duration {
+        Duration::hours(self.hold_hours)
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::seconds(self.poll_interval_secs)
+    }
+}
+
+impl JsonConfig for TravelRuleConfig {
+    const NAME: &'static str = "Travel Rule configuration";
+
+    fn validate(&self) -> Result<(), DomainError> {
+        let invalid = |reason: String| DomainError::Configuration(format!("Travel Rule configuration: {}", reason));
+        for (currency, threshold) in &self.thresholds {
+            if *threshold < Decimal::ZERO {
+                return Err(invalid(format!("threshold for {} is negative", currency)));
+            }
+        }
+        if self.hold_hours <= 0 || self.poll_interval_secs <= 0 {
+            return Err(invalid("hold_hours and poll_interval_secs must be positive".to_string()));
+        }
+        if self.originating_vasp.name.trim().is_empty() || self.originating_vasp.country.len() != 2 {
+            return Err(invalid("originating VASP needs a name and a two-letter country".to_string()));
+        }
+        let mut ids = HashSet::new();
+        for vasp in &self.counterparty_vasps {
+            if vasp.vasp_id.trim().is_empty() || !ids.insert(vasp.vasp_id.as_str()) {
+                return Err(invalid(format!("counterparty VASP id '{}' is empty or duplicated", vasp.vasp_id)));
+            }
+            match vasp.protocol {
+                VaspProtocol::HttpsJson if !vasp.endpoint.as_deref().is_some_and(|e| e.starts_with("https://")) => {
+                    return Err(invalid(format!("{} needs an https:// endpoint", vasp.vasp_id)));
+                }
+                VaspProtocol::Loopback if !self.sandbox => {
+                    return Err(invalid(format!("{} uses LOOPBACK outside a sandbox", vasp.vasp_id)));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "{} ({} thresholds, {} counterparty VASPs)",
+            self.version.as_deref().unwrap_or("unversioned"),
+            self.thresholds.len(),
+            self.counterparty_vasps.len()
+        )
+    }
+}
+
+/// A VASP as an IVMS101 legal person.
+fn vasp_person(name: &str, lei: Option<&str>, country: &str, address_lines: &[String]) -> Person {
+    Person::LegalPerson(LegalPerson {
+        name: LegalPersonName {
+            name_identifier: vec![LegalPersonNameId {
+                legal_person_name: name.to_string(),
+                legal_person_name_identifier_type: LegalPersonNameType::LegalName,
+            }],
+        },
+        geographic_address: if address_lines.is_empty() {
+            Vec::new()
+        } else {
+            vec![Address {
+                address_type: AddressType::Business,
+                street_name: None,
+                building_number: None,
+                address_line: address_lines.to_vec(),
+                post_code: None,
+                town_name: None,
+                country_sub_division: None,
+                country: country.to_string(),
+            }]
+        },
+        customer_number: None,
+        national_identification: lei.map(|lei| NationalIdentification {
+            national_identifier: lei.to_string(),
+            national_identifier_type: NationalIdentifierType::LegalEntityIdentifier,
+            country_of_issue: None,
+            registration_authority: None,
+        }),
+        country_of_registration: Some(country.to_string()),
+    })
+}
+
+impl VaspIdentity {
+    pub fn to_person(&self) -> Person {
+        vasp_person(&self.name, self.lei.as_deref(), &self.country, &self.address_lines)
+    }
+}
+
+impl CounterpartyVasp {
+    pub fn to_person(&self) -> Person {
+        vasp_person(&self.name, self.lei.as_deref(), &self.country, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    const US: &str = r#""originating_vasp": {"name": "Elights Bank", "lei": "5493001KJTIIGC8Y1R12", "country": "US"}"#;
+
+    #[test]
+    fn test_thresholds_and_directory() {
+        let config = config::parse_json::<TravelRuleConfig>(&format!(
+            r#"{{"thresholds": {{"BTC": "0.01"}}, {}, "counterparty_vasps": [
+                {{"vasp_id": "acme", "name": "Acme Exchange", "country": "DE", "protocol": "HTTPS_JSON", "endpoint": "https://tr.acme.example"}}]}}"#,
+            US
+        ))
+        .unwrap();
+        assert_eq!(config.on_missing_data, MissingDataAction::Hold);
+        assert!(!config.applies_to("BTC", dec!(0.0099)));
+        assert!(config.applies_to("BTC", dec!(0.01)));
+        assert!(config.applies_to("XMR", dec!(0.000001))); // Unlisted currencies always need data
+        assert_eq!(config.counterparty("acme").map(|v| v.protocol), Some(VaspProtocol::HttpsJson));
+        assert_eq!(config.originating_vasp.to_person().legal_name().as_deref(), Some("Elights Bank"));
+
+        let loopback = r#"[{"vasp_id": "local", "name": "Local", "country": "US", "protocol": "LOOPBACK"}]"#;
+        assert!(config::parse_json::<TravelRuleConfig>(&format!(r#"{{{}, "counterparty_vasps": {}}}"#, US, loopback)).is_err());
+        assert!(config::parse_json::<TravelRuleConfig>(&format!(r#"{{{}, "sandbox": true, "counterparty_vasps": {}}}"#, US, loopback)).is_ok());
+        let plain_http = r#"[{"vasp_id": "x", "name": "X", "country": "US", "protocol": "HTTPS_JSON", "endpoint": "http://x"}]"#;
+        assert!(config::parse_json::<TravelRuleConfig>(&format!(r#"{{{}, "counterparty_vasps": {}}}"#, US, plain_http)).is_err());
+    }
+}
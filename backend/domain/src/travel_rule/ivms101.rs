@@ -0,0 +1,403 @@
+// /home/inno/elights_jobes-research/backend/domain/src/travel_rule/ivms101.rs
+// IVMS101 (interVASP Messaging Standard) data model, in its JSON form, and the FATF Recommendation 16 check of
+// which required data elements are missing. Originators need a name, an account number and one of: address,
+// national identity number, customer identification number, or date and place of birth. Beneficiaries need a
+// name and an account number.
+use crate::error::DomainError;
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Kind of a natural person's name.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum NaturalPersonNameType {
+    #[serde(rename = "ALIA")]
+    Alias,
+    #[serde(rename = "BIRT")]
+    NameAtBirth,
+    #[serde(rename = "MAID")]
+    MaidenName,
+    #[serde(rename = "LEGL")]
+    LegalName,
+    #[serde(rename = "MISC")]
+    Unspecified,
+}
+
+/// Kind of a legal person's name.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum LegalPersonNameType {
+    #[serde(rename = "LEGL")]
+    LegalName,
+    #[serde(rename = "SHRT")]
+    ShortName,
+    #[serde(rename = "TRAD")]
+    TradingName,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum AddressType {
+    #[serde(rename = "HOME")]
+    Residential,
+    #[serde(rename = "BIZZ")]
+    Business,
+    #[serde(rename = "GEOG")]
+    Geographic,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum NationalIdentifierType {
+    #[serde(rename = "ARNU")]
+    AlienRegistrationNumber,
+    #[serde(rename = "CCPT")]
+    PassportNumber,
+    #[serde(rename = "RAID")]
+    RegistrationAuthorityId,
+    #[serde(rename = "DRLC")]
+    DriverLicenseNumber,
+    #[serde(rename = "FIIN")]
+    ForeignInvestmentIdentityNumber,
+    #[serde(rename = "TXID")]
+    TaxIdentificationNumber,
+    #[serde(rename = "SOCS")]
+    SocialSecurityNumber,
+    #[serde(rename = "IDCD")]
+    IdentityCardNumber,
+    #[serde(rename = "LEIX")]
+    LegalEntityIdentifier,
+    #[serde(rename = "MISC")]
+    Unspecified,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NaturalPersonNameId {
+    pub primary_identifier: String, // Family name, or the full name when it cannot be split
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secondary_identifier: Option<String>, // Given names
+    pub name_identifier_type: NaturalPersonNameType,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NaturalPersonName {
+    #[serde(default)]
+    pub name_identifier: Vec<NaturalPersonNameId>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LegalPersonNameId {
+    pub legal_person_name: String,
+    pub legal_person_name_identifier_type: LegalPersonNameType,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LegalPersonName {
+    #[serde(default)]
+    pub name_identifier: Vec<LegalPersonNameId>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Address {
+    pub address_type: AddressType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub street_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub building_number: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub address_line: Vec<String>, // Unstructured lines, when the parts are not known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub town_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country_sub_division: Option<String>,
+    pub country: String, // ISO 3166-1 alpha-2
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NationalIdentification {
+    pub national_identifier: String,
+    pub national_identifier_type: NationalIdentifierType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country_of_issue: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registration_authority: Option<String>, // GLEIF registration authority code, for RAID
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DateAndPlaceOfBirth {
+    pub date_of_birth: NaiveDate,
+    pub place_of_birth: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NaturalPerson {
+    #[serde(default)]
+    pub name: NaturalPersonName,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub geographic_address: Vec<Address>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub national_identification: Option<NationalIdentification>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub customer_identification: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_and_place_of_birth: Option<DateAndPlaceOfBirth>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country_of_residence: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LegalPerson {
+    #[serde(default)]
+    pub name: LegalPersonName,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub geographic_address: Vec<Address>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub customer_number: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub national_identification: Option<NationalIdentification>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country_of_registration: Option<String>,
+}
+
+/// A natural or legal person: `{"naturalPerson": {...}}` or `{"legalPerson": {...}}`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Person {
+    NaturalPerson(NaturalPerson),
+    LegalPerson(LegalPerson),
+}
+
+impl Person {
+    /// The legal name, as one line.
+    pub fn legal_name(&self) -> Option<String> {
+        match self {
+            Person::NaturalPerson(person) => person
+                .name
+                .name_identifier
+                .iter()
+                .find(|n| n.name_identifier_type == NaturalPersonNameType::LegalName && !n.primary_identifier.trim().is_empty())
+                .map(|n| match n.secondary_identifier.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+                    Some(given) => format!("{} {}", given, n.primary_identifier.trim()),
+                    None => n.primary_identifier.trim().to_string(),
+                }),
+            Person::LegalPerson(person) => person
+                .name
+                .name_identifier
+                .iter()
+                .find(|n| n.legal_person_name_identifier_type == LegalPersonNameType::LegalName)
+                .map(|n| n.legal_person_name.trim().to_string())
+                .filter(|name| !name.is_empty()),
+        }
+    }
+
+    /// Whether the person carries one of the identifying elements FATF requires of an originator besides the name.
+    fn has_originator_identifier(&self) -> bool {
+        let present = |value: &Option<String>| value.as_deref().is_some_and(|v| !v.trim().is_empty());
+        match self {
+            Person::NaturalPerson(person) => {
+                !person.geographic_address.is_empty()
+                    || person.national_identification.is_some()
+                    || present(&person.customer_identification)
+                    || person.date_and_place_of_birth.as_ref().is_some_and(|b| !b.place_of_birth.trim().is_empty())
+            }
+            Person::LegalPerson(person) => {
+                !person.geographic_address.is_empty() || person.national_identification.is_some() || present(&person.customer_number)
+            }
+        }
+    }
+
+    /// Rejects malformed values (as opposed to missing ones).
+    fn validate(&self, role: &str) -> Result<(), DomainError> {
+        let invalid = |reason: String| DomainError::Validation(format!("IVMS101 {}: {}", role, reason));
+        let is_country = |code: &str| code.len() == 2 && code.chars().all(|c| c.is_ascii_uppercase());
+        let (addresses, country, national_identification) = match self {
+            Person::NaturalPerson(person) => {
+                if person.date_and_place_of_birth.as_ref().is_some_and(|b| b.date_of_birth >= Utc::now().date_naive()) {
+                    return Err(invalid("date of birth must be in the past".to_string()));
+                }
+                (&person.geographic_address, &person.country_of_residence, &person.national_identification)
+            }
+            Person::LegalPerson(person) => {
+                (&person.geographic_address, &person.country_of_registration, &person.national_identification)
+            }
+        };
+        for address in addresses {
+            if address.address_line.is_empty() && address.street_name.is_none() && address.town_name.is_none() {
+                return Err(invalid("an address needs a street, a town or address lines".to_string()));
+            }
+        }
+        let countries = addresses
+            .iter()
+            .map(|a| a.country.as_str())
+            .chain(country.as_deref())
+            .chain(national_identification.as_ref().and_then(|id| id.country_of_issue.as_deref()));
+        for code in countries {
+            if !is_country(code) {
+                return Err(invalid(format!("'{}' is not an ISO 3166 country code", code)));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Originator {
+    #[serde(default)]
+    pub originator_persons: Vec<Person>,
+    #[serde(default)]
+    pub account_number: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Beneficiary {
+    #[serde(default)]
+    pub beneficiary_persons: Vec<Person>,
+    #[serde(default)]
+    pub account_number: Vec<String>, // For crypto transfers, the receiving address
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OriginatingVasp {
+    #[serde(rename = "originatingVASP")]
+    pub originating_vasp: Person,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BeneficiaryVasp {
+    #[serde(rename = "beneficiaryVASP")]
+    pub beneficiary_vasp: Person,
+}
+
+/// The IVMS101 identity payload exchanged between VASPs.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct IdentityPayload {
+    pub originator: Originator,
+    pub beneficiary: Beneficiary,
+    #[serde(rename = "originatingVASP", default, skip_serializing_if = "Option::is_none")]
+    pub originating_vasp: Option<OriginatingVasp>,
+    #[serde(rename = "beneficiaryVASP", default, skip_serializing_if = "Option::is_none")]
+    pub beneficiary_vasp: Option<BeneficiaryVasp>,
+}
+
+fn has_account(accounts: &[String]) -> bool {
+    accounts.iter().any(|account| !account.trim().is_empty())
+}
+
+impl IdentityPayload {
+    /// Required data elements that are missing, by name (empty when the payload is complete).
+    pub fn missing_fields(&self) -> Vec<String> {
+        let mut missing = Vec::new();
+        let originators = &self.originator.originator_persons;
+        if originators.is_empty() || originators.iter().any(|p| p.legal_name().is_none()) {
+            missing.push("originator.name".to_string());
+        }
+        if !has_account(&self.originator.account_number) {
+            missing.push("originator.accountNumber".to_string());
+        }
+        if !originators.is_empty() && !originators.iter().all(Person::has_originator_identifier) {
+            missing.push("originator.geographicAddress|nationalIdentification|customerIdentification|dateAndPlaceOfBirth".to_string());
+        }
+        let beneficiaries = &self.beneficiary.beneficiary_persons;
+        if beneficiaries.is_empty() || beneficiaries.iter().any(|p| p.legal_name().is_none()) {
+            missing.push("beneficiary.name".to_string());
+        }
+        if !has_account(&self.beneficiary.account_number) {
+            missing.push("beneficiary.accountNumber".to_string());
+        }
+        missing
+    }
+
+    /// Rejects malformed persons; missing elements are reported by `missing_fields` instead.
+    pub fn validate(&self) -> Result<(), DomainError> {
+        for person in &self.originator.originator_persons {
+            person.validate("originator")?;
+        }
+        for person in &self.beneficiary.beneficiary_persons {
+            person.validate("beneficiary")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn natural(family: &str, given: Option<&str>) -> NaturalPerson {
+        NaturalPerson {
+            name: NaturalPersonName {
+                name_identifier: vec![NaturalPersonNameId {
+                    primary_identifier: family.to_string(),
+                    secondary_identifier: given.map(str::to_string),
+                    name_identifier_type: NaturalPersonNameType::LegalName,
+                }],
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_ivms101_json_form() {
+        let json = r#"{"originator": {"originatorPersons": [{"naturalPerson": {"name": {"nameIdentifier": [
+            {"primaryIdentifier": "Doe", "secondaryIdentifier": "Jane", "nameIdentifierType": "LEGL"}]},
+            "dateAndPlaceOfBirth": {"dateOfBirth": "1980-02-29", "placeOfBirth": "Lyon"}}}],
+            "accountNumber": ["wallet-1"]},
+            "beneficiary": {"beneficiaryPersons": [{"legalPerson": {"name": {"nameIdentifier": [
+            {"legalPersonName": "Acme GmbH", "legalPersonNameIdentifierType": "LEGL"}]}}}],
+            "accountNumber": ["bc1qxy2kgdygjrsqtzq2n0yrf2493p83kkfjhx0wlh"]}}"#;
+        let payload: IdentityPayload = serde_json::from_str(json).unwrap();
+        assert_eq!(payload.originator.originator_persons[0].legal_name().as_deref(), Some("Jane Doe"));
+        assert_eq!(payload.beneficiary.beneficiary_persons[0].legal_name().as_deref(), Some("Acme GmbH"));
+        assert!(payload.missing_fields().is_empty());
+        payload.validate().unwrap();
+
+        let back = serde_json::to_value(&payload).unwrap();
+        assert_eq!(back["originator"]["originatorPersons"][0]["naturalPerson"]["name"]["nameIdentifier"][0]["nameIdentifierType"], "LEGL");
+        assert!(back.get("originatingVASP").is_none());
+    }
+
+    #[test]
+    fn test_missing_fields_follow_recommendation_16() {
+        let mut payload = IdentityPayload::default();
+        assert_eq!(payload.missing_fields().len(), 4); // Names and account numbers; no persons to check identifiers of
+
+        payload.originator.originator_persons.push(Person::NaturalPerson(natural("Doe", Some("Jane"))));
+        payload.originator.account_number.push("wallet-1".to_string());
+        payload.beneficiary.account_number.push("44AFFC8hbxv...".to_string());
+        assert_eq!(
+            payload.missing_fields(),
+            vec![
+                "originator.geographicAddress|nationalIdentification|customerIdentification|dateAndPlaceOfBirth".to_string(),
+                "beneficiary.name".to_string(),
+            ]
+        );
+
+        if let Person::NaturalPerson(person) = &mut payload.originator.originator_persons[0] {
+            person.customer_identification = Some("customer-42".to_string());
+        }
+        payload.beneficiary.beneficiary_persons.push(Person::NaturalPerson(natural("", None))); // Blank name
+        assert_eq!(payload.missing_fields(), vec!["beneficiary.name".to_string()]);
+        payload.beneficiary.beneficiary_persons[0] = Person::NaturalPerson(natural("Roe", None));
+        assert!(payload.missing_fields().is_empty());
+    }
+
+    #[test]
+    fn test_malformed_values_are_rejected() {
+        let mut person = natural("Doe", None);
+        person.country_of_residence = Some("France".to_string());
+        let payload = IdentityPayload {
+            originator: Originator { originator_persons: vec![Person::NaturalPerson(person)], account_number: vec![] },
+            ..Default::default()
+        };
+        assert!(matches!(payload.validate(), Err(DomainError::Validation(_))));
+    }
+}
@@ -0,0 +1,16 @@
+// /home/inno/elights_jobes-research/backend/domain/src/travel_rule/mod.rs
+// FATF Travel Rule: originator and beneficiary data (IVMS101) for crypto withdrawals above a threshold, stored
+// with the withdrawal and exchanged with the beneficiary's VASP before anything is broadcast.
+
+pub mod ivms101; // IVMS101 data model and the Recommendation 16 completeness check
+pub mod config; // Thresholds, missing-data action, hold window, VASP directory
+pub mod protocol; // Pluggable exchange adapters and the loopback stand-in
+pub mod transfers; // Holding, completing, exchanging and expiring withdrawals
+
+pub use config::{CounterpartyVasp, MissingDataAction, TravelRuleConfig, VaspIdentity, VaspProtocol};
+pub use ivms101::{Beneficiary, IdentityPayload, LegalPerson, NaturalPerson, Originator, Person};
+pub use protocol::{LoopbackProtocol, ProtocolError, ProtocolRouter, TransferMessage, TransferReply, TravelRuleProtocol};
+pub use transfers::{
+    apply_reply, check_withdrawal, expire_transfers, get_transfer, list_transfers, supply_data, transfer_for_withdrawal,
+    TravelRuleExchange, TravelRuleSubmission, WithdrawalCheck,
+};
@@ -0,0 +1,190 @@
+// /home/inno/elights_jobes-research/backend/domain/src/travel_rule/protocol.rs
+// Exchange of Travel Rule data with the beneficiary VASP. Each protocol (a JSON API, TRP, TRUST, ...) is an
+// adapter behind `TravelRuleProtocol`, chosen per counterparty through `ProtocolRouter`. `LoopbackProtocol`
+// answers in place of the counterparty, for tests and sandboxes.
+//
+// Loopback scenarios, by counterparty vasp_id suffix:
+//   *-reject  -> Rejected ("beneficiary unknown")
+//   *-pending -> Pending on send, Accepted when polled
+//   *-down    -> Unavailable (retried)
+//   anything else -> Accepted, beneficiary echoed back
+use super::config::{CounterpartyVasp, VaspProtocol};
+use super::ivms101::{Beneficiary, IdentityPayload};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// What is sent to the beneficiary VASP before the withdrawal is broadcast.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TransferMessage {
+    pub transfer_id: Uuid, // Our reference for the exchange
+    pub asset: String, // BTC, XMR
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+    pub destination_address: String,
+    pub ivms101: IdentityPayload,
+}
+
+/// The beneficiary VASP's answer.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "decision", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TransferReply {
+    Accepted {
+        #[serde(default)]
+        reference: Option<String>,
+        #[serde(default)]
+        beneficiary: Option<Beneficiary>, // As the beneficiary VASP knows its customer, if returned
+    },
+    Rejected {
+        #[serde(default)]
+        reference: Option<String>,
+        reason: String,
+    },
+    Pending {
+        reference: String, // Used to ask again
+    },
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ProtocolError {
+    /// Counterparty not reachable or not answering: try again later.
+    #[error("Counterparty VASP unavailable: {0}")]
+    Unavailable(String),
+    /// The counterparty cannot process the message as sent: retrying will not help.
+    #[error("Counterparty VASP refused the message: {0}")]
+    Refused(String),
+}
+
+#[async_trait]
+pub trait TravelRuleProtocol: Send + Sync {
+    /// Sends the originator and beneficiary data of a withdrawal.
+    async fn send(&self, counterparty: &CounterpartyVasp, message: &TransferMessage) -> Result<TransferReply, ProtocolError>;
+
+    /// Asks for the decision on an exchange that was answered with `Pending`.
+    async fn poll(&self, counterparty: &CounterpartyVasp, transfer_id: Uuid, reference: &str) -> Result<TransferReply, ProtocolError>;
+}
+
+/// Adapters by protocol.
+#[derive(Default, Clone)]
+pub struct ProtocolRouter {
+    adapters: HashMap<VaspProtocol, Arc<dyn TravelRuleProtocol>>,
+}
+
+impl ProtocolRouter {
+    pub fn new() -> Self {
+        ProtocolRouter::default()
+    }
+
+    pub fn with_adapter(mut self, protocol: VaspProtocol, adapter: Arc<dyn TravelRuleProtocol>) -> Self {
+        self.adapters.insert(protocol, adapter);
+        self
+    }
+
+    pub fn adapter(&self, protocol: VaspProtocol) -> Option<&dyn TravelRuleProtocol> {
+        self.adapters.get(&protocol).map(|adapter| adapter.as_ref())
+    }
+}
+
+/// Answers for the counterparty without leaving the process; see the scenarios at the top of this file.
+#[derive(Default)]
+pub struct LoopbackProtocol {
+    sent: Mutex<Vec<TransferMessage>>,
+}
+
+impl LoopbackProtocol {
+    pub fn new() -> Self {
+        LoopbackProtocol::default()
+    }
+
+    /// Messages received so far.
+    pub fn sent(&self) -> Vec<TransferMessage> {
+        self.sent.lock().map(|sent| sent.clone()).unwrap_or_default()
+    }
+
+    fn accept(message: Option<&TransferMessage>, reference: String) -> TransferReply {
+        TransferReply::Accepted { reference: Some(reference), beneficiary: message.map(|m| m.ivms101.beneficiary.clone()) }
+    }
+}
+
+#[async_trait]
+impl TravelRuleProtocol for LoopbackProtocol {
+    async fn send(&self, counterparty: &CounterpartyVasp, message: &TransferMessage) -> Result<TransferReply, ProtocolError> {
+        if counterparty.vasp_id.ends_with("-down") {
+            return Err(ProtocolError::Unavailable(format!("{} is down", counterparty.vasp_id)));
+        }
+        if let Ok(mut sent) = self.sent.lock() {
+            sent.push(message.clone());
+        }
+        let reference = format!("loopback-{}", message.transfer_id);
+        Ok(if counterparty.vasp_id.ends_with("-reject") {
+            TransferReply::Rejected { reference: Some(reference), reason: "beneficiary unknown".to_string() }
+        } else if counterparty.vasp_id.ends_with("-pending") {
+            TransferReply::Pending { reference }
+        } else {
+            Self::accept(Some(message), reference)
+        })
+    }
+
+    async fn poll(&self, counterparty: &CounterpartyVasp, transfer_id: Uuid, reference: &str) -> Result<TransferReply, ProtocolError> {
+        if counterparty.vasp_id.ends_with("-down") {
+            return Err(ProtocolError::Unavailable(format!("{} is down", counterparty.vasp_id)));
+        }
+        let sent = self.sent();
+        Ok(Self::accept(sent.iter().find(|m| m.transfer_id == transfer_id), reference.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn counterparty(vasp_id: &str) -> CounterpartyVasp {
+        CounterpartyVasp {
+            vasp_id: vasp_id.to_string(),
+            name: "Acme".to_string(),
+            lei: None,
+            country: "DE".to_string(),
+            protocol: VaspProtocol::Loopback,
+            endpoint: None,
+        }
+    }
+
+    fn message() -> TransferMessage {
+        let mut ivms101 = IdentityPayload::default();
+        ivms101.beneficiary.account_number.push("bc1q...".to_string());
+        TransferMessage { transfer_id: Uuid::new_v4(), asset: "BTC".to_string(), amount: dec!(0.5), destination_address: "bc1q...".to_string(), ivms101 }
+    }
+
+    #[tokio::test]
+    async fn test_loopback_scenarios() {
+        let router = ProtocolRouter::new().with_adapter(VaspProtocol::Loopback, Arc::new(LoopbackProtocol::new()));
+        let loopback = router.adapter(VaspProtocol::Loopback).unwrap();
+        assert!(router.adapter(VaspProtocol::HttpsJson).is_none());
+
+        let message = message();
+        match loopback.send(&counterparty("acme"), &message).await.unwrap() {
+            TransferReply::Accepted { beneficiary, .. } => assert_eq!(beneficiary, Some(message.ivms101.beneficiary.clone())),
+            other => panic!("unexpected reply {:?}", other),
+        }
+        assert!(matches!(loopback.send(&counterparty("acme-reject"), &message).await, Ok(TransferReply::Rejected { .. })));
+        assert!(matches!(loopback.send(&counterparty("acme-down"), &message).await, Err(ProtocolError::Unavailable(_))));
+
+        let reference = match loopback.send(&counterparty("acme-pending"), &message).await.unwrap() {
+            TransferReply::Pending { reference } => reference,
+            other => panic!("unexpected reply {:?}", other),
+        };
+        let polled = loopback.poll(&counterparty("acme-pending"), message.transfer_id, &reference).await.unwrap();
+        assert!(matches!(polled, TransferReply::Accepted { .. }));
+    }
+
+    #[test]
+    fn test_reply_json_form() {
+        let reply: TransferReply = serde_json::from_str(r#"{"decision": "REJECTED", "reason": "no such customer"}"#).unwrap();
+        assert_eq!(reply, TransferReply::Rejected { reference: None, reason: "no such customer".to_string() });
+    }
+}
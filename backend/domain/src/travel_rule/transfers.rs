@@ -0,0 +1,502 @@
+// /home/inno/elights_jobes-research/backend/domain/src/travel_rule/transfers.rs
+// Travel Rule handling of crypto withdrawals. Above the threshold the originator and beneficiary data is collected
+// as IVMS101 and stored with the withdrawal. With data missing the withdrawal is refused or held (per
+// configuration) until the customer completes it. To another VASP the withdrawal stays held, nothing reserved or
+// queued, until that VASP accepts the exchange; a refusal cancels it. To a self-hosted wallet the data is only
+// recorded. Held withdrawals expire after the configured window.
+use super::config::{CounterpartyVasp, MissingDataAction, TravelRuleConfig};
+use super::ivms101::{BeneficiaryVasp, IdentityPayload, OriginatingVasp, Person};
+use super::protocol::{ProtocolError, ProtocolRouter, TransferMessage, TransferReply};
+use crate::approvals::{self, PendingDispatch};
+use crate::error::DomainError;
+use crate::models::{
+    AuditOutcome, AuditTargetType, NewTravelRuleTransfer, Transaction, TransactionStatus, TravelRuleStatus, TravelRuleTransfer,
+};
+use crate::payments::outbox;
+use crate::payments::state_machine::{self, TransitionUpdate};
+use crate::security::audit;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+const ACTOR: &str = "TRAVEL_RULE";
+
+/// Travel Rule data given by the customer with a withdrawal, or later for a held one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TravelRuleSubmission {
+    #[serde(default)]
+    pub originator: Option<Person>, // The customer, as IVMS101
+    #[serde(default)]
+    pub beneficiary: Option<Person>,
+    #[serde(default)]
+    pub beneficiary_vasp_id: Option<String>, // From the VASP directory; none for a self-hosted wallet
+}
+
+/// What the withdrawal does next.
+#[derive(Debug, Clone)]
+pub enum WithdrawalCheck {
+    /// Reserve and queue as usual, merging `metadata` (if any) into the transaction.
+    Proceed { metadata: Option<serde_json::Value> },
+    /// Held in RequiresAction: nothing reserved or queued.
+    Held(Transaction),
+}
+
+fn to_json<T: Serialize>(value: &T, what: &str) -> Result<serde_json::Value, DomainError> {
+    serde_json::to_value(value).map_err(|e| DomainError::Internal(format!("Failed to serialize {}: {}", what, e)))
+}
+
+fn payload_of(transfer: &TravelRuleTransfer) -> Result<IdentityPayload, DomainError> {
+    serde_json::from_value(transfer.ivms101.clone())
+        .map_err(|e| DomainError::Internal(format!("Travel Rule transfer {} has invalid IVMS101 data: {}", transfer.transfer_id, e)))
+}
+
+/// The originator as given, identified by our customer number unless another identifier was given.
+fn originator_person(person: &Person, initiator_user_id: Uuid) -> Person {
+    let mut person = person.clone();
+    match &mut person {
+        Person::NaturalPerson(natural) => {
+            natural.customer_identification.get_or_insert_with(|| initiator_user_id.to_string());
+        }
+        Person::LegalPerson(legal) => {
+            legal.customer_number.get_or_insert_with(|| initiator_user_id.to_string());
+        }
+    }
+    person
+}
+
+fn resolve_counterparty<'c>(config: &'c TravelRuleConfig, vasp_id: Option<&str>) -> Result<Option<&'c CounterpartyVasp>, DomainError> {
+    match vasp_id {
+        Some(vasp_id) => config
+            .counterparty(vasp_id)
+            .map(Some)
+            .ok_or_else(|| DomainError::Validation(format!("Unknown beneficiary VASP '{}'", vasp_id))),
+        None => Ok(None),
+    }
+}
+
+/// Applies a submission to the payload: given persons replace the stored ones, account numbers are ours to set.
+fn apply_submission(
+    payload: &mut IdentityPayload,
+    config: &TravelRuleConfig,
+    transaction: &Transaction,
+    initiator_user_id: Uuid,
+    destination_address: &str,
+    submission: &TravelRuleSubmission,
+    counterparty: Option<&CounterpartyVasp>,
+) {
+    if let Some(originator) = &submission.originator {
+        payload.originator.originator_persons = vec![originator_person(originator, initiator_user_id)];
+    }
+    if let Some(beneficiary) = &submission.beneficiary {
+        payload.beneficiary.beneficiary_persons = vec![beneficiary.clone()];
+    }
+    payload.originator.account_number = transaction.debit_wallet_id.iter().map(Uuid::to_string).collect();
+    payload.beneficiary.account_number = vec![destination_address.trim().to_string()];
+    payload.originating_vasp = Some(OriginatingVasp { originating_vasp: config.originating_vasp.to_person() });
+    payload.beneficiary_vasp = counterparty.map(|vasp| BeneficiaryVasp { beneficiary_vasp: vasp.to_person() });
+}
+
+/// Checks a Pending crypto withdrawal against the Travel Rule. Call inside the DB transaction that created it,
+/// before the funds are reserved. Fails with `DomainError::Validation` when data is missing and the
+/// configuration blocks such withdrawals.
+#[allow(clippy::too_many_arguments)]
+pub fn check_withdrawal(
+    conn: &mut PgConnection,
+    config: &TravelRuleConfig,
+    transaction: &Transaction,
+    initiator_user_id: Uuid,
+    destination_address: &str,
+    submission: Option<&TravelRuleSubmission>,
+    dispatch: &PendingDispatch,
+    now: DateTime<Utc>,
+) -> Result<WithdrawalCheck, DomainError> {
+    if !config.applies_to(&transaction.currency_code, transaction.amount) {
+        return Ok(WithdrawalCheck::Proceed { metadata: None });
+    }
+    let submission = submission.cloned().unwrap_or_default();
+    let counterparty = resolve_counterparty(config, submission.beneficiary_vasp_id.as_deref())?;
+    let mut payload = IdentityPayload::default();
+    apply_submission(&mut payload, config, transaction, initiator_user_id, destination_address, &submission, counterparty);
+    payload.validate()?;
+    let missing = payload.missing_fields();
+
+    let status = match (missing.is_empty(), counterparty) {
+        (false, _) if config.on_missing_data == MissingDataAction::Block => {
+            return Err(DomainError::Validation(format!(
+                "Travel Rule information is required for this withdrawal; missing: {}", missing.join(", ")
+            )));
+        }
+        (false, _) => TravelRuleStatus::AwaitingData,
+        (true, Some(_)) => TravelRuleStatus::Ready,
+        (true, None) => TravelRuleStatus::Unhosted,
+    };
+    let holding = status.is_holding();
+    let transfer: TravelRuleTransfer = diesel::insert_into(crate::schema::travel_rule_transfers::table)
+        .values(&NewTravelRuleTransfer {
+            transaction_id: transaction.transaction_id,
+            initiator_user_id,
+            status: status.as_str(),
+            destination_address: destination_address.trim(),
+            beneficiary_vasp_id: counterparty.map(|vasp| vasp.vasp_id.as_str()),
+            protocol: counterparty.map(|vasp| vasp.protocol.as_str()),
+            ivms101: to_json(&payload, "IVMS101 payload")?,
+            missing_fields: json!(missing),
+            pending_dispatch: if holding { Some(to_json(dispatch, "pending dispatch")?) } else { None },
+            next_attempt_at: (status == TravelRuleStatus::Ready).then_some(now),
+            expires_at: holding.then(|| now + config.hold_window()),
+            completed_at: (!holding).then_some(now),
+        })
+        .get_result(conn)?;
+
+    let metadata = json!({"travel_rule": {"transfer_id": transfer.transfer_id, "status": transfer.status,
+        "missing_fields": missing, "expires_at": transfer.expires_at}});
+    if !holding {
+        return Ok(WithdrawalCheck::Proceed { metadata: Some(metadata) });
+    }
+    let update = TransitionUpdate { metadata: Some(metadata), ..Default::default() };
+    let transaction = state_machine::apply_transition(conn, transaction, TransactionStatus::RequiresAction, update, ACTOR)?;
+    audit::log_db_audit_event(
+        conn, Some(initiator_user_id), ACTOR, "TRAVEL_RULE_WITHDRAWAL_HELD", Some(AuditTargetType::TravelRuleTransfer),
+        Some(&transfer.transfer_id.to_string()), AuditOutcome::Success,
+        Some(json!({"transaction_id": transaction.transaction_id, "status": transfer.status, "missing_fields": transfer.missing_fields,
+            "beneficiary_vasp_id": transfer.beneficiary_vasp_id})),
+        None,
+    )?;
+    log::info!("Withdrawal {} held for the Travel Rule ({})", transaction.transaction_id, transfer.status);
+    Ok(WithdrawalCheck::Held(transaction))
+}
+
+fn pending_dispatch_of(transfer: &TravelRuleTransfer) -> Result<PendingDispatch, DomainError> {
+    let value = transfer.pending_dispatch.clone().ok_or_else(|| {
+        DomainError::Internal(format!("Travel Rule transfer {} has no pending dispatch", transfer.transfer_id))
+    })?;
+    serde_json::from_value(value)
+        .map_err(|e| DomainError::Internal(format!("Travel Rule transfer {} has an invalid pending dispatch: {}", transfer.transfer_id, e)))
+}
+
+/// Reserves and queues a held withdrawal. If the wallet no longer covers it, the withdrawal is cancelled instead.
+fn release(conn: &mut PgConnection, transfer: &TravelRuleTransfer, transaction: &Transaction, metadata: serde_json::Value) -> Result<bool, DomainError> {
+    let dispatch = pending_dispatch_of(transfer)?;
+    match conn.transaction(|conn| approvals::workflow::dispatch_held_payment(conn, transaction, &dispatch, metadata.clone(), ACTOR)) {
+        Ok(_) => Ok(true),
+        Err(DomainError::InsufficientFunds(wallet_id)) => {
+            log::warn!("Withdrawal {} cancelled after the Travel Rule exchange: wallet {} no longer covers it", transaction.transaction_id, wallet_id);
+            let update = TransitionUpdate {
+                metadata: Some(json!({"travel_rule": {"transfer_id": transfer.transfer_id, "cancelled": "insufficient funds at release"}})),
+                ..Default::default()
+            };
+            state_machine::apply_transition(conn, transaction, TransactionStatus::Cancelled, update, ACTOR)?;
+            Ok(false)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn lock_transaction(conn: &mut PgConnection, transaction_id: Uuid) -> Result<Transaction, DomainError> {
+    use crate::schema::transactions::dsl as t;
+    Ok(t::transactions.find(transaction_id).for_update().first(conn)?)
+}
+
+/// Completes the data of a withdrawal held for it. The withdrawal stays held while anything is still missing;
+/// once complete it waits for the beneficiary VASP, or is released at once to a self-hosted wallet.
+pub fn supply_data(
+    conn: &mut PgConnection,
+    config: &TravelRuleConfig,
+    transaction_id: Uuid,
+    user_id: Uuid,
+    submission: &TravelRuleSubmission,
+    now: DateTime<Utc>,
+) -> Result<TravelRuleTransfer, DomainError> {
+    use crate::schema::travel_rule_transfers::dsl as tr;
+    conn.transaction(|conn| {
+        let transfer: TravelRuleTransfer = tr::travel_rule_transfers
+            .filter(tr::transaction_id.eq(transaction_id))
+            .filter(tr::initiator_user_id.eq(user_id)) // Only the customer's own withdrawals
+            .for_update()
+            .first(conn)
+            .optional()?
+            .ok_or_else(|| DomainError::NotFound(format!("No Travel Rule data for withdrawal {}", transaction_id)))?;
+        if transfer.status != TravelRuleStatus::AwaitingData.as_str() {
+            return Err(DomainError::Validation(format!("Travel Rule data of withdrawal {} is {}, not awaiting data", transaction_id, transfer.status)));
+        }
+        let transaction = lock_transaction(conn, transaction_id)?;
+        if transaction.status != TransactionStatus::RequiresAction.to_string() {
+            return Err(DomainError::Validation(format!("Withdrawal {} is {}, no longer held", transaction_id, transaction.status)));
+        }
+
+        let vasp_id = submission.beneficiary_vasp_id.as_deref().or(transfer.beneficiary_vasp_id.as_deref());
+        let counterparty = resolve_counterparty(config, vasp_id)?;
+        let mut payload = payload_of(&transfer)?;
+        apply_submission(&mut payload, config, &transaction, user_id, &transfer.destination_address, submission, counterparty);
+        payload.validate()?;
+        let missing = payload.missing_fields();
+        let status = match (missing.is_empty(), counterparty) {
+            (false, _) => TravelRuleStatus::AwaitingData,
+            (true, Some(_)) => TravelRuleStatus::Ready,
+            (true, None) => TravelRuleStatus::Unhosted,
+        };
+
+        let transfer: TravelRuleTransfer = diesel::update(tr::travel_rule_transfers.find(transfer.transfer_id))
+            .set((
+                tr::status.eq(status.as_str()),
+                tr::ivms101.eq(to_json(&payload, "IVMS101 payload")?),
+                tr::missing_fields.eq(json!(missing)),
+                tr::beneficiary_vasp_id.eq(counterparty.map(|vasp| vasp.vasp_id.clone())),
+                tr::protocol.eq(counterparty.map(|vasp| vasp.protocol.as_str())),
+                tr::next_attempt_at.eq((status == TravelRuleStatus::Ready).then_some(now)),
+                tr::completed_at.eq((status == TravelRuleStatus::Unhosted).then_some(now)),
+            ))
+            .get_result(conn)?;
+        if status == TravelRuleStatus::Unhosted {
+            let metadata = json!({"travel_rule": {"transfer_id": transfer.transfer_id, "status": transfer.status, "missing_fields": missing}});
+            release(conn, &transfer, &transaction, metadata)?;
+        }
+
+        audit::log_db_audit_event(
+            conn, Some(user_id), &user_id.to_string(), "TRAVEL_RULE_DATA_SUPPLIED", Some(AuditTargetType::TravelRuleTransfer),
+            Some(&transfer.transfer_id.to_string()), AuditOutcome::Success,
+            Some(json!({"transaction_id": transaction_id, "status": transfer.status, "missing_fields": transfer.missing_fields})),
+            None,
+        )?;
+        Ok(transfer)
+    })
+}
+
+/// Applies the counterparty's answer (or the failure to get one) to a transfer being exchanged.
+pub fn apply_reply(
+    conn: &mut PgConnection,
+    config: &TravelRuleConfig,
+    transfer_id: Uuid,
+    reply: Result<TransferReply, ProtocolError>,
+    now: DateTime<Utc>,
+) -> Result<TravelRuleTransfer, DomainError> {
+    use crate::schema::travel_rule_transfers::dsl as tr;
+    conn.transaction(|conn| {
+        let transfer: TravelRuleTransfer = tr::travel_rule_transfers.find(transfer_id).for_update().first(conn)?;
+        if !matches!(TravelRuleStatus::parse(&transfer.status), Some(TravelRuleStatus::Ready | TravelRuleStatus::Sent)) {
+            log::warn!("Ignoring Travel Rule answer for transfer {} ({})", transfer_id, transfer.status);
+            return Ok(transfer);
+        }
+        let sent_at = transfer.sent_at.or(Some(now));
+        let response = reply.as_ref().ok().map(|reply| to_json(reply, "counterparty reply")).transpose()?;
+
+        let (reference, refusal) = match reply {
+            Ok(TransferReply::Pending { reference }) => {
+                return Ok(diesel::update(tr::travel_rule_transfers.find(transfer_id))
+                    .set((
+                        tr::status.eq(TravelRuleStatus::Sent.as_str()),
+                        tr::counterparty_reference.eq(Some(reference)),
+                        tr::counterparty_response.eq(response),
+                        tr::sent_at.eq(sent_at),
+                        tr::next_attempt_at.eq(Some(now + config.poll_interval())),
+                    ))
+                    .get_result(conn)?);
+            }
+            Err(ProtocolError::Unavailable(message)) => {
+                let attempts = transfer.attempts + 1;
+                log::warn!("Travel Rule exchange {} attempt {} failed: {}", transfer_id, attempts, message);
+                return Ok(diesel::update(tr::travel_rule_transfers.find(transfer_id))
+                    .set((
+                        tr::attempts.eq(attempts),
+                        tr::last_error.eq(Some(message)),
+                        tr::next_attempt_at.eq(Some(now + outbox::retry_delay(attempts))),
+                    ))
+                    .get_result(conn)?);
+            }
+            Ok(TransferReply::Accepted { reference, beneficiary }) => {
+                if let Some(beneficiary) = beneficiary.filter(|b| !b.beneficiary_persons.is_empty()) {
+                    let mut payload = payload_of(&transfer)?;
+                    payload.beneficiary.beneficiary_persons = beneficiary.beneficiary_persons; // As the beneficiary VASP knows them
+                    diesel::update(tr::travel_rule_transfers.find(transfer_id))
+                        .set(tr::ivms101.eq(to_json(&payload, "IVMS101 payload")?))
+                        .execute(conn)?;
+                }
+                (reference.or(transfer.counterparty_reference.clone()), None)
+            }
+            Ok(TransferReply::Rejected { reference, reason }) => (reference.or(transfer.counterparty_reference.clone()), Some(reason)),
+            Err(ProtocolError::Refused(message)) => (transfer.counterparty_reference.clone(), Some(message)),
+        };
+
+        let status = if refusal.is_some() { TravelRuleStatus::Rejected } else { TravelRuleStatus::Accepted };
+        let transfer: TravelRuleTransfer = diesel::update(tr::travel_rule_transfers.find(transfer_id))
+            .set((
+                tr::status.eq(status.as_str()),
+                tr::counterparty_reference.eq(reference),
+                tr::counterparty_response.eq(response),
+                tr::last_error.eq(refusal.clone()),
+                tr::sent_at.eq(sent_at),
+                tr::next_attempt_at.eq(None::<DateTime<Utc>>),
+                tr::completed_at.eq(Some(now)),
+            ))
+            .get_result(conn)?;
+
+        let transaction = lock_transaction(conn, transfer.transaction_id)?;
+        let metadata = json!({"travel_rule": {"transfer_id": transfer_id, "status": transfer.status, "decided_at": now}});
+        let released = if transaction.status != TransactionStatus::RequiresAction.to_string() {
+            log::warn!("Withdrawal {} is {}, Travel Rule decision not applied", transaction.transaction_id, transaction.status);
+            false
+        } else if refusal.is_none() {
+            release(conn, &transfer, &transaction, metadata)?
+        } else {
+            let update = TransitionUpdate { metadata: Some(metadata), ..Default::default() };
+            state_machine::apply_transition(conn, &transaction, TransactionStatus::Cancelled, update, ACTOR)?;
+            false
+        };
+
+        audit::log_db_audit_event(
+            conn, Some(transfer.initiator_user_id), ACTOR, "TRAVEL_RULE_EXCHANGE_DECIDED", Some(AuditTargetType::TravelRuleTransfer),
+            Some(&transfer_id.to_string()), if refusal.is_none() { AuditOutcome::Success } else { AuditOutcome::Failure },
+            Some(json!({"transaction_id": transfer.transaction_id, "beneficiary_vasp_id": transfer.beneficiary_vasp_id,
+                "counterparty_reference": transfer.counterparty_reference, "released": released})),
+            refusal.as_deref(),
+        )?;
+        log::info!("Travel Rule exchange {} {} by {:?}", transfer_id, transfer.status, transfer.beneficiary_vasp_id);
+        Ok(transfer)
+    })
+}
+
+/// Transfers due for an exchange attempt, leased until the next poll interval so that a crashed run is retried.
+fn claim_due(conn: &mut PgConnection, config: &TravelRuleConfig, limit: i64, now: DateTime<Utc>) -> Result<Vec<TravelRuleTransfer>, DomainError> {
+    use crate::schema::travel_rule_transfers::dsl as tr;
+    conn.transaction(|conn| {
+        let due: Vec<TravelRuleTransfer> = tr::travel_rule_transfers
+            .filter(tr::status.eq_any([TravelRuleStatus::Ready.as_str(), TravelRuleStatus::Sent.as_str()]))
+            .filter(tr::next_attempt_at.le(now))
+            .order(tr::next_attempt_at.asc())
+            .limit(limit)
+            .for_update()
+            .skip_locked()
+            .load(conn)?;
+        let ids: Vec<Uuid> = due.iter().map(|transfer| transfer.transfer_id).collect();
+        diesel::update(tr::travel_rule_transfers.filter(tr::transfer_id.eq_any(&ids)))
+            .set(tr::next_attempt_at.eq(Some(now + config.poll_interval())))
+            .execute(conn)?;
+        Ok(due)
+    })
+}
+
+/// Runs the exchanges with counterparty VASPs.
+pub struct TravelRuleExchange<'a> {
+    config: &'a TravelRuleConfig,
+    protocols: &'a ProtocolRouter,
+    batch_size: i64,
+}
+
+impl<'a> TravelRuleExchange<'a> {
+    pub fn new(config: &'a TravelRuleConfig, protocols: &'a ProtocolRouter) -> Self {
+        TravelRuleExchange { config, protocols, batch_size: 20 }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: i64) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Sends (or polls) one batch of due transfers. Returns how many were handled.
+    pub async fn run_once(&self, conn: &mut PgConnection) -> Result<usize, DomainError> {
+        let due = claim_due(conn, self.config, self.batch_size, Utc::now())?;
+        let count = due.len();
+        for transfer in due {
+            if let Err(e) = self.exchange(conn, &transfer).await {
+                // The lease expires and the transfer is picked up again
+                log::error!("Travel Rule exchange {} failed: {}", transfer.transfer_id, e);
+            }
+        }
+        Ok(count)
+    }
+
+    async fn exchange(&self, conn: &mut PgConnection, transfer: &TravelRuleTransfer) -> Result<(), DomainError> {
+        let Some(counterparty) = transfer.beneficiary_vasp_id.as_deref().and_then(|id| self.config.counterparty(id)) else {
+            let reply = Err(ProtocolError::Refused(format!("beneficiary VASP {:?} is no longer in the directory", transfer.beneficiary_vasp_id)));
+            apply_reply(conn, self.config, transfer.transfer_id, reply, Utc::now())?;
+            return Ok(());
+        };
+        let adapter = self.protocols.adapter(counterparty.protocol).ok_or_else(|| {
+            DomainError::Configuration(format!("No Travel Rule adapter for {}", counterparty.protocol.as_str()))
+        })?;
+
+        let reply = match (TravelRuleStatus::parse(&transfer.status), transfer.counterparty_reference.as_deref()) {
+            (Some(TravelRuleStatus::Sent), Some(reference)) => adapter.poll(counterparty, transfer.transfer_id, reference).await,
+            _ => {
+                let transaction: Transaction = crate::schema::transactions::table.find(transfer.transaction_id).first(conn)?;
+                let message = TransferMessage {
+                    transfer_id: transfer.transfer_id,
+                    asset: transaction.currency_code.clone(),
+                    amount: transaction.amount,
+                    destination_address: transfer.destination_address.clone(),
+                    ivms101: payload_of(transfer)?,
+                };
+                adapter.send(counterparty, &message).await
+            }
+        };
+        apply_reply(conn, self.config, transfer.transfer_id, reply, Utc::now())?;
+        Ok(())
+    }
+}
+
+/// Expires withdrawals held longer than the hold window. Nothing was reserved, so nothing is released.
+pub fn expire_transfers(conn: &mut PgConnection, now: DateTime<Utc>) -> Result<usize, DomainError> {
+    use crate::schema::travel_rule_transfers::dsl as tr;
+    conn.transaction(|conn| {
+        let holding = [TravelRuleStatus::AwaitingData, TravelRuleStatus::Ready, TravelRuleStatus::Sent].map(|s| s.as_str());
+        let lapsed: Vec<TravelRuleTransfer> = tr::travel_rule_transfers
+            .filter(tr::status.eq_any(holding))
+            .filter(tr::expires_at.le(now))
+            .for_update()
+            .skip_locked()
+            .load(conn)?;
+        for transfer in &lapsed {
+            diesel::update(tr::travel_rule_transfers.find(transfer.transfer_id))
+                .set((tr::status.eq(TravelRuleStatus::Expired.as_str()), tr::next_attempt_at.eq(None::<DateTime<Utc>>), tr::completed_at.eq(Some(now))))
+                .execute(conn)?;
+            let transaction = lock_transaction(conn, transfer.transaction_id)?;
+            if transaction.status == TransactionStatus::RequiresAction.to_string() {
+                let update = TransitionUpdate {
+                    metadata: Some(json!({"travel_rule": {"transfer_id": transfer.transfer_id, "status": TravelRuleStatus::Expired.as_str(), "expired_at": now}})),
+                    ..Default::default()
+                };
+                state_machine::apply_transition(conn, &transaction, TransactionStatus::Expired, update, ACTOR)?;
+            }
+            audit::log_db_audit_event(
+                conn, Some(transfer.initiator_user_id), ACTOR, "TRAVEL_RULE_HOLD_EXPIRED", Some(AuditTargetType::TravelRuleTransfer),
+                Some(&transfer.transfer_id.to_string()), AuditOutcome::Failure,
+                Some(json!({"transaction_id": transfer.transaction_id, "status": transfer.status, "missing_fields": transfer.missing_fields})),
+                Some("Travel Rule hold expired"),
+            )?;
+            log::warn!("Withdrawal {} expired waiting for the Travel Rule ({})", transfer.transaction_id, transfer.status);
+        }
+        Ok(lapsed.len())
+    })
+}
+
+/// The Travel Rule record of one of the customer's withdrawals.
+pub fn transfer_for_withdrawal(conn: &mut PgConnection, transaction_id: Uuid, user_id: Uuid) -> Result<TravelRuleTransfer, DomainError> {
+    use crate::schema::travel_rule_transfers::dsl as tr;
+    tr::travel_rule_transfers
+        .filter(tr::transaction_id.eq(transaction_id))
+        .filter(tr::initiator_user_id.eq(user_id))
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("No Travel Rule data for withdrawal {}", transaction_id)))
+}
+
+pub fn get_transfer(conn: &mut PgConnection, transfer_id: Uuid) -> Result<TravelRuleTransfer, DomainError> {
+    use crate::schema::travel_rule_transfers::dsl as tr;
+    tr::travel_rule_transfers
+        .find(transfer_id)
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("Travel Rule transfer {} not found", transfer_id)))
+}
+
+/// Transfers newest first, optionally by status.
+pub fn list_transfers(conn: &mut PgConnection, status: Option<TravelRuleStatus>) -> Result<Vec<TravelRuleTransfer>, DomainError> {
+    use crate::schema::travel_rule_transfers::dsl as tr;
+    let mut query = tr::travel_rule_transfers.into_boxed();
+    if let Some(status) = status {
+        query = query.filter(tr::status.eq(status.as_str()));
+    }
+    Ok(query.order(tr::created_at.desc()).limit(500).load(conn)?)
+}
@@ -0,0 +1,310 @@
+// /home/inno/elights_jobes-research/backend/domain/src/treasury/eod.rs
+// End-of-day nostro positions. For each nostro account the bank's current balance is fetched, the flows
+// expected on the next business day are projected against it (see `liquidity`) and shortfalls against the
+// account's minimum balance are flagged. Per currency, the banks' balances are set against our book
+// (the ledger's NOSTRO:<currency> account). The report is signed and stored once per business day.
+use super::liquidity::{self, LiquidityProjection, ProjectedFlow};
+use super::positions;
+use super::signing::{self, ReportSignature, ReportSigner};
+use crate::error::DomainError;
+use crate::ledger;
+use crate::models::{
+    AuditOutcome, AuditTargetType, EodPositionSnapshot, ExceptionStatus, LedgerAccount, LedgerAccountCategory,
+    NewEodPositionSnapshot,
+};
+use crate::payments::{BusinessCalendar, BusinessDayConvention};
+use crate::reconciliation::{NostroAccount, NostroAccountSet};
+use crate::security::audit;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+
+/// Balance of a nostro account as reported by the bank.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NostroBalance {
+    #[serde(with = "rust_decimal::serde::str")]
+    pub available: Decimal, // What can be paid out; projections start from this
+    #[serde(with = "rust_decimal::serde::str")]
+    pub ledger: Decimal, // Booked balance; compared with our book
+    pub as_of: DateTime<Utc>,
+}
+
+/// Where balances come from (the bank clients, behind the API layer).
+#[async_trait]
+pub trait BalanceSource: Send + Sync {
+    async fn fetch_balance(&self, account: &NostroAccount) -> Result<NostroBalance, DomainError>;
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NostroPosition {
+    pub nostro_account: String,
+    pub bank: String,
+    pub currency: String,
+    pub balance: Option<NostroBalance>,
+    pub balance_error: Option<String>, // Why the bank's balance is missing
+    pub flows: Vec<ProjectedFlow>,
+    pub projection: Option<LiquidityProjection>, // None without a balance to start from
+    pub open_breaks: i64, // Open reconciliation breaks; the balance may not explain itself yet
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CurrencyPosition {
+    pub currency: String,
+    pub accounts: usize,
+    pub missing_balances: usize, // Accounts whose balance could not be fetched (totals leave them out)
+    #[serde(with = "rust_decimal::serde::str")]
+    pub bank_ledger_balance: Decimal, // Actual
+    #[serde(with = "rust_decimal::serde::str")]
+    pub book_balance: Decimal, // Expected: our NOSTRO:<currency> ledger account
+    #[serde(with = "rust_decimal::serde::str")]
+    pub difference: Decimal, // Actual - expected
+    #[serde(with = "rust_decimal::serde::str")]
+    pub bank_available_balance: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub projected_closing: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub shortfall: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct EodPositionReport {
+    pub business_date: NaiveDate,
+    pub projection_date: NaiveDate,
+    pub generated_at: DateTime<Utc>,
+    pub accounts: Vec<NostroPosition>,
+    pub currencies: Vec<CurrencyPosition>,
+    pub shortfalls: Vec<String>, // Accounts projected below their minimum balance
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct SnapshotVerification {
+    pub business_date: NaiveDate,
+    pub valid: bool,
+    pub signed_with_current_key: bool,
+    pub error: Option<String>,
+}
+
+/// The business day after `business_date`.
+pub fn next_business_day(calendar: &BusinessCalendar, business_date: NaiveDate) -> NaiveDate {
+    calendar.adjust(business_date + Duration::days(1), BusinessDayConvention::Following)
+}
+
+/// Fetches every account's balance; a failing bank only leaves its own accounts without one.
+pub async fn fetch_balances(
+    source: &dyn BalanceSource,
+    accounts: &NostroAccountSet,
+) -> HashMap<String, Result<NostroBalance, String>> {
+    let mut balances = HashMap::new();
+    for account in &accounts.accounts {
+        let balance = source.fetch_balance(account).await.map_err(|e| {
+            log::error!("Balance of nostro account {} unavailable: {}", account.code, e);
+            e.to_string()
+        });
+        balances.insert(account.code.clone(), balance);
+    }
+    balances
+}
+
+/// Our book balance for a currency's nostro, without creating the ledger account if it never moved.
+fn book_balance(conn: &mut PgConnection, currency: &str) -> Result<Decimal, DomainError> {
+    use crate::schema::ledger_accounts::dsl as la;
+    let code = format!("{}:{}", LedgerAccountCategory::Nostro.as_str(), currency);
+    let account: Option<LedgerAccount> = la::ledger_accounts.filter(la::account_code.eq(&code)).first(conn).optional()?;
+    match account {
+        Some(account) => ledger::account_balance(conn, &account),
+        None => Ok(Decimal::ZERO),
+    }
+}
+
+fn open_breaks(conn: &mut PgConnection, account_code: &str) -> Result<i64, DomainError> {
+    use crate::schema::reconciliation_exceptions::dsl as re;
+    Ok(re::reconciliation_exceptions
+        .filter(re::nostro_account.eq(account_code))
+        .filter(re::status.eq(ExceptionStatus::Open.as_str()))
+        .count()
+        .get_result(conn)?)
+}
+
+/// Positions from fetched `balances`, projecting `projection_date` (from `now` if that day has begun).
+pub fn build_report(
+    conn: &mut PgConnection,
+    accounts: &NostroAccountSet,
+    balances: &HashMap<String, Result<NostroBalance, String>>,
+    business_date: NaiveDate,
+    projection_date: NaiveDate,
+    now: DateTime<Utc>,
+) -> Result<EodPositionReport, DomainError> {
+    let projection_start = projection_date.and_hms_opt(0, 0, 0).map(|t| t.and_utc())
+        .ok_or_else(|| DomainError::Validation("Invalid projection date".to_string()))?;
+    let day_start = projection_start.max(now);
+    let day_end = projection_start + Duration::days(1);
+    let mut flows = positions::expected_flows(conn, accounts, projection_date, now)?;
+
+    let mut report = EodPositionReport {
+        business_date,
+        projection_date,
+        generated_at: now,
+        accounts: Vec::new(),
+        currencies: Vec::new(),
+        shortfalls: Vec::new(),
+    };
+    for account in &accounts.accounts {
+        let account_flows = flows.remove(&account.code).unwrap_or_default();
+        let (balance, balance_error) = match balances.get(&account.code) {
+            Some(Ok(balance)) => (Some(balance.clone()), None),
+            Some(Err(e)) => (None, Some(e.clone())),
+            None => (None, Some("Balance not fetched".to_string())),
+        };
+        let minimum = account.minimum_balance.unwrap_or(Decimal::ZERO);
+        let projection = balance.as_ref()
+            .map(|balance| liquidity::project(balance.available, day_start, day_end, &account_flows, minimum));
+        if let Some(projection) = projection.as_ref().filter(|projection| projection.has_shortfall()) {
+            log::warn!("Nostro {} projected {} below its minimum of {} on {} (lowest {} at {})",
+                account.code, projection.shortfall, minimum, projection_date, projection.lowest, projection.lowest_at);
+            report.shortfalls.push(account.code.clone());
+        }
+        report.accounts.push(NostroPosition {
+            nostro_account: account.code.clone(),
+            bank: account.bank.clone(),
+            currency: account.currency.clone(),
+            balance,
+            balance_error,
+            flows: account_flows,
+            projection,
+            open_breaks: open_breaks(conn, &account.code)?,
+        });
+    }
+
+    let mut by_currency: BTreeMap<String, Vec<&NostroPosition>> = BTreeMap::new();
+    for position in &report.accounts {
+        by_currency.entry(position.currency.clone()).or_default().push(position);
+    }
+    for (currency, positions) in by_currency {
+        let with_balance: Vec<(&NostroBalance, &LiquidityProjection)> = positions.iter()
+            .filter_map(|position| Some((position.balance.as_ref()?, position.projection.as_ref()?)))
+            .collect();
+        let bank_ledger_balance: Decimal = with_balance.iter().map(|(balance, _)| balance.ledger).sum();
+        let book_balance = book_balance(conn, &currency)?;
+        report.currencies.push(CurrencyPosition {
+            accounts: positions.len(),
+            missing_balances: positions.len() - with_balance.len(),
+            bank_ledger_balance,
+            book_balance,
+            difference: bank_ledger_balance - book_balance,
+            bank_available_balance: with_balance.iter().map(|(balance, _)| balance.available).sum(),
+            projected_closing: with_balance.iter().map(|(_, projection)| projection.closing).sum(),
+            shortfall: with_balance.iter().map(|(_, projection)| projection.shortfall).sum(),
+            currency,
+        });
+    }
+    Ok(report)
+}
+
+/// Signs `report` and stores it as the snapshot of its business day, replacing an earlier run.
+pub fn store_snapshot(
+    conn: &mut PgConnection,
+    report: &EodPositionReport,
+    signer: &ReportSigner,
+    generated_by: &str,
+) -> Result<EodPositionSnapshot, DomainError> {
+    use crate::schema::eod_position_snapshots::dsl as eps;
+    let payload = serde_json::to_string(report)
+        .map_err(|e| DomainError::Internal(format!("Cannot serialize EOD position report: {}", e)))?;
+    let report_json = serde_json::to_value(report)
+        .map_err(|e| DomainError::Internal(format!("Cannot serialize EOD position report: {}", e)))?;
+    let signature = signer.sign(payload.as_bytes());
+    let snapshot = NewEodPositionSnapshot {
+        business_date: report.business_date,
+        projection_date: report.projection_date,
+        report: report_json,
+        payload: &payload,
+        payload_sha256: &signature.payload_sha256,
+        signature_algorithm: &signature.algorithm,
+        signature: &signature.signature,
+        signing_key_id: &signature.key_id,
+        signing_public_key: &signature.public_key,
+        account_count: report.accounts.len() as i32,
+        shortfall_count: report.shortfalls.len() as i32,
+        generated_by,
+        generated_at: report.generated_at,
+    };
+
+    conn.transaction(|conn| {
+        let snapshot: EodPositionSnapshot = diesel::insert_into(eps::eod_position_snapshots)
+            .values(&snapshot)
+            .on_conflict(eps::business_date)
+            .do_update()
+            .set(&snapshot)
+            .get_result(conn)?;
+        audit::log_db_audit_event(
+            conn, None, generated_by, "EOD_POSITION_SNAPSHOT", Some(AuditTargetType::EodPositionSnapshot),
+            Some(&snapshot.snapshot_id.to_string()), AuditOutcome::Success,
+            Some(json!({"business_date": snapshot.business_date, "shortfalls": report.shortfalls,
+                "signing_key_id": snapshot.signing_key_id, "payload_sha256": snapshot.payload_sha256})),
+            None,
+        )?;
+        Ok(snapshot)
+    })
+}
+
+/// The end-of-day process for `business_date`: fetch balances, project the next business day, sign, store.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_end_of_day(
+    conn: &mut PgConnection,
+    source: &dyn BalanceSource,
+    accounts: &NostroAccountSet,
+    calendar: &BusinessCalendar,
+    signer: &ReportSigner,
+    business_date: NaiveDate,
+    generated_by: &str,
+    now: DateTime<Utc>,
+) -> Result<EodPositionSnapshot, DomainError> {
+    let balances = fetch_balances(source, accounts).await;
+    let projection_date = next_business_day(calendar, business_date);
+    let report = build_report(conn, accounts, &balances, business_date, projection_date, now)?;
+    let snapshot = store_snapshot(conn, &report, signer, generated_by)?;
+    log::info!("EOD position snapshot for {} stored ({} accounts, {} shortfalls, key {})",
+        business_date, snapshot.account_count, snapshot.shortfall_count, snapshot.signing_key_id);
+    Ok(snapshot)
+}
+
+pub fn get_snapshot(conn: &mut PgConnection, business_date: NaiveDate) -> Result<EodPositionSnapshot, DomainError> {
+    use crate::schema::eod_position_snapshots::dsl as eps;
+    eps::eod_position_snapshots
+        .filter(eps::business_date.eq(business_date))
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("No EOD position snapshot for {}", business_date)))
+}
+
+/// Snapshots between two business dates, newest first.
+pub fn list_snapshots(conn: &mut PgConnection, from: NaiveDate, to: NaiveDate) -> Result<Vec<EodPositionSnapshot>, DomainError> {
+    use crate::schema::eod_position_snapshots::dsl as eps;
+    Ok(eps::eod_position_snapshots
+        .filter(eps::business_date.ge(from))
+        .filter(eps::business_date.le(to))
+        .order(eps::business_date.desc())
+        .load(conn)?)
+}
+
+/// Checks a stored snapshot's signature over its payload, and whether `signer` is the key that made it.
+pub fn verify_snapshot(snapshot: &EodPositionSnapshot, signer: &ReportSigner) -> SnapshotVerification {
+    let signature = ReportSignature {
+        algorithm: snapshot.signature_algorithm.clone(),
+        key_id: snapshot.signing_key_id.clone(),
+        public_key: snapshot.signing_public_key.clone(),
+        payload_sha256: snapshot.payload_sha256.clone(),
+        signature: snapshot.signature.clone(),
+    };
+    let result = signing::verify(snapshot.payload.as_bytes(), &signature);
+    SnapshotVerification {
+        business_date: snapshot.business_date,
+        valid: result.is_ok(),
+        signed_with_current_key: signing::key_id(&signer.public_key()) == snapshot.signing_key_id,
+        error: result.err().map(|e| e.to_string()),
+    }
+}
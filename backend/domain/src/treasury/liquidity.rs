@@ -0,0 +1,156 @@
+// /home/inno/elights_jobes-research/backend/domain/src/treasury/liquidity.rs
+// Intraday liquidity projection for one nostro account: the bank's balance at the start of the day plus
+// the flows expected during it. Flows without a known time are placed conservatively (outgoing at the
+// start of the day, incoming at its end), so the lowest projected point is what treasury must fund.
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FlowKind {
+    PendingOutgoing,   // Outbound payments created but not settled yet
+    ScheduledOutgoing, // Standing order occurrences due on the day
+    ExpectedIncoming,  // Inbound payments announced but not confirmed yet
+}
+
+/// One expected movement on the account, signed: incoming positive, outgoing negative.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ProjectedFlow {
+    pub kind: FlowKind,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub amount: Decimal,
+    pub expected_at: Option<DateTime<Utc>>, // None = some time during the day
+    pub reference: String, // Transaction id or schedule id
+}
+
+/// Projected balance right after the flows at `at`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PositionPoint {
+    pub at: DateTime<Utc>,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub balance: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LiquidityProjection {
+    #[serde(with = "rust_decimal::serde::str")]
+    pub opening: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub outgoing: Decimal, // Sum of outgoing flows (negative or zero)
+    #[serde(with = "rust_decimal::serde::str")]
+    pub incoming: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub closing: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub lowest: Decimal,
+    pub lowest_at: DateTime<Utc>,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub minimum_balance: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub shortfall: Decimal, // How far the lowest point falls below the minimum (zero if it does not)
+    pub points: Vec<PositionPoint>,
+}
+
+impl LiquidityProjection {
+    pub fn has_shortfall(&self) -> bool {
+        self.shortfall > Decimal::ZERO
+    }
+}
+
+/// When a flow is assumed to happen within `[day_start, day_end]`.
+fn placement(flow: &ProjectedFlow, day_start: DateTime<Utc>, day_end: DateTime<Utc>) -> DateTime<Utc> {
+    match flow.expected_at {
+        Some(at) => at.clamp(day_start, day_end), // Overdue flows happen first thing
+        None if flow.amount < Decimal::ZERO => day_start,
+        None => day_end,
+    }
+}
+
+/// Projects the day from `opening` at `day_start`. Flows at the same moment are applied outgoing first.
+pub fn project(
+    opening: Decimal,
+    day_start: DateTime<Utc>,
+    day_end: DateTime<Utc>,
+    flows: &[ProjectedFlow],
+    minimum_balance: Decimal,
+) -> LiquidityProjection {
+    let mut placed: Vec<(DateTime<Utc>, Decimal)> = flows.iter()
+        .map(|flow| (placement(flow, day_start, day_end), flow.amount))
+        .collect();
+    placed.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
+
+    let mut balance = opening;
+    let mut lowest = (opening, day_start);
+    let mut points = vec![PositionPoint { at: day_start, balance: opening }];
+    for (at, amount) in placed {
+        balance += amount;
+        if balance < lowest.0 {
+            lowest = (balance, at);
+        }
+        match points.last_mut() {
+            Some(point) if point.at == at => point.balance = balance,
+            _ => points.push(PositionPoint { at, balance }),
+        }
+    }
+    let outgoing = flows.iter().map(|flow| flow.amount).filter(|amount| *amount < Decimal::ZERO).sum();
+    let incoming = flows.iter().map(|flow| flow.amount).filter(|amount| *amount > Decimal::ZERO).sum();
+    LiquidityProjection {
+        opening,
+        outgoing,
+        incoming,
+        closing: balance,
+        lowest: lowest.0,
+        lowest_at: lowest.1,
+        minimum_balance,
+        shortfall: (minimum_balance - lowest.0).max(Decimal::ZERO),
+        points,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use rust_decimal_macros::dec;
+
+    fn flow(kind: FlowKind, amount: Decimal, expected_at: Option<DateTime<Utc>>) -> ProjectedFlow {
+        ProjectedFlow { kind, amount, expected_at, reference: "ref".to_string() }
+    }
+
+    #[test]
+    fn test_undated_flows_are_placed_conservatively() {
+        let start = Utc.with_ymd_and_hms(2025, 4, 22, 0, 0, 0).unwrap();
+        let end = start + Duration::days(1);
+        let flows = vec![
+            flow(FlowKind::ExpectedIncoming, dec!(500), None),
+            flow(FlowKind::PendingOutgoing, dec!(-800), None),
+            flow(FlowKind::ScheduledOutgoing, dec!(-300), Some(start + Duration::hours(10))),
+        ];
+        let projection = project(dec!(1000), start, end, &flows, dec!(0));
+        // Outgoing 800 at the start, 300 at 10:00, incoming 500 only at the end of the day
+        assert_eq!(projection.lowest, dec!(-100));
+        assert_eq!(projection.lowest_at, start + Duration::hours(10));
+        assert_eq!(projection.closing, dec!(400));
+        assert_eq!(projection.outgoing, dec!(-1100));
+        assert_eq!(projection.incoming, dec!(500));
+        assert_eq!(projection.shortfall, dec!(100));
+        assert_eq!(projection.points.len(), 3);
+        assert_eq!(projection.points[0].balance, dec!(200));
+    }
+
+    #[test]
+    fn test_no_shortfall_above_minimum() {
+        let start = Utc.with_ymd_and_hms(2025, 4, 22, 0, 0, 0).unwrap();
+        let end = start + Duration::days(1);
+        let overdue = flow(FlowKind::PendingOutgoing, dec!(-200), Some(start - Duration::days(2)));
+        let projection = project(dec!(1000), start, end, &[overdue], dec!(500));
+        assert_eq!(projection.lowest, dec!(800));
+        assert_eq!(projection.lowest_at, start);
+        assert!(!projection.has_shortfall());
+
+        let empty = project(dec!(300), start, end, &[], dec!(500));
+        assert_eq!(empty.closing, dec!(300));
+        assert_eq!(empty.shortfall, dec!(200));
+    }
+}
@@ -0,0 +1,17 @@
+// /home/inno/elights_jobes-research/backend/domain/src/treasury/mod.rs
+// Treasury: end-of-day nostro positions, intraday liquidity projections against configured minimum
+// balances, and the signed position report stored for each business day.
+
+pub mod liquidity; // Intraday balance projection and shortfall detection
+pub mod positions; // Expected flows per nostro account (in-flight payments, standing orders, inbound notices)
+pub mod signing; // Ed25519 signatures over stored reports
+pub mod eod; // End-of-day run, positions per account and currency, snapshots
+
+pub use liquidity::{project, FlowKind, LiquidityProjection, PositionPoint, ProjectedFlow};
+pub use positions::expected_flows;
+pub use signing::{ReportSignature, ReportSigner};
+pub use eod::{
+    build_report, fetch_balances, get_snapshot, list_snapshots, next_business_day, run_end_of_day, store_snapshot,
+    verify_snapshot, BalanceSource, CurrencyPosition, EodPositionReport, NostroBalance, NostroPosition,
+    SnapshotVerification,
+};
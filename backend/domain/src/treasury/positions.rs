@@ -0,0 +1,105 @@
+// /home/inno/elights_jobes-research/backend/domain/src/treasury/positions.rs
+// What is expected to move through each nostro account on a day:
+// - outbound payments created but not settled (and not yet found on a statement),
+// - standing orders due on or before the day, and claimed runs not yet submitted,
+// - inbound payments announced but not confirmed.
+// A payment is assigned to the first configured account that carries its currency and rail.
+use crate::error::DomainError;
+use crate::fees::FeeRail;
+use crate::models::{
+    PaymentSchedule, PaymentScheduleRun, ScheduleRunStatus, ScheduleStatus, Transaction, TransactionStatus,
+    TransactionType,
+};
+use crate::reconciliation::engine::signed_amount;
+use crate::reconciliation::accounts::touches_nostro;
+use crate::reconciliation::NostroAccountSet;
+use crate::treasury::liquidity::{FlowKind, ProjectedFlow};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// In-flight payments older than this are treated as stuck rather than expected (they show up as breaks).
+const FLOW_LOOKBACK_DAYS: i64 = 30;
+
+fn rail_of(transaction_type: &str) -> Option<FeeRail> {
+    TransactionType::parse(transaction_type).as_ref().and_then(FeeRail::for_transaction_type)
+}
+
+fn push(flows: &mut HashMap<String, Vec<ProjectedFlow>>, accounts: &NostroAccountSet, currency: &str, rail: Option<FeeRail>, flow: ProjectedFlow) {
+    match accounts.carrier_for(currency, rail) {
+        Some(account) => flows.entry(account.code.clone()).or_default().push(flow),
+        None => log::debug!("No nostro account carries {} {:?}; flow {} left out of projections", currency, rail, flow.reference),
+    }
+}
+
+/// Expected flows for `projection_date`, by nostro account code.
+pub fn expected_flows(
+    conn: &mut PgConnection,
+    accounts: &NostroAccountSet,
+    projection_date: NaiveDate,
+    now: DateTime<Utc>,
+) -> Result<HashMap<String, Vec<ProjectedFlow>>, DomainError> {
+    use crate::schema::payment_schedule_runs::dsl as r;
+    use crate::schema::payment_schedules::dsl as ps;
+    use crate::schema::reconciliation_match_transactions::dsl as rmt;
+    use crate::schema::transactions::dsl as t;
+
+    let mut flows: HashMap<String, Vec<ProjectedFlow>> = HashMap::new();
+
+    let in_flight: Vec<Transaction> = t::transactions
+        .filter(t::status.eq_any(vec![
+            TransactionStatus::Pending.to_string(),
+            TransactionStatus::Processing.to_string(),
+            TransactionStatus::RequiresAction.to_string(),
+            TransactionStatus::Submitted.to_string(),
+        ]))
+        .filter(t::created_at.ge(now - Duration::days(FLOW_LOOKBACK_DAYS)))
+        .filter(diesel::dsl::not(diesel::dsl::exists(
+            rmt::reconciliation_match_transactions.filter(rmt::transaction_id.eq(t::transaction_id)),
+        )))
+        .load(conn)?;
+    for transaction in in_flight {
+        let external_leg = transaction.debit_wallet_id.is_none() || transaction.credit_wallet_id.is_none();
+        let tx_type = TransactionType::parse(&transaction.transaction_type).unwrap_or(TransactionType::Unknown);
+        if !external_leg || !touches_nostro(&tx_type) {
+            continue; // Wallet-to-wallet moves, fees and interest never touch a nostro
+        }
+        let amount = signed_amount(&transaction);
+        let kind = if amount > Decimal::ZERO { FlowKind::ExpectedIncoming } else { FlowKind::PendingOutgoing };
+        let flow = ProjectedFlow { kind, amount, expected_at: None, reference: transaction.transaction_id.to_string() };
+        push(&mut flows, accounts, &transaction.currency_code, FeeRail::for_transaction_type(&tx_type), flow);
+    }
+
+    let due_schedules: Vec<PaymentSchedule> = ps::payment_schedules
+        .filter(ps::status.eq(ScheduleStatus::Active.as_str()))
+        .filter(ps::next_run_date.le(projection_date))
+        .load(conn)?;
+    for schedule in due_schedules {
+        let flow = ProjectedFlow {
+            kind: FlowKind::ScheduledOutgoing,
+            amount: -schedule.amount,
+            expected_at: None,
+            reference: schedule.schedule_id.to_string(),
+        };
+        push(&mut flows, accounts, &schedule.currency_code, rail_of(&schedule.payment_type), flow);
+    }
+
+    // Claimed but not submitted yet; once submitted, the payment is counted as an in-flight transaction
+    let pending_runs: Vec<(PaymentScheduleRun, PaymentSchedule)> = r::payment_schedule_runs
+        .inner_join(ps::payment_schedules)
+        .filter(r::status.eq(ScheduleRunStatus::Pending.as_str()))
+        .filter(r::transaction_id.is_null())
+        .filter(r::run_date.le(projection_date))
+        .load(conn)?;
+    for (run, schedule) in pending_runs {
+        let flow = ProjectedFlow {
+            kind: FlowKind::ScheduledOutgoing,
+            amount: -schedule.amount,
+            expected_at: None,
+            reference: run.run_id.to_string(),
+        };
+        push(&mut flows, accounts, &schedule.currency_code, rail_of(&schedule.payment_type), flow);
+    }
+    Ok(flows)
+}
@@ -0,0 +1,154 @@
+// /home/inno/elights_jobes-research/backend/domain/src/webhooks/dispatcher.rs
+// Applies a stored event's normalized status events through `PaymentProcessor::update_payment_status`,
+// so webhook updates get the same transition validation, ledger effects and audit trail as any other
+// status change. Events for payments we do not know and out-of-order events (e.g. "processing" after
+// "succeeded") are recorded in the results and skipped rather than failing the delivery.
+use super::providers::{PaymentRef, PaymentStatusEvent, WebhookProvider};
+use super::store;
+use crate::error::DomainError;
+use crate::models::{Transaction, TransactionStatus, TransactionType, WebhookEvent};
+use crate::payments::state_machine;
+use crate::payments::PaymentProcessor;
+use chrono::Utc;
+use diesel::prelude::*;
+use serde::Serialize;
+use serde_json::json;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EventResult {
+    Applied,
+    Unchanged, // Already in that status
+    Stale,     // Transition not allowed from the current status (late or out-of-order event)
+    Unmatched, // No such payment on our side
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AppliedEvent {
+    pub payment: PaymentRef,
+    pub transaction_id: Option<Uuid>,
+    pub status: TransactionStatus,
+    pub result: EventResult,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct WebhookDispatch {
+    pub event: WebhookEvent,
+    pub results: Vec<AppliedEvent>,
+}
+
+/// The transaction an event refers to, among the kinds of payment `types` the provider reports on.
+/// A provider reference is only unique per rail: if it still names more than one of them, nothing is
+/// guessed and the event fails.
+pub fn resolve_transaction(
+    conn: &mut PgConnection,
+    types: &[TransactionType],
+    payment: &PaymentRef,
+) -> Result<Option<Transaction>, DomainError> {
+    use crate::schema::transactions::dsl as t;
+    let types: Vec<String> = types.iter().map(TransactionType::to_string).collect();
+    let transaction = match payment {
+        PaymentRef::TransactionId(id) => t::transactions
+            .find(*id)
+            .filter(t::transaction_type.eq_any(&types))
+            .first(conn)
+            .optional()?,
+        PaymentRef::ExternalRef(reference) => {
+            let mut matches: Vec<Transaction> = t::transactions
+                .filter(t::external_ref_id.eq(reference))
+                .filter(t::transaction_type.eq_any(&types))
+                .limit(2)
+                .load(conn)?;
+            if matches.len() > 1 {
+                return Err(DomainError::Internal(format!("Provider reference '{}' matches more than one payment", reference)));
+            }
+            matches.pop()
+        }
+    };
+    Ok(transaction)
+}
+
+/// What applying `to` to `transaction` would do.
+pub fn classify(transaction: &Transaction, to: &TransactionStatus) -> EventResult {
+    let tx_type = TransactionType::parse(&transaction.transaction_type).unwrap_or(TransactionType::Unknown);
+    let Some(from) = TransactionStatus::parse(&transaction.status) else {
+        return EventResult::Stale;
+    };
+    if from == *to {
+        return EventResult::Unchanged;
+    }
+    match state_machine::transition_effect(&tx_type, &from, to) {
+        Some(_) => EventResult::Applied,
+        None => EventResult::Stale,
+    }
+}
+
+async fn apply_event(
+    processor: &mut PaymentProcessor<'_>,
+    provider: &dyn WebhookProvider,
+    event: &WebhookEvent,
+    status_event: &PaymentStatusEvent,
+) -> Result<AppliedEvent, DomainError> {
+    let transaction = resolve_transaction(processor.connection(), provider.transaction_types(), &status_event.payment)?;
+    let Some(transaction) = transaction else {
+        log::warn!("Webhook {} from '{}': no payment for {:?}", event.provider_event_id, provider.name(), status_event.payment);
+        return Ok(AppliedEvent {
+            payment: status_event.payment.clone(), transaction_id: None, status: status_event.status.clone(), result: EventResult::Unmatched,
+        });
+    };
+    let result = classify(&transaction, &status_event.status);
+    if result == EventResult::Stale {
+        log::warn!("Webhook {} from '{}': Tx {} is {}, ignoring {:?}",
+            event.provider_event_id, provider.name(), transaction.transaction_id, transaction.status, status_event.status);
+    }
+    if result == EventResult::Applied {
+        let settled = matches!(status_event.status, TransactionStatus::Completed | TransactionStatus::Settled);
+        let settlement_time = settled.then(|| status_event.occurred_at.unwrap_or_else(Utc::now));
+        // Keep the reference the payment was sent with; the provider's only fills a gap
+        let external_ref = transaction.external_ref_id.is_none().then_some(status_event.external_ref.as_deref()).flatten();
+        let metadata = json!({ "webhook": {
+            "provider": provider.name(),
+            "event_id": event.event_id,
+            "provider_event_id": event.provider_event_id,
+            "event_type": event.event_type,
+            "reason": status_event.reason,
+        }});
+        processor.update_payment_status(
+            transaction.transaction_id, status_event.status.clone(), external_ref, settlement_time, Some(metadata),
+        ).await?;
+    }
+    Ok(AppliedEvent {
+        payment: status_event.payment.clone(),
+        transaction_id: Some(transaction.transaction_id),
+        status: status_event.status.clone(),
+        result,
+    })
+}
+
+/// Applies a stored event (first delivery, retry or reprocess request) and records the attempt.
+pub async fn dispatch(
+    processor: &mut PaymentProcessor<'_>,
+    provider: &dyn WebhookProvider,
+    event: &WebhookEvent,
+) -> Result<WebhookDispatch, DomainError> {
+    if event.provider != provider.name() {
+        return Err(DomainError::Internal(format!("Webhook event {} belongs to '{}'", event.event_id, event.provider)));
+    }
+    let parsed = provider.parse(event.raw_payload.as_bytes())?;
+
+    let mut results = Vec::with_capacity(parsed.events.len());
+    for status_event in &parsed.events {
+        match apply_event(processor, provider, event, status_event).await {
+            Ok(applied) => results.push(applied),
+            Err(e) => {
+                log::error!("Webhook {} from '{}' failed: {}", event.provider_event_id, provider.name(), e);
+                store::mark_failed(processor.connection(), event.event_id, &e.to_string())?;
+                return Err(e);
+            }
+        }
+    }
+    let event = store::mark_processed(processor.connection(), event.event_id, json!(results))?;
+    log::info!("Webhook {} from '{}' processed ({} status events)", event.provider_event_id, provider.name(), results.len());
+    Ok(WebhookDispatch { event, results })
+}
@@ -0,0 +1,16 @@
+// /home/inno/elights_jobes-research/backend/domain/src/webhooks/mod.rs
+// Provider webhooks: each delivery is verified (HMAC with timestamp tolerance, HMAC, Ed25519), stored raw
+// with a seen-event check against replays, parsed into normalized payment status events and applied
+// through the payment processor. Stored events can be reprocessed.
+
+pub mod signature; // HMAC / timestamped HMAC / Ed25519 verification
+pub mod providers; // Provider trait, the providers we accept and the registry
+pub mod store; // Raw payload storage and the seen-event store
+pub mod dispatcher; // Applying status events through update_payment_status
+
+pub use providers::{
+    BtcPayProvider, MockWebhookProvider, ParsedWebhook, PartnerBankProvider, PaymentRef, PaymentStatusEvent, StripeProvider,
+    WebhookProvider, WebhookRegistry, WebhookRequest,
+};
+pub use store::{accept_delivery, claim_for_reprocess, get_event, list_events, Delivery};
+pub use dispatcher::{dispatch, AppliedEvent, EventResult, WebhookDispatch};
@@ -0,0 +1,458 @@
+// /home/inno/elights_jobes-research/backend/domain/src/webhooks/providers.rs
+// One `WebhookProvider` per sender: how its deliveries are signed and how its payloads map to
+// normalized payment status events. Providers only verify and parse; storing and applying the
+// events is shared (`store`, `dispatcher`).
+use super::signature::{self, DEFAULT_TOLERANCE_SECONDS};
+use crate::error::DomainError;
+use crate::models::{TransactionStatus, TransactionType};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use ed25519_dalek::VerifyingKey;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A delivery as received, before anything is trusted.
+#[derive(Debug, Clone)]
+pub struct WebhookRequest<'a> {
+    pub headers: Vec<(String, String)>,
+    pub body: &'a [u8],
+    pub received_at: DateTime<Utc>,
+}
+
+impl WebhookRequest<'_> {
+    /// Header value by case-insensitive name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+
+    fn required_header(&self, name: &str) -> Result<&str, DomainError> {
+        self.header(name).ok_or_else(|| DomainError::Authentication(format!("Webhook signature rejected: missing {} header", name)))
+    }
+}
+
+/// How an event points at one of our payments.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(tag = "by", content = "value", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PaymentRef {
+    TransactionId(Uuid), // We passed our id to the provider (metadata, end-to-end id)
+    ExternalRef(String), // The provider's id, stored as the transaction's external_ref_id
+}
+
+/// A provider event reduced to what our state machine understands.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct PaymentStatusEvent {
+    pub payment: PaymentRef,
+    pub status: TransactionStatus,
+    pub external_ref: Option<String>, // Recorded on the transaction if it has none yet
+    pub occurred_at: Option<DateTime<Utc>>,
+    pub reason: Option<String>, // Failure/return reason for the metadata
+}
+
+/// A parsed delivery. Event types we do not act on parse to no status events.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ParsedWebhook {
+    pub provider_event_id: String, // Seen-event key
+    pub event_type: String,
+    pub events: Vec<PaymentStatusEvent>,
+}
+
+/// A webhook sender.
+pub trait WebhookProvider: Send + Sync {
+    /// Name used in the webhook URL and stored with each event.
+    fn name(&self) -> &str;
+    /// Headers the signature depends on; stored with the raw payload.
+    fn signature_headers(&self) -> &'static [&'static str];
+    /// Checks the delivery's signature (and timestamp, where the scheme has one).
+    fn verify(&self, request: &WebhookRequest<'_>) -> Result<(), DomainError>;
+    /// Reads the provider's payload.
+    fn parse(&self, body: &[u8]) -> Result<ParsedWebhook, DomainError>;
+    /// Kinds of payment the provider reports on; its events never touch other transactions.
+    fn transaction_types(&self) -> &'static [TransactionType];
+}
+
+fn parse_json(body: &[u8]) -> Result<JsonValue, DomainError> {
+    serde_json::from_slice(body).map_err(|e| DomainError::Validation(format!("Invalid webhook JSON payload: {}", e)))
+}
+
+fn str_at<'a>(value: &'a JsonValue, pointer: &str) -> Option<&'a str> {
+    value.pointer(pointer).and_then(JsonValue::as_str).filter(|s| !s.is_empty())
+}
+
+fn required_str<'a>(value: &'a JsonValue, pointer: &str) -> Result<&'a str, DomainError> {
+    str_at(value, pointer).ok_or_else(|| DomainError::Validation(format!("Webhook payload has no {}", pointer)))
+}
+
+fn unix_time(value: &JsonValue, pointer: &str) -> Option<DateTime<Utc>> {
+    value.pointer(pointer).and_then(JsonValue::as_i64).and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+}
+
+/// Our transaction id if `id` is one, the provider's reference otherwise.
+fn payment_ref(our_id: Option<&str>, provider_ref: &str) -> PaymentRef {
+    match our_id.and_then(|id| Uuid::parse_str(id).ok()) {
+        Some(transaction_id) => PaymentRef::TransactionId(transaction_id),
+        None => PaymentRef::ExternalRef(provider_ref.to_string()),
+    }
+}
+
+// --- Stripe: timestamped HMAC (Stripe-Signature: t=..,v1=..) ---
+
+pub struct StripeProvider {
+    secret: String,
+    tolerance: Duration,
+}
+
+impl StripeProvider {
+    pub fn new(secret: &str, tolerance: Duration) -> Self {
+        StripeProvider { secret: secret.to_string(), tolerance }
+    }
+}
+
+impl WebhookProvider for StripeProvider {
+    fn name(&self) -> &str {
+        "stripe"
+    }
+
+    fn transaction_types(&self) -> &'static [TransactionType] {
+        &[TransactionType::CardAuthorization, TransactionType::CardCapture, TransactionType::CardRefund, TransactionType::CardChargeback]
+    }
+
+    fn signature_headers(&self) -> &'static [&'static str] {
+        &["Stripe-Signature"]
+    }
+
+    fn verify(&self, request: &WebhookRequest<'_>) -> Result<(), DomainError> {
+        let header = request.required_header("Stripe-Signature")?;
+        signature::verify_timestamped_hmac(self.secret.as_bytes(), request.body, header, request.received_at, self.tolerance)?;
+        Ok(())
+    }
+
+    fn parse(&self, body: &[u8]) -> Result<ParsedWebhook, DomainError> {
+        let payload = parse_json(body)?;
+        let event_id = required_str(&payload, "/id")?;
+        let event_type = required_str(&payload, "/type")?;
+        let object = payload.pointer("/data/object").cloned().unwrap_or(JsonValue::Null);
+        let occurred_at = unix_time(&payload, "/created");
+
+        let status = match event_type {
+            "payment_intent.processing" => Some(TransactionStatus::Processing),
+            "payment_intent.amount_capturable_updated" => Some(TransactionStatus::Authorized),
+            "payment_intent.succeeded" => Some(TransactionStatus::Completed),
+            "payment_intent.payment_failed" => Some(TransactionStatus::Failed),
+            "payment_intent.canceled" => Some(TransactionStatus::Cancelled),
+            "charge.dispute.created" => Some(TransactionStatus::Chargeback),
+            _ => None,
+        };
+        let events = match status {
+            Some(status) => {
+                // Disputes reference the payment intent; payment intent events are the intent itself
+                let provider_ref = str_at(&object, "/payment_intent").or_else(|| str_at(&object, "/id"))
+                    .ok_or_else(|| DomainError::Validation("Stripe event has no payment intent".to_string()))?;
+                let reason = str_at(&object, "/last_payment_error/message")
+                    .or_else(|| str_at(&object, "/cancellation_reason"))
+                    .or_else(|| str_at(&object, "/reason"))
+                    .map(str::to_string);
+                vec![PaymentStatusEvent {
+                    payment: payment_ref(str_at(&object, "/metadata/transaction_id"), provider_ref),
+                    status,
+                    external_ref: Some(provider_ref.to_string()),
+                    occurred_at,
+                    reason,
+                }]
+            }
+            None => Vec::new(),
+        };
+        Ok(ParsedWebhook { provider_event_id: event_id.to_string(), event_type: event_type.to_string(), events })
+    }
+}
+
+// --- BTCPay Server: HMAC of the body (BTCPay-Sig: sha256=..), no timestamp ---
+
+pub struct BtcPayProvider {
+    secret: String,
+}
+
+impl BtcPayProvider {
+    pub fn new(secret: &str) -> Self {
+        BtcPayProvider { secret: secret.to_string() }
+    }
+}
+
+impl WebhookProvider for BtcPayProvider {
+    fn name(&self) -> &str {
+        "btcpay"
+    }
+
+    fn transaction_types(&self) -> &'static [TransactionType] {
+        &[TransactionType::CryptoBtcReceive, TransactionType::CryptoXmrReceive] // Invoices are paid to us
+    }
+
+    fn signature_headers(&self) -> &'static [&'static str] {
+        &["BTCPay-Sig"]
+    }
+
+    fn verify(&self, request: &WebhookRequest<'_>) -> Result<(), DomainError> {
+        // No signed timestamp: replays are caught by the seen-event store only
+        let header = request.required_header("BTCPay-Sig")?;
+        signature::verify_hmac_hex(self.secret.as_bytes(), request.body, header, "sha256=")
+    }
+
+    fn parse(&self, body: &[u8]) -> Result<ParsedWebhook, DomainError> {
+        let payload = parse_json(body)?;
+        // A manual redelivery gets a new deliveryId but keeps the original one
+        let event_id = str_at(&payload, "/originalDeliveryId").map_or_else(|| required_str(&payload, "/deliveryId"), Ok)?;
+        let event_type = required_str(&payload, "/type")?;
+
+        let status = match event_type {
+            "InvoiceReceivedPayment" | "InvoiceProcessing" => Some(TransactionStatus::Processing),
+            "InvoiceSettled" => Some(TransactionStatus::Completed),
+            "InvoiceExpired" => Some(TransactionStatus::Expired),
+            "InvoiceInvalid" => Some(TransactionStatus::Failed),
+            _ => None,
+        };
+        let events = match status {
+            Some(status) => {
+                let invoice_id = required_str(&payload, "/invoiceId")?;
+                let reason = (status == TransactionStatus::Failed).then(|| "Invoice marked invalid".to_string());
+                vec![PaymentStatusEvent {
+                    payment: payment_ref(str_at(&payload, "/metadata/orderId"), invoice_id),
+                    status,
+                    external_ref: Some(invoice_id.to_string()),
+                    occurred_at: unix_time(&payload, "/timestamp"),
+                    reason,
+                }]
+            }
+            None => Vec::new(),
+        };
+        Ok(ParsedWebhook { provider_event_id: event_id.to_string(), event_type: event_type.to_string(), events })
+    }
+}
+
+// --- Partner bank: Ed25519 over "<X-Signature-Timestamp>.<body>" ---
+
+/// Payment status notifications from a bank partner signing with an Ed25519 key. The end-to-end id
+/// is the transaction id we sent in the payment message.
+pub struct PartnerBankProvider {
+    name: String,
+    public_key: VerifyingKey,
+    tolerance: Duration,
+}
+
+impl PartnerBankProvider {
+    pub fn new(name: &str, public_key_hex: &str, tolerance: Duration) -> Result<Self, DomainError> {
+        Ok(PartnerBankProvider {
+            name: name.to_string(),
+            public_key: signature::parse_ed25519_public_key(public_key_hex)?,
+            tolerance,
+        })
+    }
+}
+
+impl WebhookProvider for PartnerBankProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn transaction_types(&self) -> &'static [TransactionType] {
+        &[TransactionType::AchCredit, TransactionType::AchDebit, TransactionType::WireOutbound, TransactionType::RtgsCreditTransfer]
+    }
+
+    fn signature_headers(&self) -> &'static [&'static str] {
+        &["X-Signature-Timestamp", "X-Signature-Ed25519"]
+    }
+
+    fn verify(&self, request: &WebhookRequest<'_>) -> Result<(), DomainError> {
+        let timestamp = request.required_header("X-Signature-Timestamp")?;
+        let signature = request.required_header("X-Signature-Ed25519")?;
+        signature::verify_ed25519(&self.public_key, request.body, timestamp, signature, request.received_at, self.tolerance)?;
+        Ok(())
+    }
+
+    fn parse(&self, body: &[u8]) -> Result<ParsedWebhook, DomainError> {
+        let payload = parse_json(body)?;
+        let event_id = required_str(&payload, "/id")?;
+        let event_type = required_str(&payload, "/type")?;
+        let status = match required_str(&payload, "/payment/status")? {
+            "ACCEPTED" => TransactionStatus::Submitted,
+            "SETTLED" => TransactionStatus::Settled,
+            "REJECTED" => TransactionStatus::Failed,
+            "RETURNED" => TransactionStatus::Returned,
+            other => return Err(DomainError::Validation(format!("Unknown partner bank payment status '{}'", other))),
+        };
+        let bank_ref = str_at(&payload, "/payment/bank_reference");
+        let reason = match (str_at(&payload, "/payment/reason_code"), str_at(&payload, "/payment/reason")) {
+            (Some(code), Some(text)) => Some(format!("{}: {}", code, text)),
+            (code, text) => code.or(text).map(str::to_string),
+        };
+        let event = PaymentStatusEvent {
+            payment: payment_ref(str_at(&payload, "/payment/end_to_end_id"), bank_ref.unwrap_or_default()),
+            status,
+            external_ref: bank_ref.map(str::to_string),
+            occurred_at: str_at(&payload, "/occurred_at")
+                .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+                .map(|at| at.with_timezone(&Utc)),
+            reason,
+        };
+        if event.payment == PaymentRef::ExternalRef(String::new()) {
+            return Err(DomainError::Validation("Partner bank event names no payment".to_string()));
+        }
+        Ok(ParsedWebhook { provider_event_id: event_id.to_string(), event_type: event_type.to_string(), events: vec![event] })
+    }
+}
+
+// --- Mock: unsigned, for local development only ---
+
+/// Accepts unsigned deliveries of `{"id", "type", "transaction_id", "status", "external_ref"?, "reason"?}`
+/// with `status` a TransactionStatus name. Only registered when explicitly enabled.
+pub struct MockWebhookProvider;
+
+impl WebhookProvider for MockWebhookProvider {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn transaction_types(&self) -> &'static [TransactionType] {
+        &[
+            TransactionType::CardAuthorization, TransactionType::CardCapture, TransactionType::CardRefund,
+            TransactionType::CardChargeback, TransactionType::CryptoBtcReceive, TransactionType::CryptoXmrReceive,
+            TransactionType::AchCredit, TransactionType::AchDebit, TransactionType::WireOutbound,
+            TransactionType::RtgsCreditTransfer,
+        ]
+    }
+
+    fn signature_headers(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn verify(&self, _request: &WebhookRequest<'_>) -> Result<(), DomainError> {
+        log::warn!("Processing mock webhook - skipping signature verification.");
+        Ok(())
+    }
+
+    fn parse(&self, body: &[u8]) -> Result<ParsedWebhook, DomainError> {
+        let payload = parse_json(body)?;
+        let event_id = required_str(&payload, "/id")?;
+        let event_type = str_at(&payload, "/type").unwrap_or("payment.updated");
+        let transaction_id = Uuid::parse_str(required_str(&payload, "/transaction_id")?)
+            .map_err(|_| DomainError::Validation("Mock webhook transaction_id is not a UUID".to_string()))?;
+        let status_name = required_str(&payload, "/status")?;
+        let status = TransactionStatus::parse(status_name)
+            .ok_or_else(|| DomainError::Validation(format!("Unknown status '{}'", status_name)))?;
+        Ok(ParsedWebhook {
+            provider_event_id: event_id.to_string(),
+            event_type: event_type.to_string(),
+            events: vec![PaymentStatusEvent {
+                payment: PaymentRef::TransactionId(transaction_id),
+                status,
+                external_ref: str_at(&payload, "/external_ref").map(str::to_string),
+                occurred_at: None,
+                reason: str_at(&payload, "/reason").map(str::to_string),
+            }],
+        })
+    }
+}
+
+/// Providers we accept webhooks from, by name. A provider without configured credentials is not registered.
+#[derive(Default)]
+pub struct WebhookRegistry {
+    providers: HashMap<String, Box<dyn WebhookProvider>>,
+}
+
+impl WebhookRegistry {
+    pub fn register(mut self, provider: impl WebhookProvider + 'static) -> Self {
+        log::info!("Webhook provider '{}' enabled", provider.name());
+        self.providers.insert(provider.name().to_string(), Box::new(provider));
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn WebhookProvider> {
+        self.providers.get(name).map(|provider| provider.as_ref())
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.providers.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+/// Tolerance used when configuration does not set one.
+pub fn default_tolerance() -> Duration {
+    Duration::seconds(DEFAULT_TOLERANCE_SECONDS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webhooks::signature::tests::hmac_hex;
+    use serde_json::json;
+
+    #[test]
+    fn test_stripe_delivery() {
+        let transaction_id = Uuid::new_v4();
+        let body = json!({
+            "id": "evt_1", "type": "payment_intent.payment_failed", "created": 1_700_000_000,
+            "data": {"object": {"id": "pi_1", "metadata": {"transaction_id": transaction_id.to_string()},
+                "last_payment_error": {"message": "Your card was declined."}}},
+        }).to_string();
+        let provider = StripeProvider::new("whsec", default_tolerance());
+        let signature = hmac_hex(b"whsec", &[b"1700000000", b".", body.as_bytes()]);
+        let request = WebhookRequest {
+            headers: vec![("stripe-signature".to_string(), format!("t=1700000000,v1={}", signature))],
+            body: body.as_bytes(),
+            received_at: Utc.timestamp_opt(1_700_000_060, 0).unwrap(),
+        };
+        assert!(provider.verify(&request).is_ok());
+
+        let parsed = provider.parse(body.as_bytes()).unwrap();
+        assert_eq!(parsed.provider_event_id, "evt_1");
+        let event = &parsed.events[0];
+        assert_eq!(event.payment, PaymentRef::TransactionId(transaction_id));
+        assert_eq!(event.status, TransactionStatus::Failed);
+        assert_eq!(event.external_ref.as_deref(), Some("pi_1"));
+        assert_eq!(event.reason.as_deref(), Some("Your card was declined."));
+
+        // Events we do not act on are still recorded, with nothing to apply
+        let ignored = provider.parse(br#"{"id":"evt_2","type":"customer.created","data":{"object":{}}}"#).unwrap();
+        assert!(ignored.events.is_empty());
+    }
+
+    #[test]
+    fn test_btcpay_redelivery_keeps_event_id() {
+        let provider = BtcPayProvider::new("secret");
+        let parsed = provider.parse(json!({
+            "deliveryId": "d2", "originalDeliveryId": "d1", "isRedelivery": true, "type": "InvoiceSettled",
+            "timestamp": 1_700_000_000, "invoiceId": "inv_9", "metadata": {"orderId": "order-17"},
+        }).to_string().as_bytes()).unwrap();
+        assert_eq!(parsed.provider_event_id, "d1");
+        assert_eq!(parsed.events[0].status, TransactionStatus::Completed);
+        assert_eq!(parsed.events[0].payment, PaymentRef::ExternalRef("inv_9".to_string())); // orderId is not ours
+    }
+
+    #[test]
+    fn test_partner_bank_payload() {
+        let key = hex::encode(ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]).verifying_key().to_bytes());
+        let provider = PartnerBankProvider::new("partner_bank", &key, default_tolerance()).unwrap();
+        let transaction_id = Uuid::new_v4();
+        let parsed = provider.parse(json!({
+            "id": "bank-evt-1", "type": "payment.status", "occurred_at": "2025-05-01T10:00:00Z",
+            "payment": {"end_to_end_id": transaction_id.to_string(), "bank_reference": "B123", "status": "RETURNED",
+                "reason_code": "AC04", "reason": "Closed account"},
+        }).to_string().as_bytes()).unwrap();
+        let event = &parsed.events[0];
+        assert_eq!(event.payment, PaymentRef::TransactionId(transaction_id));
+        assert_eq!(event.status, TransactionStatus::Returned);
+        assert_eq!(event.reason.as_deref(), Some("AC04: Closed account"));
+        assert!(provider.parse(br#"{"id":"x","type":"payment.status","payment":{"status":"SETTLED"}}"#).is_err());
+        assert!(PartnerBankProvider::new("partner_bank", "not-a-key", default_tolerance()).is_err());
+    }
+
+    #[test]
+    fn test_registry_and_missing_signature() {
+        let registry = WebhookRegistry::default().register(BtcPayProvider::new("secret")).register(MockWebhookProvider);
+        assert_eq!(registry.names(), vec!["btcpay", "mock"]);
+        let request = WebhookRequest { headers: Vec::new(), body: b"{}", received_at: Utc::now() };
+        assert!(registry.get("btcpay").unwrap().verify(&request).is_err());
+        assert!(registry.get("stripe").is_none());
+    }
+}
@@ -0,0 +1,153 @@
+// /home/inno/elights_jobes-research/backend/domain/src/webhooks/signature.rs
+// Signature schemes used by webhook providers. All comparisons of MACs/signatures are constant time
+// (`Mac::verify_slice`, Ed25519 verification); timestamps outside the tolerance are rejected so a captured
+// delivery cannot be replayed later even if it was never stored.
+use crate::error::DomainError;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Default allowed clock difference between the provider's signing time and ours.
+pub const DEFAULT_TOLERANCE_SECONDS: i64 = 300;
+
+fn rejected(reason: &str) -> DomainError {
+    DomainError::Authentication(format!("Webhook signature rejected: {}", reason))
+}
+
+fn mac(secret: &[u8], parts: &[&[u8]]) -> Result<HmacSha256, DomainError> {
+    let mut mac = HmacSha256::new_from_slice(secret)
+        .map_err(|e| DomainError::Configuration(format!("Unusable webhook secret: {}", e)))?;
+    for part in parts {
+        mac.update(part);
+    }
+    Ok(mac)
+}
+
+/// Unix seconds from a signature header, checked against `now` +/- `tolerance`.
+pub fn check_timestamp(timestamp: &str, now: DateTime<Utc>, tolerance: Duration) -> Result<DateTime<Utc>, DomainError> {
+    let seconds: i64 = timestamp.trim().parse().map_err(|_| rejected("malformed timestamp"))?;
+    let signed_at = Utc.timestamp_opt(seconds, 0).single().ok_or_else(|| rejected("malformed timestamp"))?;
+    if (now - signed_at).num_seconds().abs() > tolerance.num_seconds() {
+        return Err(rejected("timestamp outside tolerance"));
+    }
+    Ok(signed_at)
+}
+
+/// HMAC-SHA256 of the body, hex encoded, optionally prefixed (e.g. "sha256=").
+pub fn verify_hmac_hex(secret: &[u8], body: &[u8], signature: &str, prefix: &str) -> Result<(), DomainError> {
+    let signature = signature.trim().strip_prefix(prefix).ok_or_else(|| rejected("unexpected signature format"))?;
+    let expected = hex::decode(signature).map_err(|_| rejected("signature is not hex"))?;
+    mac(secret, &[body])?.verify_slice(&expected).map_err(|_| rejected("signature mismatch"))
+}
+
+/// Timestamped HMAC-SHA256 as sent in a header like `t=1700000000,v1=<hex>[,v1=<hex>]`: the MAC covers
+/// "<t>.<body>". Several v1 values are sent while the provider rolls its secret; one match is enough.
+pub fn verify_timestamped_hmac(
+    secret: &[u8],
+    body: &[u8],
+    header: &str,
+    now: DateTime<Utc>,
+    tolerance: Duration,
+) -> Result<DateTime<Utc>, DomainError> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = Some(value),
+            Some(("v1", value)) => signatures.push(value),
+            _ => {} // Other schemes (v0 test signatures) are ignored
+        }
+    }
+    let timestamp = timestamp.ok_or_else(|| rejected("missing timestamp"))?;
+    if signatures.is_empty() {
+        return Err(rejected("missing v1 signature"));
+    }
+    let signed_at = check_timestamp(timestamp, now, tolerance)?;
+
+    let signed = mac(secret, &[timestamp.as_bytes(), b".", body])?;
+    let matched = signatures.iter().any(|signature| {
+        hex::decode(signature).map(|expected| signed.clone().verify_slice(&expected).is_ok()).unwrap_or(false)
+    });
+    if matched { Ok(signed_at) } else { Err(rejected("signature mismatch")) }
+}
+
+/// Parses a hex-encoded Ed25519 public key (from configuration).
+pub fn parse_ed25519_public_key(hex_key: &str) -> Result<VerifyingKey, DomainError> {
+    let bytes: [u8; 32] = hex::decode(hex_key.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| DomainError::Configuration("Ed25519 public key must be 32 bytes of hex".to_string()))?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| DomainError::Configuration(format!("Invalid Ed25519 public key: {}", e)))
+}
+
+/// Ed25519 signature (hex) over "<timestamp>.<body>".
+pub fn verify_ed25519(
+    public_key: &VerifyingKey,
+    body: &[u8],
+    timestamp: &str,
+    signature: &str,
+    now: DateTime<Utc>,
+    tolerance: Duration,
+) -> Result<DateTime<Utc>, DomainError> {
+    let signed_at = check_timestamp(timestamp, now, tolerance)?;
+    let signature = hex::decode(signature.trim())
+        .ok()
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+        .ok_or_else(|| rejected("malformed signature"))?;
+    let message = [timestamp.trim().as_bytes(), b".", body].concat();
+    public_key.verify(&message, &signature).map_err(|_| rejected("signature mismatch"))?;
+    Ok(signed_at)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    pub(crate) fn hmac_hex(secret: &[u8], parts: &[&[u8]]) -> String {
+        hex::encode(mac(secret, parts).unwrap().finalize().into_bytes())
+    }
+
+    #[test]
+    fn test_timestamped_hmac() {
+        let body = br#"{"id":"evt_1"}"#;
+        let now = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let tolerance = Duration::seconds(DEFAULT_TOLERANCE_SECONDS);
+        let good = hmac_hex(b"whsec", &[b"1700000000", b".", body]);
+
+        let header = format!("t=1700000000,v1=deadbeef,v1={}", good);
+        assert_eq!(verify_timestamped_hmac(b"whsec", body, &header, now, tolerance).unwrap(), now);
+        // Tampered body, wrong secret, stale delivery
+        assert!(verify_timestamped_hmac(b"whsec", br#"{"id":"evt_2"}"#, &header, now, tolerance).is_err());
+        assert!(verify_timestamped_hmac(b"other", body, &header, now, tolerance).is_err());
+        assert!(verify_timestamped_hmac(b"whsec", body, &header, now + Duration::seconds(301), tolerance).is_err());
+        assert!(verify_timestamped_hmac(b"whsec", body, &format!("v1={}", good), now, tolerance).is_err());
+    }
+
+    #[test]
+    fn test_plain_hmac() {
+        let body = b"payload";
+        let signature = format!("sha256={}", hmac_hex(b"secret", &[body]));
+        assert!(verify_hmac_hex(b"secret", body, &signature, "sha256=").is_ok());
+        assert!(verify_hmac_hex(b"secret", b"payload!", &signature, "sha256=").is_err());
+        assert!(verify_hmac_hex(b"secret", body, &signature[7..], "sha256=").is_err()); // Prefix required
+    }
+
+    #[test]
+    fn test_ed25519() {
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let public_key = parse_ed25519_public_key(&hex::encode(signing_key.verifying_key().to_bytes())).unwrap();
+        let now = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let tolerance = Duration::seconds(DEFAULT_TOLERANCE_SECONDS);
+        let body = br#"{"id":"bank-1"}"#;
+        let signature = hex::encode(signing_key.sign(b"1700000000.{\"id\":\"bank-1\"}").to_bytes());
+
+        assert!(verify_ed25519(&public_key, body, "1700000000", &signature, now, tolerance).is_ok());
+        assert!(verify_ed25519(&public_key, b"{}", "1700000000", &signature, now, tolerance).is_err());
+        assert!(verify_ed25519(&public_key, body, "1700000001", &signature, now, tolerance).is_err());
+        assert!(parse_ed25519_public_key("abcd").is_err());
+    }
+}
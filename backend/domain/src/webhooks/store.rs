@@ -0,0 +1,186 @@
+// /home/inno/elights_jobes-research/backend/domain/src/webhooks/store.rs
+// Verified deliveries are stored with their raw payload before anything is applied. The unique
+// (provider, provider_event_id) key doubles as the seen-event store: a replayed or redelivered event
+// finds its earlier row and is only acknowledged, unless that earlier attempt did not complete.
+// Before an event is applied it is claimed (PROCESSING) with a conditional update, so concurrent
+// deliveries of the same event cannot both apply it.
+use super::providers::{ParsedWebhook, WebhookProvider, WebhookRequest};
+use crate::error::DomainError;
+use crate::models::{AuditOutcome, AuditTargetType, NewWebhookEvent, WebhookEvent, WebhookEventStatus};
+use crate::security::audit;
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use serde_json::{json, Map, Value as JsonValue};
+use uuid::Uuid;
+
+/// How long a PROCESSING claim stands before another delivery may take the event over (the request
+/// holding it is assumed to have crashed).
+pub const CLAIM_LEASE_SECONDS: i64 = 300;
+
+/// A verified, stored delivery.
+#[derive(Debug, Clone)]
+pub struct Delivery {
+    pub event: WebhookEvent,
+    pub parsed: ParsedWebhook,
+    pub replay: bool, // The provider event was seen before
+    pub claimed: bool, // This delivery holds the PROCESSING claim and must apply the event
+}
+
+impl Delivery {
+    /// False when the event has been applied, or another delivery is applying it; this one is then
+    /// only acknowledged.
+    pub fn needs_processing(&self) -> bool {
+        self.claimed
+    }
+
+    /// True while another delivery holds the claim and has not finished.
+    pub fn in_progress(&self) -> bool {
+        !self.claimed && self.event.status == WebhookEventStatus::Processing.as_str()
+    }
+}
+
+fn signature_headers(provider: &dyn WebhookProvider, request: &WebhookRequest<'_>) -> JsonValue {
+    let headers: Map<String, JsonValue> = provider.signature_headers().iter()
+        .filter_map(|name| request.header(name).map(|value| (name.to_string(), json!(value))))
+        .collect();
+    JsonValue::Object(headers)
+}
+
+/// Verifies, parses and stores a delivery. Rejected signatures are audited and nothing is stored.
+pub fn accept_delivery(
+    conn: &mut PgConnection,
+    provider: &dyn WebhookProvider,
+    request: &WebhookRequest<'_>,
+) -> Result<Delivery, DomainError> {
+    use crate::schema::webhook_events::dsl as we;
+
+    if let Err(e) = provider.verify(request) {
+        log::warn!("Rejected webhook from '{}': {}", provider.name(), e);
+        audit::log_db_audit_event(
+            conn, None, provider.name(), "WEBHOOK_SIGNATURE_REJECTED", Some(AuditTargetType::System), None,
+            AuditOutcome::Failure, Some(json!({"provider": provider.name(), "body_bytes": request.body.len()})), Some(&e.to_string()),
+        )?;
+        return Err(e);
+    }
+    let raw_payload = std::str::from_utf8(request.body)
+        .map_err(|_| DomainError::Validation("Webhook payload is not UTF-8".to_string()))?;
+    let parsed = provider.parse(request.body)?;
+
+    let inserted: Option<WebhookEvent> = diesel::insert_into(we::webhook_events)
+        .values(&NewWebhookEvent {
+            provider: provider.name(),
+            provider_event_id: &parsed.provider_event_id,
+            event_type: &parsed.event_type,
+            raw_payload,
+            signature_headers: signature_headers(provider, request),
+            status: WebhookEventStatus::Received.as_str(),
+        })
+        .on_conflict((we::provider, we::provider_event_id))
+        .do_nothing()
+        .get_result(conn)
+        .optional()?;
+
+    let (event, replay) = match inserted {
+        Some(event) => (event, false),
+        None => {
+            let event: WebhookEvent = we::webhook_events
+                .filter(we::provider.eq(provider.name()))
+                .filter(we::provider_event_id.eq(&parsed.provider_event_id))
+                .first(conn)?;
+            if event.raw_payload != raw_payload {
+                // Same event id, different body: keep what was stored first
+                log::warn!("Webhook {} from '{}' redelivered with a different payload", parsed.provider_event_id, provider.name());
+            }
+            log::info!("Webhook {} from '{}' seen before ({})", parsed.provider_event_id, provider.name(), event.status);
+            (event, true)
+        }
+    };
+
+    // Only one delivery applies the event: the claim fails while it is processed or being processed
+    let claimable = [WebhookEventStatus::Received, WebhookEventStatus::Failed];
+    match claim(conn, event.event_id, &claimable, Utc::now())? {
+        Some(event) => Ok(Delivery { event, parsed, replay, claimed: true }),
+        None => {
+            let event = get_event(conn, event.event_id)?;
+            Ok(Delivery { event, parsed, replay, claimed: false })
+        }
+    }
+}
+
+/// Moves the event to PROCESSING if it is in one of `from`, or if an earlier claim has gone stale.
+/// Returns `None` when the event was not claimable.
+fn claim(
+    conn: &mut PgConnection,
+    event_id: Uuid,
+    from: &[WebhookEventStatus],
+    now: DateTime<Utc>,
+) -> Result<Option<WebhookEvent>, DomainError> {
+    use crate::schema::webhook_events::dsl as we;
+    let from: Vec<&str> = from.iter().map(|status| status.as_str()).collect();
+    let stale = we::status.eq(WebhookEventStatus::Processing.as_str())
+        .and(we::updated_at.lt(now - Duration::seconds(CLAIM_LEASE_SECONDS)));
+    Ok(diesel::update(we::webhook_events.find(event_id).filter(we::status.eq_any(from).or(stale)))
+        .set(we::status.eq(WebhookEventStatus::Processing.as_str()))
+        .get_result(conn)
+        .optional()?)
+}
+
+/// Claims a stored event for a reprocess request; processed events may be applied again. `None` while
+/// a delivery is applying it.
+pub fn claim_for_reprocess(conn: &mut PgConnection, event_id: Uuid) -> Result<Option<WebhookEvent>, DomainError> {
+    let claimable = [WebhookEventStatus::Received, WebhookEventStatus::Failed, WebhookEventStatus::Processed];
+    claim(conn, event_id, &claimable, Utc::now())
+}
+
+/// Records a successful processing attempt.
+pub fn mark_processed(conn: &mut PgConnection, event_id: Uuid, results: JsonValue) -> Result<WebhookEvent, DomainError> {
+    use crate::schema::webhook_events::dsl as we;
+    Ok(diesel::update(we::webhook_events.find(event_id))
+        .set((
+            we::status.eq(WebhookEventStatus::Processed.as_str()),
+            we::attempts.eq(we::attempts + 1),
+            we::last_error.eq(None::<String>),
+            we::results.eq(Some(results)),
+            we::processed_at.eq(Some(Utc::now())),
+        ))
+        .get_result(conn)?)
+}
+
+/// Records a failed processing attempt; the event is retried on redelivery or on request.
+pub fn mark_failed(conn: &mut PgConnection, event_id: Uuid, error: &str) -> Result<WebhookEvent, DomainError> {
+    use crate::schema::webhook_events::dsl as we;
+    Ok(diesel::update(we::webhook_events.find(event_id))
+        .set((
+            we::status.eq(WebhookEventStatus::Failed.as_str()),
+            we::attempts.eq(we::attempts + 1),
+            we::last_error.eq(Some(error)),
+        ))
+        .get_result(conn)?)
+}
+
+pub fn get_event(conn: &mut PgConnection, event_id: Uuid) -> Result<WebhookEvent, DomainError> {
+    use crate::schema::webhook_events::dsl as we;
+    we::webhook_events
+        .find(event_id)
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| DomainError::NotFound(format!("Webhook event {} not found", event_id)))
+}
+
+/// Stored events, newest first.
+pub fn list_events(
+    conn: &mut PgConnection,
+    provider: Option<&str>,
+    status: Option<WebhookEventStatus>,
+    limit: i64,
+) -> Result<Vec<WebhookEvent>, DomainError> {
+    use crate::schema::webhook_events::dsl as we;
+    let mut query = we::webhook_events.into_boxed();
+    if let Some(provider) = provider {
+        query = query.filter(we::provider.eq(provider));
+    }
+    if let Some(status) = status {
+        query = query.filter(we::status.eq(status.as_str()));
+    }
+    Ok(query.order(we::received_at.desc()).limit(limit).load(conn)?)
+}
@@ -61,8 +61,8 @@ impl CurrencyConverter for SimpleRateConverter {
         // 1. Get the current rate
         let quote = self.get_quote(&request.from_currency, &request.to_currency).await?;
 
-        // 2. Perform calculation
-        // TODO: Add spread/fees based on configuration if this converter handles it
+        // 2. Perform calculation at the fetched (mid) rate
+        // The customer spread is applied by the domain when it books the quote (`payments::conversion::apply_spread`)
         let converted_amount = request.amount * quote.rate;
 
         // Note: This implementation doesn't actually *execute* a trade on an exchange.
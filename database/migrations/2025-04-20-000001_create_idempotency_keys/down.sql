@@ -0,0 +1,3 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000001_create_idempotency_keys/down.sql
+DROP TRIGGER IF EXISTS set_timestamp_idempotency_keys ON core_schema.idempotency_keys;
+DROP TABLE IF EXISTS core_schema.idempotency_keys;
@@ -0,0 +1,27 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000001_create_idempotency_keys/up.sql
+
+-- Stores client-supplied Idempotency-Key values so retried API calls replay the original response
+CREATE TABLE core_schema.idempotency_keys (
+    key_id BIGSERIAL PRIMARY KEY,
+    idempotency_key VARCHAR(255) NOT NULL, -- Value of the Idempotency-Key header
+    user_id UUID REFERENCES core_schema.users(user_id) ON DELETE CASCADE, -- Nullable for unauthenticated endpoints
+    scope VARCHAR(100) NOT NULL, -- Endpoint the key was used on, e.g. 'PAYMENT_INITIATE'
+    request_fingerprint VARCHAR(64) NOT NULL, -- SHA-256 (hex) of the canonical request body
+    status VARCHAR(20) NOT NULL DEFAULT 'IN_PROGRESS', -- IN_PROGRESS, COMPLETED
+    response_status_code INTEGER, -- HTTP status of the stored response
+    response_body JSONB, -- Stored response body returned on replay
+    transaction_id UUID REFERENCES core_schema.transactions(transaction_id), -- Transaction created by the request, if any
+    locked_until TIMESTAMPTZ, -- Lease of the request holding an IN_PROGRESS key; a retry may take over once it lapses
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL DEFAULT (NOW() + INTERVAL '24 hours')
+);
+-- A key is unique per user and endpoint; COALESCE lets anonymous callers share the NULL bucket
+CREATE UNIQUE INDEX idx_idempotency_keys_unique
+    ON core_schema.idempotency_keys(scope, COALESCE(user_id, '00000000-0000-0000-0000-000000000000'::uuid), idempotency_key);
+CREATE INDEX idx_idempotency_keys_expires_at ON core_schema.idempotency_keys(expires_at);
+
+CREATE TRIGGER set_timestamp_idempotency_keys
+BEFORE UPDATE ON core_schema.idempotency_keys
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
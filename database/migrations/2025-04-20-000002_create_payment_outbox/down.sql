@@ -0,0 +1,4 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000002_create_payment_outbox/down.sql
+DROP TABLE IF EXISTS core_schema.payment_saga_steps;
+DROP TRIGGER IF EXISTS set_timestamp_payment_outbox ON core_schema.payment_outbox;
+DROP TABLE IF EXISTS core_schema.payment_outbox;
@@ -0,0 +1,37 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000002_create_payment_outbox/up.sql
+
+-- Transactional outbox for outbound payments. A row is written in the same DB transaction as the
+-- wallet debit; the outbox worker dispatches it to ACH/Wire/crypto and compensates on failure.
+CREATE TABLE core_schema.payment_outbox (
+    outbox_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    transaction_id UUID NOT NULL UNIQUE REFERENCES core_schema.transactions(transaction_id), -- One saga per transaction
+    operation VARCHAR(30) NOT NULL, -- ACH_CREDIT, WIRE_OUTBOUND, CRYPTO_SEND
+    payload JSONB NOT NULL, -- Everything the dispatcher needs (destination details, addresses...)
+    status VARCHAR(20) NOT NULL DEFAULT 'PENDING', -- PENDING, DISPATCHING, SUBMITTED, COMPENSATED, REQUIRES_REVIEW
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL DEFAULT 5,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    locked_until TIMESTAMPTZ, -- Lease held by the worker while dispatching; expired lease = crashed worker
+    dispatch_key VARCHAR(255) NOT NULL, -- Idempotency key presented to the rail on every attempt
+    external_ref VARCHAR(255), -- Rail reference once submitted
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_payment_outbox_due ON core_schema.payment_outbox(status, next_attempt_at);
+
+-- Append-only log of every saga step (debit, dispatch attempts, compensation)
+CREATE TABLE core_schema.payment_saga_steps (
+    step_id BIGSERIAL PRIMARY KEY,
+    outbox_id UUID NOT NULL REFERENCES core_schema.payment_outbox(outbox_id) ON DELETE CASCADE,
+    step VARCHAR(30) NOT NULL, -- DEBIT, DISPATCH, RETRY_SCHEDULED, SUBMITTED, COMPENSATE, REVIEW
+    outcome VARCHAR(20) NOT NULL, -- SUCCESS, FAILURE
+    details JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_payment_saga_steps_outbox_id ON core_schema.payment_saga_steps(outbox_id);
+
+CREATE TRIGGER set_timestamp_payment_outbox
+BEFORE UPDATE ON core_schema.payment_outbox
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
@@ -0,0 +1,2 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000003_create_transaction_state_transitions/down.sql
+DROP TABLE IF EXISTS core_schema.transaction_state_transitions;
@@ -0,0 +1,17 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000003_create_transaction_state_transitions/up.sql
+
+-- History of validated status transitions and the financial effect each one applied
+CREATE TABLE core_schema.transaction_state_transitions (
+    transition_id BIGSERIAL PRIMARY KEY,
+    transaction_id UUID NOT NULL REFERENCES core_schema.transactions(transaction_id) ON DELETE CASCADE,
+    from_status VARCHAR(20), -- NULL for the effect recorded when the transaction was created
+    to_status VARCHAR(20) NOT NULL,
+    financial_effect VARCHAR(10) NOT NULL, -- NONE, HOLD, POST, RELEASE, REVERSE
+    actor VARCHAR(100) NOT NULL, -- Who drove the transition (user id, SYSTEM, webhook provider...)
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_tx_state_transitions_tx_id ON core_schema.transaction_state_transitions(transaction_id);
+-- Each financial effect can be applied to a transaction at most once
+CREATE UNIQUE INDEX idx_tx_state_transitions_effect_once
+    ON core_schema.transaction_state_transitions(transaction_id, financial_effect)
+    WHERE financial_effect <> 'NONE';
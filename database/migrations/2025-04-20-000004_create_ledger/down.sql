@@ -0,0 +1,9 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000004_create_ledger/down.sql
+DROP TRIGGER IF EXISTS journal_lines_append_only ON core_schema.journal_lines;
+DROP TRIGGER IF EXISTS journal_entries_append_only ON core_schema.journal_entries;
+DROP TRIGGER IF EXISTS journal_lines_balanced ON core_schema.journal_lines;
+DROP FUNCTION IF EXISTS core_schema.reject_journal_mutation();
+DROP FUNCTION IF EXISTS core_schema.check_journal_entry_balanced();
+DROP TABLE IF EXISTS core_schema.journal_lines;
+DROP TABLE IF EXISTS core_schema.journal_entries;
+DROP TABLE IF EXISTS core_schema.ledger_accounts;
@@ -0,0 +1,104 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000004_create_ledger/up.sql
+
+-- Double-entry ledger. Every balance change is a balanced journal entry; wallets.balance is a cache
+-- of the CUSTOMER_WALLET account balance maintained by the domain ledger module.
+CREATE TABLE core_schema.ledger_accounts (
+    account_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    account_code VARCHAR(100) NOT NULL UNIQUE, -- e.g. 'WALLET:<wallet_id>', 'NOSTRO:USD', 'FEE_INCOME:EUR'
+    category VARCHAR(30) NOT NULL, -- CUSTOMER_WALLET, SUSPENSE, NOSTRO, FEE_INCOME, FX_POSITION, OPENING_BALANCE
+    normal_balance VARCHAR(6) NOT NULL CHECK (normal_balance IN ('DEBIT', 'CREDIT')),
+    currency_code VARCHAR(10) NOT NULL,
+    wallet_id UUID UNIQUE REFERENCES core_schema.wallets(wallet_id), -- Set for CUSTOMER_WALLET accounts only
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE core_schema.journal_entries (
+    entry_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    transaction_id UUID REFERENCES core_schema.transactions(transaction_id), -- Nullable for adjustments/opening balances
+    entry_type VARCHAR(30) NOT NULL, -- HOLD, POST, RELEASE, REVERSE, FEE, FX, OPENING_BALANCE, ADJUSTMENT
+    reference VARCHAR(255) NOT NULL UNIQUE, -- Deterministic key, e.g. '<transaction_id>:POST'; makes posting idempotent
+    description TEXT,
+    posted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_journal_entries_transaction_id ON core_schema.journal_entries(transaction_id);
+CREATE INDEX idx_journal_entries_posted_at ON core_schema.journal_entries(posted_at);
+
+CREATE TABLE core_schema.journal_lines (
+    line_id BIGSERIAL PRIMARY KEY,
+    entry_id UUID NOT NULL REFERENCES core_schema.journal_entries(entry_id),
+    account_id UUID NOT NULL REFERENCES core_schema.ledger_accounts(account_id),
+    direction VARCHAR(6) NOT NULL CHECK (direction IN ('DEBIT', 'CREDIT')),
+    amount NUMERIC(38, 18) NOT NULL CHECK (amount > 0),
+    currency_code VARCHAR(10) NOT NULL,
+    posted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_journal_lines_entry_id ON core_schema.journal_lines(entry_id);
+CREATE INDEX idx_journal_lines_account_id ON core_schema.journal_lines(account_id);
+CREATE INDEX idx_journal_lines_currency_posted ON core_schema.journal_lines(currency_code, posted_at);
+
+-- Safety net behind the domain check: an entry must balance per currency when its DB transaction commits
+CREATE OR REPLACE FUNCTION core_schema.check_journal_entry_balanced()
+RETURNS TRIGGER AS $$
+DECLARE
+    unbalanced_currency VARCHAR(10);
+BEGIN
+    SELECT currency_code INTO unbalanced_currency
+    FROM core_schema.journal_lines
+    WHERE entry_id = NEW.entry_id
+    GROUP BY currency_code
+    HAVING SUM(CASE WHEN direction = 'DEBIT' THEN amount ELSE -amount END) <> 0
+    LIMIT 1;
+    IF unbalanced_currency IS NOT NULL THEN
+        RAISE EXCEPTION 'Journal entry % does not balance in %', NEW.entry_id, unbalanced_currency;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE CONSTRAINT TRIGGER journal_lines_balanced
+AFTER INSERT ON core_schema.journal_lines
+DEFERRABLE INITIALLY DEFERRED
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.check_journal_entry_balanced();
+
+-- The journal is append-only: corrections are new (reversing) entries
+CREATE OR REPLACE FUNCTION core_schema.reject_journal_mutation()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'Journal is append-only (% on %)', TG_OP, TG_TABLE_NAME;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER journal_entries_append_only
+BEFORE UPDATE OR DELETE ON core_schema.journal_entries
+FOR EACH ROW EXECUTE FUNCTION core_schema.reject_journal_mutation();
+
+CREATE TRIGGER journal_lines_append_only
+BEFORE UPDATE OR DELETE ON core_schema.journal_lines
+FOR EACH ROW EXECUTE FUNCTION core_schema.reject_journal_mutation();
+
+-- --- Backfill: one ledger account per existing wallet, opening balances against OPENING_BALANCE ---
+INSERT INTO core_schema.ledger_accounts (account_code, category, normal_balance, currency_code, wallet_id)
+SELECT 'WALLET:' || wallet_id, 'CUSTOMER_WALLET', 'CREDIT', currency_code, wallet_id
+FROM core_schema.wallets;
+
+INSERT INTO core_schema.ledger_accounts (account_code, category, normal_balance, currency_code)
+SELECT DISTINCT 'OPENING_BALANCE:' || currency_code, 'OPENING_BALANCE', 'CREDIT', currency_code
+FROM core_schema.wallets
+WHERE balance > 0;
+
+INSERT INTO core_schema.journal_entries (entry_type, reference, description)
+SELECT 'OPENING_BALANCE', 'OPENING:' || wallet_id, 'Opening balance migrated from wallets.balance'
+FROM core_schema.wallets
+WHERE balance > 0;
+
+INSERT INTO core_schema.journal_lines (entry_id, account_id, direction, amount, currency_code)
+SELECT je.entry_id, opening.account_id, 'DEBIT', w.balance, w.currency_code
+FROM core_schema.wallets w
+JOIN core_schema.journal_entries je ON je.reference = 'OPENING:' || w.wallet_id
+JOIN core_schema.ledger_accounts opening ON opening.account_code = 'OPENING_BALANCE:' || w.currency_code
+UNION ALL
+SELECT je.entry_id, wa.account_id, 'CREDIT', w.balance, w.currency_code
+FROM core_schema.wallets w
+JOIN core_schema.journal_entries je ON je.reference = 'OPENING:' || w.wallet_id
+JOIN core_schema.ledger_accounts wa ON wa.wallet_id = w.wallet_id;
@@ -0,0 +1,3 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000005_create_wallet_holds/down.sql
+DROP TRIGGER IF EXISTS set_timestamp_wallet_holds ON core_schema.wallet_holds;
+DROP TABLE IF EXISTS core_schema.wallet_holds;
@@ -0,0 +1,29 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000005_create_wallet_holds/up.sql
+
+-- Reservations against a wallet's ledger balance (card authorizations, pending withdrawals, uncleared checks).
+-- available balance = ledger balance (wallets.balance) - SUM(amount) of ACTIVE, unexpired holds.
+CREATE TABLE core_schema.wallet_holds (
+    hold_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    wallet_id UUID NOT NULL REFERENCES core_schema.wallets(wallet_id),
+    transaction_id UUID REFERENCES core_schema.transactions(transaction_id), -- Nullable for manual holds
+    hold_type VARCHAR(30) NOT NULL, -- CARD_AUTHORIZATION, WITHDRAWAL, CHECK_CLEARING, MANUAL
+    amount NUMERIC(38, 18) NOT NULL CHECK (amount > 0),
+    currency_code VARCHAR(10) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'ACTIVE', -- ACTIVE, RELEASED, CONVERTED, EXPIRED
+    reason TEXT,
+    expires_at TIMESTAMPTZ, -- NULL = until released/converted
+    resolved_at TIMESTAMPTZ, -- When it stopped being ACTIVE
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_wallet_holds_active_wallet ON core_schema.wallet_holds(wallet_id) WHERE status = 'ACTIVE';
+CREATE INDEX idx_wallet_holds_transaction_id ON core_schema.wallet_holds(transaction_id);
+-- A transaction holds funds of a given kind at most once at a time
+CREATE UNIQUE INDEX uq_wallet_holds_active_transaction
+    ON core_schema.wallet_holds(transaction_id, hold_type)
+    WHERE status = 'ACTIVE' AND transaction_id IS NOT NULL;
+
+CREATE TRIGGER set_timestamp_wallet_holds
+BEFORE UPDATE ON core_schema.wallet_holds
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
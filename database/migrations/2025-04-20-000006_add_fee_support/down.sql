@@ -0,0 +1,3 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000006_add_fee_support/down.sql
+DROP INDEX IF EXISTS core_schema.idx_transactions_fee_parent;
+ALTER TABLE core_schema.users DROP COLUMN IF EXISTS fee_tier;
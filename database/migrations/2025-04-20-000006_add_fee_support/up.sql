@@ -0,0 +1,9 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000006_add_fee_support/up.sql
+
+-- Customer pricing tier, matched against the customer_tier of fee schedule rules.
+ALTER TABLE core_schema.users
+    ADD COLUMN fee_tier VARCHAR(20) NOT NULL DEFAULT 'STANDARD'; -- STANDARD, PREMIUM, CORPORATE...
+
+-- Fee transactions point at the payment they were charged for via metadata->>'parent_transaction_id'.
+CREATE INDEX idx_transactions_fee_parent ON core_schema.transactions ((metadata->>'parent_transaction_id'))
+    WHERE transaction_type = 'Fee';
@@ -0,0 +1,3 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000007_create_fx_quotes/down.sql
+DROP TRIGGER IF EXISTS set_timestamp_fx_quotes ON core_schema.fx_quotes;
+DROP TABLE IF EXISTS core_schema.fx_quotes;
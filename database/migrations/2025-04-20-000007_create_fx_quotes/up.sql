@@ -0,0 +1,35 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000007_create_fx_quotes/up.sql
+
+-- Locked FX quotes. A quote fixes the rate (mid rate +/- spread) for a short time; executing it books
+-- two linked Conversion transactions: the SELL leg debits the source wallet, the BUY leg credits the target.
+CREATE TABLE core_schema.fx_quotes (
+    quote_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES core_schema.users(user_id),
+    source_wallet_id UUID NOT NULL REFERENCES core_schema.wallets(wallet_id),
+    target_wallet_id UUID NOT NULL REFERENCES core_schema.wallets(wallet_id),
+    from_currency VARCHAR(10) NOT NULL,
+    to_currency VARCHAR(10) NOT NULL,
+    source_amount NUMERIC(38, 18) NOT NULL CHECK (source_amount > 0),
+    mid_rate NUMERIC(38, 18) NOT NULL CHECK (mid_rate > 0), -- As received from the rate source
+    spread_bps INTEGER NOT NULL CHECK (spread_bps >= 0), -- Our margin, in basis points of the mid rate
+    applied_rate NUMERIC(38, 18) NOT NULL CHECK (applied_rate > 0), -- mid_rate * (1 - spread)
+    target_amount NUMERIC(38, 18) NOT NULL CHECK (target_amount > 0),
+    fee_amount NUMERIC(38, 18) NOT NULL DEFAULT 0, -- Conversion fee, in from_currency, on top of source_amount
+    fee_rule_id VARCHAR(100), -- Fee schedule rule that priced it
+    rate_source VARCHAR(50),
+    status VARCHAR(20) NOT NULL DEFAULT 'OPEN', -- OPEN, EXECUTED, EXPIRED
+    expires_at TIMESTAMPTZ NOT NULL,
+    executed_at TIMESTAMPTZ,
+    debit_transaction_id UUID REFERENCES core_schema.transactions(transaction_id), -- SELL leg
+    credit_transaction_id UUID REFERENCES core_schema.transactions(transaction_id), -- BUY leg
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (source_wallet_id <> target_wallet_id)
+);
+CREATE INDEX idx_fx_quotes_user_id ON core_schema.fx_quotes(user_id, created_at DESC);
+CREATE INDEX idx_fx_quotes_open_expiry ON core_schema.fx_quotes(expires_at) WHERE status = 'OPEN';
+
+CREATE TRIGGER set_timestamp_fx_quotes
+BEFORE UPDATE ON core_schema.fx_quotes
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
@@ -0,0 +1,7 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000008_create_payment_schedules/down.sql
+DROP INDEX IF EXISTS core_schema.idx_transactions_scheduled_run_id;
+ALTER TABLE core_schema.transactions DROP COLUMN IF EXISTS scheduled_run_id;
+DROP TRIGGER IF EXISTS set_timestamp_payment_schedule_runs ON core_schema.payment_schedule_runs;
+DROP TABLE IF EXISTS core_schema.payment_schedule_runs;
+DROP TRIGGER IF EXISTS set_timestamp_payment_schedules ON core_schema.payment_schedules;
+DROP TABLE IF EXISTS core_schema.payment_schedules;
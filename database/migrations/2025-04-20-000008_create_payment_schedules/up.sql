@@ -0,0 +1,62 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000008_create_payment_schedules/up.sql
+
+-- Standing orders: a payment template plus a calendar rule. The scheduler submits each occurrence
+-- through the payment processor once its (business-day adjusted) run date is reached.
+CREATE TABLE core_schema.payment_schedules (
+    schedule_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES core_schema.users(user_id),
+    source_wallet_id UUID NOT NULL REFERENCES core_schema.wallets(wallet_id),
+    payment_type TEXT NOT NULL, -- AchCredit, WireOutbound
+    amount NUMERIC(38, 18) NOT NULL CHECK (amount > 0),
+    currency_code VARCHAR(10) NOT NULL,
+    charge_bearer VARCHAR(3) NOT NULL DEFAULT 'SHA',
+    payment_details JSONB NOT NULL DEFAULT '{}'::jsonb, -- ACH / wire details for the rail
+    description TEXT,
+    recurrence JSONB NOT NULL, -- {"frequency": "MONTHLY", "interval": 1, "day_of_month": 31, ...}
+    start_date DATE NOT NULL,
+    end_date DATE, -- Last nominal date (inclusive), NULL = open-ended
+    max_occurrences INTEGER CHECK (max_occurrences > 0), -- NULL = unlimited
+    business_day_convention VARCHAR(20) NOT NULL DEFAULT 'FOLLOWING', -- NONE, FOLLOWING, MODIFIED_FOLLOWING, PRECEDING
+    missed_run_policy VARCHAR(20) NOT NULL DEFAULT 'RUN_ONCE', -- RUN_ONCE, RUN_ALL, SKIP
+    status VARCHAR(20) NOT NULL DEFAULT 'ACTIVE', -- ACTIVE, PAUSED, CANCELLED, COMPLETED
+    next_occurrence DATE, -- Next nominal date per the rule, NULL once finished
+    next_run_date DATE, -- next_occurrence moved to a business day
+    occurrences_count INTEGER NOT NULL DEFAULT 0, -- Occurrences consumed (submitted, failed or skipped)
+    last_run_at TIMESTAMPTZ,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (end_date IS NULL OR end_date >= start_date)
+);
+CREATE INDEX idx_payment_schedules_user_id ON core_schema.payment_schedules(user_id);
+CREATE INDEX idx_payment_schedules_due ON core_schema.payment_schedules(next_run_date) WHERE status = 'ACTIVE';
+
+CREATE TRIGGER set_timestamp_payment_schedules
+BEFORE UPDATE ON core_schema.payment_schedules
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
+
+-- One row per occurrence; the unique key makes each occurrence submit at most once.
+CREATE TABLE core_schema.payment_schedule_runs (
+    run_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    schedule_id UUID NOT NULL REFERENCES core_schema.payment_schedules(schedule_id),
+    occurrence_date DATE NOT NULL, -- Nominal date
+    run_date DATE NOT NULL, -- Business day it was due on
+    status VARCHAR(20) NOT NULL, -- PENDING, SUBMITTED, FAILED, SKIPPED
+    transaction_id UUID REFERENCES core_schema.transactions(transaction_id),
+    error_message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (schedule_id, occurrence_date)
+);
+CREATE INDEX idx_payment_schedule_runs_pending ON core_schema.payment_schedule_runs(schedule_id) WHERE status = 'PENDING';
+
+CREATE TRIGGER set_timestamp_payment_schedule_runs
+BEFORE UPDATE ON core_schema.payment_schedule_runs
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
+
+-- The payment made for a run; unique so a run can never produce a second payment.
+ALTER TABLE core_schema.transactions ADD COLUMN scheduled_run_id UUID;
+CREATE UNIQUE INDEX idx_transactions_scheduled_run_id ON core_schema.transactions(scheduled_run_id)
+    WHERE scheduled_run_id IS NOT NULL;
@@ -0,0 +1,5 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000009_create_payout_batches/down.sql
+DROP TRIGGER IF EXISTS set_timestamp_payout_batch_items ON core_schema.payout_batch_items;
+DROP TABLE IF EXISTS core_schema.payout_batch_items;
+DROP TRIGGER IF EXISTS set_timestamp_payout_batches ON core_schema.payout_batches;
+DROP TABLE IF EXISTS core_schema.payout_batches;
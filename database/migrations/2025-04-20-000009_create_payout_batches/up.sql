@@ -0,0 +1,60 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000009_create_payout_batches/up.sql
+
+-- Bulk payouts uploaded as one CSV/JSON file. Accepted rows become individual outbound transactions funded
+-- together; ACH rows leave as a single NACHA file, wires through the payment outbox one by one.
+CREATE TABLE core_schema.payout_batches (
+    batch_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES core_schema.users(user_id),
+    source_wallet_id UUID NOT NULL REFERENCES core_schema.wallets(wallet_id),
+    currency_code VARCHAR(10) NOT NULL,
+    file_format VARCHAR(10) NOT NULL, -- CSV, JSON
+    file_name TEXT,
+    status VARCHAR(30) NOT NULL, -- PROCESSING, COMPLETED, COMPLETED_WITH_ERRORS, REJECTED
+    total_rows INTEGER NOT NULL,
+    accepted_count INTEGER NOT NULL DEFAULT 0,
+    rejected_count INTEGER NOT NULL DEFAULT 0,
+    total_amount NUMERIC(38, 18) NOT NULL DEFAULT 0, -- Sum of accepted principals
+    total_fees NUMERIC(38, 18) NOT NULL DEFAULT 0,
+    settled_count INTEGER NOT NULL DEFAULT 0,
+    failed_count INTEGER NOT NULL DEFAULT 0,
+    returned_count INTEGER NOT NULL DEFAULT 0,
+    ach_status VARCHAR(20) NOT NULL DEFAULT 'NONE', -- NONE, PENDING, DISPATCHING, SUBMITTED, FAILED, REQUIRES_REVIEW
+    ach_file_ref TEXT, -- Reference of the submitted NACHA file
+    ach_attempts INTEGER NOT NULL DEFAULT 0,
+    ach_next_attempt_at TIMESTAMPTZ,
+    ach_locked_until TIMESTAMPTZ, -- Dispatch lease
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_payout_batches_user_id ON core_schema.payout_batches(user_id);
+CREATE INDEX idx_payout_batches_ach_due ON core_schema.payout_batches(ach_next_attempt_at) WHERE ach_status IN ('PENDING', 'DISPATCHING');
+
+CREATE TRIGGER set_timestamp_payout_batches
+BEFORE UPDATE ON core_schema.payout_batches
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
+
+-- One row per line of the uploaded file, accepted or not (the per-row error report).
+CREATE TABLE core_schema.payout_batch_items (
+    item_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    batch_id UUID NOT NULL REFERENCES core_schema.payout_batches(batch_id),
+    row_number INTEGER NOT NULL, -- 1-based data row in the file
+    payment_type TEXT, -- AchCredit, WireOutbound (NULL if unreadable)
+    amount TEXT, -- As written in the file (the transaction holds the booked amount)
+    beneficiary_name TEXT,
+    reference TEXT, -- Customer's own reference (invoice, employee id...)
+    status VARCHAR(20) NOT NULL, -- ACCEPTED, REJECTED
+    errors JSONB NOT NULL DEFAULT '[]'::jsonb, -- Validation messages for rejected rows
+    payment_details JSONB, -- ACH / wire details of accepted rows
+    transaction_id UUID REFERENCES core_schema.transactions(transaction_id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (batch_id, row_number)
+);
+CREATE INDEX idx_payout_batch_items_transaction_id ON core_schema.payout_batch_items(transaction_id);
+
+CREATE TRIGGER set_timestamp_payout_batch_items
+BEFORE UPDATE ON core_schema.payout_batch_items
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
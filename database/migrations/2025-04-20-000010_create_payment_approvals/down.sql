@@ -0,0 +1,4 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000010_create_payment_approvals/down.sql
+DROP TABLE IF EXISTS core_schema.payment_approval_decisions;
+DROP TRIGGER IF EXISTS set_timestamp_payment_approvals ON core_schema.payment_approvals;
+DROP TABLE IF EXISTS core_schema.payment_approvals;
@@ -0,0 +1,37 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000010_create_payment_approvals/up.sql
+
+-- Maker-checker: an outbound payment matching an approval policy waits in RequiresAction (nothing held)
+-- until enough authorised approvers, never the initiator, approve it within the window.
+CREATE TABLE core_schema.payment_approvals (
+    approval_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    transaction_id UUID NOT NULL UNIQUE REFERENCES core_schema.transactions(transaction_id),
+    initiator_user_id UUID NOT NULL REFERENCES core_schema.users(user_id),
+    status VARCHAR(20) NOT NULL, -- PENDING, APPROVED, REJECTED, EXPIRED
+    required_approvals INTEGER NOT NULL,
+    approval_count INTEGER NOT NULL DEFAULT 0,
+    approver_roles JSONB NOT NULL, -- Roles allowed to decide, e.g. ["admin", "finance"]
+    policy_ids JSONB NOT NULL, -- Policies that matched the payment
+    pending_dispatch JSONB NOT NULL, -- Outbox operation, payload and fee quote used on release
+    expires_at TIMESTAMPTZ NOT NULL,
+    decided_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_payment_approvals_pending ON core_schema.payment_approvals(expires_at) WHERE status = 'PENDING';
+
+CREATE TRIGGER set_timestamp_payment_approvals
+BEFORE UPDATE ON core_schema.payment_approvals
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
+
+-- One row per approver decision; an approver decides at most once per payment.
+CREATE TABLE core_schema.payment_approval_decisions (
+    decision_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    approval_id UUID NOT NULL REFERENCES core_schema.payment_approvals(approval_id),
+    approver_user_id UUID NOT NULL REFERENCES core_schema.users(user_id),
+    approver_role VARCHAR(50) NOT NULL,
+    decision VARCHAR(10) NOT NULL, -- APPROVE, REJECT
+    comment TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (approval_id, approver_user_id)
+);
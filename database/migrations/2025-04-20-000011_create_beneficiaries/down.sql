@@ -0,0 +1,4 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000011_create_beneficiaries/down.sql
+DROP INDEX IF EXISTS core_schema.idx_transactions_beneficiary;
+DROP TRIGGER IF EXISTS set_timestamp_beneficiaries ON core_schema.beneficiaries;
+DROP TABLE IF EXISTS core_schema.beneficiaries;
@@ -0,0 +1,33 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000011_create_beneficiaries/up.sql
+
+-- Saved payees. Account numbers, IBANs and crypto addresses are only stored hashed (plus a masked form for
+-- display); payments are matched to a beneficiary by hashing their details the same way.
+CREATE TABLE core_schema.beneficiaries (
+    beneficiary_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES core_schema.users(user_id),
+    nickname VARCHAR(100) NOT NULL,
+    beneficiary_type VARCHAR(20) NOT NULL, -- ACH, IBAN, WIRE, CRYPTO
+    identifier_hash VARCHAR(64) NOT NULL, -- Base64 SHA-256 of the normalized identifier
+    masked_identifier VARCHAR(50) NOT NULL, -- e.g. ****6789
+    bank_identifier VARCHAR(11), -- ABA routing number or BIC (not secret)
+    crypto_network VARCHAR(10), -- BTC, XMR
+    country_code VARCHAR(2),
+    holder_name VARCHAR(140),
+    status VARCHAR(30) NOT NULL, -- PENDING_VERIFICATION, ACTIVE, DISABLED
+    verified_at TIMESTAMPTZ, -- Last (re-)verification; reset when the payment details change
+    cooling_off_until TIMESTAMPTZ, -- Reduced limits apply until then
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+-- A payee is saved once per customer (disabled entries don't count)
+CREATE UNIQUE INDEX idx_beneficiaries_user_identifier ON core_schema.beneficiaries(user_id, identifier_hash)
+    WHERE status <> 'DISABLED';
+
+CREATE TRIGGER set_timestamp_beneficiaries
+BEFORE UPDATE ON core_schema.beneficiaries
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
+
+-- Payments to a saved beneficiary carry its id in transactions.metadata->>'beneficiary_id'
+CREATE INDEX idx_transactions_beneficiary ON core_schema.transactions((metadata->>'beneficiary_id'))
+    WHERE metadata ? 'beneficiary_id';
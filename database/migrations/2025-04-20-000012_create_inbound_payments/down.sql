@@ -0,0 +1,6 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000012_create_inbound_payments/down.sql
+DROP INDEX IF EXISTS core_schema.idx_transactions_inbound_reference;
+DROP TRIGGER IF EXISTS set_timestamp_inbound_suspense ON core_schema.inbound_suspense;
+DROP TABLE IF EXISTS core_schema.inbound_suspense;
+DROP TRIGGER IF EXISTS set_timestamp_virtual_accounts ON core_schema.virtual_accounts;
+DROP TABLE IF EXISTS core_schema.virtual_accounts;
@@ -0,0 +1,47 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000012_create_inbound_payments/up.sql
+
+-- Account numbers we issue so customers can receive ACH credits and wires straight into a wallet.
+CREATE TABLE core_schema.virtual_accounts (
+    virtual_account_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    wallet_id UUID NOT NULL REFERENCES core_schema.wallets(wallet_id),
+    account_number VARCHAR(17) NOT NULL UNIQUE, -- Our own identifier, not customer data
+    status VARCHAR(20) NOT NULL, -- ACTIVE, CLOSED
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_virtual_accounts_wallet ON core_schema.virtual_accounts(wallet_id);
+
+CREATE TRIGGER set_timestamp_virtual_accounts
+BEFORE UPDATE ON core_schema.virtual_accounts
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
+
+-- Inbound payments that could not be matched to a wallet. The funds sit in SUSPENSE:<currency> until
+-- operations assign them to a wallet or return them.
+CREATE TABLE core_schema.inbound_suspense (
+    suspense_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    transaction_id UUID NOT NULL UNIQUE REFERENCES core_schema.transactions(transaction_id),
+    rail VARCHAR(20) NOT NULL, -- ACH, WIRE, CRYPTO_BTC, CRYPTO_XMR
+    amount NUMERIC(19, 8) NOT NULL,
+    currency_code VARCHAR(10) NOT NULL,
+    reason TEXT NOT NULL, -- Why no wallet was credited
+    notice JSONB NOT NULL, -- The normalized inbound notice as received
+    status VARCHAR(20) NOT NULL, -- OPEN, ASSIGNED, RETURNED
+    resolved_wallet_id UUID REFERENCES core_schema.wallets(wallet_id),
+    resolved_by UUID REFERENCES core_schema.users(user_id),
+    resolution_note TEXT,
+    resolved_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_inbound_suspense_open ON core_schema.inbound_suspense(created_at) WHERE status = 'OPEN';
+
+CREATE TRIGGER set_timestamp_inbound_suspense
+BEFORE UPDATE ON core_schema.inbound_suspense
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
+
+-- One transaction per rail reference: a re-sent notice is a duplicate, never a second credit
+CREATE UNIQUE INDEX idx_transactions_inbound_reference ON core_schema.transactions(transaction_type, external_ref_id)
+    WHERE transaction_type IN ('AchInbound', 'WireInbound', 'CryptoBtcReceive', 'CryptoXmrReceive')
+      AND external_ref_id IS NOT NULL;
@@ -0,0 +1,3 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000013_create_webhook_events/down.sql
+DROP TRIGGER IF EXISTS set_timestamp_webhook_events ON core_schema.webhook_events;
+DROP TABLE IF EXISTS core_schema.webhook_events;
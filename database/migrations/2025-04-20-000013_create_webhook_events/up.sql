@@ -0,0 +1,27 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000013_create_webhook_events/up.sql
+
+-- Every verified provider webhook, with its raw payload so it can be reprocessed. The unique
+-- (provider, provider_event_id) pair is the seen-event store: a replayed delivery is recognised here.
+CREATE TABLE core_schema.webhook_events (
+    event_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    provider VARCHAR(50) NOT NULL, -- stripe, btcpay, partner_bank, mock
+    provider_event_id VARCHAR(255) NOT NULL, -- The provider's own event/delivery id
+    event_type VARCHAR(100) NOT NULL,
+    raw_payload TEXT NOT NULL, -- Body exactly as received
+    signature_headers JSONB NOT NULL DEFAULT '{}'::jsonb, -- Headers the signature was checked against
+    status VARCHAR(20) NOT NULL, -- RECEIVED, PROCESSED, FAILED
+    attempts INT NOT NULL DEFAULT 0,
+    last_error TEXT,
+    results JSONB, -- Per-payment outcome of the last processing attempt
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    processed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT uq_webhook_events_provider_event UNIQUE (provider, provider_event_id)
+);
+CREATE INDEX idx_webhook_events_status ON core_schema.webhook_events(status, received_at);
+
+CREATE TRIGGER set_timestamp_webhook_events
+BEFORE UPDATE ON core_schema.webhook_events
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
@@ -0,0 +1,7 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000014_create_reconciliation/down.sql
+DROP TRIGGER IF EXISTS set_timestamp_reconciliation_exceptions ON core_schema.reconciliation_exceptions;
+DROP TABLE IF EXISTS core_schema.reconciliation_exceptions;
+DROP TRIGGER IF EXISTS set_timestamp_bank_statement_lines ON core_schema.bank_statement_lines;
+DROP TABLE IF EXISTS core_schema.bank_statement_lines;
+DROP TABLE IF EXISTS core_schema.reconciliation_match_transactions;
+DROP TABLE IF EXISTS core_schema.reconciliation_matches;
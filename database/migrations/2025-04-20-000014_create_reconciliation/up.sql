@@ -0,0 +1,82 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000014_create_reconciliation/up.sql
+
+-- Reconciliation groups: statement lines and transactions agreed to be the same money movement.
+CREATE TABLE core_schema.reconciliation_matches (
+    match_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    nostro_account VARCHAR(50) NOT NULL, -- Code from the nostro account configuration
+    match_kind VARCHAR(20) NOT NULL, -- ONE_TO_ONE, ONE_TO_MANY (one line, several transactions), MANY_TO_ONE
+    match_rule VARCHAR(20) NOT NULL, -- UETR, REFERENCE, AMOUNT_DATE, MANUAL
+    statement_amount NUMERIC(19, 8) NOT NULL, -- Signed sum of the lines (credits positive)
+    transaction_amount NUMERIC(19, 8) NOT NULL, -- Signed sum of the transactions, same convention
+    currency_code VARCHAR(10) NOT NULL,
+    matched_by UUID REFERENCES core_schema.users(user_id), -- NULL = automatic
+    note TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_reconciliation_matches_account ON core_schema.reconciliation_matches(nostro_account, created_at);
+
+-- Transactions in a match. A transaction is reconciled at most once.
+CREATE TABLE core_schema.reconciliation_match_transactions (
+    match_id UUID NOT NULL REFERENCES core_schema.reconciliation_matches(match_id),
+    transaction_id UUID NOT NULL UNIQUE REFERENCES core_schema.transactions(transaction_id),
+    PRIMARY KEY (match_id, transaction_id)
+);
+
+-- Bank statement entries pulled per nostro account. Pulls overlap, so lines are keyed by the bank's id.
+CREATE TABLE core_schema.bank_statement_lines (
+    line_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    nostro_account VARCHAR(50) NOT NULL,
+    bank_transaction_id VARCHAR(255) NOT NULL,
+    booked_at TIMESTAMPTZ NOT NULL,
+    value_date DATE,
+    amount NUMERIC(19, 8) NOT NULL, -- Signed: credits to our account positive, debits negative
+    currency_code VARCHAR(10) NOT NULL,
+    reference VARCHAR(255), -- End-to-end id / client reference reported by the bank
+    uetr VARCHAR(36),
+    description TEXT NOT NULL,
+    counterparty_name VARCHAR(255),
+    raw JSONB NOT NULL, -- The entry as returned by the bank client
+    status VARCHAR(20) NOT NULL, -- UNMATCHED, MATCHED, EXCEPTION
+    match_id UUID REFERENCES core_schema.reconciliation_matches(match_id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT uq_bank_statement_lines_bank_id UNIQUE (nostro_account, bank_transaction_id)
+);
+CREATE INDEX idx_bank_statement_lines_open ON core_schema.bank_statement_lines(nostro_account, booked_at)
+    WHERE status <> 'MATCHED';
+
+CREATE TRIGGER set_timestamp_bank_statement_lines
+BEFORE UPDATE ON core_schema.bank_statement_lines
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
+
+-- Breaks: anything that did not reconcile cleanly, worked by operations.
+CREATE TABLE core_schema.reconciliation_exceptions (
+    exception_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    nostro_account VARCHAR(50) NOT NULL,
+    exception_type VARCHAR(30) NOT NULL, -- UNMATCHED_STATEMENT_LINE, UNMATCHED_TRANSACTION, AMOUNT_MISMATCH
+    line_id UUID UNIQUE REFERENCES core_schema.bank_statement_lines(line_id),
+    transaction_id UUID REFERENCES core_schema.transactions(transaction_id),
+    match_id UUID REFERENCES core_schema.reconciliation_matches(match_id),
+    business_date DATE NOT NULL, -- Statement booking date, or the transaction's date for missing entries
+    expected_amount NUMERIC(19, 8), -- Our side
+    actual_amount NUMERIC(19, 8), -- The bank's side
+    currency_code VARCHAR(10) NOT NULL,
+    status VARCHAR(20) NOT NULL, -- OPEN, RESOLVED
+    resolution VARCHAR(30), -- AUTO_MATCHED, MATCHED_MANUALLY, ACCEPTED, WRITTEN_OFF, IGNORED
+    resolution_note TEXT,
+    resolved_by UUID REFERENCES core_schema.users(user_id),
+    resolved_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_reconciliation_exceptions_open ON core_schema.reconciliation_exceptions(nostro_account, business_date)
+    WHERE status = 'OPEN';
+-- One open "missing from statement" break per transaction, however often the account is reconciled
+CREATE UNIQUE INDEX idx_reconciliation_exceptions_open_tx ON core_schema.reconciliation_exceptions(transaction_id)
+    WHERE status = 'OPEN' AND exception_type = 'UNMATCHED_TRANSACTION';
+
+CREATE TRIGGER set_timestamp_reconciliation_exceptions
+BEFORE UPDATE ON core_schema.reconciliation_exceptions
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
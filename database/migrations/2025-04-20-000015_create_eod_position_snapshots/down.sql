@@ -0,0 +1,3 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000015_create_eod_position_snapshots/down.sql
+DROP TRIGGER IF EXISTS set_timestamp_eod_position_snapshots ON core_schema.eod_position_snapshots;
+DROP TABLE IF EXISTS core_schema.eod_position_snapshots;
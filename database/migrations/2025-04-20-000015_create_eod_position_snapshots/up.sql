@@ -0,0 +1,25 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000015_create_eod_position_snapshots/up.sql
+-- One signed end-of-day nostro position report per business day. A re-run for the same day replaces it.
+CREATE TABLE core_schema.eod_position_snapshots (
+    snapshot_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    business_date DATE NOT NULL UNIQUE,
+    projection_date DATE NOT NULL, -- The business day whose intraday liquidity is projected
+    report JSONB NOT NULL, -- For querying; `payload` holds the exact signed bytes
+    payload TEXT NOT NULL,
+    payload_sha256 VARCHAR(64) NOT NULL,
+    signature_algorithm VARCHAR(20) NOT NULL,
+    signature TEXT NOT NULL, -- Hex
+    signing_key_id VARCHAR(16) NOT NULL,
+    signing_public_key VARCHAR(64) NOT NULL, -- Hex
+    account_count INTEGER NOT NULL,
+    shortfall_count INTEGER NOT NULL, -- Accounts projected below their minimum balance
+    generated_by VARCHAR(100) NOT NULL, -- Worker or user that produced the report
+    generated_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER set_timestamp_eod_position_snapshots
+BEFORE UPDATE ON core_schema.eod_position_snapshots
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
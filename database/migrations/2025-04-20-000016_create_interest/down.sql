@@ -0,0 +1,6 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000016_create_interest/down.sql
+DROP TABLE IF EXISTS core_schema.interest_postings;
+DROP TABLE IF EXISTS core_schema.interest_accruals;
+DROP TRIGGER IF EXISTS set_timestamp_interest_wallet_states ON core_schema.interest_wallet_states;
+DROP TABLE IF EXISTS core_schema.interest_wallet_states;
+DROP TABLE IF EXISTS core_schema.interest_rate_versions;
@@ -0,0 +1,66 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000016_create_interest/up.sql
+-- Interest on savings-type wallets: effective-dated rate versions, daily accruals (recomputed when a
+-- backdated rate or value-dated transaction changes a past day) and monthly capitalization postings.
+
+-- Rate versions are never edited: a correction is a new version, possibly effective in the past.
+CREATE TABLE core_schema.interest_rate_versions (
+    rate_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    wallet_type VARCHAR(50) NOT NULL, -- e.g. SAVINGS_USD
+    currency_code VARCHAR(10) NOT NULL,
+    effective_from DATE NOT NULL,
+    day_count VARCHAR(10) NOT NULL, -- ACT_360, ACT_365, 30_360
+    tiers JSONB NOT NULL, -- [{ "up_to": "10000.00", "rate_percent": "1.50" }, { "up_to": null, "rate_percent": "2.00" }]
+    withholding_tax_percent NUMERIC(9, 6) NOT NULL DEFAULT 0,
+    note TEXT,
+    created_by UUID REFERENCES core_schema.users(user_id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_interest_rate_versions_lookup
+    ON core_schema.interest_rate_versions(wallet_type, currency_code, effective_from DESC, created_at DESC);
+
+-- Where each wallet's accrual stands; line/rate changes after `last_run_at` trigger recomputation.
+CREATE TABLE core_schema.interest_wallet_states (
+    wallet_id UUID PRIMARY KEY REFERENCES core_schema.wallets(wallet_id),
+    accrued_through DATE, -- Last day accrued
+    last_line_id BIGINT NOT NULL DEFAULT 0, -- Highest journal line of the wallet seen by accrual
+    last_run_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One row per wallet and day, overwritten when the day is recomputed.
+CREATE TABLE core_schema.interest_accruals (
+    accrual_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    wallet_id UUID NOT NULL REFERENCES core_schema.wallets(wallet_id),
+    accrual_date DATE NOT NULL,
+    balance NUMERIC(19, 8) NOT NULL, -- Value-dated end-of-day balance
+    annual_rate_percent NUMERIC(12, 8) NOT NULL, -- Effective (blended across tiers)
+    day_count VARCHAR(10) NOT NULL,
+    year_fraction NUMERIC(12, 10) NOT NULL,
+    amount NUMERIC(28, 12) NOT NULL, -- Unrounded
+    rate_id UUID REFERENCES core_schema.interest_rate_versions(rate_id),
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (wallet_id, accrual_date)
+);
+
+-- Capitalizations: what was credited for a period, net of tax withheld.
+CREATE TABLE core_schema.interest_postings (
+    posting_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    wallet_id UUID NOT NULL REFERENCES core_schema.wallets(wallet_id),
+    period_start DATE NOT NULL,
+    period_end DATE NOT NULL,
+    accrued_to_date NUMERIC(28, 12) NOT NULL, -- All accruals up to period_end, including recomputed earlier days
+    gross_amount NUMERIC(19, 8) NOT NULL,
+    tax_amount NUMERIC(19, 8) NOT NULL,
+    net_amount NUMERIC(19, 8) NOT NULL,
+    currency_code VARCHAR(10) NOT NULL,
+    interest_transaction_id UUID REFERENCES core_schema.transactions(transaction_id),
+    tax_transaction_id UUID REFERENCES core_schema.transactions(transaction_id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (wallet_id, period_end)
+);
+
+CREATE TRIGGER set_timestamp_interest_wallet_states
+BEFORE UPDATE ON core_schema.interest_wallet_states
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
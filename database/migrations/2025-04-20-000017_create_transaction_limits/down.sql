@@ -0,0 +1,7 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000017_create_transaction_limits/down.sql
+DROP TRIGGER IF EXISTS set_timestamp_limit_overrides ON core_schema.limit_overrides;
+DROP TABLE IF EXISTS core_schema.limit_overrides;
+DROP TABLE IF EXISTS core_schema.limit_usages;
+DROP TRIGGER IF EXISTS set_timestamp_limit_counters ON core_schema.limit_counters;
+DROP TABLE IF EXISTS core_schema.limit_counters;
+ALTER TABLE core_schema.users DROP COLUMN IF EXISTS kyc_tier;
@@ -0,0 +1,68 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000017_create_transaction_limits/up.sql
+-- Transaction limits: usage counters per user or wallet, the payments that consumed them, and
+-- admin-approved temporary increases. Windows are rolling: usage is what was consumed since now - period.
+
+ALTER TABLE core_schema.users
+    ADD COLUMN kyc_tier VARCHAR(20) NOT NULL DEFAULT 'BASIC'; -- BASIC, VERIFIED, ENHANCED...
+
+-- One row per limit rule, subject and currency; locked by a payment while it sums and adds its usage,
+-- so two concurrent payments cannot both squeeze under a limit.
+CREATE TABLE core_schema.limit_counters (
+    counter_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    rule_id VARCHAR(100) NOT NULL, -- Id of the rule in the limits policy file
+    subject_type VARCHAR(10) NOT NULL, -- USER, WALLET
+    subject_id UUID NOT NULL,
+    currency_code VARCHAR(10) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT limit_counters_subject_type_check CHECK (subject_type IN ('USER', 'WALLET')),
+    UNIQUE (rule_id, subject_type, subject_id, currency_code)
+);
+CREATE INDEX idx_limit_counters_subject ON core_schema.limit_counters(subject_type, subject_id);
+
+-- Which payment consumed what; released (and no longer counted) when the payment fails or is cancelled.
+CREATE TABLE core_schema.limit_usages (
+    usage_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    counter_id UUID NOT NULL REFERENCES core_schema.limit_counters(counter_id),
+    transaction_id UUID NOT NULL REFERENCES core_schema.transactions(transaction_id),
+    amount NUMERIC(19, 8) NOT NULL,
+    released_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (counter_id, transaction_id)
+);
+CREATE INDEX idx_limit_usages_transaction ON core_schema.limit_usages(transaction_id);
+CREATE INDEX idx_limit_usages_window ON core_schema.limit_usages(counter_id, created_at) WHERE released_at IS NULL;
+
+-- Temporary limit increases. Only APPROVED overrides inside their validity window apply.
+CREATE TABLE core_schema.limit_overrides (
+    override_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    rule_id VARCHAR(100) NOT NULL,
+    subject_type VARCHAR(10) NOT NULL,
+    subject_id UUID NOT NULL,
+    max_amount NUMERIC(19, 8), -- Replaces the rule's amount limit, NULL = unchanged
+    max_count INTEGER, -- Replaces the rule's count limit, NULL = unchanged
+    valid_from TIMESTAMPTZ NOT NULL,
+    valid_until TIMESTAMPTZ NOT NULL,
+    reason TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'PENDING', -- PENDING, APPROVED, REJECTED, REVOKED
+    requested_by UUID NOT NULL REFERENCES core_schema.users(user_id),
+    decided_by UUID REFERENCES core_schema.users(user_id),
+    decision_comment TEXT,
+    decided_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT limit_overrides_subject_type_check CHECK (subject_type IN ('USER', 'WALLET')),
+    CONSTRAINT limit_overrides_window_check CHECK (valid_until > valid_from),
+    CONSTRAINT limit_overrides_values_check CHECK (max_amount IS NOT NULL OR max_count IS NOT NULL)
+);
+CREATE INDEX idx_limit_overrides_lookup ON core_schema.limit_overrides(rule_id, subject_type, subject_id, status);
+
+CREATE TRIGGER set_timestamp_limit_counters
+BEFORE UPDATE ON core_schema.limit_counters
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
+
+CREATE TRIGGER set_timestamp_limit_overrides
+BEFORE UPDATE ON core_schema.limit_overrides
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
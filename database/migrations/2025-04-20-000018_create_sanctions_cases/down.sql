@@ -0,0 +1,3 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000018_create_sanctions_cases/down.sql
+DROP TRIGGER IF EXISTS set_timestamp_sanctions_cases ON core_schema.sanctions_cases;
+DROP TABLE IF EXISTS core_schema.sanctions_cases;
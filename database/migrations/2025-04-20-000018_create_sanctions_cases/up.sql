@@ -0,0 +1,26 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000018_create_sanctions_cases/up.sql
+-- Sanctions screening cases: outbound payments whose parties hit a watchlist wait in REQUIRES_ACTION
+-- until compliance clears them (false positive) or confirms the hit (payment cancelled).
+
+CREATE TABLE core_schema.sanctions_cases (
+    case_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    transaction_id UUID NOT NULL UNIQUE REFERENCES core_schema.transactions(transaction_id),
+    initiator_user_id UUID NOT NULL REFERENCES core_schema.users(user_id),
+    status VARCHAR(20) NOT NULL DEFAULT 'OPEN', -- OPEN, CLEARED, CONFIRMED
+    parties JSONB NOT NULL, -- Parties screened
+    hits JSONB NOT NULL, -- Watchlist hits (list, entry, matched value, score)
+    lists JSONB NOT NULL, -- Lists and entry counts loaded when screened
+    pending_dispatch JSONB NOT NULL, -- What is needed to resume the payment once cleared
+    reviewed_by UUID REFERENCES core_schema.users(user_id),
+    review_comment TEXT,
+    reviewed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT sanctions_cases_status_check CHECK (status IN ('OPEN', 'CLEARED', 'CONFIRMED'))
+);
+CREATE INDEX idx_sanctions_cases_status ON core_schema.sanctions_cases(status, created_at);
+
+CREATE TRIGGER set_timestamp_sanctions_cases
+BEFORE UPDATE ON core_schema.sanctions_cases
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
@@ -0,0 +1,7 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000019_create_aml_monitoring/down.sql
+DROP TRIGGER IF EXISTS set_timestamp_aml_monitor_state ON core_schema.aml_monitor_state;
+DROP TABLE IF EXISTS core_schema.aml_monitor_state;
+DROP TRIGGER IF EXISTS set_timestamp_aml_alerts ON core_schema.aml_alerts;
+DROP TABLE IF EXISTS core_schema.aml_alerts;
+DROP TRIGGER IF EXISTS set_timestamp_aml_cases ON core_schema.aml_cases;
+DROP TABLE IF EXISTS core_schema.aml_cases;
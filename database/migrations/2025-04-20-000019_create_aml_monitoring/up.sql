@@ -0,0 +1,64 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000019_create_aml_monitoring/up.sql
+-- AML transaction monitoring: scenario alerts over customer behaviour, grouped into one investigation
+-- case per customer, and the position of the streaming monitor in the transactions table.
+
+-- Investigation cases. A customer has at most one case OPEN or INVESTIGATING; new alerts join it.
+CREATE TABLE core_schema.aml_cases (
+    case_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES core_schema.users(user_id),
+    status VARCHAR(20) NOT NULL DEFAULT 'OPEN', -- OPEN, INVESTIGATING, CLOSED, SAR_FILED
+    risk_score INTEGER NOT NULL DEFAULT 0, -- Sum of the alerts' severity weights
+    alert_count INTEGER NOT NULL DEFAULT 0,
+    assigned_to UUID REFERENCES core_schema.users(user_id),
+    resolution_comment TEXT,
+    sar_reference VARCHAR(100), -- Filing reference when a SAR was filed
+    closed_by UUID REFERENCES core_schema.users(user_id),
+    closed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT aml_cases_status_check CHECK (status IN ('OPEN', 'INVESTIGATING', 'CLOSED', 'SAR_FILED'))
+);
+CREATE UNIQUE INDEX idx_aml_cases_active_user ON core_schema.aml_cases(user_id) WHERE status IN ('OPEN', 'INVESTIGATING');
+CREATE INDEX idx_aml_cases_queue ON core_schema.aml_cases(status, risk_score DESC);
+
+-- One detected pattern. Re-detections of the same pattern (overlapping transactions) update the alert.
+CREATE TABLE core_schema.aml_alerts (
+    alert_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    case_id UUID NOT NULL REFERENCES core_schema.aml_cases(case_id),
+    user_id UUID NOT NULL REFERENCES core_schema.users(user_id),
+    scenario_id VARCHAR(100) NOT NULL, -- Id of the scenario in the AML configuration file
+    severity VARCHAR(10) NOT NULL, -- LOW, MEDIUM, HIGH
+    transaction_ids JSONB NOT NULL, -- Transactions forming the pattern
+    window_start TIMESTAMPTZ NOT NULL,
+    window_end TIMESTAMPTZ NOT NULL,
+    summary TEXT NOT NULL,
+    facts JSONB NOT NULL, -- Scenario-specific figures (counts, totals, counterparties...)
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT aml_alerts_severity_check CHECK (severity IN ('LOW', 'MEDIUM', 'HIGH'))
+);
+CREATE INDEX idx_aml_alerts_case ON core_schema.aml_alerts(case_id);
+CREATE INDEX idx_aml_alerts_user_scenario ON core_schema.aml_alerts(user_id, scenario_id, window_end);
+
+-- Last transaction change (by updated_at, transaction_id) seen by the streaming monitor.
+CREATE TABLE core_schema.aml_monitor_state (
+    monitor_id VARCHAR(50) PRIMARY KEY,
+    last_updated_at TIMESTAMPTZ NOT NULL,
+    last_transaction_id UUID NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER set_timestamp_aml_cases
+BEFORE UPDATE ON core_schema.aml_cases
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
+
+CREATE TRIGGER set_timestamp_aml_alerts
+BEFORE UPDATE ON core_schema.aml_alerts
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
+
+CREATE TRIGGER set_timestamp_aml_monitor_state
+BEFORE UPDATE ON core_schema.aml_monitor_state
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
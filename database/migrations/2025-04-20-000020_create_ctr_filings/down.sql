@@ -0,0 +1,7 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000020_create_ctr_filings/down.sql
+DROP TRIGGER IF EXISTS set_timestamp_ctr_reports ON core_schema.ctr_reports;
+DROP TABLE IF EXISTS core_schema.ctr_reports;
+DROP TRIGGER IF EXISTS set_timestamp_ctr_batches ON core_schema.ctr_batches;
+DROP TABLE IF EXISTS core_schema.ctr_batches;
+DROP TRIGGER IF EXISTS set_timestamp_ctr_exemptions ON core_schema.ctr_exemptions;
+DROP TABLE IF EXISTS core_schema.ctr_exemptions;
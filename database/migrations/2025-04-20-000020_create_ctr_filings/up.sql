@@ -0,0 +1,75 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000020_create_ctr_filings/up.sql
+-- Currency Transaction Reports: cash activity over the threshold, aggregated per person per business day,
+-- filed to FinCEN in CTR batch XML files. Exempt persons are recorded but not filed.
+
+CREATE TABLE core_schema.ctr_exemptions (
+    exemption_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES core_schema.users(user_id),
+    exemption_type VARCHAR(20) NOT NULL, -- PHASE_I, PHASE_II (31 CFR 1020.315)
+    reason TEXT NOT NULL,
+    effective_from DATE NOT NULL,
+    expires_on DATE, -- NULL = until revoked
+    created_by UUID NOT NULL REFERENCES core_schema.users(user_id),
+    revoked_by UUID REFERENCES core_schema.users(user_id),
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT ctr_exemptions_type_check CHECK (exemption_type IN ('PHASE_I', 'PHASE_II')),
+    CONSTRAINT ctr_exemptions_period_check CHECK (expires_on IS NULL OR expires_on >= effective_from)
+);
+CREATE INDEX idx_ctr_exemptions_user ON core_schema.ctr_exemptions(user_id, effective_from);
+
+CREATE TABLE core_schema.ctr_batches (
+    batch_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    file_name VARCHAR(100) NOT NULL UNIQUE,
+    xml TEXT NOT NULL, -- FinCEN CTR batch XML, as submitted
+    report_count INT NOT NULL,
+    total_amount NUMERIC(19, 2) NOT NULL, -- Cash in plus cash out over all reports, whole dollars
+    status VARCHAR(20) NOT NULL DEFAULT 'GENERATED', -- GENERATED, SUBMITTED, ACKNOWLEDGED
+    created_by UUID NOT NULL REFERENCES core_schema.users(user_id),
+    submitted_at TIMESTAMPTZ,
+    acknowledged_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT ctr_batches_status_check CHECK (status IN ('GENERATED', 'SUBMITTED', 'ACKNOWLEDGED'))
+);
+
+CREATE TABLE core_schema.ctr_reports (
+    report_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES core_schema.users(user_id),
+    business_date DATE NOT NULL,
+    currency_code VARCHAR(10) NOT NULL,
+    cash_in_total NUMERIC(19, 4) NOT NULL,
+    cash_out_total NUMERIC(19, 4) NOT NULL,
+    details JSONB NOT NULL, -- Totals per FinCEN activity type code, cash in and cash out
+    transaction_ids JSONB NOT NULL, -- Transactions aggregated into the report
+    wallet_ids JSONB NOT NULL, -- Accounts involved
+    status VARCHAR(20) NOT NULL DEFAULT 'PENDING', -- PENDING, EXEMPT, BATCHED, FILED, ACCEPTED, REJECTED
+    exemption_id UUID REFERENCES core_schema.ctr_exemptions(exemption_id),
+    batch_id UUID REFERENCES core_schema.ctr_batches(batch_id),
+    batch_seq_num INT, -- Activity SeqNum in the batch file, used to match acknowledgements
+    bsa_id VARCHAR(20), -- BSA identifier assigned by FinCEN on acceptance
+    rejection_reason TEXT,
+    filed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT ctr_reports_user_day_unique UNIQUE (user_id, business_date, currency_code),
+    CONSTRAINT ctr_reports_status_check CHECK (status IN ('PENDING', 'EXEMPT', 'BATCHED', 'FILED', 'ACCEPTED', 'REJECTED'))
+);
+CREATE INDEX idx_ctr_reports_status ON core_schema.ctr_reports(status, business_date);
+CREATE INDEX idx_ctr_reports_batch ON core_schema.ctr_reports(batch_id);
+
+CREATE TRIGGER set_timestamp_ctr_exemptions
+BEFORE UPDATE ON core_schema.ctr_exemptions
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
+
+CREATE TRIGGER set_timestamp_ctr_batches
+BEFORE UPDATE ON core_schema.ctr_batches
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
+
+CREATE TRIGGER set_timestamp_ctr_reports
+BEFORE UPDATE ON core_schema.ctr_reports
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
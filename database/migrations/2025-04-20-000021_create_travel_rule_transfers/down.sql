@@ -0,0 +1,3 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000021_create_travel_rule_transfers/down.sql
+DROP TRIGGER IF EXISTS set_timestamp_travel_rule_transfers ON core_schema.travel_rule_transfers;
+DROP TABLE IF EXISTS core_schema.travel_rule_transfers;
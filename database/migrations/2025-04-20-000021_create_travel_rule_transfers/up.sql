@@ -0,0 +1,36 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000021_create_travel_rule_transfers/up.sql
+-- FATF Travel Rule (Recommendation 16): IVMS101 originator and beneficiary data for crypto withdrawals above
+-- the threshold, and its exchange with the beneficiary's VASP. Withdrawals wait in REQUIRES_ACTION while data
+-- is missing or the counterparty has not accepted; transfers to self-hosted wallets only record the data.
+
+CREATE TABLE core_schema.travel_rule_transfers (
+    transfer_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    transaction_id UUID NOT NULL UNIQUE REFERENCES core_schema.transactions(transaction_id),
+    initiator_user_id UUID NOT NULL REFERENCES core_schema.users(user_id),
+    status VARCHAR(20) NOT NULL, -- AWAITING_DATA, READY, SENT, ACCEPTED, REJECTED, UNHOSTED, EXPIRED
+    destination_address TEXT NOT NULL,
+    beneficiary_vasp_id VARCHAR(100), -- Counterparty from the VASP directory; NULL for self-hosted wallets
+    protocol VARCHAR(50), -- Adapter used for the exchange
+    ivms101 JSONB NOT NULL, -- IVMS101 IdentityPayload as collected (and completed by the counterparty)
+    missing_fields JSONB NOT NULL DEFAULT '[]', -- Required data elements still missing
+    pending_dispatch JSONB, -- What is needed to release the withdrawal once the exchange succeeds
+    counterparty_reference VARCHAR(255), -- The counterparty's id for the exchange
+    counterparty_response JSONB, -- Last answer received
+    attempts INT NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ, -- Held withdrawals expire after this
+    sent_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT travel_rule_transfers_status_check
+        CHECK (status IN ('AWAITING_DATA', 'READY', 'SENT', 'ACCEPTED', 'REJECTED', 'UNHOSTED', 'EXPIRED'))
+);
+CREATE INDEX idx_travel_rule_transfers_status ON core_schema.travel_rule_transfers(status, next_attempt_at);
+CREATE INDEX idx_travel_rule_transfers_user ON core_schema.travel_rule_transfers(initiator_user_id, created_at DESC);
+
+CREATE TRIGGER set_timestamp_travel_rule_transfers
+BEFORE UPDATE ON core_schema.travel_rule_transfers
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
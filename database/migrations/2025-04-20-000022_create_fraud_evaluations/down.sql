@@ -0,0 +1,4 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000022_create_fraud_evaluations/down.sql
+DROP TABLE IF EXISTS core_schema.fraud_rule_hits;
+DROP TRIGGER IF EXISTS set_timestamp_fraud_evaluations ON core_schema.fraud_evaluations;
+DROP TABLE IF EXISTS core_schema.fraud_evaluations;
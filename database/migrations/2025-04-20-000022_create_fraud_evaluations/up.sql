@@ -0,0 +1,52 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000022_create_fraud_evaluations/up.sql
+-- Fraud rule engine results: one evaluation per screened payment (the feature snapshot, score and decision of
+-- the rule set version in force) and one hit per rule that fired, shadow rules included, so analysts can see
+-- how often each rule fires and how its hits were reviewed. Payments sent to review wait in REQUIRES_ACTION.
+
+CREATE TABLE core_schema.fraud_evaluations (
+    evaluation_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    transaction_id UUID NOT NULL UNIQUE REFERENCES core_schema.transactions(transaction_id),
+    user_id UUID NOT NULL REFERENCES core_schema.users(user_id),
+    rule_set_version VARCHAR(100) NOT NULL,
+    mode VARCHAR(10) NOT NULL, -- ENFORCE or SHADOW (whole rule set evaluated without effect)
+    score INT NOT NULL, -- Sum of the live rules that fired
+    shadow_score INT NOT NULL, -- Score had the shadow rules been live too
+    decision VARCHAR(10) NOT NULL, -- ALLOW, REVIEW or BLOCK from the live rules
+    shadow_decision VARCHAR(10) NOT NULL, -- Decision had the shadow rules been live too
+    applied_decision VARCHAR(10) NOT NULL, -- What was done to the payment (ALLOW in SHADOW mode)
+    features JSONB NOT NULL, -- Feature values the rules were evaluated against
+    review_status VARCHAR(20), -- OPEN, CLEARED or CONFIRMED for payments sent to review
+    pending_dispatch JSONB, -- What is needed to release the payment once cleared
+    reviewed_by UUID REFERENCES core_schema.users(user_id),
+    review_comment TEXT,
+    reviewed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT fraud_evaluations_mode_check CHECK (mode IN ('ENFORCE', 'SHADOW')),
+    CONSTRAINT fraud_evaluations_decision_check CHECK (decision IN ('ALLOW', 'REVIEW', 'BLOCK')),
+    CONSTRAINT fraud_evaluations_shadow_decision_check CHECK (shadow_decision IN ('ALLOW', 'REVIEW', 'BLOCK')),
+    CONSTRAINT fraud_evaluations_applied_decision_check CHECK (applied_decision IN ('ALLOW', 'REVIEW', 'BLOCK')),
+    CONSTRAINT fraud_evaluations_review_status_check
+        CHECK (review_status IS NULL OR review_status IN ('OPEN', 'CLEARED', 'CONFIRMED'))
+);
+CREATE INDEX idx_fraud_evaluations_user ON core_schema.fraud_evaluations(user_id, created_at DESC);
+CREATE INDEX idx_fraud_evaluations_review ON core_schema.fraud_evaluations(review_status, created_at) WHERE review_status IS NOT NULL;
+
+CREATE TRIGGER set_timestamp_fraud_evaluations
+BEFORE UPDATE ON core_schema.fraud_evaluations
+FOR EACH ROW
+EXECUTE FUNCTION core_schema.trigger_set_timestamp();
+
+CREATE TABLE core_schema.fraud_rule_hits (
+    hit_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    evaluation_id UUID NOT NULL REFERENCES core_schema.fraud_evaluations(evaluation_id) ON DELETE CASCADE,
+    transaction_id UUID NOT NULL REFERENCES core_schema.transactions(transaction_id),
+    rule_id VARCHAR(100) NOT NULL,
+    rule_set_version VARCHAR(100) NOT NULL,
+    score INT NOT NULL,
+    action VARCHAR(10), -- Action the rule itself asks for, if any
+    shadow BOOLEAN NOT NULL, -- Fired without affecting the decision
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_fraud_rule_hits_rule ON core_schema.fraud_rule_hits(rule_id, created_at);
+CREATE INDEX idx_fraud_rule_hits_evaluation ON core_schema.fraud_rule_hits(evaluation_id);
@@ -0,0 +1,3 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000023_create_login_events/down.sql
+DROP TABLE IF EXISTS core_schema.login_events;
+ALTER TABLE core_schema.users DROP COLUMN IF EXISTS country_code;
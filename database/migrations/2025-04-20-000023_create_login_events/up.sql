@@ -0,0 +1,31 @@
+-- /home/inno/elights_jobes-research/database/migrations/2025-04-20-000023_create_login_events/up.sql
+-- Offline IP intelligence for fraud scoring: the country a customer declared (compared with where requests come
+-- from) and every successful login with what the local GeoIP/ASN databases and Tor/VPN/hosting lists said about
+-- its address, so consecutive logins can be checked for impossible travel.
+
+ALTER TABLE core_schema.users
+    ADD COLUMN country_code VARCHAR(2); -- ISO 3166-1 alpha-2 country of residence, when declared
+
+CREATE TABLE core_schema.login_events (
+    login_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES core_schema.users(user_id),
+    ip_address VARCHAR(45), -- Client address as seen behind the proxy
+    user_agent TEXT,
+    device_id VARCHAR(255),
+    country_code VARCHAR(2), -- From the GeoIP database
+    latitude DOUBLE PRECISION,
+    longitude DOUBLE PRECISION,
+    asn BIGINT, -- Autonomous system the address belongs to
+    as_org VARCHAR(255),
+    tor BOOLEAN NOT NULL DEFAULT FALSE, -- Listed Tor exit node
+    vpn BOOLEAN NOT NULL DEFAULT FALSE, -- Listed VPN range
+    hosting BOOLEAN NOT NULL DEFAULT FALSE, -- Listed hosting/data centre range
+    ip_risk INT NOT NULL DEFAULT 0, -- 0-100 network risk from the configured weights
+    previous_login_id UUID REFERENCES core_schema.login_events(login_id), -- Last located login it was compared with
+    distance_km DOUBLE PRECISION,
+    travel_speed_kmh DOUBLE PRECISION,
+    impossible_travel BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_login_events_user ON core_schema.login_events(user_id, created_at DESC);
+CREATE INDEX idx_login_events_impossible ON core_schema.login_events(created_at) WHERE impossible_travel;
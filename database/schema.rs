@@ -4,6 +4,43 @@
 diesel::schema! {
     core_schema (DbSchema) {
         // Define tables based on your up.sql migration
+        aml_alerts (alert_id) {
+            alert_id -> Uuid,
+            case_id -> Uuid,
+            user_id -> Uuid,
+            scenario_id -> Varchar,
+            severity -> Varchar,
+            transaction_ids -> Jsonb,
+            window_start -> Timestamptz,
+            window_end -> Timestamptz,
+            summary -> Text,
+            facts -> Jsonb,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        aml_cases (case_id) {
+            case_id -> Uuid,
+            user_id -> Uuid,
+            status -> Varchar,
+            risk_score -> Int4,
+            alert_count -> Int4,
+            assigned_to -> Nullable<Uuid>,
+            resolution_comment -> Nullable<Text>,
+            sar_reference -> Nullable<Varchar>,
+            closed_by -> Nullable<Uuid>,
+            closed_at -> Nullable<Timestamptz>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        aml_monitor_state (monitor_id) {
+            monitor_id -> Varchar,
+            last_updated_at -> Timestamptz,
+            last_transaction_id -> Uuid,
+            updated_at -> Timestamptz,
+        }
+
         audit_logs (log_id) {
             log_id -> Int8,
             timestamp -> Timestamptz,
@@ -17,6 +54,536 @@ diesel::schema! {
             error_message -> Nullable<Text>,
         }
 
+        bank_statement_lines (line_id) {
+            line_id -> Uuid,
+            nostro_account -> Varchar,
+            bank_transaction_id -> Varchar,
+            booked_at -> Timestamptz,
+            value_date -> Nullable<Date>,
+            amount -> Numeric,
+            currency_code -> Varchar,
+            reference -> Nullable<Varchar>,
+            uetr -> Nullable<Varchar>,
+            description -> Text,
+            counterparty_name -> Nullable<Varchar>,
+            raw -> Jsonb,
+            status -> Varchar,
+            match_id -> Nullable<Uuid>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        beneficiaries (beneficiary_id) {
+            beneficiary_id -> Uuid,
+            user_id -> Uuid,
+            nickname -> Varchar,
+            beneficiary_type -> Varchar,
+            identifier_hash -> Varchar,
+            masked_identifier -> Varchar,
+            bank_identifier -> Nullable<Varchar>,
+            crypto_network -> Nullable<Varchar>,
+            country_code -> Nullable<Varchar>,
+            holder_name -> Nullable<Varchar>,
+            status -> Varchar,
+            verified_at -> Nullable<Timestamptz>,
+            cooling_off_until -> Nullable<Timestamptz>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        ctr_batches (batch_id) {
+            batch_id -> Uuid,
+            file_name -> Varchar,
+            xml -> Text,
+            report_count -> Int4,
+            total_amount -> Numeric,
+            status -> Varchar,
+            created_by -> Uuid,
+            submitted_at -> Nullable<Timestamptz>,
+            acknowledged_at -> Nullable<Timestamptz>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        ctr_exemptions (exemption_id) {
+            exemption_id -> Uuid,
+            user_id -> Uuid,
+            exemption_type -> Varchar,
+            reason -> Text,
+            effective_from -> Date,
+            expires_on -> Nullable<Date>,
+            created_by -> Uuid,
+            revoked_by -> Nullable<Uuid>,
+            revoked_at -> Nullable<Timestamptz>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        ctr_reports (report_id) {
+            report_id -> Uuid,
+            user_id -> Uuid,
+            business_date -> Date,
+            currency_code -> Varchar,
+            cash_in_total -> Numeric,
+            cash_out_total -> Numeric,
+            details -> Jsonb,
+            transaction_ids -> Jsonb,
+            wallet_ids -> Jsonb,
+            status -> Varchar,
+            exemption_id -> Nullable<Uuid>,
+            batch_id -> Nullable<Uuid>,
+            batch_seq_num -> Nullable<Int4>,
+            bsa_id -> Nullable<Varchar>,
+            rejection_reason -> Nullable<Text>,
+            filed_at -> Nullable<Timestamptz>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        eod_position_snapshots (snapshot_id) {
+            snapshot_id -> Uuid,
+            business_date -> Date,
+            projection_date -> Date,
+            report -> Jsonb,
+            payload -> Text,
+            payload_sha256 -> Varchar,
+            signature_algorithm -> Varchar,
+            signature -> Text,
+            signing_key_id -> Varchar,
+            signing_public_key -> Varchar,
+            account_count -> Int4,
+            shortfall_count -> Int4,
+            generated_by -> Varchar,
+            generated_at -> Timestamptz,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        fraud_evaluations (evaluation_id) {
+            evaluation_id -> Uuid,
+            transaction_id -> Uuid,
+            user_id -> Uuid,
+            rule_set_version -> Varchar,
+            mode -> Varchar,
+            score -> Int4,
+            shadow_score -> Int4,
+            decision -> Varchar,
+            shadow_decision -> Varchar,
+            applied_decision -> Varchar,
+            features -> Jsonb,
+            review_status -> Nullable<Varchar>,
+            pending_dispatch -> Nullable<Jsonb>,
+            reviewed_by -> Nullable<Uuid>,
+            review_comment -> Nullable<Text>,
+            reviewed_at -> Nullable<Timestamptz>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        fraud_rule_hits (hit_id) {
+            hit_id -> Uuid,
+            evaluation_id -> Uuid,
+            transaction_id -> Uuid,
+            rule_id -> Varchar,
+            rule_set_version -> Varchar,
+            score -> Int4,
+            action -> Nullable<Varchar>,
+            shadow -> Bool,
+            created_at -> Timestamptz,
+        }
+
+        fx_quotes (quote_id) {
+            quote_id -> Uuid,
+            user_id -> Uuid,
+            source_wallet_id -> Uuid,
+            target_wallet_id -> Uuid,
+            from_currency -> Varchar,
+            to_currency -> Varchar,
+            source_amount -> Numeric,
+            mid_rate -> Numeric,
+            spread_bps -> Int4,
+            applied_rate -> Numeric,
+            target_amount -> Numeric,
+            fee_amount -> Numeric,
+            fee_rule_id -> Nullable<Varchar>,
+            rate_source -> Nullable<Varchar>,
+            status -> Varchar,
+            expires_at -> Timestamptz,
+            executed_at -> Nullable<Timestamptz>,
+            debit_transaction_id -> Nullable<Uuid>,
+            credit_transaction_id -> Nullable<Uuid>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        idempotency_keys (key_id) {
+            key_id -> Int8,
+            idempotency_key -> Varchar,
+            user_id -> Nullable<Uuid>,
+            scope -> Varchar,
+            request_fingerprint -> Varchar,
+            status -> Varchar,
+            response_status_code -> Nullable<Int4>,
+            response_body -> Nullable<Jsonb>,
+            transaction_id -> Nullable<Uuid>,
+            locked_until -> Nullable<Timestamptz>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+            expires_at -> Timestamptz,
+        }
+
+        inbound_suspense (suspense_id) {
+            suspense_id -> Uuid,
+            transaction_id -> Uuid,
+            rail -> Varchar,
+            amount -> Numeric,
+            currency_code -> Varchar,
+            reason -> Text,
+            notice -> Jsonb,
+            status -> Varchar,
+            resolved_wallet_id -> Nullable<Uuid>,
+            resolved_by -> Nullable<Uuid>,
+            resolution_note -> Nullable<Text>,
+            resolved_at -> Nullable<Timestamptz>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        interest_accruals (accrual_id) {
+            accrual_id -> Uuid,
+            wallet_id -> Uuid,
+            accrual_date -> Date,
+            balance -> Numeric,
+            annual_rate_percent -> Numeric,
+            day_count -> Varchar,
+            year_fraction -> Numeric,
+            amount -> Numeric,
+            rate_id -> Nullable<Uuid>,
+            computed_at -> Timestamptz,
+        }
+
+        interest_postings (posting_id) {
+            posting_id -> Uuid,
+            wallet_id -> Uuid,
+            period_start -> Date,
+            period_end -> Date,
+            accrued_to_date -> Numeric,
+            gross_amount -> Numeric,
+            tax_amount -> Numeric,
+            net_amount -> Numeric,
+            currency_code -> Varchar,
+            interest_transaction_id -> Nullable<Uuid>,
+            tax_transaction_id -> Nullable<Uuid>,
+            created_at -> Timestamptz,
+        }
+
+        interest_rate_versions (rate_id) {
+            rate_id -> Uuid,
+            wallet_type -> Varchar,
+            currency_code -> Varchar,
+            effective_from -> Date,
+            day_count -> Varchar,
+            tiers -> Jsonb,
+            withholding_tax_percent -> Numeric,
+            note -> Nullable<Text>,
+            created_by -> Nullable<Uuid>,
+            created_at -> Timestamptz,
+        }
+
+        interest_wallet_states (wallet_id) {
+            wallet_id -> Uuid,
+            accrued_through -> Nullable<Date>,
+            last_line_id -> Int8,
+            last_run_at -> Nullable<Timestamptz>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        journal_entries (entry_id) {
+            entry_id -> Uuid,
+            transaction_id -> Nullable<Uuid>,
+            entry_type -> Varchar,
+            reference -> Varchar,
+            description -> Nullable<Text>,
+            posted_at -> Timestamptz,
+        }
+
+        journal_lines (line_id) {
+            line_id -> Int8,
+            entry_id -> Uuid,
+            account_id -> Uuid,
+            direction -> Varchar,
+            amount -> Numeric,
+            currency_code -> Varchar,
+            posted_at -> Timestamptz,
+        }
+
+        ledger_accounts (account_id) {
+            account_id -> Uuid,
+            account_code -> Varchar,
+            category -> Varchar,
+            normal_balance -> Varchar,
+            currency_code -> Varchar,
+            wallet_id -> Nullable<Uuid>,
+            created_at -> Timestamptz,
+        }
+
+        limit_counters (counter_id) {
+            counter_id -> Uuid,
+            rule_id -> Varchar,
+            subject_type -> Varchar,
+            subject_id -> Uuid,
+            currency_code -> Varchar,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        limit_overrides (override_id) {
+            override_id -> Uuid,
+            rule_id -> Varchar,
+            subject_type -> Varchar,
+            subject_id -> Uuid,
+            max_amount -> Nullable<Numeric>,
+            max_count -> Nullable<Int4>,
+            valid_from -> Timestamptz,
+            valid_until -> Timestamptz,
+            reason -> Text,
+            status -> Varchar,
+            requested_by -> Uuid,
+            decided_by -> Nullable<Uuid>,
+            decision_comment -> Nullable<Text>,
+            decided_at -> Nullable<Timestamptz>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        limit_usages (usage_id) {
+            usage_id -> Uuid,
+            counter_id -> Uuid,
+            transaction_id -> Uuid,
+            amount -> Numeric,
+            released_at -> Nullable<Timestamptz>,
+            created_at -> Timestamptz,
+        }
+
+        login_events (login_id) {
+            login_id -> Uuid,
+            user_id -> Uuid,
+            ip_address -> Nullable<Varchar>,
+            user_agent -> Nullable<Text>,
+            device_id -> Nullable<Varchar>,
+            country_code -> Nullable<Varchar>,
+            latitude -> Nullable<Float8>,
+            longitude -> Nullable<Float8>,
+            asn -> Nullable<Int8>,
+            as_org -> Nullable<Varchar>,
+            tor -> Bool,
+            vpn -> Bool,
+            hosting -> Bool,
+            ip_risk -> Int4,
+            previous_login_id -> Nullable<Uuid>,
+            distance_km -> Nullable<Float8>,
+            travel_speed_kmh -> Nullable<Float8>,
+            impossible_travel -> Bool,
+            created_at -> Timestamptz,
+        }
+
+        payment_approval_decisions (decision_id) {
+            decision_id -> Uuid,
+            approval_id -> Uuid,
+            approver_user_id -> Uuid,
+            approver_role -> Varchar,
+            decision -> Varchar,
+            comment -> Nullable<Text>,
+            created_at -> Timestamptz,
+        }
+
+        payment_approvals (approval_id) {
+            approval_id -> Uuid,
+            transaction_id -> Uuid,
+            initiator_user_id -> Uuid,
+            status -> Varchar,
+            required_approvals -> Int4,
+            approval_count -> Int4,
+            approver_roles -> Jsonb,
+            policy_ids -> Jsonb,
+            pending_dispatch -> Jsonb,
+            expires_at -> Timestamptz,
+            decided_at -> Nullable<Timestamptz>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        payment_outbox (outbox_id) {
+            outbox_id -> Uuid,
+            transaction_id -> Uuid,
+            operation -> Varchar,
+            payload -> Jsonb,
+            status -> Varchar,
+            attempts -> Int4,
+            max_attempts -> Int4,
+            next_attempt_at -> Timestamptz,
+            locked_until -> Nullable<Timestamptz>,
+            dispatch_key -> Varchar,
+            external_ref -> Nullable<Varchar>,
+            last_error -> Nullable<Text>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        payment_saga_steps (step_id) {
+            step_id -> Int8,
+            outbox_id -> Uuid,
+            step -> Varchar,
+            outcome -> Varchar,
+            details -> Nullable<Jsonb>,
+            created_at -> Timestamptz,
+        }
+
+        payment_schedule_runs (run_id) {
+            run_id -> Uuid,
+            schedule_id -> Uuid,
+            occurrence_date -> Date,
+            run_date -> Date,
+            status -> Varchar,
+            transaction_id -> Nullable<Uuid>,
+            error_message -> Nullable<Text>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        payment_schedules (schedule_id) {
+            schedule_id -> Uuid,
+            user_id -> Uuid,
+            source_wallet_id -> Uuid,
+            payment_type -> Text,
+            amount -> Numeric,
+            currency_code -> Varchar,
+            charge_bearer -> Varchar,
+            payment_details -> Jsonb,
+            description -> Nullable<Text>,
+            recurrence -> Jsonb,
+            start_date -> Date,
+            end_date -> Nullable<Date>,
+            max_occurrences -> Nullable<Int4>,
+            business_day_convention -> Varchar,
+            missed_run_policy -> Varchar,
+            status -> Varchar,
+            next_occurrence -> Nullable<Date>,
+            next_run_date -> Nullable<Date>,
+            occurrences_count -> Int4,
+            last_run_at -> Nullable<Timestamptz>,
+            last_error -> Nullable<Text>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        payout_batch_items (item_id) {
+            item_id -> Uuid,
+            batch_id -> Uuid,
+            row_number -> Int4,
+            payment_type -> Nullable<Text>,
+            amount -> Nullable<Text>,
+            beneficiary_name -> Nullable<Text>,
+            reference -> Nullable<Text>,
+            status -> Varchar,
+            errors -> Jsonb,
+            payment_details -> Nullable<Jsonb>,
+            transaction_id -> Nullable<Uuid>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        payout_batches (batch_id) {
+            batch_id -> Uuid,
+            user_id -> Uuid,
+            source_wallet_id -> Uuid,
+            currency_code -> Varchar,
+            file_format -> Varchar,
+            file_name -> Nullable<Text>,
+            status -> Varchar,
+            total_rows -> Int4,
+            accepted_count -> Int4,
+            rejected_count -> Int4,
+            total_amount -> Numeric,
+            total_fees -> Numeric,
+            settled_count -> Int4,
+            failed_count -> Int4,
+            returned_count -> Int4,
+            ach_status -> Varchar,
+            ach_file_ref -> Nullable<Text>,
+            ach_attempts -> Int4,
+            ach_next_attempt_at -> Nullable<Timestamptz>,
+            ach_locked_until -> Nullable<Timestamptz>,
+            last_error -> Nullable<Text>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        reconciliation_exceptions (exception_id) {
+            exception_id -> Uuid,
+            nostro_account -> Varchar,
+            exception_type -> Varchar,
+            line_id -> Nullable<Uuid>,
+            transaction_id -> Nullable<Uuid>,
+            match_id -> Nullable<Uuid>,
+            business_date -> Date,
+            expected_amount -> Nullable<Numeric>,
+            actual_amount -> Nullable<Numeric>,
+            currency_code -> Varchar,
+            status -> Varchar,
+            resolution -> Nullable<Varchar>,
+            resolution_note -> Nullable<Text>,
+            resolved_by -> Nullable<Uuid>,
+            resolved_at -> Nullable<Timestamptz>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        reconciliation_match_transactions (match_id, transaction_id) {
+            match_id -> Uuid,
+            transaction_id -> Uuid,
+        }
+
+        reconciliation_matches (match_id) {
+            match_id -> Uuid,
+            nostro_account -> Varchar,
+            match_kind -> Varchar,
+            match_rule -> Varchar,
+            statement_amount -> Numeric,
+            transaction_amount -> Numeric,
+            currency_code -> Varchar,
+            matched_by -> Nullable<Uuid>,
+            note -> Nullable<Text>,
+            created_at -> Timestamptz,
+        }
+
+        sanctions_cases (case_id) {
+            case_id -> Uuid,
+            transaction_id -> Uuid,
+            initiator_user_id -> Uuid,
+            status -> Varchar,
+            parties -> Jsonb,
+            hits -> Jsonb,
+            lists -> Jsonb,
+            pending_dispatch -> Jsonb,
+            reviewed_by -> Nullable<Uuid>,
+            review_comment -> Nullable<Text>,
+            reviewed_at -> Nullable<Timestamptz>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        transaction_state_transitions (transition_id) {
+            transition_id -> Int8,
+            transaction_id -> Uuid,
+            from_status -> Nullable<Varchar>,
+            to_status -> Varchar,
+            financial_effect -> Varchar,
+            actor -> Varchar,
+            created_at -> Timestamptz,
+        }
+
         transactions (transaction_id) {
             transaction_id -> Uuid,
             debit_wallet_id -> Nullable<Uuid>,
@@ -31,6 +598,30 @@ diesel::schema! {
             created_at -> Timestamptz,
             updated_at -> Timestamptz,
             settlement_at -> Nullable<Timestamptz>,
+            scheduled_run_id -> Nullable<Uuid>,
+        }
+
+        travel_rule_transfers (transfer_id) {
+            transfer_id -> Uuid,
+            transaction_id -> Uuid,
+            initiator_user_id -> Uuid,
+            status -> Varchar,
+            destination_address -> Text,
+            beneficiary_vasp_id -> Nullable<Varchar>,
+            protocol -> Nullable<Varchar>,
+            ivms101 -> Jsonb,
+            missing_fields -> Jsonb,
+            pending_dispatch -> Nullable<Jsonb>,
+            counterparty_reference -> Nullable<Varchar>,
+            counterparty_response -> Nullable<Jsonb>,
+            attempts -> Int4,
+            last_error -> Nullable<Text>,
+            next_attempt_at -> Nullable<Timestamptz>,
+            expires_at -> Nullable<Timestamptz>,
+            sent_at -> Nullable<Timestamptz>,
+            completed_at -> Nullable<Timestamptz>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
         }
 
         users (user_id) {
@@ -40,6 +631,33 @@ diesel::schema! {
             password_hash -> Text,
             created_at -> Timestamptz,
             updated_at -> Timestamptz,
+            fee_tier -> Varchar,
+            kyc_tier -> Varchar,
+            country_code -> Nullable<Varchar>,
+        }
+
+        virtual_accounts (virtual_account_id) {
+            virtual_account_id -> Uuid,
+            wallet_id -> Uuid,
+            account_number -> Varchar,
+            status -> Varchar,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
+
+        wallet_holds (hold_id) {
+            hold_id -> Uuid,
+            wallet_id -> Uuid,
+            transaction_id -> Nullable<Uuid>,
+            hold_type -> Varchar,
+            amount -> Numeric,
+            currency_code -> Varchar,
+            status -> Varchar,
+            reason -> Nullable<Text>,
+            expires_at -> Nullable<Timestamptz>,
+            resolved_at -> Nullable<Timestamptz>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
         }
 
         wallets (wallet_id) {
@@ -59,19 +677,128 @@ diesel::schema! {
             created_at -> Timestamptz,
             updated_at -> Timestamptz,
         }
+
+        webhook_events (event_id) {
+            event_id -> Uuid,
+            provider -> Varchar,
+            provider_event_id -> Varchar,
+            event_type -> Varchar,
+            raw_payload -> Text,
+            signature_headers -> Jsonb,
+            status -> Varchar,
+            attempts -> Int4,
+            last_error -> Nullable<Text>,
+            results -> Nullable<Jsonb>,
+            received_at -> Timestamptz,
+            processed_at -> Nullable<Timestamptz>,
+            created_at -> Timestamptz,
+            updated_at -> Timestamptz,
+        }
     }
 }
 
 // Define relationships between tables
+diesel::joinable!(aml_alerts -> aml_cases (case_id));
 diesel::joinable!(audit_logs -> users (user_id));
+diesel::joinable!(bank_statement_lines -> reconciliation_matches (match_id));
+diesel::joinable!(beneficiaries -> users (user_id));
+diesel::joinable!(ctr_batches -> users (created_by));
+diesel::joinable!(ctr_exemptions -> users (user_id));
+diesel::joinable!(ctr_reports -> ctr_batches (batch_id));
+diesel::joinable!(ctr_reports -> ctr_exemptions (exemption_id));
+diesel::joinable!(fraud_evaluations -> transactions (transaction_id));
+diesel::joinable!(fraud_rule_hits -> fraud_evaluations (evaluation_id));
+diesel::joinable!(fx_quotes -> users (user_id));
+diesel::joinable!(idempotency_keys -> users (user_id));
+diesel::joinable!(inbound_suspense -> transactions (transaction_id));
+diesel::joinable!(journal_entries -> transactions (transaction_id));
+diesel::joinable!(interest_accruals -> interest_rate_versions (rate_id));
+diesel::joinable!(interest_accruals -> wallets (wallet_id));
+diesel::joinable!(interest_postings -> wallets (wallet_id));
+diesel::joinable!(interest_rate_versions -> users (created_by));
+diesel::joinable!(interest_wallet_states -> wallets (wallet_id));
+diesel::joinable!(journal_lines -> journal_entries (entry_id));
+diesel::joinable!(journal_lines -> ledger_accounts (account_id));
+diesel::joinable!(ledger_accounts -> wallets (wallet_id));
+diesel::joinable!(limit_usages -> limit_counters (counter_id));
+diesel::joinable!(limit_usages -> transactions (transaction_id));
+diesel::joinable!(login_events -> users (user_id));
+diesel::joinable!(payment_approval_decisions -> payment_approvals (approval_id));
+diesel::joinable!(payment_approval_decisions -> users (approver_user_id));
+diesel::joinable!(payment_approvals -> transactions (transaction_id));
+diesel::joinable!(payment_approvals -> users (initiator_user_id));
+diesel::joinable!(payment_outbox -> transactions (transaction_id));
+diesel::joinable!(payment_saga_steps -> payment_outbox (outbox_id));
+diesel::joinable!(payment_schedule_runs -> payment_schedules (schedule_id));
+diesel::joinable!(payment_schedule_runs -> transactions (transaction_id));
+diesel::joinable!(payment_schedules -> users (user_id));
+diesel::joinable!(payment_schedules -> wallets (source_wallet_id));
+diesel::joinable!(payout_batch_items -> payout_batches (batch_id));
+diesel::joinable!(payout_batch_items -> transactions (transaction_id));
+diesel::joinable!(payout_batches -> users (user_id));
+diesel::joinable!(payout_batches -> wallets (source_wallet_id));
+diesel::joinable!(reconciliation_exceptions -> bank_statement_lines (line_id));
+diesel::joinable!(reconciliation_exceptions -> reconciliation_matches (match_id));
+diesel::joinable!(reconciliation_exceptions -> transactions (transaction_id));
+diesel::joinable!(reconciliation_match_transactions -> reconciliation_matches (match_id));
+diesel::joinable!(reconciliation_match_transactions -> transactions (transaction_id));
+diesel::joinable!(reconciliation_matches -> users (matched_by));
+diesel::joinable!(sanctions_cases -> transactions (transaction_id));
+diesel::joinable!(transaction_state_transitions -> transactions (transaction_id));
 diesel::joinable!(transactions -> wallets (credit_wallet_id)); // Specify foreign key column name if needed
 // diesel::joinable!(transactions -> wallets (debit_wallet_id)); // Diesel doesn't easily support multiple FKs to same table by default, often handled in queries
+diesel::joinable!(travel_rule_transfers -> transactions (transaction_id));
+diesel::joinable!(virtual_accounts -> wallets (wallet_id));
+diesel::joinable!(wallet_holds -> transactions (transaction_id));
+diesel::joinable!(wallet_holds -> wallets (wallet_id));
 diesel::joinable!(wallets -> users (user_id));
 
 // Allow tables to appear in the same query (optional but often helpful)
 diesel::allow_tables_to_appear_in_same_query!(
+    aml_alerts,
+    aml_cases,
+    aml_monitor_state,
     audit_logs,
+    bank_statement_lines,
+    beneficiaries,
+    ctr_batches,
+    ctr_exemptions,
+    ctr_reports,
+    eod_position_snapshots,
+    fraud_evaluations,
+    fraud_rule_hits,
+    fx_quotes,
+    idempotency_keys,
+    inbound_suspense,
+    interest_accruals,
+    interest_postings,
+    interest_rate_versions,
+    interest_wallet_states,
+    journal_entries,
+    journal_lines,
+    ledger_accounts,
+    limit_counters,
+    limit_overrides,
+    limit_usages,
+    login_events,
+    payment_approval_decisions,
+    payment_approvals,
+    payment_outbox,
+    payment_saga_steps,
+    payment_schedule_runs,
+    payment_schedules,
+    payout_batch_items,
+    payout_batches,
+    reconciliation_exceptions,
+    reconciliation_match_transactions,
+    reconciliation_matches,
+    sanctions_cases,
+    transaction_state_transitions,
     transactions,
+    travel_rule_transfers,
     users,
+    virtual_accounts,
+    wallet_holds,
     wallets,
+    webhook_events,
 );
\ No newline at end of file
@@ -0,0 +1,125 @@
+#!/usr/bin/env python3
+# /home/inno/elights_jobes-research/scripts/make_ip_intel_fixture.py
+# Writes the small MaxMind-format database used by the domain crate's IP intelligence tests
+# (backend/domain/tests/fixtures/ip-intel-test.mmdb). Each network carries GeoIP2 City fields (country, location)
+# and GeoLite2 ASN fields, so the one file serves as both the country and the ASN database in tests.
+# Only documentation/test ranges and MaxMind's own test networks are used. Run from the repository root.
+import ipaddress
+import struct
+import sys
+
+NETWORKS = [
+    ("81.2.69.0/24", {"iso": "GB", "lat": 51.5142, "lon": -0.0931, "asn": 20712, "org": "Andrews & Arnold Ltd"}),
+    ("89.160.20.0/24", {"iso": "SE", "lat": 58.4167, "lon": 15.6167, "asn": 29518, "org": "Bredband2 AB"}),
+    ("216.160.83.0/24", {"iso": "US", "lat": 47.2513, "lon": -122.3149, "asn": 209, "org": "Qwest Communications"}),
+    ("203.0.113.0/24", {"iso": "NL", "asn": 64501, "org": "Example VPN BV"}),  # No location
+    ("2001:db8:1::/48", {"iso": "DE", "lat": 52.5200, "lon": 13.4050, "asn": 64500, "org": "Example Hosting GmbH"}),
+]
+RECORD_SIZE = 24
+
+
+def ctrl(type_id, size):
+    out = bytearray()
+    if size < 29:
+        first, extra = size, b""
+    elif size < 285:
+        first, extra = 29, bytes([size - 29])
+    elif size < 65821:
+        first, extra = 30, (size - 285).to_bytes(2, "big")
+    else:
+        first, extra = 31, (size - 65821).to_bytes(3, "big")
+    if type_id <= 7:
+        out.append((type_id << 5) | first)
+    else:
+        out.append(first)
+        out.append(type_id - 7)
+    return bytes(out) + extra
+
+
+def encode(value):
+    if isinstance(value, bool):
+        return ctrl(14, 1 if value else 0)
+    if isinstance(value, str):
+        raw = value.encode("utf-8")
+        return ctrl(2, len(raw)) + raw
+    if isinstance(value, float):
+        return ctrl(3, 8) + struct.pack(">d", value)
+    if isinstance(value, int):
+        raw = value.to_bytes((value.bit_length() + 7) // 8, "big") if value else b""
+        type_id = 5 if value < 1 << 16 else 6 if value < 1 << 32 else 9
+        return ctrl(type_id, len(raw)) + raw
+    if isinstance(value, list):
+        return ctrl(11, len(value)) + b"".join(encode(v) for v in value)
+    if isinstance(value, dict):
+        return ctrl(7, len(value)) + b"".join(encode(k) + encode(v) for k, v in value.items())
+    raise TypeError(value)
+
+
+def record(info):
+    rec = {
+        "country": {"iso_code": info["iso"], "names": {"en": info["iso"]}},
+        "registered_country": {"iso_code": info["iso"]},
+        "autonomous_system_number": info["asn"],
+        "autonomous_system_organization": info["org"],
+    }
+    if "lat" in info:
+        rec["location"] = {"latitude": info["lat"], "longitude": info["lon"], "accuracy_radius": 50}
+    return rec
+
+
+def bits(network):
+    net = ipaddress.ip_network(network)
+    value = int(net.network_address)  # IPv4 lives under ::/96, so its top 96 bits are zero
+    prefix = net.prefixlen + (96 if net.version == 4 else 0)
+    return [(value >> (127 - i)) & 1 for i in range(prefix)]
+
+
+def main(path):
+    data = bytearray()
+    offsets = []
+    for _, info in NETWORKS:
+        offsets.append(len(data))
+        data += encode(record(info))
+
+    nodes = [[None, None]]  # Each record: None (empty), ("node", n) or ("data", offset)
+    for (network, _), offset in zip(NETWORKS, offsets):
+        node = 0
+        path_bits = bits(network)
+        for depth, bit in enumerate(path_bits):
+            if depth == len(path_bits) - 1:
+                nodes[node][bit] = ("data", offset)
+                break
+            if nodes[node][bit] is None:
+                nodes.append([None, None])
+                nodes[node][bit] = ("node", len(nodes) - 1)
+            node = nodes[node][bit][1]
+
+    node_count = len(nodes)
+
+    def value(rec):
+        if rec is None:
+            return node_count
+        kind, n = rec
+        return n if kind == "node" else node_count + 16 + n
+
+    tree = bytearray()
+    for left, right in nodes:
+        tree += value(left).to_bytes(3, "big") + value(right).to_bytes(3, "big")
+
+    metadata = {
+        "node_count": node_count,
+        "record_size": RECORD_SIZE,
+        "ip_version": 6,
+        "database_type": "Elights-IP-Intel-Test",
+        "languages": ["en"],
+        "binary_format_major_version": 2,
+        "binary_format_minor_version": 0,
+        "build_epoch": 1745107200,
+        "description": {"en": "Test fixture for the domain crate's IP intelligence lookups"},
+    }
+    with open(path, "wb") as out:
+        out.write(bytes(tree) + bytes(16) + bytes(data) + b"\xab\xcd\xefMaxMind.com" + encode(metadata))
+
+
+if __name__ == "__main__":
+    main(sys.argv[1] if len(sys.argv) > 1 else "backend/domain/tests/fixtures/ip-intel-test.mmdb")